@@ -1,5 +1,5 @@
 use futures::FutureExt;
-use near_indexer_primitives::near_primitives;
+use readnode_primitives::indexer::near_primitives;
 
 mod utils;
 
@@ -59,6 +59,23 @@ impl RedisCacheStorage {
         Ok(())
     }
 
+    // Set the value which expires in `expiration_seconds`
+    async fn set_with_expiration(
+        &self,
+        key: impl redis::ToRedisArgs + std::fmt::Debug,
+        value: impl redis::ToRedisArgs + std::fmt::Debug,
+        expiration_seconds: u64,
+    ) -> anyhow::Result<()> {
+        redis::cmd("SET")
+            .arg(&key)
+            .arg(&value)
+            .arg("EX")
+            .arg(expiration_seconds)
+            .query_async(&mut self.client.clone())
+            .await?;
+        Ok(())
+    }
+
     pub async fn delete(
         &self,
         key: impl redis::ToRedisArgs + std::fmt::Debug,
@@ -70,6 +87,67 @@ impl RedisCacheStorage {
         Ok(())
     }
 
+    // Delete the keys matching the glob-style pattern and return the number of deleted keys.
+    // SCAN is used instead of KEYS to not block the Redis on the large databases
+    async fn delete_by_pattern(&self, pattern: &str) -> anyhow::Result<usize> {
+        let mut cursor: u64 = 0;
+        let mut deleted = 0;
+        loop {
+            let (next_cursor, keys): (u64, Vec<String>) = redis::cmd("SCAN")
+                .arg(cursor)
+                .arg("MATCH")
+                .arg(pattern)
+                .arg("COUNT")
+                .arg(1000)
+                .query_async(&mut self.client.clone())
+                .await?;
+            if !keys.is_empty() {
+                let deleted_keys: usize = redis::cmd("DEL")
+                    .arg(&keys)
+                    .query_async(&mut self.client.clone())
+                    .await?;
+                deleted += deleted_keys;
+            }
+            if next_cursor == 0 {
+                return Ok(deleted);
+            }
+            cursor = next_cursor;
+        }
+    }
+
+    // Returns the values of all the keys matching the glob-style pattern
+    async fn get_by_pattern<V: redis::FromRedisValue>(
+        &self,
+        pattern: &str,
+    ) -> anyhow::Result<Vec<V>> {
+        let mut cursor: u64 = 0;
+        let mut keys: Vec<String> = vec![];
+        loop {
+            let (next_cursor, scanned_keys): (u64, Vec<String>) = redis::cmd("SCAN")
+                .arg(cursor)
+                .arg("MATCH")
+                .arg(pattern)
+                .arg("COUNT")
+                .arg(1000)
+                .query_async(&mut self.client.clone())
+                .await?;
+            keys.extend(scanned_keys);
+            if next_cursor == 0 {
+                break;
+            }
+            cursor = next_cursor;
+        }
+        if keys.is_empty() {
+            return Ok(vec![]);
+        }
+        // The keys expired after the scan are returned as nil and skipped
+        let values: Vec<Option<V>> = redis::cmd("MGET")
+            .arg(&keys)
+            .query_async(&mut self.client.clone())
+            .await?;
+        Ok(values.into_iter().flatten().collect())
+    }
+
     // Insert all the specified values at the tail of the list stored at key.
     // If key does not exist, it is created as empty list before performing the push operation.
     async fn insert_or_create(
@@ -135,8 +213,8 @@ impl BlocksByFinalityCache {
 
     pub async fn update_block_by_finality(
         &self,
-        finality: near_indexer_primitives::near_primitives::types::Finality,
-        streamer_message: &near_indexer_primitives::StreamerMessage,
+        finality: readnode_primitives::indexer::near_primitives::types::Finality,
+        streamer_message: &readnode_primitives::indexer::StreamerMessage,
     ) -> anyhow::Result<()> {
         let block_height = streamer_message.block.header.height;
         let block_type = serde_json::to_string(&finality)?;
@@ -178,8 +256,8 @@ impl BlocksByFinalityCache {
 
     pub async fn get_block_by_finality(
         &self,
-        finality: near_indexer_primitives::near_primitives::types::Finality,
-    ) -> anyhow::Result<near_indexer_primitives::StreamerMessage> {
+        finality: readnode_primitives::indexer::near_primitives::types::Finality,
+    ) -> anyhow::Result<readnode_primitives::indexer::StreamerMessage> {
         let block_type = serde_json::to_string(&finality)?;
         let resp: String = self.cache_storage.get(block_type).await?;
         Ok(serde_json::from_str(&resp)?)
@@ -218,7 +296,7 @@ impl TxIndexerCache {
     // If the transaction is found by key prefix, return the transaction details by the first key.
     pub async fn get_tx_by_tx_hash(
         &self,
-        tx_hash: &near_indexer_primitives::CryptoHash,
+        tx_hash: &readnode_primitives::indexer::CryptoHash,
     ) -> anyhow::Result<readnode_primitives::TransactionDetails> {
         let tx_key = match self
             .cache_storage
@@ -264,19 +342,20 @@ impl TxIndexerCache {
     pub async fn get_tx_outcomes(
         &self,
         transaction_key: &readnode_primitives::TransactionKey,
-    ) -> anyhow::Result<Vec<near_indexer_primitives::IndexerExecutionOutcomeWithReceipt>> {
-        Ok(self
-            .cache_storage
-            .list::<Vec<Vec<u8>>>(format!("outcomes_{}", transaction_key))
-            .await?
-            .iter()
-            .map(|outcome| {
-                utils::from_slice::<near_indexer_primitives::IndexerExecutionOutcomeWithReceipt>(
-                    outcome,
-                )
-            })
-            .filter_map(|outcome| outcome.ok())
-            .collect())
+    ) -> anyhow::Result<Vec<readnode_primitives::indexer::IndexerExecutionOutcomeWithReceipt>> {
+        Ok(
+            self.cache_storage
+                .list::<Vec<Vec<u8>>>(format!("outcomes_{}", transaction_key))
+                .await?
+                .iter()
+                .map(|outcome| {
+                    utils::from_slice::<
+                        readnode_primitives::indexer::IndexerExecutionOutcomeWithReceipt,
+                    >(outcome)
+                })
+                .filter_map(|outcome| outcome.ok())
+                .collect(),
+        )
     }
 
     // Get the transaction details with outcomes by the transaction key.
@@ -286,12 +365,8 @@ impl TxIndexerCache {
     ) -> anyhow::Result<readnode_primitives::CollectingTransactionDetails> {
         let mut tx = self.get_tx(transaction_key).await?;
         for outcome in self.get_tx_outcomes(transaction_key).await? {
-            // Skip the outcome that is already in the transaction
-            if outcome.execution_outcome.id == tx.transaction_outcome.id {
-                continue;
-            };
-            tx.execution_outcomes.push(outcome.execution_outcome);
-            tx.receipts.push(outcome.receipt);
+            // Outcomes that are already in the transaction are skipped
+            tx.add_receipt_and_outcome(outcome.receipt, outcome.execution_outcome);
         }
         Ok(tx)
     }
@@ -337,13 +412,33 @@ impl TxIndexerCache {
             .collect::<anyhow::Result<_>>()
     }
 
+    // Delete the details and outcomes of the transactions from cache storage with a single command
+    pub async fn del_txs(
+        &self,
+        transaction_keys: &[readnode_primitives::TransactionKey],
+    ) -> anyhow::Result<()> {
+        if transaction_keys.is_empty() {
+            return Ok(());
+        }
+        let keys: Vec<String> = transaction_keys
+            .iter()
+            .flat_map(|transaction_key| {
+                [
+                    format!("transaction_{}", transaction_key),
+                    format!("outcomes_{}", transaction_key),
+                ]
+            })
+            .collect();
+        self.cache_storage.delete(keys).await
+    }
+
     // Add the transaction outcomes by the transaction key.
     // This inserts the outcomes into the list stored at key, only if key already exists and holds a list.
     // This is needed to avoid adding outcomes to the list if the transaction is already deleted.
     pub async fn set_outcomes_and_receipts(
         &self,
         transaction_key: &readnode_primitives::TransactionKey,
-        indexer_execution_outcome_with_receipt: near_indexer_primitives::IndexerExecutionOutcomeWithReceipt,
+        indexer_execution_outcome_with_receipt: readnode_primitives::indexer::IndexerExecutionOutcomeWithReceipt,
     ) -> anyhow::Result<()> {
         self.cache_storage
             .insert_or_ignore(
@@ -354,3 +449,135 @@ impl TxIndexerCache {
         Ok(())
     }
 }
+
+/// Nonces of the transactions submitted through the rpc-server but not indexed yet.
+/// Used to return the expected nonce from `view_access_key` right after the transaction
+/// is sent, while the indexer has not caught up with it yet.
+#[derive(Clone)]
+pub struct PendingNoncesCache {
+    cache_storage: RedisCacheStorage,
+}
+
+impl PendingNoncesCache {
+    // Use redis database 3 for pending nonces of the submitted transactions
+    pub async fn new(redis_url: String) -> anyhow::Result<Self> {
+        Ok(Self {
+            cache_storage: RedisCacheStorage::new(redis_url, 3).await?,
+        })
+    }
+
+    fn pending_nonce_key(
+        account_id: &near_primitives::types::AccountId,
+        public_key: &near_crypto::PublicKey,
+    ) -> String {
+        format!("pending_nonce_{}_{}", account_id, public_key)
+    }
+
+    // Store the nonce of the submitted transaction for `ttl_seconds`.
+    // The nonce is updated only if it is greater than the stored one.
+    pub async fn set_pending_nonce(
+        &self,
+        account_id: &near_primitives::types::AccountId,
+        public_key: &near_crypto::PublicKey,
+        nonce: near_primitives::types::Nonce,
+        ttl_seconds: u64,
+    ) -> anyhow::Result<()> {
+        // The comparison and the update are done by a single script, so the concurrent
+        // submissions from several rpc-servers never replace the greater nonce.
+        // The nonces are compared as decimal strings, Lua numbers lose the precision of u64
+        let script = redis::Script::new(
+            r"
+            local pending_nonce = redis.call('GET', KEYS[1])
+            if not pending_nonce
+                or #ARGV[1] > #pending_nonce
+                or (#ARGV[1] == #pending_nonce and ARGV[1] > pending_nonce) then
+                redis.call('SET', KEYS[1], ARGV[1], 'EX', ARGV[2])
+            end
+            return 0
+            ",
+        );
+        script
+            .key(Self::pending_nonce_key(account_id, public_key))
+            .arg(nonce.to_string())
+            .arg(ttl_seconds)
+            .invoke_async::<_, ()>(&mut self.cache_storage.client.clone())
+            .await?;
+        Ok(())
+    }
+
+    // Delete the pending nonces with the keys matching the glob-style pattern,
+    // e.g. `pending_nonce_alice.near_*` for all the keys of the account
+    pub async fn invalidate(&self, pattern: &str) -> anyhow::Result<usize> {
+        self.cache_storage.delete_by_pattern(pattern).await
+    }
+
+    // Get the greatest nonce of the submitted transactions which is not expired yet
+    pub async fn get_pending_nonce(
+        &self,
+        account_id: &near_primitives::types::AccountId,
+        public_key: &near_crypto::PublicKey,
+    ) -> anyhow::Result<Option<near_primitives::types::Nonce>> {
+        self.cache_storage
+            .get(Self::pending_nonce_key(account_id, public_key))
+            .await
+    }
+}
+
+#[derive(Clone)]
+pub struct TxPoolCache {
+    cache_storage: RedisCacheStorage,
+}
+
+impl TxPoolCache {
+    // Use redis database 4 for the submitted transactions which are not included yet
+    pub async fn new(redis_url: String) -> anyhow::Result<Self> {
+        Ok(Self {
+            cache_storage: RedisCacheStorage::new(redis_url, 4).await?,
+        })
+    }
+
+    // The account ids can't contain `:`, so the pattern of the account
+    // doesn't match the keys of the other accounts
+    fn pending_transaction_key(
+        account_id: &near_primitives::types::AccountId,
+        transaction_hash: &near_primitives::hash::CryptoHash,
+    ) -> String {
+        format!("pending_tx:{}:{}", account_id, transaction_hash)
+    }
+
+    // Store the submitted transaction for `ttl_seconds`
+    pub async fn set_pending_transaction(
+        &self,
+        pending_transaction: &readnode_primitives::PendingTransaction,
+        ttl_seconds: u64,
+    ) -> anyhow::Result<()> {
+        self.cache_storage
+            .set_with_expiration(
+                Self::pending_transaction_key(
+                    &pending_transaction.signer_id,
+                    &pending_transaction.transaction_hash,
+                ),
+                serde_json::to_string(pending_transaction)?,
+                ttl_seconds,
+            )
+            .await
+    }
+
+    // Get all the submitted transactions which are not expired yet,
+    // of all the signers if the account is not given
+    pub async fn get_pending_transactions(
+        &self,
+        account_id: Option<&near_primitives::types::AccountId>,
+    ) -> anyhow::Result<Vec<readnode_primitives::PendingTransaction>> {
+        let pattern = match account_id {
+            Some(account_id) => format!("pending_tx:{}:*", account_id),
+            None => "pending_tx:*".to_string(),
+        };
+        self.cache_storage
+            .get_by_pattern::<String>(&pattern)
+            .await?
+            .iter()
+            .map(|value| Ok(serde_json::from_str(value)?))
+            .collect()
+    }
+}
@@ -7,5 +7,6 @@ pub fn to_vec<T: serde::ser::Serialize>(value: &T) -> anyhow::Result<Vec<u8>> {
 }
 
 pub fn from_slice<'a, T: serde::de::Deserialize<'a>>(data: &'a [u8]) -> anyhow::Result<T> {
+    readnode_primitives::blob::check_blob_size(data)?;
     Ok(serde_json::from_slice(data)?)
 }
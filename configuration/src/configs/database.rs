@@ -1,4 +1,4 @@
-use near_lake_framework::near_indexer_primitives::near_primitives;
+use readnode_primitives::indexer::near_primitives;
 use validator::Validate;
 
 use crate::configs::{deserialize_data_or_env, deserialize_optional_data_or_env};
@@ -16,8 +16,198 @@ pub struct ShardDatabaseConfig {
     pub database_url: DatabaseConnectUrl,
 }
 
+// Read replica of the shard database located in the `datacenter`
+#[derive(Validate, serde_derive::Deserialize, Debug, Clone, Default)]
+pub struct ShardDatabaseReplicaConfig {
+    #[serde(deserialize_with = "deserialize_data_or_env")]
+    pub datacenter: String,
+    #[serde(deserialize_with = "deserialize_data_or_env")]
+    pub shard_id: u64,
+    #[validate(url(message = "Invalid database shard replica URL"))]
+    #[serde(deserialize_with = "deserialize_data_or_env")]
+    pub database_url: DatabaseConnectUrl,
+}
+
+#[derive(serde_derive::Deserialize, Debug, Clone, Default)]
+pub struct CommonDatabaseCredentialsConfig {
+    #[serde(deserialize_with = "deserialize_optional_data_or_env", default)]
+    pub user: Option<String>,
+    #[serde(deserialize_with = "deserialize_optional_data_or_env", default)]
+    pub password: Option<String>,
+}
+
+// Database user credentials which override the user and password
+// from the `database_url` and shards `database_url`
+#[derive(Debug, Clone)]
+pub struct DatabaseCredentials {
+    pub user: String,
+    pub password: Option<String>,
+}
+
+impl DatabaseCredentials {
+    /// Replace the user and password in the database connection URL
+    pub fn apply_to_url(&self, database_url: &str) -> anyhow::Result<DatabaseConnectUrl> {
+        let mut url = url::Url::parse(database_url)?;
+        url.set_username(&self.user)
+            .map_err(|_| anyhow::anyhow!("Failed to set database user"))?;
+        url.set_password(self.password.as_deref())
+            .map_err(|_| anyhow::anyhow!("Failed to set database password"))?;
+        Ok(url.to_string())
+    }
+}
+
+impl From<CommonDatabaseCredentialsConfig> for Option<DatabaseCredentials> {
+    fn from(credentials_config: CommonDatabaseCredentialsConfig) -> Self {
+        credentials_config.user.map(|user| DatabaseCredentials {
+            user,
+            password: credentials_config.password,
+        })
+    }
+}
+
+#[derive(serde_derive::Deserialize, Debug, Clone, Default)]
+pub struct CommonDatabaseTlsConfig {
+    #[serde(deserialize_with = "deserialize_optional_data_or_env", default)]
+    pub ssl_mode: Option<String>,
+    #[serde(deserialize_with = "deserialize_optional_data_or_env", default)]
+    pub root_cert_path: Option<String>,
+    #[serde(deserialize_with = "deserialize_optional_data_or_env", default)]
+    pub client_cert_path: Option<String>,
+    #[serde(deserialize_with = "deserialize_optional_data_or_env", default)]
+    pub client_key_path: Option<String>,
+}
+
+// TLS settings applied to the connections of the meta database and all the shards databases.
+// They override the TLS parameters from the database urls, needed for managed databases
+// which require verified TLS connections or client certificates
+#[derive(Debug, Clone, Default)]
+pub struct DatabaseTlsConfig {
+    // One of `disable`, `allow`, `prefer`, `require`, `verify-ca`, `verify-full`
+    pub ssl_mode: Option<String>,
+    // CA bundle to verify the server certificate
+    pub root_cert_path: Option<String>,
+    // Client certificate and key for the certificate authentication
+    pub client_cert_path: Option<String>,
+    pub client_key_path: Option<String>,
+}
+
+impl From<CommonDatabaseTlsConfig> for DatabaseTlsConfig {
+    fn from(tls_config: CommonDatabaseTlsConfig) -> Self {
+        Self {
+            ssl_mode: tls_config.ssl_mode,
+            root_cert_path: tls_config.root_cert_path,
+            client_cert_path: tls_config.client_cert_path,
+            client_key_path: tls_config.client_key_path,
+        }
+    }
+}
+
+#[derive(serde_derive::Deserialize, Debug, Clone, Default)]
+pub struct CommonDatabaseRedisCacheConfig {
+    #[serde(deserialize_with = "deserialize_optional_data_or_env", default)]
+    pub redis_url: Option<String>,
+    #[serde(deserialize_with = "deserialize_optional_data_or_env", default)]
+    pub block_ttl: Option<u64>,
+    #[serde(deserialize_with = "deserialize_optional_data_or_env", default)]
+    pub account_ttl: Option<u64>,
+    #[serde(deserialize_with = "deserialize_optional_data_or_env", default)]
+    pub contract_code_ttl: Option<u64>,
+    #[serde(deserialize_with = "deserialize_optional_data_or_env", default)]
+    pub access_key_ttl: Option<u64>,
+    #[serde(deserialize_with = "deserialize_optional_data_or_env", default)]
+    pub state_ttl: Option<u64>,
+}
+
+impl CommonDatabaseRedisCacheConfig {
+    pub fn default_block_ttl() -> u64 {
+        3600
+    }
+
+    pub fn default_account_ttl() -> u64 {
+        60
+    }
+
+    pub fn default_contract_code_ttl() -> u64 {
+        600
+    }
+
+    pub fn default_access_key_ttl() -> u64 {
+        60
+    }
+
+    pub fn default_state_ttl() -> u64 {
+        60
+    }
+}
+
+// Redis cache shared by the rpc-server instances in front of the reader database.
+// The cached values are keyed by the requested block height, TTLs (seconds) bound
+// the memory used by the cache
+#[derive(Debug, Clone)]
+pub struct DatabaseRedisCacheConfig {
+    pub redis_url: String,
+    pub block_ttl: u64,
+    pub account_ttl: u64,
+    pub contract_code_ttl: u64,
+    pub access_key_ttl: u64,
+    pub state_ttl: u64,
+}
+
+impl From<CommonDatabaseRedisCacheConfig> for Option<DatabaseRedisCacheConfig> {
+    fn from(cache_config: CommonDatabaseRedisCacheConfig) -> Self {
+        cache_config
+            .redis_url
+            .map(|redis_url| DatabaseRedisCacheConfig {
+                redis_url,
+                block_ttl: cache_config
+                    .block_ttl
+                    .unwrap_or_else(CommonDatabaseRedisCacheConfig::default_block_ttl),
+                account_ttl: cache_config
+                    .account_ttl
+                    .unwrap_or_else(CommonDatabaseRedisCacheConfig::default_account_ttl),
+                contract_code_ttl: cache_config
+                    .contract_code_ttl
+                    .unwrap_or_else(CommonDatabaseRedisCacheConfig::default_contract_code_ttl),
+                access_key_ttl: cache_config
+                    .access_key_ttl
+                    .unwrap_or_else(CommonDatabaseRedisCacheConfig::default_access_key_ttl),
+                state_ttl: cache_config
+                    .state_ttl
+                    .unwrap_or_else(CommonDatabaseRedisCacheConfig::default_state_ttl),
+            })
+    }
+}
+
+/// Storage backend behind the database managers, selected at startup
+#[derive(serde_derive::Deserialize, PartialEq, Debug, Clone, Copy, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum DatabaseBackend {
+    #[default]
+    Postgres,
+    /// Analytical storage of the tx-indexer data, it is not read by the rpc-server
+    Clickhouse,
+}
+
+impl std::str::FromStr for DatabaseBackend {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "postgres" | "postgresql" => Ok(DatabaseBackend::Postgres),
+            "clickhouse" => Ok(DatabaseBackend::Clickhouse),
+            // The data layout of the ScyllaDB backend is not compatible with the current indexers,
+            // the data has to be re-indexed to the PostgreSQL
+            "scylla" | "scylladb" => Err(anyhow::anyhow!(
+                "ScyllaDB database backend is not supported anymore, use PostgreSQL"
+            )),
+            _ => Err(anyhow::anyhow!("Invalid database backend: {}", s)),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct DatabaseConfig {
+    pub backend: DatabaseBackend,
     pub database_url: DatabaseConnectUrl,
     pub shards_config:
         std::collections::HashMap<near_primitives::types::ShardId, DatabaseConnectUrl>,
@@ -25,21 +215,51 @@ pub struct DatabaseConfig {
     // Migrations cannot be applied to read-only replicas
     // We should run rpc-server only on read-only replicas
     pub read_only: bool,
+    // Credentials used to apply migrations (DDL).
+    // If not provided, migrations are applied with the indexer credentials
+    pub migrations_credentials: Option<DatabaseCredentials>,
+    // Credentials used by indexers to write the data
+    pub writer_credentials: Option<DatabaseCredentials>,
+    // Credentials used by rpc-server to read the data
+    pub reader_credentials: Option<DatabaseCredentials>,
+    pub tls: DatabaseTlsConfig,
+    // Shards replicas in the preferred datacenter.
+    // Used by rpc-server for cache-like lookups (receipts_map) which tolerate the replication lag
+    pub local_replicas_config:
+        std::collections::HashMap<near_primitives::types::ShardId, DatabaseConnectUrl>,
+    // Secret to sign the pagination cursors returned to the clients
+    pub cursor_secret: Option<String>,
+    // Redis cache of the hot reads of the rpc-server, disabled if not set
+    pub redis_cache: Option<DatabaseRedisCacheConfig>,
+    // Number of the buckets the contract state of the account is split into by the key hash.
+    // 0 keeps the whole state of the account in one partition
+    pub state_key_buckets: u32,
 }
 
 impl DatabaseConfig {
     pub fn to_read_only(&self) -> Self {
         Self {
-            database_url: self.database_url.clone(),
-            shards_config: self.shards_config.clone(),
-            max_connections: self.max_connections,
             read_only: true,
+            ..self.clone()
+        }
+    }
+
+    /// Credentials of the component which use the config.
+    /// Reader credentials for read-only mode and writer credentials otherwise
+    pub fn component_credentials(&self) -> Option<&DatabaseCredentials> {
+        if self.read_only {
+            self.reader_credentials.as_ref()
+        } else {
+            self.writer_credentials.as_ref()
         }
     }
 }
 
 #[derive(Validate, serde_derive::Deserialize, Debug, Clone, Default)]
 pub struct CommonDatabaseConfig {
+    // Invalid backend fails the config reading instead of falling back to the default one
+    #[serde(deserialize_with = "deserialize_data_or_env", default)]
+    pub backend: DatabaseBackend,
     #[validate(url(message = "Invalid database URL"))]
     #[serde(deserialize_with = "deserialize_data_or_env")]
     pub database_url: DatabaseConnectUrl,
@@ -48,6 +268,25 @@ pub struct CommonDatabaseConfig {
     pub shards: Vec<ShardDatabaseConfig>,
     #[serde(deserialize_with = "deserialize_optional_data_or_env", default)]
     pub max_connections: Option<u32>,
+    #[serde(default)]
+    pub migrations_credentials: CommonDatabaseCredentialsConfig,
+    #[serde(default)]
+    pub writer_credentials: CommonDatabaseCredentialsConfig,
+    #[serde(default)]
+    pub reader_credentials: CommonDatabaseCredentialsConfig,
+    #[serde(default)]
+    pub tls: CommonDatabaseTlsConfig,
+    #[serde(deserialize_with = "deserialize_optional_data_or_env", default)]
+    pub preferred_datacenter: Option<String>,
+    #[validate(nested)]
+    #[serde(default)]
+    pub replicas: Vec<ShardDatabaseReplicaConfig>,
+    #[serde(deserialize_with = "deserialize_optional_data_or_env", default)]
+    pub cursor_secret: Option<String>,
+    #[serde(default)]
+    pub redis_cache: CommonDatabaseRedisCacheConfig,
+    #[serde(deserialize_with = "deserialize_optional_data_or_env", default)]
+    pub state_key_buckets: Option<u32>,
 }
 
 impl CommonDatabaseConfig {
@@ -61,11 +300,25 @@ impl CommonDatabaseConfig {
     pub fn default_max_connections() -> u32 {
         10
     }
+
+    pub fn default_state_key_buckets() -> u32 {
+        0
+    }
 }
 
 impl From<CommonDatabaseConfig> for DatabaseConfig {
     fn from(database_config: CommonDatabaseConfig) -> Self {
+        let local_replicas_config = match &database_config.preferred_datacenter {
+            Some(preferred_datacenter) => database_config
+                .replicas
+                .iter()
+                .filter(|replica| &replica.datacenter == preferred_datacenter)
+                .map(|replica| (replica.shard_id, replica.database_url.clone()))
+                .collect(),
+            None => std::collections::HashMap::new(),
+        };
         Self {
+            backend: database_config.backend,
             database_url: database_config.database_url,
             shards_config: database_config
                 .shards
@@ -76,6 +329,16 @@ impl From<CommonDatabaseConfig> for DatabaseConfig {
                 .max_connections
                 .unwrap_or_else(CommonDatabaseConfig::default_max_connections),
             read_only: false,
+            migrations_credentials: database_config.migrations_credentials.into(),
+            writer_credentials: database_config.writer_credentials.into(),
+            reader_credentials: database_config.reader_credentials.into(),
+            tls: database_config.tls.into(),
+            local_replicas_config,
+            cursor_secret: database_config.cursor_secret,
+            redis_cache: database_config.redis_cache.into(),
+            state_key_buckets: database_config
+                .state_key_buckets
+                .unwrap_or_else(CommonDatabaseConfig::default_state_key_buckets),
         }
     }
 }
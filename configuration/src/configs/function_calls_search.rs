@@ -0,0 +1,45 @@
+use readnode_primitives::indexer::types::AccountId;
+use serde_derive::Deserialize;
+
+use crate::configs::deserialize_optional_data_or_env;
+
+#[derive(Debug, Clone)]
+pub struct FunctionCallsSearchConfig {
+    /// Only the function calls to these contracts are stored to be searched
+    pub contracts: Vec<AccountId>,
+    /// Decoded arguments longer than this number of bytes are truncated
+    pub max_args_size: usize,
+}
+
+impl FunctionCallsSearchConfig {
+    pub fn is_searchable_contract(&self, receiver_id: &AccountId) -> bool {
+        self.contracts.contains(receiver_id)
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct CommonFunctionCallsSearchConfig {
+    #[serde(default)]
+    pub contracts: Vec<AccountId>,
+    #[serde(deserialize_with = "deserialize_optional_data_or_env", default)]
+    pub max_args_size: Option<usize>,
+}
+
+impl CommonFunctionCallsSearchConfig {
+    pub fn default_max_args_size() -> usize {
+        4096
+    }
+
+    /// The search is disabled if there are no contracts to store the function calls of
+    pub fn into_config(self) -> Option<FunctionCallsSearchConfig> {
+        if self.contracts.is_empty() {
+            return None;
+        }
+        Some(FunctionCallsSearchConfig {
+            contracts: self.contracts,
+            max_args_size: self
+                .max_args_size
+                .unwrap_or_else(Self::default_max_args_size),
+        })
+    }
+}
@@ -12,14 +12,42 @@ pub struct GeneralRpcServerConfig {
     pub chain_id: ChainId,
     pub near_rpc_url: String,
     pub near_archival_rpc_url: Option<String>,
+    pub near_rpc_api_key: Option<String>,
     pub redis_url: url::Url,
     pub referer_header_value: String,
     pub server_port: u16,
     pub max_gas_burnt: u64,
     pub contract_code_cache_size: f64,
     pub block_cache_size: f64,
+    pub caches_memory_budget_size: Option<f64>,
     pub shadow_data_consistency_rate: f64,
     pub prefetch_state_size_limit: u64,
+    pub max_request_payload_size: usize,
+    pub view_state_max_keys: usize,
+    pub max_response_size: usize,
+    pub pending_nonce_ttl: Option<u64>,
+    pub tx_pool_ttl: u64,
+    pub api_keys: Vec<ApiKeyConfig>,
+    pub require_api_key: bool,
+    pub min_block_height_wait: u64,
+    pub max_outcome_logs_size: Option<usize>,
+    pub cache_warming_blocks: u64,
+    pub cache_warming_concurrency: usize,
+    pub allow_newer_schema: bool,
+    pub block_prefetch_count: u64,
+    pub send_tx_preflight: bool,
+    pub ws_max_subscriptions: usize,
+    pub max_blob_size: usize,
+    pub http2: bool,
+    pub http2_max_concurrent_streams: Option<usize>,
+    pub keep_alive_secs: u64,
+    pub client_request_timeout_ms: u64,
+    pub max_connections: usize,
+    pub strict_params: bool,
+    pub grpc_port: Option<u16>,
+    pub enabled_methods: Option<Vec<String>>,
+    pub disabled_methods: Vec<String>,
+    pub access_sampling_rate: Option<f64>,
 }
 
 #[derive(Debug, Clone)]
@@ -27,9 +55,17 @@ pub struct GeneralTxIndexerConfig {
     pub chain_id: ChainId,
     pub near_rpc_url: String,
     pub near_archival_rpc_url: Option<String>,
+    pub near_rpc_api_key: Option<String>,
     pub redis_url: url::Url,
     pub indexer_id: String,
     pub metrics_server_port: u16,
+    pub write_queue_size: usize,
+    pub write_workers: usize,
+    pub save_block_processing_stats: bool,
+    pub group_id: Option<String>,
+    pub index_actions: bool,
+    pub save_batch_size: usize,
+    pub fold_refund_receipts: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -37,10 +73,36 @@ pub struct GeneralStateIndexerConfig {
     pub chain_id: ChainId,
     pub near_rpc_url: String,
     pub near_archival_rpc_url: Option<String>,
+    pub near_rpc_api_key: Option<String>,
     pub referer_header_value: String,
     pub indexer_id: String,
     pub metrics_server_port: u16,
     pub concurrency: usize,
+    pub state_growth_analytics: bool,
+    pub state_growth_alert_bytes: Option<u64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct GeneralEpochIndexerConfig {
+    pub chain_id: ChainId,
+    pub near_rpc_url: String,
+    pub near_archival_rpc_url: Option<String>,
+    pub near_rpc_api_key: Option<String>,
+    pub referer_header_value: String,
+    pub indexer_id: String,
+    pub metrics_server_port: u16,
+}
+
+#[derive(Debug, Clone)]
+pub struct GeneralProberConfig {
+    pub read_rpc_url: String,
+    pub api_key: Option<String>,
+    pub mix: String,
+    pub view_calls: Vec<String>,
+    pub interval_ms: u64,
+    pub timeout_ms: u64,
+    pub recent_blocks: u64,
+    pub metrics_server_port: u16,
 }
 
 #[derive(Debug, Clone)]
@@ -60,6 +122,8 @@ pub struct CommonGeneralConfig {
     #[validate(url(message = "Invalid NEAR Archival RPC URL"))]
     #[serde(deserialize_with = "deserialize_optional_data_or_env", default)]
     pub near_archival_rpc_url: Option<String>,
+    #[serde(deserialize_with = "deserialize_optional_data_or_env", default)]
+    pub near_rpc_api_key: Option<String>,
     #[validate(url(message = "Invalid referer header value"))]
     #[serde(deserialize_with = "deserialize_optional_data_or_env", default)]
     pub referer_header_value: Option<String>,
@@ -75,32 +139,87 @@ pub struct CommonGeneralConfig {
     pub state_indexer: CommonGeneralStateIndexerConfig,
     #[serde(default)]
     pub near_state_indexer: CommonGeneralNearStateIndexerConfig,
+    #[serde(default)]
+    pub epoch_indexer: CommonGeneralEpochIndexerConfig,
+    #[serde(default)]
+    pub prober: CommonGeneralProberConfig,
 }
 
+/// Chain id of the network, the same string as `chain_id` in the genesis config of the network
 #[derive(Deserialize, PartialEq, Debug, Clone, Default)]
-#[serde(rename_all = "lowercase")]
+#[serde(try_from = "String")]
 pub enum ChainId {
     #[default]
     Mainnet,
     Testnet,
     Betanet,
     Localnet,
+    /// Any other network, e.g. a localnet with the custom chain id in the genesis
+    Custom(String),
+}
+
+impl ChainId {
+    pub fn as_str(&self) -> &str {
+        match self {
+            ChainId::Mainnet => "mainnet",
+            ChainId::Testnet => "testnet",
+            ChainId::Betanet => "betanet",
+            ChainId::Localnet => "localnet",
+            ChainId::Custom(chain_id) => chain_id,
+        }
+    }
+
+    /// Checks the node the component is connected to is on the configured network
+    pub fn ensure_matches(&self, node_chain_id: &str) -> anyhow::Result<()> {
+        if self.as_str() != node_chain_id {
+            anyhow::bail!(
+                "Configured chain id `{}` doesn't match the chain id `{}` of the node",
+                self,
+                node_chain_id
+            );
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for ChainId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
 }
 
 impl FromStr for ChainId {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s {
+        // The well-known networks are matched case-insensitively,
+        // the custom chain id is kept as is to compare it with the genesis
+        let chain_id = s.trim();
+        match chain_id.to_lowercase().as_str() {
             "mainnet" => Ok(ChainId::Mainnet),
             "testnet" => Ok(ChainId::Testnet),
             "localnet" => Ok(ChainId::Localnet),
             "betanet" => Ok(ChainId::Betanet),
-            _ => Err(anyhow::anyhow!("Invalid chain id")),
+            "" => Err(anyhow::anyhow!("Invalid chain id: empty string")),
+            _ if !chain_id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')) =>
+            {
+                Err(anyhow::anyhow!("Invalid chain id: {}", s))
+            }
+            _ => Ok(ChainId::Custom(chain_id.to_string())),
         }
     }
 }
 
+impl TryFrom<String> for ChainId {
+    type Error = anyhow::Error;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
 #[derive(Validate, Deserialize, Debug, Clone)]
 pub struct CommonGeneralRpcServerConfig {
     #[serde(deserialize_with = "deserialize_optional_data_or_env", default)]
@@ -119,6 +238,12 @@ pub struct CommonGeneralRpcServerConfig {
     ))]
     #[serde(deserialize_with = "deserialize_optional_data_or_env", default)]
     pub block_cache_size: Option<f64>,
+    #[validate(range(
+        min = 0.0,
+        message = "Caches memory budget size must be greater than or equal to 0"
+    ))]
+    #[serde(deserialize_with = "deserialize_optional_data_or_env", default)]
+    pub caches_memory_budget_size: Option<f64>,
     #[validate(range(
         min = 0.0,
         max = 100.0,
@@ -128,6 +253,71 @@ pub struct CommonGeneralRpcServerConfig {
     pub shadow_data_consistency_rate: Option<f64>,
     #[serde(deserialize_with = "deserialize_optional_data_or_env", default)]
     pub prefetch_state_size_limit: Option<u64>,
+    #[serde(deserialize_with = "deserialize_optional_data_or_env", default)]
+    pub max_request_payload_size: Option<usize>,
+    #[serde(deserialize_with = "deserialize_optional_data_or_env", default)]
+    pub view_state_max_keys: Option<usize>,
+    #[serde(deserialize_with = "deserialize_optional_data_or_env", default)]
+    pub max_response_size: Option<usize>,
+    #[serde(deserialize_with = "deserialize_optional_data_or_env", default)]
+    pub pending_nonce_ttl: Option<u64>,
+    #[serde(deserialize_with = "deserialize_optional_data_or_env", default)]
+    pub tx_pool_ttl: Option<u64>,
+    #[serde(default)]
+    pub api_keys: Vec<ApiKeyConfig>,
+    #[serde(deserialize_with = "deserialize_optional_data_or_env", default)]
+    pub require_api_key: Option<bool>,
+    #[serde(deserialize_with = "deserialize_optional_data_or_env", default)]
+    pub min_block_height_wait: Option<u64>,
+    #[serde(deserialize_with = "deserialize_optional_data_or_env", default)]
+    pub max_outcome_logs_size: Option<usize>,
+    #[serde(deserialize_with = "deserialize_optional_data_or_env", default)]
+    pub cache_warming_blocks: Option<u64>,
+    #[serde(deserialize_with = "deserialize_optional_data_or_env", default)]
+    pub cache_warming_concurrency: Option<usize>,
+    #[serde(deserialize_with = "deserialize_optional_data_or_env", default)]
+    pub allow_newer_schema: Option<bool>,
+    #[serde(deserialize_with = "deserialize_optional_data_or_env", default)]
+    pub block_prefetch_count: Option<u64>,
+    #[serde(deserialize_with = "deserialize_optional_data_or_env", default)]
+    pub send_tx_preflight: Option<bool>,
+    #[serde(deserialize_with = "deserialize_optional_data_or_env", default)]
+    pub ws_max_subscriptions: Option<usize>,
+    #[serde(deserialize_with = "deserialize_optional_data_or_env", default)]
+    pub max_blob_size: Option<usize>,
+    #[serde(deserialize_with = "deserialize_optional_data_or_env", default)]
+    pub http2: Option<bool>,
+    #[serde(deserialize_with = "deserialize_optional_data_or_env", default)]
+    pub http2_max_concurrent_streams: Option<usize>,
+    #[serde(deserialize_with = "deserialize_optional_data_or_env", default)]
+    pub keep_alive_secs: Option<u64>,
+    #[serde(deserialize_with = "deserialize_optional_data_or_env", default)]
+    pub client_request_timeout_ms: Option<u64>,
+    #[serde(deserialize_with = "deserialize_optional_data_or_env", default)]
+    pub max_connections: Option<usize>,
+    #[serde(deserialize_with = "deserialize_optional_data_or_env", default)]
+    pub strict_params: Option<bool>,
+    #[serde(deserialize_with = "deserialize_optional_data_or_env", default)]
+    pub grpc_port: Option<u16>,
+    #[serde(default)]
+    pub enabled_methods: Option<Vec<String>>,
+    #[serde(default)]
+    pub disabled_methods: Vec<String>,
+    #[serde(deserialize_with = "deserialize_optional_data_or_env", default)]
+    pub access_sampling_rate: Option<f64>,
+}
+
+/// API key of the rpc-server clients
+#[derive(Deserialize, Debug, Clone)]
+pub struct ApiKeyConfig {
+    /// Name of the key used in the logs instead of the key itself
+    #[serde(deserialize_with = "deserialize_data_or_env")]
+    pub name: String,
+    #[serde(deserialize_with = "deserialize_data_or_env")]
+    pub key: String,
+    /// Methods allowed for the key, all the methods are allowed if not set
+    #[serde(default)]
+    pub allowed_methods: Option<Vec<String>>,
 }
 
 impl CommonGeneralRpcServerConfig {
@@ -154,6 +344,58 @@ impl CommonGeneralRpcServerConfig {
     pub fn default_prefetch_state_size_limit() -> u64 {
         1_000_000
     }
+
+    pub fn default_max_request_payload_size() -> usize {
+        10 * 1024 * 1024
+    }
+
+    pub fn default_view_state_max_keys() -> usize {
+        50_000
+    }
+
+    pub fn default_max_response_size() -> usize {
+        50 * 1024 * 1024
+    }
+
+    pub fn default_tx_pool_ttl() -> u64 {
+        600
+    }
+
+    pub fn default_min_block_height_wait() -> u64 {
+        500
+    }
+
+    pub fn default_cache_warming_blocks() -> u64 {
+        500
+    }
+
+    pub fn default_cache_warming_concurrency() -> usize {
+        10
+    }
+
+    pub fn default_block_prefetch_count() -> u64 {
+        10
+    }
+
+    pub fn default_ws_max_subscriptions() -> usize {
+        10
+    }
+
+    pub fn default_max_blob_size() -> usize {
+        64 * 1024 * 1024
+    }
+
+    pub fn default_keep_alive_secs() -> u64 {
+        5
+    }
+
+    pub fn default_client_request_timeout_ms() -> u64 {
+        5000
+    }
+
+    pub fn default_max_connections() -> usize {
+        25_000
+    }
 }
 
 impl Default for CommonGeneralRpcServerConfig {
@@ -163,8 +405,35 @@ impl Default for CommonGeneralRpcServerConfig {
             max_gas_burnt: Some(Self::default_max_gas_burnt()),
             contract_code_cache_size: Some(Self::default_contract_code_cache_size()),
             block_cache_size: Some(Self::default_block_cache_size()),
+            caches_memory_budget_size: None,
             shadow_data_consistency_rate: Some(Self::default_shadow_data_consistency_rate()),
             prefetch_state_size_limit: Some(Self::default_prefetch_state_size_limit()),
+            max_request_payload_size: Some(Self::default_max_request_payload_size()),
+            view_state_max_keys: Some(Self::default_view_state_max_keys()),
+            max_response_size: Some(Self::default_max_response_size()),
+            pending_nonce_ttl: None,
+            tx_pool_ttl: Some(Self::default_tx_pool_ttl()),
+            api_keys: vec![],
+            require_api_key: Some(false),
+            min_block_height_wait: Some(Self::default_min_block_height_wait()),
+            max_outcome_logs_size: None,
+            cache_warming_blocks: Some(Self::default_cache_warming_blocks()),
+            cache_warming_concurrency: Some(Self::default_cache_warming_concurrency()),
+            allow_newer_schema: Some(false),
+            block_prefetch_count: Some(Self::default_block_prefetch_count()),
+            send_tx_preflight: Some(false),
+            ws_max_subscriptions: Some(Self::default_ws_max_subscriptions()),
+            max_blob_size: Some(Self::default_max_blob_size()),
+            http2: Some(true),
+            http2_max_concurrent_streams: None,
+            keep_alive_secs: Some(Self::default_keep_alive_secs()),
+            client_request_timeout_ms: Some(Self::default_client_request_timeout_ms()),
+            max_connections: Some(Self::default_max_connections()),
+            strict_params: Some(false),
+            grpc_port: None,
+            enabled_methods: None,
+            disabled_methods: vec![],
+            access_sampling_rate: None,
         }
     }
 }
@@ -175,6 +444,20 @@ pub struct CommonGeneralTxIndexerConfig {
     pub indexer_id: Option<String>,
     #[serde(deserialize_with = "deserialize_optional_data_or_env", default)]
     pub metrics_server_port: Option<u16>,
+    #[serde(deserialize_with = "deserialize_optional_data_or_env", default)]
+    pub write_queue_size: Option<usize>,
+    #[serde(deserialize_with = "deserialize_optional_data_or_env", default)]
+    pub write_workers: Option<usize>,
+    #[serde(deserialize_with = "deserialize_optional_data_or_env", default)]
+    pub save_block_processing_stats: Option<bool>,
+    #[serde(deserialize_with = "deserialize_optional_data_or_env", default)]
+    pub group_id: Option<String>,
+    #[serde(deserialize_with = "deserialize_optional_data_or_env", default)]
+    pub index_actions: Option<bool>,
+    #[serde(deserialize_with = "deserialize_optional_data_or_env", default)]
+    pub save_batch_size: Option<usize>,
+    #[serde(deserialize_with = "deserialize_optional_data_or_env", default)]
+    pub fold_refund_receipts: Option<bool>,
 }
 
 impl CommonGeneralTxIndexerConfig {
@@ -185,6 +468,30 @@ impl CommonGeneralTxIndexerConfig {
     pub fn default_metrics_server_port() -> u16 {
         8080
     }
+
+    pub fn default_write_queue_size() -> usize {
+        10_000
+    }
+
+    pub fn default_write_workers() -> usize {
+        4
+    }
+
+    pub fn default_save_block_processing_stats() -> bool {
+        false
+    }
+
+    pub fn default_index_actions() -> bool {
+        false
+    }
+
+    pub fn default_save_batch_size() -> usize {
+        1000
+    }
+
+    pub fn default_fold_refund_receipts() -> bool {
+        false
+    }
 }
 
 impl Default for CommonGeneralTxIndexerConfig {
@@ -192,6 +499,13 @@ impl Default for CommonGeneralTxIndexerConfig {
         Self {
             indexer_id: Some(Self::default_indexer_id()),
             metrics_server_port: Some(Self::default_metrics_server_port()),
+            write_queue_size: Some(Self::default_write_queue_size()),
+            write_workers: Some(Self::default_write_workers()),
+            save_block_processing_stats: Some(Self::default_save_block_processing_stats()),
+            group_id: None,
+            index_actions: Some(Self::default_index_actions()),
+            save_batch_size: Some(Self::default_save_batch_size()),
+            fold_refund_receipts: Some(Self::default_fold_refund_receipts()),
         }
     }
 }
@@ -204,6 +518,10 @@ pub struct CommonGeneralStateIndexerConfig {
     pub metrics_server_port: Option<u16>,
     #[serde(deserialize_with = "deserialize_optional_data_or_env", default)]
     pub concurrency: Option<usize>,
+    #[serde(deserialize_with = "deserialize_optional_data_or_env", default)]
+    pub state_growth_analytics: Option<bool>,
+    #[serde(deserialize_with = "deserialize_optional_data_or_env", default)]
+    pub state_growth_alert_bytes: Option<u64>,
 }
 
 impl CommonGeneralStateIndexerConfig {
@@ -226,6 +544,8 @@ impl Default for CommonGeneralStateIndexerConfig {
             indexer_id: Some(Self::default_indexer_id()),
             metrics_server_port: Some(Self::default_metrics_server_port()),
             concurrency: Some(Self::default_concurrency()),
+            state_growth_analytics: Some(false),
+            state_growth_alert_bytes: None,
         }
     }
 }
@@ -250,12 +570,116 @@ impl Default for CommonGeneralNearStateIndexerConfig {
     }
 }
 
+#[derive(Deserialize, Debug, Clone)]
+pub struct CommonGeneralEpochIndexerConfig {
+    #[serde(deserialize_with = "deserialize_optional_data_or_env", default)]
+    pub indexer_id: Option<String>,
+    #[serde(deserialize_with = "deserialize_optional_data_or_env", default)]
+    pub metrics_server_port: Option<u16>,
+}
+
+impl CommonGeneralEpochIndexerConfig {
+    pub fn default_indexer_id() -> String {
+        "epoch-indexer".to_string()
+    }
+
+    pub fn default_metrics_server_port() -> u16 {
+        8083
+    }
+}
+
+impl Default for CommonGeneralEpochIndexerConfig {
+    fn default() -> Self {
+        Self {
+            indexer_id: Some(Self::default_indexer_id()),
+            metrics_server_port: Some(Self::default_metrics_server_port()),
+        }
+    }
+}
+
+/// Contract methods called by the prober as `contract_id:method_name:args_json`.
+/// Given as the TOML array or as the environment variable with the calls separated by `;`
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct ProberViewCalls(pub Vec<String>);
+
+impl FromStr for ProberViewCalls {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self(
+            s.split(';')
+                .map(str::trim)
+                .filter(|view_call| !view_call.is_empty())
+                .map(str::to_string)
+                .collect(),
+        ))
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct CommonGeneralProberConfig {
+    #[serde(deserialize_with = "deserialize_optional_data_or_env", default)]
+    pub read_rpc_url: Option<String>,
+    #[serde(deserialize_with = "deserialize_optional_data_or_env", default)]
+    pub api_key: Option<String>,
+    #[serde(deserialize_with = "deserialize_optional_data_or_env", default)]
+    pub mix: Option<String>,
+    #[serde(deserialize_with = "deserialize_optional_data_or_env", default)]
+    pub view_calls: Option<ProberViewCalls>,
+    #[serde(deserialize_with = "deserialize_optional_data_or_env", default)]
+    pub interval_ms: Option<u64>,
+    #[serde(deserialize_with = "deserialize_optional_data_or_env", default)]
+    pub timeout_ms: Option<u64>,
+    #[serde(deserialize_with = "deserialize_optional_data_or_env", default)]
+    pub recent_blocks: Option<u64>,
+    #[serde(deserialize_with = "deserialize_optional_data_or_env", default)]
+    pub metrics_server_port: Option<u16>,
+}
+
+impl CommonGeneralProberConfig {
+    pub fn default_mix() -> String {
+        "tx=1,account=1,view_call=1".to_string()
+    }
+
+    pub fn default_interval_ms() -> u64 {
+        1000
+    }
+
+    pub fn default_timeout_ms() -> u64 {
+        10_000
+    }
+
+    pub fn default_recent_blocks() -> u64 {
+        1000
+    }
+
+    pub fn default_metrics_server_port() -> u16 {
+        8084
+    }
+}
+
+impl Default for CommonGeneralProberConfig {
+    fn default() -> Self {
+        Self {
+            read_rpc_url: None,
+            api_key: None,
+            mix: Some(Self::default_mix()),
+            view_calls: None,
+            interval_ms: Some(Self::default_interval_ms()),
+            timeout_ms: Some(Self::default_timeout_ms()),
+            recent_blocks: Some(Self::default_recent_blocks()),
+            metrics_server_port: Some(Self::default_metrics_server_port()),
+        }
+    }
+}
+
 impl From<CommonGeneralConfig> for GeneralRpcServerConfig {
     fn from(common_config: CommonGeneralConfig) -> Self {
         Self {
             chain_id: common_config.chain_id,
             near_rpc_url: required_value_or_panic("near_rpc_url", common_config.near_rpc_url),
             near_archival_rpc_url: common_config.near_archival_rpc_url,
+            near_rpc_api_key: common_config.near_rpc_api_key,
             redis_url: url::Url::parse(
                 &common_config
                     .redis_url
@@ -281,6 +705,7 @@ impl From<CommonGeneralConfig> for GeneralRpcServerConfig {
                 .rpc_server
                 .block_cache_size
                 .unwrap_or_else(CommonGeneralRpcServerConfig::default_block_cache_size),
+            caches_memory_budget_size: common_config.rpc_server.caches_memory_budget_size,
             shadow_data_consistency_rate: common_config
                 .rpc_server
                 .shadow_data_consistency_rate
@@ -289,6 +714,77 @@ impl From<CommonGeneralConfig> for GeneralRpcServerConfig {
                 .rpc_server
                 .prefetch_state_size_limit
                 .unwrap_or_else(CommonGeneralRpcServerConfig::default_prefetch_state_size_limit),
+            max_request_payload_size: common_config
+                .rpc_server
+                .max_request_payload_size
+                .unwrap_or_else(CommonGeneralRpcServerConfig::default_max_request_payload_size),
+            view_state_max_keys: common_config
+                .rpc_server
+                .view_state_max_keys
+                .unwrap_or_else(CommonGeneralRpcServerConfig::default_view_state_max_keys),
+            max_response_size: common_config
+                .rpc_server
+                .max_response_size
+                .unwrap_or_else(CommonGeneralRpcServerConfig::default_max_response_size),
+            pending_nonce_ttl: common_config.rpc_server.pending_nonce_ttl,
+            tx_pool_ttl: common_config
+                .rpc_server
+                .tx_pool_ttl
+                .unwrap_or_else(CommonGeneralRpcServerConfig::default_tx_pool_ttl),
+            api_keys: common_config.rpc_server.api_keys,
+            require_api_key: common_config.rpc_server.require_api_key.unwrap_or_default(),
+            min_block_height_wait: common_config
+                .rpc_server
+                .min_block_height_wait
+                .unwrap_or_else(CommonGeneralRpcServerConfig::default_min_block_height_wait),
+            max_outcome_logs_size: common_config.rpc_server.max_outcome_logs_size,
+            cache_warming_blocks: common_config
+                .rpc_server
+                .cache_warming_blocks
+                .unwrap_or_else(CommonGeneralRpcServerConfig::default_cache_warming_blocks),
+            cache_warming_concurrency: common_config
+                .rpc_server
+                .cache_warming_concurrency
+                .unwrap_or_else(CommonGeneralRpcServerConfig::default_cache_warming_concurrency),
+            allow_newer_schema: common_config
+                .rpc_server
+                .allow_newer_schema
+                .unwrap_or_default(),
+            block_prefetch_count: common_config
+                .rpc_server
+                .block_prefetch_count
+                .unwrap_or_else(CommonGeneralRpcServerConfig::default_block_prefetch_count),
+            send_tx_preflight: common_config
+                .rpc_server
+                .send_tx_preflight
+                .unwrap_or_default(),
+            ws_max_subscriptions: common_config
+                .rpc_server
+                .ws_max_subscriptions
+                .unwrap_or_else(CommonGeneralRpcServerConfig::default_ws_max_subscriptions),
+            max_blob_size: common_config
+                .rpc_server
+                .max_blob_size
+                .unwrap_or_else(CommonGeneralRpcServerConfig::default_max_blob_size),
+            http2: common_config.rpc_server.http2.unwrap_or(true),
+            http2_max_concurrent_streams: common_config.rpc_server.http2_max_concurrent_streams,
+            keep_alive_secs: common_config
+                .rpc_server
+                .keep_alive_secs
+                .unwrap_or_else(CommonGeneralRpcServerConfig::default_keep_alive_secs),
+            client_request_timeout_ms: common_config
+                .rpc_server
+                .client_request_timeout_ms
+                .unwrap_or_else(CommonGeneralRpcServerConfig::default_client_request_timeout_ms),
+            max_connections: common_config
+                .rpc_server
+                .max_connections
+                .unwrap_or_else(CommonGeneralRpcServerConfig::default_max_connections),
+            strict_params: common_config.rpc_server.strict_params.unwrap_or_default(),
+            grpc_port: common_config.rpc_server.grpc_port,
+            enabled_methods: common_config.rpc_server.enabled_methods,
+            disabled_methods: common_config.rpc_server.disabled_methods,
+            access_sampling_rate: common_config.rpc_server.access_sampling_rate,
         }
     }
 }
@@ -299,6 +795,7 @@ impl From<CommonGeneralConfig> for GeneralTxIndexerConfig {
             chain_id: common_config.chain_id,
             near_rpc_url: required_value_or_panic("near_rpc_url", common_config.near_rpc_url),
             near_archival_rpc_url: common_config.near_archival_rpc_url,
+            near_rpc_api_key: common_config.near_rpc_api_key,
             redis_url: url::Url::parse(&required_value_or_panic(
                 "redis_url",
                 common_config.redis_url,
@@ -312,6 +809,31 @@ impl From<CommonGeneralConfig> for GeneralTxIndexerConfig {
                 .tx_indexer
                 .metrics_server_port
                 .unwrap_or_else(CommonGeneralTxIndexerConfig::default_metrics_server_port),
+            write_queue_size: common_config
+                .tx_indexer
+                .write_queue_size
+                .unwrap_or_else(CommonGeneralTxIndexerConfig::default_write_queue_size),
+            write_workers: common_config
+                .tx_indexer
+                .write_workers
+                .unwrap_or_else(CommonGeneralTxIndexerConfig::default_write_workers),
+            save_block_processing_stats: common_config
+                .tx_indexer
+                .save_block_processing_stats
+                .unwrap_or_else(CommonGeneralTxIndexerConfig::default_save_block_processing_stats),
+            group_id: common_config.tx_indexer.group_id,
+            index_actions: common_config
+                .tx_indexer
+                .index_actions
+                .unwrap_or_else(CommonGeneralTxIndexerConfig::default_index_actions),
+            save_batch_size: common_config
+                .tx_indexer
+                .save_batch_size
+                .unwrap_or_else(CommonGeneralTxIndexerConfig::default_save_batch_size),
+            fold_refund_receipts: common_config
+                .tx_indexer
+                .fold_refund_receipts
+                .unwrap_or_else(CommonGeneralTxIndexerConfig::default_fold_refund_receipts),
         }
     }
 }
@@ -322,6 +844,7 @@ impl From<CommonGeneralConfig> for GeneralStateIndexerConfig {
             chain_id: common_config.chain_id,
             near_rpc_url: required_value_or_panic("near_rpc_url", common_config.near_rpc_url),
             near_archival_rpc_url: common_config.near_archival_rpc_url,
+            near_rpc_api_key: common_config.near_rpc_api_key,
             referer_header_value: common_config
                 .referer_header_value
                 .unwrap_or("http://read-rpc.local".to_string()),
@@ -337,6 +860,33 @@ impl From<CommonGeneralConfig> for GeneralStateIndexerConfig {
                 .state_indexer
                 .concurrency
                 .unwrap_or_else(CommonGeneralStateIndexerConfig::default_concurrency),
+            state_growth_analytics: common_config
+                .state_indexer
+                .state_growth_analytics
+                .unwrap_or_default(),
+            state_growth_alert_bytes: common_config.state_indexer.state_growth_alert_bytes,
+        }
+    }
+}
+
+impl From<CommonGeneralConfig> for GeneralEpochIndexerConfig {
+    fn from(common_config: CommonGeneralConfig) -> Self {
+        Self {
+            chain_id: common_config.chain_id,
+            near_rpc_url: required_value_or_panic("near_rpc_url", common_config.near_rpc_url),
+            near_archival_rpc_url: common_config.near_archival_rpc_url,
+            near_rpc_api_key: common_config.near_rpc_api_key,
+            referer_header_value: common_config
+                .referer_header_value
+                .unwrap_or("http://read-rpc.local".to_string()),
+            indexer_id: common_config
+                .epoch_indexer
+                .indexer_id
+                .unwrap_or_else(CommonGeneralEpochIndexerConfig::default_indexer_id),
+            metrics_server_port: common_config
+                .epoch_indexer
+                .metrics_server_port
+                .unwrap_or_else(CommonGeneralEpochIndexerConfig::default_metrics_server_port),
         }
     }
 }
@@ -357,3 +907,40 @@ impl From<CommonGeneralConfig> for GeneralNearStateIndexerConfig {
         }
     }
 }
+
+impl From<CommonGeneralConfig> for GeneralProberConfig {
+    fn from(common_config: CommonGeneralConfig) -> Self {
+        Self {
+            read_rpc_url: required_value_or_panic(
+                "read_rpc_url",
+                common_config.prober.read_rpc_url,
+            ),
+            api_key: common_config.prober.api_key,
+            mix: common_config
+                .prober
+                .mix
+                .unwrap_or_else(CommonGeneralProberConfig::default_mix),
+            view_calls: common_config
+                .prober
+                .view_calls
+                .map(|view_calls| view_calls.0)
+                .unwrap_or_default(),
+            interval_ms: common_config
+                .prober
+                .interval_ms
+                .unwrap_or_else(CommonGeneralProberConfig::default_interval_ms),
+            timeout_ms: common_config
+                .prober
+                .timeout_ms
+                .unwrap_or_else(CommonGeneralProberConfig::default_timeout_ms),
+            recent_blocks: common_config
+                .prober
+                .recent_blocks
+                .unwrap_or_else(CommonGeneralProberConfig::default_recent_blocks),
+            metrics_server_port: common_config
+                .prober
+                .metrics_server_port
+                .unwrap_or_else(CommonGeneralProberConfig::default_metrics_server_port),
+        }
+    }
+}
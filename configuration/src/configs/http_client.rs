@@ -0,0 +1,98 @@
+use serde_derive::Deserialize;
+
+use crate::configs::deserialize_optional_data_or_env;
+
+#[derive(Debug, Clone)]
+pub struct HttpClientConfig {
+    /// PEM bundle of the CA certificates trusted in addition to the system ones
+    pub ca_bundle: Option<std::path::PathBuf>,
+    /// Max number of the idle connections kept open to every host
+    pub pool_max_idle_per_host: usize,
+    /// Idle connections are closed after this timeout
+    pub pool_idle_timeout: std::time::Duration,
+    pub connect_timeout: std::time::Duration,
+    pub request_timeout: std::time::Duration,
+    /// Number of attempts of the JSON-RPC request failed with the transient error
+    pub rpc_retry_attempts: usize,
+    /// Delay before the first retry, doubled for every next one
+    pub rpc_retry_delay: std::time::Duration,
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct CommonHttpClientConfig {
+    #[serde(deserialize_with = "deserialize_optional_data_or_env", default)]
+    pub ca_bundle: Option<String>,
+    #[serde(deserialize_with = "deserialize_optional_data_or_env", default)]
+    pub pool_max_idle_per_host: Option<usize>,
+    #[serde(deserialize_with = "deserialize_optional_data_or_env", default)]
+    pub pool_idle_timeout: Option<u64>,
+    #[serde(deserialize_with = "deserialize_optional_data_or_env", default)]
+    pub connect_timeout: Option<u64>,
+    #[serde(deserialize_with = "deserialize_optional_data_or_env", default)]
+    pub request_timeout: Option<u64>,
+    #[serde(deserialize_with = "deserialize_optional_data_or_env", default)]
+    pub rpc_retry_attempts: Option<usize>,
+    #[serde(deserialize_with = "deserialize_optional_data_or_env", default)]
+    pub rpc_retry_delay: Option<u64>,
+}
+
+impl CommonHttpClientConfig {
+    pub fn default_pool_max_idle_per_host() -> usize {
+        32
+    }
+
+    pub fn default_pool_idle_timeout() -> u64 {
+        90
+    }
+
+    pub fn default_connect_timeout() -> u64 {
+        10
+    }
+
+    pub fn default_request_timeout() -> u64 {
+        60
+    }
+
+    pub fn default_rpc_retry_attempts() -> usize {
+        3
+    }
+
+    pub fn default_rpc_retry_delay() -> u64 {
+        200
+    }
+}
+
+impl From<CommonHttpClientConfig> for HttpClientConfig {
+    fn from(common_config: CommonHttpClientConfig) -> Self {
+        Self {
+            ca_bundle: common_config.ca_bundle.map(std::path::PathBuf::from),
+            pool_max_idle_per_host: common_config
+                .pool_max_idle_per_host
+                .unwrap_or_else(CommonHttpClientConfig::default_pool_max_idle_per_host),
+            pool_idle_timeout: std::time::Duration::from_secs(
+                common_config
+                    .pool_idle_timeout
+                    .unwrap_or_else(CommonHttpClientConfig::default_pool_idle_timeout),
+            ),
+            connect_timeout: std::time::Duration::from_secs(
+                common_config
+                    .connect_timeout
+                    .unwrap_or_else(CommonHttpClientConfig::default_connect_timeout),
+            ),
+            request_timeout: std::time::Duration::from_secs(
+                common_config
+                    .request_timeout
+                    .unwrap_or_else(CommonHttpClientConfig::default_request_timeout),
+            ),
+            rpc_retry_attempts: common_config
+                .rpc_retry_attempts
+                .unwrap_or_else(CommonHttpClientConfig::default_rpc_retry_attempts)
+                .max(1),
+            rpc_retry_delay: std::time::Duration::from_millis(
+                common_config
+                    .rpc_retry_delay
+                    .unwrap_or_else(CommonHttpClientConfig::default_rpc_retry_delay),
+            ),
+        }
+    }
+}
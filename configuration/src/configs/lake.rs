@@ -1,18 +1,62 @@
 use aws_sdk_s3::config::StalledStreamProtectionConfig;
-use near_lake_framework::near_indexer_primitives::near_primitives;
+use readnode_primitives::indexer::near_primitives;
 use serde_derive::Deserialize;
 
 use crate::configs::{deserialize_optional_data_or_env, required_value_or_panic};
 
+/// S3 interoperability endpoint of Google Cloud Storage
+const GCS_ENDPOINT_URL: &str = "https://storage.googleapis.com";
+
+/// Storage the lake blocks are read from
+#[derive(serde_derive::Deserialize, PartialEq, Debug, Clone, Copy, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum LakeSource {
+    /// NEAR Lake S3 bucket
+    #[default]
+    S3,
+    /// Google Cloud Storage bucket mirroring the lake, read with the S3 interoperability API
+    /// and the HMAC keys of the service account as the AWS credentials
+    Gcs,
+    /// Local directory of the lake-format JSON files, e.g. a synced copy of the bucket
+    Filesystem,
+}
+
+impl std::str::FromStr for LakeSource {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "s3" => Ok(LakeSource::S3),
+            "gcs" => Ok(LakeSource::Gcs),
+            "filesystem" => Ok(LakeSource::Filesystem),
+            _ => Err(anyhow::anyhow!("Invalid lake source: {}", s)),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct LakeConfig {
+    pub source: LakeSource,
     pub aws_access_key_id: String,
     pub aws_secret_access_key: String,
     pub aws_default_region: String,
     pub aws_bucket_name: String,
+    /// Endpoint of the S3-compatible storage, the GCS one is used for the `gcs` source by default
+    pub endpoint_url: Option<String>,
+    /// Directory of the lake files for the `filesystem` source
+    pub blocks_path: Option<std::path::PathBuf>,
 }
 
 impl LakeConfig {
+    /// Overrides the source of the config, e.g. by the command line argument
+    pub fn with_source(&self, source: Option<LakeSource>) -> Self {
+        let mut lake_config = self.clone();
+        if let Some(source) = source {
+            lake_config.source = source;
+        }
+        lake_config
+    }
+
     pub async fn s3_config(&self) -> aws_sdk_s3::Config {
         let credentials = aws_credential_types::Credentials::new(
             &self.aws_access_key_id,
@@ -21,27 +65,39 @@ impl LakeConfig {
             None,
             "",
         );
-        aws_sdk_s3::Config::builder()
+        let mut builder = aws_sdk_s3::Config::builder()
             .stalled_stream_protection(StalledStreamProtectionConfig::disabled())
             .credentials_provider(credentials)
             .region(aws_types::region::Region::new(
                 self.aws_default_region.clone(),
-            ))
-            .build()
+            ));
+        let endpoint_url = match self.source {
+            LakeSource::Gcs => Some(self.endpoint_url.as_deref().unwrap_or(GCS_ENDPOINT_URL)),
+            LakeSource::S3 | LakeSource::Filesystem => self.endpoint_url.as_deref(),
+        };
+        if let Some(endpoint_url) = endpoint_url {
+            builder = builder.endpoint_url(endpoint_url);
+        }
+        builder.build()
     }
 
+    /// Builds the lake framework config.
+    /// `blocks_preload_pool_size` is the number of blocks fetched from S3 ahead of the processed one,
+    /// the lake framework default is used if it is not provided
     pub async fn lake_config(
         &self,
         start_block_height: near_primitives::types::BlockHeight,
+        blocks_preload_pool_size: Option<usize>,
     ) -> anyhow::Result<near_lake_framework::LakeConfig> {
-        let config_builder = near_lake_framework::LakeConfigBuilder::default();
-        Ok(config_builder
+        let mut config_builder = near_lake_framework::LakeConfigBuilder::default()
             .s3_config(self.s3_config().await)
             .s3_region_name(&self.aws_default_region)
             .s3_bucket_name(&self.aws_bucket_name)
-            .start_block_height(start_block_height)
-            .build()
-            .expect("Failed to build LakeConfig"))
+            .start_block_height(start_block_height);
+        if let Some(blocks_preload_pool_size) = blocks_preload_pool_size {
+            config_builder = config_builder.blocks_preload_pool_size(blocks_preload_pool_size);
+        }
+        Ok(config_builder.build().expect("Failed to build LakeConfig"))
     }
 
     pub async fn lake_s3_client(&self) -> near_lake_framework::s3_fetchers::LakeS3Client {
@@ -54,6 +110,8 @@ impl LakeConfig {
 
 #[derive(Deserialize, Debug, Clone, Default)]
 pub struct CommonLakeConfig {
+    #[serde(deserialize_with = "deserialize_optional_data_or_env", default)]
+    pub source: Option<LakeSource>,
     #[serde(deserialize_with = "deserialize_optional_data_or_env", default)]
     pub aws_access_key_id: Option<String>,
     #[serde(deserialize_with = "deserialize_optional_data_or_env", default)]
@@ -62,27 +120,34 @@ pub struct CommonLakeConfig {
     pub aws_default_region: Option<String>,
     #[serde(deserialize_with = "deserialize_optional_data_or_env", default)]
     pub aws_bucket_name: Option<String>,
+    #[serde(deserialize_with = "deserialize_optional_data_or_env", default)]
+    pub endpoint_url: Option<String>,
+    #[serde(deserialize_with = "deserialize_optional_data_or_env", default)]
+    pub blocks_path: Option<String>,
 }
 
 impl From<CommonLakeConfig> for LakeConfig {
     fn from(common_config: CommonLakeConfig) -> Self {
+        let source = common_config.source.unwrap_or_default();
+        // The local directory is read without the credentials
+        let required_value = |name: &str, value: Option<String>| match source {
+            LakeSource::S3 | LakeSource::Gcs => required_value_or_panic(name, value),
+            LakeSource::Filesystem => value.unwrap_or_default(),
+        };
         Self {
-            aws_access_key_id: required_value_or_panic(
-                "aws_access_key_id",
-                common_config.aws_access_key_id,
-            ),
-            aws_secret_access_key: required_value_or_panic(
+            source,
+            aws_access_key_id: required_value("aws_access_key_id", common_config.aws_access_key_id),
+            aws_secret_access_key: required_value(
                 "aws_secret_access_key",
                 common_config.aws_secret_access_key,
             ),
-            aws_default_region: required_value_or_panic(
+            aws_default_region: required_value(
                 "aws_default_region",
                 common_config.aws_default_region,
             ),
-            aws_bucket_name: required_value_or_panic(
-                "aws_bucket_name",
-                common_config.aws_bucket_name,
-            ),
+            aws_bucket_name: required_value("aws_bucket_name", common_config.aws_bucket_name),
+            endpoint_url: common_config.endpoint_url,
+            blocks_path: common_config.blocks_path.map(std::path::PathBuf::from),
         }
     }
 }
@@ -1,16 +1,17 @@
 use std::str::FromStr;
 
-use near_lake_framework::{
-    near_indexer_primitives, near_indexer_primitives::views::StateChangeValueView,
-};
+use readnode_primitives::indexer::{self, views::StateChangeValueView};
 use serde::Deserialize;
 use validator::Validate;
 
 pub(crate) mod database;
+pub(crate) mod function_calls_search;
 pub(crate) mod general;
+pub(crate) mod http_client;
 mod lake;
 mod rightsizing;
 mod tx_details_storage;
+pub(crate) mod tx_mirroring;
 
 lazy_static::lazy_static! {
     static ref RE_NAME_ENV: regex::Regex = regex::Regex::new(r"\$\{(?<env_name>\w+)}").unwrap();
@@ -88,6 +89,14 @@ pub struct CommonConfig {
     // This options needs only for tx_indexer and rpc_server
     #[serde(default)]
     pub tx_details_storage: tx_details_storage::CommonTxDetailStorageConfig,
+    #[serde(default)]
+    pub http_client: http_client::CommonHttpClientConfig,
+    // Optional, the function calls are stored by the tx_indexer only for the listed contracts
+    #[serde(default)]
+    pub function_calls_search: function_calls_search::CommonFunctionCallsSearchConfig,
+    // Optional, used only by the tx_indexer built with the `tx_mirroring` feature
+    #[serde(default)]
+    pub tx_mirroring: tx_mirroring::CommonTxMirroringConfig,
 }
 
 pub trait Config {
@@ -102,10 +111,7 @@ pub trait RightsizingConfig {
     fn state_should_be_indexed(&self, state_change_value: &StateChangeValueView) -> bool;
     /// Checks the transaction agains the rightsizing parameters to return the `bool`
     /// whether the provided transaction should be indexer or ignored
-    fn tx_should_be_indexed(
-        &self,
-        transaction: &near_indexer_primitives::IndexerTransactionWithOutcome,
-    ) -> bool;
+    fn tx_should_be_indexed(&self, transaction: &indexer::IndexerTransactionWithOutcome) -> bool;
 }
 
 /// This trait is used to provide the common methods for different indexer configs
@@ -120,6 +126,7 @@ pub struct RpcServerConfig {
     pub lake_config: lake::LakeConfig,
     pub database: database::DatabaseConfig,
     pub tx_details_storage: tx_details_storage::TxDetailsStorageConfig,
+    pub http_client: http_client::HttpClientConfig,
 }
 
 impl Config for RpcServerConfig {
@@ -131,6 +138,7 @@ impl Config for RpcServerConfig {
             tx_details_storage: tx_details_storage::TxDetailsStorageConfig::from(
                 common_config.tx_details_storage,
             ),
+            http_client: common_config.http_client.into(),
         }
     }
 }
@@ -142,15 +150,25 @@ pub struct TxIndexerConfig {
     pub lake_config: lake::LakeConfig,
     pub database: database::DatabaseConfig,
     pub tx_details_storage: tx_details_storage::TxDetailsStorageConfig,
+    pub tx_mirroring: Option<tx_mirroring::TxMirroringConfig>,
+    pub function_calls_search: Option<function_calls_search::FunctionCallsSearchConfig>,
+    pub http_client: http_client::HttpClientConfig,
 }
 
 impl TxIndexerConfig {
     pub fn tx_should_be_indexed(
         &self,
-        transaction: &near_indexer_primitives::IndexerTransactionWithOutcome,
+        transaction: &indexer::IndexerTransactionWithOutcome,
     ) -> bool {
         self.rightsizing.tx_should_be_indexed(transaction)
     }
+
+    pub fn signed_tx_should_be_indexed(
+        &self,
+        transaction: &indexer::views::SignedTransactionView,
+    ) -> bool {
+        self.rightsizing.signed_tx_should_be_indexed(transaction)
+    }
 }
 
 impl Config for TxIndexerConfig {
@@ -163,6 +181,9 @@ impl Config for TxIndexerConfig {
             tx_details_storage: tx_details_storage::TxDetailsStorageConfig::from(
                 common_config.tx_details_storage,
             ),
+            tx_mirroring: common_config.tx_mirroring.into_config(),
+            function_calls_search: common_config.function_calls_search.into_config(),
+            http_client: common_config.http_client.into(),
         }
     }
 }
@@ -173,6 +194,7 @@ pub struct StateIndexerConfig {
     pub rightsizing: rightsizing::RightsizingConfig,
     pub lake_config: lake::LakeConfig,
     pub database: database::DatabaseConfig,
+    pub http_client: http_client::HttpClientConfig,
 }
 
 impl IndexerConfig for StateIndexerConfig {
@@ -186,10 +208,7 @@ impl RightsizingConfig for StateIndexerConfig {
         self.rightsizing.state_should_be_indexed(state_change_value)
     }
 
-    fn tx_should_be_indexed(
-        &self,
-        _transaction: &near_indexer_primitives::IndexerTransactionWithOutcome,
-    ) -> bool {
+    fn tx_should_be_indexed(&self, _transaction: &indexer::IndexerTransactionWithOutcome) -> bool {
         // this method is not used by any of the state-indexers, no need to implement it
         unimplemented!("StateIndexerConfig does not implement tx_should_be_indexed")
     }
@@ -202,6 +221,32 @@ impl Config for StateIndexerConfig {
             rightsizing: common_config.rightsizing.into(),
             lake_config: common_config.lake_config.into(),
             database: database::DatabaseConfig::from(common_config.database),
+            http_client: common_config.http_client.into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct EpochIndexerConfig {
+    pub general: general::GeneralEpochIndexerConfig,
+    pub lake_config: lake::LakeConfig,
+    pub database: database::DatabaseConfig,
+    pub http_client: http_client::HttpClientConfig,
+}
+
+impl IndexerConfig for EpochIndexerConfig {
+    fn indexer_id(&self) -> &str {
+        &self.general.indexer_id
+    }
+}
+
+impl Config for EpochIndexerConfig {
+    fn from_common_config(common_config: CommonConfig) -> Self {
+        Self {
+            general: common_config.general.into(),
+            lake_config: common_config.lake_config.into(),
+            database: database::DatabaseConfig::from(common_config.database),
+            http_client: common_config.http_client.into(),
         }
     }
 }
@@ -228,10 +273,7 @@ impl RightsizingConfig for NearStateIndexerConfig {
         self.rightsizing.state_should_be_indexed(state_change_value)
     }
 
-    fn tx_should_be_indexed(
-        &self,
-        _transaction: &near_indexer_primitives::IndexerTransactionWithOutcome,
-    ) -> bool {
+    fn tx_should_be_indexed(&self, _transaction: &indexer::IndexerTransactionWithOutcome) -> bool {
         // this method is not used by any of the state-indexers, no need to implement it
         unimplemented!("NearStateIndexerConfig does not implement tx_should_be_indexed")
     }
@@ -246,3 +288,16 @@ impl Config for NearStateIndexerConfig {
         }
     }
 }
+
+#[derive(Debug, Clone)]
+pub struct ProberConfig {
+    pub general: general::GeneralProberConfig,
+}
+
+impl Config for ProberConfig {
+    fn from_common_config(common_config: CommonConfig) -> Self {
+        Self {
+            general: common_config.general.into(),
+        }
+    }
+}
@@ -1,21 +1,19 @@
-use near_lake_framework::{
-    near_indexer_primitives, near_indexer_primitives::views::StateChangeValueView,
-};
+use readnode_primitives::indexer::{self, views::StateChangeValueView};
 use serde::Deserialize;
 use std::str::FromStr;
 
 use crate::configs::deserialize_optional_data_or_env;
 
 #[derive(Deserialize, Debug, Clone, Default)]
-pub struct TrackedAccounts(pub Vec<near_indexer_primitives::types::AccountId>);
+pub struct TrackedAccounts(pub Vec<indexer::types::AccountId>);
 
 impl FromStr for TrackedAccounts {
     type Err = serde_json::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(Self(serde_json::from_str::<
-            Vec<near_indexer_primitives::types::AccountId>,
-        >(s)?))
+        Ok(Self(
+            serde_json::from_str::<Vec<indexer::types::AccountId>>(s)?,
+        ))
     }
 }
 
@@ -48,12 +46,12 @@ pub struct CommonRightsizingConfig {
 
 #[derive(Debug, Clone)]
 pub struct RightsizingConfig {
-    pub tracked_accounts: Vec<near_indexer_primitives::types::AccountId>,
+    pub tracked_accounts: Vec<indexer::types::AccountId>,
     pub tracked_changes: Vec<ChangeType>,
 }
 
 impl RightsizingConfig {
-    fn is_indexed_account(&self, account: &near_indexer_primitives::types::AccountId) -> bool {
+    fn is_indexed_account(&self, account: &indexer::types::AccountId) -> bool {
         if self.tracked_accounts.is_empty() {
             true
         } else {
@@ -97,10 +95,18 @@ impl RightsizingConfig {
     /// So in future we should to index such transactions too if it will be needed
     pub fn tx_should_be_indexed(
         &self,
-        transaction: &near_indexer_primitives::IndexerTransactionWithOutcome,
+        transaction: &indexer::IndexerTransactionWithOutcome,
     ) -> bool {
-        self.is_indexed_account(&transaction.transaction.signer_id)
-            || self.is_indexed_account(&transaction.transaction.receiver_id)
+        self.signed_tx_should_be_indexed(&transaction.transaction)
+    }
+
+    /// Same as `tx_should_be_indexed` for the transactions fetched without the outcome
+    pub fn signed_tx_should_be_indexed(
+        &self,
+        transaction: &indexer::views::SignedTransactionView,
+    ) -> bool {
+        self.is_indexed_account(&transaction.signer_id)
+            || self.is_indexed_account(&transaction.receiver_id)
     }
 }
 
@@ -0,0 +1,64 @@
+use readnode_primitives::indexer::types::AccountId;
+use serde_derive::Deserialize;
+
+use crate::configs::deserialize_optional_data_or_env;
+
+#[derive(Debug, Clone)]
+pub struct TxMirroringConfig {
+    /// RPC endpoint of the near-sandbox or localnet node the transactions are forwarded to
+    pub rpc_url: String,
+    /// Only the transactions to these contracts are mirrored
+    pub contracts: Vec<AccountId>,
+    /// Secret keys the transactions of the signer accounts are re-signed with
+    pub dev_keys: std::collections::HashMap<AccountId, String>,
+    /// Secret key of the signers missing in `dev_keys`.
+    /// The transactions of such signers are skipped if it is not set
+    pub default_dev_key: Option<String>,
+    /// Number of the transactions waiting to be forwarded,
+    /// the new ones are dropped when the queue is full
+    pub queue_size: usize,
+}
+
+impl TxMirroringConfig {
+    pub fn is_mirrored_contract(&self, receiver_id: &AccountId) -> bool {
+        self.contracts.contains(receiver_id)
+    }
+
+    pub fn dev_key(&self, signer_id: &AccountId) -> Option<&str> {
+        self.dev_keys
+            .get(signer_id)
+            .or(self.default_dev_key.as_ref())
+            .map(String::as_str)
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct CommonTxMirroringConfig {
+    #[serde(deserialize_with = "deserialize_optional_data_or_env", default)]
+    pub rpc_url: Option<String>,
+    #[serde(default)]
+    pub contracts: Vec<AccountId>,
+    #[serde(default)]
+    pub dev_keys: std::collections::HashMap<AccountId, String>,
+    #[serde(deserialize_with = "deserialize_optional_data_or_env", default)]
+    pub default_dev_key: Option<String>,
+    #[serde(deserialize_with = "deserialize_optional_data_or_env", default)]
+    pub queue_size: Option<usize>,
+}
+
+impl CommonTxMirroringConfig {
+    pub fn default_queue_size() -> usize {
+        1000
+    }
+
+    /// The mirroring is disabled if the target RPC endpoint is not configured
+    pub fn into_config(self) -> Option<TxMirroringConfig> {
+        Some(TxMirroringConfig {
+            rpc_url: self.rpc_url?,
+            contracts: self.contracts,
+            dev_keys: self.dev_keys,
+            default_dev_key: self.default_dev_key,
+            queue_size: self.queue_size.unwrap_or_else(Self::default_queue_size),
+        })
+    }
+}
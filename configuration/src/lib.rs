@@ -6,11 +6,15 @@ use validator::Validate;
 
 mod configs;
 
-pub use crate::configs::database::DatabaseConfig;
-pub use crate::configs::general::ChainId;
+pub use crate::configs::database::{DatabaseBackend, DatabaseConfig, DatabaseRedisCacheConfig};
+pub use crate::configs::function_calls_search::FunctionCallsSearchConfig;
+pub use crate::configs::general::{ApiKeyConfig, ChainId};
+pub use crate::configs::http_client::HttpClientConfig;
+pub use crate::configs::lake::{LakeConfig, LakeSource};
+pub use crate::configs::tx_mirroring::TxMirroringConfig;
 pub use crate::configs::{
-    IndexerConfig, NearStateIndexerConfig, RightsizingConfig, RpcServerConfig, StateIndexerConfig,
-    TxIndexerConfig,
+    EpochIndexerConfig, IndexerConfig, NearStateIndexerConfig, ProberConfig, RightsizingConfig,
+    RpcServerConfig, StateIndexerConfig, TxIndexerConfig,
 };
 
 pub async fn read_configuration<T>() -> anyhow::Result<T>
@@ -28,25 +32,90 @@ where
     Ok(T::from_common_config(common_config))
 }
 
-pub async fn init_tracing(service_name: &str) -> anyhow::Result<()> {
+/// Re-reads the configuration of the running service.
+/// Unlike on startup, the invalid configuration is returned as the error to keep the service running
+pub async fn reload_configuration<T>() -> anyhow::Result<T>
+where
+    T: configs::Config + Send + Sync + 'static,
+{
     let path_root = find_configs_root().await?;
-    load_env(path_root.clone()).await?;
+    let common_config = read_toml_file(path_root).await?;
+    common_config
+        .validate()
+        .map_err(|validation_errors| anyhow::anyhow!("Invalid config: {validation_errors}"))?;
+    Ok(T::from_common_config(common_config))
+}
 
-    let mut env_filter = tracing_subscriber::EnvFilter::new(format!("{}=info,info", service_name));
+/// Handle to change the log filter of the running service without restarting it
+#[derive(Clone)]
+pub struct LogLevelHandle {
+    // Directives the service is started with, the runtime directives are applied on top of them
+    base_directives: Vec<String>,
+    handle: tracing_subscriber::reload::Handle<
+        tracing_subscriber::EnvFilter,
+        tracing_subscriber::Registry,
+    >,
+}
 
-    if let Ok(rust_log) = std::env::var("RUST_LOG") {
-        if !rust_log.is_empty() {
-            for directive in rust_log.split(',').filter_map(|s| match s.parse() {
-                Ok(directive) => Some(directive),
-                Err(err) => {
-                    eprintln!("Ignoring directive `{}`: {}", s, err);
-                    None
-                }
-            }) {
-                env_filter = env_filter.add_directive(directive);
-            }
+impl LogLevelHandle {
+    /// Replaces the runtime directives (e.g. `database=debug`) and returns the applied filter.
+    /// Empty directives restore the filter the service is started with
+    pub fn set_directives(&self, directives: &str) -> anyhow::Result<String> {
+        let mut env_filter = env_filter(&self.base_directives);
+        for directive in directives
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+        {
+            env_filter =
+                env_filter.add_directive(directive.parse().map_err(|err| {
+                    anyhow::anyhow!("Invalid directive `{}`: {}", directive, err)
+                })?);
         }
+        let applied_filter = env_filter.to_string();
+        self.handle.reload(env_filter)?;
+        Ok(applied_filter)
+    }
+
+    /// Log filter currently applied
+    pub fn current_filter(&self) -> anyhow::Result<String> {
+        Ok(self
+            .handle
+            .with_current(|env_filter| env_filter.to_string())?)
     }
+}
+
+fn env_filter(directives: &[String]) -> tracing_subscriber::EnvFilter {
+    directives
+        .iter()
+        .filter_map(|s| match s.parse() {
+            Ok(directive) => Some(directive),
+            Err(err) => {
+                eprintln!("Ignoring directive `{}`: {}", s, err);
+                None
+            }
+        })
+        .fold(
+            tracing_subscriber::EnvFilter::new(""),
+            |env_filter, directive| env_filter.add_directive(directive),
+        )
+}
+
+pub async fn init_tracing(service_name: &str) -> anyhow::Result<LogLevelHandle> {
+    let path_root = find_configs_root().await?;
+    load_env(path_root.clone()).await?;
+
+    let mut base_directives = vec![format!("{}=info", service_name), "info".to_string()];
+    if let Ok(rust_log) = std::env::var("RUST_LOG") {
+        base_directives.extend(
+            rust_log
+                .split(',')
+                .filter(|s| !s.is_empty())
+                .map(str::to_string),
+        );
+    }
+    let (env_filter, reload_handle) =
+        tracing_subscriber::reload::Layer::new(env_filter(&base_directives));
 
     opentelemetry::global::shutdown_tracer_provider();
 
@@ -85,7 +154,10 @@ pub async fn init_tracing(service_name: &str) -> anyhow::Result<()> {
             .try_init()?;
     }
 
-    Ok(())
+    Ok(LogLevelHandle {
+        base_directives,
+        handle: reload_handle,
+    })
 }
 
 async fn load_env(mut path_root: PathBuf) -> anyhow::Result<()> {
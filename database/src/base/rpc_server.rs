@@ -14,57 +14,90 @@ pub trait ReaderDbManager {
         method_name: &str,
     ) -> anyhow::Result<readnode_primitives::BlockHeightShardId>;
 
-    /// Returns state for the given account id by page
-    async fn get_state_by_page(
+    /// Returns the state keys of the given account id at the given block height ordered by key,
+    /// up to `limit` keys per page. The page token continues after the last returned key,
+    /// so the large contract states are iterated without loading them at once
+    async fn get_state_keys_by_page(
         &self,
         account_id: &near_primitives::types::AccountId,
         block_height: near_primitives::types::BlockHeight,
+        limit: u64,
         page_token: crate::PageToken,
         method_name: &str,
-    ) -> anyhow::Result<(
-        std::collections::HashMap<readnode_primitives::StateKey, readnode_primitives::StateValue>,
-        crate::PageToken,
-    )>;
+    ) -> anyhow::Result<(Vec<readnode_primitives::StateKey>, crate::PageToken)>;
 
-    /// Returns state keys for the given account id filtered by the given prefix
+    /// Returns state keys for the given account id filtered by the given prefix,
+    /// at most `max_keys` of them if it is set
     async fn get_state_by_key_prefix(
         &self,
         account_id: &near_primitives::types::AccountId,
         block_height: near_primitives::types::BlockHeight,
         prefix: &[u8],
+        max_keys: Option<usize>,
         method_name: &str,
     ) -> anyhow::Result<
         std::collections::HashMap<readnode_primitives::StateKey, readnode_primitives::StateValue>,
     >;
 
-    /// Returns the state for the given account id at the given block height
+    /// Returns the state keys filtered by the given prefix changed after `from_block_height`
+    /// up to `to_block_height` with their values at both heights, ordered by key, by page
+    #[allow(clippy::too_many_arguments)]
+    async fn get_state_diff_by_page(
+        &self,
+        account_id: &near_primitives::types::AccountId,
+        from_block_height: near_primitives::types::BlockHeight,
+        to_block_height: near_primitives::types::BlockHeight,
+        prefix: &[u8],
+        limit: u64,
+        page_token: crate::PageToken,
+        method_name: &str,
+    ) -> anyhow::Result<(Vec<readnode_primitives::StateKeyDiff>, crate::PageToken)>;
+
+    /// Returns the state for the given account id at the given block height,
+    /// at most `max_keys` of the keys if it is set
     async fn get_state(
         &self,
         account_id: &near_primitives::types::AccountId,
         block_height: near_primitives::types::BlockHeight,
+        max_keys: Option<usize>,
         method_name: &str,
     ) -> anyhow::Result<
         std::collections::HashMap<readnode_primitives::StateKey, readnode_primitives::StateValue>,
     >;
 
-    /// Returns the state for the given account id at the given block height
+    /// Returns the state for the given account id at the given block height,
+    /// at most `max_keys` of the keys if it is set
     async fn get_account_state(
         &self,
         account_id: &near_primitives::types::AccountId,
         block_height: near_primitives::types::BlockHeight,
         prefix: &[u8],
+        max_keys: Option<usize>,
         method_name: &str,
     ) -> anyhow::Result<
         std::collections::HashMap<readnode_primitives::StateKey, readnode_primitives::StateValue>,
     > {
         if prefix.is_empty() {
-            self.get_state(account_id, block_height, method_name).await
+            self.get_state(account_id, block_height, max_keys, method_name)
+                .await
         } else {
-            self.get_state_by_key_prefix(account_id, block_height, prefix, method_name)
+            self.get_state_by_key_prefix(account_id, block_height, prefix, max_keys, method_name)
                 .await
         }
     }
 
+    /// Returns the number of state keys and the total size of keys and values
+    /// for the given account id at the given block height.
+    /// At most `max_keys` of the keys are scanned, including the deleted ones,
+    /// `None` if the account has more of them
+    async fn get_state_size(
+        &self,
+        account_id: &near_primitives::types::AccountId,
+        block_height: near_primitives::types::BlockHeight,
+        max_keys: usize,
+        method_name: &str,
+    ) -> anyhow::Result<Option<readnode_primitives::StateSize>>;
+
     /// Returns the state value for the given key of the given account at the given block height
     async fn get_state_key_value(
         &self,
@@ -77,6 +110,29 @@ pub trait ReaderDbManager {
         readnode_primitives::StateValue,
     )>;
 
+    /// Returns the state values of the given keys of the given account at the given block height
+    /// read in one query. The keys absent in the state are not returned
+    async fn get_state_keys_values(
+        &self,
+        account_id: &near_primitives::types::AccountId,
+        block_height: near_primitives::types::BlockHeight,
+        keys: &[readnode_primitives::StateKey],
+        method_name: &str,
+    ) -> anyhow::Result<
+        std::collections::HashMap<readnode_primitives::StateKey, readnode_primitives::StateValue>,
+    >;
+
+    /// Returns the state changes of all the shards stored at exactly the given block height,
+    /// in the order of the trie keys within the shard: accounts, contract codes,
+    /// access keys and contract data. The causes of the changes are not stored.
+    /// Returns `None` if the block is older than the access key changes log,
+    /// the changes of such block are incomplete
+    async fn get_state_changes_in_block(
+        &self,
+        block_height: near_primitives::types::BlockHeight,
+        method_name: &str,
+    ) -> anyhow::Result<Option<Vec<near_primitives::views::StateChangeValueView>>>;
+
     /// Returns the near_primitives::account::Account at the given block height
     async fn get_account(
         &self,
@@ -109,6 +165,23 @@ pub trait ReaderDbManager {
         method_name: &str,
     ) -> anyhow::Result<Vec<near_primitives::views::AccessKeyInfoView>>;
 
+    /// Returns the number of the access keys of the account at the given block height
+    async fn get_account_access_keys_count(
+        &self,
+        account_id: &near_primitives::types::AccountId,
+        block_height: near_primitives::types::BlockHeight,
+        method_name: &str,
+    ) -> anyhow::Result<u64>;
+
+    /// Returns the public keys of the access keys of the account at the given block height
+    /// without reading the access keys themselves
+    async fn get_account_public_keys(
+        &self,
+        account_id: &near_primitives::types::AccountId,
+        block_height: near_primitives::types::BlockHeight,
+        method_name: &str,
+    ) -> anyhow::Result<Vec<near_crypto::PublicKey>>;
+
     /// Returns the near_primitives::views::ReceiptView at the given receipt_id
     async fn get_receipt_by_id(
         &self,
@@ -116,6 +189,14 @@ pub trait ReaderDbManager {
         method_name: &str,
     ) -> anyhow::Result<readnode_primitives::ReceiptRecord>;
 
+    /// Returns the receipt records of the given receipt_ids in their order,
+    /// looked up with one query per shard. The receipts not found are skipped
+    async fn get_receipts_by_ids(
+        &self,
+        receipt_ids: &[near_primitives::hash::CryptoHash],
+        method_name: &str,
+    ) -> anyhow::Result<Vec<readnode_primitives::ReceiptRecord>>;
+
     /// Returns the block height and shard id by the given block height
     async fn get_block_by_height_and_shard_id(
         &self,
@@ -124,6 +205,33 @@ pub trait ReaderDbManager {
         method_name: &str,
     ) -> anyhow::Result<readnode_primitives::BlockHeightShardId>;
 
+    /// Returns the hash of the chunk of the shard marked as skipped at the given block height,
+    /// `None` if the chunk of the shard is not marked as skipped
+    async fn get_skipped_chunk(
+        &self,
+        block_height: near_primitives::types::BlockHeight,
+        shard_id: near_primitives::types::ShardId,
+        method_name: &str,
+    ) -> anyhow::Result<Option<near_primitives::hash::CryptoHash>>;
+
+    /// Returns the latest day with the state growth aggregated (number of the days since
+    /// the Unix epoch, UTC) and the accounts with the largest net state growth in bytes
+    /// within the `window_days` days ending with it
+    async fn get_top_state_growers(
+        &self,
+        window_days: u64,
+        limit: u64,
+        method_name: &str,
+    ) -> anyhow::Result<(Option<u64>, Vec<readnode_primitives::StateGrowth>)>;
+
+    /// Returns the congestion info of the shard at the given block height
+    async fn get_chunk_congestion(
+        &self,
+        block_height: near_primitives::types::BlockHeight,
+        shard_id: near_primitives::types::ShardId,
+        method_name: &str,
+    ) -> anyhow::Result<readnode_primitives::ChunkCongestion>;
+
     /// Returns epoch validators info by the given epoch id
     async fn get_validators_by_epoch_id(
         &self,
@@ -137,4 +245,119 @@ pub trait ReaderDbManager {
         block_height: near_primitives::types::BlockHeight,
         method_name: &str,
     ) -> anyhow::Result<readnode_primitives::EpochValidatorsInfo>;
+
+    /// Returns the protocol config stored by the epoch-indexer for the given epoch id
+    async fn get_protocol_config_by_epoch_id(
+        &self,
+        epoch_id: near_primitives::hash::CryptoHash,
+        method_name: &str,
+    ) -> anyhow::Result<near_chain_configs::ProtocolConfigView>;
+
+    /// Returns transactions signed by the given public key by page, newest first
+    async fn get_transactions_by_public_key(
+        &self,
+        public_key: &near_crypto::PublicKey,
+        limit: u64,
+        cursor: crate::PageToken,
+        method_name: &str,
+    ) -> anyhow::Result<(
+        Vec<readnode_primitives::TransactionByPublicKeyRecord>,
+        crate::PageToken,
+    )>;
+
+    /// Returns function calls to the contract by page, newest first.
+    /// Calls are filtered by the method name and the substring
+    /// of the decoded arguments or their `msg` field if given
+    async fn search_function_calls(
+        &self,
+        contract_id: &near_primitives::types::AccountId,
+        contract_method_name: Option<&str>,
+        args_substring: Option<&str>,
+        limit: u64,
+        cursor: crate::PageToken,
+        method_name: &str,
+    ) -> anyhow::Result<(
+        Vec<readnode_primitives::FunctionCallRecord>,
+        crate::PageToken,
+    )>;
+
+    /// Returns function call actions of the contract method by page, newest first.
+    /// Actions are filtered by the inclusive range of the block heights if given
+    #[allow(clippy::too_many_arguments)]
+    async fn get_actions_by_contract_method(
+        &self,
+        contract_id: &near_primitives::types::AccountId,
+        contract_method_name: &str,
+        from_block_height: Option<near_primitives::types::BlockHeight>,
+        to_block_height: Option<near_primitives::types::BlockHeight>,
+        limit: u64,
+        cursor: crate::PageToken,
+        method_name: &str,
+    ) -> anyhow::Result<(Vec<readnode_primitives::ActionRecord>, crate::PageToken)>;
+
+    /// Returns the timestamp (nanoseconds) of the latest block processed
+    /// by the indexers of every type ("tx", "state" or "epoch")
+    async fn get_latest_indexed_block_timestamps(
+        &self,
+        method_name: &str,
+    ) -> anyhow::Result<std::collections::HashMap<String, u64>>;
+
+    /// Listens to the blocks processed by the indexers and the stored transactions
+    async fn listen_indexer_notifications(
+        &self,
+    ) -> anyhow::Result<
+        futures::stream::BoxStream<'static, anyhow::Result<crate::primitives::IndexerNotification>>,
+    >;
+
+    /// Returns the versions of the indexers writing the data
+    async fn get_writer_versions(
+        &self,
+        method_name: &str,
+    ) -> anyhow::Result<Vec<readnode_primitives::WriterVersion>>;
+
+    /// Returns the network the stored data belongs to,
+    /// `None` if no indexer has written to the database yet
+    async fn get_network_identity(
+        &self,
+        method_name: &str,
+    ) -> anyhow::Result<Option<readnode_primitives::NetworkIdentity>>;
+
+    /// Fails if the state is split into another number of the buckets than the configured
+    /// `state_key_buckets`, the state keys would be looked up in the other buckets.
+    /// Passes if no state-indexer has recorded the number yet
+    async fn check_state_key_buckets(&self, method_name: &str) -> anyhow::Result<()>;
+
+    /// Returns the latest transactions of the signer signed by any of the given public keys
+    /// at or before the given block height, newest first
+    async fn get_recent_transactions_by_signer(
+        &self,
+        signer_id: &near_primitives::types::AccountId,
+        public_keys: &[near_crypto::PublicKey],
+        block_height: near_primitives::types::BlockHeight,
+        limit: u64,
+        method_name: &str,
+    ) -> anyhow::Result<Vec<readnode_primitives::TransactionByPublicKeyRecord>>;
+
+    /// Returns validators kickouts and stake changes by epochs, newest epoch first
+    async fn get_validators_changes_by_page(
+        &self,
+        limit: u64,
+        page_token: crate::PageToken,
+        method_name: &str,
+    ) -> anyhow::Result<(
+        Vec<readnode_primitives::EpochValidatorsChanges>,
+        crate::PageToken,
+    )>;
+
+    /// Returns the blocks and the chunks produced by the validator in the given epoch,
+    /// ordered by block height
+    async fn get_produced_blocks(
+        &self,
+        validator_id: &near_primitives::types::AccountId,
+        epoch_id: near_primitives::hash::CryptoHash,
+        method_name: &str,
+    ) -> anyhow::Result<(
+        Vec<readnode_primitives::ProducedBlock>,
+        Vec<readnode_primitives::ProducedChunk>,
+    )>;
 }
@@ -4,6 +4,7 @@ pub trait StateIndexerDbManager {
         &self,
         block_height: u64,
         block_hash: near_primitives::hash::CryptoHash,
+        block_timestamp: u64,
     ) -> anyhow::Result<()>;
 
     async fn save_chunks(
@@ -22,10 +23,36 @@ pub trait StateIndexerDbManager {
         method_name: &str,
     ) -> anyhow::Result<u64>;
 
-    async fn update_meta(&self, indexer_id: &str, block_height: u64) -> anyhow::Result<()>;
+    /// Stores the last processed block of the indexer, `block_timestamp` is in nanoseconds
+    async fn update_meta(
+        &self,
+        indexer_id: &str,
+        block_height: u64,
+        block_timestamp: u64,
+    ) -> anyhow::Result<()>;
+
+    /// Stores the last processed block of the epoch-indexer with its own `epoch` indexer type.
+    /// The epoch-indexer runs ahead of the state-indexers, so it doesn't report the state head
+    async fn update_epoch_meta(
+        &self,
+        indexer_id: &str,
+        block_height: u64,
+        block_timestamp: u64,
+    ) -> anyhow::Result<()>;
 
     async fn get_last_processed_block_height(&self, indexer_id: &str) -> anyhow::Result<u64>;
 
+    /// Records the network of the data if the database is empty
+    /// and returns the network recorded by the first writer
+    async fn save_network_identity(
+        &self,
+        network: &readnode_primitives::NetworkIdentity,
+    ) -> anyhow::Result<readnode_primitives::NetworkIdentity>;
+
+    /// Records the configured `state_key_buckets` if no state-indexer has recorded it yet.
+    /// Fails if the state is split into another number of the buckets
+    async fn save_state_key_buckets(&self) -> anyhow::Result<()>;
+
     async fn save_validators(
         &self,
         epoch_id: near_primitives::hash::CryptoHash,
@@ -35,17 +62,65 @@ pub trait StateIndexerDbManager {
         epoch_end_block_hash: near_primitives::hash::CryptoHash,
     ) -> anyhow::Result<()>;
 
+    /// Saves the protocol config the epoch runs with
+    async fn save_protocol_config(
+        &self,
+        epoch_id: near_primitives::hash::CryptoHash,
+        epoch_height: u64,
+        epoch_start_height: u64,
+        protocol_config: &near_chain_configs::ProtocolConfigView,
+    ) -> anyhow::Result<()>;
+
+    /// Saves the producer of the block and the producers of the chunks included
+    /// in the block for the first time. `chunk_producers` are `(shard_id, chunk_hash, producer)`
+    async fn save_block_producers(
+        &self,
+        block_height: u64,
+        block_hash: near_primitives::hash::CryptoHash,
+        epoch_id: near_primitives::hash::CryptoHash,
+        block_producer: &near_primitives::types::AccountId,
+        chunk_producers: Vec<(
+            crate::primitives::ShardId,
+            crate::primitives::ChunkHash,
+            near_primitives::types::AccountId,
+        )>,
+    ) -> anyhow::Result<()>;
+
+    /// Saves the congestion info of the chunk headers of the block by the shard.
+    /// The blocks of the protocol versions before the congestion control don't have it
+    async fn save_chunks_congestion(
+        &self,
+        block_height: u64,
+        chunks_congestion: Vec<(
+            crate::primitives::ShardId,
+            readnode_primitives::ChunkCongestion,
+        )>,
+    ) -> anyhow::Result<()>;
+
+    /// Returns the latest day (number of the days since the Unix epoch, UTC)
+    /// with the state growth aggregated
+    async fn get_latest_state_growth_day(&self) -> anyhow::Result<Option<u64>>;
+
+    /// Aggregates the net state growth of the accounts within the day (number of the days
+    /// since the Unix epoch, UTC) from the contract data changes of all the shards.
+    /// Returns `None` if the blocks of the day are not indexed completely yet
+    async fn aggregate_state_growth(
+        &self,
+        day: u64,
+    ) -> anyhow::Result<Option<readnode_primitives::StateGrowthDay>>;
+
     async fn save_block_with_chunks(
         &self,
         block_height: u64,
         block_hash: near_primitives::hash::CryptoHash,
+        block_timestamp: u64,
         chunks: Vec<(
             crate::primitives::ChunkHash,
             crate::primitives::ShardId,
             crate::primitives::HeightIncluded,
         )>,
     ) -> anyhow::Result<()> {
-        let add_block_future = self.save_block(block_height, block_hash);
+        let add_block_future = self.save_block(block_height, block_hash, block_timestamp);
         let add_chunks_future = self.save_chunks(block_height, chunks);
 
         futures::future::join_all([add_block_future, add_chunks_future])
@@ -62,12 +137,16 @@ pub trait StateIndexerDbManager {
         block_hash: near_primitives::hash::CryptoHash,
     ) -> anyhow::Result<()>;
 
+    /// Saves the access keys changes skipping the ones with the value unchanged since
+    /// the previous stored change. The value is compared only if the previous change is stored
+    /// at or above `unfinished_block_height`, the highest lower block which may be not stored yet
     async fn save_state_changes_access_key(
         &self,
         shard_id: near_primitives::types::ShardId,
         state_changes: Vec<near_primitives::views::StateChangeWithCauseView>,
         block_height: u64,
         block_hash: near_primitives::hash::CryptoHash,
+        unfinished_block_height: Option<u64>,
     ) -> anyhow::Result<()>;
 
     async fn save_state_changes_contract(
@@ -85,4 +164,13 @@ pub trait StateIndexerDbManager {
         block_height: u64,
         block_hash: near_primitives::hash::CryptoHash,
     ) -> anyhow::Result<()>;
+    /// Moves the state rows of up to `limit` keys of the account following `after_data_key`
+    /// into the buckets of the keys with the configured buckets count: the rows written
+    /// before the buckets were enabled and the rows written with another buckets count
+    async fn rebalance_state_buckets(
+        &self,
+        account_id: &near_primitives::types::AccountId,
+        after_data_key: Option<String>,
+        limit: u64,
+    ) -> anyhow::Result<crate::primitives::StateRebalanceBatch>;
 }
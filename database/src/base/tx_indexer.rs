@@ -27,7 +27,168 @@ pub trait TxIndexerDbManager {
             .collect::<anyhow::Result<()>>()
     }
 
-    async fn update_meta(&self, indexer_id: &str, block_height: u64) -> anyhow::Result<()>;
+    /// Saves transactions to be able to search them by the signer public key
+    async fn save_transactions_by_public_key(
+        &self,
+        transactions: Vec<readnode_primitives::TransactionByPublicKeyRecord>,
+    ) -> anyhow::Result<()>;
+
+    /// Saves the per-block processing report of the indexer
+    async fn save_block_processing_stats(
+        &self,
+        indexer_id: &str,
+        stats: &readnode_primitives::BlockProcessingStats,
+    ) -> anyhow::Result<()>;
+
+    /// Saves function calls to be able to search them by the decoded arguments
+    async fn save_function_calls(
+        &self,
+        function_calls: Vec<readnode_primitives::FunctionCallRecord>,
+    ) -> anyhow::Result<()>;
+
+    /// Saves the actions of the executed receipts to query them by the contract method
+    async fn save_actions(
+        &self,
+        actions: Vec<readnode_primitives::ActionRecord>,
+    ) -> anyhow::Result<()>;
+
+    /// Marks the chunks of the block header missing in the shards of the indexed block.
+    /// `skipped_chunks` are `(shard_id, chunk_hash)`
+    async fn save_skipped_chunks(
+        &self,
+        block_height: u64,
+        skipped_chunks: Vec<(crate::primitives::ShardId, crate::primitives::ChunkHash)>,
+    ) -> anyhow::Result<()>;
+
+    /// Records the repair of the data missing in the indexed block
+    async fn save_block_repair(
+        &self,
+        indexer_id: &str,
+        repair: &readnode_primitives::BlockRepair,
+    ) -> anyhow::Result<()>;
+
+    /// Records the block the indexer failed to process,
+    /// the attempts are counted if the block fails again
+    async fn save_missed_block(
+        &self,
+        indexer_id: &str,
+        block_height: u64,
+        error: &str,
+    ) -> anyhow::Result<()>;
+
+    /// Returns the blocks the indexer failed to process and which are not repaired yet,
+    /// the lowest first
+    async fn get_missed_blocks(
+        &self,
+        indexer_id: &str,
+    ) -> anyhow::Result<Vec<readnode_primitives::MissedBlock>>;
+
+    /// Deletes the records of the missed blocks once they are repaired
+    async fn delete_missed_blocks(
+        &self,
+        indexer_id: &str,
+        block_heights: &[u64],
+    ) -> anyhow::Result<()>;
+
+    /// Returns the heights of the blocks with the transactions stored
+    /// without the block protocol version and gas price
+    async fn get_block_heights_without_block_info(&self, limit: u64) -> anyhow::Result<Vec<u64>>;
+
+    /// Fills the block protocol version and gas price of the transactions
+    /// included in the given block
+    async fn update_transactions_block_info(
+        &self,
+        block_height: u64,
+        protocol_version: near_primitives::types::ProtocolVersion,
+        gas_price: near_primitives::types::Balance,
+    ) -> anyhow::Result<()>;
+
+    /// Updates the heartbeat and the lease of the tx-indexer instance in the group and returns
+    /// the assignments of the group sorted by `from_block_height`. The lease is the height up to which
+    /// the instance decides the claims of the blocks, it never decreases. If the alive members differ
+    /// from the latest assignment, the assignment of the alive members is agreed starting after
+    /// the leases of all the members. Members without a heartbeat during `member_timeout_secs`
+    /// are considered failed
+    async fn sync_group_assignments(
+        &self,
+        group_id: &str,
+        indexer_id: &str,
+        lease_block_height: u64,
+        member_timeout_secs: u64,
+    ) -> anyhow::Result<Vec<readnode_primitives::GroupAssignment>>;
+
+    /// Returns the number of the stored blocks, chunks, transactions and receipts
+    /// by block height for the heights in the range `from_block_height..=to_block_height`.
+    /// Heights without any stored records are absent in the result
+    async fn get_block_data_counts(
+        &self,
+        from_block_height: u64,
+        to_block_height: u64,
+    ) -> anyhow::Result<std::collections::HashMap<u64, readnode_primitives::BlockDataCounts>>;
+
+    /// Stores the last processed block of the indexer, `block_timestamp` is in nanoseconds
+    async fn update_meta(
+        &self,
+        indexer_id: &str,
+        block_height: u64,
+        block_timestamp: u64,
+    ) -> anyhow::Result<()>;
 
     async fn get_last_processed_block_height(&self, indexer_id: &str) -> anyhow::Result<u64>;
+
+    /// Records the network of the data if the database is empty
+    /// and returns the network recorded by the first writer
+    async fn save_network_identity(
+        &self,
+        network: &readnode_primitives::NetworkIdentity,
+    ) -> anyhow::Result<readnode_primitives::NetworkIdentity>;
+
+    /// Notifies the rpc-servers that the details of the transactions are stored
+    async fn notify_finalized_transactions(
+        &self,
+        transaction_hashes: &[near_primitives::hash::CryptoHash],
+    ) -> anyhow::Result<()>;
+
+    /// Returns the height of the latest stored block produced not later than the given timestamp (nanoseconds).
+    /// Fails if the indexer storing the blocks has not reached the timestamp yet,
+    /// the latest stored block is not the requested one then
+    async fn get_block_height_by_timestamp(&self, block_timestamp: u64) -> anyhow::Result<u64>;
+
+    /// Returns up to `limit` hashes of the transactions included in the blocks
+    /// lower than `before_block_height`, the oldest first.
+    /// The transactions are enumerated by the outcomes of the transactions in the outcomes
+    /// of every shard, so the transactions missing in `transactions_by_public_key` are pruned too
+    async fn get_transactions_to_prune(
+        &self,
+        before_block_height: u64,
+        limit: u64,
+    ) -> anyhow::Result<Vec<near_primitives::hash::CryptoHash>>;
+
+    /// Deletes the given transactions included in the blocks lower than `before_block_height`
+    /// from the `transactions_by_public_key` table along with the outcomes of the transactions
+    /// they are enumerated by, and returns the number of the deleted rows
+    async fn prune_transactions(
+        &self,
+        before_block_height: u64,
+        transaction_hashes: &[near_primitives::hash::CryptoHash],
+    ) -> anyhow::Result<u64>;
+
+    /// Deletes up to `limit` receipts and outcomes executed in the blocks lower than
+    /// `before_block_height` from every shard and returns the number of the deleted rows
+    async fn prune_receipts_and_outcomes(
+        &self,
+        before_block_height: u64,
+        limit: u64,
+    ) -> anyhow::Result<u64>;
+
+    /// Deletes the receipts and outcomes executed in the blocks from `from_block_height`
+    /// to `to_block_height` (exclusive) of the transactions filtered out by the `tracked_accounts`
+    /// and returns the number of the deleted rows. The transaction is filtered out only if its
+    /// rows prove that none of their receivers, predecessors and signers is tracked
+    async fn prune_receipts_of_filtered_transactions(
+        &self,
+        from_block_height: u64,
+        to_block_height: u64,
+        tracked_accounts: &[readnode_primitives::indexer::types::AccountId],
+    ) -> anyhow::Result<u64>;
 }
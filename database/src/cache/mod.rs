@@ -0,0 +1,2 @@
+//! Cache tiers in front of the database managers
+pub(crate) mod redis;
@@ -0,0 +1,615 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+
+/// Redis database number of the reader cache.
+/// The databases 0, 2 and 3 are used by the rpc-server cache storages
+const REDIS_CACHE_DATABASE_NUMBER: usize = 4;
+
+/// Reader database manager caching the hot reads in the Redis shared by the rpc-server instances.
+/// Repeated `query` calls for the same account at the same block are served from the cache
+/// instead of the database. The values are keyed by the requested block height, so the cached
+/// value is the same the database returns for the indexed block. Only the successful reads
+/// are cached, the errors (e.g. unknown account) are always read from the database.
+/// The cache is best-effort: Redis errors are counted and the read falls through to the database
+pub(crate) struct RedisCachedDbManager {
+    db_manager: Box<dyn crate::ReaderDbManager + Sync + Send + 'static>,
+    client: ::redis::aio::ConnectionManager,
+    config: configuration::DatabaseRedisCacheConfig,
+}
+
+impl RedisCachedDbManager {
+    pub(crate) async fn new(
+        db_manager: Box<dyn crate::ReaderDbManager + Sync + Send + 'static>,
+        config: &configuration::DatabaseRedisCacheConfig,
+    ) -> anyhow::Result<Self> {
+        let client = ::redis::Client::open(config.redis_url.as_str())?
+            .get_connection_manager()
+            .await?;
+        ::redis::cmd("SELECT")
+            .arg(REDIS_CACHE_DATABASE_NUMBER)
+            .query_async(&mut client.clone())
+            .await?;
+        Ok(Self {
+            db_manager,
+            client,
+            config: config.clone(),
+        })
+    }
+
+    async fn get_cached<T: BorshDeserialize>(
+        &self,
+        key: &str,
+        method_name: &str,
+    ) -> anyhow::Result<Option<T>> {
+        let value: Option<Vec<u8>> = ::redis::cmd("GET")
+            .arg(key)
+            .query_async(&mut self.client.clone())
+            .await?;
+        let Some(value) = value else {
+            return Ok(None);
+        };
+        crate::metrics::REDIS_CACHE_REQUESTS
+            .with_label_values(&[method_name, "hit"])
+            .inc();
+        Ok(Some(readnode_primitives::blob::borsh_from_slice(&value)?))
+    }
+
+    async fn set_cached<T: BorshSerialize>(
+        &self,
+        key: &str,
+        value: &T,
+        ttl: u64,
+    ) -> anyhow::Result<()> {
+        ::redis::cmd("SET")
+            .arg(key)
+            .arg(borsh::to_vec(value)?)
+            .arg("EX")
+            .arg(ttl)
+            .query_async(&mut self.client.clone())
+            .await?;
+        Ok(())
+    }
+
+    // Returns the cached value or reads it from the database and caches it for `ttl` seconds
+    async fn cached<T, F>(
+        &self,
+        key: String,
+        ttl: u64,
+        method_name: &str,
+        read_from_db: F,
+    ) -> anyhow::Result<T>
+    where
+        T: BorshSerialize + BorshDeserialize,
+        F: std::future::Future<Output = anyhow::Result<T>>,
+    {
+        match self.get_cached(&key, method_name).await {
+            Ok(Some(value)) => return Ok(value),
+            Ok(None) => {
+                crate::metrics::REDIS_CACHE_REQUESTS
+                    .with_label_values(&[method_name, "miss"])
+                    .inc();
+            }
+            Err(err) => {
+                crate::metrics::REDIS_CACHE_REQUESTS
+                    .with_label_values(&[method_name, "error"])
+                    .inc();
+                tracing::debug!("Failed to read {} from the redis cache: {:?}", key, err);
+            }
+        }
+        let value = read_from_db.await?;
+        if let Err(err) = self.set_cached(&key, &value, ttl).await {
+            crate::metrics::REDIS_CACHE_REQUESTS
+                .with_label_values(&[method_name, "error"])
+                .inc();
+            tracing::debug!("Failed to write {} to the redis cache: {:?}", key, err);
+        }
+        Ok(value)
+    }
+
+    // `QueryData` is cached as the borsh encoded data with the block it was updated in
+    async fn cached_query_data<T, F>(
+        &self,
+        key: String,
+        ttl: u64,
+        method_name: &str,
+        read_from_db: F,
+    ) -> anyhow::Result<readnode_primitives::QueryData<T>>
+    where
+        T: BorshSerialize + BorshDeserialize,
+        F: std::future::Future<Output = anyhow::Result<readnode_primitives::QueryData<T>>>,
+    {
+        let value: (Vec<u8>, u64, near_primitives::hash::CryptoHash) = self
+            .cached(key, ttl, method_name, async {
+                let query_data = read_from_db.await?;
+                Ok((
+                    borsh::to_vec(&query_data.data)?,
+                    query_data.block_height,
+                    query_data.block_hash,
+                ))
+            })
+            .await?;
+        readnode_primitives::QueryData::try_from(value)
+    }
+}
+
+#[async_trait::async_trait]
+impl crate::ReaderDbManager for RedisCachedDbManager {
+    async fn get_block_height_by_hash(
+        &self,
+        block_hash: near_primitives::hash::CryptoHash,
+        method_name: &str,
+    ) -> anyhow::Result<u64> {
+        self.cached(
+            format!("block_height_{}", block_hash),
+            self.config.block_ttl,
+            method_name,
+            self.db_manager
+                .get_block_height_by_hash(block_hash, method_name),
+        )
+        .await
+    }
+
+    async fn get_block_by_chunk_hash(
+        &self,
+        chunk_hash: near_primitives::hash::CryptoHash,
+        method_name: &str,
+    ) -> anyhow::Result<readnode_primitives::BlockHeightShardId> {
+        self.db_manager
+            .get_block_by_chunk_hash(chunk_hash, method_name)
+            .await
+    }
+
+    async fn get_state_by_key_prefix(
+        &self,
+        account_id: &near_primitives::types::AccountId,
+        block_height: near_primitives::types::BlockHeight,
+        prefix: &[u8],
+        max_keys: Option<usize>,
+        method_name: &str,
+    ) -> anyhow::Result<
+        std::collections::HashMap<readnode_primitives::StateKey, readnode_primitives::StateValue>,
+    > {
+        self.db_manager
+            .get_state_by_key_prefix(account_id, block_height, prefix, max_keys, method_name)
+            .await
+    }
+
+    async fn get_state_keys_by_page(
+        &self,
+        account_id: &near_primitives::types::AccountId,
+        block_height: near_primitives::types::BlockHeight,
+        limit: u64,
+        page_token: crate::PageToken,
+        method_name: &str,
+    ) -> anyhow::Result<(Vec<readnode_primitives::StateKey>, crate::PageToken)> {
+        self.db_manager
+            .get_state_keys_by_page(account_id, block_height, limit, page_token, method_name)
+            .await
+    }
+
+    async fn get_state_diff_by_page(
+        &self,
+        account_id: &near_primitives::types::AccountId,
+        from_block_height: near_primitives::types::BlockHeight,
+        to_block_height: near_primitives::types::BlockHeight,
+        prefix: &[u8],
+        limit: u64,
+        page_token: crate::PageToken,
+        method_name: &str,
+    ) -> anyhow::Result<(Vec<readnode_primitives::StateKeyDiff>, crate::PageToken)> {
+        self.db_manager
+            .get_state_diff_by_page(
+                account_id,
+                from_block_height,
+                to_block_height,
+                prefix,
+                limit,
+                page_token,
+                method_name,
+            )
+            .await
+    }
+
+    async fn get_state(
+        &self,
+        account_id: &near_primitives::types::AccountId,
+        block_height: near_primitives::types::BlockHeight,
+        max_keys: Option<usize>,
+        method_name: &str,
+    ) -> anyhow::Result<
+        std::collections::HashMap<readnode_primitives::StateKey, readnode_primitives::StateValue>,
+    > {
+        self.db_manager
+            .get_state(account_id, block_height, max_keys, method_name)
+            .await
+    }
+
+    async fn get_state_size(
+        &self,
+        account_id: &near_primitives::types::AccountId,
+        block_height: near_primitives::types::BlockHeight,
+        max_keys: usize,
+        method_name: &str,
+    ) -> anyhow::Result<Option<readnode_primitives::StateSize>> {
+        self.db_manager
+            .get_state_size(account_id, block_height, max_keys, method_name)
+            .await
+    }
+
+    async fn get_state_key_value(
+        &self,
+        account_id: &near_primitives::types::AccountId,
+        block_height: near_primitives::types::BlockHeight,
+        key_data: readnode_primitives::StateKey,
+        method_name: &str,
+    ) -> anyhow::Result<(
+        readnode_primitives::StateKey,
+        readnode_primitives::StateValue,
+    )> {
+        let key = format!(
+            "state_{}_{}_{}",
+            account_id,
+            key_data.to_hex(),
+            block_height
+        );
+        self.cached(
+            key,
+            self.config.state_ttl,
+            method_name,
+            self.db_manager
+                .get_state_key_value(account_id, block_height, key_data, method_name),
+        )
+        .await
+    }
+
+    async fn get_state_keys_values(
+        &self,
+        account_id: &near_primitives::types::AccountId,
+        block_height: near_primitives::types::BlockHeight,
+        keys: &[readnode_primitives::StateKey],
+        method_name: &str,
+    ) -> anyhow::Result<
+        std::collections::HashMap<readnode_primitives::StateKey, readnode_primitives::StateValue>,
+    > {
+        self.db_manager
+            .get_state_keys_values(account_id, block_height, keys, method_name)
+            .await
+    }
+
+    async fn get_state_changes_in_block(
+        &self,
+        block_height: near_primitives::types::BlockHeight,
+        method_name: &str,
+    ) -> anyhow::Result<Option<Vec<near_primitives::views::StateChangeValueView>>> {
+        self.db_manager
+            .get_state_changes_in_block(block_height, method_name)
+            .await
+    }
+
+    async fn get_account(
+        &self,
+        account_id: &near_primitives::types::AccountId,
+        request_block_height: near_primitives::types::BlockHeight,
+        method_name: &str,
+    ) -> anyhow::Result<readnode_primitives::QueryData<near_primitives::account::Account>> {
+        self.cached_query_data(
+            format!("account_{}_{}", account_id, request_block_height),
+            self.config.account_ttl,
+            method_name,
+            self.db_manager
+                .get_account(account_id, request_block_height, method_name),
+        )
+        .await
+    }
+
+    async fn get_contract_code(
+        &self,
+        account_id: &near_primitives::types::AccountId,
+        request_block_height: near_primitives::types::BlockHeight,
+        method_name: &str,
+    ) -> anyhow::Result<readnode_primitives::QueryData<Vec<u8>>> {
+        self.cached_query_data(
+            format!("contract_code_{}_{}", account_id, request_block_height),
+            self.config.contract_code_ttl,
+            method_name,
+            self.db_manager
+                .get_contract_code(account_id, request_block_height, method_name),
+        )
+        .await
+    }
+
+    async fn get_access_key(
+        &self,
+        account_id: &near_primitives::types::AccountId,
+        request_block_height: near_primitives::types::BlockHeight,
+        public_key: near_crypto::PublicKey,
+        method_name: &str,
+    ) -> anyhow::Result<readnode_primitives::QueryData<near_primitives::account::AccessKey>> {
+        let key = format!(
+            "access_key_{}_{}_{}",
+            account_id, public_key, request_block_height
+        );
+        self.cached_query_data(
+            key,
+            self.config.access_key_ttl,
+            method_name,
+            self.db_manager.get_access_key(
+                account_id,
+                request_block_height,
+                public_key,
+                method_name,
+            ),
+        )
+        .await
+    }
+
+    async fn get_account_access_keys(
+        &self,
+        account_id: &near_primitives::types::AccountId,
+        block_height: near_primitives::types::BlockHeight,
+        method_name: &str,
+    ) -> anyhow::Result<Vec<near_primitives::views::AccessKeyInfoView>> {
+        self.db_manager
+            .get_account_access_keys(account_id, block_height, method_name)
+            .await
+    }
+
+    async fn get_account_access_keys_count(
+        &self,
+        account_id: &near_primitives::types::AccountId,
+        block_height: near_primitives::types::BlockHeight,
+        method_name: &str,
+    ) -> anyhow::Result<u64> {
+        self.db_manager
+            .get_account_access_keys_count(account_id, block_height, method_name)
+            .await
+    }
+
+    async fn get_account_public_keys(
+        &self,
+        account_id: &near_primitives::types::AccountId,
+        block_height: near_primitives::types::BlockHeight,
+        method_name: &str,
+    ) -> anyhow::Result<Vec<near_crypto::PublicKey>> {
+        self.db_manager
+            .get_account_public_keys(account_id, block_height, method_name)
+            .await
+    }
+
+    async fn get_receipt_by_id(
+        &self,
+        receipt_id: near_primitives::hash::CryptoHash,
+        method_name: &str,
+    ) -> anyhow::Result<readnode_primitives::ReceiptRecord> {
+        self.db_manager
+            .get_receipt_by_id(receipt_id, method_name)
+            .await
+    }
+
+    async fn get_receipts_by_ids(
+        &self,
+        receipt_ids: &[near_primitives::hash::CryptoHash],
+        method_name: &str,
+    ) -> anyhow::Result<Vec<readnode_primitives::ReceiptRecord>> {
+        self.db_manager
+            .get_receipts_by_ids(receipt_ids, method_name)
+            .await
+    }
+
+    async fn get_block_by_height_and_shard_id(
+        &self,
+        block_height: near_primitives::types::BlockHeight,
+        shard_id: near_primitives::types::ShardId,
+        method_name: &str,
+    ) -> anyhow::Result<readnode_primitives::BlockHeightShardId> {
+        self.db_manager
+            .get_block_by_height_and_shard_id(block_height, shard_id, method_name)
+            .await
+    }
+
+    async fn get_skipped_chunk(
+        &self,
+        block_height: near_primitives::types::BlockHeight,
+        shard_id: near_primitives::types::ShardId,
+        method_name: &str,
+    ) -> anyhow::Result<Option<near_primitives::hash::CryptoHash>> {
+        self.db_manager
+            .get_skipped_chunk(block_height, shard_id, method_name)
+            .await
+    }
+
+    async fn get_top_state_growers(
+        &self,
+        window_days: u64,
+        limit: u64,
+        method_name: &str,
+    ) -> anyhow::Result<(Option<u64>, Vec<readnode_primitives::StateGrowth>)> {
+        self.db_manager
+            .get_top_state_growers(window_days, limit, method_name)
+            .await
+    }
+
+    async fn get_chunk_congestion(
+        &self,
+        block_height: near_primitives::types::BlockHeight,
+        shard_id: near_primitives::types::ShardId,
+        method_name: &str,
+    ) -> anyhow::Result<readnode_primitives::ChunkCongestion> {
+        self.db_manager
+            .get_chunk_congestion(block_height, shard_id, method_name)
+            .await
+    }
+
+    async fn get_validators_by_epoch_id(
+        &self,
+        epoch_id: near_primitives::hash::CryptoHash,
+        method_name: &str,
+    ) -> anyhow::Result<readnode_primitives::EpochValidatorsInfo> {
+        self.db_manager
+            .get_validators_by_epoch_id(epoch_id, method_name)
+            .await
+    }
+
+    async fn get_validators_by_end_block_height(
+        &self,
+        block_height: near_primitives::types::BlockHeight,
+        method_name: &str,
+    ) -> anyhow::Result<readnode_primitives::EpochValidatorsInfo> {
+        self.db_manager
+            .get_validators_by_end_block_height(block_height, method_name)
+            .await
+    }
+
+    async fn get_protocol_config_by_epoch_id(
+        &self,
+        epoch_id: near_primitives::hash::CryptoHash,
+        method_name: &str,
+    ) -> anyhow::Result<near_chain_configs::ProtocolConfigView> {
+        self.db_manager
+            .get_protocol_config_by_epoch_id(epoch_id, method_name)
+            .await
+    }
+
+    async fn get_transactions_by_public_key(
+        &self,
+        public_key: &near_crypto::PublicKey,
+        limit: u64,
+        cursor: crate::PageToken,
+        method_name: &str,
+    ) -> anyhow::Result<(
+        Vec<readnode_primitives::TransactionByPublicKeyRecord>,
+        crate::PageToken,
+    )> {
+        self.db_manager
+            .get_transactions_by_public_key(public_key, limit, cursor, method_name)
+            .await
+    }
+
+    async fn search_function_calls(
+        &self,
+        contract_id: &near_primitives::types::AccountId,
+        contract_method_name: Option<&str>,
+        args_substring: Option<&str>,
+        limit: u64,
+        cursor: crate::PageToken,
+        method_name: &str,
+    ) -> anyhow::Result<(
+        Vec<readnode_primitives::FunctionCallRecord>,
+        crate::PageToken,
+    )> {
+        self.db_manager
+            .search_function_calls(
+                contract_id,
+                contract_method_name,
+                args_substring,
+                limit,
+                cursor,
+                method_name,
+            )
+            .await
+    }
+
+    async fn get_actions_by_contract_method(
+        &self,
+        contract_id: &near_primitives::types::AccountId,
+        contract_method_name: &str,
+        from_block_height: Option<near_primitives::types::BlockHeight>,
+        to_block_height: Option<near_primitives::types::BlockHeight>,
+        limit: u64,
+        cursor: crate::PageToken,
+        method_name: &str,
+    ) -> anyhow::Result<(Vec<readnode_primitives::ActionRecord>, crate::PageToken)> {
+        self.db_manager
+            .get_actions_by_contract_method(
+                contract_id,
+                contract_method_name,
+                from_block_height,
+                to_block_height,
+                limit,
+                cursor,
+                method_name,
+            )
+            .await
+    }
+
+    async fn get_latest_indexed_block_timestamps(
+        &self,
+        method_name: &str,
+    ) -> anyhow::Result<std::collections::HashMap<String, u64>> {
+        self.db_manager
+            .get_latest_indexed_block_timestamps(method_name)
+            .await
+    }
+
+    async fn listen_indexer_notifications(
+        &self,
+    ) -> anyhow::Result<
+        futures::stream::BoxStream<'static, anyhow::Result<crate::primitives::IndexerNotification>>,
+    > {
+        self.db_manager.listen_indexer_notifications().await
+    }
+
+    async fn get_writer_versions(
+        &self,
+        method_name: &str,
+    ) -> anyhow::Result<Vec<readnode_primitives::WriterVersion>> {
+        self.db_manager.get_writer_versions(method_name).await
+    }
+
+    async fn get_network_identity(
+        &self,
+        method_name: &str,
+    ) -> anyhow::Result<Option<readnode_primitives::NetworkIdentity>> {
+        self.db_manager.get_network_identity(method_name).await
+    }
+
+    async fn check_state_key_buckets(&self, method_name: &str) -> anyhow::Result<()> {
+        self.db_manager.check_state_key_buckets(method_name).await
+    }
+
+    async fn get_recent_transactions_by_signer(
+        &self,
+        signer_id: &near_primitives::types::AccountId,
+        public_keys: &[near_crypto::PublicKey],
+        block_height: near_primitives::types::BlockHeight,
+        limit: u64,
+        method_name: &str,
+    ) -> anyhow::Result<Vec<readnode_primitives::TransactionByPublicKeyRecord>> {
+        self.db_manager
+            .get_recent_transactions_by_signer(
+                signer_id,
+                public_keys,
+                block_height,
+                limit,
+                method_name,
+            )
+            .await
+    }
+
+    async fn get_validators_changes_by_page(
+        &self,
+        limit: u64,
+        page_token: crate::PageToken,
+        method_name: &str,
+    ) -> anyhow::Result<(
+        Vec<readnode_primitives::EpochValidatorsChanges>,
+        crate::PageToken,
+    )> {
+        self.db_manager
+            .get_validators_changes_by_page(limit, page_token, method_name)
+            .await
+    }
+
+    async fn get_produced_blocks(
+        &self,
+        validator_id: &near_primitives::types::AccountId,
+        epoch_id: near_primitives::hash::CryptoHash,
+        method_name: &str,
+    ) -> anyhow::Result<(
+        Vec<readnode_primitives::ProducedBlock>,
+        Vec<readnode_primitives::ProducedChunk>,
+    )> {
+        self.db_manager
+            .get_produced_blocks(validator_id, epoch_id, method_name)
+            .await
+    }
+}
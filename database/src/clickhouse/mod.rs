@@ -0,0 +1,89 @@
+mod tx_indexer;
+
+// Statements creating the tables, applied on every start of the indexer
+static SCHEMA: &str = include_str!("schema.sql");
+
+/// Stores the tx-indexer data in ClickHouse for the analytical queries
+/// (e.g. transactions per account per day, gas used by the contracts).
+/// All the shards are stored in the same tables, `shard_id` is a column
+pub struct ClickhouseDBManager {
+    client: clickhouse::Client,
+}
+
+impl ClickhouseDBManager {
+    /// Builds the client of the HTTP interface. The database name is the path of the url,
+    /// the credentials configured for the component override the ones from the url
+    fn create_client(
+        database_url: &str,
+        credentials: Option<&configuration::DatabaseCredentials>,
+    ) -> anyhow::Result<clickhouse::Client> {
+        let mut url = url::Url::parse(database_url)?;
+        let database = url.path().trim_matches('/').to_string();
+        let (user, password) = match credentials {
+            Some(credentials) => (
+                credentials.user.clone(),
+                credentials.password.clone().unwrap_or_default(),
+            ),
+            None => (
+                url.username().to_string(),
+                url.password().unwrap_or_default().to_string(),
+            ),
+        };
+        url.set_path("");
+        url.set_username("")
+            .map_err(|_| anyhow::anyhow!("Failed to clear the database user of the url"))?;
+        url.set_password(None)
+            .map_err(|_| anyhow::anyhow!("Failed to clear the database password of the url"))?;
+
+        let mut client = clickhouse::Client::default()
+            .with_url(url.as_str().trim_end_matches('/'))
+            .with_compression(clickhouse::Compression::Lz4);
+        if !database.is_empty() {
+            client = client.with_database(database);
+        }
+        if !user.is_empty() {
+            client = client.with_user(user).with_password(password);
+        }
+        Ok(client)
+    }
+
+    async fn apply_schema(client: &clickhouse::Client) -> anyhow::Result<()> {
+        let statements = SCHEMA.split(';').filter(|statement| {
+            statement.lines().any(|line| {
+                let line = line.trim();
+                !line.is_empty() && !line.starts_with("--")
+            })
+        });
+        for statement in statements {
+            client
+                .query(statement)
+                .execute()
+                .await
+                .map_err(|err| anyhow::anyhow!("Failed to apply the ClickHouse schema: {}", err))?;
+        }
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl crate::BaseDbManager for ClickhouseDBManager {
+    async fn new(
+        config: &configuration::DatabaseConfig,
+        _shard_layout: near_primitives::shard_layout::ShardLayout,
+    ) -> anyhow::Result<Box<Self>> {
+        let client = Self::create_client(&config.database_url, config.component_credentials())?;
+        if !config.read_only {
+            // The tables can be created by a separate user with DDL privileges,
+            // so the indexer user requires only privileges to write the data
+            match &config.migrations_credentials {
+                Some(migrations_credentials) => {
+                    let migrations_client =
+                        Self::create_client(&config.database_url, Some(migrations_credentials))?;
+                    Self::apply_schema(&migrations_client).await?;
+                }
+                None => Self::apply_schema(&client).await?,
+            }
+        }
+        Ok(Box::new(Self { client }))
+    }
+}
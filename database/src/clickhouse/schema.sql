@@ -0,0 +1,153 @@
+-- Tables of the tx-indexer data in ClickHouse, applied on startup of the indexer.
+-- The rows are deduplicated by the sorting key in the background, so the blocks
+-- indexed twice (e.g. after the restart) are counted once after the merge
+-- or with the `FINAL` modifier. Add new tables and columns with `IF NOT EXISTS`,
+-- the statements are applied on every start
+
+CREATE TABLE IF NOT EXISTS transactions_by_public_key (
+    public_key String,
+    block_height UInt64,
+    transaction_hash String,
+    signer_id String,
+    receiver_id String,
+    nonce UInt64,
+    block_hash String,
+    shard_id UInt64,
+    protocol_version Nullable(UInt32),
+    gas_price Nullable(UInt128)
+) ENGINE = ReplacingMergeTree
+PARTITION BY intDiv(block_height, 10000000)
+ORDER BY (signer_id, block_height, transaction_hash, public_key);
+
+-- Protocol version and gas price of the blocks with the transactions stored without them
+CREATE TABLE IF NOT EXISTS transactions_block_info (
+    block_height UInt64,
+    protocol_version UInt32,
+    gas_price UInt128
+) ENGINE = ReplacingMergeTree
+ORDER BY block_height;
+
+CREATE TABLE IF NOT EXISTS receipts (
+    receipt_id String,
+    parent_transaction_hash String,
+    receiver_id String,
+    block_height UInt64,
+    block_hash String,
+    shard_id UInt64,
+    index_in_chunk Nullable(UInt32)
+) ENGINE = ReplacingMergeTree
+PARTITION BY intDiv(block_height, 10000000)
+ORDER BY (receiver_id, block_height, receipt_id);
+
+CREATE TABLE IF NOT EXISTS outcomes (
+    outcome_id String,
+    parent_transaction_hash String,
+    receiver_id String,
+    block_height UInt64,
+    block_hash String,
+    shard_id UInt64,
+    index_in_chunk Nullable(UInt32)
+) ENGINE = ReplacingMergeTree
+PARTITION BY intDiv(block_height, 10000000)
+ORDER BY (receiver_id, block_height, outcome_id);
+
+CREATE TABLE IF NOT EXISTS function_calls (
+    receipt_id String,
+    action_index UInt32,
+    block_height UInt64,
+    block_hash String,
+    predecessor_id String,
+    signer_id String,
+    receiver_id String,
+    method_name String,
+    args Nullable(String),
+    msg Nullable(String)
+) ENGINE = ReplacingMergeTree
+PARTITION BY intDiv(block_height, 10000000)
+ORDER BY (receiver_id, method_name, block_height, receipt_id, action_index);
+
+CREATE TABLE IF NOT EXISTS actions (
+    receipt_id String,
+    action_index UInt32,
+    block_height UInt64,
+    block_hash String,
+    predecessor_id String,
+    signer_id String,
+    receiver_id String,
+    action_kind LowCardinality(String),
+    method_name Nullable(String),
+    deposit Nullable(UInt128),
+    gas Nullable(UInt64),
+    public_key Nullable(String)
+) ENGINE = ReplacingMergeTree
+PARTITION BY intDiv(block_height, 10000000)
+ORDER BY (receiver_id, block_height, receipt_id, action_index);
+
+-- Blocks processed by the tx-indexers, the timestamps are in nanoseconds
+CREATE TABLE IF NOT EXISTS blocks (
+    block_height UInt64,
+    block_timestamp UInt64
+) ENGINE = ReplacingMergeTree
+ORDER BY block_height;
+
+CREATE TABLE IF NOT EXISTS skipped_chunks (
+    block_height UInt64,
+    shard_id UInt64,
+    chunk_hash String
+) ENGINE = ReplacingMergeTree
+ORDER BY (block_height, shard_id);
+
+CREATE TABLE IF NOT EXISTS block_processing_stats (
+    indexer_id String,
+    block_height UInt64,
+    block_hash String,
+    txs_started UInt64,
+    receipts_matched UInt64,
+    outcomes_matched UInt64,
+    txs_finalized UInt64,
+    collect_duration_ms UInt64,
+    save_tx_details_duration_ms UInt64,
+    save_outcomes_and_receipts_duration_ms UInt64,
+    save_transactions_by_public_key_duration_ms UInt64,
+    created_at DateTime DEFAULT now()
+) ENGINE = ReplacingMergeTree
+ORDER BY (indexer_id, block_height);
+
+CREATE TABLE IF NOT EXISTS block_repairs (
+    indexer_id String,
+    block_height UInt64,
+    block_hash String,
+    reason String,
+    transactions UInt64,
+    receipts UInt64,
+    error Nullable(String),
+    repaired_at DateTime DEFAULT now()
+) ENGINE = MergeTree
+ORDER BY (block_height, repaired_at);
+
+CREATE TABLE IF NOT EXISTS missed_blocks (
+    indexer_id String,
+    block_height UInt64,
+    error String,
+    missed_at DateTime DEFAULT now()
+) ENGINE = MergeTree
+ORDER BY (indexer_id, block_height, missed_at);
+
+CREATE TABLE IF NOT EXISTS meta (
+    indexer_id String,
+    last_processed_block_height UInt64,
+    last_processed_block_timestamp UInt64,
+    indexer_type LowCardinality(String),
+    crate_version String,
+    schema_version UInt32,
+    updated_at DateTime64(3) DEFAULT now64(3)
+) ENGINE = ReplacingMergeTree(updated_at)
+ORDER BY indexer_id;
+
+-- Network the data belongs to, the row written first wins
+CREATE TABLE IF NOT EXISTS network (
+    chain_id String,
+    genesis_hash String,
+    created_at DateTime64(3) DEFAULT now64(3)
+) ENGINE = MergeTree
+ORDER BY created_at;
@@ -0,0 +1,784 @@
+#[derive(clickhouse::Row, serde::Serialize)]
+struct ReceiptRow {
+    receipt_id: String,
+    parent_transaction_hash: String,
+    receiver_id: String,
+    block_height: u64,
+    block_hash: String,
+    shard_id: u64,
+    index_in_chunk: Option<u32>,
+}
+
+#[derive(clickhouse::Row, serde::Serialize)]
+struct OutcomeRow {
+    outcome_id: String,
+    parent_transaction_hash: String,
+    receiver_id: String,
+    block_height: u64,
+    block_hash: String,
+    shard_id: u64,
+    index_in_chunk: Option<u32>,
+}
+
+#[derive(clickhouse::Row, serde::Serialize)]
+struct TransactionRow {
+    public_key: String,
+    block_height: u64,
+    transaction_hash: String,
+    signer_id: String,
+    receiver_id: String,
+    nonce: u64,
+    block_hash: String,
+    shard_id: u64,
+    protocol_version: Option<u32>,
+    gas_price: Option<u128>,
+}
+
+#[derive(clickhouse::Row, serde::Serialize)]
+struct TransactionsBlockInfoRow {
+    block_height: u64,
+    protocol_version: u32,
+    gas_price: u128,
+}
+
+#[derive(clickhouse::Row, serde::Serialize)]
+struct FunctionCallRow {
+    receipt_id: String,
+    action_index: u32,
+    block_height: u64,
+    block_hash: String,
+    predecessor_id: String,
+    signer_id: String,
+    receiver_id: String,
+    method_name: String,
+    args: Option<String>,
+    msg: Option<String>,
+}
+
+#[derive(clickhouse::Row, serde::Serialize)]
+struct ActionRow {
+    receipt_id: String,
+    action_index: u32,
+    block_height: u64,
+    block_hash: String,
+    predecessor_id: String,
+    signer_id: String,
+    receiver_id: String,
+    action_kind: String,
+    method_name: Option<String>,
+    deposit: Option<u128>,
+    gas: Option<u64>,
+    public_key: Option<String>,
+}
+
+#[derive(clickhouse::Row, serde::Serialize)]
+struct SkippedChunkRow {
+    block_height: u64,
+    shard_id: u64,
+    chunk_hash: String,
+}
+
+#[derive(clickhouse::Row, serde::Serialize)]
+struct BlockProcessingStatsRow {
+    indexer_id: String,
+    block_height: u64,
+    block_hash: String,
+    txs_started: u64,
+    receipts_matched: u64,
+    outcomes_matched: u64,
+    txs_finalized: u64,
+    collect_duration_ms: u64,
+    save_tx_details_duration_ms: u64,
+    save_outcomes_and_receipts_duration_ms: u64,
+    save_transactions_by_public_key_duration_ms: u64,
+}
+
+#[derive(clickhouse::Row, serde::Serialize)]
+struct BlockRepairRow {
+    indexer_id: String,
+    block_height: u64,
+    block_hash: String,
+    reason: String,
+    transactions: u64,
+    receipts: u64,
+    error: Option<String>,
+}
+
+#[derive(clickhouse::Row, serde::Serialize)]
+struct MissedBlockRow {
+    indexer_id: String,
+    block_height: u64,
+    error: String,
+}
+
+#[derive(clickhouse::Row, serde::Deserialize)]
+struct MissedBlockAttemptsRow {
+    block_height: u64,
+    error: String,
+    attempts: u64,
+}
+
+#[derive(clickhouse::Row, serde::Serialize)]
+struct BlockRow {
+    block_height: u64,
+    block_timestamp: u64,
+}
+
+#[derive(clickhouse::Row, serde::Serialize)]
+struct MetaRow {
+    indexer_id: String,
+    last_processed_block_height: u64,
+    last_processed_block_timestamp: u64,
+    indexer_type: String,
+    crate_version: String,
+    schema_version: u32,
+}
+
+#[derive(clickhouse::Row, serde::Serialize, serde::Deserialize)]
+struct NetworkRow {
+    chain_id: String,
+    genesis_hash: String,
+}
+
+#[derive(clickhouse::Row, serde::Deserialize)]
+struct BlockHeightRow {
+    block_height: u64,
+}
+
+#[derive(clickhouse::Row, serde::Deserialize)]
+struct TransactionHashRow {
+    transaction_hash: String,
+}
+
+impl crate::ClickhouseDBManager {
+    // Inserts the rows in one request, the empty batches are not sent
+    async fn insert_rows<T>(
+        &self,
+        method_name: &str,
+        table_name: &str,
+        rows: &[T],
+    ) -> anyhow::Result<()>
+    where
+        T: clickhouse::Row + serde::Serialize,
+    {
+        if rows.is_empty() {
+            return Ok(());
+        }
+        crate::metrics::CLICKHOUSE_WRITE_QUERIES
+            .with_label_values(&[method_name, table_name])
+            .inc();
+        let mut insert = self.client.insert::<T>(table_name)?;
+        for row in rows {
+            insert.write(row).await?;
+        }
+        insert.end().await?;
+        Ok(())
+    }
+
+    // ClickHouse doesn't report the number of the deleted rows,
+    // so the rows matching the condition are counted before the delete
+    async fn delete_rows<T>(
+        &self,
+        method_name: &str,
+        table_name: &str,
+        condition: &str,
+        before_block_height: u64,
+        values: Option<T>,
+        pruned_rows_counter: &prometheus::IntCounterVec,
+    ) -> anyhow::Result<u64>
+    where
+        T: serde::Serialize + Clone,
+    {
+        #[derive(clickhouse::Row, serde::Deserialize)]
+        struct CountRow {
+            count: u64,
+        }
+
+        crate::metrics::CLICKHOUSE_READ_QUERIES
+            .with_label_values(&[method_name, table_name])
+            .inc();
+        let mut count_query = self
+            .client
+            .query(&format!(
+                "SELECT count() AS count FROM {table_name} WHERE {condition}"
+            ))
+            .bind(before_block_height);
+        if let Some(values) = values.clone() {
+            count_query = count_query.bind(values);
+        }
+        let pruned_rows = count_query.fetch_one::<CountRow>().await?.count;
+        if pruned_rows == 0 {
+            return Ok(0);
+        }
+
+        crate::metrics::CLICKHOUSE_WRITE_QUERIES
+            .with_label_values(&[method_name, table_name])
+            .inc();
+        let mut delete_query = self
+            .client
+            .query(&format!("DELETE FROM {table_name} WHERE {condition}"))
+            .bind(before_block_height);
+        if let Some(values) = values {
+            delete_query = delete_query.bind(values);
+        }
+        delete_query.execute().await?;
+        pruned_rows_counter
+            .with_label_values(&[table_name])
+            .inc_by(pruned_rows);
+        Ok(pruned_rows)
+    }
+}
+
+#[async_trait::async_trait]
+impl crate::TxIndexerDbManager for crate::ClickhouseDBManager {
+    async fn save_receipts(
+        &self,
+        _shard_id: crate::primitives::ShardId,
+        receipts: Vec<readnode_primitives::ReceiptRecord>,
+    ) -> anyhow::Result<()> {
+        // The receipt bodies are served by the rpc-server from PostgreSQL only,
+        // ClickHouse keeps the mapping of the receipts to their transactions
+        let rows: Vec<_> = receipts
+            .into_iter()
+            .map(|receipt| ReceiptRow {
+                receipt_id: receipt.receipt_id.to_string(),
+                parent_transaction_hash: receipt.parent_transaction_hash.to_string(),
+                receiver_id: receipt.receiver_id.to_string(),
+                block_height: receipt.block_height,
+                block_hash: receipt.block_hash.to_string(),
+                shard_id: receipt.shard_id,
+                index_in_chunk: receipt.index_in_chunk,
+            })
+            .collect();
+        self.insert_rows("save_receipts", "receipts", &rows).await
+    }
+
+    async fn save_outcomes(
+        &self,
+        _shard_id: crate::primitives::ShardId,
+        outcomes: Vec<readnode_primitives::OutcomeRecord>,
+    ) -> anyhow::Result<()> {
+        let rows: Vec<_> = outcomes
+            .into_iter()
+            .map(|outcome| OutcomeRow {
+                outcome_id: outcome.outcome_id.to_string(),
+                parent_transaction_hash: outcome.parent_transaction_hash.to_string(),
+                receiver_id: outcome.receiver_id.to_string(),
+                block_height: outcome.block_height,
+                block_hash: outcome.block_hash.to_string(),
+                shard_id: outcome.shard_id,
+                index_in_chunk: outcome.index_in_chunk,
+            })
+            .collect();
+        self.insert_rows("save_outcomes", "outcomes", &rows).await
+    }
+
+    async fn save_transactions_by_public_key(
+        &self,
+        transactions: Vec<readnode_primitives::TransactionByPublicKeyRecord>,
+    ) -> anyhow::Result<()> {
+        let rows: Vec<_> = transactions
+            .into_iter()
+            .map(|transaction| TransactionRow {
+                public_key: transaction.public_key,
+                block_height: transaction.block_height,
+                transaction_hash: transaction.transaction_hash.to_string(),
+                signer_id: transaction.signer_id.to_string(),
+                receiver_id: transaction.receiver_id.to_string(),
+                nonce: transaction.nonce,
+                block_hash: transaction.block_hash.to_string(),
+                shard_id: transaction.shard_id,
+                protocol_version: transaction.protocol_version,
+                gas_price: transaction.gas_price,
+            })
+            .collect();
+        self.insert_rows(
+            "save_transactions_by_public_key",
+            "transactions_by_public_key",
+            &rows,
+        )
+        .await
+    }
+
+    async fn save_block_processing_stats(
+        &self,
+        indexer_id: &str,
+        stats: &readnode_primitives::BlockProcessingStats,
+    ) -> anyhow::Result<()> {
+        let row = BlockProcessingStatsRow {
+            indexer_id: indexer_id.to_string(),
+            block_height: stats.block_height,
+            block_hash: stats.block_hash.to_string(),
+            txs_started: stats.txs_started,
+            receipts_matched: stats.receipts_matched,
+            outcomes_matched: stats.outcomes_matched,
+            txs_finalized: stats.txs_finalized,
+            collect_duration_ms: stats.collect_duration_ms,
+            save_tx_details_duration_ms: stats.save_tx_details_duration_ms,
+            save_outcomes_and_receipts_duration_ms: stats.save_outcomes_and_receipts_duration_ms,
+            save_transactions_by_public_key_duration_ms: stats
+                .save_transactions_by_public_key_duration_ms,
+        };
+        self.insert_rows(
+            "save_block_processing_stats",
+            "block_processing_stats",
+            &[row],
+        )
+        .await
+    }
+
+    async fn save_function_calls(
+        &self,
+        function_calls: Vec<readnode_primitives::FunctionCallRecord>,
+    ) -> anyhow::Result<()> {
+        let rows: Vec<_> = function_calls
+            .into_iter()
+            .map(|function_call| FunctionCallRow {
+                receipt_id: function_call.receipt_id.to_string(),
+                action_index: function_call.action_index,
+                block_height: function_call.block_height,
+                block_hash: function_call.block_hash.to_string(),
+                predecessor_id: function_call.predecessor_id.to_string(),
+                signer_id: function_call.signer_id.to_string(),
+                receiver_id: function_call.receiver_id.to_string(),
+                method_name: function_call.method_name,
+                args: function_call.args,
+                msg: function_call.msg,
+            })
+            .collect();
+        self.insert_rows("save_function_calls", "function_calls", &rows)
+            .await
+    }
+
+    async fn save_actions(
+        &self,
+        actions: Vec<readnode_primitives::ActionRecord>,
+    ) -> anyhow::Result<()> {
+        let rows: Vec<_> = actions
+            .into_iter()
+            .map(|action| ActionRow {
+                receipt_id: action.receipt_id.to_string(),
+                action_index: action.action_index,
+                block_height: action.block_height,
+                block_hash: action.block_hash.to_string(),
+                predecessor_id: action.predecessor_id.to_string(),
+                signer_id: action.signer_id.to_string(),
+                receiver_id: action.receiver_id.to_string(),
+                action_kind: action.action_kind,
+                method_name: action.method_name,
+                deposit: action.deposit,
+                gas: action.gas,
+                public_key: action.public_key,
+            })
+            .collect();
+        self.insert_rows("save_actions", "actions", &rows).await
+    }
+
+    async fn save_skipped_chunks(
+        &self,
+        block_height: u64,
+        skipped_chunks: Vec<(crate::primitives::ShardId, crate::primitives::ChunkHash)>,
+    ) -> anyhow::Result<()> {
+        let rows: Vec<_> = skipped_chunks
+            .into_iter()
+            .map(|(shard_id, chunk_hash)| SkippedChunkRow {
+                block_height,
+                shard_id,
+                chunk_hash,
+            })
+            .collect();
+        self.insert_rows("save_skipped_chunks", "skipped_chunks", &rows)
+            .await
+    }
+
+    async fn save_block_repair(
+        &self,
+        indexer_id: &str,
+        repair: &readnode_primitives::BlockRepair,
+    ) -> anyhow::Result<()> {
+        let row = BlockRepairRow {
+            indexer_id: indexer_id.to_string(),
+            block_height: repair.block_height,
+            block_hash: repair.block_hash.to_string(),
+            reason: repair.reason.clone(),
+            transactions: repair.transactions,
+            receipts: repair.receipts,
+            error: repair.error.clone(),
+        };
+        self.insert_rows("save_block_repair", "block_repairs", &[row])
+            .await
+    }
+
+    async fn save_missed_block(
+        &self,
+        indexer_id: &str,
+        block_height: u64,
+        error: &str,
+    ) -> anyhow::Result<()> {
+        // Every failed attempt is a separate row, the attempts are counted on read
+        let row = MissedBlockRow {
+            indexer_id: indexer_id.to_string(),
+            block_height,
+            error: error.to_string(),
+        };
+        self.insert_rows("save_missed_block", "missed_blocks", &[row])
+            .await
+    }
+
+    async fn get_missed_blocks(
+        &self,
+        indexer_id: &str,
+    ) -> anyhow::Result<Vec<readnode_primitives::MissedBlock>> {
+        crate::metrics::CLICKHOUSE_READ_QUERIES
+            .with_label_values(&["get_missed_blocks", "missed_blocks"])
+            .inc();
+        let rows = self
+            .client
+            .query(
+                "
+                SELECT block_height, argMax(error, missed_at) AS error, count() AS attempts
+                FROM missed_blocks
+                WHERE indexer_id = ?
+                GROUP BY block_height
+                ORDER BY block_height ASC
+                ",
+            )
+            .bind(indexer_id)
+            .fetch_all::<MissedBlockAttemptsRow>()
+            .await?;
+        Ok(rows
+            .into_iter()
+            .map(|row| readnode_primitives::MissedBlock {
+                block_height: row.block_height,
+                error: row.error,
+                attempts: row.attempts,
+            })
+            .collect())
+    }
+
+    async fn delete_missed_blocks(
+        &self,
+        indexer_id: &str,
+        block_heights: &[u64],
+    ) -> anyhow::Result<()> {
+        crate::metrics::CLICKHOUSE_WRITE_QUERIES
+            .with_label_values(&["delete_missed_blocks", "missed_blocks"])
+            .inc();
+        self.client
+            .query("DELETE FROM missed_blocks WHERE indexer_id = ? AND block_height IN ?")
+            .bind(indexer_id)
+            .bind(block_heights)
+            .execute()
+            .await?;
+        Ok(())
+    }
+
+    async fn get_block_heights_without_block_info(&self, limit: u64) -> anyhow::Result<Vec<u64>> {
+        crate::metrics::CLICKHOUSE_READ_QUERIES
+            .with_label_values(&[
+                "get_block_heights_without_block_info",
+                "transactions_by_public_key",
+            ])
+            .inc();
+        // The rows are never updated, the block info is stored in the separate table
+        let block_heights = self
+            .client
+            .query(
+                "
+                SELECT DISTINCT block_height
+                FROM transactions_by_public_key
+                WHERE protocol_version IS NULL
+                    AND block_height NOT IN (SELECT block_height FROM transactions_block_info)
+                ORDER BY block_height ASC
+                LIMIT ?
+                ",
+            )
+            .bind(limit)
+            .fetch_all::<BlockHeightRow>()
+            .await?;
+        Ok(block_heights
+            .into_iter()
+            .map(|row| row.block_height)
+            .collect())
+    }
+
+    async fn update_transactions_block_info(
+        &self,
+        block_height: u64,
+        protocol_version: near_primitives::types::ProtocolVersion,
+        gas_price: near_primitives::types::Balance,
+    ) -> anyhow::Result<()> {
+        let row = TransactionsBlockInfoRow {
+            block_height,
+            protocol_version,
+            gas_price,
+        };
+        self.insert_rows(
+            "update_transactions_block_info",
+            "transactions_block_info",
+            &[row],
+        )
+        .await
+    }
+
+    async fn sync_group_assignments(
+        &self,
+        _group_id: &str,
+        _indexer_id: &str,
+        _lease_block_height: u64,
+        _member_timeout_secs: u64,
+    ) -> anyhow::Result<Vec<readnode_primitives::GroupAssignment>> {
+        // The assignment is agreed under a lock, ClickHouse has no transactions for it
+        anyhow::bail!("Block sharding is not supported by the ClickHouse database backend")
+    }
+
+    async fn get_block_data_counts(
+        &self,
+        _from_block_height: u64,
+        _to_block_height: u64,
+    ) -> anyhow::Result<std::collections::HashMap<u64, readnode_primitives::BlockDataCounts>> {
+        // The chunks are stored by the state-indexer in PostgreSQL only,
+        // so every block would be reported as incomplete
+        anyhow::bail!("Completeness report is not supported by the ClickHouse database backend")
+    }
+
+    async fn update_meta(
+        &self,
+        indexer_id: &str,
+        block_height: u64,
+        block_timestamp: u64,
+    ) -> anyhow::Result<()> {
+        // The blocks are not stored by the state-indexer in ClickHouse,
+        // the processed ones are recorded to find the block height by timestamp
+        self.insert_rows(
+            "update_meta",
+            "blocks",
+            &[BlockRow {
+                block_height,
+                block_timestamp,
+            }],
+        )
+        .await?;
+        let row = MetaRow {
+            indexer_id: indexer_id.to_string(),
+            last_processed_block_height: block_height,
+            last_processed_block_timestamp: block_timestamp,
+            indexer_type: "tx".to_string(),
+            crate_version: env!("CARGO_PKG_VERSION").to_string(),
+            schema_version: crate::SCHEMA_VERSION,
+        };
+        self.insert_rows("update_meta", "meta", &[row]).await
+    }
+
+    async fn get_last_processed_block_height(&self, indexer_id: &str) -> anyhow::Result<u64> {
+        crate::metrics::CLICKHOUSE_READ_QUERIES
+            .with_label_values(&["get_last_processed_block_height", "meta"])
+            .inc();
+        let row = self
+            .client
+            .query(
+                "
+                SELECT argMax(last_processed_block_height, updated_at) AS block_height
+                FROM meta
+                WHERE indexer_id = ?
+                GROUP BY indexer_id
+                ",
+            )
+            .bind(indexer_id)
+            .fetch_one::<BlockHeightRow>()
+            .await?;
+        Ok(row.block_height)
+    }
+
+    async fn save_network_identity(
+        &self,
+        network: &readnode_primitives::NetworkIdentity,
+    ) -> anyhow::Result<readnode_primitives::NetworkIdentity> {
+        crate::metrics::CLICKHOUSE_READ_QUERIES
+            .with_label_values(&["save_network_identity", "network"])
+            .inc();
+        let first_network_query = "
+            SELECT chain_id, genesis_hash
+            FROM network
+            ORDER BY created_at
+            LIMIT 1
+            ";
+        let stored_row = match self
+            .client
+            .query(first_network_query)
+            .fetch_optional::<NetworkRow>()
+            .await?
+        {
+            Some(row) => row,
+            None => {
+                // The concurrent first writers may both insert, the earliest row wins
+                self.insert_rows(
+                    "save_network_identity",
+                    "network",
+                    &[NetworkRow {
+                        chain_id: network.chain_id.clone(),
+                        genesis_hash: network.genesis_hash.to_string(),
+                    }],
+                )
+                .await?;
+                self.client
+                    .query(first_network_query)
+                    .fetch_one::<NetworkRow>()
+                    .await?
+            }
+        };
+        Ok(readnode_primitives::NetworkIdentity {
+            chain_id: stored_row.chain_id,
+            genesis_hash: stored_row
+                .genesis_hash
+                .parse()
+                .map_err(|err| anyhow::anyhow!("Failed to parse `genesis_hash`: {}", err))?,
+        })
+    }
+
+    async fn notify_finalized_transactions(
+        &self,
+        _transaction_hashes: &[near_primitives::hash::CryptoHash],
+    ) -> anyhow::Result<()> {
+        // The rpc-servers don't read the data from ClickHouse, nobody is waiting for it
+        Ok(())
+    }
+
+    async fn get_block_height_by_timestamp(&self, block_timestamp: u64) -> anyhow::Result<u64> {
+        crate::metrics::CLICKHOUSE_READ_QUERIES
+            .with_label_values(&["get_block_height_by_timestamp", "blocks"])
+            .inc();
+        // The processed blocks are recorded by the tx-indexers. Until they reach the timestamp
+        // the latest recorded block is older than the requested one, so none is returned
+        let row = self
+            .client
+            .query(
+                "
+                SELECT block_height
+                FROM blocks
+                WHERE block_timestamp <= ?
+                    AND (
+                        SELECT max(last_processed_block_timestamp)
+                        FROM meta
+                        WHERE indexer_type = 'tx'
+                    ) >= ?
+                ORDER BY block_timestamp DESC
+                LIMIT 1
+                ",
+            )
+            .bind(block_timestamp)
+            .bind(block_timestamp)
+            .fetch_one::<BlockHeightRow>()
+            .await?;
+        Ok(row.block_height)
+    }
+
+    async fn get_transactions_to_prune(
+        &self,
+        before_block_height: u64,
+        limit: u64,
+    ) -> anyhow::Result<Vec<near_primitives::hash::CryptoHash>> {
+        crate::metrics::CLICKHOUSE_READ_QUERIES
+            .with_label_values(&["get_transactions_to_prune", "outcomes"])
+            .inc();
+        // The outcome of the transaction has the id of the transaction hash
+        let transaction_hashes = self
+            .client
+            .query(
+                "
+                SELECT outcome_id AS transaction_hash
+                FROM outcomes
+                WHERE block_height < ? AND outcome_id = parent_transaction_hash
+                ORDER BY block_height
+                LIMIT ?
+                ",
+            )
+            .bind(before_block_height)
+            .bind(limit)
+            .fetch_all::<TransactionHashRow>()
+            .await?;
+        transaction_hashes
+            .into_iter()
+            .map(|row| {
+                row.transaction_hash.parse().map_err(|err| {
+                    anyhow::anyhow!(
+                        "Failed to parse `transaction_hash` {}: {:?}",
+                        row.transaction_hash,
+                        err
+                    )
+                })
+            })
+            .collect()
+    }
+
+    async fn prune_transactions(
+        &self,
+        before_block_height: u64,
+        transaction_hashes: &[near_primitives::hash::CryptoHash],
+    ) -> anyhow::Result<u64> {
+        if transaction_hashes.is_empty() {
+            return Ok(0);
+        }
+        let transaction_hashes: Vec<String> = transaction_hashes
+            .iter()
+            .map(|transaction_hash| transaction_hash.to_string())
+            .collect();
+        let pruned_rows = self
+            .delete_rows(
+                "prune_transactions",
+                "transactions_by_public_key",
+                "block_height < ? AND transaction_hash IN ?",
+                before_block_height,
+                Some(transaction_hashes.clone()),
+                &crate::metrics::RETENTION_PRUNED_ROWS,
+            )
+            .await?;
+        // The outcomes the transactions are enumerated by are deleted last,
+        // so the failed run enumerates the transactions again
+        Ok(pruned_rows
+            + self
+                .delete_rows(
+                    "prune_transactions",
+                    "outcomes",
+                    "block_height < ? AND outcome_id IN ?",
+                    before_block_height,
+                    Some(transaction_hashes),
+                    &crate::metrics::RETENTION_PRUNED_ROWS,
+                )
+                .await?)
+    }
+
+    async fn prune_receipts_and_outcomes(
+        &self,
+        before_block_height: u64,
+        _limit: u64,
+    ) -> anyhow::Result<u64> {
+        // The lightweight deletes mark the rows of all the parts at once,
+        // so the rows are not deleted by batches
+        let mut pruned_rows = 0;
+        for table_name in ["receipts", "outcomes"] {
+            pruned_rows += self
+                .delete_rows::<Vec<String>>(
+                    "prune_receipts_and_outcomes",
+                    table_name,
+                    "block_height < ?",
+                    before_block_height,
+                    None,
+                    &crate::metrics::RETENTION_PRUNED_ROWS,
+                )
+                .await?;
+        }
+        Ok(pruned_rows)
+    }
+
+    async fn prune_receipts_of_filtered_transactions(
+        &self,
+        _from_block_height: u64,
+        _to_block_height: u64,
+        _tracked_accounts: &[readnode_primitives::indexer::types::AccountId],
+    ) -> anyhow::Result<u64> {
+        // The receipt bodies are not stored in ClickHouse, so the signer of the transaction
+        // is unknown and the rows can't be proven to be filtered out, they are kept
+        Ok(0)
+    }
+}
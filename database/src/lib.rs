@@ -2,6 +2,9 @@
 extern crate lazy_static;
 
 mod base;
+mod cache;
+#[cfg(feature = "clickhouse")]
+mod clickhouse;
 
 use crate::base::BaseDbManager;
 pub use crate::base::PageToken;
@@ -9,12 +12,31 @@ pub use crate::base::ReaderDbManager;
 pub use crate::base::StateIndexerDbManager;
 pub use crate::base::TxIndexerDbManager;
 
-mod metrics;
+mod metered;
+pub mod metrics;
+pub use crate::metrics::collectors as metrics_collectors;
 mod postgres;
 pub mod primitives;
+pub mod scheduler;
 
+#[cfg(feature = "clickhouse")]
+pub use crate::clickhouse::ClickhouseDBManager;
 pub use crate::postgres::PostgresDBManager;
 
+/// Version of the data layout written by the indexers and stored in the `meta` table.
+/// Bump it on every change the readers of the previous version can't read correctly,
+/// the rpc-server refuses to serve the data written with the newer schema version
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// Whether the error of the lookup means the requested record is not stored,
+/// unlike the failures of the database itself
+pub fn is_not_found_error(err: &anyhow::Error) -> bool {
+    matches!(
+        err.downcast_ref::<sqlx::Error>(),
+        Some(sqlx::Error::RowNotFound)
+    )
+}
+
 pub async fn prepare_db_manager<T>(
     config: &configuration::DatabaseConfig,
     shard_layout: near_primitives::shard_layout::ShardLayout,
@@ -24,3 +46,60 @@ where
 {
     Ok(*T::new(config, shard_layout).await?)
 }
+
+/// Prepares the reader database manager of the backend selected in the config.
+/// The rpc-server works with the backend through the `ReaderDbManager` trait object only,
+/// so the storage is switched by the config without rebuilding the binary
+pub async fn prepare_reader_db_manager(
+    config: &configuration::DatabaseConfig,
+    shard_layout: near_primitives::shard_layout::ShardLayout,
+) -> anyhow::Result<Box<dyn ReaderDbManager + Sync + Send + 'static>> {
+    tracing::info!("Database backend: {:?}", config.backend);
+    let db_manager: Box<dyn ReaderDbManager + Sync + Send + 'static> = match config.backend {
+        configuration::DatabaseBackend::Postgres => {
+            PostgresDBManager::new(config, shard_layout).await?
+        }
+        configuration::DatabaseBackend::Clickhouse => anyhow::bail!(
+            "ClickHouse database backend stores the tx-indexer data only, use PostgreSQL for the rpc-server"
+        ),
+    };
+    // The reads of the backend are measured behind the cache, the cache hits are not counted
+    let db_manager: Box<dyn ReaderDbManager + Sync + Send + 'static> =
+        Box::new(metered::MeteredDbManager::new(db_manager, config.backend));
+    let Some(redis_cache_config) = &config.redis_cache else {
+        return Ok(db_manager);
+    };
+    // The configured cache has to be available on startup,
+    // later Redis outages fall through to the database
+    let cached_db_manager = cache::redis::RedisCachedDbManager::new(db_manager, redis_cache_config)
+        .await
+        .map_err(|err| anyhow::anyhow!("Failed to connect to the redis cache: {:?}", err))?;
+    tracing::info!("Database reads are cached in the redis");
+    Ok(Box::new(cached_db_manager))
+}
+
+/// Prepares the tx-indexer database manager of the backend selected in the config.
+/// ClickHouse is available only if the binary is built with the `clickhouse` feature
+pub async fn prepare_tx_indexer_db_manager(
+    config: &configuration::DatabaseConfig,
+    shard_layout: near_primitives::shard_layout::ShardLayout,
+) -> anyhow::Result<Box<dyn TxIndexerDbManager + Sync + Send + 'static>> {
+    tracing::info!("Database backend: {:?}", config.backend);
+    let db_manager: Box<dyn TxIndexerDbManager + Sync + Send + 'static> = match config.backend {
+        configuration::DatabaseBackend::Postgres => {
+            PostgresDBManager::new(config, shard_layout).await?
+        }
+        #[cfg(feature = "clickhouse")]
+        configuration::DatabaseBackend::Clickhouse => {
+            ClickhouseDBManager::new(config, shard_layout).await?
+        }
+        #[cfg(not(feature = "clickhouse"))]
+        configuration::DatabaseBackend::Clickhouse => anyhow::bail!(
+            "ClickHouse database backend requires the binary built with the `clickhouse` feature"
+        ),
+    };
+    Ok(Box::new(metered::MeteredTxIndexerDbManager::new(
+        db_manager,
+        config.backend,
+    )))
+}
@@ -0,0 +1,975 @@
+/// Reader database manager measuring the duration of every read of the wrapped backend.
+/// The metrics have the same names for all the backends and are labeled by the backend,
+/// so the backends serving the same requests (e.g. while migrating the data between them)
+/// are compared by the same dashboards
+pub(crate) struct MeteredDbManager {
+    db_manager: Box<dyn crate::ReaderDbManager + Sync + Send + 'static>,
+    backend: &'static str,
+}
+
+impl MeteredDbManager {
+    pub(crate) fn new(
+        db_manager: Box<dyn crate::ReaderDbManager + Sync + Send + 'static>,
+        backend: configuration::DatabaseBackend,
+    ) -> Self {
+        Self {
+            db_manager,
+            backend: backend_label(backend),
+        }
+    }
+
+    async fn timed<T, F>(&self, db_method_name: &str, read: F) -> anyhow::Result<T>
+    where
+        F: std::future::Future<Output = anyhow::Result<T>>,
+    {
+        timed(
+            &crate::metrics::DATABASE_READ_DURATION,
+            &crate::metrics::DATABASE_READ_ERRORS,
+            self.backend,
+            db_method_name,
+            read,
+        )
+        .await
+    }
+}
+
+/// Tx-indexer database manager measuring every write and read of the wrapped backend.
+/// Every attempt of the retried writes is measured, so the failed ones are counted
+/// as the errors of the method even if the retry succeeds
+pub(crate) struct MeteredTxIndexerDbManager {
+    db_manager: Box<dyn crate::TxIndexerDbManager + Sync + Send + 'static>,
+    backend: &'static str,
+}
+
+impl MeteredTxIndexerDbManager {
+    pub(crate) fn new(
+        db_manager: Box<dyn crate::TxIndexerDbManager + Sync + Send + 'static>,
+        backend: configuration::DatabaseBackend,
+    ) -> Self {
+        Self {
+            db_manager,
+            backend: backend_label(backend),
+        }
+    }
+
+    async fn timed_write<T, F>(&self, db_method_name: &str, write: F) -> anyhow::Result<T>
+    where
+        F: std::future::Future<Output = anyhow::Result<T>>,
+    {
+        timed(
+            &crate::metrics::DATABASE_WRITE_DURATION,
+            &crate::metrics::DATABASE_WRITE_ERRORS,
+            self.backend,
+            db_method_name,
+            write,
+        )
+        .await
+    }
+
+    async fn timed_read<T, F>(&self, db_method_name: &str, read: F) -> anyhow::Result<T>
+    where
+        F: std::future::Future<Output = anyhow::Result<T>>,
+    {
+        timed(
+            &crate::metrics::DATABASE_READ_DURATION,
+            &crate::metrics::DATABASE_READ_ERRORS,
+            self.backend,
+            db_method_name,
+            read,
+        )
+        .await
+    }
+}
+
+fn backend_label(backend: configuration::DatabaseBackend) -> &'static str {
+    match backend {
+        configuration::DatabaseBackend::Postgres => "postgres",
+        configuration::DatabaseBackend::Clickhouse => "clickhouse",
+    }
+}
+
+// Observes the duration of the query and counts the failed queries by the database method
+async fn timed<T, F>(
+    duration: &prometheus::HistogramVec,
+    errors: &prometheus::IntCounterVec,
+    backend: &str,
+    db_method_name: &str,
+    query: F,
+) -> anyhow::Result<T>
+where
+    F: std::future::Future<Output = anyhow::Result<T>>,
+{
+    let timer = duration
+        .with_label_values(&[backend, db_method_name])
+        .start_timer();
+    let result = query.await;
+    timer.observe_duration();
+    if result.is_err() {
+        errors.with_label_values(&[backend, db_method_name]).inc();
+    }
+    result
+}
+
+#[async_trait::async_trait]
+impl crate::ReaderDbManager for MeteredDbManager {
+    async fn get_block_height_by_hash(
+        &self,
+        block_hash: near_primitives::hash::CryptoHash,
+        method_name: &str,
+    ) -> anyhow::Result<u64> {
+        self.timed(
+            "get_block_height_by_hash",
+            self.db_manager
+                .get_block_height_by_hash(block_hash, method_name),
+        )
+        .await
+    }
+
+    async fn get_block_by_chunk_hash(
+        &self,
+        chunk_hash: near_primitives::hash::CryptoHash,
+        method_name: &str,
+    ) -> anyhow::Result<readnode_primitives::BlockHeightShardId> {
+        self.timed(
+            "get_block_by_chunk_hash",
+            self.db_manager
+                .get_block_by_chunk_hash(chunk_hash, method_name),
+        )
+        .await
+    }
+
+    async fn get_state_by_key_prefix(
+        &self,
+        account_id: &near_primitives::types::AccountId,
+        block_height: near_primitives::types::BlockHeight,
+        prefix: &[u8],
+        max_keys: Option<usize>,
+        method_name: &str,
+    ) -> anyhow::Result<
+        std::collections::HashMap<readnode_primitives::StateKey, readnode_primitives::StateValue>,
+    > {
+        self.timed(
+            "get_state_by_key_prefix",
+            self.db_manager.get_state_by_key_prefix(
+                account_id,
+                block_height,
+                prefix,
+                max_keys,
+                method_name,
+            ),
+        )
+        .await
+    }
+
+    async fn get_state_keys_by_page(
+        &self,
+        account_id: &near_primitives::types::AccountId,
+        block_height: near_primitives::types::BlockHeight,
+        limit: u64,
+        page_token: crate::PageToken,
+        method_name: &str,
+    ) -> anyhow::Result<(Vec<readnode_primitives::StateKey>, crate::PageToken)> {
+        self.timed(
+            "get_state_keys_by_page",
+            self.db_manager.get_state_keys_by_page(
+                account_id,
+                block_height,
+                limit,
+                page_token,
+                method_name,
+            ),
+        )
+        .await
+    }
+
+    async fn get_state_diff_by_page(
+        &self,
+        account_id: &near_primitives::types::AccountId,
+        from_block_height: near_primitives::types::BlockHeight,
+        to_block_height: near_primitives::types::BlockHeight,
+        prefix: &[u8],
+        limit: u64,
+        page_token: crate::PageToken,
+        method_name: &str,
+    ) -> anyhow::Result<(Vec<readnode_primitives::StateKeyDiff>, crate::PageToken)> {
+        self.timed(
+            "get_state_diff_by_page",
+            self.db_manager.get_state_diff_by_page(
+                account_id,
+                from_block_height,
+                to_block_height,
+                prefix,
+                limit,
+                page_token,
+                method_name,
+            ),
+        )
+        .await
+    }
+
+    async fn get_state(
+        &self,
+        account_id: &near_primitives::types::AccountId,
+        block_height: near_primitives::types::BlockHeight,
+        max_keys: Option<usize>,
+        method_name: &str,
+    ) -> anyhow::Result<
+        std::collections::HashMap<readnode_primitives::StateKey, readnode_primitives::StateValue>,
+    > {
+        self.timed(
+            "get_state",
+            self.db_manager
+                .get_state(account_id, block_height, max_keys, method_name),
+        )
+        .await
+    }
+
+    async fn get_state_size(
+        &self,
+        account_id: &near_primitives::types::AccountId,
+        block_height: near_primitives::types::BlockHeight,
+        max_keys: usize,
+        method_name: &str,
+    ) -> anyhow::Result<Option<readnode_primitives::StateSize>> {
+        self.timed(
+            "get_state_size",
+            self.db_manager
+                .get_state_size(account_id, block_height, max_keys, method_name),
+        )
+        .await
+    }
+
+    async fn get_state_key_value(
+        &self,
+        account_id: &near_primitives::types::AccountId,
+        block_height: near_primitives::types::BlockHeight,
+        key_data: readnode_primitives::StateKey,
+        method_name: &str,
+    ) -> anyhow::Result<(
+        readnode_primitives::StateKey,
+        readnode_primitives::StateValue,
+    )> {
+        self.timed(
+            "get_state_key_value",
+            self.db_manager
+                .get_state_key_value(account_id, block_height, key_data, method_name),
+        )
+        .await
+    }
+
+    async fn get_state_keys_values(
+        &self,
+        account_id: &near_primitives::types::AccountId,
+        block_height: near_primitives::types::BlockHeight,
+        keys: &[readnode_primitives::StateKey],
+        method_name: &str,
+    ) -> anyhow::Result<
+        std::collections::HashMap<readnode_primitives::StateKey, readnode_primitives::StateValue>,
+    > {
+        self.timed(
+            "get_state_keys_values",
+            self.db_manager
+                .get_state_keys_values(account_id, block_height, keys, method_name),
+        )
+        .await
+    }
+
+    async fn get_state_changes_in_block(
+        &self,
+        block_height: near_primitives::types::BlockHeight,
+        method_name: &str,
+    ) -> anyhow::Result<Option<Vec<near_primitives::views::StateChangeValueView>>> {
+        self.timed(
+            "get_state_changes_in_block",
+            self.db_manager
+                .get_state_changes_in_block(block_height, method_name),
+        )
+        .await
+    }
+
+    async fn get_account(
+        &self,
+        account_id: &near_primitives::types::AccountId,
+        request_block_height: near_primitives::types::BlockHeight,
+        method_name: &str,
+    ) -> anyhow::Result<readnode_primitives::QueryData<near_primitives::account::Account>> {
+        self.timed(
+            "get_account",
+            self.db_manager
+                .get_account(account_id, request_block_height, method_name),
+        )
+        .await
+    }
+
+    async fn get_contract_code(
+        &self,
+        account_id: &near_primitives::types::AccountId,
+        request_block_height: near_primitives::types::BlockHeight,
+        method_name: &str,
+    ) -> anyhow::Result<readnode_primitives::QueryData<Vec<u8>>> {
+        self.timed(
+            "get_contract_code",
+            self.db_manager
+                .get_contract_code(account_id, request_block_height, method_name),
+        )
+        .await
+    }
+
+    async fn get_access_key(
+        &self,
+        account_id: &near_primitives::types::AccountId,
+        request_block_height: near_primitives::types::BlockHeight,
+        public_key: near_crypto::PublicKey,
+        method_name: &str,
+    ) -> anyhow::Result<readnode_primitives::QueryData<near_primitives::account::AccessKey>> {
+        self.timed(
+            "get_access_key",
+            self.db_manager.get_access_key(
+                account_id,
+                request_block_height,
+                public_key,
+                method_name,
+            ),
+        )
+        .await
+    }
+
+    async fn get_account_access_keys(
+        &self,
+        account_id: &near_primitives::types::AccountId,
+        block_height: near_primitives::types::BlockHeight,
+        method_name: &str,
+    ) -> anyhow::Result<Vec<near_primitives::views::AccessKeyInfoView>> {
+        self.timed(
+            "get_account_access_keys",
+            self.db_manager
+                .get_account_access_keys(account_id, block_height, method_name),
+        )
+        .await
+    }
+
+    async fn get_account_access_keys_count(
+        &self,
+        account_id: &near_primitives::types::AccountId,
+        block_height: near_primitives::types::BlockHeight,
+        method_name: &str,
+    ) -> anyhow::Result<u64> {
+        self.timed(
+            "get_account_access_keys_count",
+            self.db_manager
+                .get_account_access_keys_count(account_id, block_height, method_name),
+        )
+        .await
+    }
+
+    async fn get_account_public_keys(
+        &self,
+        account_id: &near_primitives::types::AccountId,
+        block_height: near_primitives::types::BlockHeight,
+        method_name: &str,
+    ) -> anyhow::Result<Vec<near_crypto::PublicKey>> {
+        self.timed(
+            "get_account_public_keys",
+            self.db_manager
+                .get_account_public_keys(account_id, block_height, method_name),
+        )
+        .await
+    }
+
+    async fn get_receipt_by_id(
+        &self,
+        receipt_id: near_primitives::hash::CryptoHash,
+        method_name: &str,
+    ) -> anyhow::Result<readnode_primitives::ReceiptRecord> {
+        self.timed(
+            "get_receipt_by_id",
+            self.db_manager.get_receipt_by_id(receipt_id, method_name),
+        )
+        .await
+    }
+
+    async fn get_receipts_by_ids(
+        &self,
+        receipt_ids: &[near_primitives::hash::CryptoHash],
+        method_name: &str,
+    ) -> anyhow::Result<Vec<readnode_primitives::ReceiptRecord>> {
+        self.timed(
+            "get_receipts_by_ids",
+            self.db_manager
+                .get_receipts_by_ids(receipt_ids, method_name),
+        )
+        .await
+    }
+
+    async fn get_block_by_height_and_shard_id(
+        &self,
+        block_height: near_primitives::types::BlockHeight,
+        shard_id: near_primitives::types::ShardId,
+        method_name: &str,
+    ) -> anyhow::Result<readnode_primitives::BlockHeightShardId> {
+        self.timed(
+            "get_block_by_height_and_shard_id",
+            self.db_manager
+                .get_block_by_height_and_shard_id(block_height, shard_id, method_name),
+        )
+        .await
+    }
+
+    async fn get_skipped_chunk(
+        &self,
+        block_height: near_primitives::types::BlockHeight,
+        shard_id: near_primitives::types::ShardId,
+        method_name: &str,
+    ) -> anyhow::Result<Option<near_primitives::hash::CryptoHash>> {
+        self.timed(
+            "get_skipped_chunk",
+            self.db_manager
+                .get_skipped_chunk(block_height, shard_id, method_name),
+        )
+        .await
+    }
+
+    async fn get_top_state_growers(
+        &self,
+        window_days: u64,
+        limit: u64,
+        method_name: &str,
+    ) -> anyhow::Result<(Option<u64>, Vec<readnode_primitives::StateGrowth>)> {
+        self.timed(
+            "get_top_state_growers",
+            self.db_manager
+                .get_top_state_growers(window_days, limit, method_name),
+        )
+        .await
+    }
+
+    async fn get_chunk_congestion(
+        &self,
+        block_height: near_primitives::types::BlockHeight,
+        shard_id: near_primitives::types::ShardId,
+        method_name: &str,
+    ) -> anyhow::Result<readnode_primitives::ChunkCongestion> {
+        self.timed(
+            "get_chunk_congestion",
+            self.db_manager
+                .get_chunk_congestion(block_height, shard_id, method_name),
+        )
+        .await
+    }
+
+    async fn get_validators_by_epoch_id(
+        &self,
+        epoch_id: near_primitives::hash::CryptoHash,
+        method_name: &str,
+    ) -> anyhow::Result<readnode_primitives::EpochValidatorsInfo> {
+        self.timed(
+            "get_validators_by_epoch_id",
+            self.db_manager
+                .get_validators_by_epoch_id(epoch_id, method_name),
+        )
+        .await
+    }
+
+    async fn get_validators_by_end_block_height(
+        &self,
+        block_height: near_primitives::types::BlockHeight,
+        method_name: &str,
+    ) -> anyhow::Result<readnode_primitives::EpochValidatorsInfo> {
+        self.timed(
+            "get_validators_by_end_block_height",
+            self.db_manager
+                .get_validators_by_end_block_height(block_height, method_name),
+        )
+        .await
+    }
+
+    async fn get_protocol_config_by_epoch_id(
+        &self,
+        epoch_id: near_primitives::hash::CryptoHash,
+        method_name: &str,
+    ) -> anyhow::Result<near_chain_configs::ProtocolConfigView> {
+        self.timed(
+            "get_protocol_config_by_epoch_id",
+            self.db_manager
+                .get_protocol_config_by_epoch_id(epoch_id, method_name),
+        )
+        .await
+    }
+
+    async fn get_transactions_by_public_key(
+        &self,
+        public_key: &near_crypto::PublicKey,
+        limit: u64,
+        cursor: crate::PageToken,
+        method_name: &str,
+    ) -> anyhow::Result<(
+        Vec<readnode_primitives::TransactionByPublicKeyRecord>,
+        crate::PageToken,
+    )> {
+        self.timed(
+            "get_transactions_by_public_key",
+            self.db_manager
+                .get_transactions_by_public_key(public_key, limit, cursor, method_name),
+        )
+        .await
+    }
+
+    async fn search_function_calls(
+        &self,
+        contract_id: &near_primitives::types::AccountId,
+        contract_method_name: Option<&str>,
+        args_substring: Option<&str>,
+        limit: u64,
+        cursor: crate::PageToken,
+        method_name: &str,
+    ) -> anyhow::Result<(
+        Vec<readnode_primitives::FunctionCallRecord>,
+        crate::PageToken,
+    )> {
+        self.timed(
+            "search_function_calls",
+            self.db_manager.search_function_calls(
+                contract_id,
+                contract_method_name,
+                args_substring,
+                limit,
+                cursor,
+                method_name,
+            ),
+        )
+        .await
+    }
+
+    async fn get_actions_by_contract_method(
+        &self,
+        contract_id: &near_primitives::types::AccountId,
+        contract_method_name: &str,
+        from_block_height: Option<near_primitives::types::BlockHeight>,
+        to_block_height: Option<near_primitives::types::BlockHeight>,
+        limit: u64,
+        cursor: crate::PageToken,
+        method_name: &str,
+    ) -> anyhow::Result<(Vec<readnode_primitives::ActionRecord>, crate::PageToken)> {
+        self.timed(
+            "get_actions_by_contract_method",
+            self.db_manager.get_actions_by_contract_method(
+                contract_id,
+                contract_method_name,
+                from_block_height,
+                to_block_height,
+                limit,
+                cursor,
+                method_name,
+            ),
+        )
+        .await
+    }
+
+    async fn get_latest_indexed_block_timestamps(
+        &self,
+        method_name: &str,
+    ) -> anyhow::Result<std::collections::HashMap<String, u64>> {
+        self.timed(
+            "get_latest_indexed_block_timestamps",
+            self.db_manager
+                .get_latest_indexed_block_timestamps(method_name),
+        )
+        .await
+    }
+
+    async fn listen_indexer_notifications(
+        &self,
+    ) -> anyhow::Result<
+        futures::stream::BoxStream<'static, anyhow::Result<crate::primitives::IndexerNotification>>,
+    > {
+        self.db_manager.listen_indexer_notifications().await
+    }
+
+    async fn get_writer_versions(
+        &self,
+        method_name: &str,
+    ) -> anyhow::Result<Vec<readnode_primitives::WriterVersion>> {
+        self.timed(
+            "get_writer_versions",
+            self.db_manager.get_writer_versions(method_name),
+        )
+        .await
+    }
+
+    async fn get_network_identity(
+        &self,
+        method_name: &str,
+    ) -> anyhow::Result<Option<readnode_primitives::NetworkIdentity>> {
+        self.timed(
+            "get_network_identity",
+            self.db_manager.get_network_identity(method_name),
+        )
+        .await
+    }
+
+    async fn check_state_key_buckets(&self, method_name: &str) -> anyhow::Result<()> {
+        self.timed(
+            "check_state_key_buckets",
+            self.db_manager.check_state_key_buckets(method_name),
+        )
+        .await
+    }
+
+    async fn get_recent_transactions_by_signer(
+        &self,
+        signer_id: &near_primitives::types::AccountId,
+        public_keys: &[near_crypto::PublicKey],
+        block_height: near_primitives::types::BlockHeight,
+        limit: u64,
+        method_name: &str,
+    ) -> anyhow::Result<Vec<readnode_primitives::TransactionByPublicKeyRecord>> {
+        self.timed(
+            "get_recent_transactions_by_signer",
+            self.db_manager.get_recent_transactions_by_signer(
+                signer_id,
+                public_keys,
+                block_height,
+                limit,
+                method_name,
+            ),
+        )
+        .await
+    }
+
+    async fn get_validators_changes_by_page(
+        &self,
+        limit: u64,
+        page_token: crate::PageToken,
+        method_name: &str,
+    ) -> anyhow::Result<(
+        Vec<readnode_primitives::EpochValidatorsChanges>,
+        crate::PageToken,
+    )> {
+        self.timed(
+            "get_validators_changes_by_page",
+            self.db_manager
+                .get_validators_changes_by_page(limit, page_token, method_name),
+        )
+        .await
+    }
+
+    async fn get_produced_blocks(
+        &self,
+        validator_id: &near_primitives::types::AccountId,
+        epoch_id: near_primitives::hash::CryptoHash,
+        method_name: &str,
+    ) -> anyhow::Result<(
+        Vec<readnode_primitives::ProducedBlock>,
+        Vec<readnode_primitives::ProducedChunk>,
+    )> {
+        self.timed(
+            "get_produced_blocks",
+            self.db_manager
+                .get_produced_blocks(validator_id, epoch_id, method_name),
+        )
+        .await
+    }
+}
+
+// `save_outcome_and_receipt` is not wrapped, its default implementation
+// calls the measured `save_outcomes` and `save_receipts`
+#[async_trait::async_trait]
+impl crate::TxIndexerDbManager for MeteredTxIndexerDbManager {
+    async fn save_receipts(
+        &self,
+        shard_id: crate::primitives::ShardId,
+        receipts: Vec<readnode_primitives::ReceiptRecord>,
+    ) -> anyhow::Result<()> {
+        self.timed_write(
+            "save_receipts",
+            self.db_manager.save_receipts(shard_id, receipts),
+        )
+        .await
+    }
+
+    async fn save_outcomes(
+        &self,
+        shard_id: crate::primitives::ShardId,
+        outcomes: Vec<readnode_primitives::OutcomeRecord>,
+    ) -> anyhow::Result<()> {
+        self.timed_write(
+            "save_outcomes",
+            self.db_manager.save_outcomes(shard_id, outcomes),
+        )
+        .await
+    }
+
+    async fn save_transactions_by_public_key(
+        &self,
+        transactions: Vec<readnode_primitives::TransactionByPublicKeyRecord>,
+    ) -> anyhow::Result<()> {
+        self.timed_write(
+            "save_transactions_by_public_key",
+            self.db_manager
+                .save_transactions_by_public_key(transactions),
+        )
+        .await
+    }
+
+    async fn save_block_processing_stats(
+        &self,
+        indexer_id: &str,
+        stats: &readnode_primitives::BlockProcessingStats,
+    ) -> anyhow::Result<()> {
+        self.timed_write(
+            "save_block_processing_stats",
+            self.db_manager
+                .save_block_processing_stats(indexer_id, stats),
+        )
+        .await
+    }
+
+    async fn save_function_calls(
+        &self,
+        function_calls: Vec<readnode_primitives::FunctionCallRecord>,
+    ) -> anyhow::Result<()> {
+        self.timed_write(
+            "save_function_calls",
+            self.db_manager.save_function_calls(function_calls),
+        )
+        .await
+    }
+
+    async fn save_actions(
+        &self,
+        actions: Vec<readnode_primitives::ActionRecord>,
+    ) -> anyhow::Result<()> {
+        self.timed_write("save_actions", self.db_manager.save_actions(actions))
+            .await
+    }
+
+    async fn save_skipped_chunks(
+        &self,
+        block_height: u64,
+        skipped_chunks: Vec<(crate::primitives::ShardId, crate::primitives::ChunkHash)>,
+    ) -> anyhow::Result<()> {
+        self.timed_write(
+            "save_skipped_chunks",
+            self.db_manager
+                .save_skipped_chunks(block_height, skipped_chunks),
+        )
+        .await
+    }
+
+    async fn save_block_repair(
+        &self,
+        indexer_id: &str,
+        repair: &readnode_primitives::BlockRepair,
+    ) -> anyhow::Result<()> {
+        self.timed_write(
+            "save_block_repair",
+            self.db_manager.save_block_repair(indexer_id, repair),
+        )
+        .await
+    }
+
+    async fn save_missed_block(
+        &self,
+        indexer_id: &str,
+        block_height: u64,
+        error: &str,
+    ) -> anyhow::Result<()> {
+        self.timed_write(
+            "save_missed_block",
+            self.db_manager
+                .save_missed_block(indexer_id, block_height, error),
+        )
+        .await
+    }
+
+    async fn get_missed_blocks(
+        &self,
+        indexer_id: &str,
+    ) -> anyhow::Result<Vec<readnode_primitives::MissedBlock>> {
+        self.timed_read(
+            "get_missed_blocks",
+            self.db_manager.get_missed_blocks(indexer_id),
+        )
+        .await
+    }
+
+    async fn delete_missed_blocks(
+        &self,
+        indexer_id: &str,
+        block_heights: &[u64],
+    ) -> anyhow::Result<()> {
+        self.timed_write(
+            "delete_missed_blocks",
+            self.db_manager
+                .delete_missed_blocks(indexer_id, block_heights),
+        )
+        .await
+    }
+
+    async fn get_block_heights_without_block_info(&self, limit: u64) -> anyhow::Result<Vec<u64>> {
+        self.timed_read(
+            "get_block_heights_without_block_info",
+            self.db_manager.get_block_heights_without_block_info(limit),
+        )
+        .await
+    }
+
+    async fn update_transactions_block_info(
+        &self,
+        block_height: u64,
+        protocol_version: near_primitives::types::ProtocolVersion,
+        gas_price: near_primitives::types::Balance,
+    ) -> anyhow::Result<()> {
+        self.timed_write(
+            "update_transactions_block_info",
+            self.db_manager.update_transactions_block_info(
+                block_height,
+                protocol_version,
+                gas_price,
+            ),
+        )
+        .await
+    }
+
+    async fn sync_group_assignments(
+        &self,
+        group_id: &str,
+        indexer_id: &str,
+        lease_block_height: u64,
+        member_timeout_secs: u64,
+    ) -> anyhow::Result<Vec<readnode_primitives::GroupAssignment>> {
+        self.timed_write(
+            "sync_group_assignments",
+            self.db_manager.sync_group_assignments(
+                group_id,
+                indexer_id,
+                lease_block_height,
+                member_timeout_secs,
+            ),
+        )
+        .await
+    }
+
+    async fn get_block_data_counts(
+        &self,
+        from_block_height: u64,
+        to_block_height: u64,
+    ) -> anyhow::Result<std::collections::HashMap<u64, readnode_primitives::BlockDataCounts>> {
+        self.timed_read(
+            "get_block_data_counts",
+            self.db_manager
+                .get_block_data_counts(from_block_height, to_block_height),
+        )
+        .await
+    }
+
+    async fn update_meta(
+        &self,
+        indexer_id: &str,
+        block_height: u64,
+        block_timestamp: u64,
+    ) -> anyhow::Result<()> {
+        self.timed_write(
+            "update_meta",
+            self.db_manager
+                .update_meta(indexer_id, block_height, block_timestamp),
+        )
+        .await
+    }
+
+    async fn get_last_processed_block_height(&self, indexer_id: &str) -> anyhow::Result<u64> {
+        self.timed_read(
+            "get_last_processed_block_height",
+            self.db_manager.get_last_processed_block_height(indexer_id),
+        )
+        .await
+    }
+
+    async fn save_network_identity(
+        &self,
+        network: &readnode_primitives::NetworkIdentity,
+    ) -> anyhow::Result<readnode_primitives::NetworkIdentity> {
+        self.timed_write(
+            "save_network_identity",
+            self.db_manager.save_network_identity(network),
+        )
+        .await
+    }
+
+    async fn notify_finalized_transactions(
+        &self,
+        transaction_hashes: &[near_primitives::hash::CryptoHash],
+    ) -> anyhow::Result<()> {
+        self.timed_write(
+            "notify_finalized_transactions",
+            self.db_manager
+                .notify_finalized_transactions(transaction_hashes),
+        )
+        .await
+    }
+
+    async fn get_block_height_by_timestamp(&self, block_timestamp: u64) -> anyhow::Result<u64> {
+        self.timed_read(
+            "get_block_height_by_timestamp",
+            self.db_manager
+                .get_block_height_by_timestamp(block_timestamp),
+        )
+        .await
+    }
+
+    async fn get_transactions_to_prune(
+        &self,
+        before_block_height: u64,
+        limit: u64,
+    ) -> anyhow::Result<Vec<near_primitives::hash::CryptoHash>> {
+        self.timed_read(
+            "get_transactions_to_prune",
+            self.db_manager
+                .get_transactions_to_prune(before_block_height, limit),
+        )
+        .await
+    }
+
+    async fn prune_transactions(
+        &self,
+        before_block_height: u64,
+        transaction_hashes: &[near_primitives::hash::CryptoHash],
+    ) -> anyhow::Result<u64> {
+        self.timed_write(
+            "prune_transactions",
+            self.db_manager
+                .prune_transactions(before_block_height, transaction_hashes),
+        )
+        .await
+    }
+
+    async fn prune_receipts_and_outcomes(
+        &self,
+        before_block_height: u64,
+        limit: u64,
+    ) -> anyhow::Result<u64> {
+        self.timed_write(
+            "prune_receipts_and_outcomes",
+            self.db_manager
+                .prune_receipts_and_outcomes(before_block_height, limit),
+        )
+        .await
+    }
+
+    async fn prune_receipts_of_filtered_transactions(
+        &self,
+        from_block_height: u64,
+        to_block_height: u64,
+        tracked_accounts: &[readnode_primitives::indexer::types::AccountId],
+    ) -> anyhow::Result<u64> {
+        self.timed_write(
+            "prune_receipts_of_filtered_transactions",
+            self.db_manager.prune_receipts_of_filtered_transactions(
+                from_block_height,
+                to_block_height,
+                tracked_accounts,
+            ),
+        )
+        .await
+    }
+}
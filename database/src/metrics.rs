@@ -1,4 +1,4 @@
-use prometheus::{IntCounterVec, Opts};
+use prometheus::{Histogram, HistogramOpts, HistogramVec, IntCounterVec, IntGaugeVec, Opts};
 
 type Result<T, E> = std::result::Result<T, E>;
 
@@ -13,6 +13,60 @@ fn register_int_counter_vec(
     Ok(counter)
 }
 
+/// Registers the histogram with the buckets, shared by the services of the workspace
+pub fn register_histogram(
+    name: &str,
+    help: &str,
+    buckets: &[f64],
+) -> Result<Histogram, prometheus::Error> {
+    let opts = HistogramOpts::new(name, help).buckets(buckets.to_vec());
+    let histogram = Histogram::with_opts(opts)?;
+    prometheus::register(Box::new(histogram.clone()))?;
+    Ok(histogram)
+}
+
+/// Registers the histogram with the labels and the buckets, shared by the services of the workspace
+pub fn register_histogram_vec(
+    name: &str,
+    help: &str,
+    label_names: &[&str],
+    buckets: &[f64],
+) -> Result<HistogramVec, prometheus::Error> {
+    let opts = HistogramOpts::new(name, help).buckets(buckets.to_vec());
+    let histogram = HistogramVec::new(opts, label_names)?;
+    prometheus::register(Box::new(histogram.clone()))?;
+    Ok(histogram)
+}
+
+/// Buckets of the block latency histograms of the indexers and the rpc-server.
+/// Blocks are produced every second or so, the buckets cover the healthy
+/// few seconds behind the chain as well as the catching up after the incidents
+pub const BLOCK_LATENCY_BUCKETS: [f64; 14] = [
+    0.5, 1.0, 1.5, 2.0, 3.0, 4.0, 5.0, 7.5, 10.0, 15.0, 30.0, 60.0, 120.0, 300.0,
+];
+
+/// Seconds since the block timestamp in nanoseconds.
+/// The clocks of the block producers and the services are not synchronized,
+/// so the block from the future is zero seconds old
+pub fn seconds_since_block(block_timestamp: u64) -> f64 {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos() as u64;
+    now.saturating_sub(block_timestamp) as f64 / 1_000_000_000.0
+}
+
+fn register_int_gauge_vec(
+    name: &str,
+    help: &str,
+    label_names: &[&str],
+) -> Result<IntGaugeVec, prometheus::Error> {
+    let opts = Opts::new(name, help);
+    let gauge = IntGaugeVec::new(opts, label_names)?;
+    prometheus::register(Box::new(gauge.clone()))?;
+    Ok(gauge)
+}
+
 lazy_static! {
     pub(crate) static ref SHARD_DATABASE_READ_QUERIES: IntCounterVec = register_int_counter_vec(
         "shard_database_read_queries_counter",
@@ -32,10 +86,122 @@ lazy_static! {
         &["method_name", "table_name"]
     )
     .unwrap();
+    pub(crate) static ref SKIPPED_UNCHANGED_WRITES: IntCounterVec = register_int_counter_vec(
+        "skipped_unchanged_writes_counter",
+        "Total number of rows not written because the value is the same as the latest stored one by shard_id and table_name",
+        &["shard_id", "table_name"]
+    )
+    .unwrap();
+    pub(crate) static ref LOCAL_REPLICA_READ_FALLBACKS: IntCounterVec = register_int_counter_vec(
+        "local_replica_read_fallbacks_counter",
+        "Total number of reads not found on the preferred datacenter replicas and repeated on the shards databases by method_name and table_name",
+        &["method_name", "table_name"]
+    )
+    .unwrap();
     pub(crate) static ref META_DATABASE_WRITE_QUERIES: IntCounterVec = register_int_counter_vec(
         "meta_database_write_queries_counter",
         "Total number of meta database write queries by method_name and table_name",
         &["method_name", "table_name"]
     )
     .unwrap();
+    pub(crate) static ref CLICKHOUSE_READ_QUERIES: IntCounterVec = register_int_counter_vec(
+        "clickhouse_read_queries_counter",
+        "Total number of ClickHouse read queries by method_name and table_name",
+        &["method_name", "table_name"]
+    )
+    .unwrap();
+    pub(crate) static ref CLICKHOUSE_WRITE_QUERIES: IntCounterVec = register_int_counter_vec(
+        "clickhouse_write_queries_counter",
+        "Total number of ClickHouse write queries by method_name and table_name",
+        &["method_name", "table_name"]
+    )
+    .unwrap();
+    pub(crate) static ref REDIS_CACHE_REQUESTS: IntCounterVec = register_int_counter_vec(
+        "database_redis_cache_requests_counter",
+        "Total number of reads through the redis cache of the reader database by method_name and result (hit, miss or error)",
+        &["method_name", "result"]
+    )
+    .unwrap();
+    pub(crate) static ref DATABASE_READ_DURATION: HistogramVec = register_histogram_vec(
+        "database_read_duration_seconds",
+        "Duration of the database reads in seconds by backend and db_method_name",
+        &["backend", "db_method_name"],
+        prometheus::DEFAULT_BUCKETS
+    )
+    .unwrap();
+    pub(crate) static ref DATABASE_READ_ERRORS: IntCounterVec = register_int_counter_vec(
+        "database_read_errors_counter",
+        "Total number of the failed database reads by backend and db_method_name",
+        &["backend", "db_method_name"]
+    )
+    .unwrap();
+    pub(crate) static ref DATABASE_WRITE_DURATION: HistogramVec = register_histogram_vec(
+        "database_write_duration_seconds",
+        "Duration of the indexer database writes in seconds by backend and db_method_name",
+        &["backend", "db_method_name"],
+        prometheus::DEFAULT_BUCKETS
+    )
+    .unwrap();
+    pub(crate) static ref DATABASE_WRITE_ERRORS: IntCounterVec = register_int_counter_vec(
+        "database_write_errors_counter",
+        "Total number of the failed indexer database writes by backend and db_method_name",
+        &["backend", "db_method_name"]
+    )
+    .unwrap();
+    pub(crate) static ref RETENTION_PRUNED_ROWS: IntCounterVec = register_int_counter_vec(
+        "retention_pruned_rows_counter",
+        "Total number of rows deleted by the retention policy by table_name",
+        &["table_name"]
+    )
+    .unwrap();
+    pub(crate) static ref FILTERED_PRUNED_ROWS: IntCounterVec = register_int_counter_vec(
+        "filtered_pruned_rows_counter",
+        "Total number of receipts and outcomes of the filtered out transactions deleted by table_name",
+        &["table_name"]
+    )
+    .unwrap();
+    pub(crate) static ref SCHEDULER_JOB_RUNS: IntCounterVec = register_int_counter_vec(
+        "scheduler_job_runs_counter",
+        "Total number of scheduled job runs by job_name and status (success or failure)",
+        &["job_name", "status"]
+    )
+    .unwrap();
+    pub(crate) static ref SCHEDULER_JOB_DURATION: HistogramVec = register_histogram_vec(
+        "scheduler_job_duration_seconds",
+        "Duration of the scheduled job runs in seconds by job_name",
+        &["job_name"],
+        prometheus::DEFAULT_BUCKETS
+    )
+    .unwrap();
+    pub(crate) static ref SCHEDULER_JOB_LAST_SUCCESS: IntGaugeVec = register_int_gauge_vec(
+        "scheduler_job_last_success_timestamp_seconds",
+        "Unix timestamp of the last successful run of the scheduled job by job_name",
+        &["job_name"]
+    )
+    .unwrap();
+}
+
+/// Returns all the metrics registered by the database crate.
+/// Used by the binaries to describe the exported metrics.
+pub fn collectors() -> Vec<&'static dyn prometheus::core::Collector> {
+    vec![
+        &*SHARD_DATABASE_READ_QUERIES,
+        &*SHARD_DATABASE_WRITE_QUERIES,
+        &*META_DATABASE_READ_QUERIES,
+        &*META_DATABASE_WRITE_QUERIES,
+        &*CLICKHOUSE_READ_QUERIES,
+        &*CLICKHOUSE_WRITE_QUERIES,
+        &*LOCAL_REPLICA_READ_FALLBACKS,
+        &*SKIPPED_UNCHANGED_WRITES,
+        &*REDIS_CACHE_REQUESTS,
+        &*DATABASE_READ_DURATION,
+        &*DATABASE_READ_ERRORS,
+        &*DATABASE_WRITE_DURATION,
+        &*DATABASE_WRITE_ERRORS,
+        &*RETENTION_PRUNED_ROWS,
+        &*FILTERED_PRUNED_ROWS,
+        &*SCHEDULER_JOB_RUNS,
+        &*SCHEDULER_JOB_DURATION,
+        &*SCHEDULER_JOB_LAST_SUCCESS,
+    ]
 }
@@ -0,0 +1,4 @@
+-- Add down migration script here
+DROP INDEX IF EXISTS blocks_block_timestamp_idx;
+
+ALTER TABLE blocks DROP COLUMN IF EXISTS block_timestamp;
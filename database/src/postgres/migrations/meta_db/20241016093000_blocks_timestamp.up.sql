@@ -0,0 +1,6 @@
+-- Add up migration script here
+
+-- Store block timestamp (nanoseconds) to be able to find block height by time
+ALTER TABLE blocks ADD COLUMN IF NOT EXISTS block_timestamp numeric(20,0) NULL;
+
+CREATE INDEX IF NOT EXISTS blocks_block_timestamp_idx ON blocks (block_timestamp);
@@ -0,0 +1,25 @@
+-- Add up migration script here
+
+-- Create transactions_by_public_key table
+-- Used to search transactions signed by the given public key
+CREATE TABLE IF NOT EXISTS transactions_by_public_key (
+    public_key text NOT NULL,
+    block_height numeric(20,0) NOT NULL,
+    transaction_hash text NOT NULL,
+    signer_id text NOT NULL,
+    receiver_id text NOT NULL,
+    nonce numeric(20,0) NOT NULL,
+    block_hash text NOT NULL,
+    shard_id numeric(20,0) NOT NULL,
+    PRIMARY KEY (public_key, block_height, transaction_hash)
+) PARTITION BY HASH (public_key);
+
+-- Create transactions_by_public_key partitions
+DO $$
+DECLARE
+    i INT;
+BEGIN
+    FOR i IN 0..99 LOOP
+        EXECUTE format('CREATE TABLE IF NOT EXISTS transactions_by_public_key_%s PARTITION OF transactions_by_public_key FOR VALUES WITH (MODULUS 100, REMAINDER %s)', i, i);
+    END LOOP;
+END $$;
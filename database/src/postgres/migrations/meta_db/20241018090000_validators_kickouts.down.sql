@@ -0,0 +1,5 @@
+-- Add down migration script here
+DROP INDEX IF EXISTS validators_epoch_height_idx;
+
+ALTER TABLE validators DROP COLUMN IF EXISTS stake_changes;
+ALTER TABLE validators DROP COLUMN IF EXISTS prev_epoch_kickout;
@@ -0,0 +1,12 @@
+-- Add up migration script here
+
+-- Store validators kickouts and stake changes for each epoch separately
+-- to render the history without reading the full validators info
+ALTER TABLE validators ADD COLUMN IF NOT EXISTS prev_epoch_kickout jsonb NULL;
+ALTER TABLE validators ADD COLUMN IF NOT EXISTS stake_changes jsonb NULL;
+
+-- Backfill kickouts from the already stored validators info.
+-- Stake changes of the existing epochs are calculated on read
+UPDATE validators SET prev_epoch_kickout = validators_info->'prev_epoch_kickout' WHERE prev_epoch_kickout IS NULL;
+
+CREATE INDEX IF NOT EXISTS validators_epoch_height_idx ON validators (epoch_height);
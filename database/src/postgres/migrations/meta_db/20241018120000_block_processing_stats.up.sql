@@ -0,0 +1,19 @@
+-- Add up migration script here
+
+-- Create block_processing_stats table to store the per-block processing report of the tx-indexer
+-- Optional, enabled by the `save_block_processing_stats` tx-indexer setting
+CREATE TABLE IF NOT EXISTS block_processing_stats (
+    indexer_id text NOT NULL,
+    block_height numeric(20,0) NOT NULL,
+    block_hash text NOT NULL,
+    txs_started numeric(20,0) NOT NULL,
+    receipts_matched numeric(20,0) NOT NULL,
+    outcomes_matched numeric(20,0) NOT NULL,
+    txs_finalized numeric(20,0) NOT NULL,
+    collect_duration_ms numeric(20,0) NOT NULL,
+    save_tx_details_duration_ms numeric(20,0) NOT NULL,
+    save_outcomes_and_receipts_duration_ms numeric(20,0) NOT NULL,
+    save_transactions_by_public_key_duration_ms numeric(20,0) NOT NULL,
+    processed_at timestamptz NOT NULL DEFAULT now(),
+    PRIMARY KEY (indexer_id, block_height)
+);
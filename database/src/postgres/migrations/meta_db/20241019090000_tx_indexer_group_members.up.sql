@@ -0,0 +1,11 @@
+-- Add up migration script here
+
+-- Create tx_indexer_group_members table to track the alive tx-indexer instances of the group
+-- Instances of the same group share the blocks by `block_height % N`
+-- where N is the number of the alive members sorted by indexer_id
+CREATE TABLE IF NOT EXISTS tx_indexer_group_members (
+    group_id text NOT NULL,
+    indexer_id text NOT NULL,
+    last_heartbeat timestamptz NOT NULL DEFAULT now(),
+    PRIMARY KEY (group_id, indexer_id)
+);
@@ -0,0 +1,4 @@
+-- Add down migration script here
+DROP INDEX IF EXISTS transactions_by_public_key_without_block_info_idx;
+ALTER TABLE transactions_by_public_key DROP COLUMN IF EXISTS gas_price;
+ALTER TABLE transactions_by_public_key DROP COLUMN IF EXISTS protocol_version;
@@ -0,0 +1,11 @@
+-- Add up migration script here
+
+-- Store the protocol version and the gas price of the block alongside each transaction
+-- NULL for the transactions indexed before, they are filled by the tx-indexer `--backfill-block-info` option
+ALTER TABLE transactions_by_public_key ADD COLUMN IF NOT EXISTS protocol_version numeric(20,0);
+ALTER TABLE transactions_by_public_key ADD COLUMN IF NOT EXISTS gas_price numeric(40,0);
+
+-- Index the transactions waiting for the backfill
+CREATE INDEX IF NOT EXISTS transactions_by_public_key_without_block_info_idx
+    ON transactions_by_public_key (block_height)
+    WHERE protocol_version IS NULL;
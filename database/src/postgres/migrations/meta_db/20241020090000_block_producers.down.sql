@@ -0,0 +1,3 @@
+-- Add down migration script here
+DROP TABLE IF EXISTS chunk_producers;
+DROP TABLE IF EXISTS block_producers;
@@ -0,0 +1,23 @@
+-- Add up migration script here
+
+-- Store the producers of the blocks and of the chunks included in the blocks for the first time
+-- to let the validators check their production record of the epoch
+CREATE TABLE IF NOT EXISTS block_producers (
+    block_height numeric(20,0) NOT NULL PRIMARY KEY,
+    block_hash text NOT NULL,
+    epoch_id text NOT NULL,
+    block_producer text NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS block_producers_producer_epoch_idx ON block_producers (block_producer, epoch_id);
+
+CREATE TABLE IF NOT EXISTS chunk_producers (
+    block_height numeric(20,0) NOT NULL,
+    shard_id numeric(20,0) NOT NULL,
+    chunk_hash text NOT NULL,
+    epoch_id text NOT NULL,
+    chunk_producer text NOT NULL,
+    PRIMARY KEY (block_height, shard_id)
+);
+
+CREATE INDEX IF NOT EXISTS chunk_producers_producer_epoch_idx ON chunk_producers (chunk_producer, epoch_id);
@@ -0,0 +1,17 @@
+-- Add up migration script here
+
+-- Create block_repairs table to audit the repairs of the data missing in the indexed blocks
+-- The missing transactions and receipts are re-collected by the tx-indexer from the archival RPC
+CREATE TABLE IF NOT EXISTS block_repairs (
+    id bigserial PRIMARY KEY,
+    indexer_id text NOT NULL,
+    block_height numeric(20,0) NOT NULL,
+    block_hash text NOT NULL,
+    reason text NOT NULL,
+    transactions numeric(20,0) NOT NULL,
+    receipts numeric(20,0) NOT NULL,
+    error text,
+    repaired_at timestamptz NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS block_repairs_block_height_idx ON block_repairs (block_height);
@@ -0,0 +1,26 @@
+-- Add up migration script here
+
+-- pg_trgm provides the GIN operator class to search the arguments by a substring
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+-- Create function_calls table
+-- Used to search the function calls to the contracts listed in the `[function_calls_search]`
+-- config section by the method name and a substring of the decoded arguments
+-- The table is filled only if the search is enabled in the tx-indexer
+CREATE TABLE IF NOT EXISTS function_calls (
+    receipt_id text NOT NULL,
+    action_index integer NOT NULL,
+    block_height numeric(20,0) NOT NULL,
+    block_hash text NOT NULL,
+    predecessor_id text NOT NULL,
+    signer_id text NOT NULL,
+    receiver_id text NOT NULL,
+    method_name text NOT NULL,
+    args text,
+    msg text,
+    PRIMARY KEY (receipt_id, action_index)
+);
+
+CREATE INDEX IF NOT EXISTS function_calls_receiver_id_idx ON function_calls (receiver_id, method_name, block_height);
+CREATE INDEX IF NOT EXISTS function_calls_args_idx ON function_calls USING GIN (args gin_trgm_ops);
+CREATE INDEX IF NOT EXISTS function_calls_msg_idx ON function_calls USING GIN (msg gin_trgm_ops);
@@ -0,0 +1,3 @@
+-- Add down migration script here
+ALTER TABLE meta DROP COLUMN IF EXISTS last_processed_block_timestamp;
+ALTER TABLE meta DROP COLUMN IF EXISTS indexer_type;
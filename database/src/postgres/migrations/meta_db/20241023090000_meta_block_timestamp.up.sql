@@ -0,0 +1,6 @@
+-- Add up migration script here
+
+-- Store the type of the indexer ("tx" or "state") and the timestamp (nanoseconds)
+-- of the last processed block to export the staleness of the indexed data
+ALTER TABLE meta ADD COLUMN IF NOT EXISTS indexer_type text NULL;
+ALTER TABLE meta ADD COLUMN IF NOT EXISTS last_processed_block_timestamp numeric(20,0) NULL;
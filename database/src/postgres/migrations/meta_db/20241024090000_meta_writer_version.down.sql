@@ -0,0 +1,3 @@
+-- Add down migration script here
+ALTER TABLE meta DROP COLUMN IF EXISTS schema_version;
+ALTER TABLE meta DROP COLUMN IF EXISTS crate_version;
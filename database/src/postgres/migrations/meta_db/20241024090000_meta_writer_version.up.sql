@@ -0,0 +1,6 @@
+-- Add up migration script here
+
+-- Store the crate version and the schema version of the indexer writing the data
+-- The rpc-server checks the schema version is not newer than it understands
+ALTER TABLE meta ADD COLUMN IF NOT EXISTS crate_version text NULL;
+ALTER TABLE meta ADD COLUMN IF NOT EXISTS schema_version integer NULL;
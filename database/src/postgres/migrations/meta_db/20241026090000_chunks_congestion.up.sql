@@ -0,0 +1,11 @@
+-- Add up migration script here
+
+-- Store the congestion info of the chunk headers by the block and the shard
+-- to serve the congestion level of the shards without fetching the blocks
+CREATE TABLE IF NOT EXISTS chunks_congestion (
+    block_height numeric(20,0) NOT NULL,
+    shard_id numeric(20,0) NOT NULL,
+    height_included numeric(20,0) NOT NULL,
+    congestion_info jsonb NOT NULL,
+    PRIMARY KEY (block_height, shard_id)
+);
@@ -0,0 +1,25 @@
+-- Add up migration script here
+
+-- Create actions table
+-- The actions of the executed receipts exploded to the columns to query them
+-- without decoding the receipts: function calls by the method name with the attached
+-- deposit and gas, transfers amounts and access keys added or deleted
+-- The table is filled only if `index_actions` is enabled in the tx-indexer
+CREATE TABLE IF NOT EXISTS actions (
+    receipt_id text NOT NULL,
+    action_index integer NOT NULL,
+    block_height numeric(20,0) NOT NULL,
+    block_hash text NOT NULL,
+    predecessor_id text NOT NULL,
+    signer_id text NOT NULL,
+    receiver_id text NOT NULL,
+    action_kind text NOT NULL,
+    method_name text,
+    deposit numeric(40,0),
+    gas numeric(20,0),
+    public_key text,
+    PRIMARY KEY (receipt_id, action_index)
+);
+
+CREATE INDEX IF NOT EXISTS actions_receiver_id_method_name_idx ON actions (receiver_id, method_name, block_height);
+CREATE INDEX IF NOT EXISTS actions_receiver_id_action_kind_idx ON actions (receiver_id, action_kind, block_height);
@@ -0,0 +1,2 @@
+-- Add down migration script here
+DROP INDEX IF EXISTS transactions_by_public_key_block_height_idx;
@@ -0,0 +1,6 @@
+-- Add up migration script here
+
+-- Index the transactions by the block height
+-- to prune the transactions older than the retention period
+CREATE INDEX IF NOT EXISTS transactions_by_public_key_block_height_idx
+    ON transactions_by_public_key (block_height);
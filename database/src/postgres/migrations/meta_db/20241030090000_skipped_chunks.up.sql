@@ -0,0 +1,11 @@
+-- Add up migration script here
+
+-- Mark the chunks of the block headers missing in the indexed shards of the block
+-- (the chunk producer was offline or the chunk was not available to the lake indexer),
+-- so the block is known to be indexed partially and the chunk is reported missing
+CREATE TABLE IF NOT EXISTS skipped_chunks (
+    block_height numeric(20,0) NOT NULL,
+    shard_id numeric(20,0) NOT NULL,
+    chunk_hash text NOT NULL,
+    PRIMARY KEY (block_height, shard_id)
+);
@@ -0,0 +1,3 @@
+-- Add down migration script here
+DROP TABLE IF EXISTS state_growth_days;
+DROP TABLE IF EXISTS state_growth;
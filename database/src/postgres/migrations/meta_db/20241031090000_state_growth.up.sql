@@ -0,0 +1,18 @@
+-- Add up migration script here
+
+-- Daily net growth of the contract state of the accounts by the shard,
+-- aggregated from the contract data changes by the state-indexer analytics job
+CREATE TABLE IF NOT EXISTS state_growth (
+    day bigint NOT NULL,
+    account_id text NOT NULL,
+    shard_id numeric(20,0) NOT NULL,
+    keys_delta bigint NOT NULL,
+    bytes_delta bigint NOT NULL,
+    PRIMARY KEY (day, account_id, shard_id)
+);
+
+-- Days aggregated by the job, the days without the state changes are recorded as well
+CREATE TABLE IF NOT EXISTS state_growth_days (
+    day bigint NOT NULL PRIMARY KEY,
+    accounts_count bigint NOT NULL
+);
@@ -0,0 +1,10 @@
+-- Add up migration script here
+
+-- Network the data belongs to, recorded by the first indexer writing to the database.
+-- The single row table, the components connected to another network refuse to start
+CREATE TABLE IF NOT EXISTS network (
+    id boolean NOT NULL PRIMARY KEY DEFAULT true CHECK (id),
+    chain_id text NOT NULL,
+    genesis_hash text NOT NULL,
+    created_at timestamp NOT NULL DEFAULT now()
+);
@@ -0,0 +1,12 @@
+-- Add up migration script here
+
+-- Blocks the indexer failed to process, kept next to the `last_processed_block_height` of the meta table.
+-- The indexer moves on after the failed block, the records are deleted once the blocks are repaired
+CREATE TABLE IF NOT EXISTS missed_blocks (
+    indexer_id text NOT NULL,
+    block_height numeric(20,0) NOT NULL,
+    error text NOT NULL,
+    attempts numeric(20,0) NOT NULL DEFAULT 1,
+    missed_at timestamptz NOT NULL DEFAULT now(),
+    PRIMARY KEY (indexer_id, block_height)
+);
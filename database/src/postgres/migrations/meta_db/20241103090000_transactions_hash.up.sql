@@ -0,0 +1,6 @@
+-- Add up migration script here
+
+-- Index the transactions by the hash to look up the parent transactions
+-- of the receipts pruned for the filtered out accounts
+CREATE INDEX IF NOT EXISTS transactions_by_public_key_transaction_hash_idx
+    ON transactions_by_public_key (transaction_hash);
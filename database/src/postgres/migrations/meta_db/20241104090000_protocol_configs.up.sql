@@ -0,0 +1,13 @@
+-- Add up migration script here
+
+-- Protocol config each epoch runs with, stored by the epoch-indexer at the epoch start.
+-- The config is the same for all the blocks of the epoch
+CREATE TABLE IF NOT EXISTS protocol_configs (
+    epoch_id text NOT NULL PRIMARY KEY,
+    epoch_height numeric(20,0) NOT NULL,
+    epoch_start_height numeric(20,0) NOT NULL,
+    protocol_version numeric(10,0) NOT NULL,
+    protocol_config jsonb NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS protocol_configs_epoch_start_height_idx ON protocol_configs (epoch_start_height);
@@ -0,0 +1,3 @@
+-- Add down migration script here
+DROP TABLE IF EXISTS tx_indexer_group_assignments;
+ALTER TABLE tx_indexer_group_members DROP COLUMN IF EXISTS lease_block_height;
@@ -0,0 +1,15 @@
+-- Add up migration script here
+
+-- The height up to which the member decided the claims of the blocks with the assignments it knows,
+-- the new assignment starts after the leases of all the members
+ALTER TABLE tx_indexer_group_members ADD COLUMN IF NOT EXISTS lease_block_height numeric(20,0) NOT NULL DEFAULT 0;
+
+-- Create tx_indexer_group_assignments table with the members sharing the blocks of the group
+-- by `block_height % N` starting from `from_block_height` until the next assignment.
+-- The assignment changes only at the agreed height, so every member claims the same blocks
+CREATE TABLE IF NOT EXISTS tx_indexer_group_assignments (
+    group_id text NOT NULL,
+    from_block_height numeric(20,0) NOT NULL,
+    members text[] NOT NULL,
+    PRIMARY KEY (group_id, from_block_height)
+);
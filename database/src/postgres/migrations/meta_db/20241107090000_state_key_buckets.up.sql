@@ -0,0 +1,10 @@
+-- Add up migration script here
+
+-- Number of the buckets the account state is split into, recorded by the first state-indexer.
+-- The single row table, the components configured with another `state_key_buckets`
+-- refuse to start, since they would look the state keys up in the other buckets
+CREATE TABLE IF NOT EXISTS state_key_buckets (
+    id boolean NOT NULL PRIMARY KEY DEFAULT true CHECK (id),
+    buckets integer NOT NULL,
+    created_at timestamp NOT NULL DEFAULT now()
+);
@@ -0,0 +1,3 @@
+-- Add down migration script here
+ALTER TABLE outcomes_map DROP COLUMN IF EXISTS index_in_chunk;
+ALTER TABLE receipts_map DROP COLUMN IF EXISTS index_in_chunk;
@@ -0,0 +1,7 @@
+-- Add up migration script here
+
+-- Store the position of the receipt execution outcome in the chunk
+-- to return the receipts and outcomes in the on-chain order
+-- The records stored before have NULL positions
+ALTER TABLE receipts_map ADD COLUMN IF NOT EXISTS index_in_chunk integer NULL;
+ALTER TABLE outcomes_map ADD COLUMN IF NOT EXISTS index_in_chunk integer NULL;
@@ -0,0 +1,5 @@
+-- Add down migration script here
+DROP INDEX IF EXISTS state_changes_data_block_height_idx;
+DROP INDEX IF EXISTS state_changes_access_key_block_height_idx;
+DROP INDEX IF EXISTS state_changes_contract_block_height_idx;
+DROP INDEX IF EXISTS state_changes_account_block_height_idx;
@@ -0,0 +1,8 @@
+-- Add up migration script here
+
+-- Index the state changes by the block height
+-- to read all the changes of the block for EXPERIMENTAL_changes_in_block
+CREATE INDEX IF NOT EXISTS state_changes_data_block_height_idx ON state_changes_data (block_height);
+CREATE INDEX IF NOT EXISTS state_changes_access_key_block_height_idx ON state_changes_access_key (block_height);
+CREATE INDEX IF NOT EXISTS state_changes_contract_block_height_idx ON state_changes_contract (block_height);
+CREATE INDEX IF NOT EXISTS state_changes_account_block_height_idx ON state_changes_account (block_height);
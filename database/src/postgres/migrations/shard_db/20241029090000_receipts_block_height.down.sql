@@ -0,0 +1,3 @@
+-- Add down migration script here
+DROP INDEX IF EXISTS receipts_map_block_height_idx;
+DROP INDEX IF EXISTS outcomes_map_block_height_idx;
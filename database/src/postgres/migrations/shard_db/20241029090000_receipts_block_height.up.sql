@@ -0,0 +1,6 @@
+-- Add up migration script here
+
+-- Index the receipts and outcomes by the block height
+-- to prune the records older than the retention period
+CREATE INDEX IF NOT EXISTS receipts_map_block_height_idx ON receipts_map (block_height);
+CREATE INDEX IF NOT EXISTS outcomes_map_block_height_idx ON outcomes_map (block_height);
@@ -0,0 +1,2 @@
+-- Add down migration script here
+ALTER TABLE receipts_map DROP COLUMN IF EXISTS receipt;
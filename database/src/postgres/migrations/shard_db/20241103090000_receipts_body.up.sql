@@ -0,0 +1,5 @@
+-- Add up migration script here
+
+-- Store the borsh serialized `ReceiptView` to serve the receipt without the transaction details
+-- The records stored before and the receipts mapped before their execution have NULL bodies
+ALTER TABLE receipts_map ADD COLUMN IF NOT EXISTS receipt bytea NULL;
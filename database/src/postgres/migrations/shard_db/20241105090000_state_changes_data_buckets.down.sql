@@ -0,0 +1,3 @@
+-- Add down migration script here
+DROP VIEW IF EXISTS state_changes_data_all;
+DROP TABLE IF EXISTS state_changes_data_buckets;
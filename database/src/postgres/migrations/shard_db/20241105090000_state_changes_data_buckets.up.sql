@@ -0,0 +1,35 @@
+-- Add up migration script here
+
+-- Create state_changes_data_buckets table
+-- The state of the account is split into the buckets by the hash of the key,
+-- so the state of the wide contracts is spread across the partitions
+CREATE TABLE IF NOT EXISTS state_changes_data_buckets (
+    account_id text NOT NULL,
+    key_bucket integer NOT NULL,
+    block_height numeric(20,0) NOT NULL,
+    block_hash text NOT NULL,
+    data_key text NOT NULL,
+    data_value bytea NULL,
+    PRIMARY KEY (account_id, key_bucket, data_key, block_height)
+) PARTITION BY HASH (account_id, key_bucket);
+
+-- Create state_changes_data_buckets partitions
+DO $$
+DECLARE
+    i INT;
+BEGIN
+    FOR i IN 0..99 LOOP
+        EXECUTE format('CREATE TABLE IF NOT EXISTS state_changes_data_buckets_%s PARTITION OF state_changes_data_buckets FOR VALUES WITH (MODULUS 100, REMAINDER %s)', i, i);
+    END LOOP;
+END $$;
+
+CREATE INDEX IF NOT EXISTS state_changes_data_buckets_block_height_idx ON state_changes_data_buckets (block_height);
+
+-- The state written before the buckets were enabled stays in state_changes_data
+-- until the account is rebalanced, the account-wide reads go through the view of both tables
+CREATE OR REPLACE VIEW state_changes_data_all AS
+    SELECT account_id, block_height, block_hash, data_key, data_value
+    FROM state_changes_data_buckets
+    UNION ALL
+    SELECT account_id, block_height, block_hash, data_key, data_value
+    FROM state_changes_data;
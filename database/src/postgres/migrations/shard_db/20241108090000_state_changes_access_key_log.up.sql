@@ -0,0 +1,13 @@
+-- Add up migration script here
+
+-- Create state_changes_access_key_log table
+-- The access key changes with the value equal to the stored one are not written
+-- to state_changes_access_key, the keys of all the changes of the block are logged here
+-- so EXPERIMENTAL_changes and EXPERIMENTAL_changes_in_block return every change.
+-- The value of the logged change is the latest stored value at or before the block
+CREATE TABLE IF NOT EXISTS state_changes_access_key_log (
+    block_height numeric(20,0) NOT NULL,
+    account_id text NOT NULL,
+    data_key text NOT NULL,
+    PRIMARY KEY (block_height, account_id, data_key)
+);
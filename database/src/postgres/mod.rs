@@ -1,32 +1,83 @@
+use std::str::FromStr;
+
+mod notifications;
 mod rpc_server;
 mod state_indexer;
 mod tx_indexer;
+mod types;
 
 static META_DB_MIGRATOR: sqlx::migrate::Migrator =
     sqlx::migrate!("src/postgres/migrations/meta_db");
 static SHARD_DB_MIGRATOR: sqlx::migrate::Migrator =
     sqlx::migrate!("src/postgres/migrations/shard_db");
 
+// Block timestamps are stored in nanoseconds
+const NANOSECONDS_PER_DAY: u64 = 24 * 60 * 60 * 1_000_000_000;
+
+/// Cursor to continue the search of transactions by public key
+/// from the last returned transaction
 #[derive(borsh::BorshSerialize, borsh::BorshDeserialize, Clone, Debug)]
-struct PageState {
-    pub page_size: i64,
-    pub offset: i64,
+struct TransactionsCursor {
+    pub block_height: u64,
+    pub transaction_hash: String,
 }
 
-impl PageState {
-    fn new(page_size: i64) -> Self {
-        Self {
-            page_size,
-            offset: 0,
-        }
-    }
+impl pagination_cursor::Cursor for TransactionsCursor {
+    const KIND: &'static str = "transactions_by_public_key";
+}
 
-    fn next_page(&self) -> Self {
-        Self {
-            page_size: self.page_size,
-            offset: self.offset + self.page_size,
-        }
-    }
+/// Cursor to continue the search of function calls from the last returned call
+#[derive(borsh::BorshSerialize, borsh::BorshDeserialize, Clone, Debug)]
+struct FunctionCallsCursor {
+    pub block_height: u64,
+    pub receipt_id: String,
+    pub action_index: u32,
+}
+
+impl pagination_cursor::Cursor for FunctionCallsCursor {
+    const KIND: &'static str = "function_calls";
+}
+
+/// Cursor to continue the actions of the contract method from the last returned action
+#[derive(borsh::BorshSerialize, borsh::BorshDeserialize, Clone, Debug)]
+struct ActionsCursor {
+    pub block_height: u64,
+    pub receipt_id: String,
+    pub action_index: u32,
+}
+
+impl pagination_cursor::Cursor for ActionsCursor {
+    const KIND: &'static str = "actions";
+}
+
+/// Cursor to continue the state diff from the last returned state key
+#[derive(borsh::BorshSerialize, borsh::BorshDeserialize, Clone, Debug)]
+struct StateDiffCursor {
+    pub data_key: String,
+}
+
+impl pagination_cursor::Cursor for StateDiffCursor {
+    const KIND: &'static str = "state_diff";
+}
+
+/// Cursor to continue the state keys after the last returned key
+#[derive(borsh::BorshSerialize, borsh::BorshDeserialize, Clone, Debug)]
+struct StateKeysCursor {
+    pub data_key: String,
+}
+
+impl pagination_cursor::Cursor for StateKeysCursor {
+    const KIND: &'static str = "state_keys";
+}
+
+/// Cursor to continue the validators changes from the epoch before the last returned one
+#[derive(borsh::BorshSerialize, borsh::BorshDeserialize, Clone, Debug)]
+struct ValidatorsChangesCursor {
+    pub epoch_height: u64,
+}
+
+impl pagination_cursor::Cursor for ValidatorsChangesCursor {
+    const KIND: &'static str = "validators_changes";
 }
 
 pub struct ShardIdPool<'a> {
@@ -39,37 +90,138 @@ pub struct PostgresDBManager {
     shards_pool:
         std::collections::HashMap<near_primitives::types::ShardId, sqlx::Pool<sqlx::Postgres>>,
     meta_db_pool: sqlx::Pool<sqlx::Postgres>,
+    // Shards replicas in the preferred datacenter, used only for the cache-like reads
+    local_replicas_pool:
+        std::collections::HashMap<near_primitives::types::ShardId, sqlx::Pool<sqlx::Postgres>>,
+    cursor_signer: pagination_cursor::CursorSigner,
+    // Number of the buckets the state of the account is split into, 0 if the buckets are disabled
+    state_key_buckets: u32,
 }
 
 impl PostgresDBManager {
-    async fn create_meta_db_pool(
+    async fn create_pool(
+        config: &configuration::DatabaseConfig,
         database_url: &str,
-        read_only: bool,
-        max_connections: u32,
+        migrator: &sqlx::migrate::Migrator,
     ) -> anyhow::Result<sqlx::Pool<sqlx::Postgres>> {
+        if !config.read_only {
+            // Migrations can be applied by a separate user with DDL privileges,
+            // so the indexer user requires only privileges to write the data
+            if let Some(migrations_credentials) = &config.migrations_credentials {
+                let migrations_pool = sqlx::postgres::PgPoolOptions::new()
+                    .max_connections(1)
+                    .connect_with(Self::connect_options(
+                        config,
+                        &migrations_credentials.apply_to_url(database_url)?,
+                    )?)
+                    .await?;
+                Self::run_migrations(migrator, &migrations_pool).await?;
+                migrations_pool.close().await;
+            }
+        }
+
+        let component_database_url = match config.component_credentials() {
+            Some(credentials) => credentials.apply_to_url(database_url)?,
+            None => database_url.to_string(),
+        };
         let pool = sqlx::postgres::PgPoolOptions::new()
-            .max_connections(max_connections)
-            .connect(database_url)
+            .max_connections(config.max_connections)
+            .connect_with(Self::connect_options(config, &component_database_url)?)
             .await?;
-        if !read_only {
-            Self::run_migrations(&META_DB_MIGRATOR, &pool).await?;
+        if !config.read_only && config.migrations_credentials.is_none() {
+            Self::run_migrations(migrator, &pool).await?;
         }
+        Self::verify_privileges(&pool, config.read_only).await?;
         Ok(pool)
     }
 
-    async fn create_shard_db_pool(
+    // Apply the TLS settings from the config on top of the database url parameters
+    fn connect_options(
+        config: &configuration::DatabaseConfig,
         database_url: &str,
+    ) -> anyhow::Result<sqlx::postgres::PgConnectOptions> {
+        let mut options = sqlx::postgres::PgConnectOptions::from_str(database_url)?;
+        if let Some(ssl_mode) = &config.tls.ssl_mode {
+            options = options.ssl_mode(
+                sqlx::postgres::PgSslMode::from_str(ssl_mode)
+                    .map_err(|err| anyhow::anyhow!("Invalid database ssl_mode: {}", err))?,
+            );
+        }
+        if let Some(root_cert_path) = &config.tls.root_cert_path {
+            options = options.ssl_root_cert(root_cert_path);
+        }
+        if let Some(client_cert_path) = &config.tls.client_cert_path {
+            options = options.ssl_client_cert(client_cert_path);
+        }
+        if let Some(client_key_path) = &config.tls.client_key_path {
+            options = options.ssl_client_key(client_key_path);
+        }
+        Ok(options)
+    }
+
+    // Check the database user has all privileges required by the component.
+    // rpc-server requires only SELECT, indexers require SELECT, INSERT, UPDATE and DELETE.
+    // The indexers delete the repaired missed blocks, the pruned transactions and the state changes
+    // moved into the buckets.
+    async fn verify_privileges(
+        pool: &sqlx::Pool<sqlx::Postgres>,
         read_only: bool,
-        max_connections: u32,
-    ) -> anyhow::Result<sqlx::Pool<sqlx::Postgres>> {
-        let pool = sqlx::postgres::PgPoolOptions::new()
-            .max_connections(max_connections)
-            .connect(database_url)
+    ) -> anyhow::Result<()> {
+        let required_privileges: &[&str] = if read_only {
+            &["SELECT"]
+        } else {
+            &["SELECT", "INSERT", "UPDATE", "DELETE"]
+        };
+        for privilege in required_privileges {
+            let tables: Vec<(String,)> = sqlx::query_as(
+                "
+                SELECT c.relname::text
+                FROM pg_catalog.pg_class c
+                JOIN pg_catalog.pg_namespace n ON n.oid = c.relnamespace
+                WHERE n.nspname = current_schema()
+                    AND c.relkind IN ('r', 'p')
+                    AND NOT c.relispartition
+                    AND c.relname <> '_sqlx_migrations'
+                    AND NOT has_table_privilege(c.oid, $1);
+                ",
+            )
+            .bind(privilege)
+            .fetch_all(pool)
             .await?;
-        if !read_only {
-            Self::run_migrations(&SHARD_DB_MIGRATOR, &pool).await?;
+            if !tables.is_empty() {
+                anyhow::bail!(
+                    "Database user doesn't have {} privilege on tables: {}",
+                    privilege,
+                    tables
+                        .into_iter()
+                        .map(|(table,)| table)
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                );
+            }
         }
-        Ok(pool)
+        if read_only {
+            let (has_write_privileges,): (bool,) = sqlx::query_as(
+                "
+                SELECT EXISTS (
+                    SELECT 1
+                    FROM pg_catalog.pg_class c
+                    JOIN pg_catalog.pg_namespace n ON n.oid = c.relnamespace
+                    WHERE n.nspname = current_schema()
+                        AND c.relkind IN ('r', 'p')
+                        AND has_table_privilege(c.oid, 'INSERT, UPDATE, DELETE, TRUNCATE')
+                );
+                ",
+            )
+            .fetch_one(pool)
+            .await?;
+            if has_write_privileges {
+                tracing::warn!(
+                    "Read-only database user has write privileges. Consider using a dedicated reader user"
+                );
+            }
+        }
+        Ok(())
     }
 
     async fn get_shard_connection(
@@ -77,7 +229,7 @@ impl PostgresDBManager {
         account_id: &near_primitives::types::AccountId,
     ) -> anyhow::Result<ShardIdPool> {
         let shard_id =
-            near_primitives::shard_layout::account_id_to_shard_id(account_id, &self.shard_layout);
+            readnode_primitives::indexer::account_id_to_shard_id(account_id, &self.shard_layout);
         Ok(ShardIdPool {
             shard_id,
             pool: self.shards_pool.get(&shard_id).ok_or(anyhow::anyhow!(
@@ -87,6 +239,11 @@ impl PostgresDBManager {
         })
     }
 
+    // Bucket of the state key in `state_changes_data_buckets` with the configured buckets count
+    fn state_key_bucket(&self, data_key: &readnode_primitives::StateKey) -> i32 {
+        data_key.bucket(self.state_key_buckets) as i32
+    }
+
     async fn run_migrations(
         migrator: &sqlx::migrate::Migrator,
         pool: &sqlx::Pool<sqlx::Postgres>,
@@ -94,6 +251,111 @@ impl PostgresDBManager {
         migrator.run(pool).await?;
         Ok(())
     }
+
+    async fn fetch_network_identity(
+        &self,
+        method_name: &str,
+    ) -> anyhow::Result<Option<readnode_primitives::NetworkIdentity>> {
+        crate::metrics::META_DATABASE_READ_QUERIES
+            .with_label_values(&[method_name, "network"])
+            .inc();
+        Ok(sqlx::query_as::<_, (String, types::Hash)>(
+            "
+            SELECT chain_id, genesis_hash
+            FROM network
+            LIMIT 1;
+            ",
+        )
+        .fetch_optional(&self.meta_db_pool)
+        .await?
+        .map(
+            |(chain_id, genesis_hash)| readnode_primitives::NetworkIdentity {
+                chain_id,
+                genesis_hash: genesis_hash.0,
+            },
+        ))
+    }
+
+    // The first indexer records the network, the row is never updated
+    async fn insert_network_identity(
+        &self,
+        network: &readnode_primitives::NetworkIdentity,
+    ) -> anyhow::Result<readnode_primitives::NetworkIdentity> {
+        crate::metrics::META_DATABASE_WRITE_QUERIES
+            .with_label_values(&["save_network_identity", "network"])
+            .inc();
+        sqlx::query(
+            "
+            INSERT INTO network (chain_id, genesis_hash)
+            VALUES ($1, $2)
+            ON CONFLICT (id) DO NOTHING;
+            ",
+        )
+        .bind(&network.chain_id)
+        .bind(network.genesis_hash.to_string())
+        .execute(&self.meta_db_pool)
+        .await?;
+        self.fetch_network_identity("save_network_identity")
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Network is not recorded in the database"))
+    }
+
+    async fn fetch_state_key_buckets(&self, method_name: &str) -> anyhow::Result<Option<u32>> {
+        crate::metrics::META_DATABASE_READ_QUERIES
+            .with_label_values(&[method_name, "state_key_buckets"])
+            .inc();
+        let state_key_buckets: Option<(i32,)> = sqlx::query_as(
+            "
+            SELECT buckets
+            FROM state_key_buckets
+            LIMIT 1;
+            ",
+        )
+        .fetch_optional(&self.meta_db_pool)
+        .await?;
+        state_key_buckets
+            .map(|(buckets,)| u32::try_from(buckets))
+            .transpose()
+            .map_err(|err| anyhow::anyhow!("Invalid number of the state key buckets: {}", err))
+    }
+
+    // The first state-indexer records the number of the buckets, the row is never updated
+    async fn insert_state_key_buckets(&self) -> anyhow::Result<u32> {
+        crate::metrics::META_DATABASE_WRITE_QUERIES
+            .with_label_values(&["save_state_key_buckets", "state_key_buckets"])
+            .inc();
+        sqlx::query(
+            "
+            INSERT INTO state_key_buckets (buckets)
+            VALUES ($1)
+            ON CONFLICT (id) DO NOTHING;
+            ",
+        )
+        .bind(i32::try_from(self.state_key_buckets)?)
+        .execute(&self.meta_db_pool)
+        .await?;
+        self.fetch_state_key_buckets("save_state_key_buckets")
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("State key buckets are not recorded in the database"))
+    }
+}
+
+/// The state keys are written into and looked up in the buckets of the configured number,
+/// so all the components have to be configured with the number the state is split into
+fn ensure_same_state_key_buckets(
+    configured_state_key_buckets: u32,
+    stored_state_key_buckets: u32,
+) -> anyhow::Result<()> {
+    if configured_state_key_buckets != stored_state_key_buckets {
+        anyhow::bail!(
+            "The state is split into {} key buckets, but `state_key_buckets` is configured to {}. \
+            Configure the same number, or move the state of every bucketed account with `rebalance-state-buckets` \
+            and update the `state_key_buckets` table to change it",
+            stored_state_key_buckets,
+            configured_state_key_buckets
+        );
+    }
+    Ok(())
 }
 
 #[async_trait::async_trait]
@@ -102,27 +364,61 @@ impl crate::BaseDbManager for PostgresDBManager {
         config: &configuration::DatabaseConfig,
         shard_layout: near_primitives::shard_layout::ShardLayout,
     ) -> anyhow::Result<Box<Self>> {
-        let meta_db_pool = Self::create_meta_db_pool(
-            &config.database_url,
-            config.read_only,
-            config.max_connections,
-        )
-        .await?;
+        let meta_db_pool =
+            Self::create_pool(config, &config.database_url, &META_DB_MIGRATOR).await?;
         let mut shards_pool = std::collections::HashMap::new();
         for shard_id in shard_layout.shard_ids() {
             let database_url = config
                 .shards_config
                 .get(&shard_id)
                 .unwrap_or_else(|| panic!("Shard_{shard_id} - database config not found"));
-            let pool =
-                Self::create_shard_db_pool(database_url, config.read_only, config.max_connections)
-                    .await?;
+            let pool = Self::create_pool(config, database_url, &SHARD_DB_MIGRATOR).await?;
             shards_pool.insert(shard_id, pool);
         }
+        // Replicas are never migrated and written, they are used only by the rpc-server
+        let mut local_replicas_pool = std::collections::HashMap::new();
+        if config.read_only {
+            for (shard_id, database_url) in &config.local_replicas_config {
+                let pool = Self::create_pool(config, database_url, &SHARD_DB_MIGRATOR).await?;
+                local_replicas_pool.insert(*shard_id, pool);
+            }
+        }
+        let cursor_signer = match &config.cursor_secret {
+            Some(cursor_secret) => pagination_cursor::CursorSigner::new(cursor_secret.as_bytes()),
+            None => {
+                if config.read_only {
+                    tracing::warn!(
+                        "Database cursor_secret is not set. Pagination cursors are valid only for this instance"
+                    );
+                }
+                pagination_cursor::CursorSigner::random()
+            }
+        };
         Ok(Box::new(Self {
             shard_layout,
             shards_pool,
             meta_db_pool,
+            local_replicas_pool,
+            cursor_signer,
+            state_key_buckets: config.state_key_buckets,
         }))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same_state_key_buckets() {
+        assert!(ensure_same_state_key_buckets(0, 0).is_ok());
+        assert!(ensure_same_state_key_buckets(16, 16).is_ok());
+    }
+
+    #[test]
+    fn test_different_state_key_buckets() {
+        assert!(ensure_same_state_key_buckets(16, 0).is_err());
+        assert!(ensure_same_state_key_buckets(0, 16).is_err());
+        assert!(ensure_same_state_key_buckets(16, 32).is_err());
+    }
+}
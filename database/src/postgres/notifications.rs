@@ -0,0 +1,120 @@
+//! Propagation of the indexers progress to the rpc-servers over the Postgres `LISTEN/NOTIFY`.
+//! The deployments without Redis learn about the new blocks and the finished transactions
+//! right away instead of polling the `meta` table.
+use std::str::FromStr;
+
+use futures::StreamExt;
+
+/// Channel of the blocks processed by the indexers
+const HEAD_CHANNEL: &str = "read_rpc_head";
+/// Channel of the transactions stored by the tx-indexers
+const TRANSACTIONS_CHANNEL: &str = "read_rpc_transactions";
+
+pub(crate) async fn notify_head(
+    pool: &sqlx::Pool<sqlx::Postgres>,
+    indexer_type: &str,
+    block_height: u64,
+    block_timestamp: u64,
+) -> anyhow::Result<()> {
+    let payload = serde_json::json!({
+        "indexer_type": indexer_type,
+        "block_height": block_height,
+        "block_timestamp": block_timestamp,
+    });
+    notify(pool, HEAD_CHANNEL, payload).await
+}
+
+/// The payload of the notification is limited by 8000 bytes,
+/// the transactions are sent by the batches of up to 100 hashes
+pub(crate) async fn notify_finalized_transactions(
+    pool: &sqlx::Pool<sqlx::Postgres>,
+    transaction_hashes: &[near_primitives::hash::CryptoHash],
+) -> anyhow::Result<()> {
+    for transaction_hashes in transaction_hashes.chunks(100) {
+        let payload = serde_json::json!({
+            "transaction_hashes": transaction_hashes
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+        });
+        notify(pool, TRANSACTIONS_CHANNEL, payload).await?;
+    }
+    Ok(())
+}
+
+async fn notify(
+    pool: &sqlx::Pool<sqlx::Postgres>,
+    channel: &str,
+    payload: serde_json::Value,
+) -> anyhow::Result<()> {
+    sqlx::query("SELECT pg_notify($1, $2);")
+        .bind(channel)
+        .bind(payload.to_string())
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Listens to the notifications of the indexers on the dedicated connection.
+/// The lost connection is restored by the listener, the notifications sent meanwhile are missed
+pub(crate) async fn listen(
+    pool: &sqlx::Pool<sqlx::Postgres>,
+) -> anyhow::Result<
+    futures::stream::BoxStream<'static, anyhow::Result<crate::primitives::IndexerNotification>>,
+> {
+    let mut listener = sqlx::postgres::PgListener::connect_with(pool).await?;
+    listener
+        .listen_all([HEAD_CHANNEL, TRANSACTIONS_CHANNEL])
+        .await?;
+    Ok(listener
+        .into_stream()
+        .map(|notification| {
+            let notification = notification?;
+            parse_notification(notification.channel(), notification.payload())
+        })
+        .boxed())
+}
+
+fn parse_notification(
+    channel: &str,
+    payload: &str,
+) -> anyhow::Result<crate::primitives::IndexerNotification> {
+    let payload: serde_json::Value = serde_json::from_str(payload)?;
+    let field = |name: &str| {
+        payload.get(name).ok_or_else(|| {
+            anyhow::anyhow!("`{}` is missing in the `{}` notification", name, channel)
+        })
+    };
+    let as_u64 = |name: &str| {
+        field(name)?.as_u64().ok_or_else(|| {
+            anyhow::anyhow!("`{}` of the `{}` notification is not u64", name, channel)
+        })
+    };
+    match channel {
+        HEAD_CHANNEL => Ok(crate::primitives::IndexerNotification::Head {
+            indexer_type: field("indexer_type")?
+                .as_str()
+                .ok_or_else(|| anyhow::anyhow!("`indexer_type` is not a string"))?
+                .to_string(),
+            block_height: as_u64("block_height")?,
+            block_timestamp: as_u64("block_timestamp")?,
+        }),
+        TRANSACTIONS_CHANNEL => Ok(
+            crate::primitives::IndexerNotification::TransactionsFinalized {
+                transaction_hashes: field("transaction_hashes")?
+                    .as_array()
+                    .ok_or_else(|| anyhow::anyhow!("`transaction_hashes` is not an array"))?
+                    .iter()
+                    .map(|hash| {
+                        let hash = hash
+                            .as_str()
+                            .ok_or_else(|| anyhow::anyhow!("Transaction hash is not a string"))?;
+                        near_primitives::hash::CryptoHash::from_str(hash)
+                            .map_err(|err| anyhow::anyhow!("Invalid transaction hash: {}", err))
+                    })
+                    .collect::<anyhow::Result<_>>()?,
+            },
+        ),
+        _ => anyhow::bail!("Unknown notification channel `{}`", channel),
+    }
+}
@@ -1,8 +1,7 @@
-use std::str::FromStr;
-
-use bigdecimal::ToPrimitive;
 use futures::StreamExt;
 
+use super::types;
+
 #[async_trait::async_trait]
 impl crate::ReaderDbManager for crate::PostgresDBManager {
     async fn get_block_height_by_hash(
@@ -13,7 +12,7 @@ impl crate::ReaderDbManager for crate::PostgresDBManager {
         crate::metrics::META_DATABASE_READ_QUERIES
             .with_label_values(&[method_name, "blocks"])
             .inc();
-        let (block_height,): (bigdecimal::BigDecimal,) = sqlx::query_as(
+        let (block_height,): (types::U64,) = sqlx::query_as(
             "
                 SELECT block_height
                 FROM blocks
@@ -21,12 +20,10 @@ impl crate::ReaderDbManager for crate::PostgresDBManager {
                 LIMIT 1;
                 ",
         )
-        .bind(block_hash.to_string())
+        .bind(types::Hash(block_hash))
         .fetch_one(&self.meta_db_pool)
         .await?;
-        block_height
-            .to_u64()
-            .ok_or_else(|| anyhow::anyhow!("Failed to parse `block_height` to u64"))
+        Ok(block_height.0)
     }
 
     async fn get_block_by_chunk_hash(
@@ -37,7 +34,7 @@ impl crate::ReaderDbManager for crate::PostgresDBManager {
         crate::metrics::META_DATABASE_READ_QUERIES
             .with_label_values(&[method_name, "chunks"])
             .inc();
-        let result: (bigdecimal::BigDecimal, bigdecimal::BigDecimal) = sqlx::query_as(
+        let (block_height, shard_id): (types::U64, types::U64) = sqlx::query_as(
             "
                 SELECT block_height, shard_id
                 FROM chunks
@@ -45,22 +42,25 @@ impl crate::ReaderDbManager for crate::PostgresDBManager {
                 LIMIT 1;
                 ",
         )
-        .bind(chunk_hash.to_string())
+        .bind(types::Hash(chunk_hash))
         .fetch_one(&self.meta_db_pool)
         .await?;
-        Ok(readnode_primitives::BlockHeightShardId::try_from(result)?)
+        Ok(readnode_primitives::BlockHeightShardId(
+            block_height.0,
+            shard_id.0,
+        ))
     }
 
-    async fn get_state_by_page(
+    async fn get_state_by_key_prefix(
         &self,
         account_id: &near_primitives::types::AccountId,
         block_height: near_primitives::types::BlockHeight,
-        page_token: crate::PageToken,
+        prefix: &[u8],
+        max_keys: Option<usize>,
         method_name: &str,
-    ) -> anyhow::Result<(
+    ) -> anyhow::Result<
         std::collections::HashMap<readnode_primitives::StateKey, readnode_primitives::StateValue>,
-        crate::PageToken,
-    )> {
+    > {
         let shard_id_pool = self.get_shard_connection(account_id).await?;
         crate::metrics::SHARD_DATABASE_READ_QUERIES
             .with_label_values(&[
@@ -69,11 +69,7 @@ impl crate::ReaderDbManager for crate::PostgresDBManager {
                 "state_changes_data",
             ])
             .inc();
-        let page_state = if let Some(page_state_token) = page_token {
-            borsh::from_slice::<crate::postgres::PageState>(&hex::decode(page_state_token)?)?
-        } else {
-            crate::postgres::PageState::new(1000)
-        };
+        let mut items = std::collections::HashMap::new();
         let mut stream = sqlx::query_as::<_, (String, Vec<u8>)>(
             "
                 WITH latest_blocks AS (
@@ -81,10 +77,11 @@ impl crate::ReaderDbManager for crate::PostgresDBManager {
                         data_key,
                         MAX(block_height) AS max_block_height
                     FROM 
-                        state_changes_data
+                        state_changes_data_all
                     WHERE 
                         account_id = $1
-                        AND block_height <= $2
+                        AND data_key LIKE $2
+                        AND block_height <= $3
                     GROUP BY 
                         data_key
                 )
@@ -92,7 +89,7 @@ impl crate::ReaderDbManager for crate::PostgresDBManager {
                     sc.data_key,
                     sc.data_value
                 FROM
-                    state_changes_data sc
+                    state_changes_data_all sc
                 INNER JOIN latest_blocks lb
                 ON 
                     sc.data_key = lb.data_key 
@@ -100,40 +97,36 @@ impl crate::ReaderDbManager for crate::PostgresDBManager {
                 WHERE
                     sc.account_id = $1
                     AND sc.data_value IS NOT NULL
-                ORDER BY 
-                    sc.data_key
-                LIMIT $3 OFFSET $4;
+                LIMIT $4;
                 ",
         )
         .bind(account_id.to_string())
-        .bind(bigdecimal::BigDecimal::from(block_height))
-        .bind(page_state.page_size)
-        .bind(page_state.offset)
+        .bind(format!(
+            "{}%",
+            readnode_primitives::StateKey::from(prefix).to_hex()
+        ))
+        .bind(types::U64(block_height))
+        // `LIMIT NULL` returns all the rows
+        .bind(max_keys.map(|max_keys| max_keys as i64))
         .fetch(shard_id_pool.pool);
-        let mut items = std::collections::HashMap::new();
         while let Some(row) = stream.next().await {
             let (key, value): (String, Vec<u8>) = row?;
-            items.insert(hex::decode(key)?, value);
-        }
-        if items.len() < page_state.page_size as usize {
-            Ok((items, None))
-        } else {
-            Ok((
-                items,
-                Some(hex::encode(borsh::to_vec(&page_state.next_page())?)),
-            ))
+            items.insert(
+                readnode_primitives::StateKey::from_hex(&key)?,
+                readnode_primitives::StateValue::from(value),
+            );
         }
+        Ok(items)
     }
 
-    async fn get_state_by_key_prefix(
+    async fn get_state_keys_by_page(
         &self,
         account_id: &near_primitives::types::AccountId,
         block_height: near_primitives::types::BlockHeight,
-        prefix: &[u8],
+        limit: u64,
+        page_token: crate::PageToken,
         method_name: &str,
-    ) -> anyhow::Result<
-        std::collections::HashMap<readnode_primitives::StateKey, readnode_primitives::StateValue>,
-    > {
+    ) -> anyhow::Result<(Vec<readnode_primitives::StateKey>, crate::PageToken)> {
         let shard_id_pool = self.get_shard_connection(account_id).await?;
         crate::metrics::SHARD_DATABASE_READ_QUERIES
             .with_label_values(&[
@@ -142,51 +135,155 @@ impl crate::ReaderDbManager for crate::PostgresDBManager {
                 "state_changes_data",
             ])
             .inc();
-        let mut items = std::collections::HashMap::new();
-        let mut stream = sqlx::query_as::<_, (String, Vec<u8>)>(
+        let after_data_key = match page_token {
+            Some(page_token) => {
+                self.cursor_signer
+                    .decode::<crate::postgres::StateKeysCursor>(&page_token)?
+                    .data_key
+            }
+            None => String::new(),
+        };
+        // The keys are walked in the index order starting after the last key of the previous page,
+        // the keys deleted at the block height are skipped
+        let data_keys = sqlx::query_scalar::<_, String>(
             "
-                WITH latest_blocks AS (
-                    SELECT 
+                SELECT latest.data_key
+                FROM (
+                    SELECT DISTINCT ON (data_key)
                         data_key,
-                        MAX(block_height) AS max_block_height
-                    FROM 
-                        state_changes_data
-                    WHERE 
+                        data_value
+                    FROM state_changes_data_all
+                    WHERE
                         account_id = $1
-                        AND data_key LIKE $2
+                        AND data_key > $2
                         AND block_height <= $3
-                    GROUP BY 
-                        data_key
+                    ORDER BY data_key, block_height DESC
+                ) latest
+                WHERE latest.data_value IS NOT NULL
+                ORDER BY latest.data_key
+                LIMIT $4;
+                ",
+        )
+        .bind(account_id.to_string())
+        .bind(after_data_key)
+        .bind(types::U64(block_height))
+        .bind(limit as i64)
+        .fetch_all(shard_id_pool.pool)
+        .await?;
+
+        let next_page_token = match data_keys.last() {
+            Some(data_key) if data_keys.len() as u64 == limit => Some(self.cursor_signer.encode(
+                &crate::postgres::StateKeysCursor {
+                    data_key: data_key.clone(),
+                },
+            )?),
+            _ => None,
+        };
+        let keys = data_keys
+            .iter()
+            .map(|data_key| readnode_primitives::StateKey::from_hex(data_key))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        Ok((keys, next_page_token))
+    }
+
+    async fn get_state_diff_by_page(
+        &self,
+        account_id: &near_primitives::types::AccountId,
+        from_block_height: near_primitives::types::BlockHeight,
+        to_block_height: near_primitives::types::BlockHeight,
+        prefix: &[u8],
+        limit: u64,
+        page_token: crate::PageToken,
+        method_name: &str,
+    ) -> anyhow::Result<(Vec<readnode_primitives::StateKeyDiff>, crate::PageToken)> {
+        let shard_id_pool = self.get_shard_connection(account_id).await?;
+        crate::metrics::SHARD_DATABASE_READ_QUERIES
+            .with_label_values(&[
+                &shard_id_pool.shard_id.to_string(),
+                method_name,
+                "state_changes_data",
+            ])
+            .inc();
+        let after_data_key = match page_token {
+            Some(page_token) => {
+                self.cursor_signer
+                    .decode::<crate::postgres::StateDiffCursor>(&page_token)?
+                    .data_key
+            }
+            None => String::new(),
+        };
+        // The keys changed within the range are paginated,
+        // their values are the latest values at both block heights
+        let rows = sqlx::query_as::<_, (String, Option<Vec<u8>>, Option<Vec<u8>>)>(
+            "
+                WITH changed_keys AS (
+                    SELECT DISTINCT data_key
+                    FROM state_changes_data_all
+                    WHERE
+                        account_id = $1
+                        AND data_key LIKE $2
+                        AND data_key > $3
+                        AND block_height > $4
+                        AND block_height <= $5
+                    ORDER BY data_key
+                    LIMIT $6
                 )
-                SELECT 
-                    sc.data_key,
-                    sc.data_value
-                FROM
-                    state_changes_data sc
-                INNER JOIN latest_blocks lb
-                ON 
-                    sc.data_key = lb.data_key 
-                    AND sc.block_height = lb.max_block_height
-                WHERE
-                    sc.account_id = $1
-                    AND sc.data_value IS NOT NULL;
+                SELECT
+                    ck.data_key,
+                    (
+                        SELECT sc.data_value
+                        FROM state_changes_data_all sc
+                        WHERE sc.account_id = $1 AND sc.data_key = ck.data_key AND sc.block_height <= $4
+                        ORDER BY sc.block_height DESC
+                        LIMIT 1
+                    ),
+                    (
+                        SELECT sc.data_value
+                        FROM state_changes_data_all sc
+                        WHERE sc.account_id = $1 AND sc.data_key = ck.data_key AND sc.block_height <= $5
+                        ORDER BY sc.block_height DESC
+                        LIMIT 1
+                    )
+                FROM changed_keys ck
+                ORDER BY ck.data_key;
                 ",
         )
         .bind(account_id.to_string())
-        .bind(format!("{}%", hex::encode(prefix)))
-        .bind(bigdecimal::BigDecimal::from(block_height))
-        .fetch(shard_id_pool.pool);
-        while let Some(row) = stream.next().await {
-            let (key, value): (String, Vec<u8>) = row?;
-            items.insert(hex::decode(key)?, value);
-        }
-        Ok(items)
+        .bind(format!("{}%", readnode_primitives::StateKey::from(prefix).to_hex()))
+        .bind(after_data_key)
+        .bind(types::U64(from_block_height))
+        .bind(types::U64(to_block_height))
+        .bind(limit as i64)
+        .fetch_all(shard_id_pool.pool)
+        .await?;
+
+        let next_page_token = match rows.last() {
+            Some((data_key, _, _)) if rows.len() as u64 == limit => Some(
+                self.cursor_signer
+                    .encode(&crate::postgres::StateDiffCursor {
+                        data_key: data_key.clone(),
+                    })?,
+            ),
+            _ => None,
+        };
+        let diff = rows
+            .into_iter()
+            .map(|(data_key, from_value, to_value)| {
+                Ok(readnode_primitives::StateKeyDiff {
+                    key: readnode_primitives::StateKey::from_hex(&data_key)?,
+                    from_value: from_value.map(readnode_primitives::StateValue::from),
+                    to_value: to_value.map(readnode_primitives::StateValue::from),
+                })
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        Ok((diff, next_page_token))
     }
 
     async fn get_state(
         &self,
         account_id: &near_primitives::types::AccountId,
         block_height: near_primitives::types::BlockHeight,
+        max_keys: Option<usize>,
         method_name: &str,
     ) -> anyhow::Result<
         std::collections::HashMap<readnode_primitives::StateKey, readnode_primitives::StateValue>,
@@ -207,7 +304,7 @@ impl crate::ReaderDbManager for crate::PostgresDBManager {
                         data_key,
                         MAX(block_height) AS max_block_height
                     FROM 
-                        state_changes_data
+                        state_changes_data_all
                     WHERE 
                         account_id = $1
                         AND block_height <= $2
@@ -218,26 +315,89 @@ impl crate::ReaderDbManager for crate::PostgresDBManager {
                     sc.data_key,
                     sc.data_value
                 FROM
-                    state_changes_data sc
+                    state_changes_data_all sc
                 INNER JOIN latest_blocks lb
                 ON 
                     sc.data_key = lb.data_key 
                     AND sc.block_height = lb.max_block_height
                 WHERE
                     sc.account_id = $1
-                    AND sc.data_value IS NOT NULL;
+                    AND sc.data_value IS NOT NULL
+                LIMIT $3;
                 ",
         )
         .bind(account_id.to_string())
-        .bind(bigdecimal::BigDecimal::from(block_height))
+        .bind(types::U64(block_height))
+        .bind(max_keys.map(|max_keys| max_keys as i64))
         .fetch(shard_id_pool.pool);
         while let Some(row) = stream.next().await {
             let (key, value): (String, Vec<u8>) = row?;
-            items.insert(hex::decode(key)?, value);
+            items.insert(
+                readnode_primitives::StateKey::from_hex(&key)?,
+                readnode_primitives::StateValue::from(value),
+            );
         }
         Ok(items)
     }
 
+    async fn get_state_size(
+        &self,
+        account_id: &near_primitives::types::AccountId,
+        block_height: near_primitives::types::BlockHeight,
+        max_keys: usize,
+        method_name: &str,
+    ) -> anyhow::Result<Option<readnode_primitives::StateSize>> {
+        let shard_id_pool = self.get_shard_connection(account_id).await?;
+        crate::metrics::SHARD_DATABASE_READ_QUERIES
+            .with_label_values(&[
+                &shard_id_pool.shard_id.to_string(),
+                method_name,
+                "state_changes_data",
+            ])
+            .inc();
+        // `data_key` is stored hex encoded, so the key size is a half of the text length.
+        // The keys are scanned in the order of the primary key up to one over `max_keys`,
+        // the deleted keys are scanned as well, so the scan is bounded by the limit
+        let (scanned_keys, keys_count, keys_bytes, values_bytes): (i64, i64, i64, i64) =
+            sqlx::query_as(
+                "
+                WITH latest_values AS (
+                    SELECT DISTINCT ON (data_key)
+                        data_key,
+                        data_value
+                    FROM
+                        state_changes_data_all
+                    WHERE
+                        account_id = $1
+                        AND block_height <= $2
+                    ORDER BY
+                        data_key, block_height DESC
+                    LIMIT $3
+                )
+                SELECT
+                    COUNT(*),
+                    COUNT(data_value),
+                    COALESCE(SUM(length(data_key) / 2) FILTER (WHERE data_value IS NOT NULL), 0)::bigint,
+                    COALESCE(SUM(octet_length(data_value)), 0)::bigint
+                FROM
+                    latest_values;
+                ",
+            )
+            .bind(account_id.to_string())
+            .bind(types::U64(block_height))
+            .bind(max_keys as i64 + 1)
+            .fetch_one(shard_id_pool.pool)
+            .await?;
+        if scanned_keys as usize > max_keys {
+            return Ok(None);
+        }
+        Ok(Some(readnode_primitives::StateSize {
+            keys_count: keys_count as u64,
+            keys_bytes: keys_bytes as u64,
+            values_bytes: values_bytes as u64,
+        }))
+    }
+
     async fn get_state_key_value(
         &self,
         account_id: &near_primitives::types::AccountId,
@@ -256,23 +416,122 @@ impl crate::ReaderDbManager for crate::PostgresDBManager {
                 "state_changes_data",
             ])
             .inc();
+        // The bucket of the key is looked up only, the key may still be in the unbucketed table
         let (data_value,): (Vec<u8>,) = sqlx::query_as(
             "
-                SELECT data_value 
-                FROM state_changes_data
-                WHERE account_id = $1 
-                    AND data_key = $2 
-                    AND block_height <= $3
+                SELECT data_value
+                FROM (
+                    SELECT data_value, block_height
+                    FROM state_changes_data_buckets
+                    WHERE account_id = $1
+                        AND key_bucket = $4
+                        AND data_key = $2
+                        AND block_height <= $3
+                    UNION ALL
+                    SELECT data_value, block_height
+                    FROM state_changes_data
+                    WHERE account_id = $1
+                        AND data_key = $2
+                        AND block_height <= $3
+                ) changes
                 ORDER BY block_height DESC
                 LIMIT 1;
                 ",
         )
         .bind(account_id.to_string())
-        .bind(hex::encode(&key_data).to_string())
-        .bind(bigdecimal::BigDecimal::from(block_height))
+        .bind(key_data.to_hex())
+        .bind(types::U64(block_height))
+        .bind(self.state_key_bucket(&key_data))
         .fetch_one(shard_id_pool.pool)
         .await?;
-        Ok((key_data, data_value))
+        Ok((key_data, readnode_primitives::StateValue::from(data_value)))
+    }
+
+    async fn get_state_keys_values(
+        &self,
+        account_id: &near_primitives::types::AccountId,
+        block_height: near_primitives::types::BlockHeight,
+        keys: &[readnode_primitives::StateKey],
+        method_name: &str,
+    ) -> anyhow::Result<
+        std::collections::HashMap<readnode_primitives::StateKey, readnode_primitives::StateValue>,
+    > {
+        let shard_id_pool = self.get_shard_connection(account_id).await?;
+        crate::metrics::SHARD_DATABASE_READ_QUERIES
+            .with_label_values(&[
+                &shard_id_pool.shard_id.to_string(),
+                method_name,
+                "state_changes_data",
+            ])
+            .inc();
+        let data_keys: Vec<String> = keys.iter().map(|key| key.to_hex()).collect();
+        let mut key_buckets: Vec<i32> = keys.iter().map(|key| self.state_key_bucket(key)).collect();
+        key_buckets.sort_unstable();
+        key_buckets.dedup();
+        // The latest change of every key at the block height, the deleted keys have no value
+        let rows: Vec<(String, Option<Vec<u8>>)> = sqlx::query_as(
+            "
+                SELECT DISTINCT ON (data_key)
+                    data_key,
+                    data_value
+                FROM (
+                    SELECT data_key, data_value, block_height
+                    FROM state_changes_data_buckets
+                    WHERE account_id = $1
+                        AND key_bucket = ANY($4)
+                        AND data_key = ANY($2)
+                        AND block_height <= $3
+                    UNION ALL
+                    SELECT data_key, data_value, block_height
+                    FROM state_changes_data
+                    WHERE account_id = $1
+                        AND data_key = ANY($2)
+                        AND block_height <= $3
+                ) changes
+                ORDER BY
+                    data_key, block_height DESC;
+                ",
+        )
+        .bind(account_id.to_string())
+        .bind(data_keys)
+        .bind(types::U64(block_height))
+        .bind(key_buckets)
+        .fetch_all(shard_id_pool.pool)
+        .await?;
+        let mut items = std::collections::HashMap::new();
+        for (key, value) in rows {
+            if let Some(value) = value {
+                items.insert(
+                    readnode_primitives::StateKey::from_hex(&key)?,
+                    readnode_primitives::StateValue::from(value),
+                );
+            }
+        }
+        Ok(items)
+    }
+
+    async fn get_state_changes_in_block(
+        &self,
+        block_height: near_primitives::types::BlockHeight,
+        method_name: &str,
+    ) -> anyhow::Result<Option<Vec<near_primitives::views::StateChangeValueView>>> {
+        let mut shard_ids = self.shards_pool.keys().copied().collect::<Vec<_>>();
+        shard_ids.sort();
+        let futures = shard_ids.iter().map(|shard_id| {
+            get_shard_state_changes_in_block(
+                *shard_id,
+                &self.shards_pool[shard_id],
+                block_height,
+                method_name,
+            )
+        });
+        // The changes of the shards are concatenated in the order of the shards,
+        // the block is not served if the changes of any shard are incomplete
+        Ok(futures::future::try_join_all(futures)
+            .await?
+            .into_iter()
+            .collect::<Option<Vec<_>>>()
+            .map(|changes| changes.into_iter().flatten().collect()))
     }
 
     async fn get_account(
@@ -289,7 +548,7 @@ impl crate::ReaderDbManager for crate::PostgresDBManager {
                 "state_changes_account",
             ])
             .inc();
-        let (block_height, block_hash, data_value): (bigdecimal::BigDecimal, String, Vec<u8>) =
+        let (block_height, block_hash, data_value): (types::U64, types::Hash, Vec<u8>) =
             sqlx::query_as(
                 "
                 SELECT block_height, block_hash, data_value 
@@ -301,14 +560,13 @@ impl crate::ReaderDbManager for crate::PostgresDBManager {
                 ",
             )
             .bind(account_id.to_string())
-            .bind(bigdecimal::BigDecimal::from(request_block_height))
+            .bind(types::U64(request_block_height))
             .fetch_one(shard_id_pool.pool)
             .await?;
-        let block = readnode_primitives::BlockRecord::try_from((block_hash, block_height))?;
         readnode_primitives::QueryData::<near_primitives::account::Account>::try_from((
             data_value,
-            block.height,
-            block.hash,
+            block_height.0,
+            block_hash.0,
         ))
     }
 
@@ -326,7 +584,7 @@ impl crate::ReaderDbManager for crate::PostgresDBManager {
                 "state_changes_contract",
             ])
             .inc();
-        let (block_height, block_hash, contract_code): (bigdecimal::BigDecimal, String, Vec<u8>) =
+        let (block_height, block_hash, contract_code): (types::U64, types::Hash, Vec<u8>) =
             sqlx::query_as(
                 "
                 SELECT block_height, block_hash, data_value
@@ -338,14 +596,13 @@ impl crate::ReaderDbManager for crate::PostgresDBManager {
                 ",
             )
             .bind(account_id.to_string())
-            .bind(bigdecimal::BigDecimal::from(request_block_height))
+            .bind(types::U64(request_block_height))
             .fetch_one(shard_id_pool.pool)
             .await?;
-        let block = readnode_primitives::BlockRecord::try_from((block_hash, block_height))?;
         Ok(readnode_primitives::QueryData {
             data: contract_code,
-            block_height: block.height,
-            block_hash: block.hash,
+            block_height: block_height.0,
+            block_hash: block_hash.0,
         })
     }
 
@@ -365,7 +622,7 @@ impl crate::ReaderDbManager for crate::PostgresDBManager {
             ])
             .inc();
         let key_data = borsh::to_vec(&public_key)?;
-        let (block_height, block_hash, data_value): (bigdecimal::BigDecimal, String, Vec<u8>) =
+        let (block_height, block_hash, data_value): (types::U64, types::Hash, Vec<u8>) =
             sqlx::query_as(
                 "
                 SELECT block_height, block_hash, data_value
@@ -378,15 +635,14 @@ impl crate::ReaderDbManager for crate::PostgresDBManager {
                 ",
             )
             .bind(account_id.to_string())
-            .bind(hex::encode(&key_data).to_string())
-            .bind(bigdecimal::BigDecimal::from(request_block_height))
+            .bind(readnode_primitives::encoding::encode_hex(&key_data))
+            .bind(types::U64(request_block_height))
             .fetch_one(shard_id_pool.pool)
             .await?;
-        let block = readnode_primitives::BlockRecord::try_from((block_hash, block_height))?;
         readnode_primitives::QueryData::<near_primitives::account::AccessKey>::try_from((
             data_value,
-            block.height,
-            block.hash,
+            block_height.0,
+            block_hash.0,
         ))
     }
 
@@ -405,7 +661,7 @@ impl crate::ReaderDbManager for crate::PostgresDBManager {
             ])
             .inc();
         let mut access_keys = vec![];
-        let mut stream = sqlx::query_as::<_, (String, Vec<u8>, bigdecimal::BigDecimal)>(
+        let mut stream = sqlx::query_as::<_, (String, Vec<u8>, types::U64)>(
             "
                 WITH latest_blocks AS (
                     SELECT 
@@ -437,70 +693,181 @@ impl crate::ReaderDbManager for crate::PostgresDBManager {
                 ",
         )
         .bind(account_id.to_string())
-        .bind(bigdecimal::BigDecimal::from(block_height))
+        .bind(types::U64(block_height))
         .fetch(shard_id_pool.pool);
         while let Some(row) = stream.next().await {
             let (public_key_hex, access_key, _): (String, Vec<u8>, _) = row?;
             let access_key_view = near_primitives::views::AccessKeyInfoView {
-                public_key: borsh::from_slice::<near_crypto::PublicKey>(&hex::decode(
-                    public_key_hex,
-                )?)?,
-                access_key: near_primitives::views::AccessKeyView::from(borsh::from_slice::<
-                    near_primitives::account::AccessKey,
-                >(
-                    &access_key
-                )?),
+                public_key: readnode_primitives::blob::borsh_from_slice::<near_crypto::PublicKey>(
+                    &readnode_primitives::encoding::decode_hex(&public_key_hex)?,
+                )?,
+                access_key: near_primitives::views::AccessKeyView::from(
+                    readnode_primitives::blob::borsh_from_slice::<
+                        near_primitives::account::AccessKey,
+                    >(&access_key)?,
+                ),
             };
             access_keys.push(access_key_view);
         }
         Ok(access_keys)
     }
 
+    async fn get_account_access_keys_count(
+        &self,
+        account_id: &near_primitives::types::AccountId,
+        block_height: near_primitives::types::BlockHeight,
+        method_name: &str,
+    ) -> anyhow::Result<u64> {
+        let shard_id_pool = self.get_shard_connection(account_id).await?;
+        crate::metrics::SHARD_DATABASE_READ_QUERIES
+            .with_label_values(&[
+                &shard_id_pool.shard_id.to_string(),
+                method_name,
+                "state_changes_access_key",
+            ])
+            .inc();
+        let (count,): (i64,) = sqlx::query_as(
+            "
+                SELECT COUNT(*)
+                FROM (
+                    SELECT DISTINCT ON (data_key)
+                        data_value
+                    FROM
+                        state_changes_access_key
+                    WHERE
+                        account_id = $1
+                        AND block_height <= $2
+                    ORDER BY
+                        data_key,
+                        block_height DESC
+                ) latest_access_keys
+                WHERE
+                    data_value IS NOT NULL;
+                ",
+        )
+        .bind(account_id.to_string())
+        .bind(types::U64(block_height))
+        .fetch_one(shard_id_pool.pool)
+        .await?;
+        Ok(count as u64)
+    }
+
+    async fn get_account_public_keys(
+        &self,
+        account_id: &near_primitives::types::AccountId,
+        block_height: near_primitives::types::BlockHeight,
+        method_name: &str,
+    ) -> anyhow::Result<Vec<near_crypto::PublicKey>> {
+        let shard_id_pool = self.get_shard_connection(account_id).await?;
+        crate::metrics::SHARD_DATABASE_READ_QUERIES
+            .with_label_values(&[
+                &shard_id_pool.shard_id.to_string(),
+                method_name,
+                "state_changes_access_key",
+            ])
+            .inc();
+        let data_keys: Vec<(String,)> = sqlx::query_as(
+            "
+                SELECT data_key
+                FROM (
+                    SELECT DISTINCT ON (data_key)
+                        data_key,
+                        data_value IS NOT NULL AS is_present
+                    FROM
+                        state_changes_access_key
+                    WHERE
+                        account_id = $1
+                        AND block_height <= $2
+                    ORDER BY
+                        data_key,
+                        block_height DESC
+                ) latest_access_keys
+                WHERE
+                    is_present;
+                ",
+        )
+        .bind(account_id.to_string())
+        .bind(types::U64(block_height))
+        .fetch_all(shard_id_pool.pool)
+        .await?;
+        data_keys
+            .into_iter()
+            .map(|(public_key_hex,)| {
+                readnode_primitives::blob::borsh_from_slice::<near_crypto::PublicKey>(
+                    &readnode_primitives::encoding::decode_hex(&public_key_hex)?,
+                )
+            })
+            .collect()
+    }
+
     async fn get_receipt_by_id(
         &self,
         receipt_id: near_primitives::hash::CryptoHash,
         method_name: &str,
     ) -> anyhow::Result<readnode_primitives::ReceiptRecord> {
-        // We need to query all shards because we don't know which shard the receipt is stored in
-        // and we need to return the receipt as soon as we find it.
-        // Query all shards in parallel and then we wait for the first result.
-        let futures = self.shards_pool.iter().map(|(shard_id, pool)| {
-            crate::metrics::SHARD_DATABASE_READ_QUERIES
-                .with_label_values(&[&shard_id.to_string(), method_name, "receipts_map"])
+        // The receipts map is append-only, so the lookup is safe to serve from the replicas
+        // of the preferred datacenter. The receipt can be not replicated yet,
+        // so it is looked up on the shards databases if it is not found on the replicas
+        if !self.local_replicas_pool.is_empty() {
+            let local_pools = self.shards_pool.iter().map(|(shard_id, pool)| {
+                (
+                    shard_id,
+                    self.local_replicas_pool.get(shard_id).unwrap_or(pool),
+                )
+            });
+            if let Some(row) = find_receipt(local_pools, receipt_id, method_name).await {
+                return receipt_record(row);
+            }
+            crate::metrics::LOCAL_REPLICA_READ_FALLBACKS
+                .with_label_values(&[method_name, "receipts_map"])
                 .inc();
-            sqlx::query_as::<
-                _,
+        }
+        match find_receipt(self.shards_pool.iter(), receipt_id, method_name).await {
+            Some(row) => receipt_record(row),
+            None => anyhow::bail!("Receipt not found"),
+        }
+    }
+
+    async fn get_receipts_by_ids(
+        &self,
+        receipt_ids: &[near_primitives::hash::CryptoHash],
+        method_name: &str,
+    ) -> anyhow::Result<Vec<readnode_primitives::ReceiptRecord>> {
+        if receipt_ids.is_empty() {
+            return Ok(vec![]);
+        }
+        let mut rows = std::collections::HashMap::with_capacity(receipt_ids.len());
+        // Same as `get_receipt_by_id`, the receipts not replicated yet
+        // are looked up on the shards databases
+        if !self.local_replicas_pool.is_empty() {
+            let local_pools = self.shards_pool.iter().map(|(shard_id, pool)| {
                 (
-                    String,
-                    String,
-                    String,
-                    bigdecimal::BigDecimal,
-                    String,
-                    bigdecimal::BigDecimal,
-                ),
-            >(
-                "
-                SELECT receipt_id, 
-                    parent_transaction_hash, 
-                    receiver_id, 
-                    block_height, 
-                    block_hash, 
-                    shard_id
-                FROM receipts_map
-                WHERE receipt_id = $1
-                LIMIT 1;
-                ",
-            )
-            .bind(receipt_id.to_string())
-            .fetch_one(pool)
-        });
-        let mut tasks = futures::stream::FuturesUnordered::from_iter(futures);
-        while let Some(result) = tasks.next().await {
-            if let Ok(row) = result {
-                return readnode_primitives::ReceiptRecord::try_from(row);
+                    shard_id,
+                    self.local_replicas_pool.get(shard_id).unwrap_or(pool),
+                )
+            });
+            rows.extend(find_receipts(local_pools, receipt_ids, method_name).await?);
+            if rows.len() < receipt_ids.len() {
+                crate::metrics::LOCAL_REPLICA_READ_FALLBACKS
+                    .with_label_values(&[method_name, "receipts_map"])
+                    .inc();
             }
         }
-        anyhow::bail!("Receipt not found")
+        let missing_receipt_ids = receipt_ids
+            .iter()
+            .filter(|receipt_id| !rows.contains_key(*receipt_id))
+            .copied()
+            .collect::<Vec<_>>();
+        if !missing_receipt_ids.is_empty() {
+            rows.extend(
+                find_receipts(self.shards_pool.iter(), &missing_receipt_ids, method_name).await?,
+            );
+        }
+        receipt_ids
+            .iter()
+            .filter_map(|receipt_id| rows.remove(receipt_id))
+            .map(receipt_record)
+            .collect()
     }
 
     async fn get_block_by_height_and_shard_id(
@@ -512,7 +879,7 @@ impl crate::ReaderDbManager for crate::PostgresDBManager {
         crate::metrics::META_DATABASE_READ_QUERIES
             .with_label_values(&[method_name, "chunks_duplicate"])
             .inc();
-        let result: (bigdecimal::BigDecimal, bigdecimal::BigDecimal) = sqlx::query_as(
+        let (included_in_block_height, shard_id): (types::U64, types::U64) = sqlx::query_as(
             "
                 SELECT included_in_block_height, shard_id
                 FROM chunks_duplicate
@@ -521,11 +888,119 @@ impl crate::ReaderDbManager for crate::PostgresDBManager {
                 LIMIT 1;
                 ",
         )
-        .bind(bigdecimal::BigDecimal::from(block_height))
-        .bind(bigdecimal::BigDecimal::from(shard_id))
+        .bind(types::U64(block_height))
+        .bind(types::U64(shard_id))
+        .fetch_one(&self.meta_db_pool)
+        .await?;
+        Ok(readnode_primitives::BlockHeightShardId(
+            included_in_block_height.0,
+            shard_id.0,
+        ))
+    }
+
+    async fn get_skipped_chunk(
+        &self,
+        block_height: near_primitives::types::BlockHeight,
+        shard_id: near_primitives::types::ShardId,
+        method_name: &str,
+    ) -> anyhow::Result<Option<near_primitives::hash::CryptoHash>> {
+        crate::metrics::META_DATABASE_READ_QUERIES
+            .with_label_values(&[method_name, "skipped_chunks"])
+            .inc();
+        let chunk_hash: Option<(types::Hash,)> = sqlx::query_as(
+            "
+                SELECT chunk_hash
+                FROM skipped_chunks
+                WHERE block_height = $1
+                    AND shard_id = $2
+                LIMIT 1;
+                ",
+        )
+        .bind(types::U64(block_height))
+        .bind(types::U64(shard_id))
+        .fetch_optional(&self.meta_db_pool)
+        .await?;
+        Ok(chunk_hash.map(|(chunk_hash,)| chunk_hash.0))
+    }
+
+    async fn get_top_state_growers(
+        &self,
+        window_days: u64,
+        limit: u64,
+        method_name: &str,
+    ) -> anyhow::Result<(Option<u64>, Vec<readnode_primitives::StateGrowth>)> {
+        crate::metrics::META_DATABASE_READ_QUERIES
+            .with_label_values(&[method_name, "state_growth_days"])
+            .inc();
+        let (latest_day,): (Option<i64>,) = sqlx::query_as(
+            "
+                SELECT MAX(day)
+                FROM state_growth_days;
+                ",
+        )
         .fetch_one(&self.meta_db_pool)
         .await?;
-        readnode_primitives::BlockHeightShardId::try_from(result)
+        let Some(latest_day) = latest_day else {
+            return Ok((None, vec![]));
+        };
+
+        crate::metrics::META_DATABASE_READ_QUERIES
+            .with_label_values(&[method_name, "state_growth"])
+            .inc();
+        let rows: Vec<(types::AccountId, i64, i64)> = sqlx::query_as(
+            "
+                SELECT account_id, SUM(keys_delta)::bigint, SUM(bytes_delta)::bigint AS bytes_delta
+                FROM state_growth
+                WHERE day > $1 AND day <= $2
+                GROUP BY account_id
+                ORDER BY bytes_delta DESC
+                LIMIT $3;
+                ",
+        )
+        .bind(latest_day - window_days as i64)
+        .bind(latest_day)
+        .bind(limit as i64)
+        .fetch_all(&self.meta_db_pool)
+        .await?;
+        let state_growers = rows
+            .into_iter()
+            .map(
+                |(account_id, keys_delta, bytes_delta)| readnode_primitives::StateGrowth {
+                    account_id: account_id.0,
+                    keys_delta,
+                    bytes_delta,
+                },
+            )
+            .collect();
+        Ok((Some(latest_day as u64), state_growers))
+    }
+
+    async fn get_chunk_congestion(
+        &self,
+        block_height: near_primitives::types::BlockHeight,
+        shard_id: near_primitives::types::ShardId,
+        method_name: &str,
+    ) -> anyhow::Result<readnode_primitives::ChunkCongestion> {
+        crate::metrics::META_DATABASE_READ_QUERIES
+            .with_label_values(&[method_name, "chunks_congestion"])
+            .inc();
+        let (height_included, congestion_info): (types::U64, serde_json::Value) = sqlx::query_as(
+            "
+                SELECT height_included, congestion_info
+                FROM chunks_congestion
+                WHERE block_height = $1
+                    AND shard_id = $2
+                LIMIT 1;
+                ",
+        )
+        .bind(types::U64(block_height))
+        .bind(types::U64(shard_id))
+        .fetch_one(&self.meta_db_pool)
+        .await?;
+        Ok(readnode_primitives::ChunkCongestion {
+            height_included: height_included.0,
+            congestion_info: serde_json::from_value(congestion_info)?,
+        })
     }
 
     async fn get_validators_by_epoch_id(
@@ -536,25 +1011,22 @@ impl crate::ReaderDbManager for crate::PostgresDBManager {
         crate::metrics::META_DATABASE_READ_QUERIES
             .with_label_values(&[method_name, "validators"])
             .inc();
-        let (epoch_height, validators_info): (bigdecimal::BigDecimal, serde_json::Value) =
-            sqlx::query_as(
-                "
+        let (epoch_height, validators_info): (types::U64, serde_json::Value) = sqlx::query_as(
+            "
                 SELECT epoch_height, validators_info
                 FROM validators
                 WHERE epoch_id = $1
                 LIMIT 1;
                 ",
-            )
-            .bind(epoch_id.to_string())
-            .fetch_one(&self.meta_db_pool)
-            .await?;
+        )
+        .bind(types::Hash(epoch_id))
+        .fetch_one(&self.meta_db_pool)
+        .await?;
         let validators_info: near_primitives::views::EpochValidatorInfo =
             serde_json::from_value(validators_info)?;
         Ok(readnode_primitives::EpochValidatorsInfo {
             epoch_id,
-            epoch_height: epoch_height
-                .to_u64()
-                .ok_or_else(|| anyhow::anyhow!("Failed to parse `epoch_height` to u64"))?,
+            epoch_height: epoch_height.0,
             epoch_start_height: validators_info.epoch_start_height,
             validators_info,
         })
@@ -569,8 +1041,8 @@ impl crate::ReaderDbManager for crate::PostgresDBManager {
             .with_label_values(&[method_name, "validators"])
             .inc();
         let (epoch_id, epoch_height, validators_info): (
-            String,
-            bigdecimal::BigDecimal,
+            types::Hash,
+            types::U64,
             serde_json::Value,
         ) = sqlx::query_as(
             "
@@ -580,20 +1052,930 @@ impl crate::ReaderDbManager for crate::PostgresDBManager {
                 LIMIT 1;
                 ",
         )
-        .bind(bigdecimal::BigDecimal::from(block_height))
+        .bind(types::U64(block_height))
         .fetch_one(&self.meta_db_pool)
         .await?;
-        let epoch_id = near_primitives::hash::CryptoHash::from_str(&epoch_id)
-            .map_err(|err| anyhow::anyhow!("Failed to parse `epoch_id` to CryptoHash: {}", err))?;
         let validators_info: near_primitives::views::EpochValidatorInfo =
             serde_json::from_value(validators_info)?;
         Ok(readnode_primitives::EpochValidatorsInfo {
-            epoch_id,
-            epoch_height: epoch_height
-                .to_u64()
-                .ok_or_else(|| anyhow::anyhow!("Failed to parse `epoch_height` to u64"))?,
+            epoch_id: epoch_id.0,
+            epoch_height: epoch_height.0,
             epoch_start_height: validators_info.epoch_start_height,
             validators_info,
         })
     }
+
+    async fn get_protocol_config_by_epoch_id(
+        &self,
+        epoch_id: near_primitives::hash::CryptoHash,
+        method_name: &str,
+    ) -> anyhow::Result<near_chain_configs::ProtocolConfigView> {
+        crate::metrics::META_DATABASE_READ_QUERIES
+            .with_label_values(&[method_name, "protocol_configs"])
+            .inc();
+        let (protocol_config,): (serde_json::Value,) = sqlx::query_as(
+            "
+                SELECT protocol_config
+                FROM protocol_configs
+                WHERE epoch_id = $1
+                LIMIT 1;
+                ",
+        )
+        .bind(types::Hash(epoch_id))
+        .fetch_one(&self.meta_db_pool)
+        .await?;
+        Ok(serde_json::from_value(protocol_config)?)
+    }
+
+    async fn get_transactions_by_public_key(
+        &self,
+        public_key: &near_crypto::PublicKey,
+        limit: u64,
+        cursor: crate::PageToken,
+        method_name: &str,
+    ) -> anyhow::Result<(
+        Vec<readnode_primitives::TransactionByPublicKeyRecord>,
+        crate::PageToken,
+    )> {
+        crate::metrics::META_DATABASE_READ_QUERIES
+            .with_label_values(&[method_name, "transactions_by_public_key"])
+            .inc();
+        let mut query_builder: sqlx::QueryBuilder<sqlx::Postgres> = sqlx::QueryBuilder::new(
+            "
+            SELECT public_key, block_height, transaction_hash, signer_id, receiver_id, nonce, block_hash, shard_id, protocol_version, gas_price
+            FROM transactions_by_public_key
+            WHERE public_key = ",
+        );
+        query_builder.push_bind(public_key.to_string());
+        if let Some(cursor) = cursor {
+            let cursor = self
+                .cursor_signer
+                .decode::<crate::postgres::TransactionsCursor>(&cursor)?;
+            query_builder
+                .push(" AND (block_height, transaction_hash) < (")
+                .push_bind(types::U64(cursor.block_height))
+                .push(", ")
+                .push_bind(cursor.transaction_hash)
+                .push(")");
+        }
+        query_builder
+            .push(" ORDER BY block_height DESC, transaction_hash DESC LIMIT ")
+            .push_bind(limit as i64);
+        let transactions: Vec<_> = query_builder
+            .build_query_as::<TransactionByPublicKeyRow>()
+            .fetch_all(&self.meta_db_pool)
+            .await?
+            .into_iter()
+            .map(transaction_by_public_key_record)
+            .collect();
+        let next_cursor = match transactions.last() {
+            Some(last) if transactions.len() as u64 == limit => Some(self.cursor_signer.encode(
+                &crate::postgres::TransactionsCursor {
+                    block_height: last.block_height,
+                    transaction_hash: last.transaction_hash.to_string(),
+                },
+            )?),
+            _ => None,
+        };
+        Ok((transactions, next_cursor))
+    }
+
+    async fn search_function_calls(
+        &self,
+        contract_id: &near_primitives::types::AccountId,
+        contract_method_name: Option<&str>,
+        args_substring: Option<&str>,
+        limit: u64,
+        cursor: crate::PageToken,
+        method_name: &str,
+    ) -> anyhow::Result<(
+        Vec<readnode_primitives::FunctionCallRecord>,
+        crate::PageToken,
+    )> {
+        crate::metrics::META_DATABASE_READ_QUERIES
+            .with_label_values(&[method_name, "function_calls"])
+            .inc();
+        let mut query_builder: sqlx::QueryBuilder<sqlx::Postgres> = sqlx::QueryBuilder::new(
+            "
+            SELECT receipt_id, action_index, block_height, block_hash, predecessor_id, signer_id, receiver_id, method_name, args, msg
+            FROM function_calls
+            WHERE receiver_id = ",
+        );
+        query_builder.push_bind(contract_id.to_string());
+        if let Some(contract_method_name) = contract_method_name {
+            query_builder
+                .push(" AND method_name = ")
+                .push_bind(contract_method_name.to_string());
+        }
+        if let Some(args_substring) = args_substring {
+            // The substring is searched literally, the LIKE wildcards are escaped
+            let pattern = format!(
+                "%{}%",
+                args_substring
+                    .replace('\\', "\\\\")
+                    .replace('%', "\\%")
+                    .replace('_', "\\_")
+            );
+            query_builder
+                .push(" AND (args LIKE ")
+                .push_bind(pattern.clone())
+                .push(" OR msg LIKE ")
+                .push_bind(pattern)
+                .push(")");
+        }
+        if let Some(cursor) = cursor {
+            let cursor = self
+                .cursor_signer
+                .decode::<crate::postgres::FunctionCallsCursor>(&cursor)?;
+            query_builder
+                .push(" AND (block_height, receipt_id, action_index) < (")
+                .push_bind(types::U64(cursor.block_height))
+                .push(", ")
+                .push_bind(cursor.receipt_id)
+                .push(", ")
+                .push_bind(cursor.action_index as i32)
+                .push(")");
+        }
+        query_builder
+            .push(" ORDER BY block_height DESC, receipt_id DESC, action_index DESC LIMIT ")
+            .push_bind(limit as i64);
+        let function_calls = query_builder
+            .build_query_as::<FunctionCallRow>()
+            .fetch_all(&self.meta_db_pool)
+            .await?
+            .into_iter()
+            .map(function_call_record)
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let next_cursor = match function_calls.last() {
+            Some(last) if function_calls.len() as u64 == limit => Some(self.cursor_signer.encode(
+                &crate::postgres::FunctionCallsCursor {
+                    block_height: last.block_height,
+                    receipt_id: last.receipt_id.to_string(),
+                    action_index: last.action_index,
+                },
+            )?),
+            _ => None,
+        };
+        Ok((function_calls, next_cursor))
+    }
+
+    async fn get_actions_by_contract_method(
+        &self,
+        contract_id: &near_primitives::types::AccountId,
+        contract_method_name: &str,
+        from_block_height: Option<near_primitives::types::BlockHeight>,
+        to_block_height: Option<near_primitives::types::BlockHeight>,
+        limit: u64,
+        cursor: crate::PageToken,
+        method_name: &str,
+    ) -> anyhow::Result<(Vec<readnode_primitives::ActionRecord>, crate::PageToken)> {
+        crate::metrics::META_DATABASE_READ_QUERIES
+            .with_label_values(&[method_name, "actions"])
+            .inc();
+        let mut query_builder: sqlx::QueryBuilder<sqlx::Postgres> = sqlx::QueryBuilder::new(
+            "
+            SELECT receipt_id, action_index, block_height, block_hash, predecessor_id, signer_id, receiver_id, action_kind, method_name, deposit, gas, public_key
+            FROM actions
+            WHERE receiver_id = ",
+        );
+        query_builder
+            .push_bind(contract_id.to_string())
+            .push(" AND method_name = ")
+            .push_bind(contract_method_name.to_string());
+        if let Some(from_block_height) = from_block_height {
+            query_builder
+                .push(" AND block_height >= ")
+                .push_bind(types::U64(from_block_height));
+        }
+        if let Some(to_block_height) = to_block_height {
+            query_builder
+                .push(" AND block_height <= ")
+                .push_bind(types::U64(to_block_height));
+        }
+        if let Some(cursor) = cursor {
+            let cursor = self
+                .cursor_signer
+                .decode::<crate::postgres::ActionsCursor>(&cursor)?;
+            query_builder
+                .push(" AND (block_height, receipt_id, action_index) < (")
+                .push_bind(types::U64(cursor.block_height))
+                .push(", ")
+                .push_bind(cursor.receipt_id)
+                .push(", ")
+                .push_bind(cursor.action_index as i32)
+                .push(")");
+        }
+        query_builder
+            .push(" ORDER BY block_height DESC, receipt_id DESC, action_index DESC LIMIT ")
+            .push_bind(limit as i64);
+        let actions = query_builder
+            .build_query_as::<ActionRow>()
+            .fetch_all(&self.meta_db_pool)
+            .await?
+            .into_iter()
+            .map(action_record)
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let next_cursor = match actions.last() {
+            Some(last) if actions.len() as u64 == limit => {
+                Some(self.cursor_signer.encode(&crate::postgres::ActionsCursor {
+                    block_height: last.block_height,
+                    receipt_id: last.receipt_id.to_string(),
+                    action_index: last.action_index,
+                })?)
+            }
+            _ => None,
+        };
+        Ok((actions, next_cursor))
+    }
+
+    async fn get_latest_indexed_block_timestamps(
+        &self,
+        method_name: &str,
+    ) -> anyhow::Result<std::collections::HashMap<String, u64>> {
+        crate::metrics::META_DATABASE_READ_QUERIES
+            .with_label_values(&[method_name, "meta"])
+            .inc();
+        // The indexers scaled in a group process the blocks in parallel,
+        // so the latest block of the whole group is taken
+        Ok(sqlx::query_as::<_, (String, types::U64)>(
+            "
+            SELECT indexer_type, max(last_processed_block_timestamp)
+            FROM meta
+            WHERE indexer_type IS NOT NULL AND last_processed_block_timestamp IS NOT NULL
+            GROUP BY indexer_type;
+            ",
+        )
+        .fetch_all(&self.meta_db_pool)
+        .await?
+        .into_iter()
+        .map(|(indexer_type, block_timestamp)| (indexer_type, block_timestamp.0))
+        .collect())
+    }
+
+    async fn listen_indexer_notifications(
+        &self,
+    ) -> anyhow::Result<
+        futures::stream::BoxStream<'static, anyhow::Result<crate::primitives::IndexerNotification>>,
+    > {
+        super::notifications::listen(&self.meta_db_pool).await
+    }
+
+    async fn get_writer_versions(
+        &self,
+        method_name: &str,
+    ) -> anyhow::Result<Vec<readnode_primitives::WriterVersion>> {
+        crate::metrics::META_DATABASE_READ_QUERIES
+            .with_label_values(&[method_name, "meta"])
+            .inc();
+        // The indexers which haven't processed a block since the versions were introduced
+        // have no versions stored and are skipped
+        sqlx::query_as::<_, (String, Option<String>, String, i32)>(
+            "
+            SELECT indexer_id, indexer_type, crate_version, schema_version
+            FROM meta
+            WHERE schema_version IS NOT NULL AND crate_version IS NOT NULL
+            ORDER BY indexer_id;
+            ",
+        )
+        .fetch_all(&self.meta_db_pool)
+        .await?
+        .into_iter()
+        .map(
+            |(indexer_id, indexer_type, crate_version, schema_version)| {
+                Ok(readnode_primitives::WriterVersion {
+                    indexer_id,
+                    indexer_type,
+                    crate_version,
+                    schema_version: u32::try_from(schema_version)?,
+                })
+            },
+        )
+        .collect()
+    }
+
+    async fn get_network_identity(
+        &self,
+        method_name: &str,
+    ) -> anyhow::Result<Option<readnode_primitives::NetworkIdentity>> {
+        self.fetch_network_identity(method_name).await
+    }
+
+    async fn check_state_key_buckets(&self, method_name: &str) -> anyhow::Result<()> {
+        match self.fetch_state_key_buckets(method_name).await? {
+            Some(stored_state_key_buckets) => super::ensure_same_state_key_buckets(
+                self.state_key_buckets,
+                stored_state_key_buckets,
+            ),
+            None => Ok(()),
+        }
+    }
+
+    async fn get_recent_transactions_by_signer(
+        &self,
+        signer_id: &near_primitives::types::AccountId,
+        public_keys: &[near_crypto::PublicKey],
+        block_height: near_primitives::types::BlockHeight,
+        limit: u64,
+        method_name: &str,
+    ) -> anyhow::Result<Vec<readnode_primitives::TransactionByPublicKeyRecord>> {
+        crate::metrics::META_DATABASE_READ_QUERIES
+            .with_label_values(&[method_name, "transactions_by_public_key"])
+            .inc();
+        // The table is partitioned by the public key, filtering by the keys
+        // reads only their partitions instead of scanning the whole table by the signer
+        let public_keys: Vec<String> = public_keys.iter().map(|key| key.to_string()).collect();
+        let transactions = sqlx::query_as::<_, TransactionByPublicKeyRow>(
+            "
+            SELECT public_key, block_height, transaction_hash, signer_id, receiver_id, nonce, block_hash, shard_id, protocol_version, gas_price
+            FROM transactions_by_public_key
+            WHERE public_key = ANY($1) AND signer_id = $2 AND block_height <= $3
+            ORDER BY block_height DESC, transaction_hash DESC
+            LIMIT $4;
+            ",
+        )
+        .bind(public_keys)
+        .bind(signer_id.to_string())
+        .bind(types::U64(block_height))
+        .bind(limit as i64)
+        .fetch_all(&self.meta_db_pool)
+        .await?
+        .into_iter()
+        .map(transaction_by_public_key_record)
+        .collect();
+        Ok(transactions)
+    }
+
+    async fn get_validators_changes_by_page(
+        &self,
+        limit: u64,
+        page_token: crate::PageToken,
+        method_name: &str,
+    ) -> anyhow::Result<(
+        Vec<readnode_primitives::EpochValidatorsChanges>,
+        crate::PageToken,
+    )> {
+        crate::metrics::META_DATABASE_READ_QUERIES
+            .with_label_values(&[method_name, "validators"])
+            .inc();
+        // Page token is the epoch height of the last returned epoch
+        let before_epoch_height = match page_token {
+            Some(page_token) => {
+                self.cursor_signer
+                    .decode::<crate::postgres::ValidatorsChangesCursor>(&page_token)?
+                    .epoch_height
+            }
+            None => u64::MAX,
+        };
+        // Stake changes are not stored for the epochs indexed before they were introduced,
+        // so we read the full validators info to calculate them
+        let rows: Vec<(
+            types::Hash,
+            types::U64,
+            types::U64,
+            Option<types::U64>,
+            Option<serde_json::Value>,
+            Option<serde_json::Value>,
+            Option<serde_json::Value>,
+        )> = sqlx::query_as(
+            "
+                SELECT epoch_id,
+                    epoch_height,
+                    epoch_start_height,
+                    epoch_end_height,
+                    COALESCE(prev_epoch_kickout, validators_info->'prev_epoch_kickout'),
+                    stake_changes,
+                    CASE WHEN stake_changes IS NULL THEN validators_info END
+                FROM validators
+                WHERE epoch_height < $1
+                ORDER BY epoch_height DESC
+                LIMIT $2;
+                ",
+        )
+        .bind(types::U64(before_epoch_height))
+        .bind(limit as i64)
+        .fetch_all(&self.meta_db_pool)
+        .await?;
+
+        let mut epochs = Vec::with_capacity(rows.len());
+        for (
+            epoch_id,
+            epoch_height,
+            epoch_start_height,
+            epoch_end_height,
+            prev_epoch_kickout,
+            stake_changes,
+            validators_info,
+        ) in rows
+        {
+            let stake_changes = match (stake_changes, validators_info) {
+                (Some(stake_changes), _) => serde_json::from_value(stake_changes)?,
+                (None, Some(validators_info)) => {
+                    readnode_primitives::ValidatorStakeChange::from_validators_info(
+                        &serde_json::from_value(validators_info)?,
+                    )
+                }
+                (None, None) => vec![],
+            };
+            epochs.push(readnode_primitives::EpochValidatorsChanges {
+                epoch_id: epoch_id.0,
+                epoch_height: epoch_height.0,
+                epoch_start_height: epoch_start_height.0,
+                epoch_end_height: epoch_end_height.map(|height| height.0),
+                prev_epoch_kickout: prev_epoch_kickout
+                    .map(serde_json::from_value)
+                    .transpose()?
+                    .unwrap_or_default(),
+                stake_changes,
+            });
+        }
+
+        let next_page_token = match epochs.last() {
+            Some(last) if epochs.len() as u64 == limit => Some(self.cursor_signer.encode(
+                &crate::postgres::ValidatorsChangesCursor {
+                    epoch_height: last.epoch_height,
+                },
+            )?),
+            _ => None,
+        };
+        Ok((epochs, next_page_token))
+    }
+
+    async fn get_produced_blocks(
+        &self,
+        validator_id: &near_primitives::types::AccountId,
+        epoch_id: near_primitives::hash::CryptoHash,
+        method_name: &str,
+    ) -> anyhow::Result<(
+        Vec<readnode_primitives::ProducedBlock>,
+        Vec<readnode_primitives::ProducedChunk>,
+    )> {
+        crate::metrics::META_DATABASE_READ_QUERIES
+            .with_label_values(&[method_name, "block_producers"])
+            .inc();
+        let blocks_future = sqlx::query_as::<_, (types::U64, types::Hash)>(
+            "
+                SELECT block_height, block_hash
+                FROM block_producers
+                WHERE block_producer = $1 AND epoch_id = $2
+                ORDER BY block_height;
+                ",
+        )
+        .bind(validator_id.to_string())
+        .bind(types::Hash(epoch_id))
+        .fetch_all(&self.meta_db_pool);
+
+        crate::metrics::META_DATABASE_READ_QUERIES
+            .with_label_values(&[method_name, "chunk_producers"])
+            .inc();
+        let chunks_future = sqlx::query_as::<_, (types::U64, types::U64, types::Hash)>(
+            "
+                SELECT block_height, shard_id, chunk_hash
+                FROM chunk_producers
+                WHERE chunk_producer = $1 AND epoch_id = $2
+                ORDER BY block_height, shard_id;
+                ",
+        )
+        .bind(validator_id.to_string())
+        .bind(types::Hash(epoch_id))
+        .fetch_all(&self.meta_db_pool);
+
+        let (blocks, chunks) = futures::try_join!(blocks_future, chunks_future)?;
+        let blocks = blocks
+            .into_iter()
+            .map(
+                |(block_height, block_hash)| readnode_primitives::ProducedBlock {
+                    block_height: block_height.0,
+                    block_hash: block_hash.0,
+                },
+            )
+            .collect();
+        let chunks = chunks
+            .into_iter()
+            .map(
+                |(block_height, shard_id, chunk_hash)| readnode_primitives::ProducedChunk {
+                    block_height: block_height.0,
+                    shard_id: shard_id.0,
+                    chunk_hash: chunk_hash.0,
+                },
+            )
+            .collect();
+        Ok((blocks, chunks))
+    }
+}
+
+type ReceiptRow = (
+    types::Hash,
+    types::Hash,
+    types::AccountId,
+    types::U64,
+    types::Hash,
+    types::U64,
+    Option<i32>,
+    Option<Vec<u8>>,
+);
+
+fn receipt_record(row: ReceiptRow) -> anyhow::Result<readnode_primitives::ReceiptRecord> {
+    let (
+        receipt_id,
+        parent_transaction_hash,
+        receiver_id,
+        block_height,
+        block_hash,
+        shard_id,
+        index_in_chunk,
+        receipt,
+    ) = row;
+    Ok(readnode_primitives::ReceiptRecord {
+        receipt_id: receipt_id.0,
+        parent_transaction_hash: parent_transaction_hash.0,
+        receiver_id: receiver_id.0,
+        block_height: block_height.0,
+        block_hash: block_hash.0,
+        shard_id: shard_id.0,
+        index_in_chunk: index_in_chunk
+            .map(u32::try_from)
+            .transpose()
+            .map_err(|err| anyhow::anyhow!("Failed to parse `index_in_chunk` to u32: {}", err))?,
+        receipt: receipt
+            .map(|receipt| {
+                readnode_primitives::blob::borsh_from_slice::<near_primitives::views::ReceiptView>(
+                    &receipt,
+                )
+            })
+            .transpose()
+            .map_err(|err| anyhow::anyhow!("Failed to decode the receipt body: {}", err))?,
+    })
+}
+
+type TransactionByPublicKeyRow = (
+    String,
+    types::U64,
+    types::Hash,
+    types::AccountId,
+    types::AccountId,
+    types::U64,
+    types::Hash,
+    types::U64,
+    Option<types::U32>,
+    Option<types::U128>,
+);
+
+fn transaction_by_public_key_record(
+    row: TransactionByPublicKeyRow,
+) -> readnode_primitives::TransactionByPublicKeyRecord {
+    let (
+        public_key,
+        block_height,
+        transaction_hash,
+        signer_id,
+        receiver_id,
+        nonce,
+        block_hash,
+        shard_id,
+        protocol_version,
+        gas_price,
+    ) = row;
+    readnode_primitives::TransactionByPublicKeyRecord {
+        public_key,
+        transaction_hash: transaction_hash.0,
+        signer_id: signer_id.0,
+        receiver_id: receiver_id.0,
+        nonce: nonce.0,
+        block_height: block_height.0,
+        block_hash: block_hash.0,
+        shard_id: shard_id.0,
+        protocol_version: protocol_version.map(|protocol_version| protocol_version.0),
+        gas_price: gas_price.map(|gas_price| gas_price.0),
+    }
+}
+
+type ActionRow = (
+    types::Hash,
+    i32,
+    types::U64,
+    types::Hash,
+    types::AccountId,
+    types::AccountId,
+    types::AccountId,
+    String,
+    Option<String>,
+    Option<types::U128>,
+    Option<types::U64>,
+    Option<String>,
+);
+
+fn action_record(row: ActionRow) -> anyhow::Result<readnode_primitives::ActionRecord> {
+    let (
+        receipt_id,
+        action_index,
+        block_height,
+        block_hash,
+        predecessor_id,
+        signer_id,
+        receiver_id,
+        action_kind,
+        method_name,
+        deposit,
+        gas,
+        public_key,
+    ) = row;
+    Ok(readnode_primitives::ActionRecord {
+        receipt_id: receipt_id.0,
+        action_index: u32::try_from(action_index)
+            .map_err(|err| anyhow::anyhow!("Failed to parse `action_index` to u32: {}", err))?,
+        block_height: block_height.0,
+        block_hash: block_hash.0,
+        predecessor_id: predecessor_id.0,
+        signer_id: signer_id.0,
+        receiver_id: receiver_id.0,
+        action_kind,
+        method_name,
+        deposit: deposit.map(|deposit| deposit.0),
+        gas: gas.map(|gas| gas.0),
+        public_key,
+    })
+}
+
+type FunctionCallRow = (
+    types::Hash,
+    i32,
+    types::U64,
+    types::Hash,
+    types::AccountId,
+    types::AccountId,
+    types::AccountId,
+    String,
+    Option<String>,
+    Option<String>,
+);
+
+fn function_call_record(
+    row: FunctionCallRow,
+) -> anyhow::Result<readnode_primitives::FunctionCallRecord> {
+    let (
+        receipt_id,
+        action_index,
+        block_height,
+        block_hash,
+        predecessor_id,
+        signer_id,
+        receiver_id,
+        method_name,
+        args,
+        msg,
+    ) = row;
+    Ok(readnode_primitives::FunctionCallRecord {
+        receipt_id: receipt_id.0,
+        action_index: u32::try_from(action_index)
+            .map_err(|err| anyhow::anyhow!("Failed to parse `action_index` to u32: {}", err))?,
+        block_height: block_height.0,
+        block_hash: block_hash.0,
+        predecessor_id: predecessor_id.0,
+        signer_id: signer_id.0,
+        receiver_id: receiver_id.0,
+        method_name,
+        args,
+        msg,
+    })
+}
+
+// We need to query all shards because we don't know which shard the receipt is stored in
+// and we need to return the receipt as soon as we find it.
+// Query all shards in parallel and then we wait for the first result.
+async fn find_receipt<'a>(
+    pools: impl Iterator<
+        Item = (
+            &'a near_primitives::types::ShardId,
+            &'a sqlx::Pool<sqlx::Postgres>,
+        ),
+    >,
+    receipt_id: near_primitives::hash::CryptoHash,
+    method_name: &str,
+) -> Option<ReceiptRow> {
+    let futures = pools.map(|(shard_id, pool)| {
+        crate::metrics::SHARD_DATABASE_READ_QUERIES
+            .with_label_values(&[&shard_id.to_string(), method_name, "receipts_map"])
+            .inc();
+        sqlx::query_as::<_, ReceiptRow>(
+            "
+            SELECT receipt_id, 
+                parent_transaction_hash, 
+                receiver_id, 
+                block_height, 
+                block_hash, 
+                shard_id,
+                index_in_chunk,
+                receipt
+            FROM receipts_map
+            WHERE receipt_id = $1
+            LIMIT 1;
+            ",
+        )
+        .bind(types::Hash(receipt_id))
+        .fetch_one(pool)
+    });
+    let mut tasks = futures::stream::FuturesUnordered::from_iter(futures);
+    while let Some(result) = tasks.next().await {
+        if let Ok(row) = result {
+            return Some(row);
+        }
+    }
+    None
+}
+
+// Looks up the receipts on all the shards at once, one query per shard.
+// The rows are keyed by the receipt id
+async fn find_receipts<'a>(
+    pools: impl Iterator<
+        Item = (
+            &'a near_primitives::types::ShardId,
+            &'a sqlx::Pool<sqlx::Postgres>,
+        ),
+    >,
+    receipt_ids: &[near_primitives::hash::CryptoHash],
+    method_name: &str,
+) -> anyhow::Result<Vec<(near_primitives::hash::CryptoHash, ReceiptRow)>> {
+    let receipt_ids = receipt_ids
+        .iter()
+        .map(|receipt_id| receipt_id.to_string())
+        .collect::<Vec<_>>();
+    let futures = pools.map(|(shard_id, pool)| {
+        crate::metrics::SHARD_DATABASE_READ_QUERIES
+            .with_label_values(&[&shard_id.to_string(), method_name, "receipts_map"])
+            .inc();
+        sqlx::query_as::<_, ReceiptRow>(
+            "
+            SELECT receipt_id,
+                parent_transaction_hash,
+                receiver_id,
+                block_height,
+                block_hash,
+                shard_id,
+                index_in_chunk,
+                receipt
+            FROM receipts_map
+            WHERE receipt_id = ANY($1);
+            ",
+        )
+        .bind(&receipt_ids)
+        .fetch_all(pool)
+    });
+    let mut rows = vec![];
+    for shard_rows in futures::future::try_join_all(futures).await? {
+        rows.extend(shard_rows.into_iter().map(|row| (row.0 .0, row)));
+    }
+    Ok(rows)
+}
+
+// Reads the changes of all the state tables of the shard stored at the block height.
+// The rows of every table are ordered by the key, so the changes keep the order of the trie keys.
+// Returns `None` if the block is older than the access key changes log of the shard,
+// the unchanged access keys of such block are not stored and the changes would be incomplete
+async fn get_shard_state_changes_in_block(
+    shard_id: near_primitives::types::ShardId,
+    pool: &sqlx::Pool<sqlx::Postgres>,
+    block_height: near_primitives::types::BlockHeight,
+    method_name: &str,
+) -> anyhow::Result<Option<Vec<near_primitives::views::StateChangeValueView>>> {
+    let shard_id = shard_id.to_string();
+    let block_height = types::U64(block_height);
+    let mut changes = vec![];
+
+    crate::metrics::SHARD_DATABASE_READ_QUERIES
+        .with_label_values(&[&shard_id, method_name, "state_changes_access_key_log"])
+        .inc();
+    let (first_logged_block_height,): (Option<types::U64>,) =
+        sqlx::query_as("SELECT MIN(block_height) FROM state_changes_access_key_log;")
+            .fetch_one(pool)
+            .await?;
+    if first_logged_block_height.map_or(true, |first_logged_block_height| {
+        first_logged_block_height.0 > block_height.0
+    }) {
+        return Ok(None);
+    }
+
+    crate::metrics::SHARD_DATABASE_READ_QUERIES
+        .with_label_values(&[&shard_id, method_name, "state_changes_account"])
+        .inc();
+    let rows: Vec<(types::AccountId, Option<Vec<u8>>)> = sqlx::query_as(
+        "
+        SELECT account_id, data_value
+        FROM state_changes_account
+        WHERE block_height = $1
+        ORDER BY account_id;
+        ",
+    )
+    .bind(block_height)
+    .fetch_all(pool)
+    .await?;
+    for (types::AccountId(account_id), data_value) in rows {
+        changes.push(match data_value {
+            Some(data_value) => near_primitives::views::StateChangeValueView::AccountUpdate {
+                account_id,
+                account: near_primitives::views::AccountView::from(readnode_primitives::blob::borsh_from_slice::<
+                    near_primitives::account::Account,
+                >(&data_value)?),
+            },
+            None => near_primitives::views::StateChangeValueView::AccountDeletion { account_id },
+        });
+    }
+
+    crate::metrics::SHARD_DATABASE_READ_QUERIES
+        .with_label_values(&[&shard_id, method_name, "state_changes_contract"])
+        .inc();
+    let rows: Vec<(types::AccountId, Option<Vec<u8>>)> = sqlx::query_as(
+        "
+        SELECT account_id, data_value
+        FROM state_changes_contract
+        WHERE block_height = $1
+        ORDER BY account_id;
+        ",
+    )
+    .bind(block_height)
+    .fetch_all(pool)
+    .await?;
+    for (types::AccountId(account_id), data_value) in rows {
+        changes.push(match data_value {
+            Some(code) => near_primitives::views::StateChangeValueView::ContractCodeUpdate {
+                account_id,
+                code,
+            },
+            None => {
+                near_primitives::views::StateChangeValueView::ContractCodeDeletion { account_id }
+            }
+        });
+    }
+
+    // The unchanged access keys are not stored at the block,
+    // the value of every logged change is the latest stored one at or before the block
+    crate::metrics::SHARD_DATABASE_READ_QUERIES
+        .with_label_values(&[&shard_id, method_name, "state_changes_access_key"])
+        .inc();
+    let rows: Vec<(types::AccountId, String, Option<Vec<u8>>)> = sqlx::query_as(
+        "
+        SELECT log.account_id, log.data_key, sc.data_value
+        FROM state_changes_access_key_log log
+        CROSS JOIN LATERAL (
+            SELECT data_value
+            FROM state_changes_access_key
+            WHERE account_id = log.account_id
+                AND data_key = log.data_key
+                AND block_height <= log.block_height
+            ORDER BY block_height DESC
+            LIMIT 1
+        ) sc
+        WHERE log.block_height = $1
+        ORDER BY log.account_id, log.data_key;
+        ",
+    )
+    .bind(block_height)
+    .fetch_all(pool)
+    .await?;
+    for (types::AccountId(account_id), data_key, data_value) in rows {
+        let public_key = readnode_primitives::blob::borsh_from_slice::<near_crypto::PublicKey>(
+            &readnode_primitives::encoding::decode_hex(&data_key)?,
+        )?;
+        changes.push(match data_value {
+            Some(data_value) => near_primitives::views::StateChangeValueView::AccessKeyUpdate {
+                account_id,
+                public_key,
+                access_key: near_primitives::views::AccessKeyView::from(
+                    readnode_primitives::blob::borsh_from_slice::<
+                        near_primitives::account::AccessKey,
+                    >(&data_value)?,
+                ),
+            },
+            None => near_primitives::views::StateChangeValueView::AccessKeyDeletion {
+                account_id,
+                public_key,
+            },
+        });
+    }
+
+    crate::metrics::SHARD_DATABASE_READ_QUERIES
+        .with_label_values(&[&shard_id, method_name, "state_changes_data"])
+        .inc();
+    let rows: Vec<(types::AccountId, String, Option<Vec<u8>>)> = sqlx::query_as(
+        "
+        SELECT DISTINCT ON (account_id, data_key) account_id, data_key, data_value
+        FROM state_changes_data_all
+        WHERE block_height = $1
+        ORDER BY account_id, data_key;
+        ",
+    )
+    .bind(block_height)
+    .fetch_all(pool)
+    .await?;
+    for (types::AccountId(account_id), data_key, data_value) in rows {
+        let key = readnode_primitives::StateKey::from_hex(&data_key)?.into();
+        changes.push(match data_value {
+            Some(value) => near_primitives::views::StateChangeValueView::DataUpdate {
+                account_id,
+                key,
+                value: value.into(),
+            },
+            None => near_primitives::views::StateChangeValueView::DataDeletion { account_id, key },
+        });
+    }
+    Ok(Some(changes))
 }
@@ -1,6 +1,64 @@
-use bigdecimal::ToPrimitive;
 use futures::FutureExt;
 
+// Number of the state growth rows inserted by one query
+const STATE_GROWTH_BATCH_SIZE: usize = 10_000;
+// Number of the state rows moved into the buckets by one query,
+// bounded by the limit of the query parameters
+const STATE_REBALANCE_INSERT_BATCH_SIZE: usize = 5_000;
+
+/// Stores the last processed block of the indexer of the type
+/// and notifies the rpc-servers about it
+async fn update_indexer_meta(
+    meta_db_pool: &sqlx::Pool<sqlx::Postgres>,
+    indexer_type: &str,
+    indexer_id: &str,
+    block_height: u64,
+    block_timestamp: u64,
+) -> anyhow::Result<()> {
+    crate::metrics::META_DATABASE_WRITE_QUERIES
+        .with_label_values(&["update_meta", "meta"])
+        .inc();
+    sqlx::query(
+        "
+        INSERT INTO meta (indexer_id, last_processed_block_height, last_processed_block_timestamp, indexer_type, crate_version, schema_version)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        ON CONFLICT (indexer_id)
+        DO UPDATE SET
+            last_processed_block_height = $2,
+            last_processed_block_timestamp = $3,
+            indexer_type = $4,
+            crate_version = $5,
+            schema_version = $6;
+        ",
+    )
+    .bind(indexer_id)
+    .bind(bigdecimal::BigDecimal::from(block_height))
+    .bind(bigdecimal::BigDecimal::from(block_timestamp))
+    .bind(indexer_type)
+    .bind(env!("CARGO_PKG_VERSION"))
+    .bind(crate::SCHEMA_VERSION as i32)
+    .execute(meta_db_pool)
+    .await?;
+    // The rpc-servers fall back to the polling of the meta table,
+    // so the failed notification doesn't stop the indexer
+    if let Err(err) =
+        super::notifications::notify_head(meta_db_pool, indexer_type, block_height, block_timestamp)
+            .await
+    {
+        tracing::warn!(
+            "Failed to notify the rpc-servers about the block: {:?}",
+            err
+        );
+    }
+    Ok(())
+}
+
+/// The meta row is updated once the block is saved completely, so the day
+/// ending at `day_end` is indexed once a block of the next day is processed
+fn is_day_indexed(day_end: u64, last_processed_block_timestamp: Option<u64>) -> bool {
+    last_processed_block_timestamp.is_some_and(|block_timestamp| block_timestamp >= day_end)
+}
+
 impl crate::PostgresDBManager {
     async fn save_chunks_unique(
         &self,
@@ -73,6 +131,75 @@ impl crate::PostgresDBManager {
         }
         Ok(())
     }
+
+    // Returns the sha256 hashes of the latest access keys values stored before the block height
+    // with the block height they are stored at.
+    // `None` hash for the deleted keys, the keys never stored are absent
+    async fn get_access_keys_values_hashes(
+        shard_id: near_primitives::types::ShardId,
+        pool: &sqlx::Pool<sqlx::Postgres>,
+        rows: &[(String, String, Option<Vec<u8>>)],
+        block_height: u64,
+    ) -> anyhow::Result<
+        std::collections::HashMap<
+            (String, String),
+            (u64, Option<near_primitives::hash::CryptoHash>),
+        >,
+    > {
+        crate::metrics::SHARD_DATABASE_READ_QUERIES
+            .with_label_values(&[
+                &shard_id.to_string(),
+                "save_state_changes_access_key",
+                "state_changes_access_key",
+            ])
+            .inc();
+        let (account_ids, data_keys): (Vec<String>, Vec<String>) = rows
+            .iter()
+            .map(|(account_id, data_key, _)| (account_id.clone(), data_key.clone()))
+            .unzip();
+        let previous_values: Vec<(String, String, super::types::U64, Option<Vec<u8>>)> =
+            sqlx::query_as(
+                "
+                SELECT DISTINCT ON (sc.account_id, sc.data_key)
+                    sc.account_id,
+                    sc.data_key,
+                    sc.block_height,
+                    sha256(sc.data_value)
+                FROM state_changes_access_key sc
+                INNER JOIN UNNEST($1::text[], $2::text[]) AS keys(account_id, data_key)
+                ON sc.account_id = keys.account_id AND sc.data_key = keys.data_key
+                WHERE sc.block_height < $3
+                ORDER BY sc.account_id, sc.data_key, sc.block_height DESC;
+                ",
+            )
+            .bind(account_ids)
+            .bind(data_keys)
+            .bind(bigdecimal::BigDecimal::from(block_height))
+            .fetch_all(pool)
+            .await?;
+        previous_values
+            .into_iter()
+            .map(
+                |(account_id, data_key, previous_block_height, value_hash)| {
+                    let value_hash = value_hash
+                        .map(|value_hash| {
+                            near_primitives::hash::CryptoHash::try_from(value_hash.as_slice())
+                                .map_err(|err| {
+                                    anyhow::anyhow!(
+                                        "Failed to parse access key value hash: {}",
+                                        err
+                                    )
+                                })
+                        })
+                        .transpose()?;
+                    Ok((
+                        (account_id, data_key),
+                        (previous_block_height.0, value_hash),
+                    ))
+                },
+            )
+            .collect()
+    }
 }
 #[async_trait::async_trait]
 impl crate::StateIndexerDbManager for crate::PostgresDBManager {
@@ -80,18 +207,20 @@ impl crate::StateIndexerDbManager for crate::PostgresDBManager {
         &self,
         block_height: u64,
         block_hash: near_primitives::hash::CryptoHash,
+        block_timestamp: u64,
     ) -> anyhow::Result<()> {
         crate::metrics::META_DATABASE_WRITE_QUERIES
             .with_label_values(&["save_block", "blocks"])
             .inc();
         sqlx::query(
             "
-            INSERT INTO blocks (block_height, block_hash)
-            VALUES ($1, $2) ON CONFLICT DO NOTHING;
+            INSERT INTO blocks (block_height, block_hash, block_timestamp)
+            VALUES ($1, $2, $3) ON CONFLICT DO NOTHING;
             ",
         )
         .bind(bigdecimal::BigDecimal::from(block_height))
         .bind(block_hash.to_string())
+        .bind(bigdecimal::BigDecimal::from(block_timestamp))
         .execute(&self.meta_db_pool)
         .await?;
         Ok(())
@@ -118,54 +247,350 @@ impl crate::StateIndexerDbManager for crate::PostgresDBManager {
         .collect::<anyhow::Result<()>>()
     }
 
-    async fn get_block_height_by_hash(
+    async fn save_block_producers(
         &self,
+        block_height: u64,
         block_hash: near_primitives::hash::CryptoHash,
-        method_name: &str,
-    ) -> anyhow::Result<u64> {
-        crate::metrics::META_DATABASE_READ_QUERIES
-            .with_label_values(&[method_name, "blocks"])
+        epoch_id: near_primitives::hash::CryptoHash,
+        block_producer: &near_primitives::types::AccountId,
+        chunk_producers: Vec<(
+            crate::primitives::ShardId,
+            crate::primitives::ChunkHash,
+            near_primitives::types::AccountId,
+        )>,
+    ) -> anyhow::Result<()> {
+        crate::metrics::META_DATABASE_WRITE_QUERIES
+            .with_label_values(&["save_block_producers", "block_producers"])
             .inc();
-        let (block_height,): (bigdecimal::BigDecimal,) = sqlx::query_as(
+        sqlx::query(
             "
-                SELECT block_height
-                FROM blocks
-                WHERE block_hash = $1
-                LIMIT 1;
-                ",
+            INSERT INTO block_producers (block_height, block_hash, epoch_id, block_producer)
+            VALUES ($1, $2, $3, $4) ON CONFLICT DO NOTHING;
+            ",
         )
+        .bind(bigdecimal::BigDecimal::from(block_height))
         .bind(block_hash.to_string())
+        .bind(epoch_id.to_string())
+        .bind(block_producer.to_string())
+        .execute(&self.meta_db_pool)
+        .await?;
+
+        if !chunk_producers.is_empty() {
+            crate::metrics::META_DATABASE_WRITE_QUERIES
+                .with_label_values(&["save_block_producers", "chunk_producers"])
+                .inc();
+            let mut query_builder: sqlx::QueryBuilder<sqlx::Postgres> = sqlx::QueryBuilder::new(
+                "INSERT INTO chunk_producers (block_height, shard_id, chunk_hash, epoch_id, chunk_producer) ",
+            );
+            query_builder.push_values(
+                chunk_producers.iter(),
+                |mut values, (shard_id, chunk_hash, chunk_producer)| {
+                    values
+                        .push_bind(bigdecimal::BigDecimal::from(block_height))
+                        .push_bind(bigdecimal::BigDecimal::from(*shard_id))
+                        .push_bind(chunk_hash.to_string())
+                        .push_bind(epoch_id.to_string())
+                        .push_bind(chunk_producer.to_string());
+                },
+            );
+            query_builder.push(" ON CONFLICT DO NOTHING;");
+            query_builder.build().execute(&self.meta_db_pool).await?;
+        }
+        Ok(())
+    }
+
+    async fn get_latest_state_growth_day(&self) -> anyhow::Result<Option<u64>> {
+        crate::metrics::META_DATABASE_READ_QUERIES
+            .with_label_values(&["get_latest_state_growth_day", "state_growth_days"])
+            .inc();
+        let (day,): (Option<i64>,) = sqlx::query_as(
+            "
+            SELECT MAX(day)
+            FROM state_growth_days;
+            ",
+        )
         .fetch_one(&self.meta_db_pool)
         .await?;
-        block_height
-            .to_u64()
-            .ok_or_else(|| anyhow::anyhow!("Failed to parse `block_height` to u64"))
+        Ok(day.map(|day| day as u64))
     }
 
-    async fn update_meta(&self, indexer_id: &str, block_height: u64) -> anyhow::Result<()> {
+    async fn aggregate_state_growth(
+        &self,
+        day: u64,
+    ) -> anyhow::Result<Option<readnode_primitives::StateGrowthDay>> {
+        let day_start = day * crate::postgres::NANOSECONDS_PER_DAY;
+        let day_end = day_start + crate::postgres::NANOSECONDS_PER_DAY;
+        crate::metrics::META_DATABASE_READ_QUERIES
+            .with_label_values(&["aggregate_state_growth", "meta"])
+            .inc();
+        // The indexers scaled in a group process the blocks in parallel,
+        // so the day is indexed once every indexer of the group has passed it
+        let (last_processed_block_timestamp,): (Option<super::types::U64>,) = sqlx::query_as(
+            "
+            SELECT min(last_processed_block_timestamp)
+            FROM meta
+            WHERE indexer_type = 'state';
+            ",
+        )
+        .fetch_one(&self.meta_db_pool)
+        .await?;
+        if !is_day_indexed(
+            day_end,
+            last_processed_block_timestamp.map(|block_timestamp| block_timestamp.0),
+        ) {
+            return Ok(None);
+        }
+
+        crate::metrics::META_DATABASE_READ_QUERIES
+            .with_label_values(&["aggregate_state_growth", "blocks"])
+            .inc();
+        let (from_block_height, to_block_height): (
+            Option<bigdecimal::BigDecimal>,
+            Option<bigdecimal::BigDecimal>,
+        ) = sqlx::query_as(
+            "
+            SELECT
+                (SELECT MIN(block_height) FROM blocks WHERE block_timestamp >= $1 AND block_timestamp < $2),
+                (SELECT MAX(block_height) FROM blocks WHERE block_timestamp >= $1 AND block_timestamp < $2);
+            ",
+        )
+        .bind(bigdecimal::BigDecimal::from(day_start))
+        .bind(bigdecimal::BigDecimal::from(day_end))
+        .fetch_one(&self.meta_db_pool)
+        .await?;
+
+        let mut state_growth = vec![];
+        if let (Some(from_block_height), Some(to_block_height)) =
+            (from_block_height, to_block_height)
+        {
+            for (shard_id, pool) in self.shards_pool.iter() {
+                crate::metrics::SHARD_DATABASE_READ_QUERIES
+                    .with_label_values(&[
+                        &shard_id.to_string(),
+                        "aggregate_state_growth",
+                        "state_changes_data",
+                    ])
+                    .inc();
+                // The latest value of every key changed within the day is compared
+                // with its latest value before the day, the key size is counted for
+                // the added and removed keys only
+                let rows: Vec<(String, i64, i64)> = sqlx::query_as(
+                    "
+                    WITH day_changes AS (
+                        SELECT DISTINCT ON (account_id, data_key)
+                            account_id,
+                            data_key,
+                            data_value
+                        FROM state_changes_data_all
+                        WHERE block_height >= $1 AND block_height <= $2
+                        ORDER BY account_id, data_key, block_height DESC
+                    )
+                    SELECT
+                        dc.account_id,
+                        SUM(
+                            CASE
+                                WHEN dc.data_value IS NOT NULL AND prev.data_value IS NULL THEN 1
+                                WHEN dc.data_value IS NULL AND prev.data_value IS NOT NULL THEN -1
+                                ELSE 0
+                            END
+                        )::bigint,
+                        SUM(
+                            CASE WHEN dc.data_value IS NOT NULL
+                                THEN length(dc.data_key) / 2 + octet_length(dc.data_value)
+                                ELSE 0
+                            END
+                            - CASE WHEN prev.data_value IS NOT NULL
+                                THEN length(dc.data_key) / 2 + octet_length(prev.data_value)
+                                ELSE 0
+                            END
+                        )::bigint
+                    FROM day_changes dc
+                    LEFT JOIN LATERAL (
+                        SELECT sc.data_value
+                        FROM state_changes_data_all sc
+                        WHERE sc.account_id = dc.account_id
+                            AND sc.data_key = dc.data_key
+                            AND sc.block_height < $1
+                        ORDER BY sc.block_height DESC
+                        LIMIT 1
+                    ) prev ON true
+                    GROUP BY dc.account_id;
+                    ",
+                )
+                .bind(&from_block_height)
+                .bind(&to_block_height)
+                .fetch_all(pool)
+                .await?;
+                state_growth.extend(rows.into_iter().map(
+                    |(account_id, keys_delta, bytes_delta)| {
+                        (*shard_id, account_id, keys_delta, bytes_delta)
+                    },
+                ));
+            }
+        }
+
+        // The rows are replaced, so the day aggregated again gets the same result
+        for state_growth_batch in state_growth.chunks(STATE_GROWTH_BATCH_SIZE) {
+            crate::metrics::META_DATABASE_WRITE_QUERIES
+                .with_label_values(&["aggregate_state_growth", "state_growth"])
+                .inc();
+            let mut query_builder: sqlx::QueryBuilder<sqlx::Postgres> = sqlx::QueryBuilder::new(
+                "INSERT INTO state_growth (day, account_id, shard_id, keys_delta, bytes_delta) ",
+            );
+            query_builder.push_values(
+                state_growth_batch.iter(),
+                |mut values, (shard_id, account_id, keys_delta, bytes_delta)| {
+                    values
+                        .push_bind(day as i64)
+                        .push_bind(account_id)
+                        .push_bind(bigdecimal::BigDecimal::from(*shard_id))
+                        .push_bind(keys_delta)
+                        .push_bind(bytes_delta);
+                },
+            );
+            query_builder.push(
+                " ON CONFLICT (day, account_id, shard_id) DO UPDATE SET keys_delta = EXCLUDED.keys_delta, bytes_delta = EXCLUDED.bytes_delta;",
+            );
+            query_builder.build().execute(&self.meta_db_pool).await?;
+        }
+
+        let accounts_count = state_growth
+            .iter()
+            .map(|(_shard_id, account_id, _keys_delta, _bytes_delta)| account_id)
+            .collect::<std::collections::HashSet<_>>()
+            .len() as u64;
         crate::metrics::META_DATABASE_WRITE_QUERIES
-            .with_label_values(&["update_meta", "meta"])
+            .with_label_values(&["aggregate_state_growth", "state_growth_days"])
             .inc();
         sqlx::query(
             "
-            INSERT INTO meta (indexer_id, last_processed_block_height)
+            INSERT INTO state_growth_days (day, accounts_count)
             VALUES ($1, $2)
-            ON CONFLICT (indexer_id)
-            DO UPDATE SET last_processed_block_height = $2;
+            ON CONFLICT (day) DO UPDATE SET accounts_count = EXCLUDED.accounts_count;
             ",
         )
-        .bind(indexer_id)
-        .bind(bigdecimal::BigDecimal::from(block_height))
+        .bind(day as i64)
+        .bind(accounts_count as i64)
         .execute(&self.meta_db_pool)
         .await?;
+
+        let top_grower = state_growth
+            .into_iter()
+            .max_by_key(|(_shard_id, _account_id, _keys_delta, bytes_delta)| *bytes_delta)
+            .map(|(_shard_id, account_id, keys_delta, bytes_delta)| {
+                Ok::<_, anyhow::Error>(readnode_primitives::StateGrowth {
+                    account_id: account_id.parse()?,
+                    keys_delta,
+                    bytes_delta,
+                })
+            })
+            .transpose()?;
+        Ok(Some(readnode_primitives::StateGrowthDay {
+            day,
+            accounts_count,
+            top_grower,
+        }))
+    }
+
+    async fn save_chunks_congestion(
+        &self,
+        block_height: u64,
+        chunks_congestion: Vec<(
+            crate::primitives::ShardId,
+            readnode_primitives::ChunkCongestion,
+        )>,
+    ) -> anyhow::Result<()> {
+        if chunks_congestion.is_empty() {
+            return Ok(());
+        }
+        crate::metrics::META_DATABASE_WRITE_QUERIES
+            .with_label_values(&["save_chunks_congestion", "chunks_congestion"])
+            .inc();
+        let chunks_congestion = chunks_congestion
+            .into_iter()
+            .map(|(shard_id, chunk_congestion)| {
+                serde_json::to_value(&chunk_congestion.congestion_info).map(|congestion_info| {
+                    (shard_id, chunk_congestion.height_included, congestion_info)
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut query_builder: sqlx::QueryBuilder<sqlx::Postgres> = sqlx::QueryBuilder::new(
+            "INSERT INTO chunks_congestion (block_height, shard_id, height_included, congestion_info) ",
+        );
+        query_builder.push_values(
+            chunks_congestion.iter(),
+            |mut values, (shard_id, height_included, congestion_info)| {
+                values
+                    .push_bind(bigdecimal::BigDecimal::from(block_height))
+                    .push_bind(bigdecimal::BigDecimal::from(*shard_id))
+                    .push_bind(bigdecimal::BigDecimal::from(*height_included))
+                    .push_bind(congestion_info);
+            },
+        );
+        query_builder.push(" ON CONFLICT DO NOTHING;");
+        query_builder.build().execute(&self.meta_db_pool).await?;
         Ok(())
     }
 
+    async fn get_block_height_by_hash(
+        &self,
+        block_hash: near_primitives::hash::CryptoHash,
+        method_name: &str,
+    ) -> anyhow::Result<u64> {
+        crate::metrics::META_DATABASE_READ_QUERIES
+            .with_label_values(&[method_name, "blocks"])
+            .inc();
+        let (block_height,): (super::types::U64,) = sqlx::query_as(
+            "
+                SELECT block_height
+                FROM blocks
+                WHERE block_hash = $1
+                LIMIT 1;
+                ",
+        )
+        .bind(super::types::Hash(block_hash))
+        .fetch_one(&self.meta_db_pool)
+        .await?;
+        Ok(block_height.0)
+    }
+
+    async fn update_meta(
+        &self,
+        indexer_id: &str,
+        block_height: u64,
+        block_timestamp: u64,
+    ) -> anyhow::Result<()> {
+        update_indexer_meta(
+            &self.meta_db_pool,
+            "state",
+            indexer_id,
+            block_height,
+            block_timestamp,
+        )
+        .await
+    }
+
+    async fn update_epoch_meta(
+        &self,
+        indexer_id: &str,
+        block_height: u64,
+        block_timestamp: u64,
+    ) -> anyhow::Result<()> {
+        update_indexer_meta(
+            &self.meta_db_pool,
+            "epoch",
+            indexer_id,
+            block_height,
+            block_timestamp,
+        )
+        .await
+    }
+
     async fn get_last_processed_block_height(&self, indexer_id: &str) -> anyhow::Result<u64> {
         crate::metrics::META_DATABASE_READ_QUERIES
             .with_label_values(&["get_last_processed_block_height", "meta"])
             .inc();
-        let (last_processed_block_height,): (bigdecimal::BigDecimal,) = sqlx::query_as(
+        let (last_processed_block_height,): (super::types::U64,) = sqlx::query_as(
             "
             SELECT last_processed_block_height
             FROM meta
@@ -176,9 +601,21 @@ impl crate::StateIndexerDbManager for crate::PostgresDBManager {
         .bind(indexer_id)
         .fetch_one(&self.meta_db_pool)
         .await?;
-        last_processed_block_height
-            .to_u64()
-            .ok_or_else(|| anyhow::anyhow!("Failed to parse `last_processed_block_height` to u64"))
+        Ok(last_processed_block_height.0)
+    }
+
+    async fn save_network_identity(
+        &self,
+        network: &readnode_primitives::NetworkIdentity,
+    ) -> anyhow::Result<readnode_primitives::NetworkIdentity> {
+        self.insert_network_identity(network).await
+    }
+
+    async fn save_state_key_buckets(&self) -> anyhow::Result<()> {
+        super::ensure_same_state_key_buckets(
+            self.state_key_buckets,
+            self.insert_state_key_buckets().await?,
+        )
     }
 
     async fn save_validators(
@@ -195,10 +632,12 @@ impl crate::StateIndexerDbManager for crate::PostgresDBManager {
         let epoch_end_block_height = self
             .get_block_height_by_hash(epoch_end_block_hash, "add_validators")
             .await?;
+        let stake_changes =
+            readnode_primitives::ValidatorStakeChange::from_validators_info(validators_info);
         sqlx::query(
             "
-            INSERT INTO validators (epoch_id, epoch_height, epoch_start_height, epoch_end_height, validators_info)
-            VALUES ($1, $2, $3, $4, $5) ON CONFLICT DO NOTHING;
+            INSERT INTO validators (epoch_id, epoch_height, epoch_start_height, epoch_end_height, validators_info, prev_epoch_kickout, stake_changes)
+            VALUES ($1, $2, $3, $4, $5, $6, $7) ON CONFLICT DO NOTHING;
             "
         )
             .bind(epoch_id.to_string())
@@ -206,11 +645,39 @@ impl crate::StateIndexerDbManager for crate::PostgresDBManager {
             .bind(bigdecimal::BigDecimal::from(epoch_start_height))
             .bind(bigdecimal::BigDecimal::from(epoch_end_block_height))
             .bind(&serde_json::to_value(validators_info)?)
+            .bind(&serde_json::to_value(&validators_info.prev_epoch_kickout)?)
+            .bind(&serde_json::to_value(&stake_changes)?)
             .execute(&self.meta_db_pool)
             .await?;
         Ok(())
     }
 
+    async fn save_protocol_config(
+        &self,
+        epoch_id: near_primitives::hash::CryptoHash,
+        epoch_height: u64,
+        epoch_start_height: u64,
+        protocol_config: &near_chain_configs::ProtocolConfigView,
+    ) -> anyhow::Result<()> {
+        crate::metrics::META_DATABASE_WRITE_QUERIES
+            .with_label_values(&["save_protocol_config", "protocol_configs"])
+            .inc();
+        sqlx::query(
+            "
+            INSERT INTO protocol_configs (epoch_id, epoch_height, epoch_start_height, protocol_version, protocol_config)
+            VALUES ($1, $2, $3, $4, $5) ON CONFLICT DO NOTHING;
+            ",
+        )
+        .bind(super::types::Hash(epoch_id))
+        .bind(super::types::U64(epoch_height))
+        .bind(super::types::U64(epoch_start_height))
+        .bind(super::types::U64(u64::from(protocol_config.protocol_version)))
+        .bind(&serde_json::to_value(protocol_config)?)
+        .execute(&self.meta_db_pool)
+        .await?;
+        Ok(())
+    }
+
     async fn save_state_changes_data(
         &self,
         shard_id: near_primitives::types::ShardId,
@@ -218,46 +685,76 @@ impl crate::StateIndexerDbManager for crate::PostgresDBManager {
         block_height: u64,
         block_hash: near_primitives::hash::CryptoHash,
     ) -> anyhow::Result<()> {
+        let table = if self.state_key_buckets > 0 {
+            "state_changes_data_buckets"
+        } else {
+            "state_changes_data"
+        };
         crate::metrics::SHARD_DATABASE_WRITE_QUERIES
-            .with_label_values(&[
-                &shard_id.to_string(),
-                "save_state_changes_data",
-                "state_changes_data",
-            ])
+            .with_label_values(&[&shard_id.to_string(), "save_state_changes_data", table])
             .inc();
-        let mut query_builder: sqlx::QueryBuilder<sqlx::Postgres> = sqlx::QueryBuilder::new(
-            "INSERT INTO state_changes_data (account_id, block_height, block_hash, data_key, data_value) ",
-        );
-        query_builder.push_values(state_changes.iter(), |mut values, state_change| {
-            match &state_change.value {
+        let changes: Vec<(
+            &near_primitives::types::AccountId,
+            readnode_primitives::StateKey,
+            Option<&[u8]>,
+        )> = state_changes
+            .iter()
+            .filter_map(|state_change| match &state_change.value {
                 near_primitives::views::StateChangeValueView::DataUpdate {
                     account_id,
                     key,
                     value,
-                } => {
-                    let data_key: &[u8] = key.as_ref();
-                    let data_value: &[u8] = value.as_ref();
+                } => Some((
+                    account_id,
+                    readnode_primitives::StateKey::from(key.clone()),
+                    Some(value.as_ref()),
+                )),
+                near_primitives::views::StateChangeValueView::DataDeletion { account_id, key } => {
+                    Some((
+                        account_id,
+                        readnode_primitives::StateKey::from(key.clone()),
+                        None,
+                    ))
+                }
+                _ => None,
+            })
+            .collect();
+        let mut query_builder: sqlx::QueryBuilder<sqlx::Postgres> = if self.state_key_buckets > 0 {
+            let mut query_builder = sqlx::QueryBuilder::new(
+                "INSERT INTO state_changes_data_buckets (account_id, key_bucket, block_height, block_hash, data_key, data_value) ",
+            );
+            query_builder.push_values(
+                changes.iter(),
+                |mut values, (account_id, data_key, data_value)| {
                     values
                         .push_bind(account_id.to_string())
+                        .push_bind(self.state_key_bucket(data_key))
                         .push_bind(bigdecimal::BigDecimal::from(block_height))
                         .push_bind(block_hash.to_string())
-                        .push_bind(hex::encode(data_key).to_string())
-                        .push_bind(data_value);
-                }
-                near_primitives::views::StateChangeValueView::DataDeletion { account_id, key } => {
-                    let data_key: &[u8] = key.as_ref();
-                    let data_value: Option<&[u8]> = None;
+                        .push_bind(data_key.to_hex())
+                        .push_bind(*data_value);
+                },
+            );
+            query_builder.push(" ON CONFLICT (account_id, key_bucket, data_key, block_height) DO UPDATE SET data_value = EXCLUDED.data_value;");
+            query_builder
+        } else {
+            let mut query_builder = sqlx::QueryBuilder::new(
+                "INSERT INTO state_changes_data (account_id, block_height, block_hash, data_key, data_value) ",
+            );
+            query_builder.push_values(
+                changes.iter(),
+                |mut values, (account_id, data_key, data_value)| {
                     values
                         .push_bind(account_id.to_string())
                         .push_bind(bigdecimal::BigDecimal::from(block_height))
                         .push_bind(block_hash.to_string())
-                        .push_bind(hex::encode(data_key).to_string())
-                        .push_bind(data_value);
-                }
-                _ => {}
-            }
-        });
-        query_builder.push(" ON CONFLICT (account_id, data_key, block_height) DO UPDATE SET data_value = EXCLUDED.data_value;");
+                        .push_bind(data_key.to_hex())
+                        .push_bind(*data_value);
+                },
+            );
+            query_builder.push(" ON CONFLICT (account_id, data_key, block_height) DO UPDATE SET data_value = EXCLUDED.data_value;");
+            query_builder
+        };
         query_builder
             .build()
             .execute(self.shards_pool.get(&shard_id).ok_or(anyhow::anyhow!(
@@ -274,19 +771,16 @@ impl crate::StateIndexerDbManager for crate::PostgresDBManager {
         state_changes: Vec<near_primitives::views::StateChangeWithCauseView>,
         block_height: u64,
         block_hash: near_primitives::hash::CryptoHash,
+        unfinished_block_height: Option<u64>,
     ) -> anyhow::Result<()> {
-        crate::metrics::SHARD_DATABASE_WRITE_QUERIES
-            .with_label_values(&[
-                &shard_id.to_string(),
-                "save_state_changes_access_key",
-                "state_changes_access_key",
-            ])
-            .inc();
-        let mut query_builder: sqlx::QueryBuilder<sqlx::Postgres> = sqlx::QueryBuilder::new(
-            "INSERT INTO state_changes_access_key (account_id, block_height, block_hash, data_key, data_value) ",
-        );
-        query_builder.push_values(state_changes.iter(), |mut values, state_change| {
-            match &state_change.value {
+        let pool = self.shards_pool.get(&shard_id).ok_or(anyhow::anyhow!(
+            "Database connection for Shard_{} not found",
+            shard_id
+        ))?;
+        // (account_id, data_key, data_value) rows, `None` value for the deleted keys
+        let rows: Vec<(String, String, Option<Vec<u8>>)> = state_changes
+            .iter()
+            .filter_map(|state_change| match &state_change.value {
                 near_primitives::views::StateChangeValueView::AccessKeyUpdate {
                     account_id,
                     public_key,
@@ -296,12 +790,11 @@ impl crate::StateIndexerDbManager for crate::PostgresDBManager {
                         borsh::to_vec(public_key).expect("Failed to borsh serialize public key");
                     let data_value =
                         borsh::to_vec(access_key).expect("Failed to borsh serialize access key");
-                    values
-                        .push_bind(account_id.to_string())
-                        .push_bind(bigdecimal::BigDecimal::from(block_height))
-                        .push_bind(block_hash.to_string())
-                        .push_bind(hex::encode(data_key).to_string())
-                        .push_bind(data_value);
+                    Some((
+                        account_id.to_string(),
+                        readnode_primitives::encoding::encode_hex(&data_key),
+                        Some(data_value),
+                    ))
                 }
                 near_primitives::views::StateChangeValueView::AccessKeyDeletion {
                     account_id,
@@ -309,25 +802,96 @@ impl crate::StateIndexerDbManager for crate::PostgresDBManager {
                 } => {
                     let data_key =
                         borsh::to_vec(public_key).expect("Failed to borsh serialize public key");
-                    let data_value: Option<&[u8]> = None;
-                    values
-                        .push_bind(account_id.to_string())
-                        .push_bind(bigdecimal::BigDecimal::from(block_height))
-                        .push_bind(block_hash.to_string())
-                        .push_bind(hex::encode(data_key).to_string())
-                        .push_bind(data_value);
+                    Some((
+                        account_id.to_string(),
+                        readnode_primitives::encoding::encode_hex(&data_key),
+                        None,
+                    ))
                 }
-                _ => {}
-            }
+                _ => None,
+            })
+            .collect();
+        if rows.is_empty() {
+            return Ok(());
+        }
+
+        // The keys of all the changes are logged before the unchanged values are skipped,
+        // the changes of the block are read from the log
+        crate::metrics::SHARD_DATABASE_WRITE_QUERIES
+            .with_label_values(&[
+                &shard_id.to_string(),
+                "save_state_changes_access_key",
+                "state_changes_access_key_log",
+            ])
+            .inc();
+        let mut query_builder: sqlx::QueryBuilder<sqlx::Postgres> = sqlx::QueryBuilder::new(
+            "INSERT INTO state_changes_access_key_log (block_height, account_id, data_key) ",
+        );
+        query_builder.push_values(rows.iter(), |mut values, (account_id, data_key, _)| {
+            values
+                .push_bind(bigdecimal::BigDecimal::from(block_height))
+                .push_bind(account_id)
+                .push_bind(data_key);
         });
+        query_builder.push(" ON CONFLICT DO NOTHING;");
+        query_builder.build().execute(pool).await?;
+
+        // Most of the access key changes are the nonces updates,
+        // but the keys are also touched with the same values.
+        // Such rows are skipped, the readers take the latest stored value anyway.
+        // The lower blocks are stored concurrently, so the previous value is trusted only
+        // if no block between it and this block is still being stored
+        let previous_values_hashes =
+            Self::get_access_keys_values_hashes(shard_id, pool, &rows, block_height).await?;
+        let rows_count = rows.len();
+        let rows: Vec<_> = rows
+            .into_iter()
+            .filter(|(account_id, data_key, data_value)| {
+                match previous_values_hashes.get(&(account_id.clone(), data_key.clone())) {
+                    Some((previous_block_height, previous_value_hash))
+                        if unfinished_block_height.map_or(true, |unfinished_block_height| {
+                            *previous_block_height >= unfinished_block_height
+                        }) =>
+                    {
+                        previous_value_hash
+                            != &data_value
+                                .as_ref()
+                                .map(|data_value| near_primitives::hash::hash(data_value))
+                    }
+                    _ => true,
+                }
+            })
+            .collect();
+        crate::metrics::SKIPPED_UNCHANGED_WRITES
+            .with_label_values(&[&shard_id.to_string(), "state_changes_access_key"])
+            .inc_by((rows_count - rows.len()) as u64);
+        if rows.is_empty() {
+            return Ok(());
+        }
+
+        crate::metrics::SHARD_DATABASE_WRITE_QUERIES
+            .with_label_values(&[
+                &shard_id.to_string(),
+                "save_state_changes_access_key",
+                "state_changes_access_key",
+            ])
+            .inc();
+        let mut query_builder: sqlx::QueryBuilder<sqlx::Postgres> = sqlx::QueryBuilder::new(
+            "INSERT INTO state_changes_access_key (account_id, block_height, block_hash, data_key, data_value) ",
+        );
+        query_builder.push_values(
+            rows.iter(),
+            |mut values, (account_id, data_key, data_value)| {
+                values
+                    .push_bind(account_id)
+                    .push_bind(bigdecimal::BigDecimal::from(block_height))
+                    .push_bind(block_hash.to_string())
+                    .push_bind(data_key)
+                    .push_bind(data_value);
+            },
+        );
         query_builder.push(" ON CONFLICT (account_id, data_key, block_height) DO UPDATE SET data_value = EXCLUDED.data_value;");
-        query_builder
-            .build()
-            .execute(self.shards_pool.get(&shard_id).ok_or(anyhow::anyhow!(
-                "Database connection for Shard_{} not found",
-                shard_id
-            ))?)
-            .await?;
+        query_builder.build().execute(pool).await?;
         Ok(())
     }
 
@@ -438,4 +1002,162 @@ impl crate::StateIndexerDbManager for crate::PostgresDBManager {
             .await?;
         Ok(())
     }
+
+    async fn rebalance_state_buckets(
+        &self,
+        account_id: &near_primitives::types::AccountId,
+        after_data_key: Option<String>,
+        limit: u64,
+    ) -> anyhow::Result<crate::primitives::StateRebalanceBatch> {
+        if self.state_key_buckets == 0 {
+            anyhow::bail!(
+                "State key buckets are disabled, set `state_key_buckets` in the database config"
+            );
+        }
+        let shard_id_pool = self.get_shard_connection(account_id).await?;
+        crate::metrics::SHARD_DATABASE_READ_QUERIES
+            .with_label_values(&[
+                &shard_id_pool.shard_id.to_string(),
+                "rebalance_state_buckets",
+                "state_changes_data",
+            ])
+            .inc();
+        let data_keys: Vec<String> = sqlx::query_scalar(
+            "
+            SELECT DISTINCT data_key
+            FROM state_changes_data_all
+            WHERE account_id = $1 AND data_key > $2
+            ORDER BY data_key
+            LIMIT $3;
+            ",
+        )
+        .bind(account_id.to_string())
+        .bind(after_data_key.unwrap_or_default())
+        .bind(limit as i64)
+        .fetch_all(shard_id_pool.pool)
+        .await?;
+        let key_buckets = data_keys
+            .iter()
+            .map(|data_key| {
+                Ok(self.state_key_bucket(&readnode_primitives::StateKey::from_hex(data_key)?))
+            })
+            .collect::<anyhow::Result<Vec<i32>>>()?;
+
+        crate::metrics::SHARD_DATABASE_WRITE_QUERIES
+            .with_label_values(&[
+                &shard_id_pool.shard_id.to_string(),
+                "rebalance_state_buckets",
+                "state_changes_data_buckets",
+            ])
+            .inc();
+        // The rows are deleted and inserted into the buckets in one transaction,
+        // so the readers see the state of the key either in the old place or in the new one
+        let mut transaction = shard_id_pool.pool.begin().await?;
+        let mut rows: Vec<(super::types::U64, String, String, Option<Vec<u8>>)> = sqlx::query_as(
+            "
+            DELETE FROM state_changes_data
+            WHERE account_id = $1 AND data_key = ANY($2)
+            RETURNING block_height, block_hash, data_key, data_value;
+            ",
+        )
+        .bind(account_id.to_string())
+        .bind(&data_keys)
+        .fetch_all(&mut *transaction)
+        .await?;
+        rows.extend(
+            sqlx::query_as::<_, (super::types::U64, String, String, Option<Vec<u8>>)>(
+                "
+                DELETE FROM state_changes_data_buckets sc
+                USING unnest($2::text[], $3::integer[]) AS keys(data_key, key_bucket)
+                WHERE sc.account_id = $1
+                    AND sc.data_key = keys.data_key
+                    AND sc.key_bucket <> keys.key_bucket
+                RETURNING sc.block_height, sc.block_hash, sc.data_key, sc.data_value;
+                ",
+            )
+            .bind(account_id.to_string())
+            .bind(&data_keys)
+            .bind(&key_buckets)
+            .fetch_all(&mut *transaction)
+            .await?,
+        );
+        for rows_batch in rows.chunks(STATE_REBALANCE_INSERT_BATCH_SIZE) {
+            let mut query_builder: sqlx::QueryBuilder<sqlx::Postgres> = sqlx::QueryBuilder::new(
+                "INSERT INTO state_changes_data_buckets (account_id, key_bucket, block_height, block_hash, data_key, data_value) ",
+            );
+            let mut rows_values = Vec::with_capacity(rows_batch.len());
+            for (block_height, block_hash, data_key, data_value) in rows_batch {
+                let key_bucket =
+                    self.state_key_bucket(&readnode_primitives::StateKey::from_hex(data_key)?);
+                rows_values.push((
+                    key_bucket,
+                    *block_height,
+                    block_hash.clone(),
+                    data_key.clone(),
+                    data_value.clone(),
+                ));
+            }
+            query_builder.push_values(
+                rows_values,
+                |mut values, (key_bucket, block_height, block_hash, data_key, data_value)| {
+                    values
+                        .push_bind(account_id.to_string())
+                        .push_bind(key_bucket)
+                        .push_bind(block_height)
+                        .push_bind(block_hash)
+                        .push_bind(data_key)
+                        .push_bind(data_value);
+                },
+            );
+            // The row of the same block written by the indexer meanwhile is the same change
+            query_builder
+                .push(" ON CONFLICT (account_id, key_bucket, data_key, block_height) DO NOTHING;");
+            query_builder.build().execute(&mut *transaction).await?;
+        }
+        transaction.commit().await?;
+
+        let next_data_key = match data_keys.last() {
+            Some(data_key) if data_keys.len() as u64 == limit => Some(data_key.clone()),
+            _ => None,
+        };
+        Ok(crate::primitives::StateRebalanceBatch {
+            moved_rows: rows.len() as u64,
+            next_data_key,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::postgres::NANOSECONDS_PER_DAY;
+
+    #[test]
+    fn test_day_is_not_indexed_without_processed_blocks() {
+        assert!(!is_day_indexed(NANOSECONDS_PER_DAY, None));
+    }
+
+    #[test]
+    fn test_day_is_not_indexed_while_processing_the_day() {
+        assert!(!is_day_indexed(
+            2 * NANOSECONDS_PER_DAY,
+            Some(NANOSECONDS_PER_DAY)
+        ));
+        assert!(!is_day_indexed(
+            2 * NANOSECONDS_PER_DAY,
+            Some(2 * NANOSECONDS_PER_DAY - 1)
+        ));
+    }
+
+    #[test]
+    fn test_day_is_indexed_once_the_next_day_is_processed() {
+        assert!(is_day_indexed(
+            2 * NANOSECONDS_PER_DAY,
+            Some(2 * NANOSECONDS_PER_DAY)
+        ));
+        assert!(is_day_indexed(
+            2 * NANOSECONDS_PER_DAY,
+            Some(3 * NANOSECONDS_PER_DAY)
+        ));
+    }
 }
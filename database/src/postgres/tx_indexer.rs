@@ -1,5 +1,3 @@
-use bigdecimal::ToPrimitive;
-
 #[async_trait::async_trait]
 impl crate::TxIndexerDbManager for crate::PostgresDBManager {
     async fn save_receipts(
@@ -14,18 +12,32 @@ impl crate::TxIndexerDbManager for crate::PostgresDBManager {
             .with_label_values(&[&shard_id.to_string(), "save_receipts", "receipts_map"])
             .inc();
         let mut query_builder: sqlx::QueryBuilder<sqlx::Postgres> = sqlx::QueryBuilder::new(
-            "INSERT INTO receipts_map (receipt_id, parent_transaction_hash, receiver_id, block_height, block_hash, shard_id) ",
+            "INSERT INTO receipts_map (receipt_id, parent_transaction_hash, receiver_id, block_height, block_hash, shard_id, index_in_chunk, receipt) ",
+        );
+        let receipt_bodies = receipts
+            .iter()
+            .map(|receipt| receipt.receipt.as_ref().map(borsh::to_vec).transpose())
+            .collect::<Result<Vec<_>, _>>()?;
+        query_builder.push_values(
+            receipts.iter().zip(receipt_bodies),
+            |mut values, (receipt, receipt_body)| {
+                values
+                    .push_bind(receipt.receipt_id.to_string())
+                    .push_bind(receipt.parent_transaction_hash.to_string())
+                    .push_bind(receipt.receiver_id.to_string())
+                    .push_bind(bigdecimal::BigDecimal::from(receipt.block_height))
+                    .push_bind(receipt.block_hash.to_string())
+                    .push_bind(bigdecimal::BigDecimal::from(receipt.shard_id))
+                    .push_bind(receipt.index_in_chunk.map(|index| index as i32))
+                    .push_bind(receipt_body);
+            },
+        );
+        // The receipt is mapped by its transaction before the execution,
+        // the body is added to the mapping once the receipt is executed
+        query_builder.push(
+            " ON CONFLICT (receipt_id) DO UPDATE SET receipt = EXCLUDED.receipt \
+            WHERE receipts_map.receipt IS NULL AND EXCLUDED.receipt IS NOT NULL;",
         );
-        query_builder.push_values(receipts.iter(), |mut values, receipt| {
-            values
-                .push_bind(receipt.receipt_id.to_string())
-                .push_bind(receipt.parent_transaction_hash.to_string())
-                .push_bind(receipt.receiver_id.to_string())
-                .push_bind(bigdecimal::BigDecimal::from(receipt.block_height))
-                .push_bind(receipt.block_hash.to_string())
-                .push_bind(bigdecimal::BigDecimal::from(receipt.shard_id));
-        });
-        query_builder.push(" ON CONFLICT DO NOTHING;");
         query_builder
             .build()
             .execute(self.shards_pool.get(&shard_id).ok_or(anyhow::anyhow!(
@@ -48,7 +60,7 @@ impl crate::TxIndexerDbManager for crate::PostgresDBManager {
             .with_label_values(&[&shard_id.to_string(), "save_outcomes", "outcomes_map"])
             .inc();
         let mut query_builder: sqlx::QueryBuilder<sqlx::Postgres> = sqlx::QueryBuilder::new(
-            "INSERT INTO outcomes_map (outcome_id, parent_transaction_hash, receiver_id, block_height, block_hash, shard_id) ",
+            "INSERT INTO outcomes_map (outcome_id, parent_transaction_hash, receiver_id, block_height, block_hash, shard_id, index_in_chunk) ",
         );
         query_builder.push_values(outcomes.iter(), |mut values, outcome| {
             values
@@ -57,7 +69,8 @@ impl crate::TxIndexerDbManager for crate::PostgresDBManager {
                 .push_bind(outcome.receiver_id.to_string())
                 .push_bind(bigdecimal::BigDecimal::from(outcome.block_height))
                 .push_bind(outcome.block_hash.to_string())
-                .push_bind(bigdecimal::BigDecimal::from(outcome.shard_id));
+                .push_bind(bigdecimal::BigDecimal::from(outcome.shard_id))
+                .push_bind(outcome.index_in_chunk.map(|index| index as i32));
         });
         query_builder.push(" ON CONFLICT DO NOTHING;");
         query_builder
@@ -70,22 +83,643 @@ impl crate::TxIndexerDbManager for crate::PostgresDBManager {
         Ok(())
     }
 
-    async fn update_meta(&self, indexer_id: &str, block_height: u64) -> anyhow::Result<()> {
+    async fn save_transactions_by_public_key(
+        &self,
+        transactions: Vec<readnode_primitives::TransactionByPublicKeyRecord>,
+    ) -> anyhow::Result<()> {
+        if transactions.is_empty() {
+            return Ok(());
+        }
+        crate::metrics::META_DATABASE_WRITE_QUERIES
+            .with_label_values(&[
+                "save_transactions_by_public_key",
+                "transactions_by_public_key",
+            ])
+            .inc();
+        let gas_prices = transactions
+            .iter()
+            .map(|transaction| transaction.gas_price.map(balance_to_bigdecimal).transpose())
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let mut query_builder: sqlx::QueryBuilder<sqlx::Postgres> = sqlx::QueryBuilder::new(
+            "INSERT INTO transactions_by_public_key (public_key, block_height, transaction_hash, signer_id, receiver_id, nonce, block_hash, shard_id, protocol_version, gas_price) ",
+        );
+        query_builder.push_values(
+            transactions.iter().zip(gas_prices),
+            |mut values, (transaction, gas_price)| {
+                values
+                    .push_bind(transaction.public_key.clone())
+                    .push_bind(bigdecimal::BigDecimal::from(transaction.block_height))
+                    .push_bind(transaction.transaction_hash.to_string())
+                    .push_bind(transaction.signer_id.to_string())
+                    .push_bind(transaction.receiver_id.to_string())
+                    .push_bind(bigdecimal::BigDecimal::from(transaction.nonce))
+                    .push_bind(transaction.block_hash.to_string())
+                    .push_bind(bigdecimal::BigDecimal::from(transaction.shard_id))
+                    .push_bind(
+                        transaction
+                            .protocol_version
+                            .map(bigdecimal::BigDecimal::from),
+                    )
+                    .push_bind(gas_price);
+            },
+        );
+        query_builder.push(" ON CONFLICT DO NOTHING;");
+        query_builder.build().execute(&self.meta_db_pool).await?;
+        Ok(())
+    }
+
+    async fn save_block_processing_stats(
+        &self,
+        indexer_id: &str,
+        stats: &readnode_primitives::BlockProcessingStats,
+    ) -> anyhow::Result<()> {
+        crate::metrics::META_DATABASE_WRITE_QUERIES
+            .with_label_values(&["save_block_processing_stats", "block_processing_stats"])
+            .inc();
+        sqlx::query(
+            "
+            INSERT INTO block_processing_stats (
+                indexer_id,
+                block_height,
+                block_hash,
+                txs_started,
+                receipts_matched,
+                outcomes_matched,
+                txs_finalized,
+                collect_duration_ms,
+                save_tx_details_duration_ms,
+                save_outcomes_and_receipts_duration_ms,
+                save_transactions_by_public_key_duration_ms
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+            ON CONFLICT (indexer_id, block_height) DO NOTHING;
+            ",
+        )
+        .bind(indexer_id)
+        .bind(bigdecimal::BigDecimal::from(stats.block_height))
+        .bind(stats.block_hash.to_string())
+        .bind(bigdecimal::BigDecimal::from(stats.txs_started))
+        .bind(bigdecimal::BigDecimal::from(stats.receipts_matched))
+        .bind(bigdecimal::BigDecimal::from(stats.outcomes_matched))
+        .bind(bigdecimal::BigDecimal::from(stats.txs_finalized))
+        .bind(bigdecimal::BigDecimal::from(stats.collect_duration_ms))
+        .bind(bigdecimal::BigDecimal::from(
+            stats.save_tx_details_duration_ms,
+        ))
+        .bind(bigdecimal::BigDecimal::from(
+            stats.save_outcomes_and_receipts_duration_ms,
+        ))
+        .bind(bigdecimal::BigDecimal::from(
+            stats.save_transactions_by_public_key_duration_ms,
+        ))
+        .execute(&self.meta_db_pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn save_function_calls(
+        &self,
+        function_calls: Vec<readnode_primitives::FunctionCallRecord>,
+    ) -> anyhow::Result<()> {
+        if function_calls.is_empty() {
+            return Ok(());
+        }
+        crate::metrics::META_DATABASE_WRITE_QUERIES
+            .with_label_values(&["save_function_calls", "function_calls"])
+            .inc();
+        let mut query_builder: sqlx::QueryBuilder<sqlx::Postgres> = sqlx::QueryBuilder::new(
+            "INSERT INTO function_calls (receipt_id, action_index, block_height, block_hash, predecessor_id, signer_id, receiver_id, method_name, args, msg) ",
+        );
+        query_builder.push_values(function_calls.iter(), |mut values, function_call| {
+            values
+                .push_bind(function_call.receipt_id.to_string())
+                .push_bind(function_call.action_index as i32)
+                .push_bind(bigdecimal::BigDecimal::from(function_call.block_height))
+                .push_bind(function_call.block_hash.to_string())
+                .push_bind(function_call.predecessor_id.to_string())
+                .push_bind(function_call.signer_id.to_string())
+                .push_bind(function_call.receiver_id.to_string())
+                .push_bind(function_call.method_name.clone())
+                .push_bind(function_call.args.clone())
+                .push_bind(function_call.msg.clone());
+        });
+        query_builder.push(" ON CONFLICT DO NOTHING;");
+        query_builder.build().execute(&self.meta_db_pool).await?;
+        Ok(())
+    }
+
+    async fn save_actions(
+        &self,
+        actions: Vec<readnode_primitives::ActionRecord>,
+    ) -> anyhow::Result<()> {
+        if actions.is_empty() {
+            return Ok(());
+        }
+        crate::metrics::META_DATABASE_WRITE_QUERIES
+            .with_label_values(&["save_actions", "actions"])
+            .inc();
+        let deposits = actions
+            .iter()
+            .map(|action| action.deposit.map(balance_to_bigdecimal).transpose())
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let mut query_builder: sqlx::QueryBuilder<sqlx::Postgres> = sqlx::QueryBuilder::new(
+            "INSERT INTO actions (receipt_id, action_index, block_height, block_hash, predecessor_id, signer_id, receiver_id, action_kind, method_name, deposit, gas, public_key) ",
+        );
+        query_builder.push_values(
+            actions.iter().zip(deposits),
+            |mut values, (action, deposit)| {
+                values
+                    .push_bind(action.receipt_id.to_string())
+                    .push_bind(action.action_index as i32)
+                    .push_bind(bigdecimal::BigDecimal::from(action.block_height))
+                    .push_bind(action.block_hash.to_string())
+                    .push_bind(action.predecessor_id.to_string())
+                    .push_bind(action.signer_id.to_string())
+                    .push_bind(action.receiver_id.to_string())
+                    .push_bind(action.action_kind.clone())
+                    .push_bind(action.method_name.clone())
+                    .push_bind(deposit)
+                    .push_bind(action.gas.map(bigdecimal::BigDecimal::from))
+                    .push_bind(action.public_key.clone());
+            },
+        );
+        query_builder.push(" ON CONFLICT DO NOTHING;");
+        query_builder.build().execute(&self.meta_db_pool).await?;
+        Ok(())
+    }
+
+    async fn save_skipped_chunks(
+        &self,
+        block_height: u64,
+        skipped_chunks: Vec<(crate::primitives::ShardId, crate::primitives::ChunkHash)>,
+    ) -> anyhow::Result<()> {
+        if skipped_chunks.is_empty() {
+            return Ok(());
+        }
+        crate::metrics::META_DATABASE_WRITE_QUERIES
+            .with_label_values(&["save_skipped_chunks", "skipped_chunks"])
+            .inc();
+        let mut query_builder: sqlx::QueryBuilder<sqlx::Postgres> = sqlx::QueryBuilder::new(
+            "INSERT INTO skipped_chunks (block_height, shard_id, chunk_hash) ",
+        );
+        query_builder.push_values(
+            skipped_chunks.iter(),
+            |mut values, (shard_id, chunk_hash)| {
+                values
+                    .push_bind(bigdecimal::BigDecimal::from(block_height))
+                    .push_bind(bigdecimal::BigDecimal::from(*shard_id))
+                    .push_bind(chunk_hash);
+            },
+        );
+        query_builder.push(" ON CONFLICT DO NOTHING;");
+        query_builder.build().execute(&self.meta_db_pool).await?;
+        Ok(())
+    }
+
+    async fn save_block_repair(
+        &self,
+        indexer_id: &str,
+        repair: &readnode_primitives::BlockRepair,
+    ) -> anyhow::Result<()> {
+        crate::metrics::META_DATABASE_WRITE_QUERIES
+            .with_label_values(&["save_block_repair", "block_repairs"])
+            .inc();
+        sqlx::query(
+            "
+            INSERT INTO block_repairs (
+                indexer_id,
+                block_height,
+                block_hash,
+                reason,
+                transactions,
+                receipts,
+                error
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7);
+            ",
+        )
+        .bind(indexer_id)
+        .bind(bigdecimal::BigDecimal::from(repair.block_height))
+        .bind(repair.block_hash.to_string())
+        .bind(&repair.reason)
+        .bind(bigdecimal::BigDecimal::from(repair.transactions))
+        .bind(bigdecimal::BigDecimal::from(repair.receipts))
+        .bind(&repair.error)
+        .execute(&self.meta_db_pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn save_missed_block(
+        &self,
+        indexer_id: &str,
+        block_height: u64,
+        error: &str,
+    ) -> anyhow::Result<()> {
+        crate::metrics::META_DATABASE_WRITE_QUERIES
+            .with_label_values(&["save_missed_block", "missed_blocks"])
+            .inc();
+        sqlx::query(
+            "
+            INSERT INTO missed_blocks (indexer_id, block_height, error)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (indexer_id, block_height)
+            DO UPDATE SET
+                error = EXCLUDED.error,
+                attempts = missed_blocks.attempts + 1,
+                missed_at = now();
+            ",
+        )
+        .bind(indexer_id)
+        .bind(bigdecimal::BigDecimal::from(block_height))
+        .bind(error)
+        .execute(&self.meta_db_pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn get_missed_blocks(
+        &self,
+        indexer_id: &str,
+    ) -> anyhow::Result<Vec<readnode_primitives::MissedBlock>> {
+        crate::metrics::META_DATABASE_READ_QUERIES
+            .with_label_values(&["get_missed_blocks", "missed_blocks"])
+            .inc();
+        let rows: Vec<(super::types::U64, String, super::types::U64)> = sqlx::query_as(
+            "
+            SELECT block_height, error, attempts
+            FROM missed_blocks
+            WHERE indexer_id = $1
+            ORDER BY block_height ASC;
+            ",
+        )
+        .bind(indexer_id)
+        .fetch_all(&self.meta_db_pool)
+        .await?;
+        Ok(rows
+            .into_iter()
+            .map(
+                |(block_height, error, attempts)| readnode_primitives::MissedBlock {
+                    block_height: block_height.0,
+                    error,
+                    attempts: attempts.0,
+                },
+            )
+            .collect())
+    }
+
+    async fn delete_missed_blocks(
+        &self,
+        indexer_id: &str,
+        block_heights: &[u64],
+    ) -> anyhow::Result<()> {
+        crate::metrics::META_DATABASE_WRITE_QUERIES
+            .with_label_values(&["delete_missed_blocks", "missed_blocks"])
+            .inc();
+        sqlx::query(
+            "
+            DELETE FROM missed_blocks
+            WHERE indexer_id = $1 AND block_height = ANY($2);
+            ",
+        )
+        .bind(indexer_id)
+        .bind(
+            block_heights
+                .iter()
+                .map(|block_height| bigdecimal::BigDecimal::from(*block_height))
+                .collect::<Vec<_>>(),
+        )
+        .execute(&self.meta_db_pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn get_block_heights_without_block_info(&self, limit: u64) -> anyhow::Result<Vec<u64>> {
+        crate::metrics::META_DATABASE_READ_QUERIES
+            .with_label_values(&[
+                "get_block_heights_without_block_info",
+                "transactions_by_public_key",
+            ])
+            .inc();
+        let block_heights: Vec<(super::types::U64,)> = sqlx::query_as(
+            "
+            SELECT DISTINCT block_height
+            FROM transactions_by_public_key
+            WHERE protocol_version IS NULL
+            ORDER BY block_height ASC
+            LIMIT $1;
+            ",
+        )
+        .bind(limit as i64)
+        .fetch_all(&self.meta_db_pool)
+        .await?;
+        Ok(block_heights
+            .into_iter()
+            .map(|(block_height,)| block_height.0)
+            .collect())
+    }
+
+    async fn update_transactions_block_info(
+        &self,
+        block_height: u64,
+        protocol_version: near_primitives::types::ProtocolVersion,
+        gas_price: near_primitives::types::Balance,
+    ) -> anyhow::Result<()> {
+        crate::metrics::META_DATABASE_WRITE_QUERIES
+            .with_label_values(&[
+                "update_transactions_block_info",
+                "transactions_by_public_key",
+            ])
+            .inc();
+        sqlx::query(
+            "
+            UPDATE transactions_by_public_key
+            SET protocol_version = $2, gas_price = $3
+            WHERE block_height = $1 AND protocol_version IS NULL;
+            ",
+        )
+        .bind(bigdecimal::BigDecimal::from(block_height))
+        .bind(bigdecimal::BigDecimal::from(protocol_version))
+        .bind(balance_to_bigdecimal(gas_price)?)
+        .execute(&self.meta_db_pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn sync_group_assignments(
+        &self,
+        group_id: &str,
+        indexer_id: &str,
+        lease_block_height: u64,
+        member_timeout_secs: u64,
+    ) -> anyhow::Result<Vec<readnode_primitives::GroupAssignment>> {
+        // The members of the group sync one by one, so the lease can't be extended
+        // past the assignment agreed at the same time
+        let mut transaction = self.meta_db_pool.begin().await?;
+        sqlx::query("SELECT pg_advisory_xact_lock(hashtext($1));")
+            .bind(format!("tx_indexer_group:{}", group_id))
+            .execute(&mut *transaction)
+            .await?;
+
+        crate::metrics::META_DATABASE_WRITE_QUERIES
+            .with_label_values(&["sync_group_assignments", "tx_indexer_group_members"])
+            .inc();
+        sqlx::query(
+            "
+            INSERT INTO tx_indexer_group_members (group_id, indexer_id, last_heartbeat, lease_block_height)
+            VALUES ($1, $2, now(), $3)
+            ON CONFLICT (group_id, indexer_id)
+            DO UPDATE SET
+                last_heartbeat = EXCLUDED.last_heartbeat,
+                lease_block_height = GREATEST(
+                    tx_indexer_group_members.lease_block_height,
+                    EXCLUDED.lease_block_height
+                );
+            ",
+        )
+        .bind(group_id)
+        .bind(indexer_id)
+        .bind(bigdecimal::BigDecimal::from(lease_block_height))
+        .execute(&mut *transaction)
+        .await?;
+
+        crate::metrics::META_DATABASE_READ_QUERIES
+            .with_label_values(&["sync_group_assignments", "tx_indexer_group_members"])
+            .inc();
+        let alive_members: Vec<(String,)> = sqlx::query_as(
+            "
+            SELECT indexer_id
+            FROM tx_indexer_group_members
+            WHERE group_id = $1 AND last_heartbeat > now() - make_interval(secs => $2)
+            ORDER BY indexer_id ASC;
+            ",
+        )
+        .bind(group_id)
+        .bind(member_timeout_secs as f64)
+        .fetch_all(&mut *transaction)
+        .await?;
+        let alive_members = alive_members
+            .into_iter()
+            .map(|(indexer_id,)| indexer_id)
+            .collect::<Vec<_>>();
+        // The failed members are included, they might still handle the blocks up to their leases
+        let (max_lease_block_height,): (super::types::U64,) = sqlx::query_as(
+            "
+            SELECT COALESCE(MAX(lease_block_height), 0)
+            FROM tx_indexer_group_members
+            WHERE group_id = $1;
+            ",
+        )
+        .bind(group_id)
+        .fetch_one(&mut *transaction)
+        .await?;
+
+        crate::metrics::META_DATABASE_READ_QUERIES
+            .with_label_values(&["sync_group_assignments", "tx_indexer_group_assignments"])
+            .inc();
+        let rows: Vec<(super::types::U64, Vec<String>)> = sqlx::query_as(
+            "
+            SELECT from_block_height, members
+            FROM tx_indexer_group_assignments
+            WHERE group_id = $1
+            ORDER BY from_block_height ASC;
+            ",
+        )
+        .bind(group_id)
+        .fetch_all(&mut *transaction)
+        .await?;
+        let mut assignments = rows
+            .into_iter()
+            .map(
+                |(from_block_height, members)| readnode_primitives::GroupAssignment {
+                    from_block_height: from_block_height.0,
+                    members,
+                },
+            )
+            .collect::<Vec<_>>();
+
+        // The first assignment covers all the blocks, the next ones start
+        // after the heights the members may have already claimed
+        let from_block_height = match assignments.last() {
+            None => Some(0),
+            Some(latest) if latest.members != alive_members => {
+                Some((max_lease_block_height.0 + 1).max(latest.from_block_height + 1))
+            }
+            Some(_) => None,
+        };
+        if let Some(from_block_height) = from_block_height {
+            crate::metrics::META_DATABASE_WRITE_QUERIES
+                .with_label_values(&["sync_group_assignments", "tx_indexer_group_assignments"])
+                .inc();
+            sqlx::query(
+                "
+                INSERT INTO tx_indexer_group_assignments (group_id, from_block_height, members)
+                VALUES ($1, $2, $3);
+                ",
+            )
+            .bind(group_id)
+            .bind(bigdecimal::BigDecimal::from(from_block_height))
+            .bind(&alive_members)
+            .execute(&mut *transaction)
+            .await?;
+            assignments.push(readnode_primitives::GroupAssignment {
+                from_block_height,
+                members: alive_members,
+            });
+        }
+        transaction.commit().await?;
+        Ok(assignments)
+    }
+
+    async fn get_block_data_counts(
+        &self,
+        from_block_height: u64,
+        to_block_height: u64,
+    ) -> anyhow::Result<std::collections::HashMap<u64, readnode_primitives::BlockDataCounts>> {
+        let from_block_height = bigdecimal::BigDecimal::from(from_block_height);
+        let to_block_height = bigdecimal::BigDecimal::from(to_block_height);
+        let mut counts: std::collections::HashMap<u64, readnode_primitives::BlockDataCounts> =
+            std::collections::HashMap::new();
+
+        crate::metrics::META_DATABASE_READ_QUERIES
+            .with_label_values(&["get_block_data_counts", "blocks"])
+            .inc();
+        let blocks: Vec<(super::types::U64, i64)> = sqlx::query_as(
+            "
+            SELECT block_height, count(*)
+            FROM blocks
+            WHERE block_height >= $1 AND block_height <= $2
+            GROUP BY block_height;
+            ",
+        )
+        .bind(&from_block_height)
+        .bind(&to_block_height)
+        .fetch_all(&self.meta_db_pool)
+        .await?;
+        for (block_height, blocks_count) in blocks {
+            counts.entry(block_height.0).or_default().blocks = blocks_count as u64;
+        }
+
+        // Chunks included in the block are stored in `chunks` and the chunks from the previous
+        // blocks are stored in `chunks_duplicate`, so the shards are counted across both tables
+        crate::metrics::META_DATABASE_READ_QUERIES
+            .with_label_values(&["get_block_data_counts", "chunks"])
+            .inc();
+        let chunks: Vec<(super::types::U64, i64)> = sqlx::query_as(
+            "
+            SELECT block_height, count(DISTINCT shard_id)
+            FROM (
+                SELECT block_height, shard_id
+                FROM chunks
+                WHERE block_height >= $1 AND block_height <= $2
+                UNION ALL
+                SELECT block_height, shard_id
+                FROM chunks_duplicate
+                WHERE block_height >= $1 AND block_height <= $2
+            ) AS block_chunks
+            GROUP BY block_height;
+            ",
+        )
+        .bind(&from_block_height)
+        .bind(&to_block_height)
+        .fetch_all(&self.meta_db_pool)
+        .await?;
+        for (block_height, chunks_count) in chunks {
+            counts.entry(block_height.0).or_default().chunks = chunks_count as u64;
+        }
+
+        crate::metrics::META_DATABASE_READ_QUERIES
+            .with_label_values(&["get_block_data_counts", "transactions_by_public_key"])
+            .inc();
+        let transactions: Vec<(super::types::U64, i64)> = sqlx::query_as(
+            "
+            SELECT block_height, count(*)
+            FROM transactions_by_public_key
+            WHERE block_height >= $1 AND block_height <= $2
+            GROUP BY block_height;
+            ",
+        )
+        .bind(&from_block_height)
+        .bind(&to_block_height)
+        .fetch_all(&self.meta_db_pool)
+        .await?;
+        for (block_height, transactions_count) in transactions {
+            counts.entry(block_height.0).or_default().transactions = transactions_count as u64;
+        }
+
+        // Receipts are stored in the database of the shard they were executed in
+        let receipts_futures = self.shards_pool.iter().map(|(shard_id, pool)| {
+            crate::metrics::SHARD_DATABASE_READ_QUERIES
+                .with_label_values(&[
+                    &shard_id.to_string(),
+                    "get_block_data_counts",
+                    "receipts_map",
+                ])
+                .inc();
+            sqlx::query_as::<_, (super::types::U64, i64)>(
+                "
+                SELECT block_height, count(*)
+                FROM receipts_map
+                WHERE block_height >= $1 AND block_height <= $2
+                GROUP BY block_height;
+                ",
+            )
+            .bind(&from_block_height)
+            .bind(&to_block_height)
+            .fetch_all(pool)
+        });
+        for shard_receipts in futures::future::try_join_all(receipts_futures).await? {
+            for (block_height, receipts_count) in shard_receipts {
+                counts.entry(block_height.0).or_default().receipts += receipts_count as u64;
+            }
+        }
+        Ok(counts)
+    }
+
+    async fn update_meta(
+        &self,
+        indexer_id: &str,
+        block_height: u64,
+        block_timestamp: u64,
+    ) -> anyhow::Result<()> {
         crate::metrics::META_DATABASE_WRITE_QUERIES
             .with_label_values(&["update_meta", "meta"])
             .inc();
         sqlx::query(
             "
-            INSERT INTO meta (indexer_id, last_processed_block_height)
-            VALUES ($1, $2)
+            INSERT INTO meta (indexer_id, last_processed_block_height, last_processed_block_timestamp, indexer_type, crate_version, schema_version)
+            VALUES ($1, $2, $3, 'tx', $4, $5)
             ON CONFLICT (indexer_id)
-            DO UPDATE SET last_processed_block_height = $2;
+            DO UPDATE SET
+                last_processed_block_height = $2,
+                last_processed_block_timestamp = $3,
+                indexer_type = 'tx',
+                crate_version = $4,
+                schema_version = $5;
             ",
         )
         .bind(indexer_id)
         .bind(bigdecimal::BigDecimal::from(block_height))
+        .bind(bigdecimal::BigDecimal::from(block_timestamp))
+        .bind(env!("CARGO_PKG_VERSION"))
+        .bind(crate::SCHEMA_VERSION as i32)
         .execute(&self.meta_db_pool)
         .await?;
+        // The rpc-servers fall back to the polling of the meta table,
+        // so the failed notification doesn't stop the indexer
+        if let Err(err) = super::notifications::notify_head(
+            &self.meta_db_pool,
+            "tx",
+            block_height,
+            block_timestamp,
+        )
+        .await
+        {
+            tracing::warn!(
+                "Failed to notify the rpc-servers about the block: {:?}",
+                err
+            );
+        }
         Ok(())
     }
 
@@ -93,7 +727,7 @@ impl crate::TxIndexerDbManager for crate::PostgresDBManager {
         crate::metrics::META_DATABASE_READ_QUERIES
             .with_label_values(&["get_last_processed_block_height", "meta"])
             .inc();
-        let (last_processed_block_height,): (bigdecimal::BigDecimal,) = sqlx::query_as(
+        let (last_processed_block_height,): (super::types::U64,) = sqlx::query_as(
             "
             SELECT last_processed_block_height
             FROM meta
@@ -104,8 +738,542 @@ impl crate::TxIndexerDbManager for crate::PostgresDBManager {
         .bind(indexer_id)
         .fetch_one(&self.meta_db_pool)
         .await?;
-        last_processed_block_height
-            .to_u64()
-            .ok_or_else(|| anyhow::anyhow!("Failed to parse `last_processed_block_height` to u64"))
+        Ok(last_processed_block_height.0)
+    }
+
+    async fn save_network_identity(
+        &self,
+        network: &readnode_primitives::NetworkIdentity,
+    ) -> anyhow::Result<readnode_primitives::NetworkIdentity> {
+        self.insert_network_identity(network).await
+    }
+
+    async fn notify_finalized_transactions(
+        &self,
+        transaction_hashes: &[near_primitives::hash::CryptoHash],
+    ) -> anyhow::Result<()> {
+        super::notifications::notify_finalized_transactions(&self.meta_db_pool, transaction_hashes)
+            .await
+    }
+
+    async fn get_block_height_by_timestamp(&self, block_timestamp: u64) -> anyhow::Result<u64> {
+        crate::metrics::META_DATABASE_READ_QUERIES
+            .with_label_values(&["get_block_height_by_timestamp", "blocks"])
+            .inc();
+        // The blocks are stored by the state-indexer. Until it reaches the timestamp
+        // the latest stored block is older than the requested one, so none is returned
+        let (block_height,): (super::types::U64,) = sqlx::query_as(
+            "
+            SELECT block_height
+            FROM blocks
+            WHERE block_timestamp <= $1
+                AND EXISTS (
+                    SELECT 1
+                    FROM meta
+                    WHERE indexer_type = 'state' AND last_processed_block_timestamp >= $1
+                )
+            ORDER BY block_timestamp DESC
+            LIMIT 1;
+            ",
+        )
+        .bind(bigdecimal::BigDecimal::from(block_timestamp))
+        .fetch_one(&self.meta_db_pool)
+        .await?;
+        Ok(block_height.0)
+    }
+
+    async fn get_transactions_to_prune(
+        &self,
+        before_block_height: u64,
+        limit: u64,
+    ) -> anyhow::Result<Vec<near_primitives::hash::CryptoHash>> {
+        // The outcome of the transaction has the id of the transaction hash,
+        // the oldest transactions of every shard are merged
+        let mut transactions: Vec<(bigdecimal::BigDecimal, super::types::Hash)> = vec![];
+        for (shard_id, pool) in self.shards_pool.iter() {
+            crate::metrics::SHARD_DATABASE_READ_QUERIES
+                .with_label_values(&[
+                    &shard_id.to_string(),
+                    "get_transactions_to_prune",
+                    "outcomes_map",
+                ])
+                .inc();
+            transactions.extend(
+                sqlx::query_as::<_, (bigdecimal::BigDecimal, super::types::Hash)>(
+                    "
+                    SELECT block_height, outcome_id
+                    FROM outcomes_map
+                    WHERE block_height < $1 AND outcome_id = parent_transaction_hash
+                    ORDER BY block_height ASC
+                    LIMIT $2;
+                    ",
+                )
+                .bind(bigdecimal::BigDecimal::from(before_block_height))
+                .bind(limit as i64)
+                .fetch_all(pool)
+                .await?,
+            );
+        }
+        transactions.sort_by(|(left, _), (right, _)| left.cmp(right));
+        Ok(transactions
+            .into_iter()
+            .take(limit as usize)
+            .map(|(_, transaction_hash)| transaction_hash.0)
+            .collect())
+    }
+
+    async fn prune_transactions(
+        &self,
+        before_block_height: u64,
+        transaction_hashes: &[near_primitives::hash::CryptoHash],
+    ) -> anyhow::Result<u64> {
+        if transaction_hashes.is_empty() {
+            return Ok(0);
+        }
+        crate::metrics::META_DATABASE_WRITE_QUERIES
+            .with_label_values(&["prune_transactions", "transactions_by_public_key"])
+            .inc();
+        let pruned_rows = sqlx::query(
+            "
+            DELETE FROM transactions_by_public_key
+            WHERE block_height < $1 AND transaction_hash = ANY($2);
+            ",
+        )
+        .bind(bigdecimal::BigDecimal::from(before_block_height))
+        .bind(
+            transaction_hashes
+                .iter()
+                .map(|transaction_hash| transaction_hash.to_string())
+                .collect::<Vec<_>>(),
+        )
+        .execute(&self.meta_db_pool)
+        .await?
+        .rows_affected();
+        crate::metrics::RETENTION_PRUNED_ROWS
+            .with_label_values(&["transactions_by_public_key"])
+            .inc_by(pruned_rows);
+
+        // The outcomes the transactions are enumerated by are deleted last,
+        // so the failed run enumerates the transactions again
+        let mut pruned_rows = pruned_rows;
+        for (shard_id, pool) in self.shards_pool.iter() {
+            crate::metrics::SHARD_DATABASE_WRITE_QUERIES
+                .with_label_values(&[&shard_id.to_string(), "prune_transactions", "outcomes_map"])
+                .inc();
+            let table_pruned_rows = sqlx::query(
+                "
+                DELETE FROM outcomes_map
+                WHERE block_height < $1 AND outcome_id = ANY($2);
+                ",
+            )
+            .bind(bigdecimal::BigDecimal::from(before_block_height))
+            .bind(
+                transaction_hashes
+                    .iter()
+                    .map(|transaction_hash| transaction_hash.to_string())
+                    .collect::<Vec<_>>(),
+            )
+            .execute(pool)
+            .await?
+            .rows_affected();
+            crate::metrics::RETENTION_PRUNED_ROWS
+                .with_label_values(&["outcomes_map"])
+                .inc_by(table_pruned_rows);
+            pruned_rows += table_pruned_rows;
+        }
+        Ok(pruned_rows)
+    }
+
+    async fn prune_receipts_and_outcomes(
+        &self,
+        before_block_height: u64,
+        limit: u64,
+    ) -> anyhow::Result<u64> {
+        let mut pruned_rows = 0;
+        for (shard_id, pool) in self.shards_pool.iter() {
+            for (table_name, id_column) in [
+                ("receipts_map", "receipt_id"),
+                ("outcomes_map", "outcome_id"),
+            ] {
+                crate::metrics::SHARD_DATABASE_WRITE_QUERIES
+                    .with_label_values(&[
+                        &shard_id.to_string(),
+                        "prune_receipts_and_outcomes",
+                        table_name,
+                    ])
+                    .inc();
+                let table_pruned_rows = sqlx::query(&format!(
+                    "
+                    DELETE FROM {table_name}
+                    WHERE {id_column} IN (
+                        SELECT {id_column}
+                        FROM {table_name}
+                        WHERE block_height < $1
+                        LIMIT $2
+                    );
+                    "
+                ))
+                .bind(bigdecimal::BigDecimal::from(before_block_height))
+                .bind(limit as i64)
+                .execute(pool)
+                .await?
+                .rows_affected();
+                crate::metrics::RETENTION_PRUNED_ROWS
+                    .with_label_values(&[table_name])
+                    .inc_by(table_pruned_rows);
+                pruned_rows += table_pruned_rows;
+            }
+        }
+        Ok(pruned_rows)
+    }
+
+    async fn prune_receipts_of_filtered_transactions(
+        &self,
+        from_block_height: u64,
+        to_block_height: u64,
+        tracked_accounts: &[readnode_primitives::indexer::types::AccountId],
+    ) -> anyhow::Result<u64> {
+        let from_block_height = bigdecimal::BigDecimal::from(from_block_height);
+        let to_block_height = bigdecimal::BigDecimal::from(to_block_height);
+        let tracked_accounts: std::collections::HashSet<&str> = tracked_accounts
+            .iter()
+            .map(|account_id| account_id.as_str())
+            .collect();
+
+        // The shards and the transactions are stored in the different databases,
+        // so the transactions are filtered by the rows collected from the shards
+        let mut filtered_transactions = FilteredTransactions::default();
+        for (shard_id, pool) in self.shards_pool.iter() {
+            crate::metrics::SHARD_DATABASE_READ_QUERIES
+                .with_label_values(&[
+                    &shard_id.to_string(),
+                    "prune_receipts_of_filtered_transactions",
+                    "receipts_map",
+                ])
+                .inc();
+            let receipts: Vec<(String, String, Option<Vec<u8>>)> = sqlx::query_as(
+                "
+                SELECT parent_transaction_hash, receiver_id, receipt
+                FROM receipts_map
+                WHERE block_height >= $1 AND block_height < $2;
+                ",
+            )
+            .bind(&from_block_height)
+            .bind(&to_block_height)
+            .fetch_all(pool)
+            .await?;
+            for (parent_transaction_hash, receiver_id, receipt) in receipts {
+                filtered_transactions.add_receipt(
+                    parent_transaction_hash,
+                    &receiver_id,
+                    receipt.as_deref(),
+                    &tracked_accounts,
+                );
+            }
+
+            crate::metrics::SHARD_DATABASE_READ_QUERIES
+                .with_label_values(&[
+                    &shard_id.to_string(),
+                    "prune_receipts_of_filtered_transactions",
+                    "outcomes_map",
+                ])
+                .inc();
+            let outcomes: Vec<(String, String)> = sqlx::query_as(
+                "
+                SELECT parent_transaction_hash, receiver_id
+                FROM outcomes_map
+                WHERE block_height >= $1 AND block_height < $2;
+                ",
+            )
+            .bind(&from_block_height)
+            .bind(&to_block_height)
+            .fetch_all(pool)
+            .await?;
+            for (parent_transaction_hash, receiver_id) in outcomes {
+                filtered_transactions.add_outcome(
+                    parent_transaction_hash,
+                    &receiver_id,
+                    &tracked_accounts,
+                );
+            }
+        }
+        let mut parent_transaction_hashes = filtered_transactions.into_filtered();
+        if parent_transaction_hashes.is_empty() {
+            return Ok(0);
+        }
+
+        // The transactions indexed by the public key are tracked
+        crate::metrics::META_DATABASE_READ_QUERIES
+            .with_label_values(&[
+                "prune_receipts_of_filtered_transactions",
+                "transactions_by_public_key",
+            ])
+            .inc();
+        let indexed_transaction_hashes: Vec<(String,)> = sqlx::query_as(
+            "
+            SELECT DISTINCT transaction_hash
+            FROM transactions_by_public_key
+            WHERE transaction_hash = ANY($1);
+            ",
+        )
+        .bind(
+            parent_transaction_hashes
+                .iter()
+                .cloned()
+                .collect::<Vec<_>>(),
+        )
+        .fetch_all(&self.meta_db_pool)
+        .await?;
+        for (transaction_hash,) in indexed_transaction_hashes {
+            parent_transaction_hashes.remove(&transaction_hash);
+        }
+        if parent_transaction_hashes.is_empty() {
+            return Ok(0);
+        }
+
+        let filtered_transaction_hashes = parent_transaction_hashes.into_iter().collect::<Vec<_>>();
+        let mut pruned_rows = 0;
+        for (shard_id, pool) in self.shards_pool.iter() {
+            for table_name in ["receipts_map", "outcomes_map"] {
+                crate::metrics::SHARD_DATABASE_WRITE_QUERIES
+                    .with_label_values(&[
+                        &shard_id.to_string(),
+                        "prune_receipts_of_filtered_transactions",
+                        table_name,
+                    ])
+                    .inc();
+                let table_pruned_rows = sqlx::query(&format!(
+                    "
+                    DELETE FROM {table_name}
+                    WHERE block_height >= $1 AND block_height < $2
+                        AND parent_transaction_hash = ANY($3);
+                    "
+                ))
+                .bind(&from_block_height)
+                .bind(&to_block_height)
+                .bind(&filtered_transaction_hashes)
+                .execute(pool)
+                .await?
+                .rows_affected();
+                crate::metrics::FILTERED_PRUNED_ROWS
+                    .with_label_values(&[table_name])
+                    .inc_by(table_pruned_rows);
+                pruned_rows += table_pruned_rows;
+            }
+        }
+        Ok(pruned_rows)
+    }
+}
+
+/// Collects the accounts of the receipts and outcomes of the transactions. The transaction
+/// is filtered out by the `tracked_accounts` if neither its signer nor its receiver is tracked,
+/// so it is pruned only if its signer is known from a receipt body and none of the accounts
+/// of its rows is tracked. The rows without the bodies keep the transaction
+#[derive(Default)]
+struct FilteredTransactions {
+    /// Transactions with the known untracked signer
+    untracked_signer: std::collections::HashSet<String>,
+    /// Transactions with a tracked account or with the receipt body missing
+    kept: std::collections::HashSet<String>,
+}
+
+impl FilteredTransactions {
+    fn add_receipt(
+        &mut self,
+        parent_transaction_hash: String,
+        receiver_id: &str,
+        receipt: Option<&[u8]>,
+        tracked_accounts: &std::collections::HashSet<&str>,
+    ) {
+        let receipt = receipt.and_then(|receipt| {
+            readnode_primitives::blob::borsh_from_slice::<near_primitives::views::ReceiptView>(
+                receipt,
+            )
+            .ok()
+        });
+        let Some(receipt) = receipt else {
+            self.kept.insert(parent_transaction_hash);
+            return;
+        };
+        if tracked_accounts.contains(receiver_id)
+            || tracked_accounts.contains(receipt.predecessor_id.as_str())
+        {
+            self.kept.insert(parent_transaction_hash);
+            return;
+        }
+        // Every action receipt of the transaction carries the signer of the transaction
+        if let near_primitives::views::ReceiptEnumView::Action { signer_id, .. } = &receipt.receipt
+        {
+            if tracked_accounts.contains(signer_id.as_str()) {
+                self.kept.insert(parent_transaction_hash);
+            } else {
+                self.untracked_signer.insert(parent_transaction_hash);
+            }
+        }
+    }
+
+    fn add_outcome(
+        &mut self,
+        parent_transaction_hash: String,
+        receiver_id: &str,
+        tracked_accounts: &std::collections::HashSet<&str>,
+    ) {
+        if tracked_accounts.contains(receiver_id) {
+            self.kept.insert(parent_transaction_hash);
+        }
+    }
+
+    fn into_filtered(self) -> std::collections::HashSet<String> {
+        let Self {
+            untracked_signer,
+            kept,
+        } = self;
+        untracked_signer
+            .into_iter()
+            .filter(|parent_transaction_hash| !kept.contains(parent_transaction_hash))
+            .collect()
+    }
+}
+
+// `BigDecimal` can't be built from `u128` directly, so the balance is converted via its string
+fn balance_to_bigdecimal(
+    balance: near_primitives::types::Balance,
+) -> anyhow::Result<bigdecimal::BigDecimal> {
+    use std::str::FromStr;
+    bigdecimal::BigDecimal::from_str(&balance.to_string()).map_err(|err| {
+        anyhow::anyhow!("Failed to parse balance {} to BigDecimal: {}", balance, err)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TRACKED_ID: &str = "tracked.near";
+    const UNTRACKED_ID: &str = "untracked.near";
+
+    fn receipt(predecessor_id: &str, receiver_id: &str, signer_id: &str) -> Vec<u8> {
+        let receipt: near_primitives::views::ReceiptView =
+            serde_json::from_value(serde_json::json!({
+                "predecessor_id": predecessor_id,
+                "receiver_id": receiver_id,
+                "receipt_id": near_primitives::hash::CryptoHash::default().to_string(),
+                "receipt": {
+                    "Action": {
+                        "signer_id": signer_id,
+                        "signer_public_key": "ed25519:6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp",
+                        "gas_price": "0",
+                        "output_data_receivers": [],
+                        "input_data_ids": [],
+                        "actions": [],
+                    }
+                },
+            }))
+            .unwrap();
+        borsh::to_vec(&receipt).unwrap()
+    }
+
+    fn filtered(
+        add: impl FnOnce(&mut FilteredTransactions, &std::collections::HashSet<&str>),
+    ) -> bool {
+        let tracked_accounts = std::collections::HashSet::from([TRACKED_ID]);
+        let mut filtered_transactions = FilteredTransactions::default();
+        add(&mut filtered_transactions, &tracked_accounts);
+        filtered_transactions
+            .into_filtered()
+            .contains("transaction")
+    }
+
+    #[test]
+    fn test_transaction_of_untracked_accounts_is_filtered() {
+        assert!(filtered(|transactions, tracked_accounts| {
+            let body = receipt(UNTRACKED_ID, UNTRACKED_ID, UNTRACKED_ID);
+            transactions.add_receipt(
+                "transaction".to_string(),
+                UNTRACKED_ID,
+                Some(&body),
+                tracked_accounts,
+            );
+            transactions.add_outcome("transaction".to_string(), UNTRACKED_ID, tracked_accounts);
+        }));
+    }
+
+    #[test]
+    fn test_transaction_of_tracked_signer_is_kept() {
+        assert!(!filtered(|transactions, tracked_accounts| {
+            let body = receipt(UNTRACKED_ID, UNTRACKED_ID, TRACKED_ID);
+            transactions.add_receipt(
+                "transaction".to_string(),
+                UNTRACKED_ID,
+                Some(&body),
+                tracked_accounts,
+            );
+        }));
+    }
+
+    #[test]
+    fn test_transaction_of_tracked_receiver_is_kept() {
+        assert!(!filtered(|transactions, tracked_accounts| {
+            let body = receipt(UNTRACKED_ID, UNTRACKED_ID, UNTRACKED_ID);
+            transactions.add_receipt(
+                "transaction".to_string(),
+                UNTRACKED_ID,
+                Some(&body),
+                tracked_accounts,
+            );
+            transactions.add_outcome("transaction".to_string(), TRACKED_ID, tracked_accounts);
+        }));
+        assert!(!filtered(|transactions, tracked_accounts| {
+            let body = receipt(UNTRACKED_ID, TRACKED_ID, UNTRACKED_ID);
+            transactions.add_receipt(
+                "transaction".to_string(),
+                TRACKED_ID,
+                Some(&body),
+                tracked_accounts,
+            );
+        }));
+    }
+
+    #[test]
+    fn test_transaction_of_tracked_predecessor_is_kept() {
+        assert!(!filtered(|transactions, tracked_accounts| {
+            let body = receipt(TRACKED_ID, UNTRACKED_ID, UNTRACKED_ID);
+            transactions.add_receipt(
+                "transaction".to_string(),
+                UNTRACKED_ID,
+                Some(&body),
+                tracked_accounts,
+            );
+        }));
+    }
+
+    #[test]
+    fn test_transaction_without_receipt_bodies_is_kept() {
+        assert!(!filtered(|transactions, tracked_accounts| {
+            transactions.add_outcome("transaction".to_string(), UNTRACKED_ID, tracked_accounts);
+        }));
+        assert!(!filtered(|transactions, tracked_accounts| {
+            let body = receipt(UNTRACKED_ID, UNTRACKED_ID, UNTRACKED_ID);
+            transactions.add_receipt(
+                "transaction".to_string(),
+                UNTRACKED_ID,
+                Some(&body),
+                tracked_accounts,
+            );
+            transactions.add_receipt(
+                "transaction".to_string(),
+                UNTRACKED_ID,
+                None,
+                tracked_accounts,
+            );
+        }));
+        assert!(!filtered(|transactions, tracked_accounts| {
+            transactions.add_receipt(
+                "transaction".to_string(),
+                UNTRACKED_ID,
+                Some(&[0]),
+                tracked_accounts,
+            );
+        }));
     }
 }
@@ -0,0 +1,102 @@
+//! Typed columns of the Postgres models. The heights, hashes and account ids are stored
+//! as `numeric` and `text` and are parsed right when the row is decoded, so the invalid value
+//! fails the query with the name of the column instead of being passed around as a string.
+//! The wrapped value is taken with `.0`
+use bigdecimal::ToPrimitive;
+use std::str::FromStr;
+
+macro_rules! numeric_column {
+    ($name:ident, $type:ty, $to_primitive:ident) => {
+        #[doc = concat!("`numeric` column of the `", stringify!($type), "` value")]
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub(crate) struct $name(pub $type);
+
+        impl sqlx::Type<sqlx::Postgres> for $name {
+            fn type_info() -> sqlx::postgres::PgTypeInfo {
+                <bigdecimal::BigDecimal as sqlx::Type<sqlx::Postgres>>::type_info()
+            }
+
+            fn compatible(ty: &sqlx::postgres::PgTypeInfo) -> bool {
+                <bigdecimal::BigDecimal as sqlx::Type<sqlx::Postgres>>::compatible(ty)
+            }
+        }
+
+        impl<'r> sqlx::Decode<'r, sqlx::Postgres> for $name {
+            fn decode(
+                value: sqlx::postgres::PgValueRef<'r>,
+            ) -> Result<Self, sqlx::error::BoxDynError> {
+                let value =
+                    <bigdecimal::BigDecimal as sqlx::Decode<sqlx::Postgres>>::decode(value)?;
+                value.$to_primitive().map(Self).ok_or_else(|| {
+                    format!("{} is out of the `{}` range", value, stringify!($type)).into()
+                })
+            }
+        }
+    };
+}
+
+numeric_column!(U32, u32, to_u32);
+numeric_column!(U64, u64, to_u64);
+numeric_column!(U128, u128, to_u128);
+
+// `BigDecimal` can't be built from `u128` directly, so only the `u64` values are bound typed
+impl sqlx::Encode<'_, sqlx::Postgres> for U64 {
+    fn encode_by_ref(&self, buf: &mut sqlx::postgres::PgArgumentBuffer) -> sqlx::encode::IsNull {
+        <bigdecimal::BigDecimal as sqlx::Encode<sqlx::Postgres>>::encode(
+            bigdecimal::BigDecimal::from(self.0),
+            buf,
+        )
+    }
+}
+
+/// `text` column of the base58 encoded hash
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Hash(pub near_primitives::hash::CryptoHash);
+
+impl sqlx::Type<sqlx::Postgres> for Hash {
+    fn type_info() -> sqlx::postgres::PgTypeInfo {
+        <String as sqlx::Type<sqlx::Postgres>>::type_info()
+    }
+
+    fn compatible(ty: &sqlx::postgres::PgTypeInfo) -> bool {
+        <String as sqlx::Type<sqlx::Postgres>>::compatible(ty)
+    }
+}
+
+impl<'r> sqlx::Decode<'r, sqlx::Postgres> for Hash {
+    fn decode(value: sqlx::postgres::PgValueRef<'r>) -> Result<Self, sqlx::error::BoxDynError> {
+        let value = <&str as sqlx::Decode<sqlx::Postgres>>::decode(value)?;
+        near_primitives::hash::CryptoHash::from_str(value)
+            .map(Self)
+            .map_err(|err| format!("`{}` is not a valid CryptoHash: {}", value, err).into())
+    }
+}
+
+impl sqlx::Encode<'_, sqlx::Postgres> for Hash {
+    fn encode_by_ref(&self, buf: &mut sqlx::postgres::PgArgumentBuffer) -> sqlx::encode::IsNull {
+        <String as sqlx::Encode<sqlx::Postgres>>::encode(self.0.to_string(), buf)
+    }
+}
+
+/// `text` column of the account id
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct AccountId(pub near_primitives::types::AccountId);
+
+impl sqlx::Type<sqlx::Postgres> for AccountId {
+    fn type_info() -> sqlx::postgres::PgTypeInfo {
+        <String as sqlx::Type<sqlx::Postgres>>::type_info()
+    }
+
+    fn compatible(ty: &sqlx::postgres::PgTypeInfo) -> bool {
+        <String as sqlx::Type<sqlx::Postgres>>::compatible(ty)
+    }
+}
+
+impl<'r> sqlx::Decode<'r, sqlx::Postgres> for AccountId {
+    fn decode(value: sqlx::postgres::PgValueRef<'r>) -> Result<Self, sqlx::error::BoxDynError> {
+        let value = <&str as sqlx::Decode<sqlx::Postgres>>::decode(value)?;
+        near_primitives::types::AccountId::from_str(value)
+            .map(Self)
+            .map_err(|err| format!("`{}` is not a valid AccountId: {}", value, err).into())
+    }
+}
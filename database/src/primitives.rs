@@ -1,3 +1,28 @@
 pub type ShardId = u64;
 pub type HeightIncluded = u64;
 pub type ChunkHash = String;
+
+/// Event of the indexers propagated to the rpc-servers without polling the database
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IndexerNotification {
+    /// Indexer of the type ("tx", "state" or "epoch") processed the block,
+    /// `block_timestamp` is in nanoseconds
+    Head {
+        indexer_type: String,
+        block_height: u64,
+        block_timestamp: u64,
+    },
+    /// Details of the transactions are stored and can be served
+    TransactionsFinalized {
+        transaction_hashes: Vec<near_primitives::hash::CryptoHash>,
+    },
+}
+
+/// Keys of the account state checked by one step of the state buckets rebalancing
+#[derive(Debug, Clone, Default)]
+pub struct StateRebalanceBatch {
+    /// Number of the state rows moved into the buckets of their keys
+    pub moved_rows: u64,
+    /// Last checked key to continue the rebalancing after, `None` once the whole state is checked
+    pub next_data_key: Option<String>,
+}
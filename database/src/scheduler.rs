@@ -0,0 +1,117 @@
+use rand::Rng;
+
+/// Schedule of a recurring job.
+/// The job runs every `interval` plus a random delay up to `jitter`,
+/// so the same job of several instances doesn't hit the database at the same moment
+#[derive(Debug, Clone, Copy)]
+pub struct Schedule {
+    interval: std::time::Duration,
+    jitter: std::time::Duration,
+}
+
+impl Schedule {
+    pub fn every(interval: std::time::Duration) -> Self {
+        Self {
+            interval,
+            jitter: std::time::Duration::ZERO,
+        }
+    }
+
+    pub fn with_jitter(mut self, jitter: std::time::Duration) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    fn next_delay(&self) -> std::time::Duration {
+        if self.jitter.is_zero() {
+            return self.interval;
+        }
+        let jitter_millis = rand::thread_rng().gen_range(0..=self.jitter.as_millis() as u64);
+        self.interval + std::time::Duration::from_millis(jitter_millis)
+    }
+}
+
+/// In-process scheduler of the periodic maintenance jobs
+/// (cache sweeps, retention pruning, heartbeats, legacy rows rewrites).
+/// Every job runs in its own task, the next run is scheduled after the previous one finishes,
+/// so the runs of the same job never overlap. Failed runs are logged and counted in the metrics,
+/// the job keeps running by its schedule. The jobs are stopped when the scheduler is dropped
+#[derive(Default)]
+pub struct Scheduler {
+    jobs: std::sync::Mutex<Vec<(&'static str, tokio::task::JoinHandle<()>)>>,
+}
+
+impl Scheduler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Spawns the job running by the schedule. The first run happens after the first delay
+    pub fn schedule<F, Fut>(&self, job_name: &'static str, schedule: Schedule, job: F)
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: std::future::Future<Output = anyhow::Result<()>> + Send + 'static,
+    {
+        tracing::info!("Scheduling job {} with {:?}", job_name, schedule);
+        let handle = tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(schedule.next_delay()).await;
+                run_job(job_name, &job).await;
+            }
+        });
+        self.jobs
+            .lock()
+            .expect("scheduler jobs lock poisoned")
+            .push((job_name, handle));
+    }
+
+    /// Names of the scheduled jobs
+    pub fn job_names(&self) -> Vec<&'static str> {
+        self.jobs
+            .lock()
+            .expect("scheduler jobs lock poisoned")
+            .iter()
+            .map(|(job_name, _)| *job_name)
+            .collect()
+    }
+}
+
+impl Drop for Scheduler {
+    fn drop(&mut self) {
+        if let Ok(jobs) = self.jobs.get_mut() {
+            for (_, handle) in jobs.iter() {
+                handle.abort();
+            }
+        }
+    }
+}
+
+async fn run_job<F, Fut>(job_name: &'static str, job: &F)
+where
+    F: Fn() -> Fut,
+    Fut: std::future::Future<Output = anyhow::Result<()>>,
+{
+    let started_at = std::time::Instant::now();
+    let result = job().await;
+    crate::metrics::SCHEDULER_JOB_DURATION
+        .with_label_values(&[job_name])
+        .observe(started_at.elapsed().as_secs_f64());
+    match result {
+        Ok(()) => {
+            crate::metrics::SCHEDULER_JOB_RUNS
+                .with_label_values(&[job_name, "success"])
+                .inc();
+            if let Ok(now) = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH) {
+                crate::metrics::SCHEDULER_JOB_LAST_SUCCESS
+                    .with_label_values(&[job_name])
+                    .set(now.as_secs() as i64);
+            }
+        }
+        Err(err) => {
+            crate::metrics::SCHEDULER_JOB_RUNS
+                .with_label_values(&[job_name, "failure"])
+                .inc();
+            tracing::warn!("Scheduled job {} failed: {:?}", job_name, err);
+        }
+    }
+}
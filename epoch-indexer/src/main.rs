@@ -0,0 +1,133 @@
+use clap::Parser;
+use futures::StreamExt;
+
+use logic_state_indexer::{configs, epoch, metrics, NearClient};
+use readnode_primitives::indexer::CryptoHash;
+
+// Target for tracing logs
+const INDEXER: &str = "epoch_indexer";
+
+/// The last handled block, it's the last block of the epoch once the next epoch starts
+#[derive(Debug, Clone, Copy)]
+struct HandledBlock {
+    height: u64,
+    hash: CryptoHash,
+    timestamp: u64,
+    epoch_id: CryptoHash,
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    // We use it to automatically search the for root certificates to perform HTTPS calls
+    openssl_probe::init_ssl_cert_env_vars();
+
+    configuration::init_tracing(INDEXER).await?;
+    tracing::info!("Starting {} v{}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
+
+    let indexer_config = configuration::read_configuration::<configuration::EpochIndexerConfig>().await?;
+    let opts: configs::Opts = configs::Opts::parse();
+
+    // The validators and the protocol config of the past epochs are requested,
+    // we use the Referer header to ensure we take them from the native RPC node
+    let http_client_factory = http_client::HttpClientFactory::new(&indexer_config.http_client)?;
+    let rpc_client = http_client_factory
+        .rpc_client("near_rpc", opts.rpc_url(&indexer_config.general.near_rpc_url))
+        .header("Referer", &indexer_config.general.referer_header_value)?
+        .api_key(indexer_config.general.near_rpc_api_key.as_deref())?;
+    let near_client = logic_state_indexer::NearJsonRpc::new(rpc_client);
+
+    let protocol_config_view = near_client.protocol_config().await?;
+
+    let db_manager = database::prepare_db_manager::<database::PostgresDBManager>(
+        &indexer_config.database,
+        protocol_config_view.shard_layout,
+    )
+    .await?;
+    configs::check_network(&near_client, &db_manager, &indexer_config.general.chain_id).await?;
+    let start_block_height = configs::get_start_block_height(
+        &near_client,
+        &db_manager,
+        &opts.start_options,
+        &indexer_config.general.indexer_id,
+    )
+    .await?;
+
+    let start_block_height = if opts.exclude_start_block {
+        start_block_height + 1
+    } else {
+        start_block_height
+    };
+    let (sender, stream) = lake_streamer::streamer(
+        &indexer_config.lake_config.with_source(opts.blocks_source),
+        start_block_height,
+        Some(opts.blocks_preload_pool_size),
+    )
+    .await?;
+
+    // Initiate metrics http server
+    tokio::spawn(
+        metrics::init_server(indexer_config.general.metrics_server_port).expect("Failed to start metrics server"),
+    );
+
+    // The blocks are handled one by one, the epoch boundary is found by the previous block.
+    // The error stops the indexer before the meta is updated, so the epoch isn't skipped
+    // and is handled again on the restart `from-interruption`
+    let mut last_block: Option<HandledBlock> = None;
+    let mut stream = tokio_stream::wrappers::ReceiverStream::new(stream);
+    let mut result = Ok(());
+    while let Some(streamer_message) = stream.next().await {
+        let header = &streamer_message.block.header;
+        let block = HandledBlock {
+            height: header.height,
+            hash: header.hash,
+            timestamp: header.timestamp_nanosec,
+            epoch_id: header.epoch_id,
+        };
+        if let Err(err) = handle_block(block, last_block, &near_client, &db_manager, &indexer_config).await {
+            result = Err(err);
+            break;
+        }
+        last_block = Some(block);
+    }
+    drop(stream); // close the channel so the sender will stop
+    result?;
+
+    // propagate errors from the sender
+    match sender.await {
+        Ok(Ok(())) => Ok(()),
+        Ok(Err(e)) => Err(e),
+        Err(e) => Err(anyhow::Error::from(e)), // JoinError
+    }
+}
+
+/// Saves the validators of the epoch ended by the previous block and the protocol config
+/// of the epoch the block belongs to, once per epoch
+async fn handle_block(
+    block: HandledBlock,
+    last_block: Option<HandledBlock>,
+    near_client: &impl NearClient,
+    db_manager: &(impl database::StateIndexerDbManager + Sync + Send + 'static),
+    indexer_config: &configuration::EpochIndexerConfig,
+) -> anyhow::Result<()> {
+    if last_block.map(|last_block| last_block.epoch_id) != Some(block.epoch_id) {
+        if let Some(last_block) = last_block {
+            // The end height of the epoch is resolved by the hash of its last block
+            db_manager
+                .save_block(last_block.height, last_block.hash, last_block.timestamp)
+                .await?;
+            let ended_epoch = epoch::get_epoch_info_by_id(last_block.epoch_id, near_client).await?;
+            epoch::save_epoch_info(&ended_epoch, db_manager, None, last_block.hash).await?;
+        }
+        // The first handled epoch is saved as well, the indexer may start in the middle of it
+        let current_epoch = epoch::get_epoch_info_by_id(block.epoch_id, near_client).await?;
+        let protocol_config = epoch::get_epoch_protocol_config(&current_epoch, near_client).await?;
+        epoch::save_epoch_protocol_config(&current_epoch, &protocol_config, db_manager).await?;
+    }
+
+    db_manager
+        .update_epoch_meta(&indexer_config.general.indexer_id, block.height, block.timestamp)
+        .await?;
+    metrics::BLOCK_PROCESSED_TOTAL.inc();
+    metrics::LATEST_BLOCK_HEIGHT.set(i64::try_from(block.height)?);
+    Ok(())
+}
@@ -0,0 +1,231 @@
+#[macro_use]
+extern crate lazy_static;
+
+pub mod metrics;
+
+/// Factory of the outbound clients sharing the same HTTP client,
+/// so all the requests of the service reuse the pooled connections.
+/// Proxies are taken from the `HTTPS_PROXY`, `HTTP_PROXY` and `NO_PROXY` env variables
+#[derive(Debug, Clone)]
+pub struct HttpClientFactory {
+    client: reqwest::Client,
+    rpc_retry_attempts: usize,
+    rpc_retry_delay: std::time::Duration,
+}
+
+impl HttpClientFactory {
+    pub fn new(config: &configuration::HttpClientConfig) -> anyhow::Result<Self> {
+        let mut builder = reqwest::Client::builder()
+            .pool_max_idle_per_host(config.pool_max_idle_per_host)
+            .pool_idle_timeout(config.pool_idle_timeout)
+            .connect_timeout(config.connect_timeout)
+            .timeout(config.request_timeout);
+        if let Some(ca_bundle) = &config.ca_bundle {
+            let pem = std::fs::read(ca_bundle).map_err(|err| {
+                anyhow::anyhow!("Failed to read CA bundle {}: {}", ca_bundle.display(), err)
+            })?;
+            for certificate in reqwest::Certificate::from_pem_bundle(&pem)? {
+                builder = builder.add_root_certificate(certificate);
+            }
+            tracing::info!("Trusting the CA certificates from {}", ca_bundle.display());
+        }
+        Ok(Self {
+            client: builder.build()?,
+            rpc_retry_attempts: config.rpc_retry_attempts,
+            rpc_retry_delay: config.rpc_retry_delay,
+        })
+    }
+
+    /// Shared HTTP client for the requests which are not JSON-RPC calls
+    pub fn http_client(&self) -> reqwest::Client {
+        self.client.clone()
+    }
+
+    /// JSON-RPC client of the endpoint. `target` names the endpoint in the metrics,
+    /// e.g. `near_rpc` or `archival_rpc`
+    pub fn rpc_client(&self, target: &'static str, server_addr: &str) -> RpcClient {
+        RpcClient {
+            target,
+            client: near_jsonrpc_client::JsonRpcClient::with(self.client.clone())
+                .connect(server_addr),
+            retry_attempts: self.rpc_retry_attempts,
+            retry_delay: self.rpc_retry_delay,
+        }
+    }
+}
+
+/// NEAR JSON-RPC client counting the requests in the outbound requests metrics.
+/// The requests failed with the transient errors are retried with the exponential backoff
+#[derive(Debug, Clone)]
+pub struct RpcClient {
+    target: &'static str,
+    client: near_jsonrpc_client::JsonRpcClient,
+    retry_attempts: usize,
+    retry_delay: std::time::Duration,
+}
+
+impl RpcClient {
+    /// Adds the header to every request of the client
+    pub fn header(mut self, name: &'static str, value: &str) -> anyhow::Result<Self> {
+        self.client = self.client.header((name, value.to_string()))?;
+        Ok(self)
+    }
+
+    /// Sends the API key of the RPC provider in the `x-api-key` header of every request
+    pub fn api_key(self, api_key: Option<&str>) -> anyhow::Result<Self> {
+        match api_key {
+            Some(api_key) => self.header("x-api-key", api_key),
+            None => Ok(self),
+        }
+    }
+
+    pub fn server_addr(&self) -> &str {
+        self.client.server_addr()
+    }
+
+    pub async fn call<M>(
+        &self,
+        method: M,
+    ) -> near_jsonrpc_client::MethodCallResult<M::Response, M::Error>
+    where
+        M: near_jsonrpc_client::methods::RpcMethod,
+    {
+        let method_name = method.method_name().to_string();
+        let timer = metrics::OUTBOUND_RPC_REQUEST_DURATION
+            .with_label_values(&[self.target, &method_name])
+            .start_timer();
+        let mut result = self.client.call(&method).await;
+        let mut retry_delay = self.retry_delay;
+        for _ in 1..self.retry_attempts {
+            match &result {
+                Err(err) if is_transient_error(err) => {
+                    tracing::warn!(
+                        "Retrying `{}` request to {} in {:?} after the transient error",
+                        method_name,
+                        self.target,
+                        retry_delay,
+                    );
+                    metrics::OUTBOUND_RPC_RETRIES
+                        .with_label_values(&[self.target, &method_name])
+                        .inc();
+                    tokio::time::sleep(retry_delay).await;
+                    retry_delay *= 2;
+                    result = self.client.call(&method).await;
+                }
+                _ => break,
+            }
+        }
+        timer.observe_duration();
+        metrics::OUTBOUND_RPC_REQUESTS
+            .with_label_values(&[
+                self.target,
+                &method_name,
+                if result.is_ok() { "success" } else { "error" },
+            ])
+            .inc();
+        result
+    }
+}
+
+/// The failures to send the request or to receive the response, timeouts, rate limits
+/// and unavailable nodes are worth retrying. The request which can't be serialized and
+/// the response which can't be parsed fail the same way again, they are returned as is
+/// along with the errors of the method handlers
+fn is_transient_error<E>(err: &near_jsonrpc_client::errors::JsonRpcError<E>) -> bool {
+    match err {
+        near_jsonrpc_client::errors::JsonRpcError::TransportError(transport_error) => matches!(
+            transport_error,
+            near_jsonrpc_client::errors::RpcTransportError::SendError(
+                near_jsonrpc_client::errors::JsonRpcTransportSendError::PayloadSendError(_)
+            ) | near_jsonrpc_client::errors::RpcTransportError::RecvError(
+                near_jsonrpc_client::errors::JsonRpcTransportRecvError::PayloadRecvError(_)
+            )
+        ),
+        near_jsonrpc_client::errors::JsonRpcError::ServerError(server_error) => matches!(
+            server_error,
+            near_jsonrpc_client::errors::JsonRpcServerError::ResponseStatusError(
+                near_jsonrpc_client::errors::JsonRpcServerResponseStatusError::TooManyRequests
+                    | near_jsonrpc_client::errors::JsonRpcServerResponseStatusError::TimeoutError
+                    | near_jsonrpc_client::errors::JsonRpcServerResponseStatusError::ServiceUnavailable
+            )
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_jsonrpc_client::errors::{
+        JsonRpcError, JsonRpcServerError, JsonRpcServerResponseStatusError,
+        JsonRpcTransportSendError, RpcTransportError,
+    };
+
+    fn status_error(status_error: JsonRpcServerResponseStatusError) -> JsonRpcError<String> {
+        JsonRpcError::ServerError(JsonRpcServerError::ResponseStatusError(status_error))
+    }
+
+    #[tokio::test]
+    async fn test_send_error_is_transient() {
+        // Nothing listens on the port of the dropped listener, so the connection is refused
+        let address = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let err = near_jsonrpc_client::JsonRpcClient::connect(format!("http://{address}"))
+            .call(near_jsonrpc_client::methods::status::RpcStatusRequest)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            JsonRpcError::TransportError(RpcTransportError::SendError(
+                JsonRpcTransportSendError::PayloadSendError(_)
+            ))
+        ));
+        assert!(is_transient_error(&err));
+    }
+
+    #[test]
+    fn test_serialize_error_is_not_transient() {
+        let err: JsonRpcError<String> = JsonRpcError::TransportError(RpcTransportError::SendError(
+            JsonRpcTransportSendError::PayloadSerializeError(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "invalid request payload",
+            )),
+        ));
+        assert!(!is_transient_error(&err));
+    }
+
+    #[test]
+    fn test_overloaded_node_errors_are_transient() {
+        assert!(is_transient_error(&status_error(
+            JsonRpcServerResponseStatusError::TooManyRequests
+        )));
+        assert!(is_transient_error(&status_error(
+            JsonRpcServerResponseStatusError::TimeoutError
+        )));
+        assert!(is_transient_error(&status_error(
+            JsonRpcServerResponseStatusError::ServiceUnavailable
+        )));
+    }
+
+    #[test]
+    fn test_rejected_request_errors_are_not_transient() {
+        assert!(!is_transient_error(&status_error(
+            JsonRpcServerResponseStatusError::Unauthorized
+        )));
+        assert!(!is_transient_error(&status_error(
+            JsonRpcServerResponseStatusError::BadRequest
+        )));
+    }
+
+    #[test]
+    fn test_method_errors_are_not_transient() {
+        let err: JsonRpcError<String> = JsonRpcError::ServerError(
+            JsonRpcServerError::HandlerError("UNKNOWN_BLOCK".to_string()),
+        );
+        assert!(!is_transient_error(&err));
+        let err: JsonRpcError<String> =
+            JsonRpcError::ServerError(JsonRpcServerError::InternalError { info: None });
+        assert!(!is_transient_error(&err));
+    }
+}
@@ -0,0 +1,55 @@
+use prometheus::{HistogramOpts, HistogramVec, IntCounterVec, Opts};
+
+type Result<T, E> = std::result::Result<T, E>;
+
+fn register_int_counter_vec(
+    name: &str,
+    help: &str,
+    label_names: &[&str],
+) -> Result<IntCounterVec, prometheus::Error> {
+    let opts = Opts::new(name, help);
+    let counter = IntCounterVec::new(opts, label_names)?;
+    prometheus::register(Box::new(counter.clone()))?;
+    Ok(counter)
+}
+
+fn register_histogram_vec(
+    name: &str,
+    help: &str,
+    label_names: &[&str],
+) -> Result<HistogramVec, prometheus::Error> {
+    let opts = HistogramOpts::new(name, help);
+    let histogram = HistogramVec::new(opts, label_names)?;
+    prometheus::register(Box::new(histogram.clone()))?;
+    Ok(histogram)
+}
+
+lazy_static! {
+    pub(crate) static ref OUTBOUND_RPC_REQUESTS: IntCounterVec = register_int_counter_vec(
+        "outbound_rpc_requests_total",
+        "Total number of the NEAR JSON-RPC requests sent by the service by target endpoint, method and result",
+        &["target", "method_name", "result"]
+    )
+    .unwrap();
+    pub(crate) static ref OUTBOUND_RPC_REQUEST_DURATION: HistogramVec = register_histogram_vec(
+        "outbound_rpc_request_duration_seconds",
+        "Duration of the NEAR JSON-RPC requests sent by the service by target endpoint and method",
+        &["target", "method_name"]
+    )
+    .unwrap();
+    pub(crate) static ref OUTBOUND_RPC_RETRIES: IntCounterVec = register_int_counter_vec(
+        "outbound_rpc_retries_total",
+        "Total number of the NEAR JSON-RPC requests retried after the transient error by target endpoint and method",
+        &["target", "method_name"]
+    )
+    .unwrap();
+}
+
+/// Metrics of the outbound requests to describe them along with the metrics of the service
+pub fn collectors() -> Vec<&'static dyn prometheus::core::Collector> {
+    vec![
+        &*OUTBOUND_RPC_REQUESTS,
+        &*OUTBOUND_RPC_REQUEST_DURATION,
+        &*OUTBOUND_RPC_RETRIES,
+    ]
+}
@@ -0,0 +1,263 @@
+use readnode_primitives::indexer::{self, near_primitives};
+
+/// How often the directory is checked for the new blocks once the stream caught up with it
+const POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(500);
+/// The files of the block can be still being written when the block directory appears,
+/// the incomplete block is read again this number of times before the stream fails
+const BLOCK_READ_ATTEMPTS: usize = 10;
+/// Number of the heights following the last streamed block probed for the next block directory.
+/// The chain skips a few heights at most, a longer gap of the chain halt isn't crossed
+/// and the stream has to be restarted from the block after it
+const SKIP_WINDOW: u64 = 1000;
+
+/// Streams the blocks of the directory in the lake format: the `{block_height:012}` directory
+/// of every block with the `block.json` and the `shard_{shard_id}.json` of every chunk in it.
+/// The heights skipped by the chain have no directories, the stream waits for the new blocks
+/// once it reached the last one
+pub(crate) fn streamer(
+    blocks_path: std::path::PathBuf,
+    start_block_height: near_primitives::types::BlockHeight,
+    blocks_preload_pool_size: usize,
+) -> (
+    tokio::task::JoinHandle<anyhow::Result<()>>,
+    tokio::sync::mpsc::Receiver<indexer::StreamerMessage>,
+) {
+    let (sender, receiver) = tokio::sync::mpsc::channel(blocks_preload_pool_size.max(1));
+    let handle = tokio::spawn(start(blocks_path, start_block_height, sender));
+    (handle, receiver)
+}
+
+async fn start(
+    blocks_path: std::path::PathBuf,
+    start_block_height: near_primitives::types::BlockHeight,
+    sender: tokio::sync::mpsc::Sender<indexer::StreamerMessage>,
+) -> anyhow::Result<()> {
+    tracing::info!(
+        "Streaming the blocks from {} starting from block {}",
+        blocks_path.display(),
+        start_block_height
+    );
+    if !tokio::fs::metadata(&blocks_path)
+        .await
+        .map_err(|err| {
+            anyhow::anyhow!(
+                "Failed to read the blocks directory {}: {}",
+                blocks_path.display(),
+                err
+            )
+        })?
+        .is_dir()
+    {
+        anyhow::bail!(
+            "The blocks path {} is not a directory",
+            blocks_path.display()
+        );
+    }
+    let mut next_block_height = start_block_height;
+    let mut last_block_height = None;
+    loop {
+        let Some(block_height) = find_next_block_height(&blocks_path, next_block_height).await?
+        else {
+            tokio::time::sleep(POLL_INTERVAL).await;
+            continue;
+        };
+        let streamer_message = read_block(&blocks_path, block_height).await?;
+        if !follows_last_block(last_block_height, streamer_message.block.header.prev_height) {
+            // The previous block is missing in the probed heights, its directory lands later
+            tracing::debug!(
+                "Block {} is waiting for its previous block {:?}",
+                block_height,
+                streamer_message.block.header.prev_height
+            );
+            tokio::time::sleep(POLL_INTERVAL).await;
+            continue;
+        }
+        if sender.send(streamer_message).await.is_err() {
+            // The receiver is dropped, nobody reads the stream anymore
+            return Ok(());
+        }
+        last_block_height = Some(block_height);
+        next_block_height = block_height + 1;
+    }
+}
+
+/// Returns the height of the first block directory of the `SKIP_WINDOW` heights
+/// starting from `from_block_height`. The heights are probed one by one,
+/// so the directory of all the blocks is never listed
+async fn find_next_block_height(
+    blocks_path: &std::path::Path,
+    from_block_height: near_primitives::types::BlockHeight,
+) -> anyhow::Result<Option<near_primitives::types::BlockHeight>> {
+    for block_height in from_block_height..from_block_height.saturating_add(SKIP_WINDOW) {
+        match tokio::fs::metadata(block_path(blocks_path, block_height)).await {
+            Ok(metadata) if metadata.is_dir() => return Ok(Some(block_height)),
+            Ok(_) => continue,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(err) => {
+                anyhow::bail!(
+                    "Failed to probe block {} in {}: {}",
+                    block_height,
+                    blocks_path.display(),
+                    err
+                )
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// The block directories can land out of order, e.g. with the parallel syncs of the bucket,
+/// so the block follows the last streamed one only if it's its previous block.
+/// The first streamed block has nothing to follow
+fn follows_last_block(
+    last_block_height: Option<near_primitives::types::BlockHeight>,
+    prev_height: Option<near_primitives::types::BlockHeight>,
+) -> bool {
+    match (last_block_height, prev_height) {
+        (Some(last_block_height), Some(prev_height)) => prev_height <= last_block_height,
+        _ => true,
+    }
+}
+
+fn block_path(
+    blocks_path: &std::path::Path,
+    block_height: near_primitives::types::BlockHeight,
+) -> std::path::PathBuf {
+    blocks_path.join(format!("{:0>12}", block_height))
+}
+
+async fn read_block(
+    blocks_path: &std::path::Path,
+    block_height: near_primitives::types::BlockHeight,
+) -> anyhow::Result<indexer::StreamerMessage> {
+    let block_path = block_path(blocks_path, block_height);
+    let mut attempt = 1;
+    loop {
+        match read_block_files(&block_path).await {
+            Ok(streamer_message) => return Ok(streamer_message),
+            Err(err) if attempt < BLOCK_READ_ATTEMPTS => {
+                tracing::debug!(
+                    "Block {} is not complete yet, retrying: {:?}",
+                    block_height,
+                    err
+                );
+                attempt += 1;
+                tokio::time::sleep(POLL_INTERVAL).await;
+            }
+            Err(err) => {
+                anyhow::bail!(
+                    "Failed to read block {} from {}: {:?}",
+                    block_height,
+                    block_path.display(),
+                    err
+                )
+            }
+        }
+    }
+}
+
+async fn read_block_files(
+    block_path: &std::path::Path,
+) -> anyhow::Result<indexer::StreamerMessage> {
+    let block: near_primitives::views::BlockView =
+        serde_json::from_slice(&tokio::fs::read(block_path.join("block.json")).await?)?;
+    let mut shards = Vec::with_capacity(block.chunks.len());
+    for chunk in &block.chunks {
+        let shard_path = block_path.join(format!("shard_{}.json", chunk.shard_id));
+        shards.push(serde_json::from_slice::<indexer::IndexerShard>(
+            &tokio::fs::read(shard_path).await?,
+        )?);
+    }
+    Ok(indexer::StreamerMessage { block, shards })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Empty blocks directory unique to the test
+    fn blocks_dir(test_name: &str) -> std::path::PathBuf {
+        let blocks_path = std::env::temp_dir().join(format!(
+            "lake-streamer-{}-{}",
+            test_name,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&blocks_path);
+        std::fs::create_dir_all(&blocks_path).unwrap();
+        blocks_path
+    }
+
+    fn add_block(blocks_path: &std::path::Path, block_height: u64) {
+        std::fs::create_dir_all(block_path(blocks_path, block_height)).unwrap();
+    }
+
+    fn find(
+        blocks_path: &std::path::Path,
+        from_block_height: u64,
+    ) -> Option<near_primitives::types::BlockHeight> {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(find_next_block_height(blocks_path, from_block_height))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_block_path_is_zero_padded() {
+        assert_eq!(
+            block_path(std::path::Path::new("/lake"), 123),
+            std::path::Path::new("/lake/000000000123")
+        );
+    }
+
+    #[test]
+    fn test_next_block_is_found_across_skipped_heights() {
+        let blocks_path = blocks_dir("skipped-heights");
+        add_block(&blocks_path, 100);
+        add_block(&blocks_path, 103);
+        assert_eq!(find(&blocks_path, 100), Some(100));
+        assert_eq!(find(&blocks_path, 101), Some(103));
+        assert_eq!(find(&blocks_path, 104), None);
+        std::fs::remove_dir_all(&blocks_path).unwrap();
+    }
+
+    #[test]
+    fn test_next_block_is_not_found_beyond_skip_window() {
+        let blocks_path = blocks_dir("skip-window");
+        add_block(&blocks_path, 100 + SKIP_WINDOW);
+        assert_eq!(find(&blocks_path, 100), None);
+        assert_eq!(find(&blocks_path, 101), Some(100 + SKIP_WINDOW));
+        std::fs::remove_dir_all(&blocks_path).unwrap();
+    }
+
+    #[test]
+    fn test_block_files_are_not_blocks() {
+        let blocks_path = blocks_dir("block-files");
+        std::fs::write(block_path(&blocks_path, 100), b"").unwrap();
+        add_block(&blocks_path, 101);
+        assert_eq!(find(&blocks_path, 100), Some(101));
+        std::fs::remove_dir_all(&blocks_path).unwrap();
+    }
+
+    #[test]
+    fn test_late_lower_block_is_found_first() {
+        let blocks_path = blocks_dir("late-lower-block");
+        add_block(&blocks_path, 100);
+        add_block(&blocks_path, 102);
+        // Block 102 follows block 101 which isn't synced yet
+        assert_eq!(find(&blocks_path, 101), Some(102));
+        assert!(!follows_last_block(Some(100), Some(101)));
+        add_block(&blocks_path, 101);
+        assert_eq!(find(&blocks_path, 101), Some(101));
+        assert!(follows_last_block(Some(100), Some(100)));
+        assert!(follows_last_block(Some(101), Some(101)));
+        std::fs::remove_dir_all(&blocks_path).unwrap();
+    }
+
+    #[test]
+    fn test_first_block_follows_nothing() {
+        assert!(follows_last_block(None, Some(99)));
+        assert!(follows_last_block(Some(100), None));
+    }
+}
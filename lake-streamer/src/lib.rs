@@ -0,0 +1,45 @@
+use configuration::LakeSource;
+use readnode_primitives::indexer::near_primitives;
+
+mod filesystem;
+
+/// Number of the blocks read from the directory ahead of the processed one by default
+const DEFAULT_FILESYSTEM_PRELOAD_POOL_SIZE: usize = 100;
+
+/// Streams the blocks starting from `start_block_height` from the source of the lake config.
+/// The stream of the `filesystem` source follows the new blocks written to the directory
+pub async fn streamer(
+    lake_config: &configuration::LakeConfig,
+    start_block_height: near_primitives::types::BlockHeight,
+    blocks_preload_pool_size: Option<usize>,
+) -> anyhow::Result<(
+    tokio::task::JoinHandle<anyhow::Result<()>>,
+    tokio::sync::mpsc::Receiver<readnode_primitives::indexer::StreamerMessage>,
+)> {
+    match lake_config.source {
+        LakeSource::S3 | LakeSource::Gcs => {
+            if lake_config.aws_access_key_id.is_empty()
+                || lake_config.aws_secret_access_key.is_empty()
+            {
+                anyhow::bail!(
+                    "`aws_access_key_id` and `aws_secret_access_key` are required for the {:?} lake source",
+                    lake_config.source
+                );
+            }
+            let lake_config = lake_config
+                .lake_config(start_block_height, blocks_preload_pool_size)
+                .await?;
+            Ok(near_lake_framework::streamer(lake_config))
+        }
+        LakeSource::Filesystem => {
+            let Some(blocks_path) = &lake_config.blocks_path else {
+                anyhow::bail!("`blocks_path` is required for the filesystem lake source");
+            };
+            Ok(filesystem::streamer(
+                blocks_path.clone(),
+                start_block_height,
+                blocks_preload_pool_size.unwrap_or(DEFAULT_FILESYSTEM_PRELOAD_POOL_SIZE),
+            ))
+        }
+    }
+}
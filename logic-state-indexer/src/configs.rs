@@ -7,6 +7,28 @@ pub use clap::{Parser, Subcommand};
 pub struct Opts {
     #[clap(subcommand)]
     pub start_options: StartOptions,
+    /// Number of blocks preloaded from the lake ahead of the processed one.
+    /// Controls the S3 requests rate and the memory usage of the indexer
+    #[clap(long, default_value_t = 100)]
+    pub blocks_preload_pool_size: usize,
+    /// Start from the block following the start block instead of including it
+    #[clap(long)]
+    pub exclude_start_block: bool,
+    /// Storage the lake blocks are read from: s3, gcs or filesystem.
+    /// Overrides the `source` of the `[lake_config]` config section
+    #[clap(long)]
+    pub blocks_source: Option<configuration::LakeSource>,
+    /// NEAR JSON-RPC endpoint of the indexer, e.g. the own node or the paid provider.
+    /// Overrides the `near_rpc_url` of the config
+    #[clap(long, env = "RPC_URL")]
+    pub rpc_url: Option<String>,
+}
+
+impl Opts {
+    /// NEAR JSON-RPC endpoint from the command line or the `near_rpc_url` of the config
+    pub fn rpc_url<'a>(&'a self, near_rpc_url: &'a str) -> &'a str {
+        self.rpc_url.as_deref().unwrap_or(near_rpc_url)
+    }
 }
 
 #[allow(clippy::enum_variant_names)]
@@ -20,6 +42,16 @@ pub enum StartOptions {
         height: Option<u64>,
     },
     FromLatest,
+    /// Moves the contract state of the accounts into the buckets of the configured
+    /// `state_key_buckets` instead of indexing, e.g. the state of the wide contracts
+    /// written before the buckets were enabled
+    RebalanceStateBuckets {
+        #[clap(required = true, num_args = 1..)]
+        account_ids: Vec<near_primitives::types::AccountId>,
+        /// Number of the state keys moved at once
+        #[clap(long, default_value_t = 1000)]
+        batch_size: u64,
+    },
 }
 
 pub async fn get_start_block_height(
@@ -40,10 +72,32 @@ pub async fn get_start_block_height(
             }
         }
         StartOptions::FromLatest => final_block_height(near_client).await?,
+        StartOptions::RebalanceStateBuckets { .. } => {
+            anyhow::bail!("`rebalance-state-buckets` does not start the indexing")
+        }
     };
     Ok(start_block_height - 100) // Start just a bit earlier to overlap indexed blocks to ensure we don't miss anything in-between
 }
 
+/// Checks the node is on the configured network and the database contains the data
+/// of the same network. The network is recorded if the database is empty
+pub async fn check_network(
+    near_client: &impl crate::NearClient,
+    db_manager: &(impl database::StateIndexerDbManager + Sync + Send + 'static),
+    chain_id: &configuration::ChainId,
+) -> anyhow::Result<()> {
+    let network = near_client.network_identity().await?;
+    chain_id.ensure_matches(&network.chain_id)?;
+    network.ensure_same_network(&db_manager.save_network_identity(&network).await?)?;
+    tracing::info!(
+        target: crate::INDEXER,
+        "Indexing the network `{}` (genesis hash {})",
+        network.chain_id,
+        network.genesis_hash
+    );
+    Ok(())
+}
+
 pub(crate) async fn final_block_height(
     near_client: &impl crate::NearClient,
 ) -> anyhow::Result<u64> {
@@ -1,4 +1,4 @@
-use near_indexer_primitives::{near_primitives, CryptoHash};
+use readnode_primitives::indexer::{near_primitives, CryptoHash};
 
 use tokio_retry::{strategy::FixedInterval, Retry};
 
@@ -77,3 +77,56 @@ pub async fn save_epoch_info(
     );
     Ok(())
 }
+
+pub async fn get_epoch_protocol_config(
+    epoch: &readnode_primitives::IndexedEpochInfo,
+    near_client: &impl crate::NearClient,
+) -> anyhow::Result<near_chain_configs::ProtocolConfigView> {
+    let retry_strategy = FixedInterval::from_millis(500).take(SAVE_ATTEMPTS);
+
+    let result = Retry::spawn(retry_strategy, || async {
+        near_client
+            .protocol_config_by_block_height(epoch.epoch_start_height)
+            .await
+            .map_err(|e| {
+                tracing::debug!(
+                    "Error fetching protocol config for epoch_id {}: {:?}",
+                    epoch.epoch_id,
+                    e
+                );
+                e
+            })
+    })
+    .await;
+
+    result.map_err(|e| {
+        anyhow::anyhow!(
+            "Failed to fetch protocol config for epoch_id {}: {}",
+            epoch.epoch_id,
+            e
+        )
+    })
+}
+
+pub async fn save_epoch_protocol_config(
+    epoch: &readnode_primitives::IndexedEpochInfo,
+    protocol_config: &near_chain_configs::ProtocolConfigView,
+    db_manager: &(impl database::StateIndexerDbManager + Sync + Send + 'static),
+) -> anyhow::Result<()> {
+    db_manager
+        .save_protocol_config(
+            epoch.epoch_id,
+            epoch.epoch_height,
+            epoch.epoch_start_height,
+            protocol_config,
+        )
+        .await?;
+
+    tracing::info!(
+        "Save protocol config: epoch_id: {:?}, epoch_height: {:?}, protocol_version: {}",
+        epoch.epoch_id,
+        epoch.epoch_height,
+        protocol_config.protocol_version,
+    );
+    Ok(())
+}
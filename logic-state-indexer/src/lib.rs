@@ -1,8 +1,8 @@
 use std::collections::HashMap;
 
 pub use near_client::{NearClient, NearJsonRpc};
-use near_indexer_primitives::views::StateChangeValueView;
-use near_indexer_primitives::CryptoHash;
+use readnode_primitives::indexer::views::StateChangeValueView;
+use readnode_primitives::indexer::CryptoHash;
 
 use futures::FutureExt;
 use itertools::Itertools;
@@ -13,9 +13,11 @@ extern crate lazy_static;
 use tokio_retry::{strategy::FixedInterval, Retry};
 
 pub mod configs;
-mod epoch;
+pub mod epoch;
 pub mod metrics;
 mod near_client;
+pub mod state_buckets;
+pub mod state_growth;
 
 const SAVE_ATTEMPTS: usize = 20;
 
@@ -74,6 +76,7 @@ impl StateChangesToStore {
         db_manager: &(impl database::StateIndexerDbManager + Sync + Send + 'static),
         block_height: u64,
         block_hash: CryptoHash,
+        unfinished_block_height: Option<u64>,
     ) -> anyhow::Result<()> {
         if !self.access_key.is_empty() {
             let futures: Vec<_> = self
@@ -90,6 +93,7 @@ impl StateChangesToStore {
                         state_changes,
                         block_height,
                         block_hash,
+                        unfinished_block_height,
                     )
                 })
                 .collect();
@@ -177,9 +181,15 @@ impl StateChangesToStore {
         db_manager: &(impl database::StateIndexerDbManager + Sync + Send + 'static),
         block_height: u64,
         block_hash: CryptoHash,
+        unfinished_block_height: Option<u64>,
     ) -> anyhow::Result<()> {
         let save_data_future = self.save_data(db_manager, block_height, block_hash);
-        let save_access_key_future = self.save_access_key(db_manager, block_height, block_hash);
+        let save_access_key_future = self.save_access_key(
+            db_manager,
+            block_height,
+            block_hash,
+            unfinished_block_height,
+        );
         let save_contract_future = self.save_contract(db_manager, block_height, block_hash);
         let save_account_future = self.save_account(db_manager, block_height, block_hash);
 
@@ -202,7 +212,7 @@ impl StateChangesToStore {
 #[derive(Debug)]
 struct ShardedStateChangesWithCause {
     shard_id: u64,
-    state_change: near_indexer_primitives::views::StateChangeWithCauseView,
+    state_change: readnode_primitives::indexer::views::StateChangeWithCauseView,
 }
 
 #[cfg_attr(
@@ -210,7 +220,7 @@ struct ShardedStateChangesWithCause {
     tracing::instrument(skip(streamer_message, db_manager))
 )]
 pub async fn handle_streamer_message(
-    streamer_message: near_indexer_primitives::StreamerMessage,
+    streamer_message: readnode_primitives::indexer::StreamerMessage,
     db_manager: &(impl database::StateIndexerDbManager + Sync + Send + 'static),
     near_client: &(impl NearClient + std::fmt::Debug + Sync),
     indexer_config: impl configuration::RightsizingConfig
@@ -233,6 +243,15 @@ pub async fn handle_streamer_message(
         .await
         .block_heights_processing
         .insert(block_height);
+    // The blocks are handled concurrently, the changes of the lower blocks still being handled
+    // may be not stored yet. The access keys values are compared with the stored ones above them only
+    let unfinished_block_height = stats
+        .read()
+        .await
+        .block_heights_processing
+        .range(..block_height)
+        .next_back()
+        .copied();
 
     let handle_epoch_future = handle_epoch(
         stats.read().await.current_epoch_id,
@@ -242,8 +261,11 @@ pub async fn handle_streamer_message(
         near_client,
         db_manager,
     );
-    let update_meta_future =
-        db_manager.update_meta(indexer_config.indexer_id().as_ref(), block_height);
+    let update_meta_future = db_manager.update_meta(
+        indexer_config.indexer_id().as_ref(),
+        block_height,
+        streamer_message.block.header.timestamp,
+    );
 
     let retry_strategy = FixedInterval::from_millis(500).take(SAVE_ATTEMPTS);
 
@@ -252,6 +274,7 @@ pub async fn handle_streamer_message(
             .save_block_with_chunks(
                 block_height,
                 block_hash,
+                streamer_message.block.header.timestamp,
                 streamer_message
                     .block
                     .chunks
@@ -275,12 +298,76 @@ pub async fn handle_streamer_message(
                 e
             })
     });
+    let handle_block_producers_future = Retry::spawn(retry_strategy.clone(), || async {
+        db_manager
+            .save_block_producers(
+                block_height,
+                block_hash,
+                current_epoch_id,
+                &streamer_message.block.author,
+                // Chunks are present in the shards only in the blocks they are included in
+                // for the first time, the missed chunks are not attributed
+                streamer_message
+                    .shards
+                    .iter()
+                    .filter_map(|shard| shard.chunk.as_ref())
+                    .map(|chunk| {
+                        (
+                            chunk.header.shard_id,
+                            chunk.header.chunk_hash.to_string(),
+                            chunk.author.clone(),
+                        )
+                    })
+                    .collect(),
+            )
+            .await
+            .map_err(|e| {
+                tracing::warn!(
+                    target: crate::INDEXER,
+                    "Failed to save block producers: {}",
+                    e
+                );
+                e
+            })
+    });
+    let handle_chunks_congestion_future = Retry::spawn(retry_strategy.clone(), || async {
+        db_manager
+            .save_chunks_congestion(
+                block_height,
+                streamer_message
+                    .block
+                    .chunks
+                    .iter()
+                    .filter_map(|chunk| {
+                        chunk.congestion_info.clone().map(|congestion_info| {
+                            (
+                                chunk.shard_id,
+                                readnode_primitives::ChunkCongestion {
+                                    height_included: chunk.height_included,
+                                    congestion_info,
+                                },
+                            )
+                        })
+                    })
+                    .collect(),
+            )
+            .await
+            .map_err(|e| {
+                tracing::warn!(
+                    target: crate::INDEXER,
+                    "Failed to save chunks congestion: {}",
+                    e
+                );
+                e
+            })
+    });
     let handle_state_change_future = Retry::spawn(retry_strategy, || async {
         handle_state_changes(
             &streamer_message,
             db_manager,
             block_height,
             block_hash,
+            unfinished_block_height,
             &indexer_config,
             shard_layout,
         )
@@ -298,6 +385,8 @@ pub async fn handle_streamer_message(
     futures::future::join_all([
         handle_epoch_future.boxed(),
         handle_block_future.boxed(),
+        handle_block_producers_future.boxed(),
+        handle_chunks_congestion_future.boxed(),
         handle_state_change_future.boxed(),
         update_meta_future.boxed(),
     ])
@@ -309,6 +398,7 @@ pub async fn handle_streamer_message(
     // Prometheus Gauge Metric type do not support u64
     // https://github.com/tikv/rust-prometheus/issues/470
     metrics::LATEST_BLOCK_HEIGHT.set(i64::try_from(block_height)?);
+    metrics::observe_block_indexing_latency(streamer_message.block.header.timestamp);
 
     let mut stats_lock = stats.write().await;
     stats_lock.block_heights_processing.remove(&block_height);
@@ -372,10 +462,11 @@ async fn handle_epoch(
     tracing::instrument(skip(streamer_message, db_manager))
 )]
 async fn handle_state_changes(
-    streamer_message: &near_indexer_primitives::StreamerMessage,
+    streamer_message: &readnode_primitives::indexer::StreamerMessage,
     db_manager: &(impl database::StateIndexerDbManager + Sync + Send + 'static),
     block_height: u64,
     block_hash: CryptoHash,
+    unfinished_block_height: Option<u64>,
     indexer_config: &(impl configuration::RightsizingConfig + std::fmt::Debug),
     shard_layout: &near_primitives::shard_layout::ShardLayout,
 ) -> anyhow::Result<()> {
@@ -386,10 +477,8 @@ async fn handle_state_changes(
         account: HashMap::new(),
     };
 
-    let initial_state_changes = streamer_message
-        .shards
-        .iter()
-        .flat_map(|shard| shard.clone().state_changes.into_iter());
+    let initial_state_changes =
+        readnode_primitives::indexer::state_changes(streamer_message).cloned();
 
     // Collecting a unique list of StateChangeWithCauseView for account_id + change kind + suffix
     // by overwriting the records in the HashMap
@@ -403,11 +492,15 @@ async fn handle_state_changes(
             }
             | StateChangeValueView::DataDeletion { account_id, key } => {
                 let shard_id =
-                    near_primitives::shard_layout::account_id_to_shard_id(account_id, shard_layout);
+                    readnode_primitives::indexer::account_id_to_shard_id(account_id, shard_layout);
                 // returning a hex-encoded key to ensure we store data changes to the key
                 // (if there is more than one change to the same key)
                 let data_key: &[u8] = key.as_ref();
-                let key = format!("{}_data_{}", account_id.as_str(), hex::encode(data_key));
+                let key = format!(
+                    "{}_data_{}",
+                    account_id.as_str(),
+                    readnode_primitives::encoding::encode_hex(data_key)
+                );
                 // This will override the previous record for this account_id + state change kind + suffix
                 state_changes_to_store.data.insert(
                     key,
@@ -427,13 +520,13 @@ async fn handle_state_changes(
                 public_key,
             } => {
                 let shard_id =
-                    near_primitives::shard_layout::account_id_to_shard_id(account_id, shard_layout);
+                    readnode_primitives::indexer::account_id_to_shard_id(account_id, shard_layout);
                 // returning a hex-encoded key to ensure we store data changes to the key
                 // (if there is more than one change to the same key)
                 let key = format!(
                     "{}_access_key_{}",
                     account_id.as_str(),
-                    hex::encode(borsh::to_vec(&public_key)?)
+                    readnode_primitives::encoding::encode_hex(&borsh::to_vec(&public_key)?)
                 );
                 // This will override the previous record for this account_id + state change kind + suffix
                 state_changes_to_store.access_key.insert(
@@ -448,7 +541,7 @@ async fn handle_state_changes(
             StateChangeValueView::ContractCodeUpdate { account_id, .. }
             | StateChangeValueView::ContractCodeDeletion { account_id } => {
                 let shard_id =
-                    near_primitives::shard_layout::account_id_to_shard_id(account_id, shard_layout);
+                    readnode_primitives::indexer::account_id_to_shard_id(account_id, shard_layout);
                 let key = format!("{}_contract", account_id.as_str());
                 // This will override the previous record for this account_id + state change kind + suffix
                 state_changes_to_store.contract.insert(
@@ -462,7 +555,7 @@ async fn handle_state_changes(
             StateChangeValueView::AccountUpdate { account_id, .. }
             | StateChangeValueView::AccountDeletion { account_id } => {
                 let shard_id =
-                    near_primitives::shard_layout::account_id_to_shard_id(account_id, shard_layout);
+                    readnode_primitives::indexer::account_id_to_shard_id(account_id, shard_layout);
                 let key = format!("{}_account", account_id.as_str());
                 // This will override the previous record for this account_id + state change kind + suffix
                 state_changes_to_store.account.insert(
@@ -477,6 +570,11 @@ async fn handle_state_changes(
     }
 
     state_changes_to_store
-        .save_state_changes(db_manager, block_height, block_hash)
+        .save_state_changes(
+            db_manager,
+            block_height,
+            block_hash,
+            unfinished_block_height,
+        )
         .await
 }
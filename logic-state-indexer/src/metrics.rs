@@ -1,5 +1,5 @@
 use actix_web::{get, App, HttpServer, Responder};
-use prometheus::{Encoder, IntCounter, IntGauge, Opts};
+use prometheus::{Encoder, Histogram, IntCounter, IntGauge, Opts};
 
 type Result<T, E> = std::result::Result<T, E>;
 
@@ -28,6 +28,28 @@ lazy_static! {
         "Last seen block height by indexer"
     )
     .unwrap();
+    pub static ref BLOCK_INDEXING_LATENCY_SECONDS: Histogram = database::metrics::register_histogram(
+        "block_indexing_latency_seconds",
+        "Seconds between the block timestamp and the moment the indexer finished writing the block",
+        &database::metrics::BLOCK_LATENCY_BUCKETS
+    )
+    .unwrap();
+    pub static ref STATE_GROWTH_LATEST_DAY: IntGauge = try_create_int_gauge(
+        "state_growth_latest_day",
+        "Latest day (days since the Unix epoch) with the state growth aggregated"
+    )
+    .unwrap();
+    pub static ref STATE_GROWTH_TOP_ACCOUNT_BYTES: IntGauge = try_create_int_gauge(
+        "state_growth_top_account_bytes",
+        "Largest net state growth of a single account within the latest aggregated day in bytes"
+    )
+    .unwrap();
+}
+
+/// Records the latency of the indexed block from its timestamp.
+/// The block with the timestamp ahead of the local clock is recorded with the zero latency
+pub fn observe_block_indexing_latency(block_timestamp: u64) {
+    BLOCK_INDEXING_LATENCY_SECONDS.observe(database::metrics::seconds_since_block(block_timestamp));
 }
 
 #[get("/metrics")]
@@ -51,10 +73,15 @@ async fn get_metrics() -> impl Responder {
 pub fn init_server(port: u16) -> anyhow::Result<actix_web::dev::Server> {
     tracing::info!(target: crate::INDEXER, "Starting metrics server on http://0.0.0.0:{port}/metrics");
 
-    Ok(HttpServer::new(|| App::new().service(get_metrics))
-        .bind(("0.0.0.0", port))?
-        .disable_signals()
-        .run())
+    Ok(HttpServer::new(|| {
+        App::new().service(get_metrics).configure(|_config| {
+            #[cfg(feature = "profiling")]
+            profiling::configure(_config);
+        })
+    })
+    .bind(("0.0.0.0", port))?
+    .disable_signals()
+    .run())
 }
 
 #[derive(Debug, Clone, Default)]
@@ -62,7 +89,7 @@ pub struct Stats {
     pub block_heights_processing: std::collections::BTreeSet<u64>,
     pub blocks_processed_count: u64,
     pub last_processed_block_height: u64,
-    pub current_epoch_id: Option<near_indexer_primitives::CryptoHash>,
+    pub current_epoch_id: Option<readnode_primitives::indexer::CryptoHash>,
     pub current_epoch_height: u64,
 }
 
@@ -11,23 +11,34 @@ pub trait NearClient {
         &self,
     ) -> impl std::future::Future<Output = anyhow::Result<near_chain_configs::ProtocolConfigView>> + Send;
 
+    /// Returns the ProtocolConfigView at the given block from the NEAR Protocol or an error if the call fails.
+    fn protocol_config_by_block_height(
+        &self,
+        block_height: u64,
+    ) -> impl std::future::Future<Output = anyhow::Result<near_chain_configs::ProtocolConfigView>> + Send;
+
     /// Returns the current epoch information from the NEAR Protocol or an error if the call fails.
     fn validators_by_epoch_id(
         &self,
-        epoch_id: near_indexer_primitives::CryptoHash,
+        epoch_id: readnode_primitives::indexer::CryptoHash,
     ) -> impl std::future::Future<Output = anyhow::Result<near_primitives::views::EpochValidatorInfo>>
            + Send;
+
+    /// Returns the chain id and the genesis hash of the network the node is on or an error if the call fails.
+    fn network_identity(
+        &self,
+    ) -> impl std::future::Future<Output = anyhow::Result<readnode_primitives::NetworkIdentity>> + Send;
 }
 
 /// NEAR JSON-RPC Client is an implementation of the NearClient trait that uses the JSON-RPC calls
 /// to retrieve the necessary data from the NEAR Protocol.
 #[derive(Debug, Clone)]
 pub struct NearJsonRpc {
-    client: near_jsonrpc_client::JsonRpcClient,
+    client: http_client::RpcClient,
 }
 
 impl NearJsonRpc {
-    pub fn new(client: near_jsonrpc_client::JsonRpcClient) -> Self {
+    pub fn new(client: http_client::RpcClient) -> Self {
         Self { client }
     }
 }
@@ -59,9 +70,31 @@ impl NearClient for NearJsonRpc {
         Ok(protocol_config)
     }
 
+    async fn protocol_config_by_block_height(
+        &self,
+        block_height: u64,
+    ) -> anyhow::Result<near_chain_configs::ProtocolConfigView> {
+        let protocol_config = self
+            .client
+            .call(near_jsonrpc_client::methods::EXPERIMENTAL_protocol_config::RpcProtocolConfigRequest {
+                block_reference: near_primitives::types::BlockReference::BlockId(
+                    near_primitives::types::BlockId::Height(block_height),
+                ),
+            })
+            .await
+            .map_err(|e| {
+                anyhow::anyhow!(
+                    "Failed to get protocol config at block {}: {:?}",
+                    block_height,
+                    e
+                )
+            })?;
+        Ok(protocol_config)
+    }
+
     async fn validators_by_epoch_id(
         &self,
-        epoch_id: near_indexer_primitives::CryptoHash,
+        epoch_id: readnode_primitives::indexer::CryptoHash,
     ) -> anyhow::Result<near_primitives::views::EpochValidatorInfo> {
         let validators_info = self
             .client
@@ -76,4 +109,16 @@ impl NearClient for NearJsonRpc {
             .map_err(|e| anyhow::anyhow!("Failed to get validators: {:?}", e))?;
         Ok(validators_info)
     }
+
+    async fn network_identity(&self) -> anyhow::Result<readnode_primitives::NetworkIdentity> {
+        let status = self
+            .client
+            .call(near_jsonrpc_client::methods::status::RpcStatusRequest)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to get status: {:?}", e))?;
+        Ok(readnode_primitives::NetworkIdentity {
+            chain_id: status.chain_id,
+            genesis_hash: status.genesis_hash,
+        })
+    }
 }
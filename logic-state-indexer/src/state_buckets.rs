@@ -0,0 +1,36 @@
+/// Moves the contract state of the accounts into the buckets of their keys batch by batch.
+/// The rebalancing is safe to interrupt and to run again: the rows already in their buckets
+/// are skipped, and the state stays readable while it's moved
+pub async fn rebalance(
+    db_manager: &(impl database::StateIndexerDbManager + Sync + Send + 'static),
+    account_ids: &[near_primitives::types::AccountId],
+    batch_size: u64,
+) -> anyhow::Result<()> {
+    for account_id in account_ids {
+        let mut after_data_key = None;
+        let mut moved_rows = 0;
+        loop {
+            let batch = db_manager
+                .rebalance_state_buckets(account_id, after_data_key, batch_size)
+                .await?;
+            moved_rows += batch.moved_rows;
+            tracing::debug!(
+                target: crate::INDEXER,
+                "Rebalancing the state of {}: {} rows moved so far",
+                account_id,
+                moved_rows,
+            );
+            after_data_key = batch.next_data_key;
+            if after_data_key.is_none() {
+                break;
+            }
+        }
+        tracing::info!(
+            target: crate::INDEXER,
+            "State of {} is rebalanced, {} rows moved into the buckets",
+            account_id,
+            moved_rows,
+        );
+    }
+    Ok(())
+}
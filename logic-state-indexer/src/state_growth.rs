@@ -0,0 +1,73 @@
+// How often the finished days are looked for. The day is aggregated once it is indexed completely
+const AGGREGATION_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60 * 60);
+const AGGREGATION_JITTER: std::time::Duration = std::time::Duration::from_secs(5 * 60);
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// Schedules the analytics job aggregating the daily net state growth of the accounts
+/// from the indexed contract data changes. The first run aggregates the previous day,
+/// the next runs continue from the latest aggregated day
+pub fn start(
+    db_manager: std::sync::Arc<impl database::StateIndexerDbManager + Sync + Send + 'static>,
+    scheduler: &database::scheduler::Scheduler,
+    alert_bytes: Option<u64>,
+) {
+    scheduler.schedule(
+        "state_growth_analytics",
+        database::scheduler::Schedule::every(AGGREGATION_INTERVAL).with_jitter(AGGREGATION_JITTER),
+        move || {
+            let db_manager = std::sync::Arc::clone(&db_manager);
+            async move { aggregate_finished_days(&*db_manager, alert_bytes).await }
+        },
+    );
+}
+
+async fn aggregate_finished_days(
+    db_manager: &(impl database::StateIndexerDbManager + Sync + Send + 'static),
+    alert_bytes: Option<u64>,
+) -> anyhow::Result<()> {
+    let today = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)?
+        .as_secs()
+        / SECONDS_PER_DAY;
+    let mut day = match db_manager.get_latest_state_growth_day().await? {
+        Some(latest_day) => latest_day + 1,
+        None => today.saturating_sub(1),
+    };
+    while day < today {
+        let Some(state_growth_day) = db_manager.aggregate_state_growth(day).await? else {
+            tracing::debug!(
+                target: crate::INDEXER,
+                "Day {} is not indexed completely yet, the state growth is aggregated later",
+                day
+            );
+            break;
+        };
+        tracing::info!(
+            target: crate::INDEXER,
+            "State growth of day {} is aggregated for {} accounts",
+            day,
+            state_growth_day.accounts_count
+        );
+        crate::metrics::STATE_GROWTH_LATEST_DAY.set(day as i64);
+        let top_account_bytes = state_growth_day
+            .top_grower
+            .as_ref()
+            .map_or(0, |top_grower| top_grower.bytes_delta);
+        crate::metrics::STATE_GROWTH_TOP_ACCOUNT_BYTES.set(top_account_bytes);
+        if let (Some(alert_bytes), Some(top_grower)) = (alert_bytes, &state_growth_day.top_grower) {
+            if top_grower.bytes_delta > alert_bytes as i64 {
+                tracing::warn!(
+                    target: crate::INDEXER,
+                    "State of {} grew by {} bytes ({} keys) within day {}, the alert threshold is {} bytes",
+                    top_grower.account_id,
+                    top_grower.bytes_delta,
+                    top_grower.keys_delta,
+                    day,
+                    alert_bytes
+                );
+            }
+        }
+        day += 1;
+    }
+    Ok(())
+}
@@ -1,4 +1,5 @@
 use clap::Parser;
+use database::StateIndexerDbManager;
 use futures::StreamExt;
 
 use crate::configs::Opts;
@@ -82,7 +83,7 @@ async fn run(home_dir: std::path::PathBuf) -> anyhow::Result<()> {
     let stream = indexer.streamer();
     let (view_client, client) = indexer.client_actors();
 
-    let near_client = near_client::NearViewClient::new(view_client.clone());
+    let near_client = near_client::NearViewClient::new(view_client.clone(), client.clone());
     let protocol_config_view = near_client.protocol_config().await?;
 
     tracing::info!(target: INDEXER, "Connecting to db...");
@@ -91,6 +92,13 @@ async fn run(home_dir: std::path::PathBuf) -> anyhow::Result<()> {
         protocol_config_view.shard_layout.clone(),
     )
     .await?;
+    logic_state_indexer::configs::check_network(
+        &near_client,
+        &db_manager,
+        &state_indexer_config.general.chain_id,
+    )
+    .await?;
+    db_manager.save_state_key_buckets().await?;
 
     let stats = std::sync::Arc::new(tokio::sync::RwLock::new(metrics::Stats::default()));
     tokio::spawn(metrics::state_logger(
@@ -5,11 +5,19 @@ use near_o11y::WithSpanContextExt;
 #[derive(Clone, Debug)]
 pub(crate) struct NearViewClient {
     view_client: Addr<near_client::ViewClientActor>,
+    // The node status is served by the client actor only
+    client: Addr<near_client::ClientActor>,
 }
 
 impl NearViewClient {
-    pub fn new(view_client: Addr<near_client::ViewClientActor>) -> Self {
-        Self { view_client }
+    pub fn new(
+        view_client: Addr<near_client::ViewClientActor>,
+        client: Addr<near_client::ClientActor>,
+    ) -> Self {
+        Self {
+            view_client,
+            client,
+        }
     }
 }
 
@@ -39,6 +47,21 @@ impl crate::NearClient for NearViewClient {
             .await??)
     }
 
+    async fn protocol_config_by_block_height(
+        &self,
+        block_height: u64,
+    ) -> anyhow::Result<near_chain_configs::ProtocolConfigView> {
+        Ok(self
+            .view_client
+            .send(
+                near_client::GetProtocolConfig(near_primitives::types::BlockReference::BlockId(
+                    near_primitives::types::BlockId::Height(block_height),
+                ))
+                .with_span_context(),
+            )
+            .await??)
+    }
+
     async fn validators_by_epoch_id(
         &self,
         epoch_id: near_indexer_primitives::CryptoHash,
@@ -55,4 +78,12 @@ impl crate::NearClient for NearViewClient {
             )
             .await??)
     }
+
+    async fn network_identity(&self) -> anyhow::Result<readnode_primitives::NetworkIdentity> {
+        let status = crate::utils::fetch_status(&self.client).await?;
+        Ok(readnode_primitives::NetworkIdentity {
+            chain_id: status.chain_id,
+            genesis_hash: status.genesis_hash,
+        })
+    }
 }
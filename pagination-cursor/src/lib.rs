@@ -0,0 +1,130 @@
+use base64::Engine;
+use hmac::Mac;
+use rand::RngCore;
+
+type HmacSha256 = hmac::Hmac<sha2::Sha256>;
+
+// Length of the HMAC-SHA256 tag appended to the cursor payload
+const TAG_LENGTH: usize = 32;
+
+/// Page position of a paginated endpoint passed to the clients as an opaque cursor.
+/// `KIND` is signed together with the position, so the cursor of one endpoint
+/// is rejected by another one even if the positions have the same layout
+pub trait Cursor: borsh::BorshSerialize + borsh::BorshDeserialize {
+    const KIND: &'static str;
+}
+
+/// Encodes the page positions to the signed cursors and verifies the cursors sent back by clients
+#[derive(Clone)]
+pub struct CursorSigner {
+    secret: Vec<u8>,
+}
+
+impl CursorSigner {
+    pub fn new(secret: &[u8]) -> Self {
+        Self {
+            secret: secret.to_vec(),
+        }
+    }
+
+    /// Signer with a random secret. The cursors are valid only for this process
+    pub fn random() -> Self {
+        let mut secret = vec![0u8; 32];
+        rand::thread_rng().fill_bytes(&mut secret);
+        Self { secret }
+    }
+
+    pub fn encode<T: Cursor>(&self, cursor: &T) -> anyhow::Result<String> {
+        let mut data = borsh::to_vec(cursor)?;
+        let tag = self.mac(T::KIND, &data).finalize().into_bytes();
+        data.extend_from_slice(&tag);
+        Ok(base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(data))
+    }
+
+    pub fn decode<T: Cursor>(&self, cursor: &str) -> anyhow::Result<T> {
+        let data = base64::engine::general_purpose::URL_SAFE_NO_PAD
+            .decode(cursor)
+            .map_err(|_| anyhow::anyhow!("Invalid cursor"))?;
+        if data.len() < TAG_LENGTH {
+            anyhow::bail!("Invalid cursor");
+        }
+        let (payload, tag) = data.split_at(data.len() - TAG_LENGTH);
+        self.mac(T::KIND, payload)
+            .verify_slice(tag)
+            .map_err(|_| anyhow::anyhow!("Invalid cursor"))?;
+        borsh::from_slice(payload).map_err(|_| anyhow::anyhow!("Invalid cursor"))
+    }
+
+    fn mac(&self, kind: &str, payload: &[u8]) -> HmacSha256 {
+        let mut mac =
+            HmacSha256::new_from_slice(&self.secret).expect("HMAC accepts keys of any length");
+        // The kind is length-prefixed to keep the kind and the payload apart
+        mac.update(&(kind.len() as u32).to_le_bytes());
+        mac.update(kind.as_bytes());
+        mac.update(payload);
+        mac
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(borsh::BorshSerialize, borsh::BorshDeserialize, Debug, PartialEq)]
+    struct OffsetCursor {
+        offset: u64,
+    }
+
+    impl Cursor for OffsetCursor {
+        const KIND: &'static str = "offset";
+    }
+
+    #[derive(borsh::BorshSerialize, borsh::BorshDeserialize, Debug, PartialEq)]
+    struct HeightCursor {
+        height: u64,
+    }
+
+    impl Cursor for HeightCursor {
+        const KIND: &'static str = "height";
+    }
+
+    #[test]
+    fn test_cursor_round_trip() {
+        let signer = CursorSigner::new(b"secret");
+        let cursor = signer.encode(&OffsetCursor { offset: 1000 }).unwrap();
+        assert_eq!(
+            signer.decode::<OffsetCursor>(&cursor).unwrap(),
+            OffsetCursor { offset: 1000 }
+        );
+    }
+
+    #[test]
+    fn test_forged_cursor_is_rejected() {
+        let signer = CursorSigner::new(b"secret");
+        let cursor = signer.encode(&OffsetCursor { offset: 1000 }).unwrap();
+        let mut data = base64::engine::general_purpose::URL_SAFE_NO_PAD
+            .decode(&cursor)
+            .unwrap();
+        data[0] ^= 1;
+        let forged = base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(data);
+        assert!(signer.decode::<OffsetCursor>(&forged).is_err());
+        assert!(signer.decode::<OffsetCursor>("not a cursor").is_err());
+    }
+
+    #[test]
+    fn test_cursor_of_another_kind_is_rejected() {
+        let signer = CursorSigner::new(b"secret");
+        let cursor = signer.encode(&OffsetCursor { offset: 1000 }).unwrap();
+        assert!(signer.decode::<HeightCursor>(&cursor).is_err());
+    }
+
+    #[test]
+    fn test_cursor_of_another_secret_is_rejected() {
+        let cursor = CursorSigner::new(b"secret")
+            .encode(&OffsetCursor { offset: 1000 })
+            .unwrap();
+        assert!(CursorSigner::new(b"another secret")
+            .decode::<OffsetCursor>(&cursor)
+            .is_err());
+    }
+}
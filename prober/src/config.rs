@@ -0,0 +1,155 @@
+use std::str::FromStr;
+
+use near_primitives::types::AccountId;
+
+/// Kinds of the queries issued by the prober, the name is used as the metric label
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ProbeKind {
+    /// The `tx` status of the random recent transaction
+    Tx,
+    /// The `view_account` of the signer of the random recent transaction
+    Account,
+    /// The `call_function` of the random configured view call at the random recent block
+    ViewCall,
+}
+
+impl ProbeKind {
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            ProbeKind::Tx => "tx",
+            ProbeKind::Account => "account",
+            ProbeKind::ViewCall => "view_call",
+        }
+    }
+}
+
+impl FromStr for ProbeKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "tx" => Ok(ProbeKind::Tx),
+            "account" => Ok(ProbeKind::Account),
+            "view_call" => Ok(ProbeKind::ViewCall),
+            _ => anyhow::bail!("Unknown probe `{}`, expected one of `tx`, `account`, `view_call`", s),
+        }
+    }
+}
+
+/// Weighted mix of the probes
+#[derive(Debug, Clone)]
+pub(crate) struct ProbeMix {
+    pub weights: Vec<(ProbeKind, u32)>,
+}
+
+impl ProbeMix {
+    /// Disables the probe, e.g. the `view_call` one without the configured calls
+    pub(crate) fn disable(&mut self, kind: ProbeKind) {
+        self.weights.retain(|(probe_kind, _)| *probe_kind != kind);
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.weights.iter().all(|(_, weight)| *weight == 0)
+    }
+}
+
+impl FromStr for ProbeMix {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut weights: Vec<(ProbeKind, u32)> = vec![];
+        for item in s.split(',').map(str::trim).filter(|item| !item.is_empty()) {
+            let (kind, weight) = item
+                .split_once('=')
+                .ok_or_else(|| anyhow::anyhow!("Invalid probe weight `{}`, expected `probe=weight`", item))?;
+            let kind = ProbeKind::from_str(kind.trim())?;
+            let weight = weight
+                .trim()
+                .parse::<u32>()
+                .map_err(|err| anyhow::anyhow!("Invalid weight of probe `{}`: {}", kind.name(), err))?;
+            if weights.iter().any(|(probe_kind, _)| *probe_kind == kind) {
+                anyhow::bail!("Probe `{}` is weighted twice", kind.name());
+            }
+            weights.push((kind, weight));
+        }
+        Ok(Self { weights })
+    }
+}
+
+/// Contract method called by the `view_call` probe
+#[derive(Debug, Clone)]
+pub(crate) struct ViewCall {
+    pub contract_id: AccountId,
+    pub method_name: String,
+    pub args: Vec<u8>,
+}
+
+impl FromStr for ViewCall {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // The arguments are JSON and may contain `:`, they go last
+        let mut parts = s.splitn(3, ':');
+        let (Some(contract_id), Some(method_name)) = (parts.next(), parts.next()) else {
+            anyhow::bail!("Invalid view call `{}`, expected `contract_id:method_name:args_json`", s);
+        };
+        let args = parts.next().unwrap_or("{}");
+        // The arguments are validated once on the start instead of failing every probe
+        serde_json::from_str::<serde_json::Value>(args)
+            .map_err(|err| anyhow::anyhow!("Invalid arguments of view call `{}`: {}", s, err))?;
+        Ok(Self {
+            contract_id: contract_id
+                .parse()
+                .map_err(|err| anyhow::anyhow!("Invalid contract of view call `{}`: {}", s, err))?,
+            method_name: method_name.to_string(),
+            args: args.as_bytes().to_vec(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_probe_mix_from_str() {
+        let mix = ProbeMix::from_str(" tx=2, account = 0,view_call=1,").unwrap();
+        assert_eq!(mix.weights, vec![(ProbeKind::Tx, 2), (ProbeKind::Account, 0), (ProbeKind::ViewCall, 1)]);
+        assert!(!mix.is_empty());
+    }
+
+    #[test]
+    fn test_probe_mix_from_str_invalid() {
+        assert!(ProbeMix::from_str("tx").is_err());
+        assert!(ProbeMix::from_str("block=1").is_err());
+        assert!(ProbeMix::from_str("tx=-1").is_err());
+        assert!(ProbeMix::from_str("tx=1,tx=2").is_err());
+    }
+
+    #[test]
+    fn test_probe_mix_disable() {
+        let mut mix = ProbeMix::from_str("tx=0,view_call=1").unwrap();
+        mix.disable(ProbeKind::ViewCall);
+        assert_eq!(mix.weights, vec![(ProbeKind::Tx, 0)]);
+        assert!(mix.is_empty());
+        assert!(ProbeMix::from_str("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_view_call_from_str() {
+        let view_call = ViewCall::from_str(r#"wrap.near:ft_balance_of:{"account_id":"a:b.near"}"#).unwrap();
+        assert_eq!(view_call.contract_id.as_str(), "wrap.near");
+        assert_eq!(view_call.method_name, "ft_balance_of");
+        assert_eq!(view_call.args, br#"{"account_id":"a:b.near"}"#.to_vec());
+
+        let view_call = ViewCall::from_str("wrap.near:ft_total_supply").unwrap();
+        assert_eq!(view_call.args, b"{}".to_vec());
+    }
+
+    #[test]
+    fn test_view_call_from_str_invalid() {
+        assert!(ViewCall::from_str("wrap.near").is_err());
+        assert!(ViewCall::from_str("Invalid Account:ft_total_supply").is_err());
+        assert!(ViewCall::from_str("wrap.near:ft_balance_of:{account_id}").is_err());
+    }
+}
@@ -0,0 +1,68 @@
+use rand::distributions::{Distribution, WeightedIndex};
+
+#[macro_use]
+extern crate lazy_static;
+
+mod config;
+mod metrics;
+mod probes;
+
+// Target for tracing logs
+pub(crate) const PROBER: &str = "prober";
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    // We use it to automatically search the for root certificates to perform HTTPS calls
+    openssl_probe::init_ssl_cert_env_vars();
+    configuration::init_tracing(PROBER).await?;
+    tracing::info!(target: PROBER, "Starting {} v{}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
+
+    let prober_config = configuration::read_configuration::<configuration::ProberConfig>().await?;
+    let general_config = prober_config.general;
+    let view_calls = general_config
+        .view_calls
+        .iter()
+        .map(|view_call| view_call.parse::<config::ViewCall>())
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    let mut mix = general_config.mix.parse::<config::ProbeMix>()?;
+    if view_calls.is_empty() {
+        tracing::info!(target: PROBER, "No view calls are configured, the view_call probe is disabled");
+        mix.disable(config::ProbeKind::ViewCall);
+    }
+    if mix.is_empty() {
+        anyhow::bail!("All the probes are disabled, nothing to probe");
+    }
+    let kinds = mix.weights.iter().map(|(kind, _)| *kind).collect::<Vec<_>>();
+    let distribution = WeightedIndex::new(mix.weights.iter().map(|(_, weight)| *weight))?;
+
+    let mut client = near_jsonrpc_client::JsonRpcClient::connect(general_config.read_rpc_url.as_str());
+    if let Some(api_key) = &general_config.api_key {
+        client = client.header(near_jsonrpc_client::auth::ApiKey::new(api_key)?);
+    }
+    let prober = probes::Prober::new(
+        client,
+        std::time::Duration::from_millis(general_config.timeout_ms),
+        general_config.recent_blocks,
+        view_calls,
+    );
+
+    // Initiate metrics http server
+    tokio::spawn(metrics::init_server(general_config.metrics_server_port).expect("Failed to start metrics server"));
+
+    tracing::info!(target: PROBER, "Probing {} with the mix {:?}", general_config.read_rpc_url, mix.weights);
+    let interval = std::time::Duration::from_millis(general_config.interval_ms);
+    loop {
+        let kind = kinds[distribution.sample(&mut rand::thread_rng())];
+        tokio::select! {
+            _ = prober.run(kind) => {}
+            _ = tokio::signal::ctrl_c() => break,
+        }
+        tokio::select! {
+            _ = tokio::time::sleep(interval) => {}
+            _ = tokio::signal::ctrl_c() => break,
+        }
+    }
+    tracing::info!(target: PROBER, "Prober stopped");
+    Ok(())
+}
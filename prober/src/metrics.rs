@@ -0,0 +1,84 @@
+use actix_web::{get, App, HttpServer, Responder};
+use prometheus::{Encoder, HistogramOpts, HistogramVec, IntCounterVec, IntGaugeVec, Opts};
+
+type Result<T, E> = std::result::Result<T, E>;
+
+fn try_create_int_counter_vec(
+    name: &str,
+    help: &str,
+    label_names: &[&str],
+) -> Result<IntCounterVec, prometheus::Error> {
+    let opts = Opts::new(name, help);
+    let counter = IntCounterVec::new(opts, label_names)?;
+    prometheus::register(Box::new(counter.clone()))?;
+    Ok(counter)
+}
+
+fn try_create_int_gauge_vec(name: &str, help: &str, label_names: &[&str]) -> Result<IntGaugeVec, prometheus::Error> {
+    let opts = Opts::new(name, help);
+    let gauge = IntGaugeVec::new(opts, label_names)?;
+    prometheus::register(Box::new(gauge.clone()))?;
+    Ok(gauge)
+}
+
+fn try_create_histogram_vec(
+    name: &str,
+    help: &str,
+    label_names: &[&str],
+    buckets: Vec<f64>,
+) -> Result<HistogramVec, prometheus::Error> {
+    let opts = HistogramOpts::new(name, help).buckets(buckets);
+    let histogram = HistogramVec::new(opts, label_names)?;
+    prometheus::register(Box::new(histogram.clone()))?;
+    Ok(histogram)
+}
+
+lazy_static! {
+    pub(crate) static ref PROBES_TOTAL: IntCounterVec = try_create_int_counter_vec(
+        "prober_probes_total",
+        "Total number of the probes by the probe and the result: success, error (the query failed), \
+        invalid (the response violates the invariants) or skipped (nothing to probe, e.g. no recent transactions)",
+        &["probe", "result"]
+    )
+    .unwrap();
+    pub(crate) static ref PROBE_LATENCY: HistogramVec = try_create_histogram_vec(
+        "prober_probe_latency_seconds",
+        "Latency of the probed query regardless of its result, the sampling queries are not included",
+        &["probe"],
+        vec![0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0],
+    )
+    .unwrap();
+    pub(crate) static ref LAST_SUCCESS_TIMESTAMP: IntGaugeVec = try_create_int_gauge_vec(
+        "prober_last_success_timestamp_seconds",
+        "Unix timestamp of the last successful probe, the alerts are set on its age",
+        &["probe"]
+    )
+    .unwrap();
+}
+
+#[get("/metrics")]
+async fn get_metrics() -> impl Responder {
+    let encoder = prometheus::TextEncoder::new();
+
+    let mut buffer = Vec::new();
+    if let Err(e) = encoder.encode(&prometheus::gather(), &mut buffer) {
+        tracing::error!(target: crate::PROBER, "could not encode metrics: {}", e);
+    };
+
+    match String::from_utf8(buffer.clone()) {
+        Ok(v) => v,
+        Err(e) => {
+            tracing::error!(target: crate::PROBER, "custom metrics could not be from_utf8'd: {}", e);
+            String::default()
+        }
+    }
+}
+
+pub(crate) fn init_server(port: u16) -> anyhow::Result<actix_web::dev::Server> {
+    tracing::info!(target: crate::PROBER, "Starting metrics server on http://0.0.0.0:{port}/metrics");
+
+    Ok(HttpServer::new(|| App::new().service(get_metrics))
+        .bind(("0.0.0.0", port))?
+        .disable_signals()
+        .run())
+}
@@ -0,0 +1,328 @@
+use near_jsonrpc_client::{methods, JsonRpcClient};
+use near_primitives::types::{BlockId, BlockReference, Finality};
+use near_primitives::views::{BlockView, FinalExecutionStatus, QueryRequest, SignedTransactionView, TxExecutionStatus};
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::config::{ProbeKind, ViewCall};
+
+/// The transactions of the last blocks may still have the receipts in flight,
+/// the `tx` probe samples the blocks older than this number of blocks
+const TX_SETTLE_BLOCKS: u64 = 20;
+/// The chain skips heights, the previous heights are tried if the sampled one has no block
+const MAX_SKIPPED_BLOCKS: u64 = 10;
+/// Number of the previous blocks scanned for a transaction before the probe is skipped
+const MAX_SCANNED_BLOCKS: usize = 10;
+
+/// Why the probe didn't succeed, the name is used as the metric label
+#[derive(Debug)]
+pub(crate) enum ProbeError {
+    /// The query failed or timed out
+    Failed(anyhow::Error),
+    /// The query succeeded but the response violates the invariants
+    Invalid(String),
+    /// Nothing to probe, e.g. no transactions in the recent blocks
+    Skipped(String),
+}
+
+impl ProbeError {
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            ProbeError::Failed(_) => "error",
+            ProbeError::Invalid(_) => "invalid",
+            ProbeError::Skipped(_) => "skipped",
+        }
+    }
+}
+
+impl std::fmt::Display for ProbeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProbeError::Failed(err) => write!(f, "{:?}", err),
+            ProbeError::Invalid(reason) | ProbeError::Skipped(reason) => write!(f, "{}", reason),
+        }
+    }
+}
+
+/// Fails the probe as invalid if the condition doesn't hold
+macro_rules! ensure_valid {
+    ($cond:expr, $($arg:tt)+) => {
+        if !$cond {
+            return Err(ProbeError::Invalid(format!($($arg)+)));
+        }
+    };
+}
+
+pub(crate) struct Prober {
+    client: JsonRpcClient,
+    timeout: std::time::Duration,
+    recent_blocks: u64,
+    view_calls: Vec<ViewCall>,
+}
+
+impl Prober {
+    pub(crate) fn new(
+        client: JsonRpcClient,
+        timeout: std::time::Duration,
+        recent_blocks: u64,
+        view_calls: Vec<ViewCall>,
+    ) -> Self {
+        Self {
+            client,
+            timeout,
+            recent_blocks,
+            view_calls,
+        }
+    }
+
+    /// Runs the probe and records its result and the latency of the probed query
+    pub(crate) async fn run(&self, kind: ProbeKind) {
+        let result = match kind {
+            ProbeKind::Tx => self.probe_tx().await,
+            ProbeKind::Account => self.probe_account().await,
+            ProbeKind::ViewCall => self.probe_view_call().await,
+        };
+        let result_name = match &result {
+            Ok(()) => "success",
+            Err(err) => err.name(),
+        };
+        crate::metrics::PROBES_TOTAL
+            .with_label_values(&[kind.name(), result_name])
+            .inc();
+        match result {
+            Ok(()) => {
+                let now = std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .unwrap_or_default();
+                crate::metrics::LAST_SUCCESS_TIMESTAMP
+                    .with_label_values(&[kind.name()])
+                    .set(now.as_secs() as i64);
+                tracing::debug!(target: crate::PROBER, "Probe {} succeeded", kind.name());
+            }
+            Err(err @ ProbeError::Skipped(_)) => {
+                tracing::debug!(target: crate::PROBER, "Probe {} skipped: {}", kind.name(), err);
+            }
+            Err(err) => {
+                tracing::warn!(target: crate::PROBER, "Probe {} {}: {}", kind.name(), err.name(), err);
+            }
+        }
+    }
+
+    /// The transaction is found by its hash and signer with the same content
+    /// and the final outcome
+    async fn probe_tx(&self) -> Result<(), ProbeError> {
+        let (_, transaction) = self.random_recent_transaction().await?;
+        let response = self
+            .timed_call(
+                ProbeKind::Tx,
+                methods::tx::RpcTransactionStatusRequest {
+                    transaction_info: methods::tx::TransactionInfo::TransactionId {
+                        tx_hash: transaction.hash,
+                        sender_account_id: transaction.signer_id.clone(),
+                    },
+                    wait_until: TxExecutionStatus::Final,
+                },
+            )
+            .await?;
+        let Some(outcome) = response.final_execution_outcome else {
+            return Err(ProbeError::Invalid(format!("Transaction {} has no final outcome", transaction.hash)));
+        };
+        let outcome = outcome.into_outcome();
+        ensure_valid!(
+            outcome.transaction.hash == transaction.hash && outcome.transaction_outcome.id == transaction.hash,
+            "Transaction {} is returned for transaction {}",
+            outcome.transaction.hash,
+            transaction.hash
+        );
+        ensure_valid!(
+            outcome.transaction.signer_id == transaction.signer_id
+                && outcome.transaction.receiver_id == transaction.receiver_id,
+            "Transaction {} has signer {} and receiver {}, the chunk has {} and {}",
+            transaction.hash,
+            outcome.transaction.signer_id,
+            outcome.transaction.receiver_id,
+            transaction.signer_id,
+            transaction.receiver_id
+        );
+        ensure_valid!(
+            !matches!(outcome.status, FinalExecutionStatus::NotStarted | FinalExecutionStatus::Started),
+            "Transaction {} is not finished: {:?}",
+            transaction.hash,
+            outcome.status
+        );
+        Ok(())
+    }
+
+    /// The signer of the transaction exists at the block of the transaction
+    /// and the state is returned for that block
+    async fn probe_account(&self) -> Result<(), ProbeError> {
+        let (block, transaction) = self.random_recent_transaction().await?;
+        let response = self
+            .timed_call(
+                ProbeKind::Account,
+                methods::query::RpcQueryRequest {
+                    block_reference: BlockReference::BlockId(BlockId::Height(block.header.height)),
+                    request: QueryRequest::ViewAccount {
+                        account_id: transaction.signer_id.clone(),
+                    },
+                },
+            )
+            .await?;
+        ensure_query_block(&response, &block)?;
+        ensure_valid!(
+            matches!(response.kind, methods::query::QueryResponseKind::ViewAccount(_)),
+            "Unexpected response to view_account of {}",
+            transaction.signer_id
+        );
+        Ok(())
+    }
+
+    /// The view call is executed at the requested block
+    async fn probe_view_call(&self) -> Result<(), ProbeError> {
+        let Some(view_call) = self.view_calls.choose(&mut rand::thread_rng()).cloned() else {
+            return Err(ProbeError::Skipped("No view calls are configured".to_string()));
+        };
+        let block = self.random_recent_block(0).await?;
+        let response = self
+            .timed_call(
+                ProbeKind::ViewCall,
+                methods::query::RpcQueryRequest {
+                    block_reference: BlockReference::BlockId(BlockId::Height(block.header.height)),
+                    request: QueryRequest::CallFunction {
+                        account_id: view_call.contract_id.clone(),
+                        method_name: view_call.method_name.clone(),
+                        args: view_call.args.into(),
+                    },
+                },
+            )
+            .await?;
+        ensure_query_block(&response, &block)?;
+        ensure_valid!(
+            matches!(response.kind, methods::query::QueryResponseKind::CallResult(_)),
+            "Unexpected response to call of {}.{}",
+            view_call.contract_id,
+            view_call.method_name
+        );
+        Ok(())
+    }
+
+    /// Random transaction of the recent blocks old enough to be finished.
+    /// The blocks before the sampled one are scanned if it has no transactions
+    async fn random_recent_transaction(&self) -> Result<(BlockView, SignedTransactionView), ProbeError> {
+        let sampled_block = self.random_recent_block(TX_SETTLE_BLOCKS).await?;
+        let sampled_height = sampled_block.header.height;
+        let mut block = sampled_block;
+        for scanned_blocks in 1..=MAX_SCANNED_BLOCKS {
+            let mut chunk_hashes = block
+                .chunks
+                .iter()
+                // The chunk missed in the block is the previous chunk of the shard
+                .filter(|chunk| chunk.height_included == block.header.height)
+                .map(|chunk| chunk.chunk_hash)
+                .collect::<Vec<_>>();
+            chunk_hashes.shuffle(&mut rand::thread_rng());
+            for chunk_hash in chunk_hashes {
+                let chunk = self
+                    .call(methods::chunk::RpcChunkRequest {
+                        chunk_reference: methods::chunk::ChunkReference::ChunkHash { chunk_id: chunk_hash },
+                    })
+                    .await?;
+                if let Some(transaction) = chunk.transactions.choose(&mut rand::thread_rng()) {
+                    return Ok((block, transaction.clone()));
+                }
+            }
+            if scanned_blocks < MAX_SCANNED_BLOCKS {
+                block = self
+                    .call(methods::block::RpcBlockRequest {
+                        block_reference: BlockReference::BlockId(BlockId::Hash(block.header.prev_hash)),
+                    })
+                    .await?;
+            }
+        }
+        Err(ProbeError::Skipped(format!(
+            "No transactions in {} blocks up to block {}",
+            MAX_SCANNED_BLOCKS, sampled_height
+        )))
+    }
+
+    /// Random block among the `recent_blocks` last final blocks, at least `min_age_blocks` old
+    async fn random_recent_block(&self, min_age_blocks: u64) -> Result<BlockView, ProbeError> {
+        let final_block = self
+            .call(methods::block::RpcBlockRequest {
+                block_reference: BlockReference::Finality(Finality::Final),
+            })
+            .await?;
+        let to_height = final_block.header.height.saturating_sub(min_age_blocks);
+        let from_height = final_block
+            .header
+            .height
+            .saturating_sub(self.recent_blocks)
+            .min(to_height);
+        let height = rand::thread_rng().gen_range(from_height..=to_height);
+        for block_height in (height.saturating_sub(MAX_SKIPPED_BLOCKS)..=height).rev() {
+            let request = methods::block::RpcBlockRequest {
+                block_reference: BlockReference::BlockId(BlockId::Height(block_height)),
+            };
+            match tokio::time::timeout(self.timeout, self.client.call(request)).await {
+                Ok(Ok(block)) => return Ok(block),
+                Ok(Err(err)) => match err.handler_error() {
+                    Some(methods::block::RpcBlockError::UnknownBlock { .. }) => continue,
+                    _ => {
+                        return Err(ProbeError::Failed(anyhow::anyhow!(
+                            "Failed to fetch block {}: {:?}",
+                            block_height,
+                            err
+                        )))
+                    }
+                },
+                Err(_) => return Err(ProbeError::Failed(anyhow::anyhow!("Fetching block {} timed out", block_height))),
+            }
+        }
+        Err(ProbeError::Invalid(format!(
+            "No blocks found in range {}..={} while the final block is {}",
+            height.saturating_sub(MAX_SKIPPED_BLOCKS),
+            height,
+            final_block.header.height
+        )))
+    }
+
+    /// Calls the method of the probe recording the latency of the call
+    async fn timed_call<M>(&self, kind: ProbeKind, method: M) -> Result<M::Response, ProbeError>
+    where
+        M: methods::RpcMethod,
+        M::Error: std::fmt::Debug,
+    {
+        let started_at = std::time::Instant::now();
+        let result = self.call(method).await;
+        crate::metrics::PROBE_LATENCY
+            .with_label_values(&[kind.name()])
+            .observe(started_at.elapsed().as_secs_f64());
+        result
+    }
+
+    async fn call<M>(&self, method: M) -> Result<M::Response, ProbeError>
+    where
+        M: methods::RpcMethod,
+        M::Error: std::fmt::Debug,
+    {
+        let method_name = method.method_name().to_string();
+        match tokio::time::timeout(self.timeout, self.client.call(method)).await {
+            Ok(Ok(response)) => Ok(response),
+            Ok(Err(err)) => Err(ProbeError::Failed(anyhow::anyhow!("{} failed: {:?}", method_name, err))),
+            Err(_) => Err(ProbeError::Failed(anyhow::anyhow!("{} timed out after {:?}", method_name, self.timeout))),
+        }
+    }
+}
+
+/// The query is answered for the requested block
+fn ensure_query_block(response: &methods::query::RpcQueryResponse, block: &BlockView) -> Result<(), ProbeError> {
+    ensure_valid!(
+        response.block_height == block.header.height && response.block_hash == block.header.hash,
+        "Query at block {} ({}) is answered for block {} ({})",
+        block.header.height,
+        block.header.hash,
+        response.block_height,
+        response.block_hash
+    );
+    Ok(())
+}
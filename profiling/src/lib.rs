@@ -0,0 +1,174 @@
+pub use tikv_jemallocator::Jemalloc;
+
+/// jemalloc options of the services built with the profiling, exported as the `malloc_conf`
+/// symbol by the binaries. The heap is sampled every 2^19 bytes (512KiB) of the allocations
+pub const MALLOC_CONF: &[u8] = b"prof:true,prof_active:true,lg_prof_sample:19\0";
+
+const DEFAULT_PROFILE_SECONDS: u64 = 30;
+const MAX_PROFILE_SECONDS: u64 = 300;
+/// Sampling frequency of the CPU profile in Hz
+const PROFILE_FREQUENCY: i32 = 99;
+
+// The CPU profiler is the process-wide signal handler, so only one profile is collected at a time
+static CPU_PROFILE_IN_PROGRESS: std::sync::atomic::AtomicBool =
+    std::sync::atomic::AtomicBool::new(false);
+
+// Releases the CPU profiler when the profile is collected or the request is dropped
+struct CpuProfileGuard;
+
+impl CpuProfileGuard {
+    fn acquire() -> Option<Self> {
+        CPU_PROFILE_IN_PROGRESS
+            .compare_exchange(
+                false,
+                true,
+                std::sync::atomic::Ordering::AcqRel,
+                std::sync::atomic::Ordering::Acquire,
+            )
+            .ok()
+            .map(|_| Self)
+    }
+}
+
+impl Drop for CpuProfileGuard {
+    fn drop(&mut self) {
+        CPU_PROFILE_IN_PROGRESS.store(false, std::sync::atomic::Ordering::Release);
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProfileFormat {
+    /// Protobuf of the pprof tools
+    #[default]
+    Pprof,
+    /// SVG flame graph
+    Flamegraph,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct ProfileQuery {
+    /// Duration of the CPU profile in seconds
+    seconds: Option<u64>,
+    #[serde(default)]
+    format: ProfileFormat,
+}
+
+#[derive(Debug, serde::Serialize)]
+struct JemallocStats {
+    allocated: usize,
+    active: usize,
+    metadata: usize,
+    resident: usize,
+    mapped: usize,
+    retained: usize,
+}
+
+/// Registers the profiling endpoints on the internal listener of the service (e.g. the metrics server)
+pub fn configure(config: &mut actix_web::web::ServiceConfig) {
+    config
+        .route(
+            "/debug/pprof/profile",
+            actix_web::web::get().to(|query: actix_web::web::Query<ProfileQuery>| async move {
+                cpu_profile(query.into_inner()).await
+            }),
+        )
+        .route("/debug/pprof/heap", actix_web::web::get().to(heap_profile))
+        .route(
+            "/debug/jemalloc/stats",
+            actix_web::web::get().to(|| async { jemalloc_stats() }),
+        );
+}
+
+/// Samples the CPU of the process for the requested number of seconds
+pub async fn cpu_profile(query: ProfileQuery) -> actix_web::HttpResponse {
+    let seconds = query.seconds.unwrap_or(DEFAULT_PROFILE_SECONDS);
+    if seconds == 0 || seconds > MAX_PROFILE_SECONDS {
+        return actix_web::HttpResponse::BadRequest().body(format!(
+            "`seconds` must be between 1 and {}",
+            MAX_PROFILE_SECONDS
+        ));
+    }
+    let Some(_guard) = CpuProfileGuard::acquire() else {
+        return actix_web::HttpResponse::Conflict().body("CPU profile is already in progress");
+    };
+    tracing::info!("Collecting the CPU profile for {} seconds", seconds);
+    match collect_cpu_profile(std::time::Duration::from_secs(seconds), query.format).await {
+        Ok(body) => match query.format {
+            ProfileFormat::Pprof => actix_web::HttpResponse::Ok()
+                .content_type("application/octet-stream")
+                .body(body),
+            ProfileFormat::Flamegraph => actix_web::HttpResponse::Ok()
+                .content_type("image/svg+xml")
+                .body(body),
+        },
+        Err(err) => {
+            tracing::error!("Failed to collect the CPU profile: {:?}", err);
+            actix_web::HttpResponse::InternalServerError().body(err.to_string())
+        }
+    }
+}
+
+async fn collect_cpu_profile(
+    duration: std::time::Duration,
+    format: ProfileFormat,
+) -> anyhow::Result<Vec<u8>> {
+    let guard = pprof::ProfilerGuardBuilder::default()
+        .frequency(PROFILE_FREQUENCY)
+        .blocklist(&["libc", "libgcc", "pthread", "vdso"])
+        .build()?;
+    tokio::time::sleep(duration).await;
+    let report = guard.report().build()?;
+    let mut body = Vec::new();
+    match format {
+        ProfileFormat::Pprof => {
+            use pprof::protos::Message;
+            report.pprof()?.encode(&mut body)?;
+        }
+        ProfileFormat::Flamegraph => report.flamegraph(&mut body)?,
+    }
+    Ok(body)
+}
+
+/// Dumps the heap profile of the sampled allocations in the gzipped pprof format
+pub async fn heap_profile() -> actix_web::HttpResponse {
+    let Some(prof_ctl) = jemalloc_pprof::PROF_CTL.as_ref() else {
+        return actix_web::HttpResponse::NotImplemented()
+            .body("Heap profiling is not enabled in jemalloc");
+    };
+    let mut prof_ctl = prof_ctl.lock().await;
+    if !prof_ctl.activated() {
+        return actix_web::HttpResponse::NotImplemented()
+            .body("Heap profiling is not activated in jemalloc");
+    }
+    match prof_ctl.dump_pprof() {
+        Ok(body) => actix_web::HttpResponse::Ok()
+            .content_type("application/octet-stream")
+            .body(body),
+        Err(err) => {
+            tracing::error!("Failed to dump the heap profile: {:?}", err);
+            actix_web::HttpResponse::InternalServerError().body(err.to_string())
+        }
+    }
+}
+
+/// Memory stats of jemalloc in bytes
+pub fn jemalloc_stats() -> actix_web::HttpResponse {
+    match read_jemalloc_stats() {
+        Ok(stats) => actix_web::HttpResponse::Ok().json(stats),
+        Err(err) => actix_web::HttpResponse::InternalServerError().body(err.to_string()),
+    }
+}
+
+fn read_jemalloc_stats() -> anyhow::Result<JemallocStats> {
+    // The stats are cached by jemalloc until the epoch is advanced
+    tikv_jemalloc_ctl::epoch::advance()?;
+    Ok(JemallocStats {
+        allocated: tikv_jemalloc_ctl::stats::allocated::read()?,
+        active: tikv_jemalloc_ctl::stats::active::read()?,
+        metadata: tikv_jemalloc_ctl::stats::metadata::read()?,
+        resident: tikv_jemalloc_ctl::stats::resident::read()?,
+        mapped: tikv_jemalloc_ctl::stats::mapped::read()?,
+        retained: tikv_jemalloc_ctl::stats::retained::read()?,
+    })
+}
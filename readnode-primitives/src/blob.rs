@@ -0,0 +1,101 @@
+//! Defensive decoding of the blobs read from the storages (database rows, Redis cache,
+//! transaction details storage). A single corrupted or unexpectedly large blob must fail
+//! the request reading it with an error instead of taking the whole process down.
+//! The blobs larger than the configured limit are rejected before decoding them.
+//! Borsh doesn't preallocate the collections by their length prefixes beyond a small bound,
+//! so a corrupted prefix fails on the missing bytes instead of allocating the claimed length.
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Default max size of the blob, larger than any contract code or state value
+pub const DEFAULT_MAX_BLOB_SIZE: usize = 64 * 1024 * 1024;
+
+static MAX_BLOB_SIZE: AtomicUsize = AtomicUsize::new(DEFAULT_MAX_BLOB_SIZE);
+
+/// Sets the max size of the blobs decoded by the process.
+/// Called once on the start with the configured value
+pub fn set_max_blob_size(max_blob_size: usize) {
+    MAX_BLOB_SIZE.store(max_blob_size, Ordering::Relaxed);
+}
+
+pub fn max_blob_size() -> usize {
+    MAX_BLOB_SIZE.load(Ordering::Relaxed)
+}
+
+pub fn check_blob_size(data: &[u8]) -> anyhow::Result<()> {
+    let max_blob_size = max_blob_size();
+    if data.len() > max_blob_size {
+        anyhow::bail!(
+            "Blob of {} bytes exceeds the max blob size of {} bytes",
+            data.len(),
+            max_blob_size
+        );
+    }
+    Ok(())
+}
+
+/// Decodes the borsh blob of the limited size. The whole blob must be consumed
+pub fn borsh_from_slice<T: borsh::BorshDeserialize>(data: &[u8]) -> anyhow::Result<T> {
+    check_blob_size(data)?;
+    Ok(borsh::from_slice(data)?)
+}
+
+/// Decodes the JSON blob of the limited size
+pub fn json_from_slice<T: serde::de::DeserializeOwned>(data: &[u8]) -> anyhow::Result<T> {
+    check_blob_size(data)?;
+    Ok(serde_json::from_slice(data)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The id and the values
+    type Record = (u64, Vec<Vec<u8>>);
+
+    fn record() -> Record {
+        (42, vec![b"alice.near".to_vec(), vec![], vec![0; 100]])
+    }
+
+    #[test]
+    fn test_borsh_round_trip() {
+        let data = borsh::to_vec(&record()).unwrap();
+        assert_eq!(borsh_from_slice::<Record>(&data).unwrap(), record());
+    }
+
+    #[test]
+    fn test_borsh_truncated_blob() {
+        let data = borsh::to_vec(&record()).unwrap();
+        assert!(borsh_from_slice::<Record>(&data[..data.len() - 1]).is_err());
+        assert!(borsh_from_slice::<Record>(&[]).is_err());
+    }
+
+    #[test]
+    fn test_borsh_trailing_bytes() {
+        let mut data = borsh::to_vec(&record()).unwrap();
+        data.push(0);
+        assert!(borsh_from_slice::<Record>(&data).is_err());
+    }
+
+    #[test]
+    fn test_borsh_corrupted_length_prefix() {
+        let mut data = borsh::to_vec(&record()).unwrap();
+        // The length prefix of the values follows the id
+        data[8..12].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(borsh_from_slice::<Record>(&data).is_err());
+    }
+
+    #[test]
+    fn test_oversized_blob() {
+        let data = vec![0; DEFAULT_MAX_BLOB_SIZE + 1];
+        assert!(borsh_from_slice::<Vec<u8>>(&data).is_err());
+        assert!(json_from_slice::<serde_json::Value>(&data).is_err());
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let value = serde_json::json!({"id": 42, "values": ["alice.near"]});
+        let data = serde_json::to_vec(&value).unwrap();
+        assert_eq!(json_from_slice::<serde_json::Value>(&data).unwrap(), value);
+        assert!(json_from_slice::<serde_json::Value>(&data[..data.len() - 1]).is_err());
+    }
+}
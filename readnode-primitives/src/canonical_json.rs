@@ -0,0 +1,33 @@
+//! Canonical JSON serialization with the object keys sorted recursively.
+//! The same data is always serialized the same way regardless of the fields order
+//! of the types and of the `preserve_order` feature of `serde_json` enabled by any dependency,
+//! so the serialized values can be diffed and compared with the golden files.
+
+/// Sorts the object keys of the JSON value recursively. The arrays order is kept
+pub fn canonicalize(value: serde_json::Value) -> serde_json::Value {
+    match value {
+        serde_json::Value::Object(object) => {
+            let sorted: std::collections::BTreeMap<String, serde_json::Value> = object
+                .into_iter()
+                .map(|(key, value)| (key, canonicalize(value)))
+                .collect();
+            serde_json::Value::Object(sorted.into_iter().collect())
+        }
+        serde_json::Value::Array(array) => {
+            serde_json::Value::Array(array.into_iter().map(canonicalize).collect())
+        }
+        value => value,
+    }
+}
+
+pub fn to_canonical_value<T: serde::Serialize + ?Sized>(
+    value: &T,
+) -> serde_json::Result<serde_json::Value> {
+    Ok(canonicalize(serde_json::to_value(value)?))
+}
+
+pub fn to_canonical_string_pretty<T: serde::Serialize + ?Sized>(
+    value: &T,
+) -> serde_json::Result<String> {
+    serde_json::to_string_pretty(&to_canonical_value(value)?)
+}
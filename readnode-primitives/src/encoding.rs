@@ -0,0 +1,46 @@
+//! Encodings of the contract state keys and values.
+//! The database stores the keys as hex strings, the RPC exposes the keys and values
+//! as base64 strings. All the conversions between the bytes and the strings go through
+//! this module, so both sides agree on the alphabets and the padding.
+use base64::Engine;
+
+// Optional prefix of the hex encoded values accepted at the API boundary
+const HEX_PREFIX: &str = "0x";
+
+/// Hex encoding used by the database
+pub fn encode_hex(value: &[u8]) -> String {
+    hex::encode(value)
+}
+
+pub fn decode_hex(value: &str) -> anyhow::Result<Vec<u8>> {
+    Ok(hex::decode(value)?)
+}
+
+/// Standard padded base64 encoding used by the RPC
+pub fn encode_base64(value: &[u8]) -> String {
+    base64::engine::general_purpose::STANDARD.encode(value)
+}
+
+pub fn decode_base64(value: &str) -> anyhow::Result<Vec<u8>> {
+    Ok(base64::engine::general_purpose::STANDARD.decode(value)?)
+}
+
+/// Decodes the state key or value sent by the client in either encoding.
+/// The values with the `0x` prefix followed by a valid hex string are decoded as hex.
+/// Otherwise the hex alphabet is a subset of the base64 one, so the encoding can be detected
+/// only when the value is not a valid base64 string. Valid base64 values are decoded
+/// as base64, as the RPC documents, so the existing clients are not affected.
+/// The rest of the values are decoded as hex (e.g. values of the length not divisible by 4)
+pub fn decode_lenient(value: &str) -> anyhow::Result<Vec<u8>> {
+    if let Some(bytes) = value
+        .strip_prefix(HEX_PREFIX)
+        .and_then(|hex_value| decode_hex(hex_value).ok())
+    {
+        return Ok(bytes);
+    }
+    if let Ok(bytes) = decode_base64(value) {
+        return Ok(bytes);
+    }
+    decode_hex(value)
+        .map_err(|_| anyhow::anyhow!("Value `{}` is neither base64 nor hex encoded", value))
+}
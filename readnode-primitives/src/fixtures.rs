@@ -0,0 +1,45 @@
+//! Test vectors of the final status resolution of the collecting transactions.
+//! Every fixture in `fixtures/final_status` is a `CollectingTransactionDetails` with the receipts
+//! and outcomes collected by the tx-indexer and the `FinalExecutionStatus` it resolves to
+//! (`null` if the transaction is not finished yet). The fixtures are checked by the unit tests
+//! of this crate and can be loaded by the downstream crates to validate their own resolution.
+//! The details are stored as JSON, the same way the transaction details are stored,
+//! so they are readable and don't depend on the borsh layout of the nearcore views.
+use crate::indexer::views;
+
+#[derive(serde::Deserialize, Debug, Clone)]
+pub struct FinalStatusFixture {
+    /// File name of the fixture without the extension
+    #[serde(skip)]
+    pub name: String,
+    pub description: String,
+    pub transaction_details: crate::CollectingTransactionDetails,
+    pub expected_final_status: Option<views::FinalExecutionStatus>,
+}
+
+pub fn final_status_fixtures_dir() -> std::path::PathBuf {
+    std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("fixtures")
+        .join("final_status")
+}
+
+/// Loads all the final status fixtures sorted by name
+pub fn load_final_status_fixtures() -> anyhow::Result<Vec<FinalStatusFixture>> {
+    let mut fixtures = vec![];
+    for entry in std::fs::read_dir(final_status_fixtures_dir())? {
+        let path = entry?.path();
+        if path.extension().and_then(|extension| extension.to_str()) != Some("json") {
+            continue;
+        }
+        let mut fixture: FinalStatusFixture = serde_json::from_slice(&std::fs::read(&path)?)
+            .map_err(|err| anyhow::anyhow!("Failed to parse {}: {}", path.display(), err))?;
+        fixture.name = path
+            .file_stem()
+            .and_then(|name| name.to_str())
+            .unwrap_or_default()
+            .to_string();
+        fixtures.push(fixture);
+    }
+    fixtures.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(fixtures)
+}
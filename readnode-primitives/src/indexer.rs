@@ -0,0 +1,35 @@
+//! Adapter between the workspace and the `near_indexer_primitives` crate.
+//! The crates of the workspace take the indexer types and the nearcore helpers from here
+//! instead of depending on `near_indexer_primitives` directly. When a nearcore upgrade renames
+//! or reshapes a type (e.g. the `ShardId` newtype or the shard layout methods), the aliases and
+//! the shims of this module are updated and the rest of the workspace keeps compiling as is.
+pub use near_indexer_primitives::{near_primitives, types, views};
+
+/// Block with the chunks, the transactions, the receipts and the state changes of all the shards
+pub type StreamerMessage = near_indexer_primitives::StreamerMessage;
+pub type IndexerShard = near_indexer_primitives::IndexerShard;
+pub type IndexerChunkView = near_indexer_primitives::IndexerChunkView;
+pub type IndexerTransactionWithOutcome = near_indexer_primitives::IndexerTransactionWithOutcome;
+pub type IndexerExecutionOutcomeWithReceipt =
+    near_indexer_primitives::IndexerExecutionOutcomeWithReceipt;
+pub type CryptoHash = near_indexer_primitives::CryptoHash;
+pub type ShardLayout = near_primitives::shard_layout::ShardLayout;
+
+/// Shard of the account in the shard layout.
+/// The free function is replaced by the `ShardLayout` method in the newer nearcore versions
+pub fn account_id_to_shard_id(
+    account_id: &types::AccountId,
+    shard_layout: &ShardLayout,
+) -> types::ShardId {
+    near_primitives::shard_layout::account_id_to_shard_id(account_id, shard_layout)
+}
+
+/// State changes of all the shards of the block in the order of the shards
+pub fn state_changes(
+    streamer_message: &StreamerMessage,
+) -> impl Iterator<Item = &views::StateChangeWithCauseView> {
+    streamer_message
+        .shards
+        .iter()
+        .flat_map(|shard| shard.state_changes.iter())
+}
@@ -1,9 +1,14 @@
-use num_traits::ToPrimitive;
 use std::convert::TryFrom;
 use std::fmt::Display;
 use std::str::FromStr;
 
-use near_indexer_primitives::{views, CryptoHash, IndexerTransactionWithOutcome};
+use crate::indexer::{views, CryptoHash, IndexerTransactionWithOutcome};
+
+pub mod blob;
+pub mod canonical_json;
+pub mod encoding;
+pub mod fixtures;
+pub mod indexer;
 
 #[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Debug)]
 pub struct TransactionKey {
@@ -69,6 +74,39 @@ impl CollectingTransactionDetails {
         }
     }
 
+    /// Add the receipt and its execution outcome to the transaction.
+    /// The same receipt or outcome can arrive more than once (e.g. on stream replay),
+    /// so receipts and outcomes are deduplicated by id to keep the final result consistent.
+    /// Returns `false` if both the receipt and the outcome were already collected.
+    pub fn add_receipt_and_outcome(
+        &mut self,
+        receipt: views::ReceiptView,
+        execution_outcome: views::ExecutionOutcomeWithIdView,
+    ) -> bool {
+        // The transaction outcome is stored separately and never duplicated in execution_outcomes
+        if execution_outcome.id == self.transaction_outcome.id {
+            return false;
+        }
+        let mut added = false;
+        if !self
+            .receipts
+            .iter()
+            .any(|collected| collected.receipt_id == receipt.receipt_id)
+        {
+            self.receipts.push(receipt);
+            added = true;
+        }
+        if !self
+            .execution_outcomes
+            .iter()
+            .any(|collected| collected.id == execution_outcome.id)
+        {
+            self.execution_outcomes.push(execution_outcome);
+            added = true;
+        }
+        added
+    }
+
     /// Build unique transaction key based on transaction_hash and block_height
     /// Help to handle transaction hash collisions
     pub fn transaction_key(&self) -> TransactionKey {
@@ -112,15 +150,64 @@ impl CollectingTransactionDetails {
         })
     }
 
+    /// Receipts and their outcomes in the order of the `nearcore` RPC: depth-first
+    /// from the transaction outcome by the produced receipt ids, so the result doesn't depend
+    /// on the order the outcomes of the parallel shards are collected in.
+    /// The receipts and outcomes unreachable from the transaction outcome are kept
+    /// at the end in the collecting order
+    pub fn receipts_in_execution_order(
+        &self,
+    ) -> (
+        Vec<views::ReceiptView>,
+        Vec<views::ExecutionOutcomeWithIdView>,
+    ) {
+        let outcomes_by_id: std::collections::HashMap<_, _> = self
+            .execution_outcomes
+            .iter()
+            .map(|outcome| (outcome.id, outcome))
+            .collect();
+        let mut positions = std::collections::HashMap::new();
+        // The produced receipt ids are pushed in reverse to visit them in order
+        let mut stack: Vec<CryptoHash> = self
+            .transaction_outcome
+            .outcome
+            .receipt_ids
+            .iter()
+            .rev()
+            .copied()
+            .collect();
+        while let Some(id) = stack.pop() {
+            let Some(outcome) = outcomes_by_id.get(&id) else {
+                continue;
+            };
+            if positions.contains_key(&id) {
+                continue;
+            }
+            positions.insert(id, positions.len());
+            stack.extend(outcome.outcome.receipt_ids.iter().rev().copied());
+        }
+        let position = |id: &CryptoHash| positions.get(id).copied().unwrap_or(usize::MAX);
+
+        // The sorting is stable, the unreachable ones keep the collecting order
+        let mut receipts = self.receipts.clone();
+        receipts.sort_by_key(|receipt| position(&receipt.receipt_id));
+        let mut execution_outcomes = self.execution_outcomes.clone();
+        execution_outcomes.sort_by_key(|outcome| position(&outcome.id));
+        (receipts, execution_outcomes)
+    }
+
     pub fn to_final_transaction_result(&self) -> anyhow::Result<TransactionDetails> {
         match self.final_status() {
-            Some(status) => Ok(TransactionDetails {
-                receipts: self.receipts.clone(),
-                receipts_outcome: self.execution_outcomes.clone(),
-                status,
-                transaction: self.transaction.clone(),
-                transaction_outcome: self.transaction_outcome.clone(),
-            }),
+            Some(status) => {
+                let (receipts, receipts_outcome) = self.receipts_in_execution_order();
+                Ok(TransactionDetails {
+                    receipts,
+                    receipts_outcome,
+                    status,
+                    transaction: self.transaction.clone(),
+                    transaction_outcome: self.transaction_outcome.clone(),
+                })
+            }
             None => anyhow::bail!("Results should resolve to a final outcome"),
         }
     }
@@ -136,9 +223,10 @@ impl From<CollectingTransactionDetails> for TransactionDetails {
         let status = tx
             .final_status()
             .unwrap_or(views::FinalExecutionStatus::NotStarted);
+        let (receipts, receipts_outcome) = tx.receipts_in_execution_order();
         Self {
-            receipts: tx.receipts,
-            receipts_outcome: tx.execution_outcomes,
+            receipts,
+            receipts_outcome,
             status,
             transaction: tx.transaction,
             transaction_outcome: tx.transaction_outcome,
@@ -146,6 +234,20 @@ impl From<CollectingTransactionDetails> for TransactionDetails {
     }
 }
 
+impl From<views::FinalExecutionOutcomeWithReceiptView> for TransactionDetails {
+    // NEAR JSON RPC doesn't return the local receipt of the transaction with the same
+    // signer and receiver, so it is absent in the details built from the RPC response
+    fn from(outcome: views::FinalExecutionOutcomeWithReceiptView) -> Self {
+        Self {
+            receipts: outcome.receipts,
+            receipts_outcome: outcome.final_outcome.receipts_outcome,
+            status: outcome.final_outcome.status,
+            transaction: outcome.final_outcome.transaction,
+            transaction_outcome: outcome.final_outcome.transaction_outcome,
+        }
+    }
+}
+
 #[derive(
     borsh::BorshSerialize,
     borsh::BorshDeserialize,
@@ -211,20 +313,218 @@ impl TransactionDetails {
     // Deserialize TransactionDetails from json bytes
     // This is needed to handle the backward incompatible changes in the TransactionDetails
     pub fn tx_deserialize(data: &[u8]) -> anyhow::Result<Self> {
-        Ok(serde_json::from_slice(data)?)
+        crate::blob::json_from_slice(data)
+    }
+
+    // Serialize only the FinalExecutionOutcomeView (without receipts) to json bytes
+    // It is stored alongside the full TransactionDetails to serve the `tx` method
+    // without deserializing the receipts
+    pub fn outcome_serialize(&self) -> anyhow::Result<Vec<u8>> {
+        let outcome_json = serde_json::to_value(self.to_final_execution_outcome())?.to_string();
+        Ok(outcome_json.into_bytes())
+    }
+
+    // Deserialize FinalExecutionOutcomeView from json bytes stored by `outcome_serialize`
+    pub fn outcome_deserialize(data: &[u8]) -> anyhow::Result<views::FinalExecutionOutcomeView> {
+        crate::blob::json_from_slice(data)
+    }
+}
+
+// Raw bytes of the contract state keys and values.
+// Stored in the database as hex strings (keys) and bytes (values),
+// exposed via RPC as base64 strings. Deserialized from either base64 or hex,
+// see `encoding::decode_lenient`.
+macro_rules! state_bytes_newtype {
+    ($(#[$meta:meta])* $name:ident) => {
+        $(#[$meta])*
+        #[derive(
+            borsh::BorshSerialize,
+            borsh::BorshDeserialize,
+            Debug,
+            Clone,
+            Default,
+            PartialEq,
+            Eq,
+            PartialOrd,
+            Ord,
+            Hash,
+        )]
+        pub struct $name(pub Vec<u8>);
+
+        impl $name {
+            pub fn to_hex(&self) -> String {
+                $crate::encoding::encode_hex(&self.0)
+            }
+
+            pub fn from_hex(value: &str) -> anyhow::Result<Self> {
+                Ok(Self($crate::encoding::decode_hex(value)?))
+            }
+
+            pub fn to_base64(&self) -> String {
+                $crate::encoding::encode_base64(&self.0)
+            }
+
+            pub fn from_base64(value: &str) -> anyhow::Result<Self> {
+                Ok(Self($crate::encoding::decode_base64(value)?))
+            }
+
+            /// Decodes the value sent by the client in either base64 or hex
+            pub fn from_encoded(value: &str) -> anyhow::Result<Self> {
+                Ok(Self($crate::encoding::decode_lenient(value)?))
+            }
+
+            pub fn into_inner(self) -> Vec<u8> {
+                self.0
+            }
+        }
+
+        impl std::ops::Deref for $name {
+            type Target = [u8];
+
+            fn deref(&self) -> &Self::Target {
+                &self.0
+            }
+        }
+
+        impl AsRef<[u8]> for $name {
+            fn as_ref(&self) -> &[u8] {
+                &self.0
+            }
+        }
+
+        // Allows to look up HashMap<$name, _> by &[u8]
+        impl std::borrow::Borrow<[u8]> for $name {
+            fn borrow(&self) -> &[u8] {
+                &self.0
+            }
+        }
+
+        impl From<Vec<u8>> for $name {
+            fn from(value: Vec<u8>) -> Self {
+                Self(value)
+            }
+        }
+
+        impl From<&[u8]> for $name {
+            fn from(value: &[u8]) -> Self {
+                Self(value.to_vec())
+            }
+        }
+
+        impl From<$name> for Vec<u8> {
+            fn from(value: $name) -> Self {
+                value.0
+            }
+        }
+
+        impl serde::Serialize for $name {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_str(&self.to_base64())
+            }
+        }
+
+        impl<'de> serde::Deserialize<'de> for $name {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let value = String::deserialize(deserializer)?;
+                Self::from_encoded(&value).map_err(serde::de::Error::custom)
+            }
+        }
+    };
+}
+
+state_bytes_newtype!(
+    /// Contract state key
+    StateKey
+);
+state_bytes_newtype!(
+    /// Contract state value
+    StateValue
+);
+
+impl From<crate::indexer::types::StoreKey> for StateKey {
+    fn from(value: crate::indexer::types::StoreKey) -> Self {
+        Self(value.into())
+    }
+}
+
+impl From<StateKey> for crate::indexer::types::StoreKey {
+    fn from(value: StateKey) -> Self {
+        value.0.into()
+    }
+}
+
+impl From<crate::indexer::types::StoreValue> for StateValue {
+    fn from(value: crate::indexer::types::StoreValue) -> Self {
+        Self(value.into())
+    }
+}
+
+impl From<StateValue> for crate::indexer::types::StoreValue {
+    fn from(value: StateValue) -> Self {
+        value.0.into()
+    }
+}
+
+impl StateKey {
+    /// Bucket of the key among the `buckets` buckets of the account state.
+    /// The keys are spread by the hash, so the state of the contract sharing the key prefix
+    /// is split evenly. The bucket is stored with the data, the assignment must never change
+    pub fn bucket(&self, buckets: u32) -> u32 {
+        let hash = crate::indexer::near_primitives::hash::hash(&self.0);
+        let mut prefix = [0u8; 8];
+        prefix.copy_from_slice(&hash.0[..8]);
+        (u64::from_le_bytes(prefix) % u64::from(buckets.max(1))) as u32
     }
 }
 
-pub type StateKey = Vec<u8>;
-pub type StateValue = Vec<u8>;
 pub struct BlockHeightShardId(pub u64, pub u64);
+
+/// Aggregated size of the account state (contract data) at some block height
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StateSize {
+    pub keys_count: u64,
+    pub keys_bytes: u64,
+    pub values_bytes: u64,
+}
+
+/// Net growth of the contract state of the account within a period.
+/// Negative values mean the state was shrunk
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct StateGrowth {
+    pub account_id: crate::indexer::types::AccountId,
+    /// Number of the state keys added minus the number of the keys removed
+    pub keys_delta: i64,
+    /// Size of the state keys and values added minus the size of the removed ones in bytes
+    pub bytes_delta: i64,
+}
+
+/// Result of the state growth aggregation of the day
+#[derive(Debug, Clone)]
+pub struct StateGrowthDay {
+    /// Number of the days since the Unix epoch (UTC)
+    pub day: u64,
+    /// Number of the accounts with the state changed within the day
+    pub accounts_count: u64,
+    /// Account with the largest state growth of the day in bytes
+    pub top_grower: Option<StateGrowth>,
+}
+
+/// Contract state key changed between two block heights.
+/// `None` value means the key doesn't exist at the block height
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StateKeyDiff {
+    pub key: StateKey,
+    pub from_value: Option<StateValue>,
+    pub to_value: Option<StateValue>,
+}
+
 pub struct QueryData<T: borsh::BorshDeserialize> {
     pub data: T,
     // block_height and block_hash we return here represents the moment
     // when the data was last updated in the database
     // We used to return it in the `QueryResponse` but it was replaced with
     // the logic that corresponds the logic of the `nearcore` RPC API
-    pub block_height: near_indexer_primitives::types::BlockHeight,
+    pub block_height: crate::indexer::types::BlockHeight,
     pub block_hash: CryptoHash,
 }
 
@@ -232,20 +532,380 @@ pub struct QueryData<T: borsh::BorshDeserialize> {
 pub struct ReceiptRecord {
     pub receipt_id: CryptoHash,
     pub parent_transaction_hash: CryptoHash,
-    pub receiver_id: near_indexer_primitives::types::AccountId,
-    pub block_height: near_indexer_primitives::types::BlockHeight,
+    pub receiver_id: crate::indexer::types::AccountId,
+    pub block_height: crate::indexer::types::BlockHeight,
     pub block_hash: CryptoHash,
-    pub shard_id: near_indexer_primitives::types::ShardId,
+    pub shard_id: crate::indexer::types::ShardId,
+    /// Position of the receipt execution outcome in the chunk.
+    /// `None` for the records stored before the position was tracked
+    /// and for the records repaired from the RPC
+    pub index_in_chunk: Option<u32>,
+    /// Body of the receipt to serve it without the transaction details.
+    /// `None` for the records stored before the bodies were stored
+    /// and for the receipts mapped by their transaction before the execution
+    pub receipt: Option<views::ReceiptView>,
 }
 
 #[derive(Debug, Clone)]
 pub struct OutcomeRecord {
     pub outcome_id: CryptoHash,
     pub parent_transaction_hash: CryptoHash,
-    pub receiver_id: near_indexer_primitives::types::AccountId,
-    pub block_height: near_indexer_primitives::types::BlockHeight,
+    pub receiver_id: crate::indexer::types::AccountId,
+    pub block_height: crate::indexer::types::BlockHeight,
+    pub block_hash: CryptoHash,
+    pub shard_id: crate::indexer::types::ShardId,
+    /// Position of the receipt execution outcome in the chunk.
+    /// `None` for the records stored before the position was tracked
+    /// and for the records repaired from the RPC
+    pub index_in_chunk: Option<u32>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct TransactionByPublicKeyRecord {
+    pub public_key: String,
+    pub transaction_hash: CryptoHash,
+    pub signer_id: crate::indexer::types::AccountId,
+    pub receiver_id: crate::indexer::types::AccountId,
+    pub nonce: crate::indexer::types::Nonce,
+    pub block_height: crate::indexer::types::BlockHeight,
+    pub block_hash: CryptoHash,
+    pub shard_id: crate::indexer::types::ShardId,
+    /// Protocol version of the epoch of the block including the transaction.
+    /// `None` for the transactions indexed before it was stored and not backfilled yet
+    pub protocol_version: Option<crate::indexer::types::ProtocolVersion>,
+    /// Gas price of the block including the transaction.
+    /// `None` for the transactions indexed before it was stored and not backfilled yet
+    #[serde(with = "crate::indexer::near_primitives::serialize::dec_format")]
+    pub gas_price: Option<crate::indexer::types::Balance>,
+}
+
+impl TransactionByPublicKeyRecord {
+    pub fn new(
+        transaction: &views::SignedTransactionView,
+        block_header: &views::BlockHeaderView,
+        shard_id: crate::indexer::types::ShardId,
+        protocol_version: crate::indexer::types::ProtocolVersion,
+    ) -> Self {
+        Self {
+            public_key: transaction.public_key.to_string(),
+            transaction_hash: transaction.hash,
+            signer_id: transaction.signer_id.clone(),
+            receiver_id: transaction.receiver_id.clone(),
+            nonce: transaction.nonce,
+            block_height: block_header.height,
+            block_hash: block_header.hash,
+            shard_id,
+            protocol_version: Some(protocol_version),
+            gas_price: Some(block_header.gas_price),
+        }
+    }
+}
+
+/// Transaction sent to the network through the rpc-server and kept until it expires.
+/// `submitted_at` is the unix timestamp in seconds
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct PendingTransaction {
+    pub transaction_hash: CryptoHash,
+    pub signer_id: crate::indexer::types::AccountId,
+    pub public_key: String,
+    pub receiver_id: crate::indexer::types::AccountId,
+    pub nonce: crate::indexer::types::Nonce,
+    pub submitted_at: u64,
+}
+
+impl PendingTransaction {
+    pub fn new(
+        signed_transaction: &crate::indexer::near_primitives::transaction::SignedTransaction,
+        submitted_at: u64,
+    ) -> Self {
+        let transaction = &signed_transaction.transaction;
+        Self {
+            transaction_hash: signed_transaction.get_hash(),
+            signer_id: transaction.signer_id().clone(),
+            public_key: transaction.public_key().to_string(),
+            receiver_id: transaction.receiver_id().clone(),
+            nonce: transaction.nonce(),
+            submitted_at,
+        }
+    }
+}
+
+/// Function call action executed on one of the contracts with the searchable calls.
+/// The arguments are stored decoded to search them by a substring
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct FunctionCallRecord {
+    pub receipt_id: CryptoHash,
+    /// Index of the action in the receipt
+    pub action_index: u32,
+    pub block_height: crate::indexer::types::BlockHeight,
+    pub block_hash: CryptoHash,
+    pub predecessor_id: crate::indexer::types::AccountId,
+    pub signer_id: crate::indexer::types::AccountId,
+    pub receiver_id: crate::indexer::types::AccountId,
+    pub method_name: String,
+    /// Arguments decoded as UTF-8, `None` for the binary arguments
+    pub args: Option<String>,
+    /// `msg` field of the JSON arguments, e.g. of `ft_transfer_call`
+    pub msg: Option<String>,
+}
+
+impl FunctionCallRecord {
+    /// Collects the function calls of the action receipt.
+    /// Arguments and `msg` longer than `max_args_size` bytes are truncated
+    pub fn from_receipt(
+        receipt: &views::ReceiptView,
+        block_header: &views::BlockHeaderView,
+        max_args_size: usize,
+    ) -> Vec<Self> {
+        let (signer_id, actions) = match &receipt.receipt {
+            views::ReceiptEnumView::Action {
+                signer_id, actions, ..
+            } => (signer_id, actions),
+            _ => return vec![],
+        };
+        actions
+            .iter()
+            .enumerate()
+            .filter_map(|(action_index, action)| match action {
+                views::ActionView::FunctionCall {
+                    method_name, args, ..
+                } => Some((action_index, method_name, args)),
+                _ => None,
+            })
+            .map(|(action_index, method_name, args)| {
+                let args = std::str::from_utf8(&args[..]).ok();
+                let msg = args
+                    .and_then(|args| serde_json::from_str::<serde_json::Value>(args).ok())
+                    .and_then(|args| args.get("msg")?.as_str().map(str::to_string));
+                Self {
+                    receipt_id: receipt.receipt_id,
+                    action_index: action_index as u32,
+                    block_height: block_header.height,
+                    block_hash: block_header.hash,
+                    predecessor_id: receipt.predecessor_id.clone(),
+                    signer_id: signer_id.clone(),
+                    receiver_id: receipt.receiver_id.clone(),
+                    method_name: method_name.clone(),
+                    args: args.map(|args| truncate_to_size(args.to_string(), max_args_size)),
+                    msg: msg.map(|msg| truncate_to_size(msg, max_args_size)),
+                }
+            })
+            .collect()
+    }
+}
+
+// Truncates the string to the given size in bytes keeping it valid UTF-8
+fn truncate_to_size(mut value: String, max_size: usize) -> String {
+    if value.len() > max_size {
+        let mut size = max_size;
+        while !value.is_char_boundary(size) {
+            size -= 1;
+        }
+        value.truncate(size);
+    }
+    value
+}
+
+/// Action of the executed receipt exploded to the queryable columns.
+/// Only the function calls, transfers and access keys changes are stored
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ActionRecord {
+    pub receipt_id: CryptoHash,
+    /// Index of the action in the receipt
+    pub action_index: u32,
+    pub block_height: crate::indexer::types::BlockHeight,
     pub block_hash: CryptoHash,
-    pub shard_id: near_indexer_primitives::types::ShardId,
+    pub predecessor_id: crate::indexer::types::AccountId,
+    pub signer_id: crate::indexer::types::AccountId,
+    pub receiver_id: crate::indexer::types::AccountId,
+    /// One of `function_call`, `transfer`, `add_key`, `delete_key`
+    pub action_kind: String,
+    /// Method name of the function call
+    pub method_name: Option<String>,
+    /// Attached deposit of the function call or the amount of the transfer
+    #[serde(with = "crate::indexer::near_primitives::serialize::dec_format")]
+    pub deposit: Option<crate::indexer::types::Balance>,
+    /// Attached gas of the function call
+    pub gas: Option<crate::indexer::types::Gas>,
+    /// Public key added or deleted by the access key actions
+    pub public_key: Option<String>,
+}
+
+impl ActionRecord {
+    /// Collects the actions of the action receipt
+    pub fn from_receipt(
+        receipt: &views::ReceiptView,
+        block_header: &views::BlockHeaderView,
+    ) -> Vec<Self> {
+        let (signer_id, actions) = match &receipt.receipt {
+            views::ReceiptEnumView::Action {
+                signer_id, actions, ..
+            } => (signer_id, actions),
+            _ => return vec![],
+        };
+        actions
+            .iter()
+            .enumerate()
+            .filter_map(|(action_index, action)| {
+                let (action_kind, method_name, deposit, gas, public_key) = match action {
+                    views::ActionView::FunctionCall {
+                        method_name,
+                        gas,
+                        deposit,
+                        ..
+                    } => (
+                        "function_call",
+                        Some(method_name.clone()),
+                        Some(*deposit),
+                        Some(*gas),
+                        None,
+                    ),
+                    views::ActionView::Transfer { deposit } => {
+                        ("transfer", None, Some(*deposit), None, None)
+                    }
+                    views::ActionView::AddKey { public_key, .. } => {
+                        ("add_key", None, None, None, Some(public_key.to_string()))
+                    }
+                    views::ActionView::DeleteKey { public_key } => {
+                        ("delete_key", None, None, None, Some(public_key.to_string()))
+                    }
+                    _ => return None,
+                };
+                Some(Self {
+                    receipt_id: receipt.receipt_id,
+                    action_index: action_index as u32,
+                    block_height: block_header.height,
+                    block_hash: block_header.hash,
+                    predecessor_id: receipt.predecessor_id.clone(),
+                    signer_id: signer_id.clone(),
+                    receiver_id: receipt.receiver_id.clone(),
+                    action_kind: action_kind.to_string(),
+                    method_name,
+                    deposit,
+                    gas,
+                    public_key,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Version of the indexer writing the data, stored with its last processed block
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct WriterVersion {
+    pub indexer_id: String,
+    /// "tx" or "state"
+    pub indexer_type: Option<String>,
+    pub crate_version: String,
+    pub schema_version: u32,
+}
+
+/// Network the indexed data belongs to. Recorded by the first indexer writing to the database,
+/// the components connected to another network refuse to work with the database
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct NetworkIdentity {
+    pub chain_id: String,
+    pub genesis_hash: CryptoHash,
+}
+
+impl NetworkIdentity {
+    /// Checks the data of this network can be stored together with the `stored` one.
+    /// The chain id alone doesn't identify the network, e.g. the localnets share it
+    pub fn ensure_same_network(&self, stored: &Self) -> anyhow::Result<()> {
+        if self != stored {
+            anyhow::bail!(
+                "Database contains the data of the network `{}` (genesis hash {}), \
+                but the node is on the network `{}` (genesis hash {})",
+                stored.chain_id,
+                stored.genesis_hash,
+                self.chain_id,
+                self.genesis_hash
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Summary of the block processing by the tx-indexer
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct BlockProcessingStats {
+    pub block_height: crate::indexer::types::BlockHeight,
+    pub block_hash: CryptoHash,
+    /// Transactions started to be collected in the block
+    pub txs_started: u64,
+    /// Receipts produced by the collecting transactions and added to the watching list
+    pub receipts_matched: u64,
+    /// Receipt execution outcomes matched to the collecting transactions
+    pub outcomes_matched: u64,
+    /// Transactions finished in the block and passed to be saved
+    pub txs_finalized: u64,
+    pub collect_duration_ms: u64,
+    pub save_tx_details_duration_ms: u64,
+    pub save_outcomes_and_receipts_duration_ms: u64,
+    pub save_transactions_by_public_key_duration_ms: u64,
+}
+
+/// Number of the records of the block, either stored in the database
+/// or expected from the block data. Used to find the gaps in the indexed data
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct BlockDataCounts {
+    pub blocks: u64,
+    pub chunks: u64,
+    pub transactions: u64,
+    pub receipts: u64,
+}
+
+impl std::ops::AddAssign for BlockDataCounts {
+    fn add_assign(&mut self, other: Self) {
+        self.blocks += other.blocks;
+        self.chunks += other.chunks;
+        self.transactions += other.transactions;
+        self.receipts += other.receipts;
+    }
+}
+
+impl BlockDataCounts {
+    /// Checks if any of the counts is lower than the expected one
+    pub fn is_missing_any_of(&self, expected: &Self) -> bool {
+        self.blocks < expected.blocks
+            || self.chunks < expected.chunks
+            || self.transactions < expected.transactions
+            || self.receipts < expected.receipts
+    }
+}
+
+/// Members of the tx-indexer group sharing the blocks by `block_height % members.len()`
+/// from `from_block_height` until the next assignment of the group
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GroupAssignment {
+    pub from_block_height: u64,
+    /// indexer_ids of the members sorted in ascending order, the position is the slot
+    pub members: Vec<String>,
+}
+
+/// Repair of the data missing in the indexed block, recorded for audit
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct BlockRepair {
+    pub block_height: crate::indexer::types::BlockHeight,
+    pub block_hash: CryptoHash,
+    /// What detected the missing data, e.g. `completeness_report`
+    pub reason: String,
+    /// Transactions re-collected from the archival RPC
+    pub transactions: u64,
+    /// Receipts of the re-collected transactions
+    pub receipts: u64,
+    /// Error of the failed repair
+    pub error: Option<String>,
+}
+
+/// Block the indexer failed to process, kept until the block is repaired
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct MissedBlock {
+    pub block_height: crate::indexer::types::BlockHeight,
+    /// Error of the last failed attempt
+    pub error: String,
+    /// Number of the failed attempts to process the block
+    pub attempts: u64,
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -262,6 +922,81 @@ pub struct EpochValidatorsInfo {
     pub validators_info: views::EpochValidatorInfo,
 }
 
+/// Stake change of the validator between the current and the next epoch
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ValidatorStakeChange {
+    pub account_id: crate::indexer::types::AccountId,
+    #[serde(with = "crate::indexer::near_primitives::serialize::dec_format")]
+    pub current_stake: crate::indexer::types::Balance,
+    #[serde(with = "crate::indexer::near_primitives::serialize::dec_format")]
+    pub next_stake: crate::indexer::types::Balance,
+}
+
+impl ValidatorStakeChange {
+    /// Collects the stake changes of the validators between the current and the next epoch.
+    /// Zero stake means the validator is not in the validators set of the epoch
+    pub fn from_validators_info(validators_info: &views::EpochValidatorInfo) -> Vec<Self> {
+        let mut stakes: std::collections::BTreeMap<
+            crate::indexer::types::AccountId,
+            (
+                crate::indexer::types::Balance,
+                crate::indexer::types::Balance,
+            ),
+        > = std::collections::BTreeMap::new();
+        for validator in &validators_info.current_validators {
+            stakes.entry(validator.account_id.clone()).or_default().0 = validator.stake;
+        }
+        for validator in &validators_info.next_validators {
+            stakes.entry(validator.account_id.clone()).or_default().1 = validator.stake;
+        }
+        stakes
+            .into_iter()
+            .filter(|(_, (current_stake, next_stake))| current_stake != next_stake)
+            .map(|(account_id, (current_stake, next_stake))| Self {
+                account_id,
+                current_stake,
+                next_stake,
+            })
+            .collect()
+    }
+}
+
+/// Validators kickouts and stake changes of the epoch
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct EpochValidatorsChanges {
+    pub epoch_id: CryptoHash,
+    pub epoch_height: u64,
+    pub epoch_start_height: u64,
+    pub epoch_end_height: Option<u64>,
+    pub prev_epoch_kickout: Vec<views::ValidatorKickoutView>,
+    pub stake_changes: Vec<ValidatorStakeChange>,
+}
+
+/// Block produced by the validator
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ProducedBlock {
+    pub block_height: u64,
+    pub block_hash: CryptoHash,
+}
+
+/// Chunk produced by the validator.
+/// `block_height` is the height of the block the chunk is included in
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ProducedChunk {
+    pub block_height: u64,
+    pub shard_id: u64,
+    pub chunk_hash: CryptoHash,
+}
+
+/// Congestion of the shard at the block carried by the chunk header.
+/// `height_included` is behind the block height if the chunk of the shard is missed,
+/// the congestion info of the last included chunk is carried over then
+#[derive(Debug, Clone)]
+pub struct ChunkCongestion {
+    pub height_included: u64,
+    pub congestion_info: views::CongestionInfoView,
+}
+
 #[derive(Debug)]
 pub struct IndexedEpochInfo {
     pub epoch_id: CryptoHash,
@@ -279,46 +1014,16 @@ pub struct IndexedEpochInfoWithPreviousAndNextEpochId {
 
 // TryFrom impls for defined types
 
-impl<T> TryFrom<(T, T)> for BlockHeightShardId
-where
-    T: ToPrimitive,
-{
-    type Error = anyhow::Error;
-
-    fn try_from(value: (T, T)) -> Result<Self, Self::Error> {
-        let stored_at_block_height = value
-            .0
-            .to_u64()
-            .ok_or_else(|| anyhow::anyhow!("Failed to parse `stored_at_block_height` to u64"))?;
-
-        let parsed_shard_id = value
-            .1
-            .to_u64()
-            .ok_or_else(|| anyhow::anyhow!("Failed to parse `shard_id` to u64"))?;
-
-        Ok(BlockHeightShardId(stored_at_block_height, parsed_shard_id))
-    }
-}
-
-impl<T>
-    TryFrom<(
-        Vec<u8>,
-        near_indexer_primitives::types::BlockHeight,
-        CryptoHash,
-    )> for QueryData<T>
+impl<T> TryFrom<(Vec<u8>, crate::indexer::types::BlockHeight, CryptoHash)> for QueryData<T>
 where
     T: borsh::BorshDeserialize,
 {
     type Error = anyhow::Error;
 
     fn try_from(
-        value: (
-            Vec<u8>,
-            near_indexer_primitives::types::BlockHeight,
-            CryptoHash,
-        ),
+        value: (Vec<u8>, crate::indexer::types::BlockHeight, CryptoHash),
     ) -> Result<Self, Self::Error> {
-        let data = T::try_from_slice(&value.0)?;
+        let data = crate::blob::borsh_from_slice(&value.0)?;
 
         Ok(Self {
             data,
@@ -328,64 +1033,882 @@ where
     }
 }
 
-impl<T> TryFrom<(String, String, String, T, String, T)> for ReceiptRecord
-where
-    T: ToPrimitive,
-{
-    type Error = anyhow::Error;
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIGNER_ID: &str = "signer.near";
+    const RECEIVER_ID: &str = "receiver.near";
+    // Base58 encoded zero bytes
+    const PUBLIC_KEY: &str = "ed25519:11111111111111111111111111111111";
+    const SIGNATURE: &str =
+        "ed25519:1111111111111111111111111111111111111111111111111111111111111111";
+
+    fn hash(value: &str) -> CryptoHash {
+        CryptoHash::hash_bytes(value.as_bytes())
+    }
+
+    fn outcome(
+        id: CryptoHash,
+        executor_id: &str,
+        status: serde_json::Value,
+        receipt_ids: Vec<CryptoHash>,
+    ) -> views::ExecutionOutcomeWithIdView {
+        serde_json::from_value(outcome_json(id, executor_id, status, receipt_ids)).unwrap()
+    }
 
-    fn try_from(value: (String, String, String, T, String, T)) -> Result<Self, Self::Error> {
-        let receipt_id = CryptoHash::from_str(&value.0).map_err(|err| {
-            anyhow::anyhow!("Failed to parse `receipt_id` to CryptoHash: {}", err)
-        })?;
-        let parent_transaction_hash = CryptoHash::from_str(&value.1).map_err(|err| {
-            anyhow::anyhow!(
-                "Failed to parse `parent_transaction_hash` to CryptoHash: {}",
-                err
+    fn outcome_json(
+        id: CryptoHash,
+        executor_id: &str,
+        status: serde_json::Value,
+        receipt_ids: Vec<CryptoHash>,
+    ) -> serde_json::Value {
+        serde_json::json!({
+            "proof": [],
+            "block_hash": hash("block").to_string(),
+            "id": id.to_string(),
+            "outcome": {
+                "logs": [],
+                "receipt_ids": receipt_ids.iter().map(ToString::to_string).collect::<Vec<_>>(),
+                "gas_burnt": 0,
+                "tokens_burnt": "0",
+                "executor_id": executor_id,
+                "status": status,
+                "metadata": {"version": 1, "gas_profile": null},
+            },
+        })
+    }
+
+    fn receipt(receipt_id: CryptoHash) -> views::ReceiptView {
+        serde_json::from_value(receipt_json(receipt_id)).unwrap()
+    }
+
+    fn receipt_json(receipt_id: CryptoHash) -> serde_json::Value {
+        serde_json::json!({
+            "predecessor_id": SIGNER_ID,
+            "receiver_id": RECEIVER_ID,
+            "receipt_id": receipt_id.to_string(),
+            "receipt": {
+                "Action": {
+                    "signer_id": SIGNER_ID,
+                    "signer_public_key": PUBLIC_KEY,
+                    "gas_price": "0",
+                    "output_data_receivers": [],
+                    "input_data_ids": [],
+                    "actions": [],
+                }
+            },
+        })
+    }
+
+    // Transaction converted into the receipt `receipt_1`
+    // which is executed successfully with the empty value
+    fn transaction_details() -> CollectingTransactionDetails {
+        let transaction_hash = hash("transaction");
+        let transaction: views::SignedTransactionView = serde_json::from_value(serde_json::json!({
+            "signer_id": SIGNER_ID,
+            "public_key": PUBLIC_KEY,
+            "nonce": 1,
+            "receiver_id": RECEIVER_ID,
+            "actions": [],
+            "signature": SIGNATURE,
+            "hash": transaction_hash.to_string(),
+        }))
+        .unwrap();
+        CollectingTransactionDetails {
+            transaction,
+            receipts: vec![],
+            transaction_outcome: outcome(
+                transaction_hash,
+                SIGNER_ID,
+                serde_json::json!({"SuccessReceiptId": hash("receipt_1").to_string()}),
+                vec![hash("receipt_1")],
+            ),
+            execution_outcomes: vec![],
+            block_height: 1,
+        }
+    }
+
+    fn receipt_1_outcome() -> views::ExecutionOutcomeWithIdView {
+        outcome(
+            hash("receipt_1"),
+            RECEIVER_ID,
+            serde_json::json!({"SuccessValue": ""}),
+            vec![],
+        )
+    }
+
+    #[test]
+    fn test_replayed_chunk_does_not_duplicate_receipts_and_outcomes() {
+        let mut tx = transaction_details();
+        assert!(tx.add_receipt_and_outcome(receipt(hash("receipt_1")), receipt_1_outcome()));
+        // The same chunk arrives again on stream replay
+        assert!(!tx.add_receipt_and_outcome(receipt(hash("receipt_1")), receipt_1_outcome()));
+
+        assert_eq!(tx.receipts.len(), 1);
+        assert_eq!(tx.execution_outcomes.len(), 1);
+
+        let tx_details = tx.to_final_transaction_result().unwrap();
+        assert_eq!(tx_details.receipts_outcome.len(), 1);
+        assert_eq!(
+            tx_details.status,
+            views::FinalExecutionStatus::SuccessValue(vec![])
+        );
+    }
+
+    #[test]
+    fn test_transaction_outcome_is_not_added_to_execution_outcomes() {
+        let mut tx = transaction_details();
+        let transaction_outcome = tx.transaction_outcome.clone();
+        assert!(!tx.add_receipt_and_outcome(receipt(hash("transaction")), transaction_outcome));
+
+        assert!(tx.receipts.is_empty());
+        assert!(tx.execution_outcomes.is_empty());
+        assert_eq!(
+            tx.final_status(),
+            Some(views::FinalExecutionStatus::Started)
+        );
+    }
+
+    #[test]
+    fn test_replayed_receipts_keep_collecting_order() {
+        let mut tx = transaction_details();
+        let receipt_2_outcome = outcome(
+            hash("receipt_2"),
+            SIGNER_ID,
+            serde_json::json!({"SuccessValue": ""}),
+            vec![],
+        );
+        tx.add_receipt_and_outcome(receipt(hash("receipt_1")), receipt_1_outcome());
+        tx.add_receipt_and_outcome(receipt(hash("receipt_2")), receipt_2_outcome.clone());
+        // Replay of both chunks
+        tx.add_receipt_and_outcome(receipt(hash("receipt_1")), receipt_1_outcome());
+        tx.add_receipt_and_outcome(receipt(hash("receipt_2")), receipt_2_outcome);
+
+        assert_eq!(
+            tx.execution_outcomes
+                .iter()
+                .map(|outcome| outcome.id)
+                .collect::<Vec<_>>(),
+            vec![hash("receipt_1"), hash("receipt_2")]
+        );
+        assert_eq!(
+            tx.receipts
+                .iter()
+                .map(|receipt| receipt.receipt_id)
+                .collect::<Vec<_>>(),
+            vec![hash("receipt_1"), hash("receipt_2")]
+        );
+    }
+
+    #[test]
+    fn test_final_result_keeps_execution_order() {
+        let mut tx = transaction_details();
+        // receipt_1 produces receipt_2 and receipt_3, receipt_2 produces receipt_4
+        let receipt_1_outcome = outcome(
+            hash("receipt_1"),
+            RECEIVER_ID,
+            serde_json::json!({"SuccessValue": ""}),
+            vec![hash("receipt_2"), hash("receipt_3")],
+        );
+        let leaf_outcome = |id: &str| {
+            outcome(
+                hash(id),
+                RECEIVER_ID,
+                serde_json::json!({"SuccessValue": ""}),
+                vec![],
             )
-        })?;
-        let receiver_id =
-            near_indexer_primitives::types::AccountId::from_str(&value.2).map_err(|err| {
-                anyhow::anyhow!("Failed to parse `receiver_id` to AccountId: {}", err)
-            })?;
-        let block_height = value
-            .3
-            .to_u64()
-            .ok_or_else(|| anyhow::anyhow!("Failed to parse `block_height` to u64"))?;
-        let block_hash = CryptoHash::from_str(&value.4).map_err(|err| {
-            anyhow::anyhow!("Failed to parse `block_hash` to CryptoHash: {}", err)
-        })?;
-        let shard_id = value
-            .5
-            .to_u64()
-            .ok_or_else(|| anyhow::anyhow!("Failed to parse `shard_id` to u64"))?;
-
-        Ok(ReceiptRecord {
-            receipt_id,
-            parent_transaction_hash,
-            receiver_id,
-            block_height,
-            block_hash,
-            shard_id,
+        };
+        let receipt_2_outcome = outcome(
+            hash("receipt_2"),
+            RECEIVER_ID,
+            serde_json::json!({"SuccessValue": ""}),
+            vec![hash("receipt_4")],
+        );
+        // Outcomes of the parallel shards are collected out of order
+        tx.add_receipt_and_outcome(receipt(hash("receipt_3")), leaf_outcome("receipt_3"));
+        tx.add_receipt_and_outcome(receipt(hash("receipt_4")), leaf_outcome("receipt_4"));
+        tx.add_receipt_and_outcome(receipt(hash("receipt_1")), receipt_1_outcome);
+        tx.add_receipt_and_outcome(receipt(hash("unrelated")), leaf_outcome("unrelated"));
+        tx.add_receipt_and_outcome(receipt(hash("receipt_2")), receipt_2_outcome);
+
+        let expected_order = vec![
+            hash("receipt_1"),
+            hash("receipt_2"),
+            hash("receipt_4"),
+            hash("receipt_3"),
+            hash("unrelated"),
+        ];
+        let tx_details = tx.to_final_transaction_result().unwrap();
+        assert_eq!(
+            tx_details
+                .receipts_outcome
+                .iter()
+                .map(|outcome| outcome.id)
+                .collect::<Vec<_>>(),
+            expected_order
+        );
+        assert_eq!(
+            tx_details
+                .receipts
+                .iter()
+                .map(|receipt| receipt.receipt_id)
+                .collect::<Vec<_>>(),
+            expected_order
+        );
+        assert_eq!(
+            TransactionDetails::from(tx)
+                .receipts_outcome
+                .iter()
+                .map(|outcome| outcome.id)
+                .collect::<Vec<_>>(),
+            expected_order
+        );
+    }
+
+    #[test]
+    fn test_final_status_fixtures() {
+        let fixtures = fixtures::load_final_status_fixtures().unwrap();
+        assert_eq!(
+            fixtures
+                .iter()
+                .map(|fixture| fixture.name.as_str())
+                .collect::<Vec<_>>(),
+            vec![
+                "cross_shard_transfer",
+                "delegated_function_call",
+                "failed_function_call",
+                "in_progress",
+                "multi_level_receipts",
+                "not_started",
+            ]
+        );
+        for fixture in fixtures {
+            assert_eq!(
+                fixture.transaction_details.final_status(),
+                fixture.expected_final_status,
+                "Final status of {} doesn't match the fixture",
+                fixture.name
+            );
+            // The collecting transactions are kept in the cache storage as borsh
+            let bytes = borsh::to_vec(&fixture.transaction_details).unwrap();
+            let restored: CollectingTransactionDetails = borsh::from_slice(&bytes).unwrap();
+            assert_eq!(
+                restored.final_status(),
+                fixture.expected_final_status,
+                "Final status of {} doesn't match the fixture after the borsh round trip",
+                fixture.name
+            );
+        }
+    }
+
+    #[test]
+    fn test_state_key_encodings() {
+        let key = StateKey::from(b"STATE".to_vec());
+        assert_eq!(key.to_hex(), "5354415445");
+        assert_eq!(key.to_base64(), "U1RBVEU=");
+        assert_eq!(StateKey::from_hex("5354415445").unwrap(), key);
+        assert_eq!(StateKey::from_base64("U1RBVEU=").unwrap(), key);
+        assert!(StateKey::from_hex("not hex").is_err());
+
+        // serde uses base64 as the RPC does
+        assert_eq!(serde_json::to_string(&key).unwrap(), "\"U1RBVEU=\"");
+        assert_eq!(
+            serde_json::from_str::<StateValue>("\"U1RBVEU=\"").unwrap(),
+            StateValue::from(b"STATE".to_vec())
+        );
+    }
+
+    #[test]
+    fn test_state_key_lenient_decoding() {
+        let key = StateKey::from(b"STA".to_vec());
+        // valid base64 is always decoded as base64, even if it is a valid hex too
+        assert_eq!(StateKey::from_encoded("U1RB").unwrap(), key);
+        assert_eq!(
+            StateKey::from_encoded("53544154").unwrap(),
+            StateKey::from_base64("53544154").unwrap()
+        );
+        // not a valid base64 because of the length, detected as hex
+        assert_eq!(StateKey::from_encoded("535441").unwrap(), key);
+        // `0x` prefixed hex is decoded as hex, even if it is a valid base64 too
+        assert_eq!(StateKey::from_encoded("0x535441").unwrap(), key);
+        assert_eq!(
+            StateKey::from_encoded("0x53544154").unwrap(),
+            StateKey::from(b"STAT".to_vec())
+        );
+        assert_eq!(serde_json::from_str::<StateKey>("\"535441\"").unwrap(), key);
+        assert!(StateKey::from_encoded("0xU1R").is_err());
+        assert!(StateKey::from_encoded("neither!").is_err());
+    }
+
+    // The buckets of the stored state rows are computed by the indexers and the rpc-server
+    // independently, the assignment changed by a refactoring would hide the stored state
+    #[test]
+    fn test_state_key_bucket_is_stable() {
+        assert_eq!(StateKey::from(b"STATE".to_vec()).bucket(16), 15);
+        assert_eq!(StateKey::from(b"STATE".to_vec()).bucket(1024), 255);
+        assert_eq!(StateKey::from(b"STATE\x00".to_vec()).bucket(1024), 897);
+        assert_eq!(StateKey::from(vec![]).bucket(1024), 227);
+        // the single bucket and the disabled buckets keep all the keys together
+        assert_eq!(StateKey::from(b"STATE".to_vec()).bucket(1), 0);
+        assert_eq!(StateKey::from(b"STATE".to_vec()).bucket(0), 0);
+    }
+
+    // Round-trips of the arbitrary byte keys through all the encodings.
+    // The keys are generated by a seeded xorshift, so the failures are reproducible
+    #[test]
+    fn test_state_key_encodings_round_trip_fuzz() {
+        let mut seed: u64 = 0x2545_f491_4f6c_dd1d;
+        let mut next_byte = || {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            (seed & 0xff) as u8
+        };
+        for iteration in 0..2000 {
+            let len = iteration % 80;
+            let key = StateKey::from((0..len).map(|_| next_byte()).collect::<Vec<u8>>());
+
+            assert_eq!(StateKey::from_hex(&key.to_hex()).unwrap(), key);
+            assert_eq!(StateKey::from_base64(&key.to_base64()).unwrap(), key);
+            // base64 is detected unless it looks like `0x` prefixed hex
+            let base64 = key.to_base64();
+            if !base64.starts_with("0x") || StateKey::from_hex(&base64[2..]).is_err() {
+                assert_eq!(StateKey::from_encoded(&base64).unwrap(), key);
+            }
+            // `0x` prefixed hex is always detected,
+            // the plain hex is detected when it is not a valid base64
+            assert_eq!(
+                StateKey::from_encoded(&format!("0x{}", key.to_hex())).unwrap(),
+                key
+            );
+            if StateKey::from_base64(&key.to_hex()).is_err() {
+                assert_eq!(StateKey::from_encoded(&key.to_hex()).unwrap(), key);
+            }
+            let json = serde_json::to_string(&key).unwrap();
+            assert_eq!(serde_json::from_str::<StateKey>(&json).unwrap(), key);
+            assert_eq!(
+                borsh::from_slice::<StateKey>(&borsh::to_vec(&key).unwrap()).unwrap(),
+                key
+            );
+        }
+    }
+
+    // Golden files of the JSON returned to the clients. A change of the serialization is
+    // a breaking change of the API. Run the tests with `UPDATE_GOLDEN_FILES=1` to rewrite
+    // the files once the change is agreed
+    fn assert_golden<T: serde::Serialize>(name: &str, value: &T) {
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("golden")
+            .join(format!("{}.json", name));
+        let actual = canonical_json::to_canonical_string_pretty(value).unwrap();
+        if std::env::var("UPDATE_GOLDEN_FILES").is_ok() {
+            std::fs::write(&path, format!("{}\n", actual)).unwrap();
+            return;
+        }
+        let expected = std::fs::read_to_string(&path)
+            .unwrap_or_else(|err| panic!("Failed to read {}: {}", path.display(), err));
+        assert_eq!(
+            actual,
+            expected.trim_end(),
+            "Serialization of {} doesn't match the golden file",
+            name
+        );
+    }
+
+    #[test]
+    fn test_golden_transaction_by_public_key_record() {
+        assert_golden(
+            "transaction_by_public_key_record",
+            &TransactionByPublicKeyRecord {
+                public_key: PUBLIC_KEY.to_string(),
+                transaction_hash: CryptoHash::default(),
+                signer_id: SIGNER_ID.parse().unwrap(),
+                receiver_id: RECEIVER_ID.parse().unwrap(),
+                nonce: 42,
+                block_height: 118875450,
+                block_hash: CryptoHash::default(),
+                shard_id: 2,
+                protocol_version: Some(70),
+                gas_price: Some(100000000),
+            },
+        );
+    }
+
+    #[test]
+    fn test_golden_function_call_record() {
+        assert_golden(
+            "function_call_record",
+            &FunctionCallRecord {
+                receipt_id: CryptoHash::default(),
+                action_index: 0,
+                block_height: 118875450,
+                block_hash: CryptoHash::default(),
+                predecessor_id: SIGNER_ID.parse().unwrap(),
+                signer_id: SIGNER_ID.parse().unwrap(),
+                receiver_id: RECEIVER_ID.parse().unwrap(),
+                method_name: "ft_transfer_call".to_string(),
+                args: Some(
+                    r#"{"amount":"100","msg":"{\"action\":\"swap\"}","receiver_id":"app.near"}"#
+                        .to_string(),
+                ),
+                msg: Some(r#"{"action":"swap"}"#.to_string()),
+            },
+        );
+    }
+
+    #[test]
+    fn test_golden_action_record() {
+        assert_golden(
+            "action_record",
+            &ActionRecord {
+                receipt_id: CryptoHash::default(),
+                action_index: 1,
+                block_height: 118875450,
+                block_hash: CryptoHash::default(),
+                predecessor_id: SIGNER_ID.parse().unwrap(),
+                signer_id: SIGNER_ID.parse().unwrap(),
+                receiver_id: RECEIVER_ID.parse().unwrap(),
+                action_kind: "function_call".to_string(),
+                method_name: Some("ft_transfer".to_string()),
+                deposit: Some(1),
+                gas: Some(30000000000000),
+                public_key: None,
+            },
+        );
+    }
+
+    #[test]
+    fn test_function_call_args_are_truncated_to_char_boundary() {
+        assert_eq!(truncate_to_size("abc".to_string(), 5), "abc");
+        assert_eq!(truncate_to_size("abcdef".to_string(), 3), "abc");
+        // "é" takes 2 bytes, the string is cut before it instead of splitting it
+        assert_eq!(truncate_to_size("abé".to_string(), 3), "ab");
+    }
+
+    #[test]
+    fn test_golden_writer_version() {
+        assert_golden(
+            "writer_version",
+            &WriterVersion {
+                indexer_id: "tx-indexer".to_string(),
+                indexer_type: Some("tx".to_string()),
+                crate_version: "0.3.0".to_string(),
+                schema_version: 1,
+            },
+        );
+    }
+
+    #[test]
+    fn test_golden_epoch_validators_changes() {
+        assert_golden(
+            "epoch_validators_changes",
+            &EpochValidatorsChanges {
+                epoch_id: CryptoHash::default(),
+                epoch_height: 2000,
+                epoch_start_height: 118800000,
+                epoch_end_height: Some(118843199),
+                prev_epoch_kickout: vec![views::ValidatorKickoutView {
+                    account_id: "kicked.poolv1.near".parse().unwrap(),
+                    reason: crate::indexer::types::ValidatorKickoutReason::Unstaked,
+                }],
+                stake_changes: vec![ValidatorStakeChange {
+                    account_id: "staked.poolv1.near".parse().unwrap(),
+                    current_stake: 1000,
+                    next_stake: 0,
+                }],
+            },
+        );
+    }
+
+    #[test]
+    fn test_golden_produced_blocks() {
+        assert_golden(
+            "produced_block",
+            &ProducedBlock {
+                block_height: 118875450,
+                block_hash: CryptoHash::default(),
+            },
+        );
+        assert_golden(
+            "produced_chunk",
+            &ProducedChunk {
+                block_height: 118875450,
+                shard_id: 2,
+                chunk_hash: CryptoHash::default(),
+            },
+        );
+    }
+
+    #[test]
+    fn test_golden_block_data_counts() {
+        assert_golden(
+            "block_data_counts",
+            &BlockDataCounts {
+                blocks: 1,
+                chunks: 4,
+                transactions: 10,
+                receipts: 12,
+            },
+        );
+    }
+
+    #[test]
+    fn test_golden_block_processing_stats() {
+        assert_golden(
+            "block_processing_stats",
+            &BlockProcessingStats {
+                block_height: 118875450,
+                block_hash: CryptoHash::default(),
+                txs_started: 10,
+                receipts_matched: 20,
+                outcomes_matched: 30,
+                txs_finalized: 8,
+                collect_duration_ms: 12,
+                save_tx_details_duration_ms: 34,
+                save_outcomes_and_receipts_duration_ms: 56,
+                save_transactions_by_public_key_duration_ms: 7,
+            },
+        );
+    }
+
+    #[test]
+    fn test_golden_block_repair() {
+        assert_golden(
+            "block_repair",
+            &BlockRepair {
+                block_height: 118875450,
+                block_hash: CryptoHash::default(),
+                reason: "completeness_report".to_string(),
+                transactions: 3,
+                receipts: 7,
+                error: None,
+            },
+        );
+    }
+
+    #[test]
+    fn test_golden_missed_block() {
+        assert_golden(
+            "missed_block",
+            &MissedBlock {
+                block_height: 118875450,
+                error: "Failed to save the transactions".to_string(),
+                attempts: 2,
+            },
+        );
+    }
+
+    #[test]
+    fn test_canonical_json_sorts_keys() {
+        let value = serde_json::json!({"b": {"d": 1, "c": [{"f": 1, "e": 2}]}, "a": null});
+        assert_eq!(
+            serde_json::to_string(&canonical_json::canonicalize(value)).unwrap(),
+            r#"{"a":null,"b":{"c":[{"e":2,"f":1}],"d":1}}"#
+        );
+    }
+
+    // Samples of the stored transaction details for the serialization test matrix
+
+    // Transaction is converted into the receipt but the receipt is not executed yet
+    fn started_transaction_details() -> TransactionDetails {
+        TransactionDetails::from(transaction_details())
+    }
+
+    fn success_transaction_details() -> TransactionDetails {
+        let mut tx = transaction_details();
+        tx.add_receipt_and_outcome(receipt(hash("receipt_1")), receipt_1_outcome());
+        tx.to_final_transaction_result().unwrap()
+    }
+
+    fn failure_transaction_details() -> TransactionDetails {
+        let mut tx = transaction_details();
+        tx.add_receipt_and_outcome(
+            receipt(hash("receipt_1")),
+            outcome(
+                hash("receipt_1"),
+                RECEIVER_ID,
+                serde_json::json!({"Failure": {"ActionError": {
+                    "index": 0,
+                    "kind": {"AccountDoesNotExist": {"account_id": RECEIVER_ID}},
+                }}}),
+                vec![],
+            ),
+        );
+        tx.to_final_transaction_result().unwrap()
+    }
+
+    // Transaction details are compared by the JSON representation
+    // because not all the nested views implement `PartialEq`
+    fn assert_same_transaction_details(left: &TransactionDetails, right: &TransactionDetails) {
+        assert_eq!(
+            serde_json::to_value(left).unwrap(),
+            serde_json::to_value(right).unwrap()
+        );
+        assert_eq!(
+            left.to_final_execution_outcome(),
+            right.to_final_execution_outcome()
+        );
+    }
+
+    // Generates the round-trip tests for every stored format of the given sample
+    macro_rules! transaction_details_format_tests {
+        ($($name:ident => $sample:expr),* $(,)?) => {
+            mod transaction_details_formats {
+                use super::*;
+
+                $(
+                    mod $name {
+                        use super::*;
+
+                        #[test]
+                        fn test_json_round_trip() {
+                            let tx_details = $sample;
+                            let bytes = tx_details.tx_serialize().unwrap();
+                            let restored = TransactionDetails::tx_deserialize(&bytes).unwrap();
+                            assert_same_transaction_details(&tx_details, &restored);
+                        }
+
+                        #[test]
+                        fn test_borsh_round_trip() {
+                            let tx_details = $sample;
+                            let bytes = borsh::to_vec(&tx_details).unwrap();
+                            let restored: TransactionDetails = borsh::from_slice(&bytes).unwrap();
+                            assert_same_transaction_details(&tx_details, &restored);
+                        }
+                    }
+                )*
+            }
+        };
+    }
+
+    transaction_details_format_tests! {
+        started => started_transaction_details(),
+        success => success_transaction_details(),
+        failure => failure_transaction_details(),
+    }
+
+    // Transaction details stored in the tx details storage by the previous releases.
+    // Bumping the nearcore version must not break reading of the archival data.
+    #[test]
+    fn test_stored_transaction_details_are_readable() {
+        let stored = serde_json::json!({
+            "receipts": [receipt_json(hash("receipt_1"))],
+            "receipts_outcome": [outcome_json(
+                hash("receipt_1"),
+                RECEIVER_ID,
+                serde_json::json!({"SuccessValue": ""}),
+                vec![],
+            )],
+            "status": {"SuccessValue": ""},
+            "transaction": {
+                "signer_id": SIGNER_ID,
+                "public_key": PUBLIC_KEY,
+                "nonce": 1,
+                "receiver_id": RECEIVER_ID,
+                "actions": [],
+                "signature": SIGNATURE,
+                "hash": hash("transaction").to_string(),
+            },
+            "transaction_outcome": outcome_json(
+                hash("transaction"),
+                SIGNER_ID,
+                serde_json::json!({"SuccessReceiptId": hash("receipt_1").to_string()}),
+                vec![hash("receipt_1")],
+            ),
         })
+        .to_string();
+
+        let restored = TransactionDetails::tx_deserialize(stored.as_bytes()).unwrap();
+        assert_same_transaction_details(&success_transaction_details(), &restored);
+        assert_eq!(
+            restored
+                .to_final_execution_outcome_with_receipts()
+                .receipts
+                .len(),
+            1
+        );
     }
-}
 
-impl<T> TryFrom<(String, T)> for BlockRecord
-where
-    T: ToPrimitive,
-{
-    type Error = anyhow::Error;
+    // Transaction with the given actions converted into the receipt `receipt_1`
+    // carrying the same actions
+    fn actions_transaction_details(actions: &serde_json::Value) -> TransactionDetails {
+        let mut receipt = receipt_json(hash("receipt_1"));
+        receipt["receipt"]["Action"]["actions"] = actions.clone();
+        let mut tx = transaction_details();
+        tx.transaction.actions = serde_json::from_value(actions.clone()).unwrap();
+        tx.add_receipt_and_outcome(
+            serde_json::from_value(receipt).unwrap(),
+            receipt_1_outcome(),
+        );
+        tx.to_final_transaction_result().unwrap()
+    }
+
+    fn assert_rendered_actions(rendered: &serde_json::Value, snapshot: &serde_json::Value) {
+        assert_eq!(&rendered["transaction"]["actions"], snapshot);
+        assert_eq!(
+            &rendered["receipts"][0]["receipt"]["Action"]["actions"],
+            snapshot
+        );
+    }
+
+    // Snapshots of the actions in the form nearcore renders them in the JSON RPC responses.
+    // Every action has to survive the stored formats and be returned exactly in this form
+    macro_rules! action_rendering_tests {
+        ($($name:ident => $action:expr),* $(,)?) => {
+            mod action_rendering {
+                use super::*;
+
+                $(
+                    #[test]
+                    fn $name() {
+                        let snapshot = serde_json::json!([$action]);
+                        let tx_details = actions_transaction_details(&snapshot);
 
-    fn try_from(value: (String, T)) -> Result<Self, Self::Error> {
-        let height = value
-            .1
-            .to_u64()
-            .ok_or_else(|| anyhow::anyhow!("Failed to parse `block_height` to u64"))?;
-        let hash = CryptoHash::from_str(&value.0).map_err(|err| {
-            anyhow::anyhow!("Failed to parse `block_hash` to CryptoHash: {}", err)
-        })?;
+                        let bytes = tx_details.tx_serialize().unwrap();
+                        let restored = TransactionDetails::tx_deserialize(&bytes).unwrap();
+                        assert_rendered_actions(&serde_json::to_value(&restored).unwrap(), &snapshot);
+                        assert_rendered_actions(
+                            &serde_json::to_value(restored.to_final_execution_outcome_with_receipts())
+                                .unwrap(),
+                            &snapshot,
+                        );
+
+                        let bytes = borsh::to_vec(&tx_details).unwrap();
+                        let restored: TransactionDetails = borsh::from_slice(&bytes).unwrap();
+                        assert_rendered_actions(&serde_json::to_value(&restored).unwrap(), &snapshot);
+                    }
+                )*
+            }
+        };
+    }
+
+    action_rendering_tests! {
+        create_account => "CreateAccount",
+        // Views keep the hash of the deployed code instead of the code itself
+        deploy_contract => serde_json::json!({"DeployContract": {
+            "code": "47DEQpj8HBSa+/TImW+5JCeuQeRkm5NMpJWZG3hSuFU=",
+        }}),
+        function_call => serde_json::json!({"FunctionCall": {
+            "method_name": "ft_transfer",
+            "args": "eyJhbW91bnQiOiIxIn0=",
+            "gas": 30000000000000_u64,
+            "deposit": "1",
+        }}),
+        transfer => serde_json::json!({"Transfer": {"deposit": "1000000000000000000000000"}}),
+        stake => serde_json::json!({"Stake": {
+            "stake": "1000000000000000000000000",
+            "public_key": PUBLIC_KEY,
+        }}),
+        add_full_access_key => serde_json::json!({"AddKey": {
+            "public_key": PUBLIC_KEY,
+            "access_key": {"nonce": 0, "permission": "FullAccess"},
+        }}),
+        add_function_call_key => serde_json::json!({"AddKey": {
+            "public_key": PUBLIC_KEY,
+            "access_key": {"nonce": 0, "permission": {"FunctionCall": {
+                "allowance": "250000000000000000000000",
+                "receiver_id": RECEIVER_ID,
+                "method_names": ["ft_transfer"],
+            }}},
+        }}),
+        add_unlimited_function_call_key => serde_json::json!({"AddKey": {
+            "public_key": PUBLIC_KEY,
+            "access_key": {"nonce": 0, "permission": {"FunctionCall": {
+                "allowance": null,
+                "receiver_id": RECEIVER_ID,
+                "method_names": [],
+            }}},
+        }}),
+        delete_key => serde_json::json!({"DeleteKey": {"public_key": PUBLIC_KEY}}),
+        delete_account => serde_json::json!({"DeleteAccount": {"beneficiary_id": SIGNER_ID}}),
+        // Inner actions of the delegate action are rendered as `Action`, not as `ActionView`,
+        // so the function call args stay base64 encoded bytes
+        delegate => serde_json::json!({"Delegate": {
+            "delegate_action": {
+                "sender_id": SIGNER_ID,
+                "receiver_id": RECEIVER_ID,
+                "actions": [
+                    {"Transfer": {"deposit": "1"}},
+                    {"FunctionCall": {
+                        "method_name": "ft_transfer",
+                        "args": "eyJhbW91bnQiOiIxIn0=",
+                        "gas": 30000000000000_u64,
+                        "deposit": "1",
+                    }},
+                ],
+                "nonce": 2,
+                "max_block_height": 100,
+                "public_key": PUBLIC_KEY,
+            },
+            "signature": SIGNATURE,
+        }}),
+    }
+
+    // Receipts created by `promise_yield_create` and resumed by `promise_yield_resume`
+    #[test]
+    fn test_promise_yield_and_resume_receipts_rendering() {
+        let mut yield_receipt = receipt_json(hash("receipt_1"));
+        yield_receipt["receipt"]["Action"]["is_promise_yield"] = true.into();
+        let resume_receipt = serde_json::json!({
+            "predecessor_id": RECEIVER_ID,
+            "receiver_id": RECEIVER_ID,
+            "receipt_id": hash("receipt_2").to_string(),
+            "receipt": {
+                "Data": {
+                    "data_id": hash("data").to_string(),
+                    "data": "AQID",
+                    "is_promise_resume": true,
+                }
+            },
+        });
+        let mut tx = transaction_details();
+        tx.add_receipt_and_outcome(
+            serde_json::from_value(yield_receipt.clone()).unwrap(),
+            receipt_1_outcome(),
+        );
+        tx.add_receipt_and_outcome(
+            serde_json::from_value(resume_receipt.clone()).unwrap(),
+            outcome(
+                hash("receipt_2"),
+                RECEIVER_ID,
+                serde_json::json!({"SuccessValue": ""}),
+                vec![],
+            ),
+        );
+        let tx_details = tx.to_final_transaction_result().unwrap();
+
+        let bytes = tx_details.tx_serialize().unwrap();
+        let json_restored = TransactionDetails::tx_deserialize(&bytes).unwrap();
+        let bytes = borsh::to_vec(&tx_details).unwrap();
+        let borsh_restored: TransactionDetails = borsh::from_slice(&bytes).unwrap();
+        for restored in [json_restored, borsh_restored] {
+            let rendered = serde_json::to_value(&restored).unwrap();
+            assert_eq!(rendered["receipts"][0]["receipt"], yield_receipt["receipt"]);
+            assert_eq!(
+                rendered["receipts"][1]["receipt"],
+                resume_receipt["receipt"]
+            );
+        }
+    }
 
-        Ok(BlockRecord { height, hash })
+    #[test]
+    fn network_identity_mismatch() {
+        let network = NetworkIdentity {
+            chain_id: "localnet".to_string(),
+            genesis_hash: hash("genesis"),
+        };
+        assert!(network.ensure_same_network(&network.clone()).is_ok());
+        // Localnets share the chain id, the genesis hash tells them apart
+        let other_localnet = NetworkIdentity {
+            genesis_hash: hash("other_genesis"),
+            ..network.clone()
+        };
+        assert!(network.ensure_same_network(&other_localnet).is_err());
+        let testnet = NetworkIdentity {
+            chain_id: "testnet".to_string(),
+            ..network.clone()
+        };
+        assert!(network.ensure_same_network(&testnet).is_err());
     }
 }
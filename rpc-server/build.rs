@@ -16,5 +16,9 @@ fn main() -> anyhow::Result<()> {
     let rustc_version = get_rustc_version()?;
     println!("cargo:rustc-env=RUSTC_VERSION={}", rustc_version);
 
+    // The gRPC service is generated from the protobuf definitions, requires `protoc`
+    #[cfg(feature = "grpc")]
+    tonic_build::compile_protos("proto/read_rpc.proto")?;
+
     Ok(())
 }
@@ -0,0 +1,234 @@
+/// Upper bounds of the block age buckets in seconds with their labels
+const BLOCK_AGE_BUCKETS: &[(u64, &str)] = &[
+    (60, "1m"),
+    (10 * 60, "10m"),
+    (60 * 60, "1h"),
+    (24 * 60 * 60, "1d"),
+    (7 * 24 * 60 * 60, "1w"),
+];
+const OLDER_BLOCKS_BUCKET: &str = "older";
+/// Number of the most queried accounts reported in the summary
+const TOP_ACCOUNTS: usize = 10;
+/// How often the summary of the sampled requests is logged
+pub(crate) const SUMMARY_INTERVAL: std::time::Duration = std::time::Duration::from_secs(600);
+
+#[derive(Debug, Default)]
+struct AccessStats {
+    methods: std::collections::HashMap<String, u64>,
+    block_ages: std::collections::HashMap<&'static str, u64>,
+    accounts: std::collections::HashMap<near_primitives::types::AccountId, u64>,
+}
+
+/// Samples the requests to find out which methods, accounts and blocks are queried.
+/// The methods and the block ages are exported as metrics, the accounts are only reported
+/// in the summary logged periodically to keep the cardinality of the metrics bounded.
+/// Used to size the hot caches and the retention windows by the real traffic
+pub struct AccessSampler {
+    /// Every n-th request is sampled
+    every_request: u64,
+    // The block reads and the account queries are counted separately, since a request
+    // may record both, and a shared counter would sample only one of them
+    block_requests: std::sync::atomic::AtomicU64,
+    account_requests: std::sync::atomic::AtomicU64,
+    stats: std::sync::Mutex<AccessStats>,
+}
+
+impl AccessSampler {
+    /// `rate` is the percentage of the sampled requests, from 0 to 100.
+    /// Returns `None` if the rate is 0, i.e. the sampling is disabled
+    pub fn new(rate: f64) -> Option<Self> {
+        if rate.is_nan() || rate <= 0.0 {
+            return None;
+        }
+        Some(Self {
+            every_request: (100.0 / rate.clamp(0.01, 100.0)) as u64,
+            block_requests: std::sync::atomic::AtomicU64::new(0),
+            account_requests: std::sync::atomic::AtomicU64::new(0),
+            stats: std::sync::Mutex::new(AccessStats::default()),
+        })
+    }
+
+    fn should_sample(&self, requests: &std::sync::atomic::AtomicU64) -> bool {
+        requests.fetch_add(1, std::sync::atomic::Ordering::Relaxed) % self.every_request == 0
+    }
+
+    /// Records the method reading the block produced at `block_timestamp` (nanoseconds)
+    pub(crate) fn record_block(&self, method_name: &str, block_timestamp: u64) {
+        if !self.should_sample(&self.block_requests) {
+            return;
+        }
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default();
+        let block_age = now
+            .saturating_sub(std::time::Duration::from_nanos(block_timestamp))
+            .as_secs();
+        let bucket = block_age_bucket(block_age);
+        crate::metrics::ACCESS_SAMPLES
+            .with_label_values(&[method_name, bucket])
+            .inc();
+        let mut stats = self.stats.lock().expect("Access stats lock is poisoned");
+        *stats.methods.entry(method_name.to_string()).or_default() += 1;
+        *stats.block_ages.entry(bucket).or_default() += 1;
+    }
+
+    /// Records the account queried by the method
+    pub(crate) fn record_account(&self, account_id: &near_primitives::types::AccountId) {
+        if !self.should_sample(&self.account_requests) {
+            return;
+        }
+        let mut stats = self.stats.lock().expect("Access stats lock is poisoned");
+        *stats.accounts.entry(account_id.clone()).or_default() += 1;
+    }
+
+    /// Logs the shares of the sampled methods, block ages and the most queried accounts
+    /// since the previous summary and starts collecting them again
+    pub(crate) fn log_summary(&self) {
+        let stats = std::mem::take(&mut *self.stats.lock().expect("Access stats lock is poisoned"));
+        let block_samples: u64 = stats.block_ages.values().sum();
+        if block_samples == 0 && stats.accounts.is_empty() {
+            return;
+        }
+        // The shares of the buckets are cumulative: the blocks younger than the bucket bound
+        let mut younger_samples = 0;
+        let block_ages = BLOCK_AGE_BUCKETS
+            .iter()
+            .map(|(_, label)| {
+                younger_samples += stats.block_ages.get(label).copied().unwrap_or_default();
+                format!("<{}: {}", label, percentage(younger_samples, block_samples))
+            })
+            .chain(std::iter::once(format!(
+                "{}: {}",
+                OLDER_BLOCKS_BUCKET,
+                percentage(
+                    stats
+                        .block_ages
+                        .get(OLDER_BLOCKS_BUCKET)
+                        .copied()
+                        .unwrap_or_default(),
+                    block_samples
+                )
+            )))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let mut methods = stats.methods.into_iter().collect::<Vec<_>>();
+        methods.sort_by(|a, b| b.1.cmp(&a.1));
+        let methods = methods
+            .iter()
+            .map(|(method_name, samples)| {
+                format!("{}: {}", method_name, percentage(*samples, block_samples))
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        let account_samples: u64 = stats.accounts.values().sum();
+        let mut accounts = stats.accounts.into_iter().collect::<Vec<_>>();
+        accounts.sort_by(|a, b| b.1.cmp(&a.1));
+        let top_accounts = accounts
+            .iter()
+            .take(TOP_ACCOUNTS)
+            .map(|(account_id, samples)| {
+                format!("{}: {}", account_id, percentage(*samples, account_samples))
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        tracing::info!(
+            "Access sampling summary of {} block reads and {} account queries. Block ages: [{}]. Methods: [{}]. Top accounts of {} queried: [{}]",
+            block_samples,
+            account_samples,
+            block_ages,
+            methods,
+            accounts.len(),
+            top_accounts,
+        );
+    }
+}
+
+/// Label of the bucket of the block age in seconds
+fn block_age_bucket(block_age: u64) -> &'static str {
+    BLOCK_AGE_BUCKETS
+        .iter()
+        .find(|(max_age, _)| block_age < *max_age)
+        .map_or(OLDER_BLOCKS_BUCKET, |(_, label)| label)
+}
+
+fn percentage(samples: u64, total: u64) -> String {
+    if total == 0 {
+        return "0%".to_string();
+    }
+    format!("{:.1}%", samples as f64 * 100.0 / total as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn now_nanos() -> u64 {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos() as u64
+    }
+
+    #[test]
+    fn test_zero_rate_disables_sampling() {
+        assert!(AccessSampler::new(0.0).is_none());
+        assert!(AccessSampler::new(-1.0).is_none());
+        assert!(AccessSampler::new(f64::NAN).is_none());
+        assert!(AccessSampler::new(0.01).is_some());
+    }
+
+    #[test]
+    fn test_rate_samples_every_nth_request() {
+        let access_sampler = AccessSampler::new(25.0).unwrap();
+        for _ in 0..8 {
+            access_sampler.record_block("block", now_nanos());
+        }
+        let stats = access_sampler.stats.lock().unwrap();
+        assert_eq!(stats.methods.get("block"), Some(&2));
+    }
+
+    #[test]
+    fn test_block_and_account_samples_do_not_alias() {
+        let access_sampler = AccessSampler::new(50.0).unwrap();
+        let account_id: near_primitives::types::AccountId = "test.near".parse().unwrap();
+        // Every query records both the block and the account
+        for _ in 0..4 {
+            access_sampler.record_block("query", now_nanos());
+            access_sampler.record_account(&account_id);
+        }
+        let stats = access_sampler.stats.lock().unwrap();
+        assert_eq!(stats.methods.get("query"), Some(&2));
+        assert_eq!(stats.accounts.get(&account_id), Some(&2));
+    }
+
+    #[test]
+    fn test_block_age_buckets() {
+        assert_eq!(block_age_bucket(0), "1m");
+        assert_eq!(block_age_bucket(59), "1m");
+        assert_eq!(block_age_bucket(60), "10m");
+        assert_eq!(block_age_bucket(60 * 60 - 1), "1h");
+        assert_eq!(block_age_bucket(24 * 60 * 60), "1w");
+        assert_eq!(block_age_bucket(7 * 24 * 60 * 60 - 1), "1w");
+        assert_eq!(block_age_bucket(7 * 24 * 60 * 60), OLDER_BLOCKS_BUCKET);
+    }
+
+    #[test]
+    fn test_recorded_block_age_bucket() {
+        let access_sampler = AccessSampler::new(100.0).unwrap();
+        access_sampler.record_block("block", now_nanos());
+        access_sampler.record_block("block", now_nanos() - 2 * 60 * 60 * 1_000_000_000);
+        access_sampler.record_block("block", 0);
+        let stats = access_sampler.stats.lock().unwrap();
+        assert_eq!(stats.block_ages.get("1m"), Some(&1));
+        assert_eq!(stats.block_ages.get("1d"), Some(&1));
+        assert_eq!(stats.block_ages.get(OLDER_BLOCKS_BUCKET), Some(&1));
+    }
+
+    #[test]
+    fn test_percentage() {
+        assert_eq!(percentage(0, 0), "0%");
+        assert_eq!(percentage(1, 3), "33.3%");
+        assert_eq!(percentage(1, 2), "50.0%");
+        assert_eq!(percentage(4, 4), "100.0%");
+    }
+}
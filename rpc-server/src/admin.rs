@@ -0,0 +1,265 @@
+use actix_web::{get, post, put};
+
+#[derive(Debug, serde::Serialize)]
+struct LogLevelResponse {
+    filter: String,
+}
+
+/// Applies the tracing filter directives (e.g. `database=debug,read_rpc_server=trace`)
+/// on top of the filter the server is started with. An empty body restores the startup filter
+#[put("/admin/log-level")]
+pub(crate) async fn put_log_level(
+    req: actix_web::HttpRequest,
+    body: String,
+    data: actix_web::web::Data<crate::config::ServerContext>,
+    log_level_handle: actix_web::web::Data<configuration::LogLevelHandle>,
+) -> actix_web::HttpResponse {
+    if let Err(response) = data.api_keys.authorize_admin(&req) {
+        return response;
+    }
+    match log_level_handle.set_directives(&body) {
+        Ok(filter) => {
+            tracing::warn!("Log filter is changed to `{}`", filter);
+            actix_web::HttpResponse::Ok().json(LogLevelResponse { filter })
+        }
+        Err(err) => crate::errors::error_response(
+            &req,
+            crate::errors::HttpErrorCode::InvalidRequest,
+            err.to_string(),
+        ),
+    }
+}
+
+/// Returns the tracing filter currently applied
+#[get("/admin/log-level")]
+pub(crate) async fn get_log_level(
+    req: actix_web::HttpRequest,
+    data: actix_web::web::Data<crate::config::ServerContext>,
+    log_level_handle: actix_web::web::Data<configuration::LogLevelHandle>,
+) -> actix_web::HttpResponse {
+    if let Err(response) = data.api_keys.authorize_admin(&req) {
+        return response;
+    }
+    match log_level_handle.current_filter() {
+        Ok(filter) => actix_web::HttpResponse::Ok().json(LogLevelResponse { filter }),
+        Err(err) => actix_web::HttpResponse::InternalServerError().body(err.to_string()),
+    }
+}
+
+/// Returns the methods enabled and disabled by the operator currently applied
+#[get("/admin/methods")]
+pub(crate) async fn get_methods(
+    req: actix_web::HttpRequest,
+    data: actix_web::web::Data<crate::config::ServerContext>,
+) -> actix_web::HttpResponse {
+    if let Err(response) = data.api_keys.authorize_admin(&req) {
+        return response;
+    }
+    actix_web::HttpResponse::Ok().json(data.method_access.rules())
+}
+
+/// Names of the caches the entries are invalidated in
+const INVALIDATED_CACHES: [&str; 6] = [
+    "blocks",
+    "block_hashes",
+    "prefetched_blocks",
+    "contract_code",
+    "compiled_contract_code",
+    "pending_nonces",
+];
+
+#[derive(Debug, serde::Deserialize)]
+struct CacheInvalidationRequest {
+    /// Glob-style pattern of the keys, `*` matches any sequence and `?` any single character
+    pattern: String,
+    /// Caches to invalidate the entries in, all the caches if not set
+    caches: Option<Vec<String>>,
+}
+
+#[derive(Debug, serde::Serialize)]
+struct CacheInvalidationResponse {
+    invalidated: std::collections::BTreeMap<&'static str, usize>,
+}
+
+/// Removes the cached entries with the keys matching the pattern from the in-memory caches
+/// and the Redis caches of the rpc-server, e.g. after the bad data of the contract is repaired.
+/// The blocks are keyed by the height, the block hashes and the contract codes by the hash,
+/// the pending nonces by `pending_nonce_<account_id>_<public_key>`
+#[post("/admin/cache/invalidate")]
+pub(crate) async fn invalidate_cache(
+    req: actix_web::HttpRequest,
+    request: actix_web::web::Json<CacheInvalidationRequest>,
+    data: actix_web::web::Data<crate::config::ServerContext>,
+) -> actix_web::HttpResponse {
+    if let Err(response) = data.api_keys.authorize_admin(&req) {
+        return response;
+    }
+    let CacheInvalidationRequest { pattern, caches } = request.into_inner();
+    if let Some(unknown_cache) = caches
+        .iter()
+        .flatten()
+        .find(|cache| !INVALIDATED_CACHES.contains(&cache.as_str()))
+    {
+        return crate::errors::error_response(
+            &req,
+            crate::errors::HttpErrorCode::InvalidRequest,
+            format!(
+                "Unknown cache `{}`, known caches: {}",
+                unknown_cache,
+                INVALIDATED_CACHES.join(", ")
+            ),
+        );
+    }
+    let is_invalidated = |cache_name: &str| {
+        caches.as_ref().map_or(true, |caches| {
+            caches.iter().any(|cache| cache == cache_name)
+        })
+    };
+    let mut invalidated = std::collections::BTreeMap::new();
+    if is_invalidated("blocks") {
+        let removed = data
+            .blocks_cache
+            .remove_matching(|key| glob_matches(&pattern, &key.to_string()))
+            .await;
+        invalidated.insert("blocks", removed);
+    }
+    if is_invalidated("block_hashes") {
+        let removed = data
+            .block_hashes_cache
+            .remove_matching(|key| glob_matches(&pattern, &key.to_string()))
+            .await;
+        invalidated.insert("block_hashes", removed);
+    }
+    if is_invalidated("prefetched_blocks") {
+        let removed = data
+            .block_prefetcher
+            .invalidate(|key| glob_matches(&pattern, &key.to_string()))
+            .await;
+        invalidated.insert("prefetched_blocks", removed);
+    }
+    if is_invalidated("contract_code") {
+        let removed = data
+            .contract_code_cache
+            .remove_matching(|key| glob_matches(&pattern, &key.to_string()))
+            .await;
+        invalidated.insert("contract_code", removed);
+    }
+    if is_invalidated("compiled_contract_code") {
+        let removed = data
+            .compiled_contract_code_cache
+            .local_cache
+            .remove_matching(|key| glob_matches(&pattern, &key.to_string()))
+            .await;
+        invalidated.insert("compiled_contract_code", removed);
+    }
+    let mut redis_error = None;
+    if is_invalidated("pending_nonces") {
+        if let Some(pending_nonces_cache) = &data.pending_nonces_cache {
+            // Redis applies the same glob-style pattern to its keys
+            match pending_nonces_cache.invalidate(&pattern).await {
+                Ok(removed) => {
+                    invalidated.insert("pending_nonces", removed);
+                }
+                Err(err) => redis_error = Some(err),
+            }
+        }
+    }
+
+    tracing::warn!(
+        target: crate::api_keys::AUDIT,
+        pattern = pattern.as_str(),
+        invalidated = ?invalidated,
+        remote_addr = req.connection_info().realip_remote_addr(),
+        "Cache entries are invalidated"
+    );
+    if let Some(err) = redis_error {
+        tracing::error!("Failed to invalidate pending nonces: {:?}", err);
+        return actix_web::HttpResponse::InternalServerError()
+            .body(format!("Failed to invalidate pending nonces: {}", err));
+    }
+    actix_web::HttpResponse::Ok().json(CacheInvalidationResponse { invalidated })
+}
+
+// Redis-compatible glob matching of the `*` and `?` wildcards.
+// The character classes of Redis are not supported
+fn glob_matches(pattern: &str, key: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let key: Vec<char> = key.chars().collect();
+    let (mut pattern_index, mut key_index) = (0, 0);
+    // Position of the last `*` in the pattern and of the key it is matched from,
+    // to backtrack when the rest of the pattern doesn't match
+    let mut backtrack: Option<(usize, usize)> = None;
+    while key_index < key.len() {
+        match pattern.get(pattern_index) {
+            Some('*') => {
+                backtrack = Some((pattern_index, key_index));
+                pattern_index += 1;
+            }
+            Some(&pattern_char) if pattern_char == '?' || pattern_char == key[key_index] => {
+                pattern_index += 1;
+                key_index += 1;
+            }
+            _ => match backtrack {
+                Some((star_index, star_key_index)) => {
+                    pattern_index = star_index + 1;
+                    key_index = star_key_index + 1;
+                    backtrack = Some((star_index, star_key_index + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[pattern_index..]
+        .iter()
+        .all(|pattern_char| *pattern_char == '*')
+}
+
+/// Registers the profiling endpoints, allowed only for the admin API keys
+/// as the `/admin/*` ones
+#[cfg(feature = "profiling")]
+pub(crate) fn configure_profiling(config: &mut actix_web::web::ServiceConfig) {
+    config
+        .service(cpu_profile)
+        .service(heap_profile)
+        .service(jemalloc_stats);
+}
+
+/// CPU profile of the server in the pprof format, see the `profiling` crate
+#[cfg(feature = "profiling")]
+#[get("/debug/pprof/profile")]
+async fn cpu_profile(
+    req: actix_web::HttpRequest,
+    query: actix_web::web::Query<profiling::ProfileQuery>,
+    data: actix_web::web::Data<crate::config::ServerContext>,
+) -> actix_web::HttpResponse {
+    if let Err(response) = data.api_keys.authorize_admin(&req) {
+        return response;
+    }
+    profiling::cpu_profile(query.into_inner()).await
+}
+
+/// Heap profile of the server in the pprof format
+#[cfg(feature = "profiling")]
+#[get("/debug/pprof/heap")]
+async fn heap_profile(
+    req: actix_web::HttpRequest,
+    data: actix_web::web::Data<crate::config::ServerContext>,
+) -> actix_web::HttpResponse {
+    if let Err(response) = data.api_keys.authorize_admin(&req) {
+        return response;
+    }
+    profiling::heap_profile().await
+}
+
+/// Memory stats of jemalloc
+#[cfg(feature = "profiling")]
+#[get("/debug/jemalloc/stats")]
+async fn jemalloc_stats(
+    req: actix_web::HttpRequest,
+    data: actix_web::web::Data<crate::config::ServerContext>,
+) -> actix_web::HttpResponse {
+    if let Err(response) = data.api_keys.authorize_admin(&req) {
+        return response;
+    }
+    profiling::jemalloc_stats()
+}
@@ -0,0 +1,233 @@
+/// Header with the API key of the client
+pub(crate) const API_KEY_HEADER: &str = "x-api-key";
+// Logging target of the access decisions and the admin actions,
+// lets operators route them to a separate sink
+pub(crate) const AUDIT: &str = "api_keys_audit";
+/// Method name the admin endpoints are authorized with by the API keys
+pub(crate) const ADMIN_METHOD: &str = "admin";
+
+struct ApiKey {
+    name: String,
+    // `None` means all the methods are allowed
+    allowed_methods: Option<std::collections::HashSet<String>>,
+}
+
+/// API keys of the rpc-server clients with the methods allowed for every key.
+/// The method is known only after the JSON-RPC request is parsed,
+/// so the access is checked by the rpc handler before the method is dispatched
+pub struct ApiKeys {
+    keys: std::collections::HashMap<String, ApiKey>,
+    require_api_key: bool,
+}
+
+impl ApiKeys {
+    pub fn new(api_keys: &[configuration::ApiKeyConfig], require_api_key: bool) -> Self {
+        let keys = api_keys
+            .iter()
+            .map(|api_key| {
+                (
+                    api_key.key.clone(),
+                    ApiKey {
+                        name: api_key.name.clone(),
+                        allowed_methods: api_key
+                            .allowed_methods
+                            .as_ref()
+                            .map(|methods| methods.iter().cloned().collect()),
+                    },
+                )
+            })
+            .collect();
+        Self {
+            keys,
+            require_api_key,
+        }
+    }
+
+    /// The requests without the API key are rejected
+    pub(crate) fn is_required(&self) -> bool {
+        self.require_api_key
+    }
+
+    /// Checks if the request with the given API key is allowed to call the method.
+    /// Every decision about a request with the API key is logged for audit
+    pub(crate) fn authorize(
+        &self,
+        req: &actix_web::HttpRequest,
+        method_name: &str,
+    ) -> Result<(), actix_web::HttpResponse> {
+        let key = req
+            .headers()
+            .get(API_KEY_HEADER)
+            .and_then(|value| value.to_str().ok());
+        self.check(key, method_name)
+            .map_err(|(code, message)| crate::errors::error_response(req, code, message))
+    }
+
+    /// Checks if the request is allowed to call the admin endpoints.
+    /// They change the server state and expose its internals, so the API key is required
+    /// even if `require_api_key` is off, and only the keys listing `admin`
+    /// in `allowed_methods` are allowed
+    pub(crate) fn authorize_admin(
+        &self,
+        req: &actix_web::HttpRequest,
+    ) -> Result<(), actix_web::HttpResponse> {
+        let key = req
+            .headers()
+            .get(API_KEY_HEADER)
+            .and_then(|value| value.to_str().ok());
+        self.check_admin(key)
+            .map_err(|(code, message)| crate::errors::error_response(req, code, message))
+    }
+
+    fn check_admin(&self, key: Option<&str>) -> Result<(), (crate::errors::HttpErrorCode, String)> {
+        let Some(key) = key else {
+            tracing::warn!(
+                target: AUDIT,
+                method_name = ADMIN_METHOD,
+                "Admin request without API key is denied"
+            );
+            return Err((
+                crate::errors::HttpErrorCode::Unauthorized,
+                format!(
+                    "Admin API key is required in the `{}` header",
+                    API_KEY_HEADER
+                ),
+            ));
+        };
+        if let Some(api_key) = self.keys.get(key) {
+            let is_admin = api_key
+                .allowed_methods
+                .as_ref()
+                .map_or(false, |allowed_methods| {
+                    allowed_methods.contains(ADMIN_METHOD)
+                });
+            if !is_admin {
+                tracing::warn!(
+                    target: AUDIT,
+                    api_key = api_key.name,
+                    method_name = ADMIN_METHOD,
+                    "API key without admin access is denied"
+                );
+                return Err((
+                    crate::errors::HttpErrorCode::MethodNotAllowed,
+                    format!(
+                        "API key `{}` doesn't list `{}` in `allowed_methods`",
+                        api_key.name, ADMIN_METHOD
+                    ),
+                ));
+            }
+        }
+        self.check(Some(key), ADMIN_METHOD)
+    }
+
+    /// Checks the access of the API key to the method regardless of the transport,
+    /// the denial is returned with the error code and the message for the client
+    pub(crate) fn check(
+        &self,
+        key: Option<&str>,
+        method_name: &str,
+    ) -> Result<(), (crate::errors::HttpErrorCode, String)> {
+        let Some(key) = key else {
+            if self.require_api_key {
+                tracing::warn!(
+                    target: AUDIT,
+                    method_name,
+                    "Request without API key is denied"
+                );
+                return Err((
+                    crate::errors::HttpErrorCode::Unauthorized,
+                    format!("API key is required in the `{}` header", API_KEY_HEADER),
+                ));
+            }
+            return Ok(());
+        };
+        let Some(api_key) = self.keys.get(key) else {
+            tracing::warn!(
+                target: AUDIT,
+                method_name,
+                "Request with unknown API key is denied"
+            );
+            return Err((
+                crate::errors::HttpErrorCode::Unauthorized,
+                "API key is unknown".to_string(),
+            ));
+        };
+        let allowed = api_key
+            .allowed_methods
+            .as_ref()
+            .map_or(true, |allowed_methods| {
+                allowed_methods.contains(method_name)
+            });
+        if !allowed {
+            tracing::warn!(
+                target: AUDIT,
+                api_key = api_key.name,
+                method_name,
+                "Method is not allowed for API key"
+            );
+            return Err((
+                crate::errors::HttpErrorCode::MethodNotAllowed,
+                format!(
+                    "Method `{}` is not allowed for API key `{}`",
+                    method_name, api_key.name
+                ),
+            ));
+        }
+        tracing::info!(
+            target: AUDIT,
+            api_key = api_key.name,
+            method_name,
+            "Request is allowed"
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn api_keys(require_api_key: bool) -> ApiKeys {
+        ApiKeys::new(
+            &[
+                configuration::ApiKeyConfig {
+                    name: "ops".to_string(),
+                    key: "ops-key".to_string(),
+                    allowed_methods: Some(vec![ADMIN_METHOD.to_string()]),
+                },
+                configuration::ApiKeyConfig {
+                    name: "client".to_string(),
+                    key: "client-key".to_string(),
+                    allowed_methods: None,
+                },
+            ],
+            require_api_key,
+        )
+    }
+
+    #[test]
+    fn test_admin_requires_api_key_when_keys_are_optional() {
+        let api_keys = api_keys(false);
+        assert!(api_keys.check(None, "block").is_ok());
+        assert!(matches!(
+            api_keys.check_admin(None),
+            Err((crate::errors::HttpErrorCode::Unauthorized, _))
+        ));
+        assert!(matches!(
+            api_keys.check_admin(Some("unknown-key")),
+            Err((crate::errors::HttpErrorCode::Unauthorized, _))
+        ));
+    }
+
+    #[test]
+    fn test_admin_is_allowed_only_for_keys_listing_it() {
+        let api_keys = api_keys(false);
+        assert!(api_keys.check_admin(Some("ops-key")).is_ok());
+        // The key allowed to call all the methods is not an admin key
+        assert!(api_keys.check(Some("client-key"), ADMIN_METHOD).is_ok());
+        assert!(matches!(
+            api_keys.check_admin(Some("client-key")),
+            Err((crate::errors::HttpErrorCode::MethodNotAllowed, _))
+        ));
+    }
+}
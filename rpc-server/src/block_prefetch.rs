@@ -0,0 +1,292 @@
+use futures::StreamExt;
+
+/// Header with the direction of the blocks scan of the client: `forward` or `backward`.
+/// Lets the client ask for the prefetching before the sequential access is detected
+pub(crate) const PREFETCH_HINT_HEADER: &str = "x-prefetch-hint";
+// Max number of the clients whose last requested block heights are tracked
+const TRACKED_CLIENTS: Option<std::num::NonZeroUsize> = std::num::NonZeroUsize::new(10_000);
+// Max number of the blocks being fetched at once for all the clients.
+// The scans started over the limit are not prefetched, the blocks are served from the lake
+const MAX_IN_FLIGHT_BLOCKS: usize = 100;
+// Max number of the blocks fetched at once by the single scan
+const PREFETCH_CONCURRENCY: usize = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ScanDirection {
+    Forward,
+    Backward,
+}
+
+/// Prefetcher of the blocks for the clients scanning the blocks by height one by one,
+/// e.g. explorers scrolling the blocks pages.
+/// When the client requests the block next to the previously requested one (or sends
+/// the `x-prefetch-hint` header), the next blocks in the same direction are fetched
+/// from the lake in the background and served by the `block` method from the memory
+pub struct BlockPrefetcher {
+    prefetch_count: u64,
+    blocks_cache: crate::cache::RwLockLruMemoryCache<u64, near_primitives::views::BlockView>,
+    // Last block height requested by the client, the clients are identified
+    // by the API key or the IP address
+    last_block_heights: std::sync::Mutex<lru::LruCache<String, u64>>,
+    // Blocks being fetched, so the overlapping scans don't fetch the same blocks twice
+    in_flight: std::sync::Mutex<std::collections::HashSet<u64>>,
+}
+
+impl BlockPrefetcher {
+    pub fn new(
+        prefetch_count: u64,
+        cache_size_in_bytes: usize,
+        memory_budget: std::sync::Arc<crate::cache::MemoryBudget>,
+    ) -> Self {
+        Self {
+            prefetch_count,
+            blocks_cache: crate::cache::RwLockLruMemoryCache::new(
+                cache_size_in_bytes,
+                "prefetched_blocks",
+                memory_budget,
+            ),
+            last_block_heights: std::sync::Mutex::new(lru::LruCache::new(TRACKED_CLIENTS.unwrap())),
+            in_flight: std::sync::Mutex::new(std::collections::HashSet::new()),
+        }
+    }
+
+    /// Returns the prefetched block
+    pub async fn get(
+        &self,
+        block_height: near_primitives::types::BlockHeight,
+    ) -> Option<near_primitives::views::BlockView> {
+        if self.prefetch_count == 0 {
+            return None;
+        }
+        let block_view = self.blocks_cache.get(&block_height).await;
+        if block_view.is_some() {
+            crate::metrics::PREFETCHED_BLOCKS
+                .with_label_values(&["hit"])
+                .inc();
+        }
+        block_view
+    }
+
+    /// Removes the prefetched blocks with the heights matching the predicate
+    pub async fn invalidate(
+        &self,
+        predicate: impl FnMut(&near_primitives::types::BlockHeight) -> bool,
+    ) -> usize {
+        self.blocks_cache.remove_matching(predicate).await
+    }
+
+    // Remembers the requested block height of the client and returns the direction
+    // of the scan if the block is next to the previously requested one
+    fn scan_direction(
+        &self,
+        client: String,
+        block_height: near_primitives::types::BlockHeight,
+    ) -> Option<ScanDirection> {
+        let previous_block_height = self
+            .last_block_heights
+            .lock()
+            .expect("last block heights lock poisoned")
+            .put(client, block_height)?;
+        if block_height == previous_block_height + 1 {
+            Some(ScanDirection::Forward)
+        } else if block_height + 1 == previous_block_height {
+            Some(ScanDirection::Backward)
+        } else {
+            None
+        }
+    }
+
+    // Marks the blocks as being fetched, returns only the blocks which are not fetched yet.
+    // The blocks over `MAX_IN_FLIGHT_BLOCKS` are skipped, the next request of the scan
+    // prefetches them once the fetched ones are done
+    fn start_fetching(
+        &self,
+        block_heights: Vec<near_primitives::types::BlockHeight>,
+    ) -> Vec<near_primitives::types::BlockHeight> {
+        let mut in_flight = self.in_flight.lock().expect("in flight lock poisoned");
+        let mut started = vec![];
+        for block_height in block_heights {
+            if in_flight.len() >= MAX_IN_FLIGHT_BLOCKS {
+                crate::metrics::PREFETCHED_BLOCKS
+                    .with_label_values(&["skipped"])
+                    .inc();
+                continue;
+            }
+            if in_flight.insert(block_height) {
+                started.push(block_height);
+            }
+        }
+        started
+    }
+
+    fn is_fetching(&self, block_height: near_primitives::types::BlockHeight) -> bool {
+        self.in_flight
+            .lock()
+            .expect("in flight lock poisoned")
+            .contains(&block_height)
+    }
+
+    fn finish_fetching(&self, block_height: near_primitives::types::BlockHeight) {
+        self.in_flight
+            .lock()
+            .expect("in flight lock poisoned")
+            .remove(&block_height);
+    }
+}
+
+/// Starts prefetching the next blocks if the `block` request continues the sequential scan
+/// of the client. Only the requests by the block height are taken into account
+pub(crate) async fn observe_block_request(
+    data: &actix_web::web::Data<crate::config::ServerContext>,
+    req: &actix_web::HttpRequest,
+    params: &serde_json::Value,
+) {
+    if data.block_prefetcher.prefetch_count == 0 {
+        return;
+    }
+    // The block height is passed as `{"block_id": <height>}` or as `[<height>]`
+    let Some(block_height) = params
+        .get("block_id")
+        .or_else(|| params.get(0))
+        .and_then(serde_json::Value::as_u64)
+    else {
+        return;
+    };
+    let client = req
+        .headers()
+        .get(crate::api_keys::API_KEY_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string)
+        .or_else(|| {
+            req.connection_info()
+                .realip_remote_addr()
+                .map(str::to_string)
+        });
+    let hint = req
+        .headers()
+        .get(PREFETCH_HINT_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| match value.trim() {
+            "forward" => Some(ScanDirection::Forward),
+            "backward" => Some(ScanDirection::Backward),
+            _ => None,
+        });
+    let detected_direction =
+        client.and_then(|client| data.block_prefetcher.scan_direction(client, block_height));
+    let Some(direction) = hint.or(detected_direction) else {
+        return;
+    };
+
+    let prefetch_count = data.block_prefetcher.prefetch_count;
+    let block_heights: Vec<_> = match direction {
+        ScanDirection::Forward => {
+            let final_block_height = data
+                .blocks_info_by_finality
+                .final_cache_block()
+                .await
+                .block_height;
+            (block_height + 1..=final_block_height)
+                .take(prefetch_count as usize)
+                .collect()
+        }
+        ScanDirection::Backward => {
+            let genesis_block_height = data.genesis_info.genesis_block_cache.block_height;
+            (genesis_block_height..block_height)
+                .rev()
+                .take(prefetch_count as usize)
+                .collect()
+        }
+    };
+    let mut not_cached_block_heights = vec![];
+    for block_height in block_heights {
+        if !data.block_prefetcher.is_fetching(block_height)
+            && !data
+                .block_prefetcher
+                .blocks_cache
+                .contains(&block_height)
+                .await
+        {
+            not_cached_block_heights.push(block_height);
+        }
+    }
+    let block_heights = data
+        .block_prefetcher
+        .start_fetching(not_cached_block_heights);
+    if !block_heights.is_empty() {
+        tokio::spawn(prefetch_blocks(data.clone(), block_heights));
+    }
+}
+
+// Fetches the blocks from the lake into the prefetched blocks cache.
+// The skipped heights are not found and ignored
+async fn prefetch_blocks(
+    data: actix_web::web::Data<crate::config::ServerContext>,
+    block_heights: Vec<near_primitives::types::BlockHeight>,
+) {
+    let data = &data;
+    futures::stream::iter(block_heights)
+        .for_each_concurrent(PREFETCH_CONCURRENCY, |block_height| async move {
+            match near_lake_framework::s3_fetchers::fetch_block(
+                &data.s3_client,
+                &data.s3_bucket_name,
+                block_height,
+            )
+            .await
+            {
+                Ok(block_view) => {
+                    data.block_prefetcher
+                        .blocks_cache
+                        .put(block_height, block_view)
+                        .await;
+                    crate::metrics::PREFETCHED_BLOCKS
+                        .with_label_values(&["fetched"])
+                        .inc();
+                }
+                Err(err) => {
+                    tracing::debug!("Failed to prefetch block {}: {}", block_height, err);
+                    crate::metrics::PREFETCHED_BLOCKS
+                        .with_label_values(&["failed"])
+                        .inc();
+                }
+            }
+            data.block_prefetcher.finish_fetching(block_height);
+        })
+        .await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn prefetcher() -> BlockPrefetcher {
+        BlockPrefetcher::new(
+            10,
+            1_000_000,
+            std::sync::Arc::new(crate::cache::MemoryBudget::new(1_000_000)),
+        )
+    }
+
+    #[test]
+    fn test_blocks_in_flight_are_fetched_once() {
+        let prefetcher = prefetcher();
+        assert_eq!(prefetcher.start_fetching(vec![1, 2, 3]), vec![1, 2, 3]);
+        // The overlapping scan fetches only the new blocks
+        assert_eq!(prefetcher.start_fetching(vec![2, 3, 4]), vec![4]);
+        prefetcher.finish_fetching(2);
+        assert!(!prefetcher.is_fetching(2));
+        assert_eq!(prefetcher.start_fetching(vec![2, 3]), vec![2]);
+    }
+
+    #[test]
+    fn test_blocks_in_flight_are_bounded() {
+        let prefetcher = prefetcher();
+        let block_heights = (0..MAX_IN_FLIGHT_BLOCKS as u64 + 10).collect();
+        assert_eq!(
+            prefetcher.start_fetching(block_heights).len(),
+            MAX_IN_FLIGHT_BLOCKS
+        );
+        assert!(prefetcher.start_fetching(vec![1_000]).is_empty());
+        prefetcher.finish_fetching(0);
+        assert_eq!(prefetcher.start_fetching(vec![1_000]), vec![1_000]);
+    }
+}
@@ -2,31 +2,205 @@
 
 const INITIAL_CAPACITY: Option<std::num::NonZeroUsize> = std::num::NonZeroUsize::new(10);
 
+/// Approximate amount of memory used by a cached value in bytes.
+/// `std::mem::size_of_val` only counts the stack part of the value,
+/// so values owning heap allocations have to report them explicitly.
+pub trait ApproximateSize {
+    fn approximate_size(&self) -> usize;
+}
+
+impl ApproximateSize for Vec<u8> {
+    fn approximate_size(&self) -> usize {
+        std::mem::size_of_val(self) + self.capacity()
+    }
+}
+
+impl ApproximateSize for crate::modules::blocks::CacheBlock {
+    fn approximate_size(&self) -> usize {
+        std::mem::size_of_val(self)
+    }
+}
+
+impl ApproximateSize for crate::modules::blocks::BlockHeightByHash {
+    fn approximate_size(&self) -> usize {
+        std::mem::size_of_val(self) + std::mem::size_of::<near_primitives::hash::CryptoHash>()
+    }
+}
+
+impl ApproximateSize for near_primitives::views::BlockView {
+    // The approvals and the chunk headers make up the most of the block,
+    // the validator proposals are rare and not counted
+    fn approximate_size(&self) -> usize {
+        std::mem::size_of_val(self)
+            + self.author.len()
+            + self.header.approvals.capacity()
+                * std::mem::size_of::<Option<Box<near_crypto::Signature>>>()
+            + self.header.approvals.iter().flatten().count()
+                * std::mem::size_of::<near_crypto::Signature>()
+            + self.chunks.capacity()
+                * std::mem::size_of::<near_primitives::views::ChunkHeaderView>()
+    }
+}
+
+impl ApproximateSize for near_vm_runner::CompiledContractInfo {
+    fn approximate_size(&self) -> usize {
+        let compiled_size = match &self.compiled {
+            near_vm_runner::CompiledContract::Code(code) => code.capacity(),
+            near_vm_runner::CompiledContract::CompileModuleError(_) => 0,
+        };
+        std::mem::size_of_val(self) + compiled_size
+    }
+}
+
+#[derive(Debug)]
+struct CacheMemoryUsage {
+    current_size: usize,
+    weight: usize,
+}
+
+/// Central memory budget shared by all in-memory caches.
+/// Every registered cache reports its approximate size in bytes.
+/// When the total size exceeds the global cap, the caches which use more than
+/// their weighted share of the cap are asked to evict their least recently used values.
+#[derive(Debug)]
+pub struct MemoryBudget {
+    max_size: usize,
+    caches: std::sync::Mutex<std::collections::HashMap<&'static str, CacheMemoryUsage>>,
+}
+
+impl MemoryBudget {
+    /// Create a new budget with a global cap in bytes.
+    pub fn new(max_size: usize) -> Self {
+        crate::metrics::CACHE_MEMORY_BUDGET_BYTES.set(max_size as i64);
+        Self {
+            max_size,
+            caches: std::sync::Mutex::new(std::collections::HashMap::new()),
+        }
+    }
+
+    /// Register cache in the budget.
+    /// The weight defines the share of the global cap the cache is allowed to keep under pressure.
+    fn register(&self, cache_name: &'static str, weight: usize) {
+        self.caches
+            .lock()
+            .expect("memory budget lock poisoned")
+            .insert(
+                cache_name,
+                CacheMemoryUsage {
+                    current_size: 0,
+                    weight,
+                },
+            );
+        crate::metrics::CACHE_MEMORY_USAGE_BYTES
+            .with_label_values(&[cache_name])
+            .set(0);
+    }
+
+    /// Store the current size of the cache and return how many bytes
+    /// the cache has to evict to fit into its weighted share of the global cap.
+    fn update(&self, cache_name: &'static str, current_size: usize) -> usize {
+        crate::metrics::CACHE_MEMORY_USAGE_BYTES
+            .with_label_values(&[cache_name])
+            .set(current_size as i64);
+
+        let mut caches = self.caches.lock().expect("memory budget lock poisoned");
+        let weight = match caches.get_mut(cache_name) {
+            Some(usage) => {
+                usage.current_size = current_size;
+                usage.weight
+            }
+            None => return 0,
+        };
+        let total_size: usize = caches.values().map(|usage| usage.current_size).sum();
+        if total_size <= self.max_size {
+            return 0;
+        }
+        let total_weight: usize = caches.values().map(|usage| usage.weight).sum();
+        let weighted_share =
+            (self.max_size as u128 * weight as u128 / total_weight.max(1) as u128) as usize;
+        if current_size <= weighted_share {
+            return 0;
+        }
+        (current_size - weighted_share).min(total_size - self.max_size)
+    }
+
+    /// Global cap in bytes.
+    pub fn max_size(&self) -> usize {
+        self.max_size
+    }
+
+    /// Sum of approximate sizes of all registered caches in bytes.
+    pub fn current_size(&self) -> usize {
+        self.caches
+            .lock()
+            .expect("memory budget lock poisoned")
+            .values()
+            .map(|usage| usage.current_size)
+            .sum()
+    }
+}
+
 /// An LRU-cache which operates on memory used.
 pub struct LruMemoryCache<K, V> {
     inner: lru::LruCache<K, V>,
     current_size: usize,
     max_size: usize,
+    memory_budget: Option<(&'static str, std::sync::Arc<MemoryBudget>)>,
 }
 
-impl<K: std::hash::Hash + Eq, V> LruMemoryCache<K, V> {
+impl<K: std::hash::Hash + Eq, V: ApproximateSize> LruMemoryCache<K, V> {
     /// Create a new cache with a maximum memory size of values.
     pub fn new(max_size: usize) -> Self {
         LruMemoryCache {
             inner: lru::LruCache::new(INITIAL_CAPACITY.unwrap()),
             current_size: 0,
             max_size,
+            memory_budget: None,
         }
     }
 
+    /// Register the cache in the shared memory budget.
+    /// The cache max size is used as its weight in the budget.
+    pub fn with_memory_budget(
+        mut self,
+        cache_name: &'static str,
+        memory_budget: std::sync::Arc<MemoryBudget>,
+    ) -> Self {
+        memory_budget.register(cache_name, self.max_size);
+        self.memory_budget = Some((cache_name, memory_budget));
+        self
+    }
+
     /// Remove elements until we are below the memory target.
     fn decrease(&mut self) {
         while self.current_size > self.max_size {
-            match self.inner.pop_lru() {
-                Some((_, v)) => self.current_size -= std::mem::size_of_val(&v),
-                _ => break,
+            if !self.pop_lru() {
+                break;
             }
         }
+        if let Some((cache_name, memory_budget)) = self.memory_budget.clone() {
+            let mut bytes_to_evict = memory_budget.update(cache_name, self.current_size);
+            while bytes_to_evict > 0 {
+                let size_before = self.current_size;
+                if !self.pop_lru() {
+                    break;
+                }
+                bytes_to_evict = bytes_to_evict.saturating_sub(size_before - self.current_size);
+            }
+            memory_budget.update(cache_name, self.current_size);
+        }
+    }
+
+    /// Remove the least recently used element.
+    /// Returns false if the cache is empty.
+    fn pop_lru(&mut self) -> bool {
+        match self.inner.pop_lru() {
+            Some((_, v)) => {
+                self.current_size -= v.approximate_size();
+                true
+            }
+            None => false,
+        }
     }
 
     /// Puts a key-value pair into cache.
@@ -43,11 +217,11 @@ impl<K: std::hash::Hash + Eq, V> LruMemoryCache<K, V> {
             self.inner.resize(new_cap);
         }
 
-        self.current_size += std::mem::size_of_val(&val);
+        self.current_size += val.approximate_size();
 
         // subtract any element displaced from the hash.
         if let Some(lru) = self.inner.put(key, val) {
-            self.current_size -= std::mem::size_of_val(&lru);
+            self.current_size -= lru.approximate_size();
         }
 
         self.decrease();
@@ -78,6 +252,48 @@ impl<K: std::hash::Hash + Eq, V> LruMemoryCache<K, V> {
     pub fn len(&self) -> usize {
         self.inner.len()
     }
+
+    /// Removes the values not matching the predicate.
+    /// Returns the number of removed values.
+    pub fn retain(&mut self, mut predicate: impl FnMut(&V) -> bool) -> usize
+    where
+        K: Clone,
+    {
+        let keys_to_remove = self
+            .inner
+            .iter()
+            .filter(|(_, val)| !predicate(val))
+            .map(|(key, _)| key.clone())
+            .collect::<Vec<_>>();
+        self.remove_keys(&keys_to_remove)
+    }
+
+    /// Removes the values with the keys matching the predicate.
+    /// Returns the number of removed values.
+    pub fn remove_matching(&mut self, mut predicate: impl FnMut(&K) -> bool) -> usize
+    where
+        K: Clone,
+    {
+        let keys_to_remove = self
+            .inner
+            .iter()
+            .filter(|(key, _)| predicate(key))
+            .map(|(key, _)| key.clone())
+            .collect::<Vec<_>>();
+        self.remove_keys(&keys_to_remove)
+    }
+
+    fn remove_keys(&mut self, keys_to_remove: &[K]) -> usize {
+        for key in keys_to_remove {
+            if let Some(val) = self.inner.pop(key) {
+                self.current_size -= val.approximate_size();
+            }
+        }
+        if let Some((cache_name, memory_budget)) = &self.memory_budget {
+            memory_budget.update(cache_name, self.current_size);
+        }
+        keys_to_remove.len()
+    }
 }
 
 // Wrapper RwLock around LruMemoryCache that provides async access to the cache.
@@ -86,10 +302,16 @@ pub struct RwLockLruMemoryCache<K, V> {
     inner: futures_locks::RwLock<LruMemoryCache<K, V>>,
 }
 
-impl<K: std::hash::Hash + Eq, V: Clone> RwLockLruMemoryCache<K, V> {
-    pub fn new(max_size: usize) -> Self {
+impl<K: std::hash::Hash + Eq, V: Clone + ApproximateSize> RwLockLruMemoryCache<K, V> {
+    pub fn new(
+        max_size: usize,
+        cache_name: &'static str,
+        memory_budget: std::sync::Arc<MemoryBudget>,
+    ) -> Self {
         RwLockLruMemoryCache {
-            inner: futures_locks::RwLock::new(LruMemoryCache::new(max_size)),
+            inner: futures_locks::RwLock::new(
+                LruMemoryCache::new(max_size).with_memory_budget(cache_name, memory_budget),
+            ),
         }
     }
 
@@ -101,7 +323,6 @@ impl<K: std::hash::Hash + Eq, V: Clone> RwLockLruMemoryCache<K, V> {
         self.inner.read().await.get(key).cloned()
     }
 
-    #[allow(unused)]
     pub async fn contains(&self, key: &K) -> bool {
         self.inner.read().await.contains(key)
     }
@@ -117,4 +338,107 @@ impl<K: std::hash::Hash + Eq, V: Clone> RwLockLruMemoryCache<K, V> {
     pub async fn len(&self) -> usize {
         self.inner.read().await.len()
     }
+
+    pub async fn retain(&self, predicate: impl FnMut(&V) -> bool) -> usize
+    where
+        K: Clone,
+    {
+        self.inner.write().await.retain(predicate)
+    }
+
+    pub async fn remove_matching(&self, predicate: impl FnMut(&K) -> bool) -> usize
+    where
+        K: Clone,
+    {
+        self.inner.write().await.remove_matching(predicate)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // `Vec<u8>` of 76 bytes takes 100 bytes with its stack part
+    fn value() -> Vec<u8> {
+        vec![0u8; 100 - std::mem::size_of::<Vec<u8>>()]
+    }
+
+    #[test]
+    fn test_cache_evicts_least_recently_used_over_its_max_size() {
+        let mut cache = LruMemoryCache::new(250);
+        for key in 1..=3 {
+            cache.put(key, value());
+        }
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.current_size(), 200);
+        assert!(!cache.contains(&1));
+        assert!(cache.contains(&2) && cache.contains(&3));
+    }
+
+    #[test]
+    fn test_budget_does_not_evict_under_the_cap() {
+        let budget = std::sync::Arc::new(MemoryBudget::new(1_000));
+        let mut first = LruMemoryCache::new(1_000).with_memory_budget("first", budget.clone());
+        let mut second = LruMemoryCache::new(1_000).with_memory_budget("second", budget.clone());
+        for key in 0..5 {
+            first.put(key, value());
+            second.put(key, value());
+        }
+        assert_eq!(first.len(), 5);
+        assert_eq!(second.len(), 5);
+        assert_eq!(budget.current_size(), 1_000);
+    }
+
+    #[test]
+    fn test_budget_evicts_from_the_cache_over_its_share() {
+        let budget = std::sync::Arc::new(MemoryBudget::new(300));
+        let mut first = LruMemoryCache::new(1_000).with_memory_budget("first", budget.clone());
+        let mut second = LruMemoryCache::new(1_000).with_memory_budget("second", budget.clone());
+        first.put(1, value());
+        first.put(2, value());
+        second.put(1, value());
+        second.put(2, value());
+
+        // The total 400 bytes exceed the cap, the second cache is over its 150 bytes share
+        // and evicts its least recently used value, the first cache is not touched
+        assert_eq!(first.len(), 2);
+        assert_eq!(second.len(), 1);
+        assert!(second.contains(&2));
+        assert_eq!(budget.current_size(), 300);
+    }
+
+    #[test]
+    fn test_budget_share_follows_the_weights() {
+        let budget = MemoryBudget::new(400);
+        budget.register("heavy", 300);
+        budget.register("light", 100);
+        budget.update("light", 100);
+        // The heavy cache share is 300 bytes, it evicts the bytes over the share
+        // but not more than the total is over the cap
+        assert_eq!(budget.update("heavy", 350), 50);
+        assert_eq!(budget.update("heavy", 300), 0);
+        // The light cache is over its 100 bytes share
+        assert_eq!(budget.update("light", 200), 100);
+    }
+
+    #[test]
+    fn test_budget_ignores_unregistered_cache() {
+        let budget = MemoryBudget::new(100);
+        assert_eq!(budget.update("unknown", 1_000), 0);
+        assert_eq!(budget.current_size(), 0);
+    }
+
+    #[test]
+    fn test_removed_values_are_released_from_the_budget() {
+        let budget = std::sync::Arc::new(MemoryBudget::new(1_000));
+        let mut cache = LruMemoryCache::new(1_000).with_memory_budget("cache", budget.clone());
+        for key in 0..4 {
+            cache.put(key, value());
+        }
+        assert_eq!(cache.remove_matching(|key| key % 2 == 0), 2);
+        assert_eq!(cache.current_size(), 200);
+        assert_eq!(budget.current_size(), 200);
+        assert_eq!(cache.retain(|_| false), 2);
+        assert_eq!(budget.current_size(), 0);
+    }
 }
@@ -9,6 +9,17 @@ static NEARD_VERSION: &str = env!("CARGO_PKG_VERSION");
 static NEARD_BUILD: &str = env!("BUILD_VERSION");
 static RUSTC_VERSION: &str = env!("RUSTC_VERSION");
 
+/// NEAR Read RPC server
+/// Serves the NEAR JSON RPC from the indexed data and proxies the rest to the nodes
+#[derive(clap::Parser, Debug)]
+#[command(version)]
+pub(crate) struct Opts {
+    /// Database backend to read the data from: postgres.
+    /// Overrides the `backend` of the `[database]` config section
+    #[clap(long, env = "DATABASE_BACKEND")]
+    pub database_backend: Option<configuration::DatabaseBackend>,
+}
+
 // Struct to store genesis_config and genesis_block in the server context
 // Fetch once genesis info on start of the server and put it in the context
 #[derive(Debug, Clone)]
@@ -57,6 +68,16 @@ pub struct ServerContext {
     pub tx_details_storage: std::sync::Arc<tx_details_storage::TxDetailsStorage>,
     /// Connection to cache storage with transactions in process
     pub tx_cache_storage: Option<cache_storage::TxIndexerCache>,
+    /// Nonces of the sent transactions which are not indexed yet.
+    /// `None` if the pending nonce overlay is disabled
+    pub pending_nonces_cache: Option<cache_storage::PendingNoncesCache>,
+    /// How long the pending nonces are kept, in seconds
+    pub pending_nonce_ttl: u64,
+    /// Transactions sent through the rpc-servers which are not included yet.
+    /// `None` if Redis is unavailable
+    pub tx_pool_cache: Option<cache_storage::TxPoolCache>,
+    /// How long the sent transactions are kept, in seconds
+    pub tx_pool_ttl: u64,
     /// Genesis info include genesis_config and genesis_block
     pub genesis_info: GenesisInfo,
     /// Near rpc client
@@ -65,21 +86,61 @@ pub struct ServerContext {
     pub s3_bucket_name: String,
     /// Blocks cache
     pub blocks_cache: std::sync::Arc<crate::cache::RwLockLruMemoryCache<u64, CacheBlock>>,
+    /// Block heights by block hashes, including the recently requested unknown hashes
+    pub block_hashes_cache: std::sync::Arc<
+        crate::cache::RwLockLruMemoryCache<
+            near_primitives::hash::CryptoHash,
+            crate::modules::blocks::BlockHeightByHash,
+        >,
+    >,
+    /// Prefetcher of the blocks for the sequential scans by height
+    pub block_prefetcher: std::sync::Arc<crate::block_prefetch::BlockPrefetcher>,
     /// Final block info include final_block_cache and current_validators_info
     pub blocks_info_by_finality: std::sync::Arc<BlocksInfoByFinality>,
+    /// Latest blocks processed by the indexers, learned from their notifications
+    pub indexed_heads: std::sync::Arc<crate::indexer_notifications::IndexedHeads>,
+    /// Versions of the indexers returned by `status`, cached to not query the database every call
+    pub writer_versions_cache: crate::modules::network::WriterVersionsCache,
     /// Cache to store compiled contract codes
     pub compiled_contract_code_cache: std::sync::Arc<CompiledCodeCache>,
     /// Cache to store contract codes
     pub contract_code_cache: std::sync::Arc<
         crate::cache::RwLockLruMemoryCache<near_primitives::hash::CryptoHash, Vec<u8>>,
     >,
+    /// Global memory budget shared by all in-memory caches
+    pub caches_memory_budget: std::sync::Arc<crate::cache::MemoryBudget>,
     /// Max gas burnt for contract function call
+    #[cfg(not(feature = "tx-only"))]
     pub max_gas_burnt: near_primitives::types::Gas,
     /// How many requests we should check for data consistency
     #[cfg(feature = "shadow-data-consistency")]
     pub shadow_data_consistency_rate: f64,
     /// Max size for state prefetch during a view_call
+    #[cfg(not(feature = "tx-only"))]
     pub prefetch_state_size_limit: u64,
+    /// Max number of state keys returned by the view_state query
+    #[cfg(not(feature = "tx-only"))]
+    pub view_state_max_keys: usize,
+    /// Max size of the serialized response
+    pub max_response_size: usize,
+    /// Max size of the logs of every outcome in the transactions responses.
+    /// The logs are not truncated if not set
+    pub max_outcome_logs_size: Option<usize>,
+    /// API keys of the clients with the allowed methods
+    pub api_keys: crate::api_keys::ApiKeys,
+    /// Methods enabled and disabled by the operator
+    pub method_access: crate::method_access::MethodAccess,
+    /// Sampler of the queried methods, accounts and block ages. `None` if the sampling is disabled
+    pub access_sampler: Option<crate::access_sampling::AccessSampler>,
+    /// Reject the requests with the params not matching the method schema
+    pub strict_params: bool,
+    /// Validate the sent transactions against the indexed state before sending them
+    #[cfg(not(feature = "tx-only"))]
+    pub send_tx_preflight: bool,
+    /// Max number of the subscriptions of the single WebSocket connection
+    pub ws_max_subscriptions: usize,
+    /// Time to wait for the block requested in the `x-min-block-height` header before proxying
+    pub min_block_height_wait: std::time::Duration,
     /// Port of the server.
     pub server_port: u16,
     /// Timestamp of starting server.
@@ -93,22 +154,76 @@ impl ServerContext {
         rpc_server_config: configuration::RpcServerConfig,
         near_rpc_client: crate::utils::JsonRpcClient,
     ) -> anyhow::Result<Self> {
-        let contract_code_cache_size_in_bytes =
+        // Contracts are never run in the tx-only build, their codes are not cached
+        let contract_code_cache_size_in_bytes = if cfg!(feature = "tx-only") {
+            0
+        } else {
             crate::utils::gigabytes_to_bytes(rpc_server_config.general.contract_code_cache_size)
-                .await;
+                .await
+        };
+        let block_cache_size_in_bytes =
+            crate::utils::gigabytes_to_bytes(rpc_server_config.general.block_cache_size).await;
+        // Block hashes entries are much smaller than the blocks,
+        // a quarter of the blocks cache is enough to index all the cached blocks
+        let block_hashes_cache_size_in_bytes = block_cache_size_in_bytes / 4;
+        // Full block views are much bigger than the cached blocks,
+        // a half of the blocks cache keeps enough of them for the scans in progress
+        let prefetched_blocks_cache_size_in_bytes =
+            if rpc_server_config.general.block_prefetch_count == 0 {
+                0
+            } else {
+                block_cache_size_in_bytes / 2
+            };
+
+        // If the budget is not configured, it covers the sum of all caches limits
+        // (contract codes cache and compiled codes cache have the same limit).
+        let caches_memory_budget_in_bytes =
+            match rpc_server_config.general.caches_memory_budget_size {
+                Some(caches_memory_budget_size) => {
+                    crate::utils::gigabytes_to_bytes(caches_memory_budget_size).await
+                }
+                None => {
+                    contract_code_cache_size_in_bytes * 2
+                        + block_cache_size_in_bytes
+                        + block_hashes_cache_size_in_bytes
+                        + prefetched_blocks_cache_size_in_bytes
+                }
+            };
+        let caches_memory_budget = std::sync::Arc::new(crate::cache::MemoryBudget::new(
+            caches_memory_budget_in_bytes,
+        ));
+
         let contract_code_cache = std::sync::Arc::new(crate::cache::RwLockLruMemoryCache::new(
             contract_code_cache_size_in_bytes,
+            "contract_code",
+            std::sync::Arc::clone(&caches_memory_budget),
         ));
 
-        let block_cache_size_in_bytes =
-            crate::utils::gigabytes_to_bytes(rpc_server_config.general.block_cache_size).await;
         let blocks_cache = std::sync::Arc::new(crate::cache::RwLockLruMemoryCache::new(
             block_cache_size_in_bytes,
+            "blocks",
+            std::sync::Arc::clone(&caches_memory_budget),
+        ));
+
+        let block_hashes_cache = std::sync::Arc::new(crate::cache::RwLockLruMemoryCache::new(
+            block_hashes_cache_size_in_bytes,
+            "block_hashes",
+            std::sync::Arc::clone(&caches_memory_budget),
+        ));
+
+        let block_prefetcher = std::sync::Arc::new(crate::block_prefetch::BlockPrefetcher::new(
+            rpc_server_config.general.block_prefetch_count,
+            prefetched_blocks_cache_size_in_bytes,
+            std::sync::Arc::clone(&caches_memory_budget),
         ));
 
         let blocks_info_by_finality =
             std::sync::Arc::new(BlocksInfoByFinality::new(&near_rpc_client, &blocks_cache).await);
 
+        // The blocks are fetched by height from the bucket, the local directory is not supported
+        if rpc_server_config.lake_config.source == configuration::LakeSource::Filesystem {
+            anyhow::bail!("rpc-server reads the blocks from the S3 or GCS lake only, the filesystem lake source is not supported");
+        }
         let s3_client = rpc_server_config.lake_config.lake_s3_client().await;
 
         let tx_details_storage = tx_details_storage::TxDetailsStorage::new(
@@ -124,6 +239,31 @@ impl ServerContext {
                 })
                 .ok();
 
+        let pending_nonces_cache = if rpc_server_config.general.pending_nonce_ttl.is_some() {
+            cache_storage::PendingNoncesCache::new(rpc_server_config.general.redis_url.to_string())
+                .await
+                .map_err(|err| {
+                    tracing::warn!(
+                        "Failed to connect to Redis, pending nonce overlay is disabled: {:?}",
+                        err
+                    );
+                })
+                .ok()
+        } else {
+            None
+        };
+
+        let tx_pool_cache =
+            cache_storage::TxPoolCache::new(rpc_server_config.general.redis_url.to_string())
+                .await
+                .map_err(|err| {
+                    tracing::warn!(
+                        "Failed to connect to Redis, EXPERIMENTAL_tx_pool is unavailable: {:?}",
+                        err
+                    );
+                })
+                .ok();
+
         let genesis_info = GenesisInfo::get(
             &near_rpc_client,
             &s3_client,
@@ -144,31 +284,72 @@ impl ServerContext {
                 .latest_protocol_version,
         );
 
-        let db_manager = database::prepare_db_manager::<database::PostgresDBManager>(
+        let db_manager = database::prepare_reader_db_manager(
             &rpc_server_config.database,
             epoch_config.shard_layout,
         )
         .await?;
 
-        let compiled_contract_code_cache =
-            std::sync::Arc::new(CompiledCodeCache::new(contract_code_cache_size_in_bytes));
+        let compiled_contract_code_cache = std::sync::Arc::new(CompiledCodeCache::new(
+            contract_code_cache_size_in_bytes,
+            std::sync::Arc::clone(&caches_memory_budget),
+        ));
 
         Ok(Self {
             s3_client,
-            db_manager: std::sync::Arc::new(Box::new(db_manager)),
+            db_manager: std::sync::Arc::new(db_manager),
             tx_details_storage: std::sync::Arc::new(tx_details_storage),
             tx_cache_storage,
+            pending_nonces_cache,
+            pending_nonce_ttl: rpc_server_config
+                .general
+                .pending_nonce_ttl
+                .unwrap_or_default(),
+            tx_pool_cache,
+            tx_pool_ttl: rpc_server_config.general.tx_pool_ttl,
             genesis_info,
             near_rpc_client,
             s3_bucket_name: rpc_server_config.lake_config.aws_bucket_name.clone(),
             blocks_cache,
+            block_hashes_cache,
+            block_prefetcher,
             blocks_info_by_finality,
+            indexed_heads: std::sync::Arc::new(
+                crate::indexer_notifications::IndexedHeads::default(),
+            ),
+            writer_versions_cache: crate::modules::network::WriterVersionsCache::default(),
             compiled_contract_code_cache,
             contract_code_cache,
+            caches_memory_budget,
+            #[cfg(not(feature = "tx-only"))]
             max_gas_burnt: rpc_server_config.general.max_gas_burnt,
             #[cfg(feature = "shadow-data-consistency")]
             shadow_data_consistency_rate: rpc_server_config.general.shadow_data_consistency_rate,
+            #[cfg(not(feature = "tx-only"))]
             prefetch_state_size_limit: rpc_server_config.general.prefetch_state_size_limit,
+            #[cfg(not(feature = "tx-only"))]
+            view_state_max_keys: rpc_server_config.general.view_state_max_keys,
+            max_response_size: rpc_server_config.general.max_response_size,
+            max_outcome_logs_size: rpc_server_config.general.max_outcome_logs_size,
+            api_keys: crate::api_keys::ApiKeys::new(
+                &rpc_server_config.general.api_keys,
+                rpc_server_config.general.require_api_key,
+            ),
+            method_access: crate::method_access::MethodAccess::new(
+                rpc_server_config.general.enabled_methods.clone(),
+                rpc_server_config.general.disabled_methods.clone(),
+            )?,
+            access_sampler: rpc_server_config
+                .general
+                .access_sampling_rate
+                .and_then(crate::access_sampling::AccessSampler::new),
+            strict_params: rpc_server_config.general.strict_params,
+            #[cfg(not(feature = "tx-only"))]
+            send_tx_preflight: rpc_server_config.general.send_tx_preflight,
+            ws_max_subscriptions: rpc_server_config.general.ws_max_subscriptions,
+            min_block_height_wait: std::time::Duration::from_millis(
+                rpc_server_config.general.min_block_height_wait,
+            ),
             server_port: rpc_server_config.general.server_port,
             boot_time_seconds: chrono::Utc::now().timestamp(),
             version: near_primitives::version::Version {
@@ -191,10 +372,15 @@ pub struct CompiledCodeCache {
 }
 
 impl CompiledCodeCache {
-    pub fn new(contract_code_cache_size: usize) -> Self {
+    pub fn new(
+        contract_code_cache_size: usize,
+        memory_budget: std::sync::Arc<crate::cache::MemoryBudget>,
+    ) -> Self {
         Self {
             local_cache: std::sync::Arc::new(crate::cache::RwLockLruMemoryCache::new(
                 contract_code_cache_size,
+                "compiled_contract_code",
+                memory_budget,
             )),
         }
     }
@@ -0,0 +1,144 @@
+use actix_web::body::{BoxBody, MessageBody};
+use actix_web::dev::{Service, ServiceRequest, ServiceResponse};
+
+/// State of the client connection shared by the requests (HTTP/2 streams) it serves.
+/// It is stored in the connection data, so it is dropped when the connection is closed
+/// and all its requests are finished
+pub(crate) struct ConnectionState {
+    active_streams: std::cell::Cell<usize>,
+    served_streams: std::cell::Cell<u64>,
+    // The client sent the data by the time the connection was registered.
+    // The connections closed without sending anything, e.g. the TCP probes of the load balancers
+    // and the connections opened ahead by the clients, are not the handshake failures
+    received_data: bool,
+}
+
+impl Drop for ConnectionState {
+    fn drop(&mut self) {
+        crate::metrics::HTTP_OPEN_CONNECTIONS.dec();
+        // The connection sent the data but failed the HTTP/2 handshake
+        // or sent the malformed HTTP/1.1 request
+        if self.received_data && self.served_streams.get() == 0 {
+            crate::metrics::HTTP_HANDSHAKE_FAILURES.inc();
+        }
+    }
+}
+
+/// Registers the accepted client connection, called by the server for every TCP connection
+/// before the protocol is negotiated. With `http2` on, the protocol is detected by the first
+/// bytes of the connection, so it is called once they are received or the connection is closed
+pub(crate) fn on_connect(
+    connection: &dyn std::any::Any,
+    extensions: &mut actix_web::dev::Extensions,
+) {
+    crate::metrics::HTTP_OPEN_CONNECTIONS.inc();
+    extensions.insert(std::rc::Rc::new(ConnectionState {
+        active_streams: std::cell::Cell::new(0),
+        served_streams: std::cell::Cell::new(0),
+        received_data: received_data(connection),
+    }));
+}
+
+// Peeks the socket without consuming the data, the socket is non-blocking,
+// so the connection with no data received yet is not waited for
+fn received_data(connection: &dyn std::any::Any) -> bool {
+    let Some(stream) = connection.downcast_ref::<actix_web::rt::net::TcpStream>() else {
+        return false;
+    };
+    let mut buf = [std::mem::MaybeUninit::<u8>::uninit(); 1];
+    matches!(socket2::SockRef::from(stream).peek(&mut buf), Ok(len) if len > 0)
+}
+
+/// Request in progress of the connection, the counters are updated when it is finished
+struct ActiveStream {
+    connection: std::rc::Rc<ConnectionState>,
+    protocol: &'static str,
+}
+
+impl ActiveStream {
+    fn new(connection: std::rc::Rc<ConnectionState>, protocol: &'static str) -> Self {
+        connection
+            .active_streams
+            .set(connection.active_streams.get() + 1);
+        connection
+            .served_streams
+            .set(connection.served_streams.get() + 1);
+        crate::metrics::HTTP_ACTIVE_STREAMS
+            .with_label_values(&[protocol])
+            .inc();
+        Self {
+            connection,
+            protocol,
+        }
+    }
+}
+
+impl Drop for ActiveStream {
+    fn drop(&mut self) {
+        self.connection
+            .active_streams
+            .set(self.connection.active_streams.get() - 1);
+        crate::metrics::HTTP_ACTIVE_STREAMS
+            .with_label_values(&[self.protocol])
+            .dec();
+    }
+}
+
+/// Tracks the requests in progress of every connection and rejects the requests
+/// over `max_concurrent_streams` with the `TOO_MANY_STREAMS` error.
+/// The HTTP/2 settings of the server are not configurable, so the limit is applied
+/// to the streams accepted by the server. HTTP/1.1 connections serve one request at a time
+pub(crate) fn track_streams<S, B>(
+    req: ServiceRequest,
+    service: &S,
+    max_concurrent_streams: Option<usize>,
+) -> futures::future::LocalBoxFuture<'static, Result<ServiceResponse<BoxBody>, actix_web::Error>>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    let protocol = match req.version() {
+        actix_web::http::Version::HTTP_2 => "http2",
+        _ => "http1",
+    };
+    let connection = req
+        .request()
+        .conn_data::<std::rc::Rc<ConnectionState>>()
+        .cloned();
+    let stream = match connection {
+        Some(connection) => {
+            if let Some(max_concurrent_streams) = max_concurrent_streams {
+                if connection.active_streams.get() >= max_concurrent_streams {
+                    crate::metrics::HTTP_REJECTED_STREAMS.inc();
+                    let response = crate::errors::error_response(
+                        req.request(),
+                        crate::errors::HttpErrorCode::TooManyStreams,
+                        format!(
+                            "Connection has too many requests in progress (limit: {})",
+                            max_concurrent_streams
+                        ),
+                    );
+                    return Box::pin(async move { Ok(req.into_response(response)) });
+                }
+            }
+            Some(ActiveStream::new(connection, protocol))
+        }
+        None => None,
+    };
+    let response = service.call(req);
+    Box::pin(async move {
+        let response = response.await;
+        drop(stream);
+        response.map(ServiceResponse::map_into_boxed_body)
+    })
+}
+
+/// Keep-alive of the idle client connections, 0 seconds disables it
+pub(crate) fn keep_alive(keep_alive_secs: u64) -> actix_web::http::KeepAlive {
+    if keep_alive_secs == 0 {
+        actix_web::http::KeepAlive::Disabled
+    } else {
+        actix_web::http::KeepAlive::Timeout(std::time::Duration::from_secs(keep_alive_secs))
+    }
+}
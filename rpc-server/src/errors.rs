@@ -0,0 +1,102 @@
+use actix_web::HttpMessage;
+
+/// Header with the correlation id of the request, the same id is logged by the tracing logger
+pub(crate) const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Machine-readable codes of the errors returned outside of the JSON-RPC protocol,
+/// when the request is rejected before it reaches the JSON-RPC handler.
+/// The codes are documented in `docs/HTTP_ERRORS.md`
+#[derive(Debug, Clone, Copy, serde::Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub(crate) enum HttpErrorCode {
+    /// Request body exceeds the allowed size
+    PayloadTooLarge,
+    /// Request body is not sent with the `application/json` content type
+    UnsupportedMediaType,
+    /// Request body is not a valid JSON-RPC message
+    InvalidJson,
+    /// JSON-RPC message is not a request (e.g. a response or a notification)
+    InvalidRequest,
+    /// Unknown path or HTTP method
+    NotFound,
+    /// API key is missing or unknown
+    Unauthorized,
+    /// JSON-RPC method is not allowed for the API key
+    MethodNotAllowed,
+    /// Method is disabled by the operator
+    MethodDisabled,
+    /// Connection has too many requests in progress
+    TooManyStreams,
+}
+
+impl HttpErrorCode {
+    fn status_code(&self) -> actix_web::http::StatusCode {
+        match self {
+            Self::PayloadTooLarge => actix_web::http::StatusCode::PAYLOAD_TOO_LARGE,
+            Self::UnsupportedMediaType => actix_web::http::StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            Self::InvalidJson | Self::InvalidRequest => actix_web::http::StatusCode::BAD_REQUEST,
+            Self::NotFound => actix_web::http::StatusCode::NOT_FOUND,
+            Self::Unauthorized => actix_web::http::StatusCode::UNAUTHORIZED,
+            Self::MethodNotAllowed | Self::MethodDisabled => actix_web::http::StatusCode::FORBIDDEN,
+            Self::TooManyStreams => actix_web::http::StatusCode::TOO_MANY_REQUESTS,
+        }
+    }
+}
+
+#[derive(Debug, serde::Serialize)]
+struct HttpErrorDetails {
+    code: HttpErrorCode,
+    message: String,
+    request_id: Option<String>,
+}
+
+#[derive(Debug, serde::Serialize)]
+struct HttpErrorBody {
+    error: HttpErrorDetails,
+}
+
+/// Builds the unified JSON error response with the correlation id of the request
+pub(crate) fn error_response(
+    req: &actix_web::HttpRequest,
+    code: HttpErrorCode,
+    message: impl Into<String>,
+) -> actix_web::HttpResponse {
+    let request_id = req
+        .extensions()
+        .get::<tracing_actix_web::RequestId>()
+        .map(|request_id| request_id.to_string());
+    let mut response = actix_web::HttpResponse::build(code.status_code());
+    if let Some(request_id) = &request_id {
+        response.insert_header((REQUEST_ID_HEADER, request_id.as_str()));
+    }
+    response.json(HttpErrorBody {
+        error: HttpErrorDetails {
+            code,
+            message: message.into(),
+            request_id,
+        },
+    })
+}
+
+/// Replaces the framework plain text responses for the rejected JSON payloads
+pub(crate) fn json_error_handler(
+    err: actix_web::error::JsonPayloadError,
+    req: &actix_web::HttpRequest,
+) -> actix_web::Error {
+    let code = match &err {
+        actix_web::error::JsonPayloadError::Overflow { .. }
+        | actix_web::error::JsonPayloadError::OverflowKnownLength { .. } => {
+            HttpErrorCode::PayloadTooLarge
+        }
+        actix_web::error::JsonPayloadError::ContentType => HttpErrorCode::UnsupportedMediaType,
+        _ => HttpErrorCode::InvalidJson,
+    };
+    let response = error_response(req, code, err.to_string());
+    actix_web::error::InternalError::from_response(err, response).into()
+}
+
+/// Default service for the unknown paths and methods
+pub(crate) async fn not_found(req: actix_web::HttpRequest) -> actix_web::HttpResponse {
+    let message = format!("{} {} is not found", req.method(), req.path());
+    error_response(&req, HttpErrorCode::NotFound, message)
+}
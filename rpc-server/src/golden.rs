@@ -0,0 +1,225 @@
+//! Golden files of the responses of the custom methods.
+//! The responses are compared in the canonical form, so only the field names
+//! and the values matter. Set `UPDATE_GOLDEN_FILES=1` to rewrite the files
+//! once a serialization change is agreed. The nearcore views nested in the responses
+//! are covered by nearcore and the readnode-primitives records by their own golden files,
+//! the responses wrapping the nearcore views only are not snapshotted here
+use near_primitives::hash::CryptoHash;
+
+const ACCOUNT_ID: &str = "contract.near";
+const SIGNER_ID: &str = "signer.near";
+
+fn assert_golden<T: serde::Serialize>(name: &str, value: &T) {
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("golden")
+        .join(format!("{}.json", name));
+    let actual = readnode_primitives::canonical_json::to_canonical_string_pretty(value).unwrap();
+    if std::env::var("UPDATE_GOLDEN_FILES").is_ok() {
+        std::fs::write(&path, format!("{}\n", actual)).unwrap();
+        return;
+    }
+    let expected = std::fs::read_to_string(&path)
+        .unwrap_or_else(|err| panic!("Failed to read {}: {}", path.display(), err));
+    assert_eq!(
+        actual,
+        expected.trim_end(),
+        "Serialization of {} doesn't match the golden file",
+        name
+    );
+}
+
+#[test]
+fn test_golden_transactions_by_public_key_response() {
+    assert_golden(
+        "transactions_by_public_key_response",
+        &crate::modules::transactions::RpcTransactionsByPublicKeyResponse {
+            transactions: vec![],
+            cursor: Some("118875450".to_string()),
+        },
+    );
+}
+
+#[test]
+fn test_golden_tx_pool_response() {
+    assert_golden(
+        "tx_pool_response",
+        &crate::modules::transactions::RpcTxPoolResponse {
+            block_height: 118875450,
+            transactions: vec![readnode_primitives::PendingTransaction {
+                transaction_hash: CryptoHash::default(),
+                signer_id: SIGNER_ID.parse().unwrap(),
+                public_key: "ed25519:11111111111111111111111111111111".to_string(),
+                receiver_id: ACCOUNT_ID.parse().unwrap(),
+                nonce: 42,
+                submitted_at: 1700000000,
+            }],
+        },
+    );
+}
+
+#[test]
+fn test_golden_search_function_calls_response() {
+    assert_golden(
+        "search_function_calls_response",
+        &crate::modules::receipts::RpcSearchFunctionCallsResponse {
+            function_calls: vec![],
+            cursor: None,
+        },
+    );
+}
+
+#[test]
+fn test_golden_actions_by_contract_method_response() {
+    assert_golden(
+        "actions_by_contract_method_response",
+        &crate::modules::receipts::RpcActionsByContractMethodResponse {
+            actions: vec![],
+            cursor: Some("118875450:0".to_string()),
+        },
+    );
+}
+
+#[test]
+fn test_golden_receipt_record_response() {
+    assert_golden(
+        "receipt_record_response",
+        &crate::modules::receipts::RpcReceiptRecordResponse {
+            receipt_id: CryptoHash::default(),
+            parent_transaction_hash: CryptoHash::default(),
+            block_height: 118875450,
+            shard_id: 2,
+            index_in_chunk: Some(3),
+        },
+    );
+}
+
+#[test]
+fn test_golden_validators_changes_response() {
+    assert_golden(
+        "validators_changes_response",
+        &crate::modules::network::RpcValidatorsChangesResponse {
+            epochs: vec![],
+            next_page_token: None,
+        },
+    );
+}
+
+#[test]
+fn test_golden_produced_blocks_response() {
+    assert_golden(
+        "produced_blocks_response",
+        &crate::modules::network::RpcProducedBlocksResponse {
+            validator_id: SIGNER_ID.parse().unwrap(),
+            epoch_id: CryptoHash::default(),
+            num_produced_blocks: 10,
+            num_produced_chunks: 40,
+            produced_blocks: vec![],
+            produced_chunks: vec![],
+        },
+    );
+}
+
+#[cfg(not(feature = "tx-only"))]
+mod state {
+    use super::*;
+
+    fn state_item(key: &[u8], value: &[u8]) -> near_primitives::views::StateItem {
+        near_primitives::views::StateItem {
+            key: key.to_vec().into(),
+            value: value.to_vec().into(),
+        }
+    }
+
+    #[test]
+    fn test_golden_view_state_paginated_response() {
+        assert_golden(
+            "view_state_paginated_response",
+            &crate::modules::state::RpcViewStatePaginatedResponse {
+                values: vec![state_item(b"STATE", b"value")],
+                block_height: 118875450,
+                block_hash: CryptoHash::default(),
+                next_page_token: Some("U1RBVEU=".to_string()),
+            },
+        );
+    }
+
+    #[test]
+    fn test_golden_view_state_keys_response() {
+        assert_golden(
+            "view_state_keys_response",
+            &crate::modules::state::RpcViewStateKeysResponse {
+                account_id: ACCOUNT_ID.parse().unwrap(),
+                values: vec![
+                    crate::modules::state::ViewStateKeyItem {
+                        key: b"STATE".to_vec().into(),
+                        value: Some(b"value".to_vec().into()),
+                    },
+                    crate::modules::state::ViewStateKeyItem {
+                        key: b"missing".to_vec().into(),
+                        value: None,
+                    },
+                ],
+                block_height: 118875450,
+                block_hash: CryptoHash::default(),
+            },
+        );
+    }
+
+    #[test]
+    fn test_golden_state_diff_response() {
+        assert_golden(
+            "state_diff_response",
+            &crate::modules::state::RpcStateDiffResponse {
+                account_id: ACCOUNT_ID.parse().unwrap(),
+                added: vec![state_item(b"new", b"value")],
+                changed: vec![crate::modules::state::StateDiffChangedItem {
+                    key: b"count".to_vec().into(),
+                    from_value: b"old".to_vec().into(),
+                    to_value: b"new".to_vec().into(),
+                }],
+                removed: vec![state_item(b"old", b"value")],
+                from_block_height: 118875400,
+                from_block_hash: CryptoHash::default(),
+                to_block_height: 118875450,
+                to_block_hash: CryptoHash::default(),
+                next_page_token: None,
+            },
+        );
+    }
+
+    #[test]
+    fn test_golden_storage_breakdown_response() {
+        assert_golden(
+            "storage_breakdown_response",
+            &crate::modules::state::RpcStorageBreakdownResponse {
+                account_id: ACCOUNT_ID.parse().unwrap(),
+                state_keys_count: 2,
+                state_keys_bytes: 10,
+                state_values_bytes: 8,
+                contract_code_bytes: 1024,
+                storage_usage: 1342,
+                storage_amount_per_byte: 10000000000000000000,
+                storage_staking_requirement: 13420000000000000000000,
+                block_height: 118875450,
+                block_hash: CryptoHash::default(),
+            },
+        );
+    }
+
+    #[test]
+    fn test_golden_top_state_growers_response() {
+        assert_golden(
+            "top_state_growers_response",
+            &crate::modules::state::RpcTopStateGrowersResponse {
+                accounts: vec![readnode_primitives::StateGrowth {
+                    account_id: ACCOUNT_ID.parse().unwrap(),
+                    keys_delta: 12,
+                    bytes_delta: -340,
+                }],
+                window: 7,
+                from_timestamp: Some(1699395200),
+                to_timestamp: None,
+            },
+        );
+    }
+}
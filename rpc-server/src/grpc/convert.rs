@@ -0,0 +1,901 @@
+use super::proto;
+use near_primitives::views;
+
+pub(super) fn crypto_hash(
+    bytes: &[u8],
+    field_name: &str,
+) -> Result<near_primitives::hash::CryptoHash, tonic::Status> {
+    near_primitives::hash::CryptoHash::try_from(bytes)
+        .map_err(|_| tonic::Status::invalid_argument(format!("`{}` must be 32 bytes", field_name)))
+}
+
+pub(super) fn account_id(
+    value: String,
+    field_name: &str,
+) -> Result<near_primitives::types::AccountId, tonic::Status> {
+    value.parse().map_err(|err| {
+        tonic::Status::invalid_argument(format!("Invalid `{}`: {}", field_name, err))
+    })
+}
+
+/// Returns the JSON-RPC error in the message of the status, so the clients get the same
+/// error details. The code is picked by the kind of the error and its name
+pub(super) fn rpc_error_status(
+    error: impl Into<near_jsonrpc::primitives::errors::RpcError>,
+) -> tonic::Status {
+    let error = error.into();
+    let code = match &error.error_struct {
+        Some(near_jsonrpc::primitives::errors::RpcErrorKind::RequestValidationError(_)) => {
+            tonic::Code::InvalidArgument
+        }
+        Some(near_jsonrpc::primitives::errors::RpcErrorKind::HandlerError(error_struct)) => {
+            match error_struct.get("name").and_then(serde_json::Value::as_str) {
+                Some(
+                    "UNKNOWN_BLOCK"
+                    | "UNKNOWN_CHUNK"
+                    | "UNKNOWN_ACCOUNT"
+                    | "UNKNOWN_ACCESS_KEY"
+                    | "NO_CONTRACT_CODE"
+                    | "UNKNOWN_TRANSACTION"
+                    | "UNKNOWN_RECEIPT",
+                ) => tonic::Code::NotFound,
+                Some("TIMEOUT_ERROR") => tonic::Code::DeadlineExceeded,
+                _ => tonic::Code::FailedPrecondition,
+            }
+        }
+        Some(near_jsonrpc::primitives::errors::RpcErrorKind::InternalError(_)) | None => {
+            tonic::Code::Internal
+        }
+    };
+    let message = serde_json::to_string(&error).unwrap_or_else(|_| error.message.clone());
+    tonic::Status::new(code, message)
+}
+
+pub(super) fn block_reference(
+    block_reference: Option<proto::BlockReference>,
+) -> Result<near_primitives::types::BlockReference, tonic::Status> {
+    let reference = match block_reference.and_then(|block_reference| block_reference.reference) {
+        None => near_primitives::types::BlockReference::Finality(
+            near_primitives::types::Finality::Final,
+        ),
+        Some(proto::block_reference::Reference::Finality(finality)) => {
+            near_primitives::types::BlockReference::Finality(
+                match proto::Finality::try_from(finality) {
+                    Ok(proto::Finality::Final) => near_primitives::types::Finality::Final,
+                    Ok(proto::Finality::Optimistic) => near_primitives::types::Finality::None,
+                    Ok(proto::Finality::Doomslug) => near_primitives::types::Finality::DoomSlug,
+                    Err(_) => {
+                        return Err(tonic::Status::invalid_argument(format!(
+                            "Unknown finality: {}",
+                            finality
+                        )))
+                    }
+                },
+            )
+        }
+        Some(proto::block_reference::Reference::BlockHeight(block_height)) => {
+            near_primitives::types::BlockReference::BlockId(
+                near_primitives::types::BlockId::Height(block_height),
+            )
+        }
+        Some(proto::block_reference::Reference::BlockHash(block_hash)) => {
+            near_primitives::types::BlockReference::BlockId(near_primitives::types::BlockId::Hash(
+                crypto_hash(&block_hash, "block_hash")?,
+            ))
+        }
+    };
+    Ok(reference)
+}
+
+#[cfg(not(feature = "tx-only"))]
+pub(super) fn query_request(
+    request: proto::QueryRequest,
+) -> Result<near_jsonrpc::primitives::types::query::RpcQueryRequest, tonic::Status> {
+    let query_request = match request.request {
+        Some(proto::query_request::Request::ViewAccount(request)) => {
+            views::QueryRequest::ViewAccount {
+                account_id: account_id(request.account_id, "account_id")?,
+            }
+        }
+        Some(proto::query_request::Request::ViewCode(request)) => views::QueryRequest::ViewCode {
+            account_id: account_id(request.account_id, "account_id")?,
+        },
+        Some(proto::query_request::Request::ViewState(request)) => views::QueryRequest::ViewState {
+            account_id: account_id(request.account_id, "account_id")?,
+            prefix: near_primitives::types::StoreKey::from(request.prefix),
+            include_proof: request.include_proof,
+        },
+        Some(proto::query_request::Request::ViewAccessKey(request)) => {
+            views::QueryRequest::ViewAccessKey {
+                account_id: account_id(request.account_id, "account_id")?,
+                public_key: request.public_key.parse().map_err(|err| {
+                    tonic::Status::invalid_argument(format!("Invalid `public_key`: {}", err))
+                })?,
+            }
+        }
+        Some(proto::query_request::Request::ViewAccessKeyList(request)) => {
+            views::QueryRequest::ViewAccessKeyList {
+                account_id: account_id(request.account_id, "account_id")?,
+            }
+        }
+        Some(proto::query_request::Request::CallFunction(request)) => {
+            views::QueryRequest::CallFunction {
+                account_id: account_id(request.account_id, "account_id")?,
+                method_name: request.method_name,
+                args: near_primitives::types::FunctionArgs::from(request.args),
+            }
+        }
+        None => return Err(tonic::Status::invalid_argument("`request` is required")),
+    };
+    Ok(near_jsonrpc::primitives::types::query::RpcQueryRequest {
+        block_reference: block_reference(request.block_reference)?,
+        request: query_request,
+    })
+}
+
+#[cfg(not(feature = "tx-only"))]
+pub(super) fn query_response(
+    response: near_jsonrpc::primitives::types::query::RpcQueryResponse,
+) -> proto::QueryResponse {
+    let result = match response.kind {
+        near_jsonrpc::primitives::types::query::QueryResponseKind::ViewAccount(account) => {
+            proto::query_response::Result::Account(proto::Account {
+                amount: account.amount.to_string(),
+                locked: account.locked.to_string(),
+                code_hash: account.code_hash.0.to_vec(),
+                storage_usage: account.storage_usage,
+            })
+        }
+        near_jsonrpc::primitives::types::query::QueryResponseKind::ViewCode(code) => {
+            proto::query_response::Result::Code(proto::ContractCode {
+                code: code.code,
+                hash: code.hash.0.to_vec(),
+            })
+        }
+        near_jsonrpc::primitives::types::query::QueryResponseKind::ViewState(state) => {
+            proto::query_response::Result::State(proto::ViewStateResult {
+                values: state
+                    .values
+                    .into_iter()
+                    .map(|item| proto::StateItem {
+                        key: item.key.to_vec(),
+                        value: item.value.to_vec(),
+                    })
+                    .collect(),
+                proof: state.proof.iter().map(|node| node.to_vec()).collect(),
+            })
+        }
+        near_jsonrpc::primitives::types::query::QueryResponseKind::CallResult(call_result) => {
+            proto::query_response::Result::CallResult(proto::CallResult {
+                result: call_result.result,
+                logs: call_result.logs,
+            })
+        }
+        near_jsonrpc::primitives::types::query::QueryResponseKind::AccessKey(access_key) => {
+            proto::query_response::Result::AccessKey(self::access_key(access_key))
+        }
+        near_jsonrpc::primitives::types::query::QueryResponseKind::AccessKeyList(
+            access_key_list,
+        ) => proto::query_response::Result::AccessKeyList(proto::AccessKeyList {
+            keys: access_key_list
+                .keys
+                .into_iter()
+                .map(|key| proto::AccessKeyInfo {
+                    public_key: key.public_key.to_string(),
+                    access_key: Some(access_key(key.access_key)),
+                })
+                .collect(),
+        }),
+    };
+    proto::QueryResponse {
+        block_height: response.block_height,
+        block_hash: response.block_hash.0.to_vec(),
+        result: Some(result),
+    }
+}
+
+#[cfg(not(feature = "tx-only"))]
+fn access_key(access_key: views::AccessKeyView) -> proto::AccessKey {
+    let permission = match access_key.permission {
+        views::AccessKeyPermissionView::FullAccess => {
+            proto::access_key::Permission::FullAccess(proto::Empty {})
+        }
+        views::AccessKeyPermissionView::FunctionCall {
+            allowance,
+            receiver_id,
+            method_names,
+        } => proto::access_key::Permission::FunctionCall(proto::FunctionCallPermission {
+            allowance: allowance.map(|allowance| allowance.to_string()),
+            receiver_id,
+            method_names,
+        }),
+    };
+    proto::AccessKey {
+        nonce: access_key.nonce,
+        permission: Some(permission),
+    }
+}
+
+pub(super) fn block_response(block: views::BlockView) -> proto::BlockResponse {
+    let header = block.header;
+    proto::BlockResponse {
+        author: block.author.to_string(),
+        header: Some(proto::BlockHeader {
+            height: header.height,
+            prev_height: header.prev_height,
+            hash: header.hash.0.to_vec(),
+            prev_hash: header.prev_hash.0.to_vec(),
+            epoch_id: header.epoch_id.0.to_vec(),
+            next_epoch_id: header.next_epoch_id.0.to_vec(),
+            prev_state_root: header.prev_state_root.0.to_vec(),
+            outcome_root: header.outcome_root.0.to_vec(),
+            timestamp_nanosec: header.timestamp_nanosec,
+            gas_price: header.gas_price.to_string(),
+            total_supply: header.total_supply.to_string(),
+            latest_protocol_version: header.latest_protocol_version,
+            last_final_block: header.last_final_block.0.to_vec(),
+            last_ds_final_block: header.last_ds_final_block.0.to_vec(),
+            next_bp_hash: header.next_bp_hash.0.to_vec(),
+            block_merkle_root: header.block_merkle_root.0.to_vec(),
+            block_ordinal: header.block_ordinal,
+            chunk_mask: header.chunk_mask,
+        }),
+        chunks: block
+            .chunks
+            .into_iter()
+            .map(|chunk| proto::ChunkHeader {
+                chunk_hash: chunk.chunk_hash.0.to_vec(),
+                shard_id: chunk.shard_id,
+                height_created: chunk.height_created,
+                height_included: chunk.height_included,
+                prev_state_root: chunk.prev_state_root.0.to_vec(),
+                outcome_root: chunk.outcome_root.0.to_vec(),
+                tx_root: chunk.tx_root.0.to_vec(),
+                gas_used: chunk.gas_used,
+                gas_limit: chunk.gas_limit,
+                balance_burnt: chunk.balance_burnt.to_string(),
+            })
+            .collect(),
+    }
+}
+
+pub(super) fn tx_response(
+    response: near_jsonrpc::primitives::types::transactions::RpcTransactionResponse,
+) -> proto::TxResponse {
+    let final_execution_status = match response.final_execution_status {
+        views::TxExecutionStatus::None => proto::TxExecutionStatus::None,
+        views::TxExecutionStatus::Included => proto::TxExecutionStatus::Included,
+        views::TxExecutionStatus::ExecutedOptimistic => {
+            proto::TxExecutionStatus::ExecutedOptimistic
+        }
+        views::TxExecutionStatus::IncludedFinal => proto::TxExecutionStatus::IncludedFinal,
+        views::TxExecutionStatus::Executed => proto::TxExecutionStatus::Executed,
+        views::TxExecutionStatus::Final => proto::TxExecutionStatus::Final,
+    };
+    let outcome = response.final_execution_outcome.map(|outcome| {
+        let outcome = match outcome {
+            views::FinalExecutionOutcomeViewEnum::FinalExecutionOutcome(outcome) => outcome,
+            views::FinalExecutionOutcomeViewEnum::FinalExecutionOutcomeWithReceipt(outcome) => {
+                outcome.final_outcome
+            }
+        };
+        let status = match outcome.status {
+            views::FinalExecutionStatus::NotStarted => {
+                proto::final_execution_outcome::Status::NotStarted(proto::Empty {})
+            }
+            views::FinalExecutionStatus::Started => {
+                proto::final_execution_outcome::Status::Started(proto::Empty {})
+            }
+            views::FinalExecutionStatus::Failure(error) => {
+                proto::final_execution_outcome::Status::Failure(
+                    serde_json::to_string(&error).unwrap_or_default(),
+                )
+            }
+            views::FinalExecutionStatus::SuccessValue(value) => {
+                proto::final_execution_outcome::Status::SuccessValue(value)
+            }
+        };
+        let transaction = outcome.transaction;
+        proto::FinalExecutionOutcome {
+            status: Some(status),
+            transaction: Some(proto::Transaction {
+                hash: transaction.hash.0.to_vec(),
+                signer_id: transaction.signer_id.to_string(),
+                public_key: transaction.public_key.to_string(),
+                nonce: transaction.nonce,
+                receiver_id: transaction.receiver_id.to_string(),
+                actions: transaction.actions.into_iter().map(action).collect(),
+                signature: transaction.signature.to_string(),
+            }),
+            transaction_outcome: Some(execution_outcome(outcome.transaction_outcome)),
+            receipts_outcome: outcome
+                .receipts_outcome
+                .into_iter()
+                .map(execution_outcome)
+                .collect(),
+        }
+    });
+    proto::TxResponse {
+        final_execution_status: final_execution_status as i32,
+        outcome,
+    }
+}
+
+fn execution_outcome(outcome: views::ExecutionOutcomeWithIdView) -> proto::ExecutionOutcome {
+    let status = match outcome.outcome.status {
+        views::ExecutionStatusView::Unknown => {
+            proto::execution_outcome::Status::Unknown(proto::Empty {})
+        }
+        views::ExecutionStatusView::Failure(error) => proto::execution_outcome::Status::Failure(
+            serde_json::to_string(&error).unwrap_or_default(),
+        ),
+        views::ExecutionStatusView::SuccessValue(value) => {
+            proto::execution_outcome::Status::SuccessValue(value)
+        }
+        views::ExecutionStatusView::SuccessReceiptId(receipt_id) => {
+            proto::execution_outcome::Status::SuccessReceiptId(receipt_id.0.to_vec())
+        }
+    };
+    proto::ExecutionOutcome {
+        id: outcome.id.0.to_vec(),
+        block_hash: outcome.block_hash.0.to_vec(),
+        executor_id: outcome.outcome.executor_id.to_string(),
+        logs: outcome.outcome.logs,
+        receipt_ids: outcome
+            .outcome
+            .receipt_ids
+            .iter()
+            .map(|receipt_id| receipt_id.0.to_vec())
+            .collect(),
+        gas_burnt: outcome.outcome.gas_burnt,
+        tokens_burnt: outcome.outcome.tokens_burnt.to_string(),
+        status: Some(status),
+    }
+}
+
+// The actions unknown to the protobuf definitions are returned without the oneof set
+fn action(action: views::ActionView) -> proto::Action {
+    let action = match action {
+        views::ActionView::CreateAccount => {
+            Some(proto::action::Action::CreateAccount(proto::Empty {}))
+        }
+        views::ActionView::DeployContract { code } => Some(proto::action::Action::DeployContract(
+            proto::DeployContractAction { code },
+        )),
+        views::ActionView::FunctionCall {
+            method_name,
+            args,
+            gas,
+            deposit,
+        } => Some(proto::action::Action::FunctionCall(
+            proto::FunctionCallAction {
+                method_name,
+                args: args.to_vec(),
+                gas,
+                deposit: deposit.to_string(),
+            },
+        )),
+        views::ActionView::Transfer { deposit } => {
+            Some(proto::action::Action::Transfer(proto::TransferAction {
+                deposit: deposit.to_string(),
+            }))
+        }
+        views::ActionView::Stake { stake, public_key } => {
+            Some(proto::action::Action::Stake(proto::StakeAction {
+                stake: stake.to_string(),
+                public_key: public_key.to_string(),
+            }))
+        }
+        views::ActionView::AddKey {
+            public_key,
+            access_key,
+        } => Some(proto::action::Action::AddKey(proto::AddKeyAction {
+            public_key: public_key.to_string(),
+            access_key: Some(self::access_key(access_key)),
+        })),
+        views::ActionView::DeleteKey { public_key } => {
+            Some(proto::action::Action::DeleteKey(proto::DeleteKeyAction {
+                public_key: public_key.to_string(),
+            }))
+        }
+        views::ActionView::DeleteAccount { beneficiary_id } => Some(
+            proto::action::Action::DeleteAccount(proto::DeleteAccountAction {
+                beneficiary_id: beneficiary_id.to_string(),
+            }),
+        ),
+        views::ActionView::Delegate {
+            delegate_action,
+            signature,
+        } => Some(proto::action::Action::Delegate(proto::DelegateAction {
+            sender_id: delegate_action.sender_id.to_string(),
+            receiver_id: delegate_action.receiver_id.to_string(),
+            actions: delegate_action
+                .actions
+                .into_iter()
+                .map(|non_delegate_action| {
+                    self::action(views::ActionView::from(
+                        near_primitives::transaction::Action::from(non_delegate_action),
+                    ))
+                })
+                .collect(),
+            nonce: delegate_action.nonce,
+            max_block_height: delegate_action.max_block_height,
+            public_key: delegate_action.public_key.to_string(),
+            signature: signature.to_string(),
+        })),
+        #[allow(unreachable_patterns)]
+        _ => None,
+    };
+    proto::Action { action }
+}
+
+pub(super) fn receipt_response(receipt: views::ReceiptView) -> proto::ReceiptResponse {
+    let receipt_enum = match receipt.receipt {
+        views::ReceiptEnumView::Action {
+            signer_id,
+            signer_public_key,
+            gas_price,
+            output_data_receivers,
+            input_data_ids,
+            actions,
+            ..
+        } => proto::receipt_response::Receipt::Action(proto::ActionReceipt {
+            signer_id: signer_id.to_string(),
+            signer_public_key: signer_public_key.to_string(),
+            gas_price: gas_price.to_string(),
+            output_data_receivers: output_data_receivers
+                .into_iter()
+                .map(|data_receiver| proto::DataReceiver {
+                    data_id: data_receiver.data_id.0.to_vec(),
+                    receiver_id: data_receiver.receiver_id.to_string(),
+                })
+                .collect(),
+            input_data_ids: input_data_ids
+                .iter()
+                .map(|data_id| data_id.0.to_vec())
+                .collect(),
+            actions: actions.into_iter().map(action).collect(),
+        }),
+        views::ReceiptEnumView::Data { data_id, data, .. } => {
+            proto::receipt_response::Receipt::Data(proto::DataReceipt {
+                data_id: data_id.0.to_vec(),
+                data,
+            })
+        }
+    };
+    proto::ReceiptResponse {
+        receipt_id: receipt.receipt_id.0.to_vec(),
+        predecessor_id: receipt.predecessor_id.to_string(),
+        receiver_id: receipt.receiver_id.to_string(),
+        receipt: Some(receipt_enum),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const HASH: &str = "11111111111111111111111111111111";
+    const PUBLIC_KEY: &str = "ed25519:11111111111111111111111111111111";
+    const SIGNATURE: &str =
+        "ed25519:1111111111111111111111111111111111111111111111111111111111111111";
+
+    fn hash_bytes() -> Vec<u8> {
+        near_primitives::hash::CryptoHash::default().0.to_vec()
+    }
+
+    fn json_block_reference(
+        reference: Option<proto::block_reference::Reference>,
+    ) -> serde_json::Value {
+        serde_json::to_value(block_reference(Some(proto::BlockReference { reference })).unwrap())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_block_reference_matches_json_rpc() {
+        assert_eq!(
+            serde_json::to_value(block_reference(None).unwrap()).unwrap(),
+            json!({"finality": "final"})
+        );
+        assert_eq!(json_block_reference(None), json!({"finality": "final"}));
+        assert_eq!(
+            json_block_reference(Some(proto::block_reference::Reference::Finality(
+                proto::Finality::Optimistic as i32
+            ))),
+            json!({"finality": "optimistic"})
+        );
+        assert_eq!(
+            json_block_reference(Some(proto::block_reference::Reference::Finality(
+                proto::Finality::Doomslug as i32
+            ))),
+            json!({"finality": "near-final"})
+        );
+        assert_eq!(
+            json_block_reference(Some(proto::block_reference::Reference::BlockHeight(
+                118875450
+            ))),
+            json!({"block_id": 118875450})
+        );
+        assert_eq!(
+            json_block_reference(Some(proto::block_reference::Reference::BlockHash(
+                hash_bytes()
+            ))),
+            json!({"block_id": HASH})
+        );
+    }
+
+    #[test]
+    fn test_invalid_block_reference() {
+        let status = block_reference(Some(proto::BlockReference {
+            reference: Some(proto::block_reference::Reference::BlockHash(vec![0; 31])),
+        }))
+        .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+        let status = block_reference(Some(proto::BlockReference {
+            reference: Some(proto::block_reference::Reference::Finality(42)),
+        }))
+        .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+    }
+
+    #[cfg(not(feature = "tx-only"))]
+    #[test]
+    fn test_query_request_round_trip() {
+        let requests = [
+            (
+                proto::query_request::Request::ViewAccount(proto::ViewAccountRequest {
+                    account_id: "contract.near".to_string(),
+                }),
+                json!({"request_type": "view_account", "account_id": "contract.near"}),
+            ),
+            (
+                proto::query_request::Request::ViewState(proto::ViewStateRequest {
+                    account_id: "contract.near".to_string(),
+                    prefix: b"state".to_vec(),
+                    include_proof: true,
+                }),
+                json!({
+                    "request_type": "view_state",
+                    "account_id": "contract.near",
+                    "prefix_base64": "c3RhdGU=",
+                    "include_proof": true,
+                }),
+            ),
+            (
+                proto::query_request::Request::ViewAccessKey(proto::ViewAccessKeyRequest {
+                    account_id: "contract.near".to_string(),
+                    public_key: PUBLIC_KEY.to_string(),
+                }),
+                json!({
+                    "request_type": "view_access_key",
+                    "account_id": "contract.near",
+                    "public_key": PUBLIC_KEY,
+                }),
+            ),
+            (
+                proto::query_request::Request::CallFunction(proto::CallFunctionRequest {
+                    account_id: "contract.near".to_string(),
+                    method_name: "get_status".to_string(),
+                    args: b"{}".to_vec(),
+                }),
+                json!({
+                    "request_type": "call_function",
+                    "account_id": "contract.near",
+                    "method_name": "get_status",
+                    "args_base64": "e30=",
+                }),
+            ),
+        ];
+        for (request, mut expected) in requests {
+            let request_view = query_request(proto::QueryRequest {
+                block_reference: Some(proto::BlockReference {
+                    reference: Some(proto::block_reference::Reference::BlockHeight(118875450)),
+                }),
+                request: Some(request),
+            })
+            .unwrap();
+            expected["block_id"] = json!(118875450);
+            assert_eq!(serde_json::to_value(&request_view).unwrap(), expected);
+            // The same request sent to JSON-RPC is parsed into the same view
+            let json_rpc_request =
+                <near_jsonrpc::primitives::types::query::RpcQueryRequest as near_jsonrpc::RpcRequest>::parse(expected)
+                    .unwrap();
+            assert_eq!(
+                serde_json::to_value(json_rpc_request).unwrap(),
+                serde_json::to_value(request_view).unwrap()
+            );
+        }
+    }
+
+    #[cfg(not(feature = "tx-only"))]
+    #[test]
+    fn test_invalid_query_request() {
+        let status = query_request(proto::QueryRequest {
+            block_reference: None,
+            request: None,
+        })
+        .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+        let status = query_request(proto::QueryRequest {
+            block_reference: None,
+            request: Some(proto::query_request::Request::ViewAccessKey(
+                proto::ViewAccessKeyRequest {
+                    account_id: "contract.near".to_string(),
+                    public_key: "not a key".to_string(),
+                },
+            )),
+        })
+        .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+    }
+
+    #[cfg(not(feature = "tx-only"))]
+    #[test]
+    fn test_query_response_matches_json_rpc_view() {
+        let account: views::AccountView = serde_json::from_value(json!({
+            "amount": "1000000000000000000000000",
+            "locked": "0",
+            "code_hash": HASH,
+            "storage_usage": 182,
+            "storage_paid_at": 0,
+        }))
+        .unwrap();
+        let response = query_response(near_jsonrpc::primitives::types::query::RpcQueryResponse {
+            kind: near_jsonrpc::primitives::types::query::QueryResponseKind::ViewAccount(account),
+            block_height: 118875450,
+            block_hash: near_primitives::hash::CryptoHash::default(),
+        });
+        assert_eq!(response.block_height, 118875450);
+        assert_eq!(response.block_hash, hash_bytes());
+        assert_eq!(
+            response.result,
+            Some(proto::query_response::Result::Account(proto::Account {
+                amount: "1000000000000000000000000".to_string(),
+                locked: "0".to_string(),
+                code_hash: hash_bytes(),
+                storage_usage: 182,
+            }))
+        );
+
+        let access_key_list: views::AccessKeyList = serde_json::from_value(json!({
+            "keys": [
+                {
+                    "public_key": PUBLIC_KEY,
+                    "access_key": {"nonce": 7, "permission": "FullAccess"},
+                },
+                {
+                    "public_key": PUBLIC_KEY,
+                    "access_key": {
+                        "nonce": 8,
+                        "permission": {
+                            "FunctionCall": {
+                                "allowance": null,
+                                "receiver_id": "contract.near",
+                                "method_names": ["get_status"],
+                            },
+                        },
+                    },
+                },
+            ],
+        }))
+        .unwrap();
+        let response = query_response(near_jsonrpc::primitives::types::query::RpcQueryResponse {
+            kind: near_jsonrpc::primitives::types::query::QueryResponseKind::AccessKeyList(
+                access_key_list,
+            ),
+            block_height: 118875450,
+            block_hash: near_primitives::hash::CryptoHash::default(),
+        });
+        assert_eq!(
+            response.result,
+            Some(proto::query_response::Result::AccessKeyList(
+                proto::AccessKeyList {
+                    keys: vec![
+                        proto::AccessKeyInfo {
+                            public_key: PUBLIC_KEY.to_string(),
+                            access_key: Some(proto::AccessKey {
+                                nonce: 7,
+                                permission: Some(proto::access_key::Permission::FullAccess(
+                                    proto::Empty {}
+                                )),
+                            }),
+                        },
+                        proto::AccessKeyInfo {
+                            public_key: PUBLIC_KEY.to_string(),
+                            access_key: Some(proto::AccessKey {
+                                nonce: 8,
+                                permission: Some(proto::access_key::Permission::FunctionCall(
+                                    proto::FunctionCallPermission {
+                                        allowance: None,
+                                        receiver_id: "contract.near".to_string(),
+                                        method_names: vec!["get_status".to_string()],
+                                    }
+                                )),
+                            }),
+                        },
+                    ],
+                }
+            ))
+        );
+    }
+
+    #[test]
+    fn test_tx_response_matches_json_rpc_view() {
+        let outcome: views::FinalExecutionOutcomeView = serde_json::from_value(json!({
+            "status": {"SuccessValue": "e30="},
+            "transaction": {
+                "signer_id": "signer.near",
+                "public_key": PUBLIC_KEY,
+                "nonce": 42,
+                "receiver_id": "contract.near",
+                "actions": [
+                    {"Transfer": {"deposit": "1"}},
+                    {
+                        "FunctionCall": {
+                            "method_name": "ft_transfer",
+                            "args": "e30=",
+                            "gas": 30000000000000u64,
+                            "deposit": "1",
+                        },
+                    },
+                ],
+                "signature": SIGNATURE,
+                "hash": HASH,
+            },
+            "transaction_outcome": {
+                "proof": [],
+                "block_hash": HASH,
+                "id": HASH,
+                "outcome": {
+                    "logs": ["log"],
+                    "receipt_ids": [HASH],
+                    "gas_burnt": 223182562500u64,
+                    "tokens_burnt": "22318256250000000000",
+                    "executor_id": "signer.near",
+                    "status": {"SuccessReceiptId": HASH},
+                    "metadata": {"version": 1, "gas_profile": null},
+                },
+            },
+            "receipts_outcome": [],
+        }))
+        .unwrap();
+        let response = tx_response(
+            near_jsonrpc::primitives::types::transactions::RpcTransactionResponse {
+                final_execution_outcome: Some(
+                    views::FinalExecutionOutcomeViewEnum::FinalExecutionOutcome(outcome),
+                ),
+                final_execution_status: views::TxExecutionStatus::Final,
+            },
+        );
+        assert_eq!(
+            response.final_execution_status,
+            proto::TxExecutionStatus::Final as i32
+        );
+        let outcome = response.outcome.unwrap();
+        assert_eq!(
+            outcome.status,
+            Some(proto::final_execution_outcome::Status::SuccessValue(
+                b"{}".to_vec()
+            ))
+        );
+        let transaction = outcome.transaction.unwrap();
+        assert_eq!(transaction.hash, hash_bytes());
+        assert_eq!(transaction.signer_id, "signer.near");
+        assert_eq!(transaction.public_key, PUBLIC_KEY);
+        assert_eq!(transaction.nonce, 42);
+        assert_eq!(transaction.signature, SIGNATURE);
+        assert_eq!(
+            transaction.actions,
+            vec![
+                proto::Action {
+                    action: Some(proto::action::Action::Transfer(proto::TransferAction {
+                        deposit: "1".to_string(),
+                    })),
+                },
+                proto::Action {
+                    action: Some(proto::action::Action::FunctionCall(
+                        proto::FunctionCallAction {
+                            method_name: "ft_transfer".to_string(),
+                            args: b"{}".to_vec(),
+                            gas: 30000000000000,
+                            deposit: "1".to_string(),
+                        }
+                    )),
+                },
+            ]
+        );
+        assert_eq!(
+            outcome.transaction_outcome,
+            Some(proto::ExecutionOutcome {
+                id: hash_bytes(),
+                block_hash: hash_bytes(),
+                executor_id: "signer.near".to_string(),
+                logs: vec!["log".to_string()],
+                receipt_ids: vec![hash_bytes()],
+                gas_burnt: 223182562500,
+                tokens_burnt: "22318256250000000000".to_string(),
+                status: Some(proto::execution_outcome::Status::SuccessReceiptId(
+                    hash_bytes()
+                )),
+            })
+        );
+        assert!(outcome.receipts_outcome.is_empty());
+    }
+
+    #[test]
+    fn test_receipt_response_matches_json_rpc_view() {
+        let receipt: views::ReceiptView = serde_json::from_value(json!({
+            "predecessor_id": "signer.near",
+            "receiver_id": "contract.near",
+            "receipt_id": HASH,
+            "receipt": {
+                "Action": {
+                    "signer_id": "signer.near",
+                    "signer_public_key": PUBLIC_KEY,
+                    "gas_price": "100000000",
+                    "output_data_receivers": [
+                        {"data_id": HASH, "receiver_id": "other.near"},
+                    ],
+                    "input_data_ids": [HASH],
+                    "actions": [{"DeleteKey": {"public_key": PUBLIC_KEY}}],
+                },
+            },
+        }))
+        .unwrap();
+        assert_eq!(
+            receipt_response(receipt),
+            proto::ReceiptResponse {
+                receipt_id: hash_bytes(),
+                predecessor_id: "signer.near".to_string(),
+                receiver_id: "contract.near".to_string(),
+                receipt: Some(proto::receipt_response::Receipt::Action(
+                    proto::ActionReceipt {
+                        signer_id: "signer.near".to_string(),
+                        signer_public_key: PUBLIC_KEY.to_string(),
+                        gas_price: "100000000".to_string(),
+                        output_data_receivers: vec![proto::DataReceiver {
+                            data_id: hash_bytes(),
+                            receiver_id: "other.near".to_string(),
+                        }],
+                        input_data_ids: vec![hash_bytes()],
+                        actions: vec![proto::Action {
+                            action: Some(proto::action::Action::DeleteKey(
+                                proto::DeleteKeyAction {
+                                    public_key: PUBLIC_KEY.to_string(),
+                                }
+                            )),
+                        }],
+                    }
+                )),
+            }
+        );
+
+        let receipt: views::ReceiptView = serde_json::from_value(json!({
+            "predecessor_id": "signer.near",
+            "receiver_id": "contract.near",
+            "receipt_id": HASH,
+            "receipt": {"Data": {"data_id": HASH, "data": "e30="}},
+        }))
+        .unwrap();
+        assert_eq!(
+            receipt_response(receipt).receipt,
+            Some(proto::receipt_response::Receipt::Data(proto::DataReceipt {
+                data_id: hash_bytes(),
+                data: Some(b"{}".to_vec()),
+            }))
+        );
+    }
+
+    #[test]
+    fn test_rpc_error_status_code() {
+        let status = rpc_error_status(
+            near_jsonrpc::primitives::types::receipts::RpcReceiptError::UnknownReceipt {
+                receipt_id: near_primitives::hash::CryptoHash::default(),
+            },
+        );
+        assert_eq!(status.code(), tonic::Code::NotFound);
+        let error: serde_json::Value = serde_json::from_str(status.message()).unwrap();
+        assert_eq!(error["cause"]["name"], "UNKNOWN_RECEIPT");
+    }
+}
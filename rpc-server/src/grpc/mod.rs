@@ -0,0 +1,181 @@
+use crate::config::ServerContext;
+use actix_web::web::Data;
+
+mod convert;
+
+pub(crate) mod proto {
+    tonic::include_proto!("read_rpc.v1");
+}
+
+/// Serves the gRPC API along with JSON-RPC. The methods are handled by the same handlers
+/// as the JSON-RPC ones, only the request and the response are converted from/to protobuf
+pub(crate) async fn serve(
+    data: Data<ServerContext>,
+    port: u16,
+    max_request_payload_size: usize,
+) -> anyhow::Result<()> {
+    let address = std::net::SocketAddr::from(([0, 0, 0, 0], port));
+    let max_response_size = data.max_response_size;
+    let service = proto::read_rpc_server::ReadRpcServer::new(ReadRpcService { data })
+        .max_decoding_message_size(max_request_payload_size)
+        .max_encoding_message_size(max_response_size);
+    tracing::info!("gRPC API is served on {}", address);
+    tonic::transport::Server::builder()
+        .add_service(service)
+        .serve(address)
+        .await?;
+    Ok(())
+}
+
+struct ReadRpcService {
+    data: Data<ServerContext>,
+}
+
+impl ReadRpcService {
+    // The access rules are the same as for the JSON-RPC methods,
+    // the API key is sent in the metadata with the same name as the header
+    fn authorize<T>(
+        &self,
+        request: &tonic::Request<T>,
+        method_name: &str,
+    ) -> Result<(), tonic::Status> {
+        if cfg!(feature = "tx-only") && !crate::rpc_methods::is_tx_only_method(method_name) {
+            return Err(tonic::Status::unimplemented(format!(
+                "Method `{}` is not served by the tx-only build",
+                method_name
+            )));
+        }
+        if self.data.method_access.is_disabled(method_name) {
+            return Err(tonic::Status::unimplemented(format!(
+                "Method `{}` is disabled by the operator",
+                method_name
+            )));
+        }
+        let key = request
+            .metadata()
+            .get(crate::api_keys::API_KEY_HEADER)
+            .and_then(|value| value.to_str().ok());
+        self.data
+            .api_keys
+            .check(key, method_name)
+            .map_err(|(code, message)| match code {
+                crate::errors::HttpErrorCode::MethodNotAllowed => {
+                    tonic::Status::permission_denied(message)
+                }
+                _ => tonic::Status::unauthenticated(message),
+            })
+    }
+
+    async fn call<T, R, F>(
+        &self,
+        request: tonic::Request<T>,
+        method_name: &str,
+        handler: impl FnOnce(Data<ServerContext>, T) -> F,
+    ) -> Result<tonic::Response<R>, tonic::Status>
+    where
+        F: std::future::Future<Output = Result<R, tonic::Status>>,
+    {
+        let result = match self.authorize(&request, method_name) {
+            Ok(()) => handler(self.data.clone(), request.into_inner()).await,
+            Err(status) => Err(status),
+        };
+        let code = match &result {
+            Ok(_) => tonic::Code::Ok,
+            Err(status) => status.code(),
+        };
+        crate::metrics::GRPC_CALLS_TOTAL
+            .with_label_values(&[method_name, &format!("{:?}", code)])
+            .inc();
+        result.map(tonic::Response::new)
+    }
+}
+
+#[tonic::async_trait]
+impl proto::read_rpc_server::ReadRpc for ReadRpcService {
+    async fn query(
+        &self,
+        request: tonic::Request<proto::QueryRequest>,
+    ) -> Result<tonic::Response<proto::QueryResponse>, tonic::Status> {
+        #[cfg(feature = "tx-only")]
+        return self
+            .call(request, "query", |_, _| async move {
+                Err(tonic::Status::unimplemented(
+                    "Method `query` is not served by the tx-only build",
+                ))
+            })
+            .await;
+        #[cfg(not(feature = "tx-only"))]
+        self.call(request, "query", |data, request| async move {
+            let response =
+                crate::modules::queries::methods::query(data, convert::query_request(request)?)
+                    .await
+                    .map_err(convert::rpc_error_status)?;
+            Ok(convert::query_response(response))
+        })
+        .await
+    }
+
+    async fn block(
+        &self,
+        request: tonic::Request<proto::BlockRequest>,
+    ) -> Result<tonic::Response<proto::BlockResponse>, tonic::Status> {
+        self.call(request, "block", |data, request| async move {
+            let request_data = near_jsonrpc::primitives::types::blocks::RpcBlockRequest {
+                block_reference: convert::block_reference(request.block_reference)?,
+            };
+            let response = crate::modules::blocks::methods::block(data, request_data)
+                .await
+                .map_err(convert::rpc_error_status)?;
+            Ok(convert::block_response(response.block_view))
+        })
+        .await
+    }
+
+    async fn tx(
+        &self,
+        request: tonic::Request<proto::TxRequest>,
+    ) -> Result<tonic::Response<proto::TxResponse>, tonic::Status> {
+        self.call(request, "tx", |data, request| async move {
+            let request_data =
+                near_jsonrpc::primitives::types::transactions::RpcTransactionStatusRequest {
+                    transaction_info:
+                        near_jsonrpc::primitives::types::transactions::TransactionInfo::TransactionId {
+                            tx_hash: convert::crypto_hash(&request.tx_hash, "tx_hash")?,
+                            sender_account_id: convert::account_id(
+                                request.sender_account_id,
+                                "sender_account_id",
+                            )?,
+                        },
+                    wait_until: Default::default(),
+                };
+            let response = crate::modules::transactions::methods::tx(data, request_data)
+                .await
+                .map_err(convert::rpc_error_status)?;
+            Ok(convert::tx_response(response))
+        })
+        .await
+    }
+
+    async fn receipt(
+        &self,
+        request: tonic::Request<proto::ReceiptRequest>,
+    ) -> Result<tonic::Response<proto::ReceiptResponse>, tonic::Status> {
+        self.call(
+            request,
+            "EXPERIMENTAL_receipt",
+            |data, request| async move {
+                let request_data = near_jsonrpc::primitives::types::receipts::RpcReceiptRequest {
+                    receipt_reference:
+                        near_jsonrpc::primitives::types::receipts::ReceiptReference {
+                            receipt_id: convert::crypto_hash(&request.receipt_id, "receipt_id")?,
+                        },
+                };
+                let response = crate::modules::receipts::methods::receipt(data, request_data)
+                    .await
+                    .map_err(convert::rpc_error_status)?;
+                Ok(convert::receipt_response(response.receipt_view))
+            },
+        )
+        .await
+    }
+}
@@ -16,6 +16,9 @@ pub struct RPCHealthStatusResponse {
     max_compiled_codes_cache_size: String,
     current_compiled_codes_cache_size: String,
 
+    max_caches_memory_budget: String,
+    current_caches_memory_usage: String,
+
     final_block_height: u64,
 }
 
@@ -58,6 +61,13 @@ impl RPCHealthStatusResponse {
                     .await,
             ),
 
+            max_caches_memory_budget: friendly_memory_size_format(
+                server_context.caches_memory_budget.max_size(),
+            ),
+            current_caches_memory_usage: friendly_memory_size_format(
+                server_context.caches_memory_budget.current_size(),
+            ),
+
             final_block_height: server_context
                 .blocks_info_by_finality
                 .final_cache_block()
@@ -0,0 +1,147 @@
+use futures::StreamExt;
+
+// Interval between the attempts to listen to the notifications after the failure
+const RELISTEN_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5);
+/// Number of the finalized transactions notifications buffered for the WebSocket connections
+const FINALIZED_TRANSACTIONS_CHANNEL_CAPACITY: usize = 256;
+
+#[derive(Debug, Clone, Copy)]
+pub struct IndexedHead {
+    pub block_height: near_primitives::types::BlockHeight,
+    /// Block timestamp in nanoseconds
+    pub block_timestamp: u64,
+}
+
+/// Latest blocks processed by the indexers of every type ("tx" or "state"),
+/// updated by the notifications of the indexers. While the notifications are received,
+/// the rpc-server doesn't poll the `meta` table for the indexers progress
+#[derive(Debug)]
+pub struct IndexedHeads {
+    heads: std::sync::RwLock<std::collections::HashMap<String, IndexedHead>>,
+    listening: std::sync::atomic::AtomicBool,
+    /// Hashes of the transactions finalized by the tx-indexer for the WebSocket subscriptions
+    pub finalized_transactions_sender:
+        tokio::sync::broadcast::Sender<std::sync::Arc<Vec<near_primitives::hash::CryptoHash>>>,
+}
+
+impl Default for IndexedHeads {
+    fn default() -> Self {
+        Self {
+            heads: Default::default(),
+            listening: Default::default(),
+            finalized_transactions_sender: tokio::sync::broadcast::channel(
+                FINALIZED_TRANSACTIONS_CHANNEL_CAPACITY,
+            )
+            .0,
+        }
+    }
+}
+
+impl IndexedHeads {
+    /// Returns the heads if they are kept up to date by the notifications
+    pub fn heads(&self) -> Option<std::collections::HashMap<String, IndexedHead>> {
+        if !self.listening.load(std::sync::atomic::Ordering::Relaxed) {
+            return None;
+        }
+        let heads = self.heads.read().expect("indexed heads lock poisoned");
+        if heads.is_empty() {
+            None
+        } else {
+            Some(heads.clone())
+        }
+    }
+
+    // The indexers scaled in a group process the blocks in parallel,
+    // so the latest block of the whole group is kept
+    fn update(&self, indexer_type: String, head: IndexedHead) {
+        let mut heads = self.heads.write().expect("indexed heads lock poisoned");
+        let current_head = heads.entry(indexer_type).or_insert(head);
+        if head.block_height > current_head.block_height {
+            *current_head = head;
+        }
+    }
+
+    fn set_listening(&self, listening: bool) {
+        self.listening
+            .store(listening, std::sync::atomic::Ordering::Relaxed);
+    }
+}
+
+/// Listens to the Postgres notifications of the indexers until the server stops.
+/// The notifications missed while the connection is restored are caught up by the next block
+pub(crate) async fn listen_indexer_notifications(
+    data: actix_web::web::Data<crate::config::ServerContext>,
+) {
+    let indexed_heads = &data.indexed_heads;
+    loop {
+        match data.db_manager.listen_indexer_notifications().await {
+            Ok(mut notifications) => {
+                tracing::info!("Listening to the indexer notifications");
+                indexed_heads.set_listening(true);
+                while let Some(notification) = notifications.next().await {
+                    match notification {
+                        Ok(notification) => handle_notification(&data, notification).await,
+                        Err(err) => {
+                            tracing::warn!("Failed to receive the indexer notification: {:?}", err);
+                            break;
+                        }
+                    }
+                }
+                indexed_heads.set_listening(false);
+            }
+            Err(err) => {
+                tracing::warn!("Failed to listen to the indexer notifications: {:?}", err);
+            }
+        }
+        tokio::time::sleep(RELISTEN_INTERVAL).await;
+    }
+}
+
+async fn handle_notification(
+    data: &crate::config::ServerContext,
+    notification: database::primitives::IndexerNotification,
+) {
+    let indexed_heads = &data.indexed_heads;
+    match notification {
+        database::primitives::IndexerNotification::Head {
+            indexer_type,
+            block_height,
+            block_timestamp,
+        } => {
+            crate::metrics::INDEXER_NOTIFICATIONS
+                .with_label_values(&["head"])
+                .inc();
+            crate::metrics::observe_block_latency(
+                &format!("{}_indexed", indexer_type),
+                block_timestamp,
+            );
+            indexed_heads.update(
+                indexer_type,
+                IndexedHead {
+                    block_height,
+                    block_timestamp,
+                },
+            );
+            // The final block is still delivered by the lake streamer or Redis
+            // if it can't be read now
+            if let Err(err) = crate::utils::update_final_block_from_head(data, block_height).await {
+                tracing::debug!(
+                    "Failed to update the final block from the head notification: {:?}",
+                    err
+                );
+            }
+        }
+        database::primitives::IndexerNotification::TransactionsFinalized { transaction_hashes } => {
+            crate::metrics::INDEXER_NOTIFICATIONS
+                .with_label_values(&["transactions"])
+                .inc_by(transaction_hashes.len() as u64);
+            tracing::debug!("Transactions are finalized: {:?}", transaction_hashes);
+            // The hashes are sent only if there are WebSocket connections to notify
+            if indexed_heads.finalized_transactions_sender.receiver_count() > 0 {
+                let _ = indexed_heads
+                    .finalized_transactions_sender
+                    .send(std::sync::Arc::new(transaction_hashes));
+            }
+        }
+    }
+}
@@ -1,17 +1,46 @@
+#[cfg(not(feature = "profiling"))]
 use mimalloc::MiMalloc;
 
+#[cfg(not(feature = "profiling"))]
 #[global_allocator]
 static GLOBAL: MiMalloc = MiMalloc;
 
+#[cfg(feature = "profiling")]
+#[global_allocator]
+static GLOBAL: profiling::Jemalloc = profiling::Jemalloc;
+
+#[cfg(feature = "profiling")]
+#[allow(non_upper_case_globals)]
+#[export_name = "malloc_conf"]
+pub static malloc_conf: &[u8] = profiling::MALLOC_CONF;
+
 #[macro_use]
 extern crate lazy_static;
 
+mod access_sampling;
+mod admin;
+mod api_keys;
+mod block_prefetch;
 mod cache;
 mod config;
+mod connections;
+mod errors;
+#[cfg(test)]
+mod golden;
+#[cfg(feature = "grpc")]
+mod grpc;
 mod health;
+mod indexer_notifications;
+mod method_access;
 mod metrics;
+mod min_block_height;
 mod modules;
+mod network_config;
+mod outcome_logs;
+mod params;
+mod rpc_methods;
 mod utils;
+mod ws;
 
 // Categories for logging
 pub(crate) const RPC_SERVER: &str = "read_rpc_server";
@@ -46,189 +75,157 @@ where
     serialize_response(callback(R::parse(request.params)?).await?)
 }
 
+/// Counts the bytes written by the serializer without allocating the serialized response
+#[derive(Default)]
+struct ByteCounter(usize);
+
+impl std::io::Write for ByteCounter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0 += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Replaces the response exceeding the configured size with the error
+/// advising to paginate the request instead of sending it to the client.
+fn check_response_size(
+    value: serde_json::Value,
+    max_response_size: usize,
+) -> Result<serde_json::Value, near_jsonrpc::primitives::errors::RpcError> {
+    let mut counter = ByteCounter::default();
+    serde_json::to_writer(&mut counter, &value).map_err(|err| {
+        near_jsonrpc::primitives::errors::RpcError::serialization_error(err.to_string())
+    })?;
+    if counter.0 > max_response_size {
+        let error_message = format!(
+            "Response size {} bytes exceeds the limit of {} bytes. \
+            Please, use the paginated method or narrow down the request",
+            counter.0, max_response_size
+        );
+        return Err(
+            near_jsonrpc::primitives::errors::RpcError::new_handler_error(
+                Some(serde_json::Value::String(error_message.clone())),
+                serde_json::json!({
+                    "name": "RESPONSE_TOO_LARGE",
+                    "info": {
+                        "response_size": counter.0,
+                        "max_response_size": max_response_size,
+                        "error_message": error_message,
+                    }
+                }),
+            ),
+        );
+    }
+    Ok(value)
+}
+
 async fn rpc_handler(
+    req: actix_web::HttpRequest,
     data: actix_web::web::Data<config::ServerContext>,
     payload: actix_web::web::Json<near_jsonrpc::primitives::message::Message>,
 ) -> actix_web::HttpResponse {
-    let near_jsonrpc::primitives::message::Message::Request(request) = payload.0 else {
-        return actix_web::HttpResponse::BadRequest().finish();
+    let near_jsonrpc::primitives::message::Message::Request(mut request) = payload.0 else {
+        return errors::error_response(
+            &req,
+            errors::HttpErrorCode::InvalidRequest,
+            "JSON-RPC message is not a request",
+        );
     };
 
     let id = request.id.clone();
 
     let method_name = request.method.clone();
-    let mut method_not_found = false;
-
-    let result = match method_name.as_ref() {
-        // custom request methods
-        "view_state_paginated" => {
-            if let Ok(request_data) = serde_json::from_value(request.params) {
-                serialize_response(
-                    modules::state::methods::view_state_paginated(data, request_data).await,
-                )
-            } else {
-                Err(near_jsonrpc::primitives::errors::RpcError::parse_error(
-                    "Failed to parse request data".to_string(),
-                ))
-            }
-        }
-        "view_receipt_record" => {
-            process_method_call(request, |params| {
-                modules::receipts::methods::view_receipt_record(data, params)
-            })
-            .await
-        }
-        // request methods
-        "query" => {
-            process_method_call(request, |params| {
-                modules::queries::methods::query(data, params)
-            })
-            .await
-        }
-        // basic requests methods
-        "block" => {
-            process_method_call(request, |params| {
-                modules::blocks::methods::block(data, params)
-            })
-            .await
-        }
-        "broadcast_tx_async" => {
-            process_method_call(request, |params| {
-                modules::transactions::methods::broadcast_tx_async(data, params)
-            })
-            .await
-        }
-        "broadcast_tx_commit" => {
-            process_method_call(request, |params| {
-                modules::transactions::methods::broadcast_tx_commit(data, params)
-            })
-            .await
-        }
-        "chunk" => {
-            process_method_call(request, |params| {
-                modules::blocks::methods::chunk(data, params)
-            })
-            .await
-        }
-        "gas_price" => {
-            process_method_call(request, |params| {
-                modules::gas::methods::gas_price(data, params)
-            })
-            .await
-        }
-        "health" => {
-            process_method_call(request, |_: ()| modules::network::methods::health(data)).await
-        }
-        "light_client_proof" => {
-            process_method_call(request, |params| {
-                modules::clients::methods::light_client_proof(data, params)
-            })
-            .await
-        }
-        "next_light_client_block" => {
-            process_method_call(request, |params| {
-                modules::clients::methods::next_light_client_block(data, params)
-            })
-            .await
-        }
-        "network_info" => {
-            process_method_call(request, |_: ()| {
-                modules::network::methods::network_info(data)
-            })
-            .await
-        }
-        "send_tx" => {
-            process_method_call(request, |params| {
-                modules::transactions::methods::send_tx(data, params)
-            })
-            .await
-        }
-        "status" => {
-            process_method_call(request, |_: ()| modules::network::methods::status(data)).await
-        }
-        "tx" => {
-            process_method_call(request, |params| {
-                modules::transactions::methods::tx(data, params)
-            })
-            .await
-        }
-        "validators" => {
-            process_method_call(request, |params| {
-                modules::network::methods::validators(data, params)
-            })
-            .await
-        }
-        "client_config" => {
-            process_method_call(request, |_: ()| {
-                modules::network::methods::client_config(data)
-            })
-            .await
-        }
-        "EXPERIMENTAL_changes" => {
-            process_method_call(request, |params| {
-                modules::blocks::methods::changes_in_block_by_type(data, params)
-            })
-            .await
-        }
-        "EXPERIMENTAL_changes_in_block" => {
-            process_method_call(request, |params| {
-                modules::blocks::methods::changes_in_block(data, params)
-            })
-            .await
-        }
-        "EXPERIMENTAL_genesis_config" => {
-            process_method_call(request, |_: ()| {
-                modules::network::methods::genesis_config(data)
-            })
-            .await
+    if let Err(response) = data.api_keys.authorize(&req, &method_name) {
+        return response;
+    }
+    // Keep the reads monotonic for the clients balanced between the replicas.
+    // If the server doesn't reach the block seen by the client in time,
+    // the standard methods are proxied to the NEAR RPC node and the custom ones are rejected
+    let not_reached_min_block_height = match min_block_height::requested_min_block_height(&req) {
+        Ok(Some(min_block_height)) => {
+            let reached = min_block_height::wait_for_block_height(
+                &data.blocks_info_by_finality,
+                min_block_height,
+                data.min_block_height_wait,
+            )
+            .await;
+            Some(min_block_height).filter(|_| !reached)
         }
-        "EXPERIMENTAL_light_client_proof" => {
-            process_method_call(request, |params| {
-                modules::clients::methods::light_client_proof(data, params)
-            })
-            .await
+        Ok(None) => None,
+        Err(response) => return response,
+    };
+    // The logs truncation opt-out is not a part of the method params
+    let truncate_logs = outcome_logs::METHODS_WITH_LOGS.contains(&method_name.as_str())
+        && outcome_logs::take_truncate_logs_param(&mut request.params);
+    let mut method_not_found = false;
+    let max_response_size = data.max_response_size;
+    // The mismatches are counted in the lenient mode too,
+    // so the clients are checked before switching to the strict mode
+    let params_error = match params::validate(&method_name, &request.params) {
+        Ok(()) => None,
+        Err(error_message) => {
+            metrics::INVALID_PARAMS_TOTAL
+                .with_label_values(&[&method_name])
+                .inc();
+            tracing::debug!(
+                target: RPC_SERVER,
+                "Invalid params of `{}`: {}",
+                method_name,
+                error_message
+            );
+            Some(error_message).filter(|_| data.strict_params)
         }
-        "EXPERIMENTAL_protocol_config" => {
-            process_method_call(request, |params| {
-                modules::network::methods::protocol_config(data, params)
-            })
-            .await
+    };
+
+    let result = match rpc_methods::rpc_method(&method_name) {
+        _ if cfg!(feature = "tx-only") && !rpc_methods::is_tx_only_method(&method_name) => {
+            method_not_found = true;
+            Err(near_jsonrpc::primitives::errors::RpcError::method_not_found(method_name.clone()))
         }
-        "EXPERIMENTAL_receipt" => {
-            process_method_call(request, |params| {
-                modules::receipts::methods::receipt(data, params)
-            })
-            .await
+        _ if data.method_access.is_disabled(&method_name) => {
+            Err(method_access::method_disabled_error(&method_name))
         }
-        "EXPERIMENTAL_tx_status" => {
-            process_method_call(request, |params| {
-                modules::transactions::methods::tx_status(data, params)
-            })
-            .await
+        _ if params_error.is_some() => {
+            Err(near_jsonrpc::primitives::errors::RpcError::invalid_params(
+                params_error.unwrap_or_default(),
+            ))
         }
-        "EXPERIMENTAL_validators_ordered" => {
-            process_method_call(request, |params| {
-                modules::network::methods::validators_ordered(data, params)
-            })
-            .await
+        Some(rpc_method) if not_reached_min_block_height.is_some() && rpc_method.custom => {
+            Err(min_block_height::not_reached_error(
+                not_reached_min_block_height.unwrap_or_default(),
+                min_block_height::latest_block_height(&data.blocks_info_by_finality).await,
+                data.min_block_height_wait,
+            ))
         }
-        "EXPERIMENTAL_maintenance_windows" => {
-            process_method_call(request, |_: ()| {
-                modules::network::methods::maintenance_windows(data)
-            })
-            .await
+        // The custom methods are not known to the NEAR RPC node and are rejected above
+        _ if not_reached_min_block_height.is_some() => {
+            min_block_height::proxy(&data.near_rpc_client, &method_name, request.params).await
         }
-        "EXPERIMENTAL_split_storage_info" => {
-            process_method_call(request, |_: ()| {
-                modules::network::methods::split_storage_info(data)
+        Some(rpc_method) => {
+            (rpc_method.handler)(rpc_methods::MethodCall {
+                req: req.clone(),
+                data: data.clone(),
+                request,
             })
             .await
         }
-        _ => {
+        None => {
             method_not_found = true;
             Err(near_jsonrpc::primitives::errors::RpcError::method_not_found(method_name.clone()))
         }
     };
+    let result = match data.max_outcome_logs_size {
+        Some(max_logs_size) if truncate_logs => result.map(|mut value| {
+            outcome_logs::truncate_outcomes_logs(&mut value, max_logs_size);
+            value
+        }),
+        _ => result,
+    };
+    let result = result.and_then(|value| check_response_size(value, max_response_size));
 
     // increase METHOD_CALLS_COUNTER for each method call
     if method_not_found {
@@ -304,6 +301,12 @@ async fn rpc_handler(
         }
     };
 
+    response.insert_header((
+        min_block_height::BLOCK_HEIGHT_HEADER,
+        min_block_height::latest_block_height(&data.blocks_info_by_finality)
+            .await
+            .to_string(),
+    ));
     response.json(near_jsonrpc::primitives::message::Message::response(
         id,
         result.map_err(near_jsonrpc::primitives::errors::RpcError::from),
@@ -312,31 +315,129 @@ async fn rpc_handler(
 
 #[actix_web::main]
 async fn main() -> anyhow::Result<()> {
-    configuration::init_tracing(RPC_SERVER).await?;
+    let opts = <config::Opts as clap::Parser>::parse();
+    let log_level_handle =
+        actix_web::web::Data::new(configuration::init_tracing(RPC_SERVER).await?);
     tracing::info!(
         "Starting {} v{}",
         env!("CARGO_PKG_NAME"),
         env!("CARGO_PKG_VERSION"),
     );
 
-    let rpc_server_config =
+    let mut rpc_server_config =
         configuration::read_configuration::<configuration::RpcServerConfig>().await?;
+    if let Some(database_backend) = opts.database_backend {
+        rpc_server_config.database.backend = database_backend;
+    }
 
+    let http_client_factory = http_client::HttpClientFactory::new(&rpc_server_config.http_client)?;
     let near_rpc_client = utils::JsonRpcClient::new(
-        rpc_server_config.general.near_rpc_url.clone(),
-        rpc_server_config.general.near_archival_rpc_url.clone(),
+        &http_client_factory,
+        &rpc_server_config.general.near_rpc_url,
+        rpc_server_config.general.near_archival_rpc_url.as_deref(),
     );
     // We want to set a custom referer to let NEAR JSON RPC nodes know that we are a read-rpc instance
-    let near_rpc_client = near_rpc_client.header(
-        "Referer".to_string(),
-        rpc_server_config.general.referer_header_value.clone(),
-    )?;
+    let near_rpc_client = near_rpc_client
+        .header("Referer", &rpc_server_config.general.referer_header_value)?
+        .api_key(rpc_server_config.general.near_rpc_api_key.as_deref())?;
+
+    readnode_primitives::blob::set_max_blob_size(rpc_server_config.general.max_blob_size);
 
     let server_port = rpc_server_config.general.server_port;
+    let max_request_payload_size = rpc_server_config.general.max_request_payload_size;
+    let http2_max_concurrent_streams = rpc_server_config.general.http2_max_concurrent_streams;
+    let http2 = rpc_server_config.general.http2;
+    let keep_alive_secs = rpc_server_config.general.keep_alive_secs;
+    let client_request_timeout =
+        std::time::Duration::from_millis(rpc_server_config.general.client_request_timeout_ms);
+    let max_connections = rpc_server_config.general.max_connections;
+    let grpc_port = rpc_server_config.general.grpc_port;
 
     let server_context = actix_web::web::Data::new(
         config::ServerContext::init(rpc_server_config.clone(), near_rpc_client.clone()).await?,
     );
+    modules::network::check_schema_compatibility(
+        &**server_context.db_manager,
+        rpc_server_config.general.allow_newer_schema,
+    )
+    .await?;
+    modules::network::check_network(&server_context, &rpc_server_config.general.chain_id).await?;
+    #[cfg(not(feature = "tx-only"))]
+    server_context
+        .db_manager
+        .check_state_key_buckets("check_state_key_buckets")
+        .await?;
+
+    // Warms up the blocks caches in the background, the server starts serving right away
+    let warm_up_context = server_context.clone();
+    let cache_warming_blocks = rpc_server_config.general.cache_warming_blocks;
+    let cache_warming_concurrency = rpc_server_config.general.cache_warming_concurrency;
+    tokio::spawn(async move {
+        modules::blocks::utils::warm_up_blocks_cache(
+            warm_up_context,
+            cache_warming_blocks,
+            cache_warming_concurrency,
+        )
+        .await
+    });
+
+    // Runs the periodic maintenance jobs until the server stops
+    let scheduler = database::scheduler::Scheduler::new();
+    let block_hashes_cache = std::sync::Arc::clone(&server_context.block_hashes_cache);
+    scheduler.schedule(
+        "block_hashes_cache_sweep",
+        database::scheduler::Schedule::every(std::time::Duration::from_secs(60))
+            .with_jitter(std::time::Duration::from_secs(10)),
+        move || {
+            let block_hashes_cache = std::sync::Arc::clone(&block_hashes_cache);
+            async move {
+                modules::blocks::utils::sweep_expired_block_hashes(&block_hashes_cache).await
+            }
+        },
+    );
+
+    if server_context.access_sampler.is_some() {
+        let access_sampling_context = server_context.clone();
+        scheduler.schedule(
+            "access_sampling_summary",
+            database::scheduler::Schedule::every(access_sampling::SUMMARY_INTERVAL),
+            move || {
+                let access_sampling_context = access_sampling_context.clone();
+                async move {
+                    if let Some(access_sampler) = &access_sampling_context.access_sampler {
+                        access_sampler.log_summary();
+                    }
+                    Ok(())
+                }
+            },
+        );
+    }
+
+    // Keeps the indexers progress and the final block up to date without polling the meta table
+    tokio::spawn(indexer_notifications::listen_indexer_notifications(
+        server_context.clone(),
+    ));
+
+    let db_manager = std::sync::Arc::clone(&server_context.db_manager);
+    let blocks_info_by_finality = std::sync::Arc::clone(&server_context.blocks_info_by_finality);
+    let indexed_heads = std::sync::Arc::clone(&server_context.indexed_heads);
+    scheduler.schedule(
+        "data_staleness",
+        database::scheduler::Schedule::every(std::time::Duration::from_secs(5)),
+        move || {
+            let db_manager = std::sync::Arc::clone(&db_manager);
+            let blocks_info_by_finality = std::sync::Arc::clone(&blocks_info_by_finality);
+            let indexed_heads = std::sync::Arc::clone(&indexed_heads);
+            async move {
+                metrics::update_data_staleness(
+                    &**db_manager,
+                    &blocks_info_by_finality,
+                    &indexed_heads,
+                )
+                .await
+            }
+        },
+    );
 
     let blocks_cache_clone = std::sync::Arc::clone(&server_context.blocks_cache);
     let blocks_info_by_finality_clone =
@@ -396,20 +497,74 @@ async fn main() -> anyhow::Result<()> {
         });
     }
 
-    actix_web::HttpServer::new(move || {
+    let method_access_context = server_context.clone();
+    tokio::spawn(async move {
+        if let Err(err) = method_access::reload_on_sighup(method_access_context).await {
+            tracing::error!("Method rules are not reloaded on SIGHUP: {:?}", err);
+        }
+    });
+
+    #[cfg(feature = "grpc")]
+    if let Some(grpc_port) = grpc_port {
+        let grpc_context = server_context.clone();
+        tokio::spawn(async move {
+            if let Err(err) = grpc::serve(grpc_context, grpc_port, max_request_payload_size).await {
+                tracing::error!("gRPC server stopped: {:?}", err);
+            }
+        });
+    }
+    #[cfg(not(feature = "grpc"))]
+    if grpc_port.is_some() {
+        tracing::warn!(
+            "`grpc_port` is ignored, the rpc-server is built without the `grpc` feature"
+        );
+    }
+
+    let http_server = actix_web::HttpServer::new(move || {
         let cors = actix_cors::Cors::permissive();
 
         actix_web::App::new()
+            .wrap_fn(move |req, service| {
+                connections::track_streams(req, service, http2_max_concurrent_streams)
+            })
             .wrap(cors)
             .wrap(tracing_actix_web::TracingLogger::default())
             .app_data(server_context.clone())
+            .app_data(log_level_handle.clone())
+            .app_data(
+                actix_web::web::JsonConfig::default()
+                    .limit(max_request_payload_size)
+                    .error_handler(errors::json_error_handler),
+            )
             .service(actix_web::web::scope("/").route("", actix_web::web::post().to(rpc_handler)))
             .service(metrics::get_metrics)
+            .service(metrics::get_metrics_meta)
             .service(health::get_health_status)
+            .service(network_config::get_network_config)
+            .service(admin::put_log_level)
+            .service(admin::get_log_level)
+            .service(admin::invalidate_cache)
+            .service(admin::get_methods)
+            .configure(|_config| {
+                #[cfg(feature = "profiling")]
+                admin::configure_profiling(_config);
+            })
+            .service(ws::subscribe)
+            .default_service(actix_web::web::to(errors::not_found))
     })
-    .bind(format!("0.0.0.0:{:0>5}", server_port))?
-    .run()
-    .await?;
+    .on_connect(connections::on_connect)
+    .keep_alive(connections::keep_alive(keep_alive_secs))
+    .client_request_timeout(client_request_timeout)
+    .max_connections(max_connections);
+
+    let server_address = format!("0.0.0.0:{:0>5}", server_port);
+    // HTTP/2 is served over cleartext along with HTTP/1.1, the protocol is detected by the preface
+    let http_server = if http2 {
+        http_server.bind_auto_h2c(server_address)?
+    } else {
+        http_server.bind(server_address)?
+    };
+    http_server.run().await?;
 
     Ok(())
 }
@@ -0,0 +1,196 @@
+/// Name of the handler error of the methods disabled by the operator
+pub(crate) const METHOD_DISABLED: &str = "METHOD_DISABLED";
+
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub(crate) struct MethodRules {
+    /// Methods served by the server, all the methods are served if not set
+    enabled_methods: Option<std::collections::BTreeSet<String>>,
+    /// Methods rejected even if they are enabled
+    disabled_methods: std::collections::BTreeSet<String>,
+}
+
+impl MethodRules {
+    /// The unknown method names are rejected, so the typo in the configuration
+    /// doesn't leave the method served
+    pub(crate) fn new(
+        enabled_methods: Option<Vec<String>>,
+        disabled_methods: Vec<String>,
+    ) -> anyhow::Result<Self> {
+        let rules = Self {
+            enabled_methods: enabled_methods.map(|methods| methods.into_iter().collect()),
+            disabled_methods: disabled_methods.into_iter().collect(),
+        };
+        let unknown_methods: std::collections::BTreeSet<_> = rules
+            .enabled_methods
+            .iter()
+            .flatten()
+            .chain(rules.disabled_methods.iter())
+            .filter(|method_name| !is_known_method(method_name))
+            .map(String::as_str)
+            .collect();
+        if !unknown_methods.is_empty() {
+            anyhow::bail!(
+                "Unknown methods in `enabled_methods` or `disabled_methods`: {}",
+                unknown_methods.into_iter().collect::<Vec<_>>().join(", ")
+            );
+        }
+        Ok(rules)
+    }
+
+    fn is_disabled(&self, method_name: &str) -> bool {
+        self.disabled_methods.contains(method_name)
+            || self
+                .enabled_methods
+                .as_ref()
+                .is_some_and(|methods| !methods.contains(method_name))
+    }
+}
+
+/// Methods dispatched by the server and the WebSocket subscriptions
+fn is_known_method(method_name: &str) -> bool {
+    method_name == crate::ws::SUBSCRIBE_METHOD
+        || crate::rpc_methods::methods().any(|method| method == method_name)
+}
+
+/// Methods enabled and disabled by the operator. The rules are evaluated by the dispatcher
+/// for every request and are replaced on SIGHUP without restarting the server
+pub struct MethodAccess {
+    rules: std::sync::RwLock<MethodRules>,
+}
+
+impl MethodAccess {
+    pub fn new(
+        enabled_methods: Option<Vec<String>>,
+        disabled_methods: Vec<String>,
+    ) -> anyhow::Result<Self> {
+        Ok(Self {
+            rules: std::sync::RwLock::new(MethodRules::new(enabled_methods, disabled_methods)?),
+        })
+    }
+
+    pub(crate) fn is_disabled(&self, method_name: &str) -> bool {
+        self.rules
+            .read()
+            .expect("Method rules lock is poisoned")
+            .is_disabled(method_name)
+    }
+
+    pub(crate) fn rules(&self) -> MethodRules {
+        self.rules
+            .read()
+            .expect("Method rules lock is poisoned")
+            .clone()
+    }
+
+    fn replace(&self, rules: MethodRules) {
+        *self.rules.write().expect("Method rules lock is poisoned") = rules;
+    }
+}
+
+/// Error of the JSON-RPC method disabled by the operator
+pub(crate) fn method_disabled_error(
+    method_name: &str,
+) -> near_jsonrpc::primitives::errors::RpcError {
+    let error_message = format!("Method `{}` is disabled by the operator", method_name);
+    near_jsonrpc::primitives::errors::RpcError::new_handler_error(
+        Some(serde_json::Value::String(error_message.clone())),
+        serde_json::json!({
+            "name": METHOD_DISABLED,
+            "info": {
+                "method_name": method_name,
+                "error_message": error_message,
+            }
+        }),
+    )
+}
+
+/// Re-reads the enabled and disabled methods from the configuration file on every SIGHUP.
+/// The rules stay unchanged if the configuration is invalid
+pub(crate) async fn reload_on_sighup(
+    data: actix_web::web::Data<crate::config::ServerContext>,
+) -> anyhow::Result<()> {
+    let mut hangup = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup())?;
+    while hangup.recv().await.is_some() {
+        let rules = configuration::reload_configuration::<configuration::RpcServerConfig>()
+            .await
+            .and_then(|rpc_server_config| {
+                MethodRules::new(
+                    rpc_server_config.general.enabled_methods,
+                    rpc_server_config.general.disabled_methods,
+                )
+            });
+        match rules {
+            Ok(rules) => {
+                tracing::warn!(
+                    target: crate::api_keys::AUDIT,
+                    enabled_methods = ?rules.enabled_methods,
+                    disabled_methods = ?rules.disabled_methods,
+                    "Method rules are reloaded"
+                );
+                data.method_access.replace(rules);
+            }
+            Err(err) => {
+                tracing::error!("Failed to reload the method rules: {:?}", err);
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn methods(method_names: &[&str]) -> Vec<String> {
+        method_names
+            .iter()
+            .map(|method_name| method_name.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_all_methods_served_by_default() {
+        let rules = MethodRules::new(None, vec![]).unwrap();
+        assert!(crate::rpc_methods::methods().all(|method_name| !rules.is_disabled(method_name)));
+        assert!(!rules.is_disabled(crate::ws::SUBSCRIBE_METHOD));
+    }
+
+    #[test]
+    fn test_enabled_methods() {
+        let rules = MethodRules::new(Some(methods(&["block", "query"])), vec![]).unwrap();
+        assert!(!rules.is_disabled("block"));
+        assert!(!rules.is_disabled("query"));
+        assert!(rules.is_disabled("tx"));
+        assert!(rules.is_disabled(crate::ws::SUBSCRIBE_METHOD));
+    }
+
+    #[test]
+    fn test_disabled_methods_win_over_enabled() {
+        let rules = MethodRules::new(
+            Some(methods(&["block", "query"])),
+            methods(&["query", "subscribe"]),
+        )
+        .unwrap();
+        assert!(!rules.is_disabled("block"));
+        assert!(rules.is_disabled("query"));
+        assert!(rules.is_disabled(crate::ws::SUBSCRIBE_METHOD));
+    }
+
+    #[test]
+    fn test_unknown_methods_are_rejected() {
+        let err = MethodRules::new(None, methods(&["EXPERIMENTAL_change", "block"])).unwrap_err();
+        assert!(err.to_string().contains("EXPERIMENTAL_change"));
+        assert!(!err.to_string().contains("block"));
+        let err = MethodRules::new(Some(methods(&["blocks"])), vec![]).unwrap_err();
+        assert!(err.to_string().contains("blocks"));
+        assert!(MethodAccess::new(None, methods(&["Query"])).is_err());
+    }
+
+    #[test]
+    fn test_rules_are_replaced() {
+        let method_access = MethodAccess::new(None, vec![]).unwrap();
+        assert!(!method_access.is_disabled("query"));
+        method_access.replace(MethodRules::new(None, methods(&["query"])).unwrap());
+        assert!(method_access.is_disabled("query"));
+    }
+}
@@ -1,5 +1,7 @@
 use actix_web::{get, Responder};
-use prometheus::{Encoder, IntCounterVec, IntGauge, IntGaugeVec, Opts};
+use prometheus::{
+    Encoder, GaugeVec, HistogramVec, IntCounter, IntCounterVec, IntGauge, IntGaugeVec, Opts,
+};
 
 type Result<T, E> = std::result::Result<T, E>;
 
@@ -21,6 +23,13 @@ fn try_create_int_gauge(name: &str, help: &str) -> Result<IntGauge, prometheus::
     Ok(gauge)
 }
 
+fn try_create_int_counter(name: &str, help: &str) -> Result<IntCounter, prometheus::Error> {
+    let opts = Opts::new(name, help);
+    let counter = IntCounter::with_opts(opts)?;
+    prometheus::register(Box::new(counter.clone()))?;
+    Ok(counter)
+}
+
 fn register_int_gauge_vec(
     name: &str,
     help: &str,
@@ -32,6 +41,21 @@ fn register_int_gauge_vec(
     Ok(counter)
 }
 
+fn register_gauge_vec(
+    name: &str,
+    help: &str,
+    label_names: &[&str],
+) -> Result<GaugeVec, prometheus::Error> {
+    let opts = Opts::new(name, help);
+    let gauge = GaugeVec::new(opts, label_names)?;
+    prometheus::register(Box::new(gauge.clone()))?;
+    Ok(gauge)
+}
+
+// A single differing field is the common case, the large numbers mean
+// the responses are of the different blocks or shapes
+const SHADOW_MISMATCHED_PATHS_BUCKETS: [f64; 8] = [1.0, 2.0, 3.0, 5.0, 10.0, 20.0, 50.0, 100.0];
+
 // Struct to store the optimistic updating state
 // This is used to track if the optimistic updating is working or not
 // By default, it is set as working
@@ -96,6 +120,12 @@ lazy_static! {
         &["method_name"] // This declares a label named `method name`
     ).unwrap();
 
+    pub(crate) static ref INVALID_PARAMS_TOTAL: IntCounterVec = register_int_counter_vec(
+        "invalid_params_total",
+        "Total number of the requests with the params not matching the method schema by method_name. Counted in the lenient mode as well",
+        &["method_name"]
+    ).unwrap();
+
     pub(crate) static ref METHOD_ERRORS_TOTAL: IntCounterVec = register_int_counter_vec(
         "method_errors_total",
         "Total number of errors for method",
@@ -120,6 +150,95 @@ lazy_static! {
         &["lookup_type"]
     ).unwrap();
 
+    pub(crate) static ref CACHE_MEMORY_USAGE_BYTES: IntGaugeVec = register_int_gauge_vec(
+        "cache_memory_usage_bytes",
+        "Approximate memory usage of the in-memory cache in bytes",
+        &["cache_name"] // This declares a label named `cache_name`
+    ).unwrap();
+
+    pub(crate) static ref CACHE_MEMORY_BUDGET_BYTES: IntGauge = try_create_int_gauge(
+        "cache_memory_budget_bytes",
+        "Global memory budget shared by all in-memory caches in bytes",
+    ).unwrap();
+
+    pub(crate) static ref MIN_BLOCK_HEIGHT_REQUESTS: IntCounterVec = register_int_counter_vec(
+        "min_block_height_requests",
+        "Total number of requests with the minimum block height by the way they are served",
+        &["result"] // This declares a label named `result`: "reached", "waited", "proxied" or "rejected"
+    ).unwrap();
+
+    pub(crate) static ref PREFETCHED_BLOCKS: IntCounterVec = register_int_counter_vec(
+        "prefetched_blocks",
+        "Total number of the blocks prefetched for the sequential scans by the result",
+        &["result"] // This declares a label named `result`: "fetched", "failed", "hit" or "skipped" (over the in-flight limit)
+    ).unwrap();
+
+    pub(crate) static ref SEND_TX_PREFLIGHT_REJECTIONS: IntCounterVec = register_int_counter_vec(
+        "send_tx_preflight_rejections",
+        "Total number of the transactions rejected by the preflight check by the error: `invalid_signature` or `invalid_nonce`",
+        &["error_type"] // This declares a label named `error_type`
+    ).unwrap();
+
+    pub(crate) static ref WS_SUBSCRIPTIONS: IntGaugeVec = register_int_gauge_vec(
+        "ws_subscriptions",
+        "Number of the active WebSocket subscriptions by the topic",
+        &["topic"] // This declares a label named `topic`
+    ).unwrap();
+
+    pub(crate) static ref GRPC_CALLS_TOTAL: IntCounterVec = register_int_counter_vec(
+        "grpc_calls_total",
+        "Total number of the gRPC API calls by the method and the status code",
+        &["method_name", "code"] // The method names are the same as in JSON-RPC
+    ).unwrap();
+
+    pub(crate) static ref ACCESS_SAMPLES: IntCounterVec = register_int_counter_vec(
+        "access_samples",
+        "Total number of the sampled block reads by the method and the age of the block",
+        &["method_name", "block_age"] // "1m", "10m", "1h", "1d", "1w" (younger than) or "older"
+    ).unwrap();
+
+    pub(crate) static ref INDEXER_NOTIFICATIONS: IntCounterVec = register_int_counter_vec(
+        "indexer_notifications",
+        "Total number of the notifications received from the indexers by the channel",
+        &["channel"] // This declares a label named `channel`: "head" or "transactions"
+    ).unwrap();
+
+    pub(crate) static ref BLOCK_LATENCY_SECONDS: HistogramVec = database::metrics::register_histogram_vec(
+        "block_latency_seconds",
+        "Seconds between the block timestamp and the moment the block reached the stage",
+        // This declares a label named `stage`: "tx_indexed" or "state_indexed" when the indexer
+        // notified the block is written, "observed" when the rpc-server follows the final block
+        &["stage"],
+        &database::metrics::BLOCK_LATENCY_BUCKETS,
+    ).unwrap();
+
+    pub(crate) static ref DATA_STALENESS_SECONDS: GaugeVec = register_gauge_vec(
+        "read_rpc_data_staleness_seconds",
+        "Seconds since the timestamp of the latest block of the data served by the rpc-server",
+        &["domain"] // This declares a label named `domain`: "tx", "state" or "blocks"
+    ).unwrap();
+
+    pub(crate) static ref HTTP_OPEN_CONNECTIONS: IntGauge = try_create_int_gauge(
+        "http_open_connections",
+        "Number of the open client connections",
+    ).unwrap();
+
+    pub(crate) static ref HTTP_ACTIVE_STREAMS: IntGaugeVec = register_int_gauge_vec(
+        "http_active_streams",
+        "Number of the requests in progress by the protocol of the connection",
+        &["protocol"] // This declares a label named `protocol`: "http1" or "http2"
+    ).unwrap();
+
+    pub(crate) static ref HTTP_REJECTED_STREAMS: IntCounter = try_create_int_counter(
+        "http_rejected_streams",
+        "Total number of the requests rejected by the concurrent streams limit of the connection",
+    ).unwrap();
+
+    pub(crate) static ref HTTP_HANDSHAKE_FAILURES: IntCounter = try_create_int_counter(
+        "http_handshake_failures",
+        "Total number of the connections which sent the data but were closed before the first request was served",
+    ).unwrap();
+
     // Error metrics
     // 0: ReadRPC success, NEAR RPC success"
     // 1: ReadRPC success, NEAR RPC error"
@@ -132,6 +251,148 @@ lazy_static! {
         &["method", "error_type"]
     ).unwrap();
 
+    pub(crate) static ref SHADOW_COMPARISONS: IntCounterVec = register_int_counter_vec(
+        "shadow_comparisons_total",
+        "Total number of the responses compared with the archival NEAR RPC by method",
+        &["method"]
+    ).unwrap();
+
+    pub(crate) static ref SHADOW_COMPARISONS_DROPPED: IntCounterVec = register_int_counter_vec(
+        "shadow_comparisons_dropped_total",
+        "Total number of the sampled responses not compared since too many comparisons are running by method",
+        &["method"]
+    ).unwrap();
+
+    pub(crate) static ref SHADOW_MISMATCHED_PATHS: HistogramVec = database::metrics::register_histogram_vec(
+        "shadow_mismatched_paths",
+        "Number of the differing JSON paths of the responses mismatching the archival NEAR RPC by method",
+        &["method"],
+        &SHADOW_MISMATCHED_PATHS_BUCKETS,
+    ).unwrap();
+
+}
+
+/// Updates the staleness of the served data by domain.
+/// The blocks staleness is taken from the final block known to the rpc-server,
+/// the transactions and the state staleness from the latest blocks processed by the indexers.
+/// The indexers progress is polled from the database only if their notifications are not received.
+/// The domains are not exported until their indexers store the block timestamps
+pub(crate) async fn update_data_staleness(
+    db_manager: &(dyn database::ReaderDbManager + Sync + Send),
+    blocks_info_by_finality: &crate::modules::blocks::BlocksInfoByFinality,
+    indexed_heads: &crate::indexer_notifications::IndexedHeads,
+) -> anyhow::Result<()> {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)?
+        .as_nanos() as u64;
+    let staleness_seconds =
+        |block_timestamp: u64| now.saturating_sub(block_timestamp) as f64 / 1_000_000_000.0;
+
+    let final_block = blocks_info_by_finality.final_cache_block().await;
+    DATA_STALENESS_SECONDS
+        .with_label_values(&["blocks"])
+        .set(staleness_seconds(final_block.block_timestamp));
+
+    let indexed_block_timestamps = match indexed_heads.heads() {
+        Some(heads) => heads
+            .into_iter()
+            .map(|(indexer_type, head)| (indexer_type, head.block_timestamp))
+            .collect(),
+        None => {
+            db_manager
+                .get_latest_indexed_block_timestamps("data_staleness")
+                .await?
+        }
+    };
+    for domain in ["tx", "state"] {
+        if let Some(block_timestamp) = indexed_block_timestamps.get(domain) {
+            DATA_STALENESS_SECONDS
+                .with_label_values(&[domain])
+                .set(staleness_seconds(*block_timestamp));
+        }
+    }
+    Ok(())
+}
+
+/// Records the latency of the block reaching the stage, measured from the block timestamp.
+/// The clocks of the block producers and the servers are not synchronized,
+/// so the block from the future is recorded with the zero latency
+pub(crate) fn observe_block_latency(stage: &str, block_timestamp: u64) {
+    BLOCK_LATENCY_SECONDS
+        .with_label_values(&[stage])
+        .observe(database::metrics::seconds_since_block(block_timestamp));
+}
+
+/// Returns all the metrics registered by the rpc-server.
+/// Every new metric should be added here to be described by the `/metrics/meta` endpoint
+fn collectors() -> Vec<&'static dyn prometheus::core::Collector> {
+    let mut collectors: Vec<&'static dyn prometheus::core::Collector> = vec![
+        &*LATEST_BLOCK_HEIGHT_BY_FINALITIY,
+        &*METHOD_CALLS_COUNTER,
+        &*INVALID_PARAMS_TOTAL,
+        &*METHOD_ERRORS_TOTAL,
+        &*TOTAL_REQUESTS_COUNTER,
+        &*OPTIMISTIC_STATUS,
+        &*LEGACY_DATABASE_TX_DETAILS,
+        &*CACHE_MEMORY_USAGE_BYTES,
+        &*CACHE_MEMORY_BUDGET_BYTES,
+        &*MIN_BLOCK_HEIGHT_REQUESTS,
+        &*PREFETCHED_BLOCKS,
+        &*SEND_TX_PREFLIGHT_REJECTIONS,
+        &*WS_SUBSCRIPTIONS,
+        &*GRPC_CALLS_TOTAL,
+        &*ACCESS_SAMPLES,
+        &*INDEXER_NOTIFICATIONS,
+        &*BLOCK_LATENCY_SECONDS,
+        &*REQUESTS_ERRORS,
+        &*SHADOW_COMPARISONS,
+        &*SHADOW_COMPARISONS_DROPPED,
+        &*SHADOW_MISMATCHED_PATHS,
+        &*DATA_STALENESS_SECONDS,
+        &*HTTP_OPEN_CONNECTIONS,
+        &*HTTP_ACTIVE_STREAMS,
+        &*HTTP_REJECTED_STREAMS,
+        &*HTTP_HANDSHAKE_FAILURES,
+    ];
+    collectors.extend(http_client::metrics::collectors());
+    collectors
+}
+
+/// Description of the exported metric
+#[derive(serde::Serialize, Debug)]
+pub(crate) struct MetricMetadata {
+    name: String,
+    help: String,
+    #[serde(rename = "type")]
+    metric_type: String,
+    labels: Vec<String>,
+}
+
+/// Collects the metadata of the given metrics.
+/// Metric type is taken from the collected families because `Desc` doesn't contain it.
+/// Families are collected even if the metric vec has no children yet,
+/// in contrast to `prometheus::gather()` which skips them.
+fn metrics_metadata(
+    collectors: Vec<&'static dyn prometheus::core::Collector>,
+) -> Vec<MetricMetadata> {
+    let mut metadata = collectors
+        .into_iter()
+        .flat_map(|collector| {
+            collector
+                .desc()
+                .into_iter()
+                .zip(collector.collect())
+                .map(|(desc, family)| MetricMetadata {
+                    name: desc.fq_name.clone(),
+                    help: desc.help.clone(),
+                    metric_type: format!("{:?}", family.get_field_type()).to_lowercase(),
+                    labels: desc.variable_labels.clone(),
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    metadata.sort_by(|a, b| a.name.cmp(&b.name));
+    metadata
 }
 
 /// Help method to increment block category metrics
@@ -211,3 +472,13 @@ pub(crate) async fn get_metrics() -> impl Responder {
         String::default()
     })
 }
+
+/// Exposes the description of every metric exported by the rpc-server
+/// with help text, type and label names
+/// It is used to generate dashboard templates
+#[get("/metrics/meta")]
+pub(crate) async fn get_metrics_meta() -> impl Responder {
+    let mut collectors = collectors();
+    collectors.extend(database::metrics_collectors());
+    actix_web::web::Json(metrics_metadata(collectors))
+}
@@ -0,0 +1,139 @@
+/// Header with the highest block height seen by the client.
+/// The request is served only after the server reaches this block
+pub(crate) const MIN_BLOCK_HEIGHT_HEADER: &str = "x-min-block-height";
+/// Header with the latest block height known to the server when the response is sent.
+/// Clients pass the highest value they have seen back in the `x-min-block-height` header
+pub(crate) const BLOCK_HEIGHT_HEADER: &str = "x-block-height";
+
+/// Parses the minimum block height requested by the client
+pub(crate) fn requested_min_block_height(
+    req: &actix_web::HttpRequest,
+) -> Result<Option<near_primitives::types::BlockHeight>, actix_web::HttpResponse> {
+    let Some(value) = req.headers().get(MIN_BLOCK_HEIGHT_HEADER) else {
+        return Ok(None);
+    };
+    value
+        .to_str()
+        .ok()
+        .and_then(|value| value.trim().parse().ok())
+        .map(Some)
+        .ok_or_else(|| {
+            crate::errors::error_response(
+                req,
+                crate::errors::HttpErrorCode::InvalidRequest,
+                format!(
+                    "`{}` header must be a block height",
+                    MIN_BLOCK_HEIGHT_HEADER
+                ),
+            )
+        })
+}
+
+/// Latest block height the server serves the data for.
+/// The optimistic block is behind the final one while the optimistic updating is not working
+pub(crate) async fn latest_block_height(
+    blocks_info_by_finality: &crate::modules::blocks::BlocksInfoByFinality,
+) -> near_primitives::types::BlockHeight {
+    std::cmp::max(
+        blocks_info_by_finality
+            .optimistic_cache_block()
+            .await
+            .block_height,
+        blocks_info_by_finality
+            .final_cache_block()
+            .await
+            .block_height,
+    )
+}
+
+/// Error returned for the custom method if the server doesn't reach the requested block in time.
+/// The custom methods are not known to the NEAR RPC node, so they can't be proxied
+pub(crate) fn not_reached_error(
+    min_block_height: near_primitives::types::BlockHeight,
+    latest_block_height: near_primitives::types::BlockHeight,
+    wait: std::time::Duration,
+) -> near_jsonrpc::primitives::errors::RpcError {
+    crate::metrics::MIN_BLOCK_HEIGHT_REQUESTS
+        .with_label_values(&["rejected"])
+        .inc();
+    let error_message = format!(
+        "Block {} requested in the `{}` header is not reached in {} ms, the latest block is {}. \
+        Please, retry the request",
+        min_block_height,
+        MIN_BLOCK_HEIGHT_HEADER,
+        wait.as_millis(),
+        latest_block_height
+    );
+    near_jsonrpc::primitives::errors::RpcError::new_handler_error(
+        Some(serde_json::Value::String(error_message.clone())),
+        serde_json::json!({
+            "name": "BLOCK_HEIGHT_NOT_REACHED",
+            "info": {
+                "min_block_height": min_block_height,
+                "latest_block_height": latest_block_height,
+                "error_message": error_message,
+            }
+        }),
+    )
+}
+
+/// Waits up to `wait` for the server to reach the minimum block height requested by the client.
+/// Replicas behind the load balancer follow the chain independently, so the client can hit
+/// the replica lagging behind the one it has seen the block on.
+/// `wait` is the max wait configured by `min_block_height_wait`, the header can't extend it.
+/// Returns `false` if the block is not reached in time and the request should be proxied
+pub(crate) async fn wait_for_block_height(
+    blocks_info_by_finality: &crate::modules::blocks::BlocksInfoByFinality,
+    min_block_height: near_primitives::types::BlockHeight,
+    wait: std::time::Duration,
+) -> bool {
+    let deadline = tokio::time::Instant::now() + wait;
+    let mut waited = false;
+    loop {
+        // Registered before the check, so the update right after the check is not missed
+        let head_updated = blocks_info_by_finality.head_updated.notified();
+        if latest_block_height(blocks_info_by_finality).await >= min_block_height {
+            let result = if waited { "waited" } else { "reached" };
+            crate::metrics::MIN_BLOCK_HEIGHT_REQUESTS
+                .with_label_values(&[result])
+                .inc();
+            return true;
+        }
+        let now = tokio::time::Instant::now();
+        if now >= deadline {
+            return false;
+        }
+        waited = true;
+        let _ = tokio::time::timeout(deadline - now, head_updated).await;
+    }
+}
+
+/// Proxies the request as is to the NEAR RPC node, which is ahead of the server.
+/// The proxied requests are counted in `min_block_height_requests`, the method name is not
+/// used as a label since it is not validated before proxying
+pub(crate) async fn proxy(
+    near_rpc_client: &crate::utils::JsonRpcClient,
+    method_name: &str,
+    params: serde_json::Value,
+) -> Result<serde_json::Value, near_jsonrpc::primitives::errors::RpcError> {
+    crate::metrics::MIN_BLOCK_HEIGHT_REQUESTS
+        .with_label_values(&["proxied"])
+        .inc();
+    let request = near_jsonrpc_client::methods::any::<Result<serde_json::Value, serde_json::Value>>(
+        method_name,
+        params,
+    );
+    near_rpc_client
+        .call(request, None)
+        .await
+        .map_err(|err| match err.handler_error() {
+            Some(error_struct) => near_jsonrpc::primitives::errors::RpcError::new_handler_error(
+                None,
+                error_struct.clone(),
+            ),
+            None => near_jsonrpc::primitives::errors::RpcError::new_internal_error(
+                None,
+                err.to_string(),
+            ),
+        })
+}
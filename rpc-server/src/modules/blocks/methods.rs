@@ -4,7 +4,8 @@ use near_primitives::views::StateChangeValueView;
 
 use crate::config::ServerContext;
 use crate::modules::blocks::utils::{
-    check_block_height, fetch_block_from_cache_or_get, fetch_chunk_from_s3, is_matching_change,
+    check_block_height, fetch_block_from_cache_or_get, fetch_chunk_from_s3,
+    get_block_height_by_hash, is_matching_change,
 };
 
 /// `block` rpc method implementation
@@ -67,6 +68,105 @@ pub async fn chunk(
     chunk_result
 }
 
+/// `EXPERIMENTAL_congestion_level` rpc method implementation.
+/// Returns the congestion level of the shard at the block computed the same way as nearcore does,
+/// so the relayers can adapt the gas attached to the transactions to the receiver shard.
+/// The congestion info is read from the database and from the block itself if it is not indexed
+/// yet. The congestion control config of the block protocol version is used
+#[cfg_attr(feature = "tracing-instrumentation", tracing::instrument(skip(data)))]
+pub async fn congestion_level(
+    data: Data<ServerContext>,
+    request_data: crate::modules::blocks::RpcCongestionLevelRequest,
+) -> Result<
+    crate::modules::blocks::RpcCongestionLevelResponse,
+    near_jsonrpc::primitives::errors::RpcError,
+> {
+    tracing::debug!(
+        "`EXPERIMENTAL_congestion_level` called with parameters: {:?}",
+        request_data
+    );
+    let cache_block = fetch_block_from_cache_or_get(
+        &data,
+        &request_data.block_reference,
+        "EXPERIMENTAL_congestion_level",
+    )
+    .await?;
+    let chunk_congestion = match data
+        .db_manager
+        .get_chunk_congestion(
+            cache_block.block_height,
+            request_data.shard_id,
+            "EXPERIMENTAL_congestion_level",
+        )
+        .await
+    {
+        Ok(chunk_congestion) => chunk_congestion,
+        Err(_) => {
+            chunk_congestion_from_block(&data, cache_block.block_height, request_data.shard_id)
+                .await?
+        }
+    };
+    let congestion_control_config = near_parameters::RuntimeConfigStore::for_chain_id(
+        &data.genesis_info.genesis_config.chain_id,
+    )
+    .get_config(cache_block.latest_protocol_version)
+    .congestion_control_config;
+    // The congestion grows while the chunks of the shard are missed
+    let missed_chunks_count = cache_block
+        .block_height
+        .saturating_sub(chunk_congestion.height_included);
+    let congestion_level = near_primitives::congestion_info::CongestionControl::new(
+        congestion_control_config,
+        chunk_congestion.congestion_info.clone().into(),
+        missed_chunks_count,
+    )
+    .congestion_level();
+    Ok(crate::modules::blocks::RpcCongestionLevelResponse {
+        block_height: cache_block.block_height,
+        shard_id: request_data.shard_id,
+        congestion_level,
+        congestion_info: chunk_congestion.congestion_info,
+    })
+}
+
+// Takes the congestion info from the chunk header of the block
+// for the blocks not indexed by the state-indexer yet
+async fn chunk_congestion_from_block(
+    data: &Data<ServerContext>,
+    block_height: near_primitives::types::BlockHeight,
+    shard_id: near_primitives::types::ShardId,
+) -> Result<readnode_primitives::ChunkCongestion, near_jsonrpc::primitives::errors::RpcError> {
+    let block_view = fetch_block(
+        data,
+        &near_primitives::types::BlockReference::BlockId(near_primitives::types::BlockId::Height(
+            block_height,
+        )),
+        "EXPERIMENTAL_congestion_level",
+    )
+    .await?
+    .block_view;
+    let chunk_header = block_view
+        .chunks
+        .into_iter()
+        .find(|chunk_header| chunk_header.shard_id == shard_id)
+        .ok_or(
+            near_jsonrpc::primitives::types::chunks::RpcChunkError::InvalidShardId { shard_id },
+        )?;
+    let congestion_info = chunk_header.congestion_info.ok_or_else(|| {
+        near_jsonrpc::primitives::errors::RpcError::new_internal_error(
+            None,
+            format!(
+                "Congestion info is not available for the block {} of the protocol version {}",
+                block_height, block_view.header.latest_protocol_version
+            ),
+        )
+    })?;
+    Ok(readnode_primitives::ChunkCongestion {
+        height_included: chunk_header.height_included,
+        congestion_info,
+    })
+}
+
 /// `EXPERIMENTAL_changes` rpc method implementation
 /// calls proxy_rpc_call to get `EXPERIMENTAL_changes` from near-rpc if request parameters not supported by read-rpc
 /// as example: BlockReference for Finality::None is not supported by read-rpc
@@ -208,7 +308,29 @@ async fn changes_in_block_call(
         }
     })?;
 
-    let result = fetch_changes_in_block(&data, cache_block, &params.block_reference).await;
+    let result = match fetch_changes_in_block(&data, cache_block, &params.block_reference).await {
+        Ok(Some(response)) => Ok(response),
+        // The stored changes of the block are incomplete, the complete ones are proxied
+        Ok(None) => data
+            .near_rpc_client
+            .archival_call(
+                near_jsonrpc::primitives::types::changes::RpcStateChangesInBlockRequest {
+                    block_reference: near_primitives::types::BlockReference::from(
+                        near_primitives::types::BlockId::Height(cache_block.block_height),
+                    ),
+                },
+                Some("EXPERIMENTAL_changes_in_block"),
+            )
+            .await
+            .map_err(|err| {
+                err.handler_error().cloned().unwrap_or(
+                    near_jsonrpc::primitives::types::changes::RpcStateChangesError::InternalError {
+                        error_message: err.to_string(),
+                    },
+                )
+            }),
+        Err(err) => Err(err),
+    };
     #[cfg(feature = "shadow-data-consistency")]
     {
         if let near_primitives::types::BlockReference::Finality(_) = params.block_reference {
@@ -248,13 +370,37 @@ async fn changes_in_block_by_type_call(
                 }
             })?;
 
-    let result = fetch_changes_in_block_by_type(
+    let result = match fetch_changes_in_block_by_type(
         &data,
         cache_block,
         &params.state_changes_request,
         &params.block_reference,
     )
-    .await;
+    .await
+    {
+        Ok(Some(response)) => Ok(response),
+        // The stored changes of the block are incomplete, the complete ones are proxied
+        Ok(None) => data
+            .near_rpc_client
+            .archival_call(
+                near_jsonrpc::primitives::types::changes::RpcStateChangesInBlockByTypeRequest {
+                    block_reference: near_primitives::types::BlockReference::from(
+                        near_primitives::types::BlockId::Height(cache_block.block_height),
+                    ),
+                    state_changes_request: params.state_changes_request.clone(),
+                },
+                Some("EXPERIMENTAL_changes"),
+            )
+            .await
+            .map_err(|err| {
+                err.handler_error().cloned().unwrap_or(
+                    near_jsonrpc::primitives::types::changes::RpcStateChangesError::InternalError {
+                        error_message: err.to_string(),
+                    },
+                )
+            }),
+        Err(err) => Err(err),
+    };
 
     #[cfg(feature = "shadow-data-consistency")]
     {
@@ -293,11 +439,7 @@ pub async fn fetch_block(
                 Ok(*block_height)
             }
             near_primitives::types::BlockId::Hash(block_hash) => {
-                match data
-                    .db_manager
-                    .get_block_height_by_hash(*block_hash, method_name)
-                    .await
-                {
+                match get_block_height_by_hash(data, *block_hash, method_name).await {
                     Ok(block_height) => Ok(block_height),
                     Err(err) => {
                         tracing::error!("Failed to fetch block by hash: {}", err);
@@ -358,6 +500,8 @@ pub async fn fetch_block(
             .block_height
     {
         data.blocks_info_by_finality.optimistic_block_view().await
+    } else if let Some(block_view) = data.block_prefetcher.get(block_height).await {
+        block_view
     } else {
         near_lake_framework::s3_fetchers::fetch_block(
             &data.s3_client,
@@ -398,16 +542,16 @@ pub async fn fetch_chunk(
                     })?;
                         block_height
                     }
-                    near_primitives::types::BlockId::Hash(block_hash) => data
-                        .db_manager
-                        .get_block_height_by_hash(block_hash, "chunk")
-                        .await
-                        .map_err(|err| {
-                            tracing::error!("Failed to fetch block by hash: {}", err);
-                            near_jsonrpc::primitives::types::chunks::RpcChunkError::UnknownBlock {
-                                error_message: format!("BLOCK: {:?}", block_hash),
-                            }
-                        })?,
+                    near_primitives::types::BlockId::Hash(block_hash) => get_block_height_by_hash(
+                        data, block_hash, "chunk",
+                    )
+                    .await
+                    .map_err(|err| {
+                        tracing::error!("Failed to fetch block by hash: {}", err);
+                        near_jsonrpc::primitives::types::chunks::RpcChunkError::UnknownBlock {
+                            error_message: format!("BLOCK: {:?}", block_hash),
+                        }
+                    })?,
                 };
             // Check if the chunk stored in block with the given height
             if let Ok(block_height_shard_id) = data
@@ -431,13 +575,41 @@ pub async fn fetch_chunk(
             )
             .map(|block_height_shard_id| (block_height_shard_id.0, block_height_shard_id.1))?,
     };
-    let chunk_view = fetch_chunk_from_s3(
+    let chunk_view = match fetch_chunk_from_s3(
         &data.s3_client,
         &data.s3_bucket_name,
         block_height,
         shard_id,
     )
-    .await?;
+    .await
+    {
+        Ok(chunk_view) => chunk_view,
+        Err(err) => {
+            // The chunk missing in the indexed block is marked by the tx-indexer,
+            // it is reported the same way nearcore reports the chunk unavailable on the node
+            match data
+                .db_manager
+                .get_skipped_chunk(block_height, shard_id, "chunk")
+                .await
+            {
+                Ok(Some(chunk_hash)) => {
+                    return Err(
+                        near_jsonrpc::primitives::types::chunks::RpcChunkError::UnknownChunk {
+                            chunk_hash: chunk_hash.into(),
+                        },
+                    )
+                }
+                Ok(None) => return Err(err),
+                Err(db_err) => {
+                    return Err(
+                        near_jsonrpc::primitives::types::chunks::RpcChunkError::InternalError {
+                            error_message: db_err.to_string(),
+                        },
+                    )
+                }
+            }
+        }
+    };
     // increase block category metrics
     crate::metrics::increase_request_category_metrics(
         data,
@@ -458,19 +630,23 @@ async fn fetch_changes_in_block(
     cache_block: crate::modules::blocks::CacheBlock,
     block_reference: &near_primitives::types::BlockReference,
 ) -> Result<
-    near_jsonrpc::primitives::types::changes::RpcStateChangesInBlockByTypeResponse,
+    Option<near_jsonrpc::primitives::types::changes::RpcStateChangesInBlockByTypeResponse>,
     near_jsonrpc::primitives::types::changes::RpcStateChangesError,
 > {
-    let trie_keys = fetch_state_changes(data, cache_block, block_reference)
-        .await
-        .map_err(|err| {
-            near_jsonrpc::primitives::types::changes::RpcStateChangesError::UnknownBlock {
-                error_message: err.to_string(),
-            }
-        })?
-        .into_iter()
-        .map(
-            |state_change_with_cause| match state_change_with_cause.value {
+    let Some(state_change_values) = fetch_state_changes_values(
+        data,
+        cache_block,
+        block_reference,
+        "EXPERIMENTAL_changes_in_block",
+    )
+    .await?
+    else {
+        return Ok(None);
+    };
+    let trie_keys =
+        state_change_values
+            .into_iter()
+            .map(|state_change_value| match state_change_value {
                 StateChangeValueView::AccountUpdate { account_id, .. }
                 | StateChangeValueView::AccountDeletion { account_id } => {
                     TrieKey::Account { account_id }
@@ -498,8 +674,7 @@ async fn fetch_changes_in_block(
                     account_id,
                     public_key,
                 },
-            },
-        );
+            });
 
     let mut unique_trie_keys = vec![];
     for trie_key in trie_keys {
@@ -531,12 +706,12 @@ async fn fetch_changes_in_block(
         })
         .collect();
 
-    Ok(
+    Ok(Some(
         near_jsonrpc::primitives::types::changes::RpcStateChangesInBlockByTypeResponse {
             block_hash: cache_block.block_hash,
             changes,
         },
-    )
+    ))
 }
 
 #[cfg_attr(feature = "tracing-instrumentation", tracing::instrument(skip(data)))]
@@ -546,92 +721,141 @@ async fn fetch_changes_in_block_by_type(
     state_changes_request: &near_primitives::views::StateChangesRequestView,
     block_reference: &near_primitives::types::BlockReference,
 ) -> Result<
-    near_jsonrpc::primitives::types::changes::RpcStateChangesInBlockResponse,
+    Option<near_jsonrpc::primitives::types::changes::RpcStateChangesInBlockResponse>,
     near_jsonrpc::primitives::types::changes::RpcStateChangesError,
 > {
-    let changes = fetch_state_changes(data, cache_block, block_reference)
-        .await
-        .map_err(|err| {
-            near_jsonrpc::primitives::types::changes::RpcStateChangesError::UnknownBlock {
-                error_message: err.to_string(),
-            }
-        })?
-        .into_iter()
-        .filter(|change| is_matching_change(change, state_changes_request))
-        .collect();
-    Ok(
+    let changes = if let near_primitives::types::BlockReference::Finality(_) = block_reference {
+        fetch_state_changes(data, block_reference)
+            .await
+            .map_err(|err| {
+                near_jsonrpc::primitives::types::changes::RpcStateChangesError::UnknownBlock {
+                    error_message: err.to_string(),
+                }
+            })?
+    } else {
+        let Some(state_change_values) =
+            fetch_state_changes_from_db(data, cache_block, "EXPERIMENTAL_changes").await?
+        else {
+            return Ok(None);
+        };
+        // The causes of the changes are not stored in the database,
+        // the changes of the past blocks are returned with the `not_writable_to_disk` cause
+        state_change_values
+            .into_iter()
+            .map(|value| near_primitives::views::StateChangeWithCauseView {
+                cause: near_primitives::views::StateChangeCauseView::NotWritableToDisk,
+                value,
+            })
+            .collect()
+    }
+    .into_iter()
+    .filter(|change| is_matching_change(change, state_changes_request))
+    .collect();
+    Ok(Some(
         near_jsonrpc::primitives::types::changes::RpcStateChangesInBlockResponse {
             block_hash: cache_block.block_hash,
             changes,
         },
-    )
+    ))
 }
 
-// Helper method to fetch state changes from the cache or from the S3
-// depending on the block reference
-// If the block reference is Finality, then the state changes are returned from cached optimistic or final blocks
-// Otherwise, the state changes are fetched from the S3
+// Helper method to fetch the state changes of the cached optimistic or final block
+// by the finality block reference
 #[cfg_attr(feature = "tracing-instrumentation", tracing::instrument(skip(data)))]
 async fn fetch_state_changes(
     data: &Data<ServerContext>,
-    cache_block: crate::modules::blocks::CacheBlock,
     block_reference: &near_primitives::types::BlockReference,
 ) -> anyhow::Result<near_primitives::views::StateChangesView> {
-    if let near_primitives::types::BlockReference::Finality(finality) = block_reference {
-        match finality {
-            near_primitives::types::Finality::None => {
-                if crate::metrics::OPTIMISTIC_UPDATING.is_not_working() {
-                    Err(anyhow::anyhow!(
-                        "Failed to fetch shards! Finality::None is not supported by rpc_server",
-                    ))
-                } else {
-                    Ok(data
-                        .blocks_info_by_finality
-                        .optimistic_block_changes()
-                        .await)
-                }
-            }
-            near_primitives::types::Finality::DoomSlug
-            | near_primitives::types::Finality::Final => {
-                Ok(data.blocks_info_by_finality.final_block_changes().await)
+    match block_reference {
+        near_primitives::types::BlockReference::Finality(
+            near_primitives::types::Finality::None,
+        ) => {
+            if crate::metrics::OPTIMISTIC_UPDATING.is_not_working() {
+                Err(anyhow::anyhow!(
+                    "Failed to fetch shards! Finality::None is not supported by rpc_server",
+                ))
+            } else {
+                Ok(data
+                    .blocks_info_by_finality
+                    .optimistic_block_changes()
+                    .await)
             }
         }
-    } else {
-        Ok(fetch_shards_by_cache_block(data, cache_block)
-            .await?
-            .into_iter()
-            .flat_map(|shard| shard.state_changes)
-            .collect())
+        near_primitives::types::BlockReference::Finality(_) => {
+            Ok(data.blocks_info_by_finality.final_block_changes().await)
+        }
+        _ => Err(anyhow::anyhow!(
+            "The state changes of the past blocks are read from the database"
+        )),
     }
 }
 
-// Helper method to fetch block shards from the S3 by the cache block
+// Helper method to fetch the state changes values without the causes
+// If the block reference is Finality, then the state changes are returned from cached optimistic or final blocks
+// Otherwise, the state changes are read from the database by the block height
 #[cfg_attr(feature = "tracing-instrumentation", tracing::instrument(skip(data)))]
-async fn fetch_shards_by_cache_block(
+async fn fetch_state_changes_values(
     data: &Data<ServerContext>,
     cache_block: crate::modules::blocks::CacheBlock,
-) -> anyhow::Result<Vec<near_indexer_primitives::IndexerShard>> {
-    let fetch_shards_futures = (0..cache_block.chunks_included)
-        .collect::<Vec<u64>>()
-        .into_iter()
-        .map(|shard_id| {
-            near_lake_framework::s3_fetchers::fetch_shard(
-                &data.s3_client,
-                &data.s3_bucket_name,
-                cache_block.block_height,
-                shard_id,
-            )
-        });
+    block_reference: &near_primitives::types::BlockReference,
+    method_name: &str,
+) -> Result<
+    Option<Vec<StateChangeValueView>>,
+    near_jsonrpc::primitives::types::changes::RpcStateChangesError,
+> {
+    if let near_primitives::types::BlockReference::Finality(_) = block_reference {
+        Ok(Some(
+            fetch_state_changes(data, block_reference)
+                .await
+                .map_err(|err| {
+                    near_jsonrpc::primitives::types::changes::RpcStateChangesError::UnknownBlock {
+                        error_message: err.to_string(),
+                    }
+                })?
+                .into_iter()
+                .map(|state_change_with_cause| state_change_with_cause.value)
+                .collect(),
+        ))
+    } else {
+        fetch_state_changes_from_db(data, cache_block, method_name).await
+    }
+}
 
-    futures::future::join_all(fetch_shards_futures)
+// Helper method to read the state changes of the past block from the database.
+// The changes of the block are stored once the state-indexer processes it,
+// the blocks ahead of the state-indexer are rejected instead of returning no changes.
+// Returns `None` if the stored changes of the block are incomplete
+#[cfg_attr(feature = "tracing-instrumentation", tracing::instrument(skip(data)))]
+async fn fetch_state_changes_from_db(
+    data: &Data<ServerContext>,
+    cache_block: crate::modules::blocks::CacheBlock,
+    method_name: &str,
+) -> Result<
+    Option<Vec<StateChangeValueView>>,
+    near_jsonrpc::primitives::types::changes::RpcStateChangesError,
+> {
+    let internal_error = |err: anyhow::Error| {
+        near_jsonrpc::primitives::types::changes::RpcStateChangesError::InternalError {
+            error_message: err.to_string(),
+        }
+    };
+    let state_block_timestamp = match data.indexed_heads.heads() {
+        Some(heads) => heads.get("state").map(|head| head.block_timestamp),
+        None => data
+            .db_manager
+            .get_latest_indexed_block_timestamps(method_name)
+            .await
+            .map_err(internal_error)?
+            .get("state")
+            .copied(),
+    };
+    if state_block_timestamp.map_or(true, |block_timestamp| {
+        block_timestamp < cache_block.block_timestamp
+    }) {
+        return Err(near_jsonrpc::primitives::types::changes::RpcStateChangesError::NotSyncedYet);
+    }
+    data.db_manager
+        .get_state_changes_in_block(cache_block.block_height, method_name)
         .await
-        .into_iter()
-        .collect::<Result<_, _>>()
-        .map_err(|err| {
-            anyhow::anyhow!(
-                "Failed to fetch shards for block {} with error: {}",
-                cache_block.block_height,
-                err
-            )
-        })
+        .map_err(internal_error)
 }
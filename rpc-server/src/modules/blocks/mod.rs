@@ -15,6 +15,30 @@ pub struct CacheBlock {
     pub epoch_id: near_primitives::hash::CryptoHash,
 }
 
+/// Result of the block height lookup by the block hash kept in the block hashes cache.
+/// Unknown hashes are kept only until `expires_at`, the block can be indexed later
+#[derive(Clone, Copy, Debug)]
+pub enum BlockHeightByHash {
+    Known(near_primitives::types::BlockHeight),
+    Unknown { expires_at: std::time::Instant },
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct RpcCongestionLevelRequest {
+    #[serde(flatten)]
+    pub block_reference: near_primitives::types::BlockReference,
+    pub shard_id: near_primitives::types::ShardId,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct RpcCongestionLevelResponse {
+    pub block_height: near_primitives::types::BlockHeight,
+    pub shard_id: near_primitives::types::ShardId,
+    /// From 0.0 (no congestion) to 1.0 (the shard doesn't accept the new receipts)
+    pub congestion_level: f64,
+    pub congestion_info: near_primitives::views::CongestionInfoView,
+}
+
 #[derive(Debug, Clone)]
 pub enum AccountChanges {
     None,
@@ -88,7 +112,7 @@ impl BlockInfo {
     // Create new BlockInfo from StreamerMessage.
     // This is using to update final and optimistic blocks regularly.
     pub async fn new_from_streamer_message(
-        streamer_message: near_indexer_primitives::StreamerMessage,
+        streamer_message: readnode_primitives::indexer::StreamerMessage,
     ) -> Self {
         Self {
             block_cache: CacheBlock::from(&streamer_message.block),
@@ -162,13 +186,13 @@ impl BlockInfo {
                     let acc = changes_map
                         .entry(account_id)
                         .or_insert_with(AccountChangesInBlock::new);
-                    acc.state_changes.insert(key.to_vec(), Some(value.to_vec()));
+                    acc.state_changes.insert(key.into(), Some(value.into()));
                 }
                 StateChangeValueView::DataDeletion { account_id, key } => {
                     let acc = changes_map
                         .entry(account_id)
                         .or_insert_with(AccountChangesInBlock::new);
-                    acc.state_changes.insert(key.to_vec(), None);
+                    acc.state_changes.insert(key.into(), None);
                 }
             }
         }
@@ -291,6 +315,10 @@ pub struct BlocksInfoByFinality {
     pub optimistic_changes: futures_locks::RwLock<OptimisticChanges>,
     pub current_validators: futures_locks::RwLock<CurrentValidatorInfo>,
     pub current_protocol_version: futures_locks::RwLock<CurrentProtocolVersion>,
+    // New final blocks with the state changes for the WebSocket subscriptions
+    pub final_blocks_sender: tokio::sync::broadcast::Sender<std::sync::Arc<BlockInfo>>,
+    // Wakes the requests waiting for the server to reach the block
+    pub head_updated: std::sync::Arc<tokio::sync::Notify>,
 }
 
 impl BlocksInfoByFinality {
@@ -329,6 +357,11 @@ impl BlocksInfoByFinality {
             current_protocol_version: futures_locks::RwLock::new(CurrentProtocolVersion {
                 protocol_version: near_primitives::version::PROTOCOL_VERSION,
             }),
+            final_blocks_sender: tokio::sync::broadcast::channel(
+                crate::ws::FINAL_BLOCKS_CHANNEL_CAPACITY,
+            )
+            .0,
+            head_updated: std::sync::Arc::new(tokio::sync::Notify::new()),
         }
     }
 
@@ -339,10 +372,18 @@ impl BlocksInfoByFinality {
             "Update final block info: {:?}",
             block_info.block_cache.block_height
         );
+        // The block is copied only if there are WebSocket connections to notify
+        if self.final_blocks_sender.receiver_count() > 0 {
+            let _ = self
+                .final_blocks_sender
+                .send(std::sync::Arc::new(block_info.clone()));
+        }
         let mut final_block_lock = self.final_block.write().await;
         final_block_lock.block_cache = block_info.block_cache;
         final_block_lock.block_view = block_info.block_view;
         final_block_lock.changes = block_info.changes;
+        drop(final_block_lock);
+        self.head_updated.notify_waiters();
     }
 
     // Update optimistic block changes and optimistic block info in the cache.
@@ -360,6 +401,9 @@ impl BlocksInfoByFinality {
         optimistic_block_lock.block_cache = block_info.block_cache;
         optimistic_block_lock.block_view = block_info.block_view;
         optimistic_block_lock.changes = block_info.changes;
+        drop(optimistic_block_lock);
+        drop(optimistic_changes_lock);
+        self.head_updated.notify_waiters();
     }
 
     // Update current validators info in the cache.
@@ -1,8 +1,13 @@
+use futures::StreamExt;
 use near_primitives::views::{StateChangeValueView, StateChangesRequestView};
 
 use crate::config::ServerContext;
 use crate::modules::blocks::methods::fetch_block;
-use crate::modules::blocks::CacheBlock;
+use crate::modules::blocks::{BlockHeightByHash, CacheBlock};
+
+// How long the unknown block hash is cached.
+// Short enough to not hide the blocks which are indexed a bit later
+const UNKNOWN_BLOCK_HASH_TTL: std::time::Duration = std::time::Duration::from_secs(2);
 
 // Helper function to check if the requested block height is within the range of the available blocks
 // If block height is lower than genesis block height, return an error block height is too low
@@ -41,6 +46,134 @@ pub async fn check_block_height(
     Ok(())
 }
 
+// Removes the expired unknown block hashes from the block hashes cache.
+// They are ignored on lookup anyway, but keep the cache memory until evicted
+pub async fn sweep_expired_block_hashes(
+    block_hashes_cache: &crate::cache::RwLockLruMemoryCache<
+        near_primitives::hash::CryptoHash,
+        BlockHeightByHash,
+    >,
+) -> anyhow::Result<()> {
+    let now = std::time::Instant::now();
+    let removed = block_hashes_cache
+        .retain(|block_height_by_hash| {
+            !matches!(
+                block_height_by_hash,
+                BlockHeightByHash::Unknown { expires_at } if *expires_at <= now
+            )
+        })
+        .await;
+    tracing::debug!(
+        "{} expired unknown block hashes removed from the cache",
+        removed
+    );
+    Ok(())
+}
+
+// Pre-populates the blocks caches with the last `blocks_count` blocks up to the final block,
+// so the first requests after a deploy don't hit the lake and the database with cold misses.
+// The blocks are fetched from the lake by `concurrency` requests at a time,
+// the skipped heights are not found and ignored
+pub async fn warm_up_blocks_cache(
+    data: actix_web::web::Data<ServerContext>,
+    blocks_count: u64,
+    concurrency: usize,
+) -> anyhow::Result<()> {
+    if blocks_count == 0 {
+        return Ok(());
+    }
+    let started_at = std::time::Instant::now();
+    let final_block_height = data
+        .blocks_info_by_finality
+        .final_cache_block()
+        .await
+        .block_height;
+    let from_block_height = final_block_height.saturating_sub(blocks_count - 1);
+    tracing::info!(
+        "Warming up the blocks cache with blocks {}..={}",
+        from_block_height,
+        final_block_height
+    );
+    let data = &data;
+    let block_heights = (from_block_height..=final_block_height).rev();
+    let warmed_blocks_count = futures::stream::iter(block_heights)
+        .map(|block_height| async move {
+            near_lake_framework::s3_fetchers::fetch_block(
+                &data.s3_client,
+                &data.s3_bucket_name,
+                block_height,
+            )
+            .await
+            .ok()
+        })
+        .buffer_unordered(concurrency.max(1))
+        .filter_map(|block_view| async move { block_view })
+        .fold(0u64, |warmed_blocks_count, block_view| async move {
+            let block = CacheBlock::from(&block_view);
+            data.blocks_cache.put(block.block_height, block).await;
+            data.block_hashes_cache
+                .put(
+                    block.block_hash,
+                    BlockHeightByHash::Known(block.block_height),
+                )
+                .await;
+            warmed_blocks_count + 1
+        })
+        .await;
+    tracing::info!(
+        "Blocks cache warmed up with {} blocks in {:?}",
+        warmed_blocks_count,
+        started_at.elapsed()
+    );
+    Ok(())
+}
+
+// Helper function to get the block height by the block hash
+// Looks up the block hashes cache first to avoid the database roundtrip.
+// Unknown hashes not found in the database are cached for `UNKNOWN_BLOCK_HASH_TTL`,
+// since clients retry the queries with unknown hashes aggressively
+pub async fn get_block_height_by_hash(
+    data: &actix_web::web::Data<ServerContext>,
+    block_hash: near_primitives::hash::CryptoHash,
+    method_name: &str,
+) -> anyhow::Result<near_primitives::types::BlockHeight> {
+    match data.block_hashes_cache.get(&block_hash).await {
+        Some(BlockHeightByHash::Known(block_height)) => return Ok(block_height),
+        Some(BlockHeightByHash::Unknown { expires_at })
+            if expires_at > std::time::Instant::now() =>
+        {
+            anyhow::bail!("Block {} is not found", block_hash)
+        }
+        _ => {}
+    }
+    match data
+        .db_manager
+        .get_block_height_by_hash(block_hash, method_name)
+        .await
+    {
+        Ok(block_height) => {
+            data.block_hashes_cache
+                .put(block_hash, BlockHeightByHash::Known(block_height))
+                .await;
+            Ok(block_height)
+        }
+        // Only the missing block is remembered, the database failures are not cached
+        // to not answer the known blocks as unknown until the entry expires
+        Err(err) if database::is_not_found_error(&err) => {
+            data.block_hashes_cache
+                .put(
+                    block_hash,
+                    BlockHeightByHash::Unknown {
+                        expires_at: std::time::Instant::now() + UNKNOWN_BLOCK_HASH_TTL,
+                    },
+                )
+                .await;
+            Err(err)
+        }
+        Err(err) => Err(err),
+    }
+}
+
 #[cfg_attr(
     feature = "tracing-instrumentation",
     tracing::instrument(skip(s3_client))
@@ -68,7 +201,7 @@ pub async fn fetch_chunk_from_s3(
         Ok(shard) => match shard.chunk {
             Some(chunk) => {
                 // We collect a list of local receipt ids to filter out local receipts from the chunk
-                let local_receipt_ids: Vec<near_indexer_primitives::CryptoHash> = chunk
+                let local_receipt_ids: Vec<readnode_primitives::indexer::CryptoHash> = chunk
                     .transactions
                     .iter()
                     .filter(|indexer_tx| {
@@ -126,15 +259,15 @@ pub async fn fetch_block_from_cache_or_get(
                     check_block_height(data, *block_height).await?;
                     *block_height
                 }
-                near_primitives::types::BlockId::Hash(hash) => data
-                    .db_manager
-                    .get_block_height_by_hash(*hash, method_name)
-                    .await
-                    .map_err(|err| {
-                        near_jsonrpc::primitives::types::blocks::RpcBlockError::UnknownBlock {
-                            error_message: err.to_string(),
-                        }
-                    })?,
+                near_primitives::types::BlockId::Hash(hash) => {
+                    get_block_height_by_hash(data, *hash, method_name)
+                        .await
+                        .map_err(|err| {
+                            near_jsonrpc::primitives::types::blocks::RpcBlockError::UnknownBlock {
+                                error_message: err.to_string(),
+                            }
+                        })?
+                }
             };
             data.blocks_cache.get(&block_height).await
         }
@@ -170,9 +303,18 @@ pub async fn fetch_block_from_cache_or_get(
             let block = CacheBlock::from(&block_from_s3.block_view);
 
             data.blocks_cache.put(block.block_height, block).await;
+            data.block_hashes_cache
+                .put(
+                    block.block_hash,
+                    BlockHeightByHash::Known(block.block_height),
+                )
+                .await;
             block
         }
     };
+    if let Some(access_sampler) = &data.access_sampler {
+        access_sampler.record_block(method_name, cache_block.block_timestamp);
+    }
     // increase block category metrics
     crate::metrics::increase_request_category_metrics(
         data,
@@ -244,8 +386,7 @@ pub(crate) fn is_matching_change(
             {
                 let key: Vec<u8> = key.clone().into();
                 let key_prefix: Vec<u8> = key_prefix.clone().into();
-                account_ids.contains(account_id)
-                    && hex::encode(key).starts_with(&hex::encode(key_prefix))
+                account_ids.contains(account_id) && key.starts_with(&key_prefix)
             } else {
                 false
             }
@@ -2,7 +2,9 @@ pub mod blocks;
 pub mod clients;
 pub mod gas;
 pub mod network;
+#[cfg(not(feature = "tx-only"))]
 pub mod queries;
 pub mod receipts;
+#[cfg(not(feature = "tx-only"))]
 pub mod state;
 pub mod transactions;
@@ -3,7 +3,7 @@ use near_primitives::epoch_manager::{AllEpochConfig, EpochConfig};
 
 use crate::config::ServerContext;
 use crate::modules::blocks::utils::fetch_block_from_cache_or_get;
-use crate::modules::network::get_protocol_version;
+use crate::modules::network::{get_epoch_id, get_protocol_version};
 
 pub async fn client_config(
     _data: Data<ServerContext>,
@@ -35,9 +35,16 @@ pub async fn split_storage_info(
 pub async fn status(
     data: Data<ServerContext>,
 ) -> Result<
-    near_primitives::views::StatusResponse,
+    crate::modules::network::RpcStatusResponse,
     near_jsonrpc::primitives::types::status::RpcStatusError,
 > {
+    let read_rpc_versions = crate::modules::network::ReadRpcVersions::fetch_cached(&data, "status")
+        .await
+        .map_err(
+            |err| near_jsonrpc::primitives::types::status::RpcStatusError::InternalError {
+                error_message: err.to_string(),
+            },
+        )?;
     let final_block = data.blocks_info_by_finality.final_cache_block().await;
     let validators = data.blocks_info_by_finality.validators().await;
     let current_validators = validators
@@ -49,7 +56,7 @@ pub async fn status(
         })
         .collect();
 
-    Ok(near_primitives::views::StatusResponse {
+    let status = near_primitives::views::StatusResponse {
         version: data.version.clone(),
         chain_id: data.genesis_info.genesis_config.chain_id.clone(),
         protocol_version: data
@@ -91,6 +98,10 @@ pub async fn status(
         // Not using for status method
         detailed_debug_status: None,
         genesis_hash: data.genesis_info.genesis_block_cache.block_hash,
+    };
+    Ok(crate::modules::network::RpcStatusResponse {
+        status,
+        read_rpc: read_rpc_versions,
     })
 }
 
@@ -279,6 +290,97 @@ pub async fn validators_ordered(
     }
 }
 
+/// Returns validators kickouts and stake changes of the historical epochs, newest epoch first.
+/// Lets staking dashboards render kickouts without archival nodes.
+#[cfg_attr(feature = "tracing-instrumentation", tracing::instrument(skip(data)))]
+pub async fn validators_changes(
+    data: Data<ServerContext>,
+    request_data: crate::modules::network::RpcValidatorsChangesRequest,
+) -> Result<
+    crate::modules::network::RpcValidatorsChangesResponse,
+    near_jsonrpc::primitives::errors::RpcError,
+> {
+    tracing::debug!(
+        "`EXPERIMENTAL_validators_changes` called with parameters: {:?}",
+        request_data
+    );
+    let limit = request_data
+        .limit
+        .unwrap_or(crate::modules::network::DEFAULT_VALIDATORS_CHANGES_LIMIT)
+        .clamp(1, crate::modules::network::MAX_VALIDATORS_CHANGES_LIMIT);
+    let (epochs, next_page_token) = data
+        .db_manager
+        .get_validators_changes_by_page(
+            limit,
+            request_data.next_page_token,
+            "EXPERIMENTAL_validators_changes",
+        )
+        .await
+        .map_err(|err| {
+            near_jsonrpc::primitives::errors::RpcError::new_internal_error(None, err.to_string())
+        })?;
+    Ok(crate::modules::network::RpcValidatorsChangesResponse {
+        epochs,
+        next_page_token,
+    })
+}
+
+/// Returns the blocks and the chunks produced by the validator in the epoch.
+/// Lets staking pools verify their production record without scraping every block.
+#[cfg_attr(feature = "tracing-instrumentation", tracing::instrument(skip(data)))]
+pub async fn produced_blocks(
+    data: Data<ServerContext>,
+    request_data: crate::modules::network::RpcProducedBlocksRequest,
+) -> Result<
+    crate::modules::network::RpcProducedBlocksResponse,
+    near_jsonrpc::primitives::errors::RpcError,
+> {
+    tracing::debug!(
+        "`EXPERIMENTAL_produced_blocks` called with parameters: {:?}",
+        request_data
+    );
+    let epoch_id = match request_data
+        .epoch_reference
+        .unwrap_or(near_primitives::types::EpochReference::Latest)
+    {
+        near_primitives::types::EpochReference::EpochId(epoch_id) => epoch_id.0,
+        near_primitives::types::EpochReference::BlockId(block_id) => {
+            fetch_block_from_cache_or_get(
+                &data,
+                &near_primitives::types::BlockReference::BlockId(block_id),
+                "EXPERIMENTAL_produced_blocks",
+            )
+            .await?
+            .epoch_id
+        }
+        near_primitives::types::EpochReference::Latest => {
+            data.blocks_info_by_finality
+                .final_cache_block()
+                .await
+                .epoch_id
+        }
+    };
+    let (produced_blocks, produced_chunks) = data
+        .db_manager
+        .get_produced_blocks(
+            &request_data.validator_id,
+            epoch_id,
+            "EXPERIMENTAL_produced_blocks",
+        )
+        .await
+        .map_err(|err| {
+            near_jsonrpc::primitives::errors::RpcError::new_internal_error(None, err.to_string())
+        })?;
+    Ok(crate::modules::network::RpcProducedBlocksResponse {
+        validator_id: request_data.validator_id,
+        epoch_id,
+        num_produced_blocks: produced_blocks.len() as u64,
+        num_produced_chunks: produced_chunks.len() as u64,
+        produced_blocks,
+        produced_chunks,
+    })
+}
+
 pub async fn genesis_config(
     data: Data<ServerContext>,
 ) -> Result<near_chain_configs::GenesisConfig, near_jsonrpc::primitives::errors::RpcError> {
@@ -373,6 +475,23 @@ async fn protocol_config_call(
     near_chain_configs::ProtocolConfigView,
     near_jsonrpc::primitives::types::config::RpcProtocolConfigError,
 > {
+    // The protocol config stored by the epoch-indexer is the one the nodes served in the epoch,
+    // it's computed from the genesis config for the epochs which are not indexed
+    if let Ok(epoch_id) = get_epoch_id(data, &block_reference, "EXPERIMENTAL_protocol_config").await
+    {
+        match data
+            .db_manager
+            .get_protocol_config_by_epoch_id(epoch_id, "EXPERIMENTAL_protocol_config")
+            .await
+        {
+            Ok(protocol_config) => return Ok(protocol_config),
+            Err(err) => tracing::debug!(
+                "Protocol config of the epoch {} is not stored, computing it: {:?}",
+                epoch_id,
+                err
+            ),
+        }
+    }
     let protocol_version =
         get_protocol_version(data, block_reference, "EXPERIMENTAL_protocol_config")
             .await
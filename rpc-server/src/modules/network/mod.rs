@@ -1,5 +1,222 @@
 pub mod methods;
 
+/// Number of epochs returned by `EXPERIMENTAL_validators_changes` if `limit` is not set
+const DEFAULT_VALIDATORS_CHANGES_LIMIT: u64 = 10;
+/// Max number of epochs returned by `EXPERIMENTAL_validators_changes`, greater `limit` is clamped
+const MAX_VALIDATORS_CHANGES_LIMIT: u64 = 100;
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Default)]
+pub struct RpcValidatorsChangesRequest {
+    pub limit: Option<u64>,
+    pub next_page_token: database::PageToken,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct RpcValidatorsChangesResponse {
+    pub epochs: Vec<readnode_primitives::EpochValidatorsChanges>,
+    pub next_page_token: database::PageToken,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct RpcProducedBlocksRequest {
+    pub validator_id: near_primitives::types::AccountId,
+    /// The latest epoch if not set
+    pub epoch_reference: Option<near_primitives::types::EpochReference>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct RpcProducedBlocksResponse {
+    pub validator_id: near_primitives::types::AccountId,
+    pub epoch_id: near_primitives::hash::CryptoHash,
+    pub num_produced_blocks: u64,
+    pub num_produced_chunks: u64,
+    pub produced_blocks: Vec<readnode_primitives::ProducedBlock>,
+    pub produced_chunks: Vec<readnode_primitives::ProducedChunk>,
+}
+
+/// `status` response extended with the versions of the rpc-server and the indexers
+#[derive(serde::Serialize, Debug)]
+pub struct RpcStatusResponse {
+    #[serde(flatten)]
+    pub status: near_primitives::views::StatusResponse,
+    pub read_rpc: ReadRpcVersions,
+}
+
+#[derive(serde::Serialize, Debug)]
+pub struct ReadRpcVersions {
+    pub crate_version: String,
+    /// Latest schema version the rpc-server understands
+    pub schema_version: u32,
+    /// Versions of the indexers writing the served data
+    pub writers: Vec<readnode_primitives::WriterVersion>,
+    /// False if any of the indexers writes the data with the newer schema version
+    pub compatible: bool,
+}
+
+/// How long the writer versions returned by `status` are cached.
+/// `status` is polled by the load balancers and the monitoring,
+/// while the versions change only when the indexers are redeployed
+const WRITER_VERSIONS_TTL: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// Writer versions read from the database with the time they are read at.
+/// The lock is held while the versions are read, so the concurrent
+/// `status` requests don't query the database at once when they expire
+#[derive(Default)]
+pub struct WriterVersionsCache(
+    tokio::sync::Mutex<Option<(std::time::Instant, Vec<readnode_primitives::WriterVersion>)>>,
+);
+
+impl WriterVersionsCache {
+    async fn get_or_fetch(
+        &self,
+        db_manager: &(dyn database::ReaderDbManager + Sync + Send),
+        method_name: &str,
+    ) -> anyhow::Result<Vec<readnode_primitives::WriterVersion>> {
+        let mut cached = self.0.lock().await;
+        if let Some((fetched_at, writers)) = cached.as_ref() {
+            if fetched_at.elapsed() < WRITER_VERSIONS_TTL {
+                return Ok(writers.clone());
+            }
+        }
+        let writers = db_manager.get_writer_versions(method_name).await?;
+        *cached = Some((std::time::Instant::now(), writers.clone()));
+        Ok(writers)
+    }
+}
+
+impl ReadRpcVersions {
+    pub(crate) async fn fetch(
+        db_manager: &(dyn database::ReaderDbManager + Sync + Send),
+        method_name: &str,
+    ) -> anyhow::Result<Self> {
+        let writers = db_manager.get_writer_versions(method_name).await?;
+        Ok(Self::new(writers))
+    }
+
+    /// Versions with the writers cached for `WRITER_VERSIONS_TTL`, used by `status`
+    pub(crate) async fn fetch_cached(
+        data: &crate::config::ServerContext,
+        method_name: &str,
+    ) -> anyhow::Result<Self> {
+        let writers = data
+            .writer_versions_cache
+            .get_or_fetch(&**data.db_manager, method_name)
+            .await?;
+        Ok(Self::new(writers))
+    }
+
+    fn new(writers: Vec<readnode_primitives::WriterVersion>) -> Self {
+        let compatible = writers
+            .iter()
+            .all(|writer| writer.schema_version <= database::SCHEMA_VERSION);
+        Self {
+            crate_version: env!("CARGO_PKG_VERSION").to_string(),
+            schema_version: database::SCHEMA_VERSION,
+            writers,
+            compatible,
+        }
+    }
+}
+
+/// Checks the indexers don't write the data with the newer schema version
+/// than the rpc-server understands. Such data may be misread after a partial upgrade,
+/// so the rpc-server refuses to start unless `allow_newer_schema` is set
+pub(crate) async fn check_schema_compatibility(
+    db_manager: &(dyn database::ReaderDbManager + Sync + Send),
+    allow_newer_schema: bool,
+) -> anyhow::Result<()> {
+    let versions = ReadRpcVersions::fetch(db_manager, "check_schema_compatibility").await?;
+    let newer_writers = versions
+        .writers
+        .iter()
+        .filter(|writer| writer.schema_version > database::SCHEMA_VERSION)
+        .map(|writer| {
+            format!(
+                "{} (version {}, schema {})",
+                writer.indexer_id, writer.crate_version, writer.schema_version
+            )
+        })
+        .collect::<Vec<_>>();
+    if newer_writers.is_empty() {
+        tracing::info!(
+            "Data schema version {} is compatible with {} indexers",
+            database::SCHEMA_VERSION,
+            versions.writers.len()
+        );
+        return Ok(());
+    }
+    let message = format!(
+        "Indexers write the data with the newer schema than the supported {}: {}",
+        database::SCHEMA_VERSION,
+        newer_writers.join(", ")
+    );
+    if allow_newer_schema {
+        tracing::warn!(
+            "{}. Serving anyway because `allow_newer_schema` is set",
+            message
+        );
+        Ok(())
+    } else {
+        anyhow::bail!(
+            "{}. Upgrade the rpc-server or set `allow_newer_schema`",
+            message
+        )
+    }
+}
+
+/// Checks the rpc-server and the database are on the configured network.
+/// The network is recorded in the database by the first indexer,
+/// the rpc-server doesn't write it and starts with the empty database
+pub(crate) async fn check_network(
+    data: &crate::config::ServerContext,
+    chain_id: &configuration::ChainId,
+) -> anyhow::Result<()> {
+    let network = readnode_primitives::NetworkIdentity {
+        chain_id: data.genesis_info.genesis_config.chain_id.clone(),
+        genesis_hash: data.genesis_info.genesis_block_cache.block_hash,
+    };
+    chain_id.ensure_matches(&network.chain_id)?;
+    match data.db_manager.get_network_identity("check_network").await? {
+        Some(stored_network) => {
+            network.ensure_same_network(&stored_network)?;
+            tracing::info!(
+                "Database contains the data of the network `{}` (genesis hash {})",
+                network.chain_id,
+                network.genesis_hash
+            );
+        }
+        None => tracing::warn!(
+            "Network of the data is not recorded in the database yet, it is recorded by the indexers"
+        ),
+    }
+    Ok(())
+}
+
+/// Returns the epoch of the block, the final block is used for all finality types
+pub(crate) async fn get_epoch_id(
+    data: &actix_web::web::Data<crate::config::ServerContext>,
+    block_reference: &near_primitives::types::BlockReference,
+    method_name: &str,
+) -> anyhow::Result<near_primitives::hash::CryptoHash> {
+    match block_reference {
+        near_primitives::types::BlockReference::Finality(_)
+        | near_primitives::types::BlockReference::SyncCheckpoint(_) => Ok(data
+            .blocks_info_by_finality
+            .final_cache_block()
+            .await
+            .epoch_id),
+        near_primitives::types::BlockReference::BlockId(_) => {
+            let block = crate::modules::blocks::utils::fetch_block_from_cache_or_get(
+                data,
+                block_reference,
+                method_name,
+            )
+            .await?;
+            Ok(block.epoch_id)
+        }
+    }
+}
+
 // Helper function to get the protocol version
 pub(crate) async fn get_protocol_version(
     data: &actix_web::web::Data<crate::config::ServerContext>,
@@ -55,6 +55,7 @@ impl CodeStorage {
                 &account_id,
                 block_height,
                 &[],
+                None,
                 "query_call_function",
             )
             .await
@@ -86,7 +87,7 @@ impl CodeStorage {
                 &self.db_manager,
                 &self.account_id,
                 self.block_height,
-                key.to_vec(),
+                key.into(),
                 "query_call_function",
             );
             let (_, data) = block_on(get_db_data);
@@ -101,7 +102,7 @@ impl CodeStorage {
         if let Some(value) = self.optimistic_data.get(key) {
             Ok(value.as_ref().map(|data| {
                 Box::new(StorageValuePtr {
-                    value: data.clone(),
+                    value: data.to_vec(),
                 }) as Box<_>
             }))
         } else {
@@ -115,7 +116,9 @@ impl CodeStorage {
     ) -> Result<Option<Box<dyn near_vm_runner::logic::ValuePtr>>> {
         let data = self.get_state_key_data(key);
         Ok(if !data.is_empty() {
-            Some(Box::new(StorageValuePtr { value: data }) as Box<_>)
+            Some(Box::new(StorageValuePtr {
+                value: data.into_inner(),
+            }) as Box<_>)
         } else {
             None
         })
@@ -242,14 +245,14 @@ impl near_vm_runner::logic::External for CodeStorage {
         _receiver_id: near_primitives::types::AccountId,
     ) -> Result<(
         near_vm_runner::logic::types::ReceiptIndex,
-        near_indexer_primitives::CryptoHash,
+        readnode_primitives::indexer::CryptoHash,
     )> {
         panic!("Prohibited in view. `create_promise_yield_receipt`");
     }
 
     fn submit_promise_resume_data(
         &mut self,
-        _data_id: near_indexer_primitives::CryptoHash,
+        _data_id: readnode_primitives::indexer::CryptoHash,
         _data: Vec<u8>,
     ) -> Result<bool> {
         panic!("Prohibited in view. `submit_promise_resume_data`");
@@ -38,6 +38,17 @@ pub async fn query(
     crate::metrics::METHOD_CALLS_COUNTER
         .with_label_values(&[method_name])
         .inc();
+    if let Some(access_sampler) = &data.access_sampler {
+        let account_id = match &request_data.request {
+            near_primitives::views::QueryRequest::ViewAccount { account_id }
+            | near_primitives::views::QueryRequest::ViewCode { account_id }
+            | near_primitives::views::QueryRequest::ViewAccessKey { account_id, .. }
+            | near_primitives::views::QueryRequest::ViewState { account_id, .. }
+            | near_primitives::views::QueryRequest::CallFunction { account_id, .. }
+            | near_primitives::views::QueryRequest::ViewAccessKeyList { account_id } => account_id,
+        };
+        access_sampler.record_account(account_id);
+    }
 
     if let near_primitives::types::BlockReference::Finality(
         near_primitives::types::Finality::None,
@@ -68,7 +79,7 @@ pub async fn query(
 /// fetch query result from read-rpc
 #[allow(unused_mut)]
 #[cfg_attr(feature = "tracing-instrumentation", tracing::instrument(skip(data)))]
-async fn query_call(
+pub(crate) async fn query_call(
     data: &Data<ServerContext>,
     mut query_request: near_jsonrpc::primitives::types::query::RpcQueryRequest,
     method_name: &str,
@@ -96,7 +107,17 @@ async fn query_call(
         near_primitives::views::QueryRequest::ViewAccessKey {
             account_id,
             public_key,
-        } => view_access_key(data, block, account_id, public_key, is_optimistic).await,
+        } => {
+            let result = view_access_key(data, block, account_id, public_key, is_optimistic).await;
+            // Pending nonces are relevant only for the latest state
+            if let near_primitives::types::BlockReference::Finality(_) =
+                &query_request.block_reference
+            {
+                with_pending_nonce(data, account_id, public_key, result).await
+            } else {
+                result
+            }
+        }
         near_primitives::views::QueryRequest::ViewState {
             account_id,
             prefix,
@@ -458,6 +479,23 @@ async fn view_state(
     } else {
         database_view_state(data, block, account_id, prefix).await?
     };
+    // The optimistic changes may add the keys to the ones read from the database
+    if state_item.len() > data.view_state_max_keys {
+        tracing::debug!(
+            "`view_state` call. AccountID {} has {} state keys with prefix {:?}, limit is {}",
+            account_id,
+            state_item.len(),
+            prefix,
+            data.view_state_max_keys,
+        );
+        return Err(
+            near_jsonrpc::primitives::types::query::RpcQueryError::TooLargeContractState {
+                contract_account_id: account_id.clone(),
+                block_height: block.block_height,
+                block_hash: block.block_hash,
+            },
+        );
+    }
 
     Ok(near_jsonrpc::primitives::types::query::RpcQueryResponse {
         kind: near_jsonrpc::primitives::types::query::QueryResponseKind::ViewState(
@@ -471,28 +509,59 @@ async fn view_state(
     })
 }
 
-#[cfg_attr(feature = "tracing-instrumentation", tracing::instrument(skip(data)))]
-async fn optimistic_view_state(
+/// Reads at most one key over `view_state_max_keys` from the database,
+/// so the state over the limit is rejected without loading all of it
+async fn view_state_from_db(
     data: &Data<ServerContext>,
     block: CacheBlock,
     account_id: &near_primitives::types::AccountId,
     prefix: &[u8],
 ) -> Result<
-    Vec<near_primitives::views::StateItem>,
+    std::collections::HashMap<readnode_primitives::StateKey, readnode_primitives::StateValue>,
     near_jsonrpc::primitives::types::query::RpcQueryError,
 > {
-    let mut optimistic_data = data
-        .blocks_info_by_finality
-        .optimistic_state_changes_in_block(account_id, prefix)
-        .await;
     let state_from_db = get_state_from_db(
         &data.db_manager,
         account_id,
         block.block_height,
         prefix,
+        Some(data.view_state_max_keys + 1),
         "query_view_state",
     )
     .await;
+    if state_from_db.len() > data.view_state_max_keys {
+        tracing::debug!(
+            "`view_state` call. AccountID {} has more than {} state keys with prefix {:?}",
+            account_id,
+            data.view_state_max_keys,
+            prefix,
+        );
+        return Err(
+            near_jsonrpc::primitives::types::query::RpcQueryError::TooLargeContractState {
+                contract_account_id: account_id.clone(),
+                block_height: block.block_height,
+                block_hash: block.block_hash,
+            },
+        );
+    }
+    Ok(state_from_db)
+}
+
+#[cfg_attr(feature = "tracing-instrumentation", tracing::instrument(skip(data)))]
+async fn optimistic_view_state(
+    data: &Data<ServerContext>,
+    block: CacheBlock,
+    account_id: &near_primitives::types::AccountId,
+    prefix: &[u8],
+) -> Result<
+    Vec<near_primitives::views::StateItem>,
+    near_jsonrpc::primitives::types::query::RpcQueryError,
+> {
+    let mut optimistic_data = data
+        .blocks_info_by_finality
+        .optimistic_state_changes_in_block(account_id, prefix)
+        .await;
+    let state_from_db = view_state_from_db(data, block, account_id, prefix).await?;
 
     let mut values: Vec<near_primitives::views::StateItem> = state_from_db
         .into_iter()
@@ -533,14 +602,7 @@ async fn database_view_state(
     Vec<near_primitives::views::StateItem>,
     near_jsonrpc::primitives::types::query::RpcQueryError,
 > {
-    let state_from_db = get_state_from_db(
-        &data.db_manager,
-        account_id,
-        block.block_height,
-        prefix,
-        "query_view_state",
-    )
-    .await;
+    let state_from_db = view_state_from_db(data, block, account_id, prefix).await?;
 
     let values: Vec<near_primitives::views::StateItem> = state_from_db
         .into_iter()
@@ -552,6 +614,47 @@ async fn database_view_state(
     Ok(values)
 }
 
+/// Bumps the nonce of the access key to the nonce of the last transaction sent
+/// through the rpc-server, if the indexer has not caught up with it yet
+async fn with_pending_nonce(
+    data: &Data<ServerContext>,
+    account_id: &near_primitives::types::AccountId,
+    public_key: &near_crypto::PublicKey,
+    result: Result<
+        near_jsonrpc::primitives::types::query::RpcQueryResponse,
+        near_jsonrpc::primitives::types::query::RpcQueryError,
+    >,
+) -> Result<
+    near_jsonrpc::primitives::types::query::RpcQueryResponse,
+    near_jsonrpc::primitives::types::query::RpcQueryError,
+> {
+    let Some(pending_nonces_cache) = &data.pending_nonces_cache else {
+        return result;
+    };
+    let mut response = result?;
+    if let near_jsonrpc::primitives::types::query::QueryResponseKind::AccessKey(access_key) =
+        &mut response.kind
+    {
+        match pending_nonces_cache
+            .get_pending_nonce(account_id, public_key)
+            .await
+        {
+            Ok(Some(pending_nonce)) if pending_nonce > access_key.nonce => {
+                tracing::debug!(
+                    "`view_access_key` call. AccountID {}, pending nonce {} overlays indexed nonce {}",
+                    account_id,
+                    pending_nonce,
+                    access_key.nonce,
+                );
+                access_key.nonce = pending_nonce;
+            }
+            Ok(_) => {}
+            Err(err) => tracing::warn!("Failed to get pending nonce: {:?}", err),
+        }
+    }
+    Ok(response)
+}
+
 #[cfg_attr(feature = "tracing-instrumentation", tracing::instrument(skip(data)))]
 async fn view_access_key(
     data: &Data<ServerContext>,
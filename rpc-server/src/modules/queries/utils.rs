@@ -32,6 +32,7 @@ pub async fn get_state_from_db(
     account_id: &near_primitives::types::AccountId,
     block_height: near_primitives::types::BlockHeight,
     prefix: &[u8],
+    max_keys: Option<usize>,
     method_name: &str,
 ) -> HashMap<readnode_primitives::StateKey, readnode_primitives::StateValue> {
     tracing::debug!(
@@ -41,7 +42,7 @@ pub async fn get_state_from_db(
         prefix,
     );
     db_manager
-        .get_account_state(account_id, block_height, prefix, method_name)
+        .get_account_state(account_id, block_height, prefix, max_keys, method_name)
         .await
         .unwrap_or_default()
 }
@@ -30,6 +30,114 @@ pub async fn receipt(
     receipt_result
 }
 
+/// Returns function calls to the contract, newest first, filtered by the method name
+/// and the substring of the decoded arguments or their `msg` field.
+/// Only the calls to the contracts listed in the `[function_calls_search]` config section
+/// of the tx-indexer are stored. Used by the support teams to trace the user actions.
+/// Pass the returned `cursor` to get the next page.
+#[cfg_attr(feature = "tracing-instrumentation", tracing::instrument(skip(data)))]
+pub async fn search_function_calls(
+    data: Data<ServerContext>,
+    request_data: crate::modules::receipts::RpcSearchFunctionCallsRequest,
+) -> Result<
+    crate::modules::receipts::RpcSearchFunctionCallsResponse,
+    near_jsonrpc::primitives::errors::RpcError,
+> {
+    tracing::debug!(
+        "`EXPERIMENTAL_search_function_calls` call. Params: {:?}",
+        request_data
+    );
+    if let Some(args_substring) = &request_data.args_substring {
+        if args_substring.chars().count() < crate::modules::receipts::MIN_ARGS_SUBSTRING_LENGTH {
+            return Err(near_jsonrpc::primitives::errors::RpcError::invalid_params(
+                format!(
+                    "`args_substring` should be at least {} characters long",
+                    crate::modules::receipts::MIN_ARGS_SUBSTRING_LENGTH
+                ),
+            ));
+        }
+    }
+    let limit = request_data
+        .limit
+        .unwrap_or(crate::modules::receipts::DEFAULT_SEARCH_FUNCTION_CALLS_LIMIT)
+        .clamp(1, crate::modules::receipts::MAX_SEARCH_FUNCTION_CALLS_LIMIT);
+
+    let (function_calls, cursor) = data
+        .db_manager
+        .search_function_calls(
+            &request_data.contract_id,
+            request_data.method_name.as_deref(),
+            request_data.args_substring.as_deref(),
+            limit,
+            request_data.cursor,
+            "EXPERIMENTAL_search_function_calls",
+        )
+        .await
+        .map_err(|err| {
+            near_jsonrpc::primitives::errors::RpcError::new_internal_error(None, err.to_string())
+        })?;
+
+    Ok(crate::modules::receipts::RpcSearchFunctionCallsResponse {
+        function_calls,
+        cursor,
+    })
+}
+
+/// Returns function calls of the contract method, newest first, optionally limited
+/// by the range of the block heights. The actions are stored only if `index_actions`
+/// is enabled in the tx-indexer. Used for the method-level analytics of the contracts.
+/// Pass the returned `cursor` to get the next page.
+#[cfg_attr(feature = "tracing-instrumentation", tracing::instrument(skip(data)))]
+pub async fn actions_by_contract_method(
+    data: Data<ServerContext>,
+    request_data: crate::modules::receipts::RpcActionsByContractMethodRequest,
+) -> Result<
+    crate::modules::receipts::RpcActionsByContractMethodResponse,
+    near_jsonrpc::primitives::errors::RpcError,
+> {
+    tracing::debug!(
+        "`EXPERIMENTAL_actions_by_contract_method` call. Params: {:?}",
+        request_data
+    );
+    if let (Some(from_block_height), Some(to_block_height)) =
+        (request_data.from_block_height, request_data.to_block_height)
+    {
+        if from_block_height > to_block_height {
+            return Err(near_jsonrpc::primitives::errors::RpcError::invalid_params(
+                format!(
+                    "from_block_height {} must not be greater than to_block_height {}",
+                    from_block_height, to_block_height
+                ),
+            ));
+        }
+    }
+    let limit = request_data
+        .limit
+        .unwrap_or(crate::modules::receipts::DEFAULT_ACTIONS_BY_CONTRACT_METHOD_LIMIT)
+        .clamp(
+            1,
+            crate::modules::receipts::MAX_ACTIONS_BY_CONTRACT_METHOD_LIMIT,
+        );
+
+    let (actions, cursor) = data
+        .db_manager
+        .get_actions_by_contract_method(
+            &request_data.contract_id,
+            &request_data.method_name,
+            request_data.from_block_height,
+            request_data.to_block_height,
+            limit,
+            request_data.cursor,
+            "EXPERIMENTAL_actions_by_contract_method",
+        )
+        .await
+        .map_err(|err| {
+            near_jsonrpc::primitives::errors::RpcError::new_internal_error(None, err.to_string())
+        })?;
+
+    Ok(crate::modules::receipts::RpcActionsByContractMethodResponse { actions, cursor })
+}
+
 /// Fetches a receipt record by it's ID
 #[cfg_attr(feature = "tracing-instrumentation", tracing::instrument(skip(data)))]
 pub async fn view_receipt_record(
@@ -58,7 +166,11 @@ async fn fetch_receipt(
     let receipt_id = request.receipt_reference.receipt_id;
 
     let receipt_record = fetch_receipt_record(data, request, "EXPERIMENTAL_receipt").await?;
+    if let Some(receipt_view) = receipt_record.receipt {
+        return Ok(near_jsonrpc::primitives::types::receipts::RpcReceiptResponse { receipt_view });
+    }
 
+    // The receipts stored without the body are looked up in the details of their transaction
     let transaction_details =
         try_get_transaction_details_by_hash(data, &receipt_record.parent_transaction_hash)
             .await
@@ -1,11 +1,57 @@
 pub mod methods;
 
+/// Default and maximum number of function calls returned by `EXPERIMENTAL_search_function_calls`
+const DEFAULT_SEARCH_FUNCTION_CALLS_LIMIT: u64 = 100;
+const MAX_SEARCH_FUNCTION_CALLS_LIMIT: u64 = 1000;
+
+/// Shorter substrings can't use the trigram index and would scan all the calls of the contract
+const MIN_ARGS_SUBSTRING_LENGTH: usize = 3;
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct RpcSearchFunctionCallsRequest {
+    pub contract_id: readnode_primitives::indexer::types::AccountId,
+    pub method_name: Option<String>,
+    pub args_substring: Option<String>,
+    pub limit: Option<u64>,
+    pub cursor: database::PageToken,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct RpcSearchFunctionCallsResponse {
+    pub function_calls: Vec<readnode_primitives::FunctionCallRecord>,
+    pub cursor: database::PageToken,
+}
+
+/// Default and maximum number of actions returned by `EXPERIMENTAL_actions_by_contract_method`
+const DEFAULT_ACTIONS_BY_CONTRACT_METHOD_LIMIT: u64 = 100;
+const MAX_ACTIONS_BY_CONTRACT_METHOD_LIMIT: u64 = 1000;
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct RpcActionsByContractMethodRequest {
+    pub contract_id: readnode_primitives::indexer::types::AccountId,
+    pub method_name: String,
+    /// Inclusive range of the block heights of the actions
+    pub from_block_height: Option<readnode_primitives::indexer::types::BlockHeight>,
+    pub to_block_height: Option<readnode_primitives::indexer::types::BlockHeight>,
+    pub limit: Option<u64>,
+    pub cursor: database::PageToken,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct RpcActionsByContractMethodResponse {
+    pub actions: Vec<readnode_primitives::ActionRecord>,
+    pub cursor: database::PageToken,
+}
+
 #[derive(serde::Serialize, serde::Deserialize, Debug)]
 pub struct RpcReceiptRecordResponse {
-    pub receipt_id: near_indexer_primitives::CryptoHash,
-    pub parent_transaction_hash: near_indexer_primitives::CryptoHash,
-    pub block_height: near_indexer_primitives::types::BlockHeight,
-    pub shard_id: near_indexer_primitives::types::ShardId,
+    pub receipt_id: readnode_primitives::indexer::CryptoHash,
+    pub parent_transaction_hash: readnode_primitives::indexer::CryptoHash,
+    pub block_height: readnode_primitives::indexer::types::BlockHeight,
+    pub shard_id: readnode_primitives::indexer::types::ShardId,
+    /// Position of the receipt execution outcome in the chunk of the block,
+    /// lets the clients check the on-chain order against the chunk
+    pub index_in_chunk: Option<u32>,
 }
 
 impl From<readnode_primitives::ReceiptRecord> for RpcReceiptRecordResponse {
@@ -15,6 +61,7 @@ impl From<readnode_primitives::ReceiptRecord> for RpcReceiptRecordResponse {
             parent_transaction_hash: receipt.parent_transaction_hash,
             block_height: receipt.block_height,
             shard_id: receipt.shard_id,
+            index_in_chunk: receipt.index_in_chunk,
         }
     }
 }
@@ -4,6 +4,8 @@ use crate::config::ServerContext;
 use crate::modules::blocks::utils::fetch_block_from_cache_or_get;
 use crate::modules::state::utils::get_state_from_db_paginated;
 
+/// Returns the contract state of the account by pages of up to `limit` keys ordered by key.
+/// Pass the returned `next_page_token` to get the next page, the last page has no token
 #[cfg_attr(feature = "tracing-instrumentation", tracing::instrument(skip(data)))]
 pub async fn view_state_paginated(
     data: Data<ServerContext>,
@@ -17,13 +19,22 @@ pub async fn view_state_paginated(
     let block =
         fetch_block_from_cache_or_get(&data, &block_reference, "view_state_paginated").await?;
 
+    let limit = request_data
+        .limit
+        .unwrap_or(crate::modules::state::DEFAULT_VIEW_STATE_PAGE_LIMIT)
+        .clamp(1, crate::modules::state::MAX_VIEW_STATE_PAGE_LIMIT);
+
     let state_values = get_state_from_db_paginated(
         &data.db_manager,
         &request_data.account_id,
         block.block_height,
+        limit,
         request_data.next_page_token,
     )
-    .await;
+    .await
+    .map_err(|err| {
+        near_jsonrpc::primitives::errors::RpcError::new_internal_error(None, err.to_string())
+    })?;
 
     Ok(crate::modules::state::RpcViewStatePaginatedResponse {
         values: state_values.values,
@@ -32,3 +43,402 @@ pub async fn view_state_paginated(
         block_hash: block.block_hash,
     })
 }
+
+/// Returns the values of the given state keys of the account read at the same block
+/// in one database query. Clients knowing the keys they need get a consistent snapshot
+/// of them instead of calling `view_state` for every key
+#[cfg_attr(feature = "tracing-instrumentation", tracing::instrument(skip(data)))]
+pub async fn view_state_keys(
+    data: Data<ServerContext>,
+    request_data: crate::modules::state::RpcViewStateKeysRequest,
+) -> Result<
+    crate::modules::state::RpcViewStateKeysResponse,
+    near_jsonrpc::primitives::errors::RpcError,
+> {
+    tracing::debug!(
+        "`EXPERIMENTAL_view_state_keys` call. Params: {:?}",
+        request_data
+    );
+    if request_data.keys.len() > crate::modules::state::MAX_VIEW_STATE_KEYS {
+        return Err(near_jsonrpc::primitives::errors::RpcError::invalid_params(
+            format!(
+                "Too many keys requested: {}, the maximum is {}",
+                request_data.keys.len(),
+                crate::modules::state::MAX_VIEW_STATE_KEYS
+            ),
+        ));
+    }
+    let block = fetch_block_from_cache_or_get(
+        &data,
+        &request_data.block_reference,
+        "EXPERIMENTAL_view_state_keys",
+    )
+    .await?;
+
+    let state_values = if request_data.keys.is_empty() {
+        std::collections::HashMap::new()
+    } else {
+        data.db_manager
+            .get_state_keys_values(
+                &request_data.account_id,
+                block.block_height,
+                &request_data.keys,
+                "EXPERIMENTAL_view_state_keys",
+            )
+            .await
+            .map_err(|err| {
+                near_jsonrpc::primitives::errors::RpcError::new_internal_error(
+                    None,
+                    err.to_string(),
+                )
+            })?
+    };
+
+    let values = request_data
+        .keys
+        .into_iter()
+        .map(|key| {
+            // The same key requested twice gets the value both times
+            let value = state_values.get(&key).cloned();
+            crate::modules::state::ViewStateKeyItem { key, value }
+        })
+        .collect();
+
+    Ok(crate::modules::state::RpcViewStateKeysResponse {
+        account_id: request_data.account_id,
+        values,
+        block_height: block.block_height,
+        block_hash: block.block_hash,
+    })
+}
+
+/// Returns the storage usage breakdown of the account: state keys count,
+/// total state keys and values size, contract code size and the storage staking requirement.
+#[cfg_attr(feature = "tracing-instrumentation", tracing::instrument(skip(data)))]
+pub async fn storage_breakdown(
+    data: Data<ServerContext>,
+    request_data: crate::modules::state::RpcStorageBreakdownRequest,
+) -> Result<
+    crate::modules::state::RpcStorageBreakdownResponse,
+    near_jsonrpc::primitives::errors::RpcError,
+> {
+    tracing::debug!(
+        "`EXPERIMENTAL_storage_breakdown` call. Params: {:?}",
+        request_data
+    );
+    let block = fetch_block_from_cache_or_get(
+        &data,
+        &request_data.block_reference,
+        "EXPERIMENTAL_storage_breakdown",
+    )
+    .await?;
+
+    let account = data
+        .db_manager
+        .get_account(
+            &request_data.account_id,
+            block.block_height,
+            "EXPERIMENTAL_storage_breakdown",
+        )
+        .await
+        .map_err(|err| {
+            if database::is_not_found_error(&err) {
+                near_jsonrpc::primitives::errors::RpcError::from(
+                    near_jsonrpc::primitives::types::query::RpcQueryError::UnknownAccount {
+                        requested_account_id: request_data.account_id.clone(),
+                        block_height: block.block_height,
+                        block_hash: block.block_hash,
+                    },
+                )
+            } else {
+                near_jsonrpc::primitives::errors::RpcError::new_internal_error(
+                    None,
+                    err.to_string(),
+                )
+            }
+        })?
+        .data;
+
+    // The state is scanned up to the same number of the keys `view_state` returns
+    let state_size = data
+        .db_manager
+        .get_state_size(
+            &request_data.account_id,
+            block.block_height,
+            data.view_state_max_keys,
+            "EXPERIMENTAL_storage_breakdown",
+        )
+        .await
+        .map_err(|err| {
+            near_jsonrpc::primitives::errors::RpcError::new_internal_error(None, err.to_string())
+        })?
+        .ok_or(
+            near_jsonrpc::primitives::types::query::RpcQueryError::TooLargeContractState {
+                contract_account_id: request_data.account_id.clone(),
+                block_height: block.block_height,
+                block_hash: block.block_hash,
+            },
+        )?;
+
+    let contract_code_bytes = if account.code_hash() == near_primitives::hash::CryptoHash::default()
+    {
+        0
+    } else {
+        data.db_manager
+            .get_contract_code(
+                &request_data.account_id,
+                block.block_height,
+                "EXPERIMENTAL_storage_breakdown",
+            )
+            .await
+            .map(|code| code.data.len() as u64)
+            .map_err(|err| {
+                near_jsonrpc::primitives::errors::RpcError::new_internal_error(
+                    None,
+                    err.to_string(),
+                )
+            })?
+    };
+
+    let protocol_version = crate::modules::network::get_protocol_version(
+        &data,
+        request_data.block_reference,
+        "EXPERIMENTAL_storage_breakdown",
+    )
+    .await
+    .map_err(|err| {
+        near_jsonrpc::primitives::errors::RpcError::new_internal_error(None, err.to_string())
+    })?;
+    let storage_amount_per_byte = near_parameters::RuntimeConfigStore::for_chain_id(
+        &data.genesis_info.genesis_config.chain_id,
+    )
+    .get_config(protocol_version)
+    .storage_amount_per_byte();
+
+    Ok(crate::modules::state::RpcStorageBreakdownResponse {
+        account_id: request_data.account_id,
+        state_keys_count: state_size.keys_count,
+        state_keys_bytes: state_size.keys_bytes,
+        state_values_bytes: state_size.values_bytes,
+        contract_code_bytes,
+        storage_usage: account.storage_usage(),
+        storage_amount_per_byte,
+        storage_staking_requirement: storage_amount_per_byte
+            * near_primitives::types::Balance::from(account.storage_usage()),
+        block_height: block.block_height,
+        block_hash: block.block_hash,
+    })
+}
+
+/// Returns the account data, contract presence, access keys count and the recent transactions
+/// in one response. Wallets need all of them on the startup
+#[cfg_attr(feature = "tracing-instrumentation", tracing::instrument(skip(data)))]
+pub async fn account_overview(
+    data: Data<ServerContext>,
+    request_data: crate::modules::state::RpcAccountOverviewRequest,
+) -> Result<
+    crate::modules::state::RpcAccountOverviewResponse,
+    near_jsonrpc::primitives::errors::RpcError,
+> {
+    tracing::debug!(
+        "`EXPERIMENTAL_account_overview` call. Params: {:?}",
+        request_data
+    );
+    let block = fetch_block_from_cache_or_get(
+        &data,
+        &request_data.block_reference,
+        "EXPERIMENTAL_account_overview",
+    )
+    .await?;
+    let transactions_limit = request_data
+        .transactions_limit
+        .unwrap_or(crate::modules::state::DEFAULT_ACCOUNT_OVERVIEW_TXS_LIMIT)
+        .clamp(1, crate::modules::state::MAX_ACCOUNT_OVERVIEW_TXS_LIMIT);
+
+    let (account, access_keys_count, public_keys) = futures::join!(
+        data.db_manager.get_account(
+            &request_data.account_id,
+            block.block_height,
+            "EXPERIMENTAL_account_overview",
+        ),
+        data.db_manager.get_account_access_keys_count(
+            &request_data.account_id,
+            block.block_height,
+            "EXPERIMENTAL_account_overview",
+        ),
+        data.db_manager.get_account_public_keys(
+            &request_data.account_id,
+            block.block_height,
+            "EXPERIMENTAL_account_overview",
+        ),
+    );
+    let account = account
+        .map_err(
+            |_err| near_jsonrpc::primitives::types::query::RpcQueryError::UnknownAccount {
+                requested_account_id: request_data.account_id.clone(),
+                block_height: block.block_height,
+                block_hash: block.block_hash,
+            },
+        )?
+        .data;
+    let access_keys_count = access_keys_count.map_err(|err| {
+        near_jsonrpc::primitives::errors::RpcError::new_internal_error(None, err.to_string())
+    })?;
+    let public_keys = public_keys.map_err(|err| {
+        near_jsonrpc::primitives::errors::RpcError::new_internal_error(None, err.to_string())
+    })?;
+
+    let recent_transactions = data
+        .db_manager
+        .get_recent_transactions_by_signer(
+            &request_data.account_id,
+            &public_keys,
+            block.block_height,
+            transactions_limit,
+            "EXPERIMENTAL_account_overview",
+        )
+        .await
+        .map_err(|err| {
+            near_jsonrpc::primitives::errors::RpcError::new_internal_error(None, err.to_string())
+        })?;
+
+    Ok(crate::modules::state::RpcAccountOverviewResponse {
+        account_id: request_data.account_id,
+        has_contract: account.code_hash() != near_primitives::hash::CryptoHash::default(),
+        account: near_primitives::views::AccountView::from(account),
+        access_keys_count,
+        recent_transactions,
+        block_height: block.block_height,
+        block_hash: block.block_hash,
+    })
+}
+
+/// Returns the contract state keys added, changed and removed between two blocks.
+/// Only the keys changed in the range are read, so the full state is never loaded.
+/// Pass the returned `next_page_token` to get the next page
+#[cfg_attr(feature = "tracing-instrumentation", tracing::instrument(skip(data)))]
+pub async fn state_diff(
+    data: Data<ServerContext>,
+    request_data: crate::modules::state::RpcStateDiffRequest,
+) -> Result<crate::modules::state::RpcStateDiffResponse, near_jsonrpc::primitives::errors::RpcError>
+{
+    tracing::debug!("`EXPERIMENTAL_state_diff` call. Params: {:?}", request_data);
+    let from_block = fetch_block_from_cache_or_get(
+        &data,
+        &near_primitives::types::BlockReference::BlockId(request_data.from_block.clone()),
+        "EXPERIMENTAL_state_diff",
+    )
+    .await?;
+    let to_block = fetch_block_from_cache_or_get(
+        &data,
+        &near_primitives::types::BlockReference::BlockId(request_data.to_block.clone()),
+        "EXPERIMENTAL_state_diff",
+    )
+    .await?;
+    if from_block.block_height >= to_block.block_height {
+        return Err(near_jsonrpc::primitives::errors::RpcError::invalid_params(
+            format!(
+                "from_block {} must be lower than to_block {}",
+                from_block.block_height, to_block.block_height
+            ),
+        ));
+    }
+    let limit = request_data
+        .limit
+        .unwrap_or(crate::modules::state::DEFAULT_STATE_DIFF_LIMIT)
+        .clamp(1, crate::modules::state::MAX_STATE_DIFF_LIMIT);
+
+    let (diff, next_page_token) = data
+        .db_manager
+        .get_state_diff_by_page(
+            &request_data.account_id,
+            from_block.block_height,
+            to_block.block_height,
+            &request_data.prefix_base64,
+            limit,
+            request_data.next_page_token,
+            "EXPERIMENTAL_state_diff",
+        )
+        .await
+        .map_err(|err| {
+            near_jsonrpc::primitives::errors::RpcError::new_internal_error(None, err.to_string())
+        })?;
+
+    let mut added = vec![];
+    let mut changed = vec![];
+    let mut removed = vec![];
+    for key_diff in diff {
+        match (key_diff.from_value, key_diff.to_value) {
+            (None, Some(to_value)) => added.push(near_primitives::views::StateItem {
+                key: key_diff.key.into(),
+                value: to_value.into(),
+            }),
+            (Some(from_value), None) => removed.push(near_primitives::views::StateItem {
+                key: key_diff.key.into(),
+                value: from_value.into(),
+            }),
+            (Some(from_value), Some(to_value)) if from_value != to_value => {
+                changed.push(crate::modules::state::StateDiffChangedItem {
+                    key: key_diff.key,
+                    from_value,
+                    to_value,
+                })
+            }
+            // The key was changed and restored or created and deleted within the range
+            _ => {}
+        }
+    }
+
+    Ok(crate::modules::state::RpcStateDiffResponse {
+        account_id: request_data.account_id,
+        added,
+        changed,
+        removed,
+        from_block_height: from_block.block_height,
+        from_block_hash: from_block.block_hash,
+        to_block_height: to_block.block_height,
+        to_block_hash: to_block.block_hash,
+        next_page_token,
+    })
+}
+
+/// Returns the accounts with the largest net contract state growth within the latest days
+/// aggregated by the state-indexer. The state growth analytics has to be enabled there
+#[cfg_attr(feature = "tracing-instrumentation", tracing::instrument(skip(data)))]
+pub async fn top_state_growers(
+    data: Data<ServerContext>,
+    request_data: crate::modules::state::RpcTopStateGrowersRequest,
+) -> Result<
+    crate::modules::state::RpcTopStateGrowersResponse,
+    near_jsonrpc::primitives::errors::RpcError,
+> {
+    tracing::debug!(
+        "`EXPERIMENTAL_top_state_growers` call. Params: {:?}",
+        request_data
+    );
+    let limit = request_data
+        .limit
+        .unwrap_or(crate::modules::state::DEFAULT_TOP_STATE_GROWERS_LIMIT)
+        .clamp(1, crate::modules::state::MAX_TOP_STATE_GROWERS_LIMIT);
+    let window_days = request_data
+        .window
+        .unwrap_or(crate::modules::state::DEFAULT_TOP_STATE_GROWERS_WINDOW_DAYS)
+        .clamp(1, crate::modules::state::MAX_TOP_STATE_GROWERS_WINDOW_DAYS);
+
+    let (latest_day, accounts) = data
+        .db_manager
+        .get_top_state_growers(window_days, limit, "EXPERIMENTAL_top_state_growers")
+        .await
+        .map_err(|err| {
+            near_jsonrpc::primitives::errors::RpcError::new_internal_error(None, err.to_string())
+        })?;
+
+    const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+    Ok(crate::modules::state::RpcTopStateGrowersResponse {
+        accounts,
+        window: window_days,
+        from_timestamp: latest_day
+            .map(|latest_day| (latest_day + 1).saturating_sub(window_days) * SECONDS_PER_DAY),
+        to_timestamp: latest_day.map(|latest_day| (latest_day + 1) * SECONDS_PER_DAY),
+    })
+}
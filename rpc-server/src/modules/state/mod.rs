@@ -7,17 +7,170 @@ pub struct PageStateValues {
     pub next_page_token: database::PageToken,
 }
 
+/// Default and maximum number of the state keys returned by `view_state_paginated` per page
+const DEFAULT_VIEW_STATE_PAGE_LIMIT: u64 = 1000;
+const MAX_VIEW_STATE_PAGE_LIMIT: u64 = 10_000;
+
 #[derive(serde::Serialize, serde::Deserialize, Debug)]
 pub struct RpcViewStatePaginatedRequest {
     pub account_id: near_primitives::types::AccountId,
     pub block_id: near_primitives::types::BlockId,
+    pub limit: Option<u64>,
     pub next_page_token: database::PageToken,
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Debug)]
 pub struct RpcViewStatePaginatedResponse {
+    /// State items of the page ordered by key
     pub values: Vec<near_primitives::views::StateItem>,
     pub block_height: near_primitives::types::BlockHeight,
     pub block_hash: near_primitives::hash::CryptoHash,
     pub next_page_token: database::PageToken,
 }
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct RpcStorageBreakdownRequest {
+    pub account_id: near_primitives::types::AccountId,
+    #[serde(flatten)]
+    pub block_reference: near_primitives::types::BlockReference,
+}
+
+/// Maximum number of the keys requested by `EXPERIMENTAL_view_state_keys` at once
+const MAX_VIEW_STATE_KEYS: usize = 1000;
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct RpcViewStateKeysRequest {
+    pub account_id: near_primitives::types::AccountId,
+    /// State keys to read, base64 (or hex) encoded
+    pub keys: Vec<readnode_primitives::StateKey>,
+    #[serde(flatten)]
+    pub block_reference: near_primitives::types::BlockReference,
+}
+
+/// Value of the requested state key, `null` if the key is absent in the state
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct ViewStateKeyItem {
+    pub key: readnode_primitives::StateKey,
+    pub value: Option<readnode_primitives::StateValue>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct RpcViewStateKeysResponse {
+    pub account_id: near_primitives::types::AccountId,
+    /// Values of the keys in the order of the request
+    pub values: Vec<ViewStateKeyItem>,
+    pub block_height: near_primitives::types::BlockHeight,
+    pub block_hash: near_primitives::hash::CryptoHash,
+}
+
+/// Default and maximum number of the changed keys returned by `EXPERIMENTAL_state_diff` per page
+const DEFAULT_STATE_DIFF_LIMIT: u64 = 1000;
+const MAX_STATE_DIFF_LIMIT: u64 = 10_000;
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct RpcStateDiffRequest {
+    pub account_id: near_primitives::types::AccountId,
+    pub from_block: near_primitives::types::BlockId,
+    pub to_block: near_primitives::types::BlockId,
+    /// Only the keys starting with the prefix are compared
+    #[serde(default)]
+    pub prefix_base64: readnode_primitives::StateKey,
+    pub limit: Option<u64>,
+    pub next_page_token: database::PageToken,
+}
+
+/// State key with different values at the `from_block` and the `to_block`
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct StateDiffChangedItem {
+    pub key: readnode_primitives::StateKey,
+    pub from_value: readnode_primitives::StateValue,
+    pub to_value: readnode_primitives::StateValue,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct RpcStateDiffResponse {
+    pub account_id: near_primitives::types::AccountId,
+    /// Keys absent at the `from_block` with their values at the `to_block`
+    pub added: Vec<near_primitives::views::StateItem>,
+    pub changed: Vec<StateDiffChangedItem>,
+    /// Keys absent at the `to_block` with their values at the `from_block`
+    pub removed: Vec<near_primitives::views::StateItem>,
+    pub from_block_height: near_primitives::types::BlockHeight,
+    pub from_block_hash: near_primitives::hash::CryptoHash,
+    pub to_block_height: near_primitives::types::BlockHeight,
+    pub to_block_hash: near_primitives::hash::CryptoHash,
+    pub next_page_token: database::PageToken,
+}
+
+/// Default and maximum number of the recent transactions returned by `EXPERIMENTAL_account_overview`
+const DEFAULT_ACCOUNT_OVERVIEW_TXS_LIMIT: u64 = 10;
+const MAX_ACCOUNT_OVERVIEW_TXS_LIMIT: u64 = 100;
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct RpcAccountOverviewRequest {
+    pub account_id: near_primitives::types::AccountId,
+    #[serde(flatten)]
+    pub block_reference: near_primitives::types::BlockReference,
+    /// Number of the recent transactions to return
+    pub transactions_limit: Option<u64>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct RpcAccountOverviewResponse {
+    pub account_id: near_primitives::types::AccountId,
+    pub account: near_primitives::views::AccountView,
+    /// `true` if the account has a deployed contract
+    pub has_contract: bool,
+    pub access_keys_count: u64,
+    /// Latest transactions signed by the account with its current access keys, newest first
+    pub recent_transactions: Vec<readnode_primitives::TransactionByPublicKeyRecord>,
+    pub block_height: near_primitives::types::BlockHeight,
+    pub block_hash: near_primitives::hash::CryptoHash,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct RpcStorageBreakdownResponse {
+    pub account_id: near_primitives::types::AccountId,
+    /// Number of the contract state keys
+    pub state_keys_count: u64,
+    /// Total size of the contract state keys in bytes
+    pub state_keys_bytes: u64,
+    /// Total size of the contract state values in bytes
+    pub state_values_bytes: u64,
+    /// Size of the deployed contract code in bytes
+    pub contract_code_bytes: u64,
+    /// Storage usage of the account as it is calculated by the runtime
+    pub storage_usage: near_primitives::types::StorageUsage,
+    #[serde(with = "near_primitives::serialize::dec_format")]
+    pub storage_amount_per_byte: near_primitives::types::Balance,
+    /// Amount of tokens locked to cover the account storage
+    #[serde(with = "near_primitives::serialize::dec_format")]
+    pub storage_staking_requirement: near_primitives::types::Balance,
+    pub block_height: near_primitives::types::BlockHeight,
+    pub block_hash: near_primitives::hash::CryptoHash,
+}
+
+/// Default and maximum number of the accounts returned by `EXPERIMENTAL_top_state_growers`
+const DEFAULT_TOP_STATE_GROWERS_LIMIT: u64 = 10;
+const MAX_TOP_STATE_GROWERS_LIMIT: u64 = 100;
+/// Default and maximum number of the days the state growth is summed over
+const DEFAULT_TOP_STATE_GROWERS_WINDOW_DAYS: u64 = 7;
+const MAX_TOP_STATE_GROWERS_WINDOW_DAYS: u64 = 90;
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct RpcTopStateGrowersRequest {
+    pub limit: Option<u64>,
+    /// Number of the latest aggregated days to sum the state growth over
+    pub window: Option<u64>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct RpcTopStateGrowersResponse {
+    /// Accounts ordered by the net state growth in bytes, largest first
+    pub accounts: Vec<readnode_primitives::StateGrowth>,
+    /// Number of the days the state growth is summed over
+    pub window: u64,
+    /// Unix timestamps (seconds) of the window start and end, `null` if nothing is aggregated yet
+    pub from_timestamp: Option<u64>,
+    pub to_timestamp: Option<u64>,
+}
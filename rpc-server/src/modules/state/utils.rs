@@ -6,29 +6,47 @@ pub async fn get_state_from_db_paginated(
     db_manager: &std::sync::Arc<Box<dyn database::ReaderDbManager + Sync + Send + 'static>>,
     account_id: &near_primitives::types::AccountId,
     block_height: near_primitives::types::BlockHeight,
+    limit: u64,
     page_token: database::PageToken,
-) -> crate::modules::state::PageStateValues {
+) -> anyhow::Result<crate::modules::state::PageStateValues> {
     tracing::debug!(
-        "`get_state_from_db_paginated` call. AccountId {}, block {}, page_token {:?}",
+        "`get_state_from_db_paginated` call. AccountId {}, block {}, limit {}, page_token {:?}",
         account_id,
         block_height,
+        limit,
         page_token,
     );
-    if let Ok((values, next_page_token)) = db_manager
-        .get_state_by_page(account_id, block_height, page_token, "view_state_paginated")
-        .await
-    {
-        crate::modules::state::PageStateValues {
-            values: values
-                .into_iter()
-                .map(|(k, v)| near_primitives::views::StateItem {
-                    key: k.into(),
-                    value: v.into(),
-                })
-                .collect(),
-            next_page_token,
-        }
+    // The page of the keys is read first and their values are read at the same block,
+    // so only one page of the state is in memory at once
+    let (keys, next_page_token) = db_manager
+        .get_state_keys_by_page(
+            account_id,
+            block_height,
+            limit,
+            page_token,
+            "view_state_paginated",
+        )
+        .await?;
+    let mut state_values = if keys.is_empty() {
+        std::collections::HashMap::new()
     } else {
-        crate::modules::state::PageStateValues::default()
-    }
+        db_manager
+            .get_state_keys_values(account_id, block_height, &keys, "view_state_paginated")
+            .await?
+    };
+    let values = keys
+        .into_iter()
+        .filter_map(|key| {
+            state_values
+                .remove(&key)
+                .map(|value| near_primitives::views::StateItem {
+                    key: key.into(),
+                    value: value.into(),
+                })
+        })
+        .collect();
+    Ok(crate::modules::state::PageStateValues {
+        values,
+        next_page_token,
+    })
 }
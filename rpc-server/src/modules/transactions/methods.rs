@@ -12,7 +12,13 @@ pub async fn send_tx(
     near_jsonrpc::primitives::types::transactions::RpcTransactionResponse,
     near_jsonrpc::primitives::types::transactions::RpcTransactionError,
 > {
-    data.near_rpc_client
+    let signed_transaction = request_data.signed_transaction.clone();
+    #[cfg(not(feature = "tx-only"))]
+    if data.send_tx_preflight {
+        super::utils::preflight_check(&data, &signed_transaction).await?;
+    }
+    let result = data
+        .near_rpc_client
         .call(request_data, Some("send_tx"))
         .await
         .map_err(|err| {
@@ -21,7 +27,347 @@ pub async fn send_tx(
                     debug_info: err.to_string(),
                 },
             )
-        })
+        })?;
+    save_pending_transaction(&data, &signed_transaction).await;
+    Ok(result)
+}
+
+/// Remembers the sent transaction for `EXPERIMENTAL_tx_pool` and its nonce to return it
+/// from `view_access_key` until the transaction is indexed.
+/// The nonce is not saved if the pending nonce overlay is disabled
+async fn save_pending_transaction(
+    data: &Data<ServerContext>,
+    signed_transaction: &near_primitives::transaction::SignedTransaction,
+) {
+    if let Some(tx_pool_cache) = &data.tx_pool_cache {
+        let submitted_at = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        if let Err(err) = tx_pool_cache
+            .set_pending_transaction(
+                &readnode_primitives::PendingTransaction::new(signed_transaction, submitted_at),
+                data.tx_pool_ttl,
+            )
+            .await
+        {
+            tracing::warn!(
+                "Failed to save pending transaction {}: {:?}",
+                signed_transaction.get_hash(),
+                err
+            );
+        }
+    }
+    if let Some(pending_nonces_cache) = &data.pending_nonces_cache {
+        let transaction = &signed_transaction.transaction;
+        if let Err(err) = pending_nonces_cache
+            .set_pending_nonce(
+                transaction.signer_id(),
+                transaction.public_key(),
+                transaction.nonce(),
+                data.pending_nonce_ttl,
+            )
+            .await
+        {
+            tracing::warn!(
+                "Failed to save pending nonce of transaction {}: {:?}",
+                signed_transaction.get_hash(),
+                err
+            );
+        }
+    }
+}
+
+/// Returns the transactions sent to the network through the rpc-servers which are not included
+/// yet, oldest first. Helps to debug the transactions vanished before the inclusion.
+/// The node doesn't expose its transaction pool, so only the transactions sent through
+/// read-rpc are known. They are kept for the `tx_pool_ttl`. The transaction is considered
+/// included once the nonce of its access key at the final block reaches the transaction nonce
+#[cfg_attr(feature = "tracing-instrumentation", tracing::instrument(skip(data)))]
+pub async fn tx_pool(
+    data: Data<ServerContext>,
+    request_data: crate::modules::transactions::RpcTxPoolRequest,
+) -> Result<
+    crate::modules::transactions::RpcTxPoolResponse,
+    near_jsonrpc::primitives::errors::RpcError,
+> {
+    tracing::debug!("`EXPERIMENTAL_tx_pool` call. Params: {:?}", request_data);
+    let Some(tx_pool_cache) = &data.tx_pool_cache else {
+        return Err(
+            near_jsonrpc::primitives::errors::RpcError::new_internal_error(
+                None,
+                "Transaction pool is not available, Redis is unavailable".to_string(),
+            ),
+        );
+    };
+    let mut transactions = tx_pool_cache
+        .get_pending_transactions(request_data.account_id.as_ref())
+        .await
+        .map_err(|err| {
+            near_jsonrpc::primitives::errors::RpcError::new_internal_error(None, err.to_string())
+        })?;
+
+    // The overlay of the pending nonces is applied to the finality references only,
+    // the access keys are read at the final block height to get the indexed nonces
+    let block_height = data
+        .blocks_info_by_finality
+        .final_cache_block()
+        .await
+        .block_height;
+    // The access keys are not indexed for the tx-only build,
+    // the transactions are kept until the pending nonce TTL
+    #[cfg(not(feature = "tx-only"))]
+    drop_included_transactions(&data, block_height, &mut transactions).await;
+    // The limit is applied to the pending transactions only, after the included ones are dropped
+    transactions.sort_by_key(|transaction| transaction.submitted_at);
+    transactions.truncate(crate::modules::transactions::MAX_TX_POOL_TRANSACTIONS);
+    Ok(crate::modules::transactions::RpcTxPoolResponse {
+        block_height,
+        transactions,
+    })
+}
+
+/// Drops the transactions with the nonce reached by the nonce of their access key
+/// indexed at the block height
+#[cfg(not(feature = "tx-only"))]
+async fn drop_included_transactions(
+    data: &Data<ServerContext>,
+    block_height: near_primitives::types::BlockHeight,
+    transactions: &mut Vec<readnode_primitives::PendingTransaction>,
+) {
+    let mut access_key_nonces = std::collections::HashMap::new();
+    for transaction in transactions.iter() {
+        let key = (
+            transaction.signer_id.clone(),
+            transaction.public_key.clone(),
+        );
+        if access_key_nonces.contains_key(&key) {
+            continue;
+        }
+        let Ok(public_key) = transaction.public_key.parse::<near_crypto::PublicKey>() else {
+            continue;
+        };
+        let query_request = near_jsonrpc::primitives::types::query::RpcQueryRequest {
+            block_reference: near_primitives::types::BlockReference::BlockId(
+                near_primitives::types::BlockId::Height(block_height),
+            ),
+            request: near_primitives::views::QueryRequest::ViewAccessKey {
+                account_id: transaction.signer_id.clone(),
+                public_key,
+            },
+        };
+        // The unknown access key is kept as pending, it can be added by the pending transaction
+        let access_key_nonce = match crate::modules::queries::methods::query_call(
+            data,
+            query_request,
+            "EXPERIMENTAL_tx_pool",
+            false,
+        )
+        .await
+        {
+            Ok(response) => match response.kind {
+                near_jsonrpc::primitives::types::query::QueryResponseKind::AccessKey(
+                    access_key,
+                ) => Some(access_key.nonce),
+                _ => None,
+            },
+            Err(_) => None,
+        };
+        access_key_nonces.insert(key, access_key_nonce);
+    }
+    transactions.retain(|transaction| {
+        access_key_nonces
+            .get(&(
+                transaction.signer_id.clone(),
+                transaction.public_key.clone(),
+            ))
+            .copied()
+            .flatten()
+            .map_or(true, |access_key_nonce| {
+                transaction.nonce > access_key_nonce
+            })
+    });
+}
+
+/// Returns transactions signed by the given public key, newest first.
+/// Used for key-rotation audits and compromised-key investigations.
+/// Pass the returned `cursor` to get the next page.
+#[cfg_attr(feature = "tracing-instrumentation", tracing::instrument(skip(data)))]
+pub async fn txs_by_public_key(
+    data: Data<ServerContext>,
+    request_data: crate::modules::transactions::RpcTransactionsByPublicKeyRequest,
+) -> Result<
+    crate::modules::transactions::RpcTransactionsByPublicKeyResponse,
+    near_jsonrpc::primitives::errors::RpcError,
+> {
+    tracing::debug!(
+        "`EXPERIMENTAL_txs_by_public_key` call. Params: {:?}",
+        request_data
+    );
+    let limit = request_data
+        .limit
+        .unwrap_or(crate::modules::transactions::DEFAULT_TXS_BY_PUBLIC_KEY_LIMIT)
+        .clamp(1, crate::modules::transactions::MAX_TXS_BY_PUBLIC_KEY_LIMIT);
+
+    let (transactions, cursor) = data
+        .db_manager
+        .get_transactions_by_public_key(
+            &request_data.public_key,
+            limit,
+            request_data.cursor,
+            "EXPERIMENTAL_txs_by_public_key",
+        )
+        .await
+        .map_err(|err| {
+            near_jsonrpc::primitives::errors::RpcError::new_internal_error(None, err.to_string())
+        })?;
+
+    Ok(
+        crate::modules::transactions::RpcTransactionsByPublicKeyResponse {
+            transactions,
+            cursor,
+        },
+    )
+}
+
+/// Returns the merkle proof of the transaction inclusion in the given block.
+/// The proof is built from the stored transaction outcome and the outcome roots of the chunks
+/// and is verified against the `outcome_root` of the block header before it is returned.
+/// Used by exchanges to prove the deposit transaction is included in the block
+#[cfg_attr(feature = "tracing-instrumentation", tracing::instrument(skip(data)))]
+pub async fn tx_inclusion_proof(
+    data: Data<ServerContext>,
+    request_data: crate::modules::transactions::RpcTxInclusionProofRequest,
+) -> Result<
+    crate::modules::transactions::RpcTxInclusionProofResponse,
+    near_jsonrpc::primitives::errors::RpcError,
+> {
+    tracing::debug!(
+        "`EXPERIMENTAL_tx_inclusion_proof` call. Params: {:?}",
+        request_data
+    );
+    let outcome_proof = crate::modules::transactions::try_get_transaction_details_by_hash(
+        &data,
+        &request_data.tx_hash,
+    )
+    .await
+    .map_err(|_err| {
+        inclusion_proof_error(
+            "UNKNOWN_TRANSACTION",
+            format!("Transaction {} is not found", request_data.tx_hash),
+        )
+    })?
+    .transaction_outcome;
+    if outcome_proof.block_hash != request_data.block_hash {
+        return Err(inclusion_proof_error(
+            "TRANSACTION_NOT_IN_BLOCK",
+            format!(
+                "Transaction {} is included in block {}, not in block {}",
+                request_data.tx_hash, outcome_proof.block_hash, request_data.block_hash
+            ),
+        ));
+    }
+
+    let block_height = crate::modules::blocks::utils::get_block_height_by_hash(
+        &data,
+        request_data.block_hash,
+        "EXPERIMENTAL_tx_inclusion_proof",
+    )
+    .await
+    .map_err(|err| inclusion_proof_error("UNKNOWN_BLOCK", err.to_string()))?;
+
+    // The outcomes of the chunk are committed to by the next chunk of the same shard,
+    // its header carries the outcome root of the previous chunk
+    let shard_outcome_root = near_primitives::merkle::compute_root_from_path(
+        &outcome_proof.proof,
+        near_primitives::hash::CryptoHash::hash_borsh(outcome_proof.to_hashes()),
+    );
+    let final_block_height = data
+        .blocks_info_by_finality
+        .final_cache_block()
+        .await
+        .block_height;
+    let last_block_height = final_block_height
+        .min(block_height + crate::modules::transactions::MAX_TX_INCLUSION_PROOF_BLOCKS_SCAN);
+    for next_block_height in block_height + 1..=last_block_height {
+        // Skipped heights have no blocks
+        let Ok(next_block) = crate::modules::blocks::methods::fetch_block(
+            &data,
+            &near_primitives::types::BlockReference::BlockId(
+                near_primitives::types::BlockId::Height(next_block_height),
+            ),
+            "EXPERIMENTAL_tx_inclusion_proof",
+        )
+        .await
+        else {
+            continue;
+        };
+        let header = &next_block.block_view.header;
+        let Some(shard_index) = next_block.block_view.chunks.iter().position(|chunk| {
+            chunk.height_included == header.height && chunk.outcome_root == shard_outcome_root
+        }) else {
+            continue;
+        };
+        let outcome_roots: Vec<_> = next_block
+            .block_view
+            .chunks
+            .iter()
+            .map(|chunk| chunk.outcome_root)
+            .collect();
+        let (_, mut outcome_root_proofs) = near_primitives::merkle::merklize(&outcome_roots);
+        let outcome_root_proof = outcome_root_proofs.swap_remove(shard_index);
+        if !near_primitives::merkle::verify_path(
+            header.outcome_root,
+            &outcome_root_proof,
+            shard_outcome_root,
+        ) {
+            return Err(
+                near_jsonrpc::primitives::errors::RpcError::new_internal_error(
+                    None,
+                    format!(
+                        "Outcome root proof doesn't match the header of block {}",
+                        header.hash
+                    ),
+                ),
+            );
+        }
+        return Ok(crate::modules::transactions::RpcTxInclusionProofResponse {
+            tx_hash: request_data.tx_hash,
+            block_hash: request_data.block_hash,
+            block_height,
+            shard_id: next_block.block_view.chunks[shard_index].shard_id,
+            outcome_proof,
+            shard_outcome_root,
+            outcome_root_proof,
+            outcome_root_block_hash: header.hash,
+            outcome_root_block_height: header.height,
+            outcome_root: header.outcome_root,
+        });
+    }
+    Err(inclusion_proof_error(
+        "PROOF_NOT_AVAILABLE",
+        format!(
+            "Block with the outcome root of transaction {} is not found in blocks {}..={}",
+            request_data.tx_hash,
+            block_height + 1,
+            last_block_height
+        ),
+    ))
+}
+
+fn inclusion_proof_error(
+    name: &str,
+    error_message: String,
+) -> near_jsonrpc::primitives::errors::RpcError {
+    near_jsonrpc::primitives::errors::RpcError::new_handler_error(
+        Some(serde_json::Value::String(error_message.clone())),
+        serde_json::json!({
+            "name": name,
+            "info": {
+                "error_message": error_message,
+            }
+        }),
+    )
 }
 
 /// Queries status of a transaction by hash and returns the final transaction result.
@@ -35,7 +381,13 @@ pub async fn tx(
 > {
     tracing::debug!("`tx` call. Params: {:?}", request_data);
 
-    let tx_result = tx_status_common(&data, &request_data.transaction_info, false).await;
+    let tx_result = tx_status_common(
+        &data,
+        &request_data.transaction_info,
+        &request_data.wait_until,
+        false,
+    )
+    .await;
 
     #[cfg(feature = "shadow-data-consistency")]
     {
@@ -69,7 +421,13 @@ pub async fn tx_status(
 > {
     tracing::debug!("`tx_status` call. Params: {:?}", request_data);
 
-    let tx_result = tx_status_common(&data, &request_data.transaction_info, true).await;
+    let tx_result = tx_status_common(
+        &data,
+        &request_data.transaction_info,
+        &request_data.wait_until,
+        true,
+    )
+    .await;
 
     #[cfg(feature = "shadow-data-consistency")]
     {
@@ -98,16 +456,23 @@ pub async fn broadcast_tx_async(
     request_data: near_jsonrpc::primitives::types::transactions::RpcSendTransactionRequest,
 ) -> Result<near_primitives::hash::CryptoHash, near_jsonrpc::primitives::errors::RpcError> {
     tracing::debug!("`broadcast_tx_async` call. Params: {:?}", request_data);
+    #[cfg(not(feature = "tx-only"))]
+    if data.send_tx_preflight {
+        super::utils::preflight_check(&data, &request_data.signed_transaction).await?;
+    }
     let proxy_params =
         near_jsonrpc_client::methods::broadcast_tx_async::RpcBroadcastTxAsyncRequest {
-            signed_transaction: request_data.signed_transaction,
+            signed_transaction: request_data.signed_transaction.clone(),
         };
     match data
         .near_rpc_client
         .call(proxy_params, Some("broadcast_tx_async"))
         .await
     {
-        Ok(resp) => Ok(resp),
+        Ok(resp) => {
+            save_pending_transaction(&data, &request_data.signed_transaction).await;
+            Ok(resp)
+        }
         Err(err) => Err(
             near_jsonrpc::primitives::errors::RpcError::new_internal_error(None, err.to_string()),
         ),
@@ -123,9 +488,13 @@ pub async fn broadcast_tx_commit(
     near_jsonrpc::primitives::types::transactions::RpcTransactionError,
 > {
     tracing::debug!("`broadcast_tx_commit` call. Params: {:?}", request_data);
+    #[cfg(not(feature = "tx-only"))]
+    if data.send_tx_preflight {
+        super::utils::preflight_check(&data, &request_data.signed_transaction).await?;
+    }
     let proxy_params =
         near_jsonrpc_client::methods::broadcast_tx_commit::RpcBroadcastTxCommitRequest {
-            signed_transaction: request_data.signed_transaction,
+            signed_transaction: request_data.signed_transaction.clone(),
         };
     let result = data
         .near_rpc_client
@@ -138,6 +507,7 @@ pub async fn broadcast_tx_commit(
                 },
             )
         })?;
+    save_pending_transaction(&data, &request_data.signed_transaction).await;
     Ok(
         near_jsonrpc::primitives::types::transactions::RpcTransactionResponse {
             final_execution_outcome: Some(FinalExecutionOutcome(result)),
@@ -153,6 +523,7 @@ pub async fn broadcast_tx_commit(
 async fn tx_status_common(
     data: &Data<ServerContext>,
     transaction_info: &near_jsonrpc::primitives::types::transactions::TransactionInfo,
+    wait_until: &near_primitives::views::TxExecutionStatus,
     fetch_receipt: bool,
 ) -> Result<
     near_jsonrpc::primitives::types::transactions::RpcTransactionResponse,
@@ -169,42 +540,77 @@ async fn tx_status_common(
         } => *tx_hash,
     };
 
-    let transaction_details = super::try_get_transaction_details_by_hash(data, &tx_hash)
-        .await
-        .map_err(|err| {
-            // logging the error at debug level since it's expected to see some "not found"
-            // errors in the logs that doesn't mean that something is really wrong, but want to
-            // keep track of them to see if there are any patterns
-            tracing::debug!("Error while fetching transaction details: {:?}", err);
-            near_jsonrpc::primitives::types::transactions::RpcTransactionError::UnknownTransaction {
-                requested_transaction_hash: tx_hash,
-            }
-        })?;
+    // logging the error at debug level since it's expected to see some "not found"
+    // errors in the logs that doesn't mean that something is really wrong, but want to
+    // keep track of them to see if there are any patterns
+    let map_not_found_error = |err: anyhow::Error| {
+        tracing::debug!("Error while fetching transaction details: {:?}", err);
+        near_jsonrpc::primitives::types::transactions::RpcTransactionError::UnknownTransaction {
+            requested_transaction_hash: tx_hash,
+        }
+    };
 
-    // TODO (@kobayurii): rewrite this since we support optimistic finalities already
-    if fetch_receipt {
-        Ok(
-            near_jsonrpc::primitives::types::transactions::RpcTransactionResponse {
-                final_execution_outcome: Some(FinalExecutionOutcomeWithReceipt(
+    let finished_outcome = if fetch_receipt {
+        super::try_get_finished_transaction_details_by_hash(data, &tx_hash)
+            .await
+            .map(|transaction_details| {
+                FinalExecutionOutcomeWithReceipt(
                     transaction_details.to_final_execution_outcome_with_receipts(),
-                )),
-                // With the fact that we don't support non-finalised data yet,
-                // final_execution_status field can be always filled with FINAL.
-                // This logic will be more complicated when we add support of optimistic blocks.
-                final_execution_status: near_primitives::views::TxExecutionStatus::Final,
-            },
-        )
+                )
+            })
     } else {
-        Ok(
+        // `tx` doesn't need receipts, so we avoid reading the full transaction details if possible
+        super::try_get_transaction_outcome_by_hash(data, &tx_hash)
+            .await
+            .map(FinalExecutionOutcome)
+    };
+    if let Ok(final_execution_outcome) = finished_outcome {
+        return Ok(
             near_jsonrpc::primitives::types::transactions::RpcTransactionResponse {
-                final_execution_outcome: Some(FinalExecutionOutcome(
-                    transaction_details.to_final_execution_outcome(),
-                )),
-                // With the fact that we don't support non-finalised data yet,
-                // final_execution_status field can be always filled with FINAL.
-                // This logic will be more complicated when we add support of optimistic blocks.
+                final_execution_outcome: Some(final_execution_outcome),
                 final_execution_status: near_primitives::views::TxExecutionStatus::Final,
             },
-        )
+        );
+    }
+
+    // The transaction is not finished yet. The transaction in process is returned
+    // if it has reached the requested status, there is nothing to wait for in read-rpc
+    let (transaction_details, execution_status) =
+        super::try_get_collecting_transaction_by_hash(data, &tx_hash)
+            .await
+            .map_err(map_not_found_error)?;
+    let is_reached = match wait_until {
+        near_primitives::views::TxExecutionStatus::None
+        | near_primitives::views::TxExecutionStatus::Included
+        | near_primitives::views::TxExecutionStatus::IncludedFinal => true,
+        near_primitives::views::TxExecutionStatus::ExecutedOptimistic
+        | near_primitives::views::TxExecutionStatus::Executed => {
+            execution_status == near_primitives::views::TxExecutionStatus::Executed
+        }
+        near_primitives::views::TxExecutionStatus::Final => false,
+    };
+    if !is_reached {
+        return Err(
+            near_jsonrpc::primitives::types::transactions::RpcTransactionError::TimeoutError,
+        );
     }
+    // Like nearcore, the outcome is returned only when the transaction is executed
+    let final_execution_outcome =
+        if execution_status == near_primitives::views::TxExecutionStatus::Executed {
+            Some(if fetch_receipt {
+                FinalExecutionOutcomeWithReceipt(
+                    transaction_details.to_final_execution_outcome_with_receipts(),
+                )
+            } else {
+                FinalExecutionOutcome(transaction_details.to_final_execution_outcome())
+            })
+        } else {
+            None
+        };
+    Ok(
+        near_jsonrpc::primitives::types::transactions::RpcTransactionResponse {
+            final_execution_outcome,
+            final_execution_status: execution_status,
+        },
+    )
 }
@@ -3,10 +3,137 @@ use actix_web::web::Data;
 use crate::config::ServerContext;
 
 pub mod methods;
+#[cfg(not(feature = "tx-only"))]
+mod utils;
+
+/// Number of transactions returned by `EXPERIMENTAL_txs_by_public_key` if `limit` is not set
+const DEFAULT_TXS_BY_PUBLIC_KEY_LIMIT: u64 = 100;
+/// Max number of transactions returned by `EXPERIMENTAL_txs_by_public_key`, greater `limit` is clamped
+const MAX_TXS_BY_PUBLIC_KEY_LIMIT: u64 = 1000;
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct RpcTransactionsByPublicKeyRequest {
+    pub public_key: near_crypto::PublicKey,
+    pub limit: Option<u64>,
+    pub cursor: database::PageToken,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct RpcTransactionsByPublicKeyResponse {
+    pub transactions: Vec<readnode_primitives::TransactionByPublicKeyRecord>,
+    pub cursor: database::PageToken,
+}
+
+/// Max number of the transactions returned by `EXPERIMENTAL_tx_pool`
+const MAX_TX_POOL_TRANSACTIONS: usize = 1000;
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct RpcTxPoolRequest {
+    /// Transactions of all the signers if not set
+    #[serde(default)]
+    pub account_id: Option<near_primitives::types::AccountId>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct RpcTxPoolResponse {
+    /// Final block height the transactions are checked to be not included at
+    pub block_height: near_primitives::types::BlockHeight,
+    pub transactions: Vec<readnode_primitives::PendingTransaction>,
+}
+
+/// How many blocks after the transaction block are scanned
+/// for the next chunk of the shard carrying the transaction outcome root
+const MAX_TX_INCLUSION_PROOF_BLOCKS_SCAN: u64 = 20;
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct RpcTxInclusionProofRequest {
+    pub tx_hash: readnode_primitives::indexer::CryptoHash,
+    /// Block the transaction is claimed to be included in
+    pub block_hash: readnode_primitives::indexer::CryptoHash,
+}
+
+/// Proof of the transaction inclusion.
+/// `outcome_proof.proof` leads from the transaction outcome to `shard_outcome_root`,
+/// `outcome_root_proof` leads from `shard_outcome_root` to the `outcome_root`
+/// of the header of the block `outcome_root_block_hash`
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct RpcTxInclusionProofResponse {
+    pub tx_hash: readnode_primitives::indexer::CryptoHash,
+    pub block_hash: readnode_primitives::indexer::CryptoHash,
+    pub block_height: near_primitives::types::BlockHeight,
+    pub shard_id: near_primitives::types::ShardId,
+    pub outcome_proof: near_primitives::views::ExecutionOutcomeWithIdView,
+    pub shard_outcome_root: readnode_primitives::indexer::CryptoHash,
+    pub outcome_root_proof: near_primitives::merkle::MerklePath,
+    pub outcome_root_block_hash: readnode_primitives::indexer::CryptoHash,
+    pub outcome_root_block_height: near_primitives::types::BlockHeight,
+    pub outcome_root: readnode_primitives::indexer::CryptoHash,
+}
+
+/// Returns the outcome without receipts of the finished transaction.
+/// Reads the slim outcome stored alongside the transaction details
+/// and falls back to the full transaction details for the older transactions
+pub(crate) async fn try_get_transaction_outcome_by_hash(
+    data: &Data<ServerContext>,
+    tx_hash: &readnode_primitives::indexer::CryptoHash,
+) -> anyhow::Result<near_primitives::views::FinalExecutionOutcomeView> {
+    if let Ok(outcome_bytes) = &data
+        .tx_details_storage
+        .retrieve_outcome(&tx_hash.to_string())
+        .await
+    {
+        readnode_primitives::TransactionDetails::outcome_deserialize(outcome_bytes)
+    } else {
+        Ok(try_get_finished_transaction_details_by_hash(data, tx_hash)
+            .await?
+            .to_final_execution_outcome())
+    }
+}
+
+/// Returns the finished transaction from the transaction details storage
+pub(crate) async fn try_get_finished_transaction_details_by_hash(
+    data: &Data<ServerContext>,
+    tx_hash: &readnode_primitives::indexer::CryptoHash,
+) -> anyhow::Result<readnode_primitives::TransactionDetails> {
+    let transaction_details_bytes = data
+        .tx_details_storage
+        .retrieve(&tx_hash.to_string())
+        .await?;
+    readnode_primitives::TransactionDetails::tx_deserialize(&transaction_details_bytes)
+}
+
+/// Returns the transaction in process collected by the tx-indexer with its execution status.
+/// The tx-indexer indexes only the final blocks, so the collected transaction is
+/// `INCLUDED_FINAL` until its result is known and `EXECUTED` after that.
+/// The transaction becomes `FINAL` once all its receipts are executed
+/// and it is moved to the transaction details storage
+pub(crate) async fn try_get_collecting_transaction_by_hash(
+    data: &Data<ServerContext>,
+    tx_hash: &readnode_primitives::indexer::CryptoHash,
+) -> anyhow::Result<(
+    readnode_primitives::TransactionDetails,
+    near_primitives::views::TxExecutionStatus,
+)> {
+    let Some(tx_cache_storage) = &data.tx_cache_storage else {
+        anyhow::bail!("Transaction not found")
+    };
+    let transaction_details = tx_cache_storage.get_tx_by_tx_hash(tx_hash).await?;
+    let execution_status = match transaction_details.status {
+        near_primitives::views::FinalExecutionStatus::SuccessValue(_)
+        | near_primitives::views::FinalExecutionStatus::Failure(_) => {
+            near_primitives::views::TxExecutionStatus::Executed
+        }
+        near_primitives::views::FinalExecutionStatus::NotStarted
+        | near_primitives::views::FinalExecutionStatus::Started => {
+            near_primitives::views::TxExecutionStatus::IncludedFinal
+        }
+    };
+    Ok((transaction_details, execution_status))
+}
 
 pub(crate) async fn try_get_transaction_details_by_hash(
     data: &Data<ServerContext>,
-    tx_hash: &near_indexer_primitives::CryptoHash,
+    tx_hash: &readnode_primitives::indexer::CryptoHash,
 ) -> anyhow::Result<readnode_primitives::TransactionDetails> {
     if let Ok(transaction_details_bytes) =
         &data.tx_details_storage.retrieve(&tx_hash.to_string()).await
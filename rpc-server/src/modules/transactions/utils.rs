@@ -0,0 +1,102 @@
+use actix_web::web::Data;
+use near_jsonrpc::primitives::types::query::{QueryResponseKind, RpcQueryError};
+use near_primitives::errors::InvalidTxError;
+
+use crate::config::ServerContext;
+
+/// Validates the signed transaction against the indexed access key before it is sent
+/// to the network, so the transaction which is certain to fail is rejected right away
+/// with the same error the network would return seconds later.
+/// The index may lag behind the network, so only the errors the newer state can't fix
+/// are returned: the invalid signature and the nonce already used at the final block
+/// (the nonces of the access key only grow). The signer, the access key, the balance
+/// or the permissions may be changed by the blocks not indexed yet, such transactions
+/// and the ones the state is not available for are forwarded to the network as is
+pub(crate) async fn preflight_check(
+    data: &Data<ServerContext>,
+    signed_transaction: &near_primitives::transaction::SignedTransaction,
+) -> Result<(), near_jsonrpc::primitives::types::transactions::RpcTransactionError> {
+    let Err(err) = check_transaction(data, signed_transaction).await else {
+        return Ok(());
+    };
+    let error_type = match &err {
+        InvalidTxError::InvalidSignature => "invalid_signature",
+        _ => "invalid_nonce",
+    };
+    crate::metrics::SEND_TX_PREFLIGHT_REJECTIONS
+        .with_label_values(&[error_type])
+        .inc();
+    tracing::debug!(
+        "Transaction {} is rejected by the preflight check: {:?}",
+        signed_transaction.get_hash(),
+        err
+    );
+    Err(
+        near_jsonrpc::primitives::types::transactions::RpcTransactionError::InvalidTransaction {
+            context: err,
+        },
+    )
+}
+
+// The checks follow the order of the transaction validation by the network,
+// so the client gets the same error
+async fn check_transaction(
+    data: &Data<ServerContext>,
+    signed_transaction: &near_primitives::transaction::SignedTransaction,
+) -> Result<(), InvalidTxError> {
+    let transaction = &signed_transaction.transaction;
+    let public_key = transaction.public_key();
+    if !signed_transaction
+        .signature
+        .verify(signed_transaction.get_hash().as_ref(), public_key)
+    {
+        return Err(InvalidTxError::InvalidSignature);
+    }
+
+    let access_key_request = near_primitives::views::QueryRequest::ViewAccessKey {
+        account_id: transaction.signer_id().clone(),
+        public_key: public_key.clone(),
+    };
+    // The unknown signer or access key may be created by the blocks not indexed yet
+    let Ok(QueryResponseKind::AccessKey(access_key)) =
+        query_final_state(data, access_key_request).await
+    else {
+        return Ok(());
+    };
+    // Retries of the transactions known to the indexer are answered by the network
+    // with the transaction status, they are not rejected
+    if transaction.nonce() <= access_key.nonce
+        && super::try_get_transaction_details_by_hash(data, &signed_transaction.get_hash())
+            .await
+            .is_err()
+    {
+        return Err(InvalidTxError::InvalidNonce {
+            tx_nonce: transaction.nonce(),
+            ak_nonce: access_key.nonce,
+        });
+    }
+    Ok(())
+}
+
+// Reads the state at the final block. The optimistic state may be reverted
+// and the latest state is overlaid with the nonces of the transactions
+// which are sent but may never be included
+async fn query_final_state(
+    data: &Data<ServerContext>,
+    request: near_primitives::views::QueryRequest,
+) -> Result<QueryResponseKind, RpcQueryError> {
+    let final_block_height = data
+        .blocks_info_by_finality
+        .final_cache_block()
+        .await
+        .block_height;
+    let query_request = near_jsonrpc::primitives::types::query::RpcQueryRequest {
+        block_reference: near_primitives::types::BlockReference::BlockId(
+            near_primitives::types::BlockId::Height(final_block_height),
+        ),
+        request,
+    };
+    crate::modules::queries::methods::query_call(data, query_request, "send_tx_preflight", false)
+        .await
+        .map(|response| response.kind)
+}
@@ -0,0 +1,141 @@
+/// Links of the wallet and the explorer and the linkdrop account the CLI needs
+/// to create the accounts and to show the sent transactions, known for the public networks only
+struct NetworkHints {
+    wallet_url: &'static str,
+    explorer_transaction_url: &'static str,
+    linkdrop_account_id: &'static str,
+}
+
+fn network_hints(chain_id: &str) -> Option<NetworkHints> {
+    match chain_id {
+        "mainnet" => Some(NetworkHints {
+            wallet_url: "https://app.mynearwallet.com/",
+            explorer_transaction_url: "https://nearblocks.io/txns/",
+            linkdrop_account_id: "near",
+        }),
+        "testnet" => Some(NetworkHints {
+            wallet_url: "https://testnet.mynearwallet.com/",
+            explorer_transaction_url: "https://testnet.nearblocks.io/txns/",
+            linkdrop_account_id: "testnet",
+        }),
+        _ => None,
+    }
+}
+
+/// Network connection of near-cli-rs with the read-rpc specifics
+#[derive(Debug, serde::Serialize)]
+struct NetworkConfig {
+    network_name: String,
+    rpc_url: String,
+    wallet_url: Option<&'static str>,
+    explorer_transaction_url: Option<&'static str>,
+    linkdrop_account_id: Option<&'static str>,
+    /// Header the API key of the client is sent in
+    rpc_api_key_header: &'static str,
+    /// The requests without the API key are rejected
+    rpc_api_key_required: bool,
+    /// Methods served by the deployment, the CLI warns about the operations using the other ones
+    methods: Vec<&'static str>,
+    /// near-cli-rs command adding the connection, the missing settings are prompted
+    add_connection_command: String,
+}
+
+/// Returns the near-cli-rs network connection of the deployment, so the CLI tooling
+/// is pointed at it with one command. The RPC URL is the one the request is sent to,
+/// the `Forwarded` and `X-Forwarded-*` headers of the load balancer are respected
+#[actix_web::get("/network-config.json")]
+pub(crate) async fn get_network_config(
+    req: actix_web::HttpRequest,
+    data: actix_web::web::Data<crate::config::ServerContext>,
+) -> actix_web::HttpResponse {
+    let connection_info = req.connection_info();
+    let rpc_url = format!("{}://{}/", connection_info.scheme(), connection_info.host());
+    let network_name = data.genesis_info.genesis_config.chain_id.clone();
+    let hints = network_hints(&network_name);
+
+    let add_connection_command = add_connection_command(&network_name, &rpc_url, hints.as_ref());
+
+    actix_web::HttpResponse::Ok().json(NetworkConfig {
+        rpc_url,
+        wallet_url: hints.as_ref().map(|hints| hints.wallet_url),
+        explorer_transaction_url: hints.as_ref().map(|hints| hints.explorer_transaction_url),
+        linkdrop_account_id: hints.as_ref().map(|hints| hints.linkdrop_account_id),
+        rpc_api_key_header: crate::api_keys::API_KEY_HEADER,
+        rpc_api_key_required: data.api_keys.is_required(),
+        methods: crate::rpc_methods::methods()
+            .filter(|method| {
+                !cfg!(feature = "tx-only") || crate::rpc_methods::is_tx_only_method(method)
+            })
+            .filter(|method| !data.method_access.is_disabled(method))
+            .collect(),
+        add_connection_command,
+        network_name,
+    })
+}
+
+/// near-cli-rs command adding the connection. The RPC URL is built from the request headers,
+/// so every value is shell-quoted to keep the command safe to paste into the shell
+fn add_connection_command(
+    network_name: &str,
+    rpc_url: &str,
+    hints: Option<&NetworkHints>,
+) -> String {
+    let mut add_connection_command = format!(
+        "near config add-connection --network-name {} --connection-name {} --rpc-url {}",
+        shell_quote(network_name),
+        shell_quote(&format!("read-rpc-{}", network_name)),
+        shell_quote(rpc_url),
+    );
+    if let Some(hints) = hints {
+        add_connection_command.push_str(&format!(
+            " --wallet-url {} --explorer-transaction-url {}",
+            shell_quote(hints.wallet_url),
+            shell_quote(hints.explorer_transaction_url)
+        ));
+    }
+    add_connection_command
+}
+
+/// Wraps the value in the single quotes, the quotes inside it are closed, escaped and reopened
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shell_quote() {
+        assert_eq!(shell_quote("mainnet"), "'mainnet'");
+        assert_eq!(shell_quote("it's"), r"'it'\''s'");
+        assert_eq!(shell_quote("$(rm -rf ~)`id`;"), "'$(rm -rf ~)`id`;'");
+    }
+
+    #[test]
+    fn test_add_connection_command_of_public_network() {
+        assert_eq!(
+            add_connection_command(
+                "mainnet",
+                "https://read-rpc.example.com/",
+                network_hints("mainnet").as_ref()
+            ),
+            "near config add-connection --network-name 'mainnet' --connection-name 'read-rpc-mainnet' \
+            --rpc-url 'https://read-rpc.example.com/' --wallet-url 'https://app.mynearwallet.com/' \
+            --explorer-transaction-url 'https://nearblocks.io/txns/'"
+        );
+    }
+
+    #[test]
+    fn test_add_connection_command_quotes_forged_host() {
+        let command = add_connection_command(
+            "localnet",
+            "http://example.com';touch /tmp/pwned;'/",
+            network_hints("localnet").as_ref(),
+        );
+        assert_eq!(
+            command,
+            r"near config add-connection --network-name 'localnet' --connection-name 'read-rpc-localnet' --rpc-url 'http://example.com'\'';touch /tmp/pwned;'\''/'"
+        );
+    }
+}
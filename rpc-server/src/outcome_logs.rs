@@ -0,0 +1,86 @@
+/// Request param to opt out of the logs truncation. It is taken out of the params
+/// before they are parsed by the method
+const TRUNCATE_LOGS_PARAM: &str = "truncate_logs";
+
+/// Methods returning the transaction outcomes with the logs
+pub(crate) const METHODS_WITH_LOGS: &[&str] = &[
+    "tx",
+    "EXPERIMENTAL_tx_status",
+    "broadcast_tx_commit",
+    "send_tx",
+];
+
+/// Takes the opt-out param out of the request params.
+/// Returns `false` if the client asked for the full logs
+pub(crate) fn take_truncate_logs_param(params: &mut serde_json::Value) -> bool {
+    params
+        .as_object_mut()
+        .and_then(|params| params.remove(TRUNCATE_LOGS_PARAM))
+        .and_then(|value| value.as_bool())
+        .unwrap_or(true)
+}
+
+/// Truncates the logs of every outcome in the serialized transaction response
+/// to `max_logs_size` bytes. Some contracts emit megabytes of logs,
+/// the truncation is applied to the response only, the stored outcomes are not changed
+pub(crate) fn truncate_outcomes_logs(value: &mut serde_json::Value, max_logs_size: usize) {
+    let Some(result) = value.as_object_mut() else {
+        return;
+    };
+    if let Some(outcome) = result.get_mut("transaction_outcome") {
+        truncate_outcome_logs(outcome, max_logs_size);
+    }
+    if let Some(serde_json::Value::Array(outcomes)) = result.get_mut("receipts_outcome") {
+        for outcome in outcomes {
+            truncate_outcome_logs(outcome, max_logs_size);
+        }
+    }
+}
+
+/// Keeps the leading logs fitting into `max_logs_size` bytes, the first log not fitting
+/// is cut at the char boundary and the rest are dropped.
+/// The truncated outcome is marked with `logs_truncated: true`
+fn truncate_outcome_logs(outcome_with_id: &mut serde_json::Value, max_logs_size: usize) {
+    let Some(outcome) = outcome_with_id
+        .get_mut("outcome")
+        .and_then(serde_json::Value::as_object_mut)
+    else {
+        return;
+    };
+    let Some(serde_json::Value::Array(logs)) = outcome.get_mut("logs") else {
+        return;
+    };
+    let logs_size: usize = logs
+        .iter()
+        .filter_map(serde_json::Value::as_str)
+        .map(str::len)
+        .sum();
+    if logs_size <= max_logs_size {
+        return;
+    }
+    let mut remaining_size = max_logs_size;
+    let mut truncated_logs = vec![];
+    for log in logs.iter().filter_map(serde_json::Value::as_str) {
+        if log.len() <= remaining_size {
+            remaining_size -= log.len();
+            truncated_logs.push(serde_json::Value::String(log.to_string()));
+        } else {
+            if remaining_size > 0 {
+                truncated_logs.push(serde_json::Value::String(
+                    truncate_at_char_boundary(log, remaining_size).to_string(),
+                ));
+            }
+            break;
+        }
+    }
+    *logs = truncated_logs;
+    outcome.insert("logs_truncated".to_string(), serde_json::Value::Bool(true));
+}
+
+fn truncate_at_char_boundary(value: &str, max_len: usize) -> &str {
+    let mut end = max_len.min(value.len());
+    while !value.is_char_boundary(end) {
+        end -= 1;
+    }
+    &value[..end]
+}
@@ -0,0 +1,1066 @@
+// Schemas of the JSON-RPC params of the methods. The params are checked against them
+// before they are parsed by the method, so the error names the field and its expected type
+// instead of the generic deserialization failure. The checks are applied to the object
+// params only, the positional (array) params of the standard methods are left to the method
+
+#[derive(Debug, Clone, Copy)]
+enum ParamType {
+    AccountId,
+    CryptoHash,
+    PublicKey,
+    /// Block height or block hash
+    BlockId,
+    Integer,
+    Boolean,
+    String,
+    Base64,
+    /// Contract state key, base64 or hex encoded
+    StateKey,
+    /// Page token or cursor returned by the previous page
+    PageToken,
+    Array,
+    Object,
+    Enum(&'static [&'static str]),
+}
+
+impl ParamType {
+    fn expected(&self) -> String {
+        match self {
+            ParamType::AccountId => "an account id string".to_string(),
+            ParamType::CryptoHash => "a base58 encoded hash string".to_string(),
+            ParamType::PublicKey => "a public key string (e.g. `ed25519:...`)".to_string(),
+            ParamType::BlockId => "a block height integer or a block hash string".to_string(),
+            ParamType::Integer => "a non-negative integer".to_string(),
+            ParamType::Boolean => "a boolean".to_string(),
+            ParamType::String => "a string".to_string(),
+            ParamType::Base64 => "a base64 encoded string".to_string(),
+            ParamType::StateKey => "a base64 (or hex) encoded string".to_string(),
+            ParamType::PageToken => "a token string from the previous page or null".to_string(),
+            ParamType::Array => "an array".to_string(),
+            ParamType::Object => "an object".to_string(),
+            ParamType::Enum(values) => format!("one of: {}", values.join(", ")),
+        }
+    }
+
+    fn matches(&self, value: &serde_json::Value) -> bool {
+        match self {
+            ParamType::AccountId => {
+                serde_json::from_value::<near_primitives::types::AccountId>(value.clone()).is_ok()
+            }
+            ParamType::CryptoHash => {
+                serde_json::from_value::<near_primitives::hash::CryptoHash>(value.clone()).is_ok()
+            }
+            ParamType::PublicKey => {
+                serde_json::from_value::<near_crypto::PublicKey>(value.clone()).is_ok()
+            }
+            ParamType::BlockId => {
+                serde_json::from_value::<near_primitives::types::BlockId>(value.clone()).is_ok()
+            }
+            ParamType::Integer => value.is_u64(),
+            ParamType::Boolean => value.is_boolean(),
+            ParamType::String => value.is_string(),
+            ParamType::Base64 => {
+                serde_json::from_value::<near_primitives::types::FunctionArgs>(value.clone())
+                    .is_ok()
+            }
+            ParamType::StateKey => {
+                serde_json::from_value::<readnode_primitives::StateKey>(value.clone()).is_ok()
+            }
+            ParamType::PageToken => value.is_string() || value.is_null(),
+            ParamType::Array => value.is_array(),
+            ParamType::Object => value.is_object(),
+            ParamType::Enum(values) => value
+                .as_str()
+                .map_or(false, |value| values.contains(&value)),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Field {
+    name: &'static str,
+    param_type: ParamType,
+    required: bool,
+}
+
+const fn required(name: &'static str, param_type: ParamType) -> Field {
+    Field {
+        name,
+        param_type,
+        required: true,
+    }
+}
+
+const fn optional(name: &'static str, param_type: ParamType) -> Field {
+    Field {
+        name,
+        param_type,
+        required: false,
+    }
+}
+
+/// Fields of which at most one can be set, one of them is required if `required`
+#[derive(Debug)]
+struct OneOf {
+    fields: &'static [&'static str],
+    required: bool,
+}
+
+#[derive(Debug)]
+struct ParamsSchema {
+    fields: &'static [Field],
+    one_of: Option<OneOf>,
+    /// Standard methods accept the params as an array of the positional values
+    positional: bool,
+}
+
+const FINALITY: ParamType = ParamType::Enum(&["optimistic", "near-final", "final"]);
+const SYNC_CHECKPOINT: ParamType = ParamType::Enum(&["genesis", "earliest_available"]);
+const WAIT_UNTIL: ParamType = ParamType::Enum(&[
+    "NONE",
+    "INCLUDED",
+    "EXECUTED_OPTIMISTIC",
+    "INCLUDED_FINAL",
+    "EXECUTED",
+    "FINAL",
+]);
+const BLOCK_REFERENCE_FIELDS: &[&str] = &["block_id", "finality", "sync_checkpoint"];
+
+/// Declares the schema of the params with the flattened block reference
+/// (`block_id`, `finality` or `sync_checkpoint`)
+macro_rules! with_block_reference {
+    ($required:expr, $positional:expr, [$($field:expr),* $(,)?]) => {
+        ParamsSchema {
+            fields: &[
+                $($field,)*
+                optional("block_id", ParamType::BlockId),
+                optional("finality", FINALITY),
+                optional("sync_checkpoint", SYNC_CHECKPOINT),
+            ],
+            one_of: Some(OneOf {
+                fields: BLOCK_REFERENCE_FIELDS,
+                required: $required,
+            }),
+            positional: $positional,
+        }
+    };
+}
+
+macro_rules! schema {
+    ($positional:expr, [$($field:expr),* $(,)?]) => {
+        ParamsSchema {
+            fields: &[$($field,)*],
+            one_of: None,
+            positional: $positional,
+        }
+    };
+}
+
+static BLOCK: ParamsSchema = with_block_reference!(true, true, []);
+static CHUNK_BY_ID: ParamsSchema = schema!(true, [required("chunk_id", ParamType::CryptoHash)]);
+static CHUNK_BY_BLOCK_SHARD: ParamsSchema = schema!(
+    true,
+    [
+        required("block_id", ParamType::BlockId),
+        required("shard_id", ParamType::Integer),
+    ]
+);
+static GAS_PRICE: ParamsSchema = schema!(true, [optional("block_id", ParamType::BlockId)]);
+static TX_BY_HASH: ParamsSchema = schema!(
+    true,
+    [
+        required("tx_hash", ParamType::CryptoHash),
+        required("sender_account_id", ParamType::AccountId),
+        optional("wait_until", WAIT_UNTIL),
+    ]
+);
+static TX_SIGNED: ParamsSchema = schema!(
+    true,
+    [
+        required("signed_tx_base64", ParamType::Base64),
+        optional("wait_until", WAIT_UNTIL),
+    ]
+);
+static RECEIPT: ParamsSchema = schema!(true, [required("receipt_id", ParamType::CryptoHash)]);
+static NEXT_LIGHT_CLIENT_BLOCK: ParamsSchema =
+    schema!(true, [required("last_block_hash", ParamType::CryptoHash)]);
+const LIGHT_CLIENT_PROOF_TYPES: ParamType = ParamType::Enum(&["transaction", "receipt"]);
+static LIGHT_CLIENT_TRANSACTION_PROOF: ParamsSchema = schema!(
+    true,
+    [
+        required("type", LIGHT_CLIENT_PROOF_TYPES),
+        required("transaction_hash", ParamType::CryptoHash),
+        required("sender_id", ParamType::AccountId),
+        required("light_client_head", ParamType::CryptoHash),
+    ]
+);
+static LIGHT_CLIENT_RECEIPT_PROOF: ParamsSchema = schema!(
+    true,
+    [
+        required("type", LIGHT_CLIENT_PROOF_TYPES),
+        required("receipt_id", ParamType::CryptoHash),
+        required("receiver_id", ParamType::AccountId),
+        required("light_client_head", ParamType::CryptoHash),
+    ]
+);
+static VALIDATORS: ParamsSchema = ParamsSchema {
+    fields: &[
+        optional("epoch_id", ParamType::CryptoHash),
+        optional("block_id", ParamType::BlockId),
+    ],
+    one_of: Some(OneOf {
+        fields: &["epoch_id", "block_id"],
+        required: false,
+    }),
+    positional: true,
+};
+static VALIDATORS_ORDERED: ParamsSchema = schema!(true, [optional("block_id", ParamType::BlockId)]);
+
+static QUERY_ACCOUNT: ParamsSchema = with_block_reference!(
+    true,
+    true,
+    [
+        required("request_type", ParamType::String),
+        required("account_id", ParamType::AccountId),
+    ]
+);
+static QUERY_VIEW_STATE: ParamsSchema = with_block_reference!(
+    true,
+    true,
+    [
+        required("request_type", ParamType::String),
+        required("account_id", ParamType::AccountId),
+        required("prefix_base64", ParamType::Base64),
+        optional("include_proof", ParamType::Boolean),
+    ]
+);
+static QUERY_VIEW_ACCESS_KEY: ParamsSchema = with_block_reference!(
+    true,
+    true,
+    [
+        required("request_type", ParamType::String),
+        required("account_id", ParamType::AccountId),
+        required("public_key", ParamType::PublicKey),
+    ]
+);
+static QUERY_CALL_FUNCTION: ParamsSchema = with_block_reference!(
+    true,
+    true,
+    [
+        required("request_type", ParamType::String),
+        required("account_id", ParamType::AccountId),
+        required("method_name", ParamType::String),
+        required("args_base64", ParamType::Base64),
+    ]
+);
+const QUERY_REQUEST_TYPES: ParamType = ParamType::Enum(&[
+    "view_account",
+    "view_code",
+    "view_state",
+    "view_access_key",
+    "view_access_key_list",
+    "call_function",
+]);
+
+static CHANGES_BY_ACCOUNTS: ParamsSchema = with_block_reference!(
+    true,
+    true,
+    [
+        required("changes_type", ParamType::String),
+        required("account_ids", ParamType::Array),
+    ]
+);
+static CHANGES_SINGLE_ACCESS_KEY: ParamsSchema = with_block_reference!(
+    true,
+    true,
+    [
+        required("changes_type", ParamType::String),
+        required("keys", ParamType::Array),
+    ]
+);
+static CHANGES_DATA: ParamsSchema = with_block_reference!(
+    true,
+    true,
+    [
+        required("changes_type", ParamType::String),
+        required("account_ids", ParamType::Array),
+        required("key_prefix_base64", ParamType::Base64),
+    ]
+);
+const CHANGES_TYPES: ParamType = ParamType::Enum(&[
+    "account_changes",
+    "single_access_key_changes",
+    "all_access_key_changes",
+    "contract_code_changes",
+    "data_changes",
+]);
+
+static VIEW_STATE_PAGINATED: ParamsSchema = schema!(
+    false,
+    [
+        required("account_id", ParamType::AccountId),
+        required("block_id", ParamType::BlockId),
+        optional("limit", ParamType::Integer),
+        optional("next_page_token", ParamType::PageToken),
+    ]
+);
+static VIEW_STATE_KEYS: ParamsSchema = with_block_reference!(
+    true,
+    false,
+    [
+        required("account_id", ParamType::AccountId),
+        required("keys", ParamType::Array),
+    ]
+);
+static VALIDATORS_CHANGES: ParamsSchema = schema!(
+    false,
+    [
+        optional("limit", ParamType::Integer),
+        optional("next_page_token", ParamType::PageToken),
+    ]
+);
+static PRODUCED_BLOCKS: ParamsSchema = schema!(
+    false,
+    [
+        required("validator_id", ParamType::AccountId),
+        optional("epoch_reference", ParamType::Object),
+    ]
+);
+static ACCOUNT_BY_BLOCK_REFERENCE: ParamsSchema =
+    with_block_reference!(true, false, [required("account_id", ParamType::AccountId)]);
+static STATE_DIFF: ParamsSchema = schema!(
+    false,
+    [
+        required("account_id", ParamType::AccountId),
+        required("from_block", ParamType::BlockId),
+        required("to_block", ParamType::BlockId),
+        optional("prefix_base64", ParamType::StateKey),
+        optional("limit", ParamType::Integer),
+        optional("next_page_token", ParamType::PageToken),
+    ]
+);
+static TOP_STATE_GROWERS: ParamsSchema = schema!(
+    false,
+    [
+        optional("limit", ParamType::Integer),
+        optional("window", ParamType::Integer),
+    ]
+);
+static ACCOUNT_OVERVIEW: ParamsSchema = with_block_reference!(
+    true,
+    false,
+    [
+        required("account_id", ParamType::AccountId),
+        optional("transactions_limit", ParamType::Integer),
+    ]
+);
+static TXS_BY_PUBLIC_KEY: ParamsSchema = schema!(
+    false,
+    [
+        required("public_key", ParamType::PublicKey),
+        optional("limit", ParamType::Integer),
+        optional("cursor", ParamType::PageToken),
+    ]
+);
+static SEARCH_FUNCTION_CALLS: ParamsSchema = schema!(
+    false,
+    [
+        required("contract_id", ParamType::AccountId),
+        optional("method_name", ParamType::String),
+        optional("args_substring", ParamType::String),
+        optional("limit", ParamType::Integer),
+        optional("cursor", ParamType::PageToken),
+    ]
+);
+static ACTIONS_BY_CONTRACT_METHOD: ParamsSchema = schema!(
+    false,
+    [
+        required("contract_id", ParamType::AccountId),
+        required("method_name", ParamType::String),
+        optional("from_block_height", ParamType::Integer),
+        optional("to_block_height", ParamType::Integer),
+        optional("limit", ParamType::Integer),
+        optional("cursor", ParamType::PageToken),
+    ]
+);
+static CONGESTION_LEVEL: ParamsSchema =
+    with_block_reference!(true, false, [required("shard_id", ParamType::Integer)]);
+static TX_POOL: ParamsSchema = schema!(false, [optional("account_id", ParamType::AccountId)]);
+static TX_INCLUSION_PROOF: ParamsSchema = schema!(
+    false,
+    [
+        required("tx_hash", ParamType::CryptoHash),
+        required("block_hash", ParamType::CryptoHash),
+    ]
+);
+static NO_PARAMS: ParamsSchema = schema!(true, []);
+
+/// Returns the schema of the method params. Some methods accept several shapes of the params,
+/// the shape is selected by the field present in the params
+fn method_schema(
+    method_name: &str,
+    params: &serde_json::Map<String, serde_json::Value>,
+) -> Result<Option<&'static ParamsSchema>, String> {
+    Ok(Some(match method_name {
+        "block" | "EXPERIMENTAL_changes_in_block" | "EXPERIMENTAL_protocol_config" => &BLOCK,
+        "chunk" => {
+            if params.contains_key("chunk_id") {
+                &CHUNK_BY_ID
+            } else {
+                &CHUNK_BY_BLOCK_SHARD
+            }
+        }
+        "gas_price" => &GAS_PRICE,
+        "tx" | "EXPERIMENTAL_tx_status" => {
+            if params.contains_key("signed_tx_base64") {
+                &TX_SIGNED
+            } else {
+                &TX_BY_HASH
+            }
+        }
+        "send_tx" | "broadcast_tx_async" | "broadcast_tx_commit" => &TX_SIGNED,
+        "EXPERIMENTAL_receipt" | "view_receipt_record" => &RECEIPT,
+        "next_light_client_block" => &NEXT_LIGHT_CLIENT_BLOCK,
+        "light_client_proof" | "EXPERIMENTAL_light_client_proof" => {
+            match params.get("type").and_then(serde_json::Value::as_str) {
+                Some("receipt") => &LIGHT_CLIENT_RECEIPT_PROOF,
+                _ => &LIGHT_CLIENT_TRANSACTION_PROOF,
+            }
+        }
+        "validators" => &VALIDATORS,
+        "EXPERIMENTAL_validators_ordered" => &VALIDATORS_ORDERED,
+        "query" => {
+            let request_type =
+                required_discriminator(method_name, params, "request_type", QUERY_REQUEST_TYPES)?;
+            match request_type {
+                "view_state" => &QUERY_VIEW_STATE,
+                "view_access_key" => &QUERY_VIEW_ACCESS_KEY,
+                "call_function" => &QUERY_CALL_FUNCTION,
+                _ => &QUERY_ACCOUNT,
+            }
+        }
+        "EXPERIMENTAL_changes" => {
+            let changes_type =
+                required_discriminator(method_name, params, "changes_type", CHANGES_TYPES)?;
+            match changes_type {
+                "single_access_key_changes" => &CHANGES_SINGLE_ACCESS_KEY,
+                "data_changes" => &CHANGES_DATA,
+                _ => &CHANGES_BY_ACCOUNTS,
+            }
+        }
+        "view_state_paginated" => &VIEW_STATE_PAGINATED,
+        "EXPERIMENTAL_view_state_keys" => &VIEW_STATE_KEYS,
+        "EXPERIMENTAL_validators_changes" => &VALIDATORS_CHANGES,
+        "EXPERIMENTAL_produced_blocks" => &PRODUCED_BLOCKS,
+        "EXPERIMENTAL_storage_breakdown" => &ACCOUNT_BY_BLOCK_REFERENCE,
+        "EXPERIMENTAL_state_diff" => &STATE_DIFF,
+        "EXPERIMENTAL_top_state_growers" => &TOP_STATE_GROWERS,
+        "EXPERIMENTAL_account_overview" => &ACCOUNT_OVERVIEW,
+        "EXPERIMENTAL_txs_by_public_key" => &TXS_BY_PUBLIC_KEY,
+        "EXPERIMENTAL_search_function_calls" => &SEARCH_FUNCTION_CALLS,
+        "EXPERIMENTAL_actions_by_contract_method" => &ACTIONS_BY_CONTRACT_METHOD,
+        "EXPERIMENTAL_congestion_level" => &CONGESTION_LEVEL,
+        "EXPERIMENTAL_tx_pool" => &TX_POOL,
+        "EXPERIMENTAL_tx_inclusion_proof" => &TX_INCLUSION_PROOF,
+        "health"
+        | "status"
+        | "network_info"
+        | "client_config"
+        | "EXPERIMENTAL_genesis_config"
+        | "EXPERIMENTAL_maintenance_windows"
+        | "EXPERIMENTAL_split_storage_info" => &NO_PARAMS,
+        _ => return Ok(None),
+    }))
+}
+
+/// Returns the value of the field selecting the shape of the params
+fn required_discriminator<'a>(
+    method_name: &str,
+    params: &'a serde_json::Map<String, serde_json::Value>,
+    field_name: &str,
+    param_type: ParamType,
+) -> Result<&'a str, String> {
+    let Some(value) = params.get(field_name) else {
+        return Err(format!(
+            "Missing required field `{}` of `{}` params, expected {}",
+            field_name,
+            method_name,
+            param_type.expected()
+        ));
+    };
+    if !param_type.matches(value) {
+        return Err(invalid_type_message(
+            method_name,
+            field_name,
+            param_type,
+            value,
+        ));
+    }
+    Ok(value.as_str().unwrap_or_default())
+}
+
+fn json_type_name(value: &serde_json::Value) -> &'static str {
+    match value {
+        serde_json::Value::Null => "null",
+        serde_json::Value::Bool(_) => "boolean",
+        serde_json::Value::Number(_) => "number",
+        serde_json::Value::String(_) => "string",
+        serde_json::Value::Array(_) => "array",
+        serde_json::Value::Object(_) => "object",
+    }
+}
+
+fn invalid_type_message(
+    method_name: &str,
+    field_name: &str,
+    param_type: ParamType,
+    value: &serde_json::Value,
+) -> String {
+    let actual = match value {
+        serde_json::Value::String(value) => format!("string {:?}", value),
+        serde_json::Value::Number(value) => format!("number {}", value),
+        value => json_type_name(value).to_string(),
+    };
+    format!(
+        "Invalid field `{}` of `{}` params, expected {}, got {}",
+        field_name,
+        method_name,
+        param_type.expected(),
+        actual
+    )
+}
+
+/// Checks the params of the method against its schema and returns the message
+/// naming the offending field and the expected type. The methods without a schema
+/// (e.g. unknown methods) pass the check
+pub(crate) fn validate(method_name: &str, params: &serde_json::Value) -> Result<(), String> {
+    let no_params = serde_json::Map::new();
+    let params = match params {
+        serde_json::Value::Object(params) => params,
+        // The methods without the params are called with `null`, `[]` or `{}`
+        serde_json::Value::Null => &no_params,
+        // The standard methods accept the positional params and some scalar shortcuts
+        // (e.g. `"latest"` of `validators`), the custom methods accept the objects only
+        value => {
+            return match method_schema(method_name, &no_params) {
+                Ok(Some(schema))
+                    if !schema.positional
+                        && value.as_array().map_or(true, |values| !values.is_empty()) =>
+                {
+                    Err(format!(
+                        "Params of `{}` are expected to be an object, got {}",
+                        method_name,
+                        json_type_name(value)
+                    ))
+                }
+                _ => Ok(()),
+            };
+        }
+    };
+    let Some(schema) = method_schema(method_name, params)? else {
+        return Ok(());
+    };
+
+    for (field_name, value) in params {
+        let Some(field) = schema.fields.iter().find(|field| field.name == field_name) else {
+            let expected_fields: Vec<_> = schema.fields.iter().map(|field| field.name).collect();
+            return Err(if expected_fields.is_empty() {
+                format!(
+                    "Unknown field `{}` of `{}` params, the method has no params",
+                    field_name, method_name
+                )
+            } else {
+                format!(
+                    "Unknown field `{}` of `{}` params, expected fields: {}",
+                    field_name,
+                    method_name,
+                    expected_fields.join(", ")
+                )
+            });
+        };
+        // The optional fields can be explicitly set to null
+        if value.is_null() && !field.required {
+            continue;
+        }
+        if !field.param_type.matches(value) {
+            return Err(invalid_type_message(
+                method_name,
+                field.name,
+                field.param_type,
+                value,
+            ));
+        }
+    }
+
+    if let Some(field) = schema
+        .fields
+        .iter()
+        .find(|field| field.required && !params.contains_key(field.name))
+    {
+        return Err(format!(
+            "Missing required field `{}` of `{}` params, expected {}",
+            field.name,
+            method_name,
+            field.param_type.expected()
+        ));
+    }
+
+    if let Some(one_of) = &schema.one_of {
+        let set_fields: Vec<_> = one_of
+            .fields
+            .iter()
+            .filter(|field_name| {
+                params
+                    .get(**field_name)
+                    .is_some_and(|value| !value.is_null())
+            })
+            .collect();
+        if set_fields.len() > 1 {
+            return Err(format!(
+                "Only one of the fields {} of `{}` params is expected",
+                quoted_fields(one_of.fields),
+                method_name
+            ));
+        }
+        if set_fields.is_empty() && one_of.required {
+            return Err(format!(
+                "One of the fields {} of `{}` params is required",
+                quoted_fields(one_of.fields),
+                method_name
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn quoted_fields(fields: &[&str]) -> String {
+    fields
+        .iter()
+        .map(|field| format!("`{}`", field))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_jsonrpc::primitives::types;
+    use near_jsonrpc::RpcRequest;
+    use serde_json::json;
+
+    const HASH: &str = "11111111111111111111111111111111";
+    const PUBLIC_KEY: &str = "ed25519:11111111111111111111111111111111";
+
+    fn signed_tx_base64() -> String {
+        near_primitives::serialize::to_base64(
+            &borsh::to_vec(&near_primitives::transaction::SignedTransaction::empty(
+                near_primitives::hash::CryptoHash::default(),
+            ))
+            .unwrap(),
+        )
+    }
+
+    /// Example params of every dispatched method, each shape of the params at least once
+    fn examples() -> Vec<(&'static str, serde_json::Value)> {
+        vec![
+            ("block", json!({"finality": "final"})),
+            ("block", json!({"block_id": 118875450})),
+            ("block", json!({"sync_checkpoint": "genesis"})),
+            ("EXPERIMENTAL_changes_in_block", json!({"block_id": HASH})),
+            (
+                "EXPERIMENTAL_protocol_config",
+                json!({"finality": "optimistic"}),
+            ),
+            ("chunk", json!({"chunk_id": HASH})),
+            ("chunk", json!({"block_id": 118875450, "shard_id": 0})),
+            ("gas_price", json!({"block_id": 118875450})),
+            ("gas_price", json!({"block_id": null})),
+            (
+                "tx",
+                json!({"tx_hash": HASH, "sender_account_id": "signer.near"}),
+            ),
+            (
+                "EXPERIMENTAL_tx_status",
+                json!({"tx_hash": HASH, "sender_account_id": "signer.near", "wait_until": "FINAL"}),
+            ),
+            ("tx", json!({"signed_tx_base64": signed_tx_base64()})),
+            (
+                "send_tx",
+                json!({"signed_tx_base64": signed_tx_base64(), "wait_until": "INCLUDED"}),
+            ),
+            (
+                "broadcast_tx_async",
+                json!({"signed_tx_base64": signed_tx_base64()}),
+            ),
+            (
+                "broadcast_tx_commit",
+                json!({"signed_tx_base64": signed_tx_base64()}),
+            ),
+            ("EXPERIMENTAL_receipt", json!({"receipt_id": HASH})),
+            ("view_receipt_record", json!({"receipt_id": HASH})),
+            ("next_light_client_block", json!({"last_block_hash": HASH})),
+            (
+                "light_client_proof",
+                json!({
+                    "type": "transaction",
+                    "transaction_hash": HASH,
+                    "sender_id": "signer.near",
+                    "light_client_head": HASH,
+                }),
+            ),
+            (
+                "EXPERIMENTAL_light_client_proof",
+                json!({
+                    "type": "receipt",
+                    "receipt_id": HASH,
+                    "receiver_id": "contract.near",
+                    "light_client_head": HASH,
+                }),
+            ),
+            ("validators", json!({"epoch_id": HASH})),
+            ("validators", json!({"block_id": 118875450})),
+            ("EXPERIMENTAL_validators_ordered", json!({"block_id": HASH})),
+            (
+                "query",
+                json!({"request_type": "view_account", "finality": "final", "account_id": "contract.near"}),
+            ),
+            (
+                "query",
+                json!({"request_type": "view_code", "block_id": 118875450, "account_id": "contract.near"}),
+            ),
+            (
+                "query",
+                json!({
+                    "request_type": "view_state",
+                    "finality": "final",
+                    "account_id": "contract.near",
+                    "prefix_base64": "c3RhdGU=",
+                    "include_proof": false,
+                }),
+            ),
+            (
+                "query",
+                json!({
+                    "request_type": "view_access_key",
+                    "finality": "final",
+                    "account_id": "contract.near",
+                    "public_key": PUBLIC_KEY,
+                }),
+            ),
+            (
+                "query",
+                json!({
+                    "request_type": "view_access_key_list",
+                    "finality": "final",
+                    "account_id": "contract.near",
+                }),
+            ),
+            (
+                "query",
+                json!({
+                    "request_type": "call_function",
+                    "finality": "final",
+                    "account_id": "contract.near",
+                    "method_name": "get_status",
+                    "args_base64": "e30=",
+                }),
+            ),
+            (
+                "EXPERIMENTAL_changes",
+                json!({"changes_type": "account_changes", "account_ids": ["contract.near"], "block_id": 118875450}),
+            ),
+            (
+                "EXPERIMENTAL_changes",
+                json!({"changes_type": "all_access_key_changes", "account_ids": ["contract.near"], "finality": "final"}),
+            ),
+            (
+                "EXPERIMENTAL_changes",
+                json!({"changes_type": "contract_code_changes", "account_ids": ["contract.near"], "finality": "final"}),
+            ),
+            (
+                "EXPERIMENTAL_changes",
+                json!({
+                    "changes_type": "single_access_key_changes",
+                    "keys": [{"account_id": "contract.near", "public_key": PUBLIC_KEY}],
+                    "finality": "final",
+                }),
+            ),
+            (
+                "EXPERIMENTAL_changes",
+                json!({
+                    "changes_type": "data_changes",
+                    "account_ids": ["contract.near"],
+                    "key_prefix_base64": "c3RhdGU=",
+                    "finality": "final",
+                }),
+            ),
+            (
+                "view_state_paginated",
+                json!({"account_id": "contract.near", "block_id": 118875450, "limit": 100}),
+            ),
+            (
+                "view_state_paginated",
+                json!({"account_id": "contract.near", "block_id": HASH, "next_page_token": "token"}),
+            ),
+            (
+                "EXPERIMENTAL_view_state_keys",
+                json!({"account_id": "contract.near", "keys": ["c3RhdGU="], "finality": "final"}),
+            ),
+            ("EXPERIMENTAL_validators_changes", json!({})),
+            (
+                "EXPERIMENTAL_validators_changes",
+                json!({"limit": 10, "next_page_token": null}),
+            ),
+            (
+                "EXPERIMENTAL_produced_blocks",
+                json!({"validator_id": "validator.near"}),
+            ),
+            (
+                "EXPERIMENTAL_produced_blocks",
+                json!({"validator_id": "validator.near", "epoch_reference": {"epoch_id": HASH}}),
+            ),
+            (
+                "EXPERIMENTAL_storage_breakdown",
+                json!({"account_id": "contract.near", "finality": "final"}),
+            ),
+            (
+                "EXPERIMENTAL_state_diff",
+                json!({"account_id": "contract.near", "from_block": 118875450, "to_block": HASH}),
+            ),
+            (
+                "EXPERIMENTAL_state_diff",
+                json!({
+                    "account_id": "contract.near",
+                    "from_block": 118875450,
+                    "to_block": 118875460,
+                    "prefix_base64": "c3RhdGU=",
+                    "limit": 100,
+                    "next_page_token": "token",
+                }),
+            ),
+            ("EXPERIMENTAL_top_state_growers", json!({})),
+            (
+                "EXPERIMENTAL_top_state_growers",
+                json!({"limit": 10, "window": 7}),
+            ),
+            (
+                "EXPERIMENTAL_account_overview",
+                json!({"account_id": "contract.near", "block_id": 118875450, "transactions_limit": 10}),
+            ),
+            (
+                "EXPERIMENTAL_txs_by_public_key",
+                json!({"public_key": PUBLIC_KEY}),
+            ),
+            (
+                "EXPERIMENTAL_txs_by_public_key",
+                json!({"public_key": PUBLIC_KEY, "limit": 10, "cursor": "cursor"}),
+            ),
+            (
+                "EXPERIMENTAL_search_function_calls",
+                json!({"contract_id": "contract.near", "method_name": "ft_transfer", "args_substring": "alice", "limit": 10}),
+            ),
+            (
+                "EXPERIMENTAL_actions_by_contract_method",
+                json!({
+                    "contract_id": "contract.near",
+                    "method_name": "ft_transfer",
+                    "from_block_height": 118875450,
+                    "to_block_height": 118875460,
+                    "limit": 10,
+                    "cursor": null,
+                }),
+            ),
+            (
+                "EXPERIMENTAL_congestion_level",
+                json!({"shard_id": 0, "finality": "final"}),
+            ),
+            ("EXPERIMENTAL_tx_pool", json!({})),
+            ("EXPERIMENTAL_tx_pool", json!({"account_id": "signer.near"})),
+            (
+                "EXPERIMENTAL_tx_inclusion_proof",
+                json!({"tx_hash": HASH, "block_hash": HASH}),
+            ),
+            ("health", json!(null)),
+            ("status", json!([])),
+            ("network_info", json!({})),
+            ("client_config", json!(null)),
+            ("EXPERIMENTAL_genesis_config", json!(null)),
+            ("EXPERIMENTAL_maintenance_windows", json!(null)),
+            ("EXPERIMENTAL_split_storage_info", json!(null)),
+        ]
+    }
+
+    fn from_value<T: serde::de::DeserializeOwned>(params: serde_json::Value) -> Result<(), String> {
+        serde_json::from_value::<T>(params)
+            .map(|_| ())
+            .map_err(|err| err.to_string())
+    }
+
+    fn parse<T: RpcRequest>(params: serde_json::Value) -> Result<(), String> {
+        T::parse(params)
+            .map(|_| ())
+            .map_err(|err| format!("{:?}", err))
+    }
+
+    /// Parses the params the same way the dispatcher does
+    fn parse_params(method_name: &str, params: serde_json::Value) -> Result<(), String> {
+        match method_name {
+            #[cfg(not(feature = "tx-only"))]
+            "view_state_paginated" => {
+                from_value::<crate::modules::state::RpcViewStatePaginatedRequest>(params)
+            }
+            #[cfg(not(feature = "tx-only"))]
+            "EXPERIMENTAL_view_state_keys" => {
+                from_value::<crate::modules::state::RpcViewStateKeysRequest>(params)
+            }
+            "EXPERIMENTAL_validators_changes" => {
+                from_value::<crate::modules::network::RpcValidatorsChangesRequest>(params)
+            }
+            "EXPERIMENTAL_produced_blocks" => {
+                from_value::<crate::modules::network::RpcProducedBlocksRequest>(params)
+            }
+            #[cfg(not(feature = "tx-only"))]
+            "EXPERIMENTAL_storage_breakdown" => {
+                from_value::<crate::modules::state::RpcStorageBreakdownRequest>(params)
+            }
+            #[cfg(not(feature = "tx-only"))]
+            "EXPERIMENTAL_state_diff" => {
+                from_value::<crate::modules::state::RpcStateDiffRequest>(params)
+            }
+            #[cfg(not(feature = "tx-only"))]
+            "EXPERIMENTAL_top_state_growers" => {
+                from_value::<crate::modules::state::RpcTopStateGrowersRequest>(params)
+            }
+            #[cfg(not(feature = "tx-only"))]
+            "EXPERIMENTAL_account_overview" => {
+                from_value::<crate::modules::state::RpcAccountOverviewRequest>(params)
+            }
+            "EXPERIMENTAL_txs_by_public_key" => from_value::<
+                crate::modules::transactions::RpcTransactionsByPublicKeyRequest,
+            >(params),
+            "EXPERIMENTAL_search_function_calls" => {
+                from_value::<crate::modules::receipts::RpcSearchFunctionCallsRequest>(params)
+            }
+            "EXPERIMENTAL_actions_by_contract_method" => {
+                from_value::<crate::modules::receipts::RpcActionsByContractMethodRequest>(params)
+            }
+            "EXPERIMENTAL_congestion_level" => {
+                from_value::<crate::modules::blocks::RpcCongestionLevelRequest>(params)
+            }
+            "EXPERIMENTAL_tx_pool" => {
+                from_value::<crate::modules::transactions::RpcTxPoolRequest>(params)
+            }
+            "EXPERIMENTAL_tx_inclusion_proof" => {
+                from_value::<crate::modules::transactions::RpcTxInclusionProofRequest>(params)
+            }
+            "view_receipt_record" | "EXPERIMENTAL_receipt" => {
+                parse::<types::receipts::RpcReceiptRequest>(params)
+            }
+            "query" => parse::<types::query::RpcQueryRequest>(params),
+            "block" => parse::<types::blocks::RpcBlockRequest>(params),
+            "chunk" => parse::<types::chunks::RpcChunkRequest>(params),
+            "gas_price" => parse::<types::gas_price::RpcGasPriceRequest>(params),
+            "tx" | "EXPERIMENTAL_tx_status" => {
+                parse::<types::transactions::RpcTransactionStatusRequest>(params)
+            }
+            "send_tx" | "broadcast_tx_async" | "broadcast_tx_commit" => {
+                parse::<types::transactions::RpcSendTransactionRequest>(params)
+            }
+            "next_light_client_block" => {
+                parse::<types::light_client::RpcLightClientNextBlockRequest>(params)
+            }
+            "light_client_proof" | "EXPERIMENTAL_light_client_proof" => {
+                parse::<types::light_client::RpcLightClientExecutionProofRequest>(params)
+            }
+            "validators" => parse::<types::validator::RpcValidatorRequest>(params),
+            "EXPERIMENTAL_validators_ordered" => {
+                parse::<types::validator::RpcValidatorsOrderedRequest>(params)
+            }
+            "EXPERIMENTAL_changes" => {
+                parse::<types::changes::RpcStateChangesInBlockByTypeRequest>(params)
+            }
+            "EXPERIMENTAL_changes_in_block" => {
+                parse::<types::changes::RpcStateChangesInBlockRequest>(params)
+            }
+            "EXPERIMENTAL_protocol_config" => {
+                parse::<types::config::RpcProtocolConfigRequest>(params)
+            }
+            "health"
+            | "status"
+            | "network_info"
+            | "client_config"
+            | "EXPERIMENTAL_genesis_config"
+            | "EXPERIMENTAL_maintenance_windows"
+            | "EXPERIMENTAL_split_storage_info" => parse::<()>(params),
+            _ => Err(format!("Method `{}` is not dispatched", method_name)),
+        }
+    }
+
+    #[test]
+    fn test_examples_cover_dispatched_methods() {
+        let examples = examples();
+        for method_name in crate::rpc_methods::methods() {
+            assert!(
+                examples.iter().any(|(name, _)| *name == method_name),
+                "No example params of `{}`",
+                method_name
+            );
+        }
+    }
+
+    #[test]
+    fn test_example_params_pass_schema_and_parsing() {
+        for (method_name, params) in examples() {
+            // The methods not served by the `tx-only` server are not parsed
+            if cfg!(feature = "tx-only") && !crate::rpc_methods::is_tx_only_method(method_name) {
+                continue;
+            }
+            if let Err(err) = validate(method_name, &params) {
+                panic!("Schema rejects {} params {}: {}", method_name, params, err);
+            }
+            if let Err(err) = parse_params(method_name, params.clone()) {
+                panic!("Failed to parse {} params {}: {}", method_name, params, err);
+            }
+        }
+    }
+
+    #[test]
+    fn test_invalid_params_are_rejected_by_schema_and_parsing() {
+        let invalid = [
+            ("chunk", json!({"chunk_id": "not a hash"})),
+            ("tx", json!({"tx_hash": HASH})),
+            ("EXPERIMENTAL_tx_pool", json!({"account_id": 42})),
+            (
+                "EXPERIMENTAL_congestion_level",
+                json!({"shard_id": "0", "finality": "final"}),
+            ),
+            (
+                "EXPERIMENTAL_txs_by_public_key",
+                json!({"public_key": "not a key"}),
+            ),
+            ("EXPERIMENTAL_tx_inclusion_proof", json!({"tx_hash": HASH})),
+        ];
+        for (method_name, params) in invalid {
+            assert!(
+                validate(method_name, &params).is_err(),
+                "Schema accepts {} params {}",
+                method_name,
+                params
+            );
+            assert!(
+                parse_params(method_name, params.clone()).is_err(),
+                "Parsing accepts {} params {}",
+                method_name,
+                params
+            );
+        }
+    }
+
+    #[test]
+    fn test_positional_params_are_left_to_the_method() {
+        assert!(validate("block", &json!([118875450])).is_ok());
+        assert!(validate("validators", &json!("latest")).is_ok());
+        assert!(validate("EXPERIMENTAL_tx_pool", &json!([])).is_ok());
+        assert!(validate("EXPERIMENTAL_tx_pool", &json!(["signer.near"])).is_err());
+    }
+}
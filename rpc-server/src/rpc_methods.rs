@@ -0,0 +1,574 @@
+//! Methods served by the rpc handler. The table is the only list of the method names,
+//! the dispatcher, the `tx-only` filter and the published network config are built from it.
+use futures::FutureExt;
+
+use crate::{block_prefetch, config, modules, process_method_call, serialize_response};
+
+type MethodResult = Result<serde_json::Value, near_jsonrpc::primitives::errors::RpcError>;
+
+type MethodHandler = fn(MethodCall) -> futures::future::LocalBoxFuture<'static, MethodResult>;
+
+/// Request passed to the handler of the method
+pub(crate) struct MethodCall {
+    pub(crate) req: actix_web::HttpRequest,
+    pub(crate) data: actix_web::web::Data<config::ServerContext>,
+    pub(crate) request: near_jsonrpc::primitives::message::Request,
+}
+
+pub(crate) struct RpcMethod {
+    pub(crate) name: &'static str,
+    /// Custom methods are not known to the NEAR RPC node, so they are never proxied to it
+    pub(crate) custom: bool,
+    /// Served by the server built with the `tx-only` feature.
+    /// Blocks, chunks and the node status are kept to give the transactions their context
+    pub(crate) tx_only: bool,
+    pub(crate) handler: MethodHandler,
+}
+
+static RPC_METHODS: &[RpcMethod] = &[
+    // custom request methods
+    #[cfg(not(feature = "tx-only"))]
+    RpcMethod {
+        name: "view_state_paginated",
+        custom: true,
+        tx_only: false,
+        handler: |call| {
+            process_custom_method_call(call.request, move |params| async move {
+                serialize_response(
+                    modules::state::methods::view_state_paginated(call.data, params).await,
+                )
+            })
+            .boxed_local()
+        },
+    },
+    #[cfg(not(feature = "tx-only"))]
+    RpcMethod {
+        name: "EXPERIMENTAL_view_state_keys",
+        custom: true,
+        tx_only: false,
+        handler: |call| {
+            process_custom_method_call(call.request, move |params| {
+                modules::state::methods::view_state_keys(call.data, params)
+            })
+            .boxed_local()
+        },
+    },
+    RpcMethod {
+        name: "EXPERIMENTAL_validators_changes",
+        custom: true,
+        tx_only: false,
+        handler: |call| {
+            process_custom_method_call(call.request, move |params| {
+                modules::network::methods::validators_changes(call.data, params)
+            })
+            .boxed_local()
+        },
+    },
+    RpcMethod {
+        name: "EXPERIMENTAL_produced_blocks",
+        custom: true,
+        tx_only: false,
+        handler: |call| {
+            process_custom_method_call(call.request, move |params| {
+                modules::network::methods::produced_blocks(call.data, params)
+            })
+            .boxed_local()
+        },
+    },
+    #[cfg(not(feature = "tx-only"))]
+    RpcMethod {
+        name: "EXPERIMENTAL_storage_breakdown",
+        custom: true,
+        tx_only: false,
+        handler: |call| {
+            process_custom_method_call(call.request, move |params| {
+                modules::state::methods::storage_breakdown(call.data, params)
+            })
+            .boxed_local()
+        },
+    },
+    #[cfg(not(feature = "tx-only"))]
+    RpcMethod {
+        name: "EXPERIMENTAL_state_diff",
+        custom: true,
+        tx_only: false,
+        handler: |call| {
+            process_custom_method_call(call.request, move |params| {
+                modules::state::methods::state_diff(call.data, params)
+            })
+            .boxed_local()
+        },
+    },
+    #[cfg(not(feature = "tx-only"))]
+    RpcMethod {
+        name: "EXPERIMENTAL_top_state_growers",
+        custom: true,
+        tx_only: false,
+        handler: |call| {
+            process_custom_method_call(call.request, move |params| {
+                modules::state::methods::top_state_growers(call.data, params)
+            })
+            .boxed_local()
+        },
+    },
+    #[cfg(not(feature = "tx-only"))]
+    RpcMethod {
+        name: "EXPERIMENTAL_account_overview",
+        custom: true,
+        tx_only: false,
+        handler: |call| {
+            process_custom_method_call(call.request, move |params| {
+                modules::state::methods::account_overview(call.data, params)
+            })
+            .boxed_local()
+        },
+    },
+    RpcMethod {
+        name: "EXPERIMENTAL_txs_by_public_key",
+        custom: true,
+        tx_only: true,
+        handler: |call| {
+            process_custom_method_call(call.request, move |params| {
+                modules::transactions::methods::txs_by_public_key(call.data, params)
+            })
+            .boxed_local()
+        },
+    },
+    RpcMethod {
+        name: "EXPERIMENTAL_search_function_calls",
+        custom: true,
+        tx_only: true,
+        handler: |call| {
+            process_custom_method_call(call.request, move |params| {
+                modules::receipts::methods::search_function_calls(call.data, params)
+            })
+            .boxed_local()
+        },
+    },
+    RpcMethod {
+        name: "EXPERIMENTAL_actions_by_contract_method",
+        custom: true,
+        tx_only: true,
+        handler: |call| {
+            process_custom_method_call(call.request, move |params| {
+                modules::receipts::methods::actions_by_contract_method(call.data, params)
+            })
+            .boxed_local()
+        },
+    },
+    RpcMethod {
+        name: "EXPERIMENTAL_congestion_level",
+        custom: true,
+        tx_only: false,
+        handler: |call| {
+            process_custom_method_call(call.request, move |params| {
+                modules::blocks::methods::congestion_level(call.data, params)
+            })
+            .boxed_local()
+        },
+    },
+    RpcMethod {
+        name: "EXPERIMENTAL_tx_pool",
+        custom: true,
+        tx_only: true,
+        handler: |call| {
+            process_custom_method_call(call.request, move |params| {
+                modules::transactions::methods::tx_pool(call.data, params)
+            })
+            .boxed_local()
+        },
+    },
+    RpcMethod {
+        name: "EXPERIMENTAL_tx_inclusion_proof",
+        custom: true,
+        tx_only: true,
+        handler: |call| {
+            process_custom_method_call(call.request, move |params| {
+                modules::transactions::methods::tx_inclusion_proof(call.data, params)
+            })
+            .boxed_local()
+        },
+    },
+    RpcMethod {
+        name: "view_receipt_record",
+        custom: true,
+        tx_only: true,
+        handler: |call| {
+            process_method_call(call.request, move |params| {
+                modules::receipts::methods::view_receipt_record(call.data, params)
+            })
+            .boxed_local()
+        },
+    },
+    // request methods
+    #[cfg(not(feature = "tx-only"))]
+    RpcMethod {
+        name: "query",
+        custom: false,
+        tx_only: false,
+        handler: |call| {
+            process_method_call(call.request, move |params| {
+                modules::queries::methods::query(call.data, params)
+            })
+            .boxed_local()
+        },
+    },
+    // basic requests methods
+    RpcMethod {
+        name: "block",
+        custom: false,
+        tx_only: true,
+        handler: |call| {
+            async move {
+                block_prefetch::observe_block_request(&call.data, &call.req, &call.request.params)
+                    .await;
+                process_method_call(call.request, move |params| {
+                    modules::blocks::methods::block(call.data, params)
+                })
+                .await
+            }
+            .boxed_local()
+        },
+    },
+    RpcMethod {
+        name: "broadcast_tx_async",
+        custom: false,
+        tx_only: true,
+        handler: |call| {
+            process_method_call(call.request, move |params| {
+                modules::transactions::methods::broadcast_tx_async(call.data, params)
+            })
+            .boxed_local()
+        },
+    },
+    RpcMethod {
+        name: "broadcast_tx_commit",
+        custom: false,
+        tx_only: true,
+        handler: |call| {
+            process_method_call(call.request, move |params| {
+                modules::transactions::methods::broadcast_tx_commit(call.data, params)
+            })
+            .boxed_local()
+        },
+    },
+    RpcMethod {
+        name: "chunk",
+        custom: false,
+        tx_only: true,
+        handler: |call| {
+            process_method_call(call.request, move |params| {
+                modules::blocks::methods::chunk(call.data, params)
+            })
+            .boxed_local()
+        },
+    },
+    RpcMethod {
+        name: "gas_price",
+        custom: false,
+        tx_only: false,
+        handler: |call| {
+            process_method_call(call.request, move |params| {
+                modules::gas::methods::gas_price(call.data, params)
+            })
+            .boxed_local()
+        },
+    },
+    RpcMethod {
+        name: "health",
+        custom: false,
+        tx_only: true,
+        handler: |call| {
+            process_method_call(call.request, move |_: ()| {
+                modules::network::methods::health(call.data)
+            })
+            .boxed_local()
+        },
+    },
+    RpcMethod {
+        name: "light_client_proof",
+        custom: false,
+        tx_only: false,
+        handler: |call| {
+            process_method_call(call.request, move |params| {
+                modules::clients::methods::light_client_proof(call.data, params)
+            })
+            .boxed_local()
+        },
+    },
+    RpcMethod {
+        name: "next_light_client_block",
+        custom: false,
+        tx_only: false,
+        handler: |call| {
+            process_method_call(call.request, move |params| {
+                modules::clients::methods::next_light_client_block(call.data, params)
+            })
+            .boxed_local()
+        },
+    },
+    RpcMethod {
+        name: "network_info",
+        custom: false,
+        tx_only: false,
+        handler: |call| {
+            process_method_call(call.request, move |_: ()| {
+                modules::network::methods::network_info(call.data)
+            })
+            .boxed_local()
+        },
+    },
+    RpcMethod {
+        name: "send_tx",
+        custom: false,
+        tx_only: true,
+        handler: |call| {
+            process_method_call(call.request, move |params| {
+                modules::transactions::methods::send_tx(call.data, params)
+            })
+            .boxed_local()
+        },
+    },
+    RpcMethod {
+        name: "status",
+        custom: false,
+        tx_only: true,
+        handler: |call| {
+            process_method_call(call.request, move |_: ()| {
+                modules::network::methods::status(call.data)
+            })
+            .boxed_local()
+        },
+    },
+    RpcMethod {
+        name: "tx",
+        custom: false,
+        tx_only: true,
+        handler: |call| {
+            process_method_call(call.request, move |params| {
+                modules::transactions::methods::tx(call.data, params)
+            })
+            .boxed_local()
+        },
+    },
+    RpcMethod {
+        name: "validators",
+        custom: false,
+        tx_only: false,
+        handler: |call| {
+            process_method_call(call.request, move |params| {
+                modules::network::methods::validators(call.data, params)
+            })
+            .boxed_local()
+        },
+    },
+    RpcMethod {
+        name: "client_config",
+        custom: false,
+        tx_only: false,
+        handler: |call| {
+            process_method_call(call.request, move |_: ()| {
+                modules::network::methods::client_config(call.data)
+            })
+            .boxed_local()
+        },
+    },
+    RpcMethod {
+        name: "EXPERIMENTAL_changes",
+        custom: false,
+        tx_only: false,
+        handler: |call| {
+            process_method_call(call.request, move |params| {
+                modules::blocks::methods::changes_in_block_by_type(call.data, params)
+            })
+            .boxed_local()
+        },
+    },
+    RpcMethod {
+        name: "EXPERIMENTAL_changes_in_block",
+        custom: false,
+        tx_only: false,
+        handler: |call| {
+            process_method_call(call.request, move |params| {
+                modules::blocks::methods::changes_in_block(call.data, params)
+            })
+            .boxed_local()
+        },
+    },
+    RpcMethod {
+        name: "EXPERIMENTAL_genesis_config",
+        custom: false,
+        tx_only: false,
+        handler: |call| {
+            process_method_call(call.request, move |_: ()| {
+                modules::network::methods::genesis_config(call.data)
+            })
+            .boxed_local()
+        },
+    },
+    RpcMethod {
+        name: "EXPERIMENTAL_light_client_proof",
+        custom: false,
+        tx_only: false,
+        handler: |call| {
+            process_method_call(call.request, move |params| {
+                modules::clients::methods::light_client_proof(call.data, params)
+            })
+            .boxed_local()
+        },
+    },
+    RpcMethod {
+        name: "EXPERIMENTAL_protocol_config",
+        custom: false,
+        tx_only: false,
+        handler: |call| {
+            process_method_call(call.request, move |params| {
+                modules::network::methods::protocol_config(call.data, params)
+            })
+            .boxed_local()
+        },
+    },
+    RpcMethod {
+        name: "EXPERIMENTAL_receipt",
+        custom: false,
+        tx_only: true,
+        handler: |call| {
+            process_method_call(call.request, move |params| {
+                modules::receipts::methods::receipt(call.data, params)
+            })
+            .boxed_local()
+        },
+    },
+    RpcMethod {
+        name: "EXPERIMENTAL_tx_status",
+        custom: false,
+        tx_only: true,
+        handler: |call| {
+            process_method_call(call.request, move |params| {
+                modules::transactions::methods::tx_status(call.data, params)
+            })
+            .boxed_local()
+        },
+    },
+    RpcMethod {
+        name: "EXPERIMENTAL_validators_ordered",
+        custom: false,
+        tx_only: false,
+        handler: |call| {
+            process_method_call(call.request, move |params| {
+                modules::network::methods::validators_ordered(call.data, params)
+            })
+            .boxed_local()
+        },
+    },
+    RpcMethod {
+        name: "EXPERIMENTAL_maintenance_windows",
+        custom: false,
+        tx_only: false,
+        handler: |call| {
+            process_method_call(call.request, move |_: ()| {
+                modules::network::methods::maintenance_windows(call.data)
+            })
+            .boxed_local()
+        },
+    },
+    RpcMethod {
+        name: "EXPERIMENTAL_split_storage_info",
+        custom: false,
+        tx_only: false,
+        handler: |call| {
+            process_method_call(call.request, move |_: ()| {
+                modules::network::methods::split_storage_info(call.data)
+            })
+            .boxed_local()
+        },
+    },
+];
+
+/// Returns the method served by the rpc handler
+pub(crate) fn rpc_method(method_name: &str) -> Option<&'static RpcMethod> {
+    RPC_METHODS.iter().find(|method| method.name == method_name)
+}
+
+/// Methods dispatched by the rpc handler, the custom ones included.
+/// The list is published in the network config
+pub(crate) fn methods() -> impl Iterator<Item = &'static str> {
+    RPC_METHODS.iter().map(|method| method.name)
+}
+
+/// Whether the method is served by the server built with the `tx-only` feature
+pub(crate) fn is_tx_only_method(method_name: &str) -> bool {
+    rpc_method(method_name).is_some_and(|method| method.tx_only)
+}
+
+/// Processes a call of the custom method. The params are deserialized
+/// into the request of the method as is, without the NEAR RPC request parsing
+async fn process_custom_method_call<R, V, F>(
+    request: near_jsonrpc::primitives::message::Request,
+    callback: impl FnOnce(R) -> F,
+) -> MethodResult
+where
+    R: serde::de::DeserializeOwned,
+    V: serde::ser::Serialize,
+    F: std::future::Future<Output = Result<V, near_jsonrpc::primitives::errors::RpcError>>,
+{
+    let Ok(request_data) = serde_json::from_value(request.params) else {
+        return Err(near_jsonrpc::primitives::errors::RpcError::parse_error(
+            "Failed to parse request data".to_string(),
+        ));
+    };
+    callback(request_data).await.and_then(serialize_response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_method_names_are_unique() {
+        let names: std::collections::HashSet<&str> = methods().collect();
+        assert_eq!(names.len(), RPC_METHODS.len());
+    }
+
+    #[test]
+    fn test_custom_methods_are_not_near_rpc_methods() {
+        assert!(rpc_method("view_receipt_record").is_some_and(|method| method.custom));
+        assert!(rpc_method("EXPERIMENTAL_tx_pool").is_some_and(|method| method.custom));
+        assert!(rpc_method("tx").is_some_and(|method| !method.custom));
+        assert!(rpc_method("EXPERIMENTAL_changes").is_some_and(|method| !method.custom));
+        assert!(rpc_method("unknown_method").is_none());
+    }
+
+    #[test]
+    fn test_tx_only_methods() {
+        let tx_only_methods: Vec<&str> = RPC_METHODS
+            .iter()
+            .filter(|method| method.tx_only)
+            .map(|method| method.name)
+            .collect();
+        assert_eq!(
+            tx_only_methods,
+            [
+                "EXPERIMENTAL_txs_by_public_key",
+                "EXPERIMENTAL_search_function_calls",
+                "EXPERIMENTAL_actions_by_contract_method",
+                "EXPERIMENTAL_tx_pool",
+                "EXPERIMENTAL_tx_inclusion_proof",
+                "view_receipt_record",
+                "block",
+                "broadcast_tx_async",
+                "broadcast_tx_commit",
+                "chunk",
+                "health",
+                "send_tx",
+                "status",
+                "tx",
+                "EXPERIMENTAL_receipt",
+                "EXPERIMENTAL_tx_status",
+            ]
+        );
+        assert!(is_tx_only_method("tx"));
+        assert!(!is_tx_only_method("EXPERIMENTAL_changes"));
+        assert!(!is_tx_only_method("unknown_method"));
+    }
+}
@@ -5,23 +5,42 @@ use futures::StreamExt;
 
 #[cfg(feature = "shadow-data-consistency")]
 const DEFAULT_RETRY_COUNT: u8 = 3;
+/// Max number of the shadow comparisons running in the background at once.
+/// The comparisons of the requests sampled above the limit are dropped,
+/// so a slow archival NEAR RPC doesn't pile up the tasks and the responses in memory
+#[cfg(feature = "shadow-data-consistency")]
+const MAX_SHADOW_COMPARISONS_IN_FLIGHT: usize = 100;
+
+#[cfg(feature = "shadow-data-consistency")]
+lazy_static! {
+    static ref SHADOW_COMPARISON_PERMITS: std::sync::Arc<tokio::sync::Semaphore> =
+        std::sync::Arc::new(tokio::sync::Semaphore::new(
+            MAX_SHADOW_COMPARISONS_IN_FLIGHT
+        ));
+}
 
 /// JsonRpcClient represents a client capable of interacting with NEAR JSON-RPC endpoints,
 /// The client is capable of handling requests to both regular and archival nodes.
 #[derive(Clone, Debug)]
 pub struct JsonRpcClient {
-    regular_client: near_jsonrpc_client::JsonRpcClient,
-    archival_client: near_jsonrpc_client::JsonRpcClient,
+    regular_client: http_client::RpcClient,
+    archival_client: http_client::RpcClient,
 }
 
 impl JsonRpcClient {
     /// Creates a new JsonRpcClient.
     /// The client is capable of handling requests to both regular and archival nodes.
     /// If the `archival_rpc_url` is not provided, the client will use the regular endpoint for both
-    pub fn new(rpc_url: String, archival_rpc_url: Option<String>) -> Self {
-        let regular_client = near_jsonrpc_client::JsonRpcClient::connect(rpc_url);
+    pub fn new(
+        http_client_factory: &http_client::HttpClientFactory,
+        rpc_url: &str,
+        archival_rpc_url: Option<&str>,
+    ) -> Self {
+        let regular_client = http_client_factory.rpc_client("near_rpc", rpc_url);
         let archival_client = match archival_rpc_url {
-            Some(archival_rpc_url) => near_jsonrpc_client::JsonRpcClient::connect(archival_rpc_url),
+            Some(archival_rpc_url) => {
+                http_client_factory.rpc_client("archival_rpc", archival_rpc_url)
+            }
             None => regular_client.clone(),
         };
         Self {
@@ -31,12 +50,16 @@ impl JsonRpcClient {
     }
 
     /// Adds a custom header to the RPC request.
-    pub fn header(mut self, header_name: String, header_value: String) -> anyhow::Result<Self> {
-        let header_name: &'static str = Box::leak(header_name.into_boxed_str());
-        let header_value: &'static str = Box::leak(header_value.into_boxed_str());
+    pub fn header(mut self, header_name: &'static str, header_value: &str) -> anyhow::Result<Self> {
+        self.regular_client = self.regular_client.header(header_name, header_value)?;
+        self.archival_client = self.archival_client.header(header_name, header_value)?;
+        Ok(self)
+    }
 
-        self.regular_client = self.regular_client.header((header_name, header_value))?;
-        self.archival_client = self.archival_client.header((header_name, header_value))?;
+    /// Sends the API key of the RPC provider to both endpoints
+    pub fn api_key(mut self, api_key: Option<&str>) -> anyhow::Result<Self> {
+        self.regular_client = self.regular_client.api_key(api_key)?;
+        self.archival_client = self.archival_client.api_key(api_key)?;
         Ok(self)
     }
 
@@ -147,7 +170,7 @@ pub async fn get_current_validators(
 }
 
 async fn handle_streamer_message(
-    streamer_message: near_indexer_primitives::StreamerMessage,
+    streamer_message: readnode_primitives::indexer::StreamerMessage,
     blocks_cache: std::sync::Arc<crate::cache::RwLockLruMemoryCache<u64, CacheBlock>>,
     blocks_info_by_finality: std::sync::Arc<BlocksInfoByFinality>,
     near_rpc_client: &JsonRpcClient,
@@ -168,6 +191,7 @@ async fn handle_streamer_message(
         }
 
         blocks_info_by_finality.update_final_block(block).await;
+        crate::metrics::observe_block_latency("observed", block_cache.block_timestamp);
         blocks_cache
             .put(block_cache.block_height, block_cache)
             .await;
@@ -178,6 +202,54 @@ async fn handle_streamer_message(
     Ok(())
 }
 
+/// Updates the final block from the head notification of the indexer.
+/// The indexers process only the final blocks, so the block of the head is final
+/// and is read from the lake before the lake streamer or Redis delivers it
+pub(crate) async fn update_final_block_from_head(
+    data: &crate::config::ServerContext,
+    block_height: near_primitives::types::BlockHeight,
+) -> anyhow::Result<()> {
+    if block_height as i64
+        <= crate::metrics::LATEST_BLOCK_HEIGHT_BY_FINALITIY
+            .with_label_values(&["final"])
+            .get()
+    {
+        return Ok(());
+    }
+    let block = near_lake_framework::s3_fetchers::fetch_block(
+        &data.s3_client,
+        &data.s3_bucket_name,
+        block_height,
+    )
+    .await
+    .map_err(|err| anyhow::anyhow!("Failed to fetch block {}: {:?}", block_height, err))?;
+    let shards = futures::future::try_join_all(block.chunks.iter().map(|chunk| async move {
+        near_lake_framework::s3_fetchers::fetch_shard(
+            &data.s3_client,
+            &data.s3_bucket_name,
+            block_height,
+            chunk.shard_id,
+        )
+        .await
+        .map_err(|err| {
+            anyhow::anyhow!(
+                "Failed to fetch shard {} of block {}: {:?}",
+                chunk.shard_id,
+                block_height,
+                err
+            )
+        })
+    }))
+    .await?;
+    handle_streamer_message(
+        readnode_primitives::indexer::StreamerMessage { block, shards },
+        std::sync::Arc::clone(&data.blocks_cache),
+        std::sync::Arc::clone(&data.blocks_info_by_finality),
+        &data.near_rpc_client,
+    )
+    .await
+}
+
 pub async fn update_final_block_regularly_from_lake(
     blocks_cache: std::sync::Arc<crate::cache::RwLockLruMemoryCache<u64, CacheBlock>>,
     blocks_info_by_finality: std::sync::Arc<BlocksInfoByFinality>,
@@ -185,16 +257,15 @@ pub async fn update_final_block_regularly_from_lake(
     near_rpc_client: JsonRpcClient,
 ) -> anyhow::Result<()> {
     tracing::info!("Task to get final block from lake and store in the cache started");
-    let lake_config = rpc_server_config
-        .lake_config
-        .lake_config(
-            blocks_info_by_finality
-                .optimistic_cache_block()
-                .await
-                .block_height,
-        )
-        .await?;
-    let (sender, stream) = near_lake_framework::streamer(lake_config);
+    let (sender, stream) = lake_streamer::streamer(
+        &rpc_server_config.lake_config,
+        blocks_info_by_finality
+            .optimistic_cache_block()
+            .await
+            .block_height,
+        None,
+    )
+    .await?;
     let mut handlers = tokio_stream::wrappers::ReceiverStream::new(stream)
         .map(|streamer_message| {
             handle_streamer_message(
@@ -369,6 +440,10 @@ pub fn friendly_memory_size_format(memory_size_bytes: usize) -> String {
     }
 }
 
+/// Compares the sampled response of the read-rpc with the response of the archival NEAR RPC
+/// to the same request. The comparison runs in the background, so the response is not delayed.
+/// The mismatches are logged with the differing JSON paths as the structured fields
+/// and counted in the `requests_methods_errors` and `shadow_mismatched_paths` metrics
 #[cfg(feature = "shadow-data-consistency")]
 pub async fn shadow_compare_results_handler<T, E, M>(
     shadow_rate: f64,
@@ -377,22 +452,38 @@ pub async fn shadow_compare_results_handler<T, E, M>(
     params: M,
     method_name: &str,
 ) where
-    M: near_jsonrpc_client::methods::RpcMethod + std::fmt::Debug,
-    <M as near_jsonrpc_client::methods::RpcMethod>::Response: serde::ser::Serialize,
-    <M as near_jsonrpc_client::methods::RpcMethod>::Error: std::fmt::Debug + serde::ser::Serialize,
+    M: near_jsonrpc_client::methods::RpcMethod + std::fmt::Debug + Send + Sync + 'static,
+    <M as near_jsonrpc_client::methods::RpcMethod>::Response: serde::ser::Serialize + Send,
+    <M as near_jsonrpc_client::methods::RpcMethod>::Error:
+        std::fmt::Debug + serde::ser::Serialize + Send,
     T: serde::ser::Serialize,
     E: std::fmt::Debug + serde::ser::Serialize,
 {
     let method_total_requests = crate::metrics::METHOD_CALLS_COUNTER
         .with_label_values(&[method_name])
         .get();
-    let err_code = if is_should_shadow_compare_results(method_total_requests, shadow_rate).await {
+    if !is_should_shadow_compare_results(method_total_requests, shadow_rate).await {
+        return;
+    }
+    let Ok(permit) = SHADOW_COMPARISON_PERMITS.clone().try_acquire_owned() else {
+        crate::metrics::SHADOW_COMPARISONS_DROPPED
+            .with_label_values(&[method_name])
+            .inc();
+        return;
+    };
+    // The response is serialized before it is returned to the client
+    let (read_rpc_response_json, is_response_ok) = match read_rpc_result {
+        Ok(res) => (serde_json::to_value(res), true),
+        Err(err) => (serde_json::to_value(err), false),
+    };
+    let method_name = method_name.to_string();
+    tokio::spawn(async move {
+        let _permit = permit;
         let meta_data = format!("{:?}", params);
-        let (read_rpc_response_json, is_response_ok) = match read_rpc_result {
-            Ok(res) => (serde_json::to_value(res), true),
-            Err(err) => (serde_json::to_value(err), false),
-        };
         let read_rpc_response_meta_data = format!("{:?}", &read_rpc_response_json);
+        crate::metrics::SHADOW_COMPARISONS
+            .with_label_values(&[&method_name])
+            .inc();
         let comparison_result = shadow_compare_results(
             read_rpc_response_json,
             near_rpc_client,
@@ -401,48 +492,61 @@ pub async fn shadow_compare_results_handler<T, E, M>(
         )
         .await;
 
-        match comparison_result {
+        let err_code = match comparison_result {
             Ok(_) => {
-                tracing::info!(target: "shadow_data_consistency", "Shadow data check: CORRECT\n{}", meta_data);
+                tracing::info!(
+                    target: "shadow_data_consistency",
+                    method_name = %method_name,
+                    params = %meta_data,
+                    "Shadow data check: CORRECT"
+                );
                 None
             }
+            Err(ShadowDataConsistencyError::ResultsDontMatch {
+                reason,
+                read_rpc_response,
+                near_rpc_response,
+                diff,
+                ..
+            }) => {
+                let mismatched_paths =
+                    count_mismatched_paths(&read_rpc_response, &near_rpc_response);
+                crate::metrics::SHADOW_MISMATCHED_PATHS
+                    .with_label_values(&[&method_name])
+                    .observe(mismatched_paths as f64);
+                tracing::warn!(
+                    target: "shadow_data_consistency",
+                    method_name = %method_name,
+                    code = %reason.code(),
+                    reason = reason.reason(),
+                    params = %meta_data,
+                    mismatched_paths,
+                    diff = %diff,
+                    read_rpc_response = %read_rpc_response,
+                    near_rpc_response = %near_rpc_response,
+                    "Shadow data check: ERROR"
+                );
+                Some(reason.code())
+            }
             Err(err) => {
-                if let ShadowDataConsistencyError::ResultsDontMatch {
-                    reason,
-                    read_rpc_response,
-                    near_rpc_response,
-                    ..
-                } = &err
-                {
-                    tracing::warn!(
-                        target: "shadow_data_consistency",
-                        "Shadow data check: ERROR\n{}:{}: {}\n{}",
-                        method_name,
-                        reason.code(),
-                        meta_data,
-                        format!("{}, ReadRPC: {:?}, NearRPC: {:?}", reason.reason(), read_rpc_response, near_rpc_response),
-                    );
-                    Some(reason.code())
-                } else {
-                    tracing::warn!(
-                        target: "shadow_data_consistency",
-                        "Shadow data check: ERROR\n{}:4: {}\n{:?}",
-                        method_name,
-                        meta_data,
-                        format!("NearRPC: {}, ReadRPC: {:?}", err, read_rpc_response_meta_data),
-                    );
-                    Some("4".to_string())
-                }
+                tracing::warn!(
+                    target: "shadow_data_consistency",
+                    method_name = %method_name,
+                    code = "4",
+                    params = %meta_data,
+                    error = %err,
+                    read_rpc_response = %read_rpc_response_meta_data,
+                    "Shadow data check: ERROR"
+                );
+                Some("4".to_string())
             }
-        }
-    } else {
-        None
-    };
-    if let Some(err_code) = &err_code {
-        crate::metrics::REQUESTS_ERRORS
-            .with_label_values(&[method_name, err_code])
-            .inc();
-    };
+        };
+        if let Some(err_code) = &err_code {
+            crate::metrics::REQUESTS_ERRORS
+                .with_label_values(&[&method_name, err_code])
+                .inc();
+        };
+    });
 }
 
 #[cfg(feature = "shadow-data-consistency")]
@@ -546,10 +650,13 @@ where
     let config = Config::new(CompareMode::Strict).numeric_mode(NumericMode::AssumeFloat);
 
     // Sorts the values of the JSON objects before comparing them.
-    let read_rpc_json = json_sort_value(read_rpc_response_json);
-    let near_rpc_json = json_sort_value(near_rpc_response_json);
+    // The canonical keys order keeps the logged mismatches comparable line by line
+    let read_rpc_json =
+        readnode_primitives::canonical_json::canonicalize(json_sort_value(read_rpc_response_json));
+    let near_rpc_json =
+        readnode_primitives::canonical_json::canonicalize(json_sort_value(near_rpc_response_json));
 
-    if let Err(_err) = assert_json_matches_no_panic(&read_rpc_json, &near_rpc_json, config) {
+    if let Err(diff) = assert_json_matches_no_panic(&read_rpc_json, &near_rpc_json, config) {
         // separate mismatching successful and failure responses into different targets
         // to make it easier to find reasons of the mismatching
         let results_dont_match_error = if read_rpc_response_is_ok && near_rpc_response_is_ok {
@@ -560,6 +667,7 @@ where
                 reason: DataMismatchReason::SuccessNearRpcSuccess,
                 read_rpc_response: read_rpc_json,
                 near_rpc_response: near_rpc_json,
+                diff,
             }
         } else if !read_rpc_response_is_ok && near_rpc_response_is_ok {
             // read_rpc service has error response and near_rpc has successful response
@@ -568,6 +676,7 @@ where
                 reason: DataMismatchReason::ErrorNearRpcSuccess,
                 read_rpc_response: read_rpc_json,
                 near_rpc_response: near_rpc_json,
+                diff,
             }
         } else if read_rpc_response_is_ok && !near_rpc_response_is_ok {
             // read_rpc service has successful response and near_rpc has error response
@@ -577,6 +686,7 @@ where
                 reason: DataMismatchReason::SuccessNearRpcError,
                 read_rpc_response: read_rpc_json,
                 near_rpc_response: near_rpc_json,
+                diff,
             }
         } else {
             // Both services(read_rpc and near_rpc) have an error response
@@ -587,6 +697,7 @@ where
                 reason: DataMismatchReason::ErrorNearRpcError,
                 read_rpc_response: read_rpc_json,
                 near_rpc_response: near_rpc_json,
+                diff,
             }
         };
         return Err(results_dont_match_error);
@@ -610,6 +721,8 @@ pub enum ShadowDataConsistencyError {
         reason: DataMismatchReason,
         read_rpc_response: serde_json::Value,
         near_rpc_response: serde_json::Value,
+        /// Differences of the responses by the JSON path
+        diff: String,
     },
 }
 
@@ -654,6 +767,35 @@ impl DataMismatchReason {
     }
 }
 
+/// Counts the JSON paths the values differ at, the same way they are compared:
+/// strictly with the numbers compared as floats. The missing object keys and array items
+/// are counted as the differing paths
+#[cfg(feature = "shadow-data-consistency")]
+fn count_mismatched_paths(left: &serde_json::Value, right: &serde_json::Value) -> usize {
+    match (left, right) {
+        (serde_json::Value::Object(left), serde_json::Value::Object(right)) => {
+            left.iter()
+                .map(|(key, left_value)| match right.get(key) {
+                    Some(right_value) => count_mismatched_paths(left_value, right_value),
+                    None => 1,
+                })
+                .sum::<usize>()
+                + right.keys().filter(|key| !left.contains_key(*key)).count()
+        }
+        (serde_json::Value::Array(left), serde_json::Value::Array(right)) => {
+            left.iter()
+                .zip(right.iter())
+                .map(|(left_value, right_value)| count_mismatched_paths(left_value, right_value))
+                .sum::<usize>()
+                + left.len().abs_diff(right.len())
+        }
+        (serde_json::Value::Number(left), serde_json::Value::Number(right)) => {
+            usize::from(left.as_f64() != right.as_f64())
+        }
+        (left, right) => usize::from(left != right),
+    }
+}
+
 /// Sort json value
 ///
 /// 1. sort object key
@@ -720,3 +862,51 @@ fn generate_array_key(value: &serde_json::Value) -> String {
         }),
     }
 }
+
+#[cfg(all(test, feature = "shadow-data-consistency"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_equal_values_have_no_mismatched_paths() {
+        let value = serde_json::json!({"a": [1, {"b": "c"}], "d": null});
+        assert_eq!(count_mismatched_paths(&value, &value), 0);
+        assert_eq!(
+            count_mismatched_paths(&serde_json::json!(1), &serde_json::json!(1.0)),
+            0
+        );
+    }
+
+    #[test]
+    fn test_mismatched_paths_are_counted_by_leaf() {
+        assert_eq!(
+            count_mismatched_paths(
+                &serde_json::json!({"a": {"b": 1, "c": "x"}, "d": [1, 2]}),
+                &serde_json::json!({"a": {"b": 2, "c": "y"}, "d": [1, 3]}),
+            ),
+            3
+        );
+    }
+
+    #[test]
+    fn test_missing_keys_and_items_are_mismatched_paths() {
+        assert_eq!(
+            count_mismatched_paths(
+                &serde_json::json!({"a": 1, "b": [1, 2, 3]}),
+                &serde_json::json!({"c": 1, "b": [1]}),
+            ),
+            4
+        );
+    }
+
+    #[test]
+    fn test_different_types_are_one_mismatched_path() {
+        assert_eq!(
+            count_mismatched_paths(
+                &serde_json::json!({"a": {"b": 1, "c": 2}}),
+                &serde_json::json!({"a": "text"}),
+            ),
+            1
+        );
+    }
+}
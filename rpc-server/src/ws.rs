@@ -0,0 +1,455 @@
+use futures::StreamExt;
+
+/// Method name the WebSocket subscriptions are authorized with by the API keys
+pub(crate) const SUBSCRIBE_METHOD: &str = "subscribe";
+/// Number of the final blocks buffered for the WebSocket connections.
+/// The connection lagging behind by more blocks is closed, so the client knows it missed events
+pub(crate) const FINAL_BLOCKS_CHANNEL_CAPACITY: usize = 64;
+
+/// Subscription request of the WebSocket client in the JSON-RPC format
+#[derive(serde::Deserialize, Debug)]
+struct SubscriptionRequest {
+    id: serde_json::Value,
+    #[serde(flatten)]
+    method: SubscriptionMethod,
+}
+
+#[derive(serde::Deserialize, Debug)]
+#[serde(tag = "method", content = "params", rename_all = "snake_case")]
+enum SubscriptionMethod {
+    Subscribe(Topic),
+    Unsubscribe { subscription: u64 },
+}
+
+#[derive(serde::Deserialize, Debug, Clone)]
+#[serde(tag = "topic", rename_all = "snake_case")]
+enum Topic {
+    /// New final blocks
+    NewBlock,
+    /// Final status of the transaction, the subscription ends after the notification
+    TxStatus {
+        tx_hash: near_primitives::hash::CryptoHash,
+    },
+    /// All the changes of the account: the balance, the access keys, the contract code and storage
+    AccountChanges {
+        account_id: near_primitives::types::AccountId,
+    },
+    /// Changes of the contract storage of the account with the keys starting with the prefix.
+    /// All the storage changes of the account are sent if the prefix is not set
+    StateChanges {
+        account_id: near_primitives::types::AccountId,
+        #[serde(rename = "prefix_base64", default)]
+        key_prefix: Option<near_primitives::types::StoreKey>,
+    },
+}
+
+impl Topic {
+    fn name(&self) -> &'static str {
+        match self {
+            Topic::NewBlock => "new_block",
+            Topic::TxStatus { .. } => "tx_status",
+            Topic::AccountChanges { .. } => "account_changes",
+            Topic::StateChanges { .. } => "state_changes",
+        }
+    }
+
+    // Event of the block for the topic or `None` if the block has nothing for the subscriber.
+    // The changes are filtered on the server side, so the clients receive only the changes
+    // they are subscribed to
+    fn block_event(&self, block: &crate::modules::blocks::BlockInfo) -> Option<serde_json::Value> {
+        let changes: near_primitives::views::StateChangesView = match self {
+            Topic::NewBlock => return Some(serde_json::json!(block.block_view)),
+            // The transactions are notified by the tx-indexer, not by the blocks
+            Topic::TxStatus { .. } => return None,
+            Topic::AccountChanges { account_id } => block
+                .changes
+                .iter()
+                .filter(|change| changed_account_id(&change.value) == account_id)
+                .cloned()
+                .collect(),
+            Topic::StateChanges {
+                account_id,
+                key_prefix,
+            } => {
+                let key_prefix: &[u8] = key_prefix.as_deref().unwrap_or_default();
+                block
+                    .changes
+                    .iter()
+                    .filter(|change| match &change.value {
+                        near_primitives::views::StateChangeValueView::DataUpdate {
+                            account_id: changed_account_id,
+                            key,
+                            ..
+                        }
+                        | near_primitives::views::StateChangeValueView::DataDeletion {
+                            account_id: changed_account_id,
+                            key,
+                        } => changed_account_id == account_id && key.starts_with(key_prefix),
+                        _ => false,
+                    })
+                    .cloned()
+                    .collect()
+            }
+        };
+        if changes.is_empty() {
+            return None;
+        }
+        Some(serde_json::json!(StateChangesEvent {
+            block_height: block.block_cache.block_height,
+            block_hash: block.block_cache.block_hash,
+            changes,
+        }))
+    }
+}
+
+fn changed_account_id(
+    change: &near_primitives::views::StateChangeValueView,
+) -> &near_primitives::types::AccountId {
+    match change {
+        near_primitives::views::StateChangeValueView::AccountUpdate { account_id, .. }
+        | near_primitives::views::StateChangeValueView::AccountDeletion { account_id }
+        | near_primitives::views::StateChangeValueView::AccessKeyUpdate { account_id, .. }
+        | near_primitives::views::StateChangeValueView::AccessKeyDeletion { account_id, .. }
+        | near_primitives::views::StateChangeValueView::DataUpdate { account_id, .. }
+        | near_primitives::views::StateChangeValueView::DataDeletion { account_id, .. }
+        | near_primitives::views::StateChangeValueView::ContractCodeUpdate { account_id, .. }
+        | near_primitives::views::StateChangeValueView::ContractCodeDeletion { account_id } => {
+            account_id
+        }
+    }
+}
+
+#[derive(serde::Serialize, Debug)]
+struct SubscriptionNotification {
+    subscription: u64,
+    result: serde_json::Value,
+}
+
+#[derive(serde::Serialize, Debug)]
+struct StateChangesEvent {
+    block_height: near_primitives::types::BlockHeight,
+    block_hash: near_primitives::hash::CryptoHash,
+    changes: near_primitives::views::StateChangesView,
+}
+
+/// Subscriptions of the single WebSocket connection
+struct Subscriptions {
+    topics: std::collections::BTreeMap<u64, Topic>,
+    next_subscription_id: u64,
+    max_subscriptions: usize,
+}
+
+impl Subscriptions {
+    fn new(max_subscriptions: usize) -> Self {
+        Self {
+            topics: std::collections::BTreeMap::new(),
+            next_subscription_id: 0,
+            max_subscriptions,
+        }
+    }
+
+    // Handles the JSON-RPC request of the client and returns the response to send back
+    // along with the hash of the subscribed transaction, which may be finished already
+    fn handle_request(
+        &mut self,
+        text: &str,
+    ) -> (serde_json::Value, Option<near_primitives::hash::CryptoHash>) {
+        let request = match serde_json::from_str::<SubscriptionRequest>(text) {
+            Ok(request) => request,
+            Err(err) => {
+                return (
+                    error_response(serde_json::Value::Null, -32700, err.to_string()),
+                    None,
+                );
+            }
+        };
+        match request.method {
+            SubscriptionMethod::Subscribe(topic) => {
+                if self.topics.len() >= self.max_subscriptions {
+                    return (
+                        error_response(
+                            request.id,
+                            -32000,
+                            format!(
+                                "Max number of the subscriptions per connection is {}",
+                                self.max_subscriptions
+                            ),
+                        ),
+                        None,
+                    );
+                }
+                let subscription = self.next_subscription_id;
+                self.next_subscription_id += 1;
+                crate::metrics::WS_SUBSCRIPTIONS
+                    .with_label_values(&[topic.name()])
+                    .inc();
+                let tx_hash = match &topic {
+                    Topic::TxStatus { tx_hash } => Some(*tx_hash),
+                    _ => None,
+                };
+                self.topics.insert(subscription, topic);
+                (
+                    result_response(request.id, serde_json::json!(subscription)),
+                    tx_hash,
+                )
+            }
+            SubscriptionMethod::Unsubscribe { subscription } => {
+                let unsubscribed = self.remove(subscription);
+                (
+                    result_response(request.id, serde_json::json!(unsubscribed)),
+                    None,
+                )
+            }
+        }
+    }
+
+    fn remove(&mut self, subscription: u64) -> bool {
+        match self.topics.remove(&subscription) {
+            Some(topic) => {
+                crate::metrics::WS_SUBSCRIPTIONS
+                    .with_label_values(&[topic.name()])
+                    .dec();
+                true
+            }
+            None => false,
+        }
+    }
+
+    // Notifications of the subscriptions with the events in the block.
+    // Subscriptions without the events in the block are not notified
+    fn notifications(&self, block: &crate::modules::blocks::BlockInfo) -> Vec<serde_json::Value> {
+        self.topics
+            .iter()
+            .filter_map(|(subscription, topic)| {
+                topic
+                    .block_event(block)
+                    .map(|result| notification(*subscription, result))
+            })
+            .collect()
+    }
+
+    // Subscriptions to the statuses of the transactions.
+    // All the transactions are returned if the finalized ones are not known
+    fn tx_status_subscriptions(
+        &self,
+        finalized_tx_hashes: Option<&[near_primitives::hash::CryptoHash]>,
+    ) -> Vec<(u64, near_primitives::hash::CryptoHash)> {
+        self.topics
+            .iter()
+            .filter_map(|(subscription, topic)| match topic {
+                Topic::TxStatus { tx_hash }
+                    if finalized_tx_hashes.map_or(true, |hashes| hashes.contains(tx_hash)) =>
+                {
+                    Some((*subscription, *tx_hash))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    // Notifications of the finished transactions, their subscriptions are ended.
+    // The transactions not finished yet are notified by the tx-indexer later
+    async fn tx_status_notifications(
+        &mut self,
+        data: &actix_web::web::Data<crate::config::ServerContext>,
+        finalized_tx_hashes: Option<&[near_primitives::hash::CryptoHash]>,
+    ) -> Vec<serde_json::Value> {
+        let mut notifications = vec![];
+        for (subscription, tx_hash) in self.tx_status_subscriptions(finalized_tx_hashes) {
+            let outcome = match crate::modules::transactions::try_get_transaction_outcome_by_hash(
+                data, &tx_hash,
+            )
+            .await
+            {
+                Ok(outcome) => outcome,
+                Err(err) => {
+                    tracing::debug!("Transaction {} is not finished yet: {:?}", tx_hash, err);
+                    continue;
+                }
+            };
+            // The same result as of the `tx` method
+            let result = near_jsonrpc::primitives::types::transactions::RpcTransactionResponse {
+                final_execution_outcome: Some(
+                    near_primitives::views::FinalExecutionOutcomeViewEnum::FinalExecutionOutcome(
+                        outcome,
+                    ),
+                ),
+                final_execution_status: near_primitives::views::TxExecutionStatus::Final,
+            };
+            notifications.push(notification(subscription, serde_json::json!(result)));
+            self.remove(subscription);
+        }
+        notifications
+    }
+}
+
+fn notification(subscription: u64, result: serde_json::Value) -> serde_json::Value {
+    serde_json::json!({
+        "jsonrpc": "2.0",
+        "method": "subscription",
+        "params": SubscriptionNotification {
+            subscription,
+            result,
+        },
+    })
+}
+
+impl Drop for Subscriptions {
+    fn drop(&mut self) {
+        for topic in self.topics.values() {
+            crate::metrics::WS_SUBSCRIPTIONS
+                .with_label_values(&[topic.name()])
+                .dec();
+        }
+    }
+}
+
+fn result_response(id: serde_json::Value, result: serde_json::Value) -> serde_json::Value {
+    serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "result": result,
+    })
+}
+
+fn error_response(id: serde_json::Value, code: i64, message: String) -> serde_json::Value {
+    serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": {
+            "code": code,
+            "message": message,
+        },
+    })
+}
+
+/// WebSocket endpoint for the subscriptions to the events of the final blocks
+/// and to the statuses of the transactions finalized by the tx-indexer.
+/// The client sends the JSON-RPC `subscribe` and `unsubscribe` requests,
+/// the events are pushed as the `subscription` notifications
+#[actix_web::get("/ws")]
+pub(crate) async fn subscribe(
+    req: actix_web::HttpRequest,
+    body: actix_web::web::Payload,
+    data: actix_web::web::Data<crate::config::ServerContext>,
+) -> actix_web::HttpResponse {
+    if data.method_access.is_disabled(SUBSCRIBE_METHOD) {
+        return crate::errors::error_response(
+            &req,
+            crate::errors::HttpErrorCode::MethodDisabled,
+            format!("Method `{}` is disabled by the operator", SUBSCRIBE_METHOD),
+        );
+    }
+    if let Err(response) = data.api_keys.authorize(&req, SUBSCRIBE_METHOD) {
+        return response;
+    }
+    let (response, session, messages) = match actix_ws::handle(&req, body) {
+        Ok(handshake) => handshake,
+        Err(err) => {
+            return crate::errors::error_response(
+                &req,
+                crate::errors::HttpErrorCode::InvalidRequest,
+                err.to_string(),
+            )
+        }
+    };
+    let final_blocks = data.blocks_info_by_finality.final_blocks_sender.subscribe();
+    let finalized_transactions = data.indexed_heads.finalized_transactions_sender.subscribe();
+    actix_web::rt::spawn(run_session(
+        data.clone(),
+        session,
+        messages,
+        final_blocks,
+        finalized_transactions,
+    ));
+    response
+}
+
+async fn run_session(
+    data: actix_web::web::Data<crate::config::ServerContext>,
+    mut session: actix_ws::Session,
+    mut messages: actix_ws::MessageStream,
+    mut final_blocks: tokio::sync::broadcast::Receiver<
+        std::sync::Arc<crate::modules::blocks::BlockInfo>,
+    >,
+    mut finalized_transactions: tokio::sync::broadcast::Receiver<
+        std::sync::Arc<Vec<near_primitives::hash::CryptoHash>>,
+    >,
+) {
+    let mut subscriptions = Subscriptions::new(data.ws_max_subscriptions);
+    let close_reason = loop {
+        tokio::select! {
+            message = messages.next() => match message {
+                Some(Ok(actix_ws::Message::Text(text))) => {
+                    let (response, tx_hash) = subscriptions.handle_request(&text);
+                    if session.text(response.to_string()).await.is_err() {
+                        return;
+                    }
+                    // The transaction finished before the subscription is notified right away
+                    if let Some(tx_hash) = tx_hash {
+                        for notification in subscriptions
+                            .tx_status_notifications(&data, Some(std::slice::from_ref(&tx_hash)))
+                            .await
+                        {
+                            if session.text(notification.to_string()).await.is_err() {
+                                return;
+                            }
+                        }
+                    }
+                }
+                Some(Ok(actix_ws::Message::Ping(bytes))) => {
+                    if session.pong(&bytes).await.is_err() {
+                        return;
+                    }
+                }
+                Some(Ok(actix_ws::Message::Close(reason))) => break reason,
+                Some(Ok(_)) => {}
+                Some(Err(err)) => {
+                    tracing::debug!("WebSocket protocol error: {}", err);
+                    break None;
+                }
+                None => break None,
+            },
+            block = final_blocks.recv() => match block {
+                Ok(block) => {
+                    for notification in subscriptions.notifications(&block) {
+                        if session.text(notification.to_string()).await.is_err() {
+                            return;
+                        }
+                    }
+                }
+                Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped_blocks)) => {
+                    tracing::debug!(
+                        "WebSocket connection lagged behind by {} blocks",
+                        skipped_blocks
+                    );
+                    break Some(actix_ws::CloseReason {
+                        code: actix_ws::CloseCode::Again,
+                        description: Some(format!(
+                            "Lagged behind by {} blocks, subscribe again",
+                            skipped_blocks
+                        )),
+                    });
+                }
+                Err(tokio::sync::broadcast::error::RecvError::Closed) => break None,
+            },
+            tx_hashes = finalized_transactions.recv() => {
+                // The missed notifications are caught up by checking all the subscribed transactions
+                let finalized_tx_hashes = match &tx_hashes {
+                    Ok(tx_hashes) => Some(tx_hashes.as_slice()),
+                    Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => None,
+                    Err(tokio::sync::broadcast::error::RecvError::Closed) => break None,
+                };
+                for notification in subscriptions
+                    .tx_status_notifications(&data, finalized_tx_hashes)
+                    .await
+                {
+                    if session.text(notification.to_string()).await.is_err() {
+                        return;
+                    }
+                }
+            },
+        }
+    };
+    let _ = session.close(close_reason).await;
+}
@@ -1,8 +1,18 @@
 use clap::Parser;
+use database::StateIndexerDbManager;
 use futures::StreamExt;
 
 use logic_state_indexer::{configs, handle_streamer_message, metrics, NearClient, INDEXER};
 
+#[cfg(feature = "profiling")]
+#[global_allocator]
+static GLOBAL: profiling::Jemalloc = profiling::Jemalloc;
+
+#[cfg(feature = "profiling")]
+#[allow(non_upper_case_globals)]
+#[export_name = "malloc_conf"]
+pub static malloc_conf: &[u8] = profiling::MALLOC_CONF;
+
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     // We use it to automatically search the for root certificates to perform HTTPS calls
@@ -17,33 +27,67 @@ async fn main() -> anyhow::Result<()> {
 
     // Here we have to get the latest ProtocolConfigView to get the up-to-date ShardLayout
     // we use the Referer header to ensure we take it from the native RPC node
-    let rpc_client = near_jsonrpc_client::JsonRpcClient::connect(&indexer_config.general.near_rpc_url)
-        .header(("Referer", indexer_config.general.referer_header_value.clone()))?;
+    let http_client_factory = http_client::HttpClientFactory::new(&indexer_config.http_client)?;
+    let rpc_client = http_client_factory
+        .rpc_client("near_rpc", opts.rpc_url(&indexer_config.general.near_rpc_url))
+        .header("Referer", &indexer_config.general.referer_header_value)?
+        .api_key(indexer_config.general.near_rpc_api_key.as_deref())?;
     let near_client = logic_state_indexer::NearJsonRpc::new(rpc_client);
 
     let protocol_config_view = near_client.protocol_config().await?;
 
-    let db_manager = database::prepare_db_manager::<database::PostgresDBManager>(
-        &indexer_config.database,
-        protocol_config_view.shard_layout.clone(),
-    )
-    .await?;
+    let db_manager = std::sync::Arc::new(
+        database::prepare_db_manager::<database::PostgresDBManager>(
+            &indexer_config.database,
+            protocol_config_view.shard_layout.clone(),
+        )
+        .await?,
+    );
+    configs::check_network(&near_client, &*db_manager, &indexer_config.general.chain_id).await?;
+    if let configs::StartOptions::RebalanceStateBuckets {
+        account_ids,
+        batch_size,
+    } = &opts.start_options
+    {
+        return logic_state_indexer::state_buckets::rebalance(&*db_manager, account_ids, *batch_size).await;
+    }
+    // The rebalancing above moves the state into the buckets of the changed number, so it isn't checked
+    db_manager.save_state_key_buckets().await?;
     let start_block_height = configs::get_start_block_height(
         &near_client,
-        &db_manager,
+        &*db_manager,
         &opts.start_options,
         &indexer_config.general.indexer_id,
     )
     .await?;
 
-    let lake_config = indexer_config.lake_config.lake_config(start_block_height).await?;
-    let (sender, stream) = near_lake_framework::streamer(lake_config);
+    let start_block_height = if opts.exclude_start_block {
+        start_block_height + 1
+    } else {
+        start_block_height
+    };
+    let (sender, stream) = lake_streamer::streamer(
+        &indexer_config.lake_config.with_source(opts.blocks_source),
+        start_block_height,
+        Some(opts.blocks_preload_pool_size),
+    )
+    .await?;
 
     // Initiate metrics http server
     tokio::spawn(
         metrics::init_server(indexer_config.general.metrics_server_port).expect("Failed to start metrics server"),
     );
 
+    // Runs the analytics jobs until the indexer stops
+    let scheduler = database::scheduler::Scheduler::new();
+    if indexer_config.general.state_growth_analytics {
+        logic_state_indexer::state_growth::start(
+            std::sync::Arc::clone(&db_manager),
+            &scheduler,
+            indexer_config.general.state_growth_alert_bytes,
+        );
+    }
+
     let stats = std::sync::Arc::new(tokio::sync::RwLock::new(metrics::Stats::default()));
     tokio::spawn(metrics::state_logger(std::sync::Arc::clone(&stats), near_client.clone()));
 
@@ -51,7 +95,7 @@ async fn main() -> anyhow::Result<()> {
         .map(|streamer_message| {
             handle_streamer_message(
                 streamer_message,
-                &db_manager,
+                &*db_manager,
                 &near_client,
                 indexer_config.clone(),
                 std::sync::Arc::clone(&stats),
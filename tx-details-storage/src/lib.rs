@@ -1,7 +1,12 @@
+use google_cloud_storage::http::objects::delete::DeleteObjectRequest;
 use google_cloud_storage::http::objects::download::Range;
 use google_cloud_storage::http::objects::get::GetObjectRequest;
 use google_cloud_storage::http::objects::upload::{Media, UploadObjectRequest, UploadType};
 
+// Suffix of the object with the slim `FinalExecutionOutcomeView` stored alongside the full
+// transaction details. It lets the `tx` method avoid downloading and deserializing receipts
+const OUTCOME_KEY_SUFFIX: &str = ".outcome";
+
 pub struct TxDetailsStorage {
     client: google_cloud_storage::client::Client,
     bucket_name: String,
@@ -44,4 +49,35 @@ impl TxDetailsStorage {
             .await?;
         Ok(data)
     }
+
+    /// Deletes the object. The missing object is considered deleted,
+    /// so the deletion can be repeated after a failure
+    pub async fn delete(&self, key: &str) -> anyhow::Result<()> {
+        let result = self
+            .client
+            .delete_object(&DeleteObjectRequest {
+                bucket: self.bucket_name.to_string(),
+                object: key.to_string(),
+                ..Default::default()
+            })
+            .await;
+        match result {
+            Ok(()) => Ok(()),
+            Err(google_cloud_storage::http::Error::Response(err)) if err.code == 404 => Ok(()),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    pub async fn store_outcome(&self, key: &str, data: Vec<u8>) -> anyhow::Result<()> {
+        self.store(&format!("{key}{OUTCOME_KEY_SUFFIX}"), data)
+            .await
+    }
+
+    pub async fn retrieve_outcome(&self, key: &str) -> anyhow::Result<Vec<u8>> {
+        self.retrieve(&format!("{key}{OUTCOME_KEY_SUFFIX}")).await
+    }
+
+    pub async fn delete_outcome(&self, key: &str) -> anyhow::Result<()> {
+        self.delete(&format!("{key}{OUTCOME_KEY_SUFFIX}")).await
+    }
 }
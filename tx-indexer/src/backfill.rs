@@ -0,0 +1,57 @@
+use near_jsonrpc_client::methods;
+use readnode_primitives::indexer::types::{BlockId, BlockReference};
+
+// Number of blocks fetched from the RPC per backfill iteration
+const BACKFILL_BATCH_SIZE: u64 = 100;
+
+/// Fills the protocol version and gas price of the transactions stored before they were
+/// indexed. Block headers and the protocol versions of their epochs are fetched from the NEAR RPC,
+/// so it has to be an archival node to backfill the old blocks.
+/// Runs alongside the indexing and stops once everything is filled
+pub(crate) async fn backfill_transactions_block_info(
+    rpc_client: http_client::RpcClient,
+    db_manager: std::sync::Arc<Box<dyn database::TxIndexerDbManager + Sync + Send + 'static>>,
+) -> anyhow::Result<()> {
+    tracing::info!(target: crate::INDEXER, "Starting transactions block info backfill...");
+    let protocol_versions =
+        crate::protocol_versions::EpochProtocolVersions::new(rpc_client.clone());
+    let mut blocks_backfilled = 0;
+    loop {
+        let block_heights = db_manager
+            .get_block_heights_without_block_info(BACKFILL_BATCH_SIZE)
+            .await?;
+        if block_heights.is_empty() {
+            break;
+        }
+        for block_height in block_heights {
+            let block = rpc_client
+                .call(methods::block::RpcBlockRequest {
+                    block_reference: BlockReference::BlockId(BlockId::Height(block_height)),
+                })
+                .await
+                .map_err(|err| {
+                    anyhow::anyhow!("Failed to fetch block {} from RPC: {:?}", block_height, err)
+                })?;
+            let protocol_version = protocol_versions.get(&block.header).await?;
+            db_manager
+                .update_transactions_block_info(
+                    block_height,
+                    protocol_version,
+                    block.header.gas_price,
+                )
+                .await?;
+            blocks_backfilled += 1;
+        }
+        tracing::debug!(
+            target: crate::INDEXER,
+            "Transactions block info backfilled for {} blocks",
+            blocks_backfilled
+        );
+    }
+    tracing::info!(
+        target: crate::INDEXER,
+        "Transactions block info backfill finished. {} blocks backfilled",
+        blocks_backfilled
+    );
+    Ok(())
+}
@@ -0,0 +1,123 @@
+use readnode_primitives::indexer::CryptoHash;
+
+/// Number of the indexed blocks buffered for every subscriber.
+/// The subscriber lagging behind by more blocks is disconnected
+const BLOCK_STREAM_CAPACITY: usize = 1000;
+
+/// Block finished by the indexer, emitted to the subscribers of the block stream
+#[derive(Debug, serde::Serialize)]
+pub(crate) struct IndexedBlock {
+    pub block_height: u64,
+    pub block_hash: CryptoHash,
+    /// Transactions included in the chunks of the block
+    pub tx_hashes: Vec<CryptoHash>,
+    /// Receipts executed in the block
+    pub receipt_ids: Vec<CryptoHash>,
+}
+
+impl IndexedBlock {
+    fn from_streamer_message(
+        streamer_message: &readnode_primitives::indexer::StreamerMessage,
+    ) -> Self {
+        Self {
+            block_height: streamer_message.block.header.height,
+            block_hash: streamer_message.block.header.hash,
+            tx_hashes: streamer_message
+                .shards
+                .iter()
+                .filter_map(|shard| shard.chunk.as_ref())
+                .flat_map(|chunk| chunk.transactions.iter())
+                .map(|transaction| transaction.transaction.hash)
+                .collect(),
+            receipt_ids: streamer_message
+                .shards
+                .iter()
+                .flat_map(|shard| shard.receipt_execution_outcomes.iter())
+                .map(|outcome| outcome.receipt.receipt_id)
+                .collect(),
+        }
+    }
+}
+
+/// Stream of the blocks finished by the indexer, served on the metrics server
+/// for the auxiliary indexers to chain off the indexer instead of reading the lake again
+pub(crate) struct BlockStream {
+    sender: tokio::sync::broadcast::Sender<std::sync::Arc<IndexedBlock>>,
+}
+
+impl BlockStream {
+    pub(crate) fn new() -> Self {
+        let (sender, _) = tokio::sync::broadcast::channel(BLOCK_STREAM_CAPACITY);
+        Self { sender }
+    }
+
+    /// Emits the block to the subscribers, nothing is buffered without subscribers
+    pub(crate) fn publish(&self, streamer_message: &readnode_primitives::indexer::StreamerMessage) {
+        if self.sender.receiver_count() == 0 {
+            return;
+        }
+        let block = IndexedBlock::from_streamer_message(streamer_message);
+        // The subscribers can be gone since the check, the block is dropped then
+        let _ = self.sender.send(std::sync::Arc::new(block));
+    }
+
+    fn subscribe(&self) -> tokio::sync::broadcast::Receiver<std::sync::Arc<IndexedBlock>> {
+        self.sender.subscribe()
+    }
+}
+
+/// Streams the blocks finished from now on as newline delimited JSON.
+/// The response ends if the subscriber lags behind, the subscriber fetches the skipped blocks
+/// on its own and reconnects
+#[actix_web::get("/blocks/stream")]
+pub(crate) async fn stream_blocks(
+    block_stream: actix_web::web::Data<BlockStream>,
+) -> actix_web::HttpResponse {
+    let receiver = block_stream.subscribe();
+    crate::metrics::BLOCK_STREAM_SUBSCRIBERS.inc();
+    let subscription = Subscription(receiver);
+    let body = futures::stream::unfold(subscription, |mut subscription| async move {
+        match subscription.0.recv().await {
+            Ok(block) => {
+                let mut line = match serde_json::to_vec(&*block) {
+                    Ok(line) => line,
+                    Err(err) => {
+                        tracing::error!(
+                            target: crate::INDEXER,
+                            "#{} failed to serialize the streamed block: {:?}",
+                            block.block_height,
+                            err
+                        );
+                        return None;
+                    }
+                };
+                line.push(b'\n');
+                Some((
+                    Ok::<_, actix_web::Error>(actix_web::web::Bytes::from(line)),
+                    subscription,
+                ))
+            }
+            Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
+                tracing::warn!(
+                    target: crate::INDEXER,
+                    "Block stream subscriber lagged behind by {} blocks, disconnecting",
+                    skipped
+                );
+                None
+            }
+            Err(tokio::sync::broadcast::error::RecvError::Closed) => None,
+        }
+    });
+    actix_web::HttpResponse::Ok()
+        .content_type("application/x-ndjson")
+        .streaming(body)
+}
+
+// Counts the subscriber until its response is dropped
+struct Subscription(tokio::sync::broadcast::Receiver<std::sync::Arc<IndexedBlock>>);
+
+impl Drop for Subscription {
+    fn drop(&mut self) {
+        crate::metrics::BLOCK_STREAM_SUBSCRIBERS.dec();
+    }
+}
@@ -1,4 +1,4 @@
-use near_indexer_primitives::IndexerTransactionWithOutcome;
+use readnode_primitives::indexer::IndexerTransactionWithOutcome;
 
 use futures::{FutureExt, StreamExt};
 use tokio_retry::{strategy::FixedInterval, Retry};
@@ -8,47 +8,164 @@ use crate::storage;
 
 const SAVE_ATTEMPTS: usize = 20;
 
+// Number of receipt execution outcomes matched to the collecting transactions
+// and number of the receipts produced by them and added to the watching list
+#[derive(Debug, Default, Clone, Copy)]
+struct MatchedOutcomes {
+    outcomes: u64,
+    receipts: u64,
+}
+
+impl std::ops::Add for MatchedOutcomes {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            outcomes: self.outcomes + other.outcomes,
+            receipts: self.receipts + other.receipts,
+        }
+    }
+}
+
+// Awaits the future and measures how long it took
+async fn timed<F: std::future::Future>(future: F) -> (F::Output, std::time::Duration) {
+    let start = std::time::Instant::now();
+    let output = future.await;
+    (output, start.elapsed())
+}
+
 #[allow(unused_variables)]
 #[cfg_attr(feature = "tracing-instrumentation", tracing::instrument(skip_all))]
 pub(crate) async fn index_transactions(
-    streamer_message: &near_indexer_primitives::StreamerMessage,
+    streamer_message: &std::sync::Arc<readnode_primitives::indexer::StreamerMessage>,
     db_manager: &std::sync::Arc<Box<dyn database::TxIndexerDbManager + Sync + Send + 'static>>,
     tx_collecting_storage: &std::sync::Arc<crate::storage::CacheStorage>,
-    tx_details_storage: &std::sync::Arc<crate::TxDetailsStorage>,
+    collector_events: &std::sync::Arc<crate::events::CollectorEvents>,
     indexer_config: &configuration::TxIndexerConfig,
-) -> anyhow::Result<()> {
-    extract_transactions_to_collect(streamer_message, tx_collecting_storage, indexer_config)
-        .await?;
-    collect_receipts_and_outcomes(streamer_message, tx_collecting_storage).await?;
+    protocol_versions: &crate::protocol_versions::EpochProtocolVersions,
+    block_claimed: bool,
+    concurrency: usize,
+) -> anyhow::Result<readnode_primitives::BlockProcessingStats> {
+    let (collected, collect_duration) = timed(collect_shards(
+        streamer_message,
+        tx_collecting_storage,
+        collector_events,
+        indexer_config,
+        block_claimed,
+        concurrency,
+    ))
+    .await;
+    let (txs_started, matched) = collected?;
 
-    let save_finished_tx_details_future =
-        save_finished_transaction_details(tx_collecting_storage, tx_details_storage);
+    let save_finished_tx_details_future = timed(save_finished_transaction_details(
+        tx_collecting_storage,
+        collector_events,
+    ));
 
-    let save_outcomes_and_receipts_future = {
+    let save_outcomes_and_receipts_future = timed({
         #[cfg(feature = "save_outcomes_and_receipts")]
         {
-            save_outcomes_and_receipts(db_manager, tx_collecting_storage)
+            save_outcomes_and_receipts(
+                db_manager,
+                tx_collecting_storage,
+                indexer_config.general.save_batch_size,
+            )
+            .boxed()
         }
         #[cfg(not(feature = "save_outcomes_and_receipts"))]
         {
             // if feature is disabled just return Ok(()) to skip saving outcomes and receipts
             // to the database, this is useful for testing and reindexing only transaction details
-            futures::future::ready(Ok(()))
+            futures::future::ready(anyhow::Ok(())).boxed()
+        }
+    });
+    let save_transactions_by_public_key_future = timed(async {
+        if block_claimed {
+            save_transactions_by_public_key(
+                streamer_message,
+                db_manager,
+                indexer_config,
+                protocol_versions,
+            )
+            .await
+        } else {
+            Ok(())
+        }
+    });
+
+    // The function calls are stored only if the search is enabled, the receipts are
+    // executed in the block, so they are saved by the instance which claimed it
+    let save_function_calls_future = async {
+        match &indexer_config.function_calls_search {
+            Some(search_config) if block_claimed => {
+                save_function_calls(streamer_message, db_manager, search_config).await
+            }
+            _ => Ok(()),
         }
     };
-    futures::future::join_all([
-        save_finished_tx_details_future.boxed(),
-        save_outcomes_and_receipts_future.boxed(),
-    ])
-    .await
-    .into_iter()
-    .collect::<anyhow::Result<_>>()
+
+    let save_actions_future = async {
+        if indexer_config.general.index_actions && block_claimed {
+            save_actions(streamer_message, db_manager).await
+        } else {
+            Ok(())
+        }
+    };
+
+    let save_skipped_chunks_future = async {
+        if block_claimed {
+            save_skipped_chunks(streamer_message, db_manager).await
+        } else {
+            Ok(())
+        }
+    };
+
+    // Wait for all the futures even if some of them failed
+    let (
+        (txs_finalized, save_tx_details_duration),
+        (save_outcomes_and_receipts_result, save_outcomes_and_receipts_duration),
+        (save_transactions_by_public_key_result, save_transactions_by_public_key_duration),
+        save_function_calls_result,
+        save_actions_result,
+        save_skipped_chunks_result,
+    ) = futures::join!(
+        save_finished_tx_details_future,
+        save_outcomes_and_receipts_future,
+        save_transactions_by_public_key_future,
+        save_function_calls_future,
+        save_actions_future,
+        save_skipped_chunks_future,
+    );
+    let txs_finalized = txs_finalized?;
+    save_outcomes_and_receipts_result?;
+    save_transactions_by_public_key_result?;
+    save_function_calls_result?;
+    save_actions_result?;
+    save_skipped_chunks_result?;
+
+    Ok(readnode_primitives::BlockProcessingStats {
+        block_height: streamer_message.block.header.height,
+        block_hash: streamer_message.block.header.hash,
+        txs_started,
+        receipts_matched: matched.receipts,
+        outcomes_matched: matched.outcomes,
+        txs_finalized,
+        collect_duration_ms: collect_duration.as_millis() as u64,
+        save_tx_details_duration_ms: save_tx_details_duration.as_millis() as u64,
+        save_outcomes_and_receipts_duration_ms: save_outcomes_and_receipts_duration.as_millis()
+            as u64,
+        save_transactions_by_public_key_duration_ms: save_transactions_by_public_key_duration
+            .as_millis() as u64,
+    })
 }
 
-async fn save_finished_transaction_details(
+// Emits the finished transactions to be saved by the writer subscribed to them.
+// Waits for the free space in the write-ahead queue to slow down the stream consumption
+// when the storage can't keep up
+pub(crate) async fn save_finished_transaction_details(
     tx_collecting_storage: &std::sync::Arc<crate::storage::CacheStorage>,
-    tx_details_storage: &std::sync::Arc<crate::TxDetailsStorage>,
-) -> anyhow::Result<()> {
+    collector_events: &crate::events::CollectorEvents,
+) -> anyhow::Result<u64> {
     let finished_transaction_details =
         tx_collecting_storage
             .transactions_to_save()
@@ -62,30 +179,23 @@ async fn save_finished_transaction_details(
                 err
             })?;
 
-    if !finished_transaction_details.is_empty() {
-        let tx_collecting_storage = tx_collecting_storage.clone();
-        let tx_details_storage = tx_details_storage.clone();
-        tokio::spawn(async move {
-            let send_finished_transaction_details_futures =
-                finished_transaction_details.into_iter().map(|tx_details| {
-                    save_transaction_details(
-                        &tx_collecting_storage,
-                        &tx_details_storage,
-                        tx_details,
-                    )
-                });
-
-            futures::future::join_all(send_finished_transaction_details_futures).await;
-        });
+    let finalized = finished_transaction_details.len() as u64;
+    for transaction_details in finished_transaction_details {
+        collector_events
+            .emit(crate::events::CollectorEvent::TransactionFinalized {
+                transaction_details,
+            })
+            .await?;
     }
 
-    Ok(())
+    Ok(finalized)
 }
 
 #[cfg(feature = "save_outcomes_and_receipts")]
 async fn save_outcomes_and_receipts(
     db_manager: &std::sync::Arc<Box<dyn database::TxIndexerDbManager + Sync + Send + 'static>>,
     tx_collecting_storage: &std::sync::Arc<crate::storage::CacheStorage>,
+    save_batch_size: usize,
 ) -> anyhow::Result<()> {
     let receipts_and_outcomes_to_save = tx_collecting_storage
         .outcomes_and_receipts_to_save()
@@ -102,26 +212,72 @@ async fn save_outcomes_and_receipts(
     if !receipts_and_outcomes_to_save.is_empty() {
         let db_manager_clone = db_manager.clone();
         let tx_collecting_storage_clone = tx_collecting_storage.clone();
+        // The shutdown waits for the saving to finish
+        let saving_guard = tx_collecting_storage.start_saving().await;
         tokio::spawn(async move {
-            let save_receipts_and_outcomes_futures = receipts_and_outcomes_to_save.into_iter().map(
-                |(shard_id, receipts_and_outcomes)| {
-                    save_receipts_and_outcomes_details(
-                        &db_manager_clone,
-                        &tx_collecting_storage_clone,
-                        shard_id,
-                        receipts_and_outcomes.receipts,
-                        receipts_and_outcomes.outcomes,
-                    )
-                },
-            );
-
-            futures::future::join_all(save_receipts_and_outcomes_futures).await;
+            save_receipts_and_outcomes_to_shards(
+                &db_manager_clone,
+                &tx_collecting_storage_clone,
+                receipts_and_outcomes_to_save,
+                save_batch_size,
+            )
+            .await;
+            drop(saving_guard);
         });
     }
 
     Ok(())
 }
 
+// Saves the receipts and outcomes left in the cache storage on shutdown.
+// Waits for the saving in progress, so the records returned by its failures are saved too
+#[cfg(feature = "save_outcomes_and_receipts")]
+pub(crate) async fn flush_outcomes_and_receipts(
+    db_manager: &std::sync::Arc<Box<dyn database::TxIndexerDbManager + Sync + Send + 'static>>,
+    tx_collecting_storage: &std::sync::Arc<crate::storage::CacheStorage>,
+    save_batch_size: usize,
+) -> anyhow::Result<()> {
+    tx_collecting_storage.wait_for_saving().await;
+    let receipts_and_outcomes_to_save = tx_collecting_storage
+        .outcomes_and_receipts_to_save()
+        .await?;
+    save_receipts_and_outcomes_to_shards(
+        db_manager,
+        tx_collecting_storage,
+        receipts_and_outcomes_to_save,
+        save_batch_size,
+    )
+    .await;
+    Ok(())
+}
+
+#[cfg(feature = "save_outcomes_and_receipts")]
+async fn save_receipts_and_outcomes_to_shards(
+    db_manager: &std::sync::Arc<Box<dyn database::TxIndexerDbManager + Sync + Send + 'static>>,
+    tx_collecting_storage: &std::sync::Arc<crate::storage::CacheStorage>,
+    receipts_and_outcomes_to_save: std::collections::HashMap<
+        database::primitives::ShardId,
+        crate::storage::ReceiptsAndOutcomesToSave,
+    >,
+    save_batch_size: usize,
+) {
+    let save_receipts_and_outcomes_futures =
+        receipts_and_outcomes_to_save
+            .into_iter()
+            .map(|(shard_id, receipts_and_outcomes)| {
+                save_receipts_and_outcomes_details(
+                    db_manager,
+                    tx_collecting_storage,
+                    shard_id,
+                    receipts_and_outcomes.receipts,
+                    receipts_and_outcomes.outcomes,
+                    save_batch_size,
+                )
+            });
+
+    futures::future::join_all(save_receipts_and_outcomes_futures).await;
+}
+
 #[cfg(feature = "save_outcomes_and_receipts")]
 #[cfg_attr(feature = "tracing-instrumentation", tracing::instrument(skip_all))]
 async fn save_receipts_and_outcomes_details(
@@ -130,15 +286,31 @@ async fn save_receipts_and_outcomes_details(
     shard_id: database::primitives::ShardId,
     receipts: Vec<readnode_primitives::ReceiptRecord>,
     outcomes: Vec<readnode_primitives::OutcomeRecord>,
+    save_batch_size: usize,
 ) {
-    match save_outcome_and_receipt_to_shard(
-        db_manager,
-        shard_id,
-        receipts.clone(),
-        outcomes.clone(),
-    )
-    .await
-    {
+    // The receipts and outcomes collected from many blocks while catching up are saved
+    // by the batches, a single INSERT of all of them may exceed the limit of the query parameters.
+    // The failed batch returns all the records to save them again,
+    // the records of the saved batches are skipped by the database on conflict
+    let save_batch_size = save_batch_size.max(1);
+    let batches_count = receipts.len().max(outcomes.len()).div_ceil(save_batch_size);
+    let mut result = Ok(());
+    for batch in 0..batches_count {
+        let batch_range = |len: usize| {
+            (batch * save_batch_size).min(len)..((batch + 1) * save_batch_size).min(len)
+        };
+        result = save_outcome_and_receipt_to_shard(
+            db_manager,
+            shard_id,
+            receipts[batch_range(receipts.len())].to_vec(),
+            outcomes[batch_range(outcomes.len())].to_vec(),
+        )
+        .await;
+        if result.is_err() {
+            break;
+        }
+    }
+    match result {
         Ok(_) => {
             tracing::debug!(
                 target: crate::INDEXER,
@@ -189,6 +361,9 @@ async fn save_outcome_and_receipt_to_shard(
     };
 
     Retry::spawn(retry_strategy, operation).await.map_err(|e| {
+        crate::metrics::DB_SAVE_FAILURES_TOTAL
+            .with_label_values(&["save_outcome_and_receipt"])
+            .inc();
         anyhow::anyhow!(
             "Failed to save receipts and outcomes for shard {} after {} attempts: {}",
             shard_id,
@@ -206,19 +381,256 @@ async fn save_outcome_and_receipt_to_shard(
     Ok(())
 }
 
-// Extracts all Transactions from the given `StreamerMessage` and pushes them to the memory storage
-// by calling the function `new_transaction_details_to_collecting_pool`.
+// Saves all indexed Transactions from the given `StreamerMessage` to the DB
+// to be able to search them by the signer public key
 #[cfg_attr(feature = "tracing-instrumentation", tracing::instrument(skip_all))]
-async fn extract_transactions_to_collect(
-    streamer_message: &near_indexer_primitives::StreamerMessage,
-    tx_collecting_storage: &std::sync::Arc<crate::storage::CacheStorage>,
+async fn save_transactions_by_public_key(
+    streamer_message: &readnode_primitives::indexer::StreamerMessage,
+    db_manager: &std::sync::Arc<Box<dyn database::TxIndexerDbManager + Sync + Send + 'static>>,
     indexer_config: &configuration::TxIndexerConfig,
+    protocol_versions: &crate::protocol_versions::EpochProtocolVersions,
 ) -> anyhow::Result<()> {
-    let block = readnode_primitives::BlockRecord {
-        height: streamer_message.block.header.height,
-        hash: streamer_message.block.header.hash,
+    let protocol_version = protocol_versions
+        .get(&streamer_message.block.header)
+        .await?;
+    let transactions = streamer_message
+        .shards
+        .iter()
+        .filter_map(|shard| shard.chunk.as_ref())
+        .map(|chunk| (chunk.header.shard_id, chunk.transactions.iter()))
+        .flat_map(|(shard_id, transactions)| {
+            transactions
+                .filter(move |tx| indexer_config.tx_should_be_indexed(tx))
+                .map(move |tx| {
+                    readnode_primitives::TransactionByPublicKeyRecord::new(
+                        &tx.transaction,
+                        &streamer_message.block.header,
+                        shard_id,
+                        protocol_version,
+                    )
+                })
+        })
+        .collect::<Vec<_>>();
+
+    let retry_strategy = FixedInterval::from_millis(500).take(SAVE_ATTEMPTS);
+    let operation = || async {
+        db_manager
+            .save_transactions_by_public_key(transactions.clone())
+            .await
+            .map_err(|e| {
+                tracing::warn!(
+                    target: crate::INDEXER,
+                    "Failed to save transactions by public key for block {}: Error {}",
+                    streamer_message.block.header.height,
+                    e
+                );
+                e
+            })
+    };
+
+    Retry::spawn(retry_strategy, operation).await.map_err(|e| {
+        crate::metrics::DB_SAVE_FAILURES_TOTAL
+            .with_label_values(&["save_transactions_by_public_key"])
+            .inc();
+        anyhow::anyhow!(
+            "Failed to save transactions by public key for block {} after {} attempts: {}",
+            streamer_message.block.header.height,
+            SAVE_ATTEMPTS,
+            e
+        )
+    })
+}
+
+// Saves the function calls to the searchable contracts executed in the given `StreamerMessage`
+// to the DB to be able to search them by the decoded arguments
+#[cfg_attr(feature = "tracing-instrumentation", tracing::instrument(skip_all))]
+async fn save_function_calls(
+    streamer_message: &readnode_primitives::indexer::StreamerMessage,
+    db_manager: &std::sync::Arc<Box<dyn database::TxIndexerDbManager + Sync + Send + 'static>>,
+    search_config: &configuration::FunctionCallsSearchConfig,
+) -> anyhow::Result<()> {
+    let function_calls = streamer_message
+        .shards
+        .iter()
+        .flat_map(|shard| shard.receipt_execution_outcomes.iter())
+        .filter(|outcome| search_config.is_searchable_contract(&outcome.receipt.receiver_id))
+        .flat_map(|outcome| {
+            readnode_primitives::FunctionCallRecord::from_receipt(
+                &outcome.receipt,
+                &streamer_message.block.header,
+                search_config.max_args_size,
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let retry_strategy = FixedInterval::from_millis(500).take(SAVE_ATTEMPTS);
+    let operation = || async {
+        db_manager
+            .save_function_calls(function_calls.clone())
+            .await
+            .map_err(|e| {
+                tracing::warn!(
+                    target: crate::INDEXER,
+                    "Failed to save function calls for block {}: Error {}",
+                    streamer_message.block.header.height,
+                    e
+                );
+                e
+            })
+    };
+
+    Retry::spawn(retry_strategy, operation).await.map_err(|e| {
+        crate::metrics::DB_SAVE_FAILURES_TOTAL
+            .with_label_values(&["save_function_calls"])
+            .inc();
+        anyhow::anyhow!(
+            "Failed to save function calls for block {} after {} attempts: {}",
+            streamer_message.block.header.height,
+            SAVE_ATTEMPTS,
+            e
+        )
+    })
+}
+
+// Saves the actions of the receipts executed in the given `StreamerMessage`
+// to the DB to be able to query them by the contract method
+#[cfg_attr(feature = "tracing-instrumentation", tracing::instrument(skip_all))]
+async fn save_actions(
+    streamer_message: &readnode_primitives::indexer::StreamerMessage,
+    db_manager: &std::sync::Arc<Box<dyn database::TxIndexerDbManager + Sync + Send + 'static>>,
+) -> anyhow::Result<()> {
+    let actions = streamer_message
+        .shards
+        .iter()
+        .flat_map(|shard| shard.receipt_execution_outcomes.iter())
+        .flat_map(|outcome| {
+            readnode_primitives::ActionRecord::from_receipt(
+                &outcome.receipt,
+                &streamer_message.block.header,
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let retry_strategy = FixedInterval::from_millis(500).take(SAVE_ATTEMPTS);
+    let operation = || async {
+        db_manager.save_actions(actions.clone()).await.map_err(|e| {
+            tracing::warn!(
+                target: crate::INDEXER,
+                "Failed to save actions for block {}: Error {}",
+                streamer_message.block.header.height,
+                e
+            );
+            e
+        })
+    };
+
+    Retry::spawn(retry_strategy, operation).await.map_err(|e| {
+        crate::metrics::DB_SAVE_FAILURES_TOTAL
+            .with_label_values(&["save_actions"])
+            .inc();
+        anyhow::anyhow!(
+            "Failed to save actions for block {} after {} attempts: {}",
+            streamer_message.block.header.height,
+            SAVE_ATTEMPTS,
+            e
+        )
+    })
+}
+
+// Returns the chunks included in the block header for the first time which are missing
+// in the shards of the `StreamerMessage` as `(shard_id, chunk_hash)`. The chunk is absent
+// if its producer was offline and the chunk was not available to the lake indexer,
+// the transactions of such a chunk can't be indexed from this block
+fn find_skipped_chunks(
+    streamer_message: &readnode_primitives::indexer::StreamerMessage,
+) -> Vec<(u64, readnode_primitives::indexer::CryptoHash)> {
+    streamer_message
+        .block
+        .chunks
+        .iter()
+        // The chunks included in the previous blocks are missing in the shards by design
+        .filter(|chunk_header| chunk_header.height_included == streamer_message.block.header.height)
+        .filter(|chunk_header| {
+            !streamer_message.shards.iter().any(|shard| {
+                shard
+                    .chunk
+                    .as_ref()
+                    .is_some_and(|chunk| chunk.header.shard_id == chunk_header.shard_id)
+            })
+        })
+        .map(|chunk_header| (chunk_header.shard_id, chunk_header.chunk_hash))
+        .collect()
+}
+
+// Marks the chunks missing in the given `StreamerMessage` in the DB,
+// so the block is known to be indexed partially instead of silently missing the data
+#[cfg_attr(feature = "tracing-instrumentation", tracing::instrument(skip_all))]
+async fn save_skipped_chunks(
+    streamer_message: &readnode_primitives::indexer::StreamerMessage,
+    db_manager: &std::sync::Arc<Box<dyn database::TxIndexerDbManager + Sync + Send + 'static>>,
+) -> anyhow::Result<()> {
+    let skipped_chunks = find_skipped_chunks(streamer_message);
+    if skipped_chunks.is_empty() {
+        return Ok(());
+    }
+    for (shard_id, chunk_hash) in &skipped_chunks {
+        tracing::warn!(
+            target: crate::INDEXER,
+            "Chunk {} of shard {} is missing in block {}, the shard is indexed without it",
+            chunk_hash,
+            shard_id,
+            streamer_message.block.header.height,
+        );
+    }
+    crate::metrics::SKIPPED_CHUNKS_TOTAL.inc_by(skipped_chunks.len() as u64);
+    let skipped_chunks = skipped_chunks
+        .into_iter()
+        .map(|(shard_id, chunk_hash)| (shard_id, chunk_hash.to_string()))
+        .collect::<Vec<_>>();
+
+    let retry_strategy = FixedInterval::from_millis(500).take(SAVE_ATTEMPTS);
+    let operation = || async {
+        db_manager
+            .save_skipped_chunks(streamer_message.block.header.height, skipped_chunks.clone())
+            .await
+            .map_err(|e| {
+                tracing::warn!(
+                    target: crate::INDEXER,
+                    "Failed to save skipped chunks for block {}: Error {}",
+                    streamer_message.block.header.height,
+                    e
+                );
+                e
+            })
     };
 
+    Retry::spawn(retry_strategy, operation).await.map_err(|e| {
+        crate::metrics::DB_SAVE_FAILURES_TOTAL
+            .with_label_values(&["save_skipped_chunks"])
+            .inc();
+        anyhow::anyhow!(
+            "Failed to save skipped chunks for block {} after {} attempts: {}",
+            streamer_message.block.header.height,
+            SAVE_ATTEMPTS,
+            e
+        )
+    })
+}
+
+// Collects the transactions, receipts and outcomes of the block by the shards.
+// Every shard is processed by its own task, so the blocks with many shards are collected
+// on all the cores, `concurrency` limits the number of the shards processed at once.
+// The receipt of the transaction is executed in the same block only if it is local,
+// i.e. on the same shard, so the shards don't depend on each other within the block
+#[cfg_attr(feature = "tracing-instrumentation", tracing::instrument(skip_all))]
+async fn collect_shards(
+    streamer_message: &std::sync::Arc<readnode_primitives::indexer::StreamerMessage>,
+    tx_collecting_storage: &std::sync::Arc<crate::storage::CacheStorage>,
+    collector_events: &std::sync::Arc<crate::events::CollectorEvents>,
+    indexer_config: &configuration::TxIndexerConfig,
+    block_claimed: bool,
+    concurrency: usize,
+) -> anyhow::Result<(u64, MatchedOutcomes)> {
     let txs_in_block = streamer_message
         .shards
         .iter()
@@ -231,42 +643,118 @@ async fn extract_transactions_to_collect(
         .sum::<usize>();
     crate::metrics::TX_IN_BLOCK_TOTAL.set(txs_in_block as i64);
 
-    let futures = streamer_message
-        .shards
-        .iter()
-        .filter_map(|shard| shard.chunk.as_ref())
-        .map(|chunk| (chunk.header.shard_id, chunk.transactions.iter()))
-        .flat_map(|(shard_id, transactions)| {
-            transactions.map(move |tx| {
-                new_transaction_details_to_collecting_pool(
-                    tx,
-                    block,
-                    shard_id,
-                    tx_collecting_storage,
-                    indexer_config,
-                )
-            })
-        });
+    let indexer_config = std::sync::Arc::new(indexer_config.clone());
+    let shard_tasks = (0..streamer_message.shards.len()).map(|shard_index| {
+        let streamer_message = streamer_message.clone();
+        let tx_collecting_storage = tx_collecting_storage.clone();
+        let collector_events = collector_events.clone();
+        let indexer_config = indexer_config.clone();
+        tokio::spawn(async move {
+            collect_shard(
+                &streamer_message,
+                &streamer_message.shards[shard_index],
+                &tx_collecting_storage,
+                &collector_events,
+                &indexer_config,
+                block_claimed,
+            )
+            .await
+        })
+    });
+
+    futures::stream::iter(shard_tasks)
+        .buffer_unordered(concurrency.max(1))
+        .collect::<Vec<_>>()
+        .await
+        .into_iter()
+        .try_fold((0, MatchedOutcomes::default()), |total, collected| {
+            let (txs_started, matched) = collected??;
+            anyhow::Ok((total.0 + txs_started, total.1 + matched))
+        })
+}
+
+#[cfg_attr(feature = "tracing-instrumentation", tracing::instrument(skip_all))]
+async fn collect_shard(
+    streamer_message: &readnode_primitives::indexer::StreamerMessage,
+    shard: &readnode_primitives::indexer::IndexerShard,
+    tx_collecting_storage: &std::sync::Arc<crate::storage::CacheStorage>,
+    collector_events: &crate::events::CollectorEvents,
+    indexer_config: &configuration::TxIndexerConfig,
+    block_claimed: bool,
+) -> anyhow::Result<(u64, MatchedOutcomes)> {
+    let block = readnode_primitives::BlockRecord {
+        height: streamer_message.block.header.height,
+        hash: streamer_message.block.header.hash,
+    };
+    // New transactions are collected only from the blocks claimed by this instance.
+    // Receipts and outcomes are collected from every block to finish
+    // the transactions which are already in progress
+    let txs_started = match shard.chunk.as_ref() {
+        Some(chunk) if block_claimed => {
+            extract_transactions_to_collect(
+                chunk,
+                block,
+                tx_collecting_storage,
+                collector_events,
+                indexer_config,
+            )
+            .await?
+        }
+        _ => 0,
+    };
+    let matched = process_shard(
+        tx_collecting_storage,
+        collector_events,
+        block,
+        shard,
+        indexer_config.general.fold_refund_receipts,
+    )
+    .await?;
+    Ok((txs_started, matched))
+}
+
+// Extracts all Transactions from the given chunk and pushes them to the memory storage
+// by calling the function `new_transaction_details_to_collecting_pool`.
+#[cfg_attr(feature = "tracing-instrumentation", tracing::instrument(skip_all))]
+async fn extract_transactions_to_collect(
+    chunk: &readnode_primitives::indexer::IndexerChunkView,
+    block: readnode_primitives::BlockRecord,
+    tx_collecting_storage: &std::sync::Arc<crate::storage::CacheStorage>,
+    collector_events: &crate::events::CollectorEvents,
+    indexer_config: &configuration::TxIndexerConfig,
+) -> anyhow::Result<u64> {
+    let futures = chunk.transactions.iter().map(|tx| {
+        new_transaction_details_to_collecting_pool(
+            tx,
+            block,
+            chunk.header.shard_id,
+            tx_collecting_storage,
+            collector_events,
+            indexer_config,
+        )
+    });
 
-    futures::future::join_all(futures)
+    let started = futures::future::join_all(futures)
         .await
         .into_iter()
-        .collect::<anyhow::Result<_>>()
+        .collect::<anyhow::Result<Vec<bool>>>()?;
+    Ok(started.into_iter().filter(|is_started| *is_started).count() as u64)
 }
 
 // Converts Transaction into CollectingTransactionDetails and puts it into memory storage.
 // Also, adds the Receipt produced by ExecutionOutcome of the given Transaction to the watching list
-// in memory storage
+// in memory storage. Returns false if the Transaction is not indexed
 #[cfg_attr(feature = "tracing-instrumentation", tracing::instrument(skip_all))]
 async fn new_transaction_details_to_collecting_pool(
     transaction: &IndexerTransactionWithOutcome,
     block: readnode_primitives::BlockRecord,
     shard_id: u64,
     tx_collecting_storage: &std::sync::Arc<storage::CacheStorage>,
+    collector_events: &crate::events::CollectorEvents,
     indexer_config: &configuration::TxIndexerConfig,
-) -> anyhow::Result<()> {
+) -> anyhow::Result<bool> {
     if !indexer_config.tx_should_be_indexed(transaction) {
-        return Ok(());
+        return Ok(false);
     };
     crate::metrics::TX_IN_MEMORY_CACHE.inc();
     let converted_into_receipt_id = transaction
@@ -277,7 +765,8 @@ async fn new_transaction_details_to_collecting_pool(
         .first()
         .expect("`receipt_ids` must contain one Receipt ID");
 
-    // Save the Receipt produced by the Transaction to the DB Map
+    // Save the Receipt produced by the Transaction to the DB Map.
+    // The transaction outcome is not a receipt execution outcome, so it has no position
     add_outcome_and_receipt_to_save(
         tx_collecting_storage,
         &transaction.outcome.execution_outcome.id,
@@ -286,6 +775,8 @@ async fn new_transaction_details_to_collecting_pool(
         &transaction.transaction.receiver_id,
         block,
         shard_id,
+        None,
+        None,
     )
     .await?;
 
@@ -301,86 +792,104 @@ async fn new_transaction_details_to_collecting_pool(
                     converted_into_receipt_id.to_string(),
                     transaction_key,
                 )
-                .await?
+                .await?;
+            collector_events
+                .emit(crate::events::CollectorEvent::TransactionStarted {
+                    transaction_hash: transaction.transaction.hash,
+                    block_height: block.height,
+                    shard_id,
+                })
+                .await?;
+            Ok(true)
+        }
+        Err(e) => {
+            tracing::error!(
+                target: crate::INDEXER,
+                "Failed to add TransactionDetails to memory storage\n{:#?}",
+                e
+            );
+            Ok(false)
         }
-        Err(e) => tracing::error!(
-            target: crate::INDEXER,
-            "Failed to add TransactionDetails to memory storage\n{:#?}",
-            e
-        ),
     }
-
-    Ok(())
-}
-
-#[cfg_attr(feature = "tracing-instrumentation", tracing::instrument(skip_all))]
-async fn collect_receipts_and_outcomes(
-    streamer_message: &near_indexer_primitives::StreamerMessage,
-    tx_collecting_storage: &std::sync::Arc<crate::storage::CacheStorage>,
-) -> anyhow::Result<()> {
-    let block = readnode_primitives::BlockRecord {
-        height: streamer_message.block.header.height,
-        hash: streamer_message.block.header.hash,
-    };
-    let shard_futures = streamer_message
-        .shards
-        .iter()
-        .map(|shard| process_shard(tx_collecting_storage, block, shard));
-
-    futures::future::join_all(shard_futures)
-        .await
-        .into_iter()
-        .collect::<anyhow::Result<_>>()
 }
 
 #[cfg_attr(feature = "tracing-instrumentation", tracing::instrument(skip_all))]
 async fn process_shard(
     tx_collecting_storage: &std::sync::Arc<crate::storage::CacheStorage>,
+    collector_events: &crate::events::CollectorEvents,
     block: readnode_primitives::BlockRecord,
-    shard: &near_indexer_primitives::IndexerShard,
-) -> anyhow::Result<()> {
+    shard: &readnode_primitives::indexer::IndexerShard,
+    fold_refund_receipts: bool,
+) -> anyhow::Result<MatchedOutcomes> {
+    // The outcomes are processed concurrently,
+    // so the position in the chunk is passed along to keep the on-chain order
     let process_receipt_execution_outcome_futures =
-        shard
-            .receipt_execution_outcomes
-            .iter()
-            .map(|receipt_execution_outcome| {
+        shard.receipt_execution_outcomes.iter().enumerate().map(
+            |(index_in_chunk, receipt_execution_outcome)| {
                 process_receipt_execution_outcome(
                     tx_collecting_storage,
+                    collector_events,
                     block,
                     shard.shard_id,
+                    index_in_chunk as u32,
                     receipt_execution_outcome,
+                    fold_refund_receipts,
                 )
-            });
+            },
+        );
 
     futures::future::join_all(process_receipt_execution_outcome_futures)
         .await
         .into_iter()
-        .collect::<anyhow::Result<_>>()
+        .try_fold(MatchedOutcomes::default(), |total, matched| {
+            anyhow::Ok(total + matched?)
+        })
 }
 
 #[cfg_attr(feature = "tracing-instrumentation", tracing::instrument(skip_all))]
 async fn process_receipt_execution_outcome(
     tx_collecting_storage: &std::sync::Arc<storage::CacheStorage>,
+    collector_events: &crate::events::CollectorEvents,
     block: readnode_primitives::BlockRecord,
     shard_id: u64,
-    receipt_execution_outcome: &near_indexer_primitives::IndexerExecutionOutcomeWithReceipt,
-) -> anyhow::Result<()> {
+    index_in_chunk: u32,
+    receipt_execution_outcome: &readnode_primitives::indexer::IndexerExecutionOutcomeWithReceipt,
+    fold_refund_receipts: bool,
+) -> anyhow::Result<MatchedOutcomes> {
+    let mut matched = MatchedOutcomes::default();
     if let Ok(transaction_key) = tx_collecting_storage
         .get_transaction_hash_by_receipt_id(
             &receipt_execution_outcome.receipt.receipt_id.to_string(),
         )
         .await
     {
-        add_outcome_and_receipt_to_save(
-            tx_collecting_storage,
-            &receipt_execution_outcome.execution_outcome.id,
-            &receipt_execution_outcome.receipt.receipt_id,
-            &transaction_key.transaction_hash,
-            &receipt_execution_outcome.receipt.receiver_id,
-            block,
-            shard_id,
-        )
-        .await?;
+        // The folded refund receipts are kept only in the details of the transaction,
+        // they are still matched to finish the transaction
+        let is_folded_refund = fold_refund_receipts
+            && receipt_execution_outcome.receipt.predecessor_id.as_str() == "system";
+        if is_folded_refund {
+            crate::metrics::REFUND_RECEIPTS_FOLDED.inc();
+        } else {
+            add_outcome_and_receipt_to_save(
+                tx_collecting_storage,
+                &receipt_execution_outcome.execution_outcome.id,
+                &receipt_execution_outcome.receipt.receipt_id,
+                &transaction_key.transaction_hash,
+                &receipt_execution_outcome.receipt.receiver_id,
+                block,
+                shard_id,
+                Some(index_in_chunk),
+                Some(&receipt_execution_outcome.receipt),
+            )
+            .await?;
+        }
+
+        matched.outcomes = 1;
+        matched.receipts = receipt_execution_outcome
+            .execution_outcome
+            .outcome
+            .receipt_ids
+            .len() as u64;
 
         let mut tasks = futures::stream::FuturesUnordered::new();
 
@@ -419,51 +928,75 @@ async fn process_receipt_execution_outcome(
                 );
                 err
             })?;
+
+        collector_events
+            .emit(crate::events::CollectorEvent::ReceiptMatched {
+                transaction_hash: transaction_key.transaction_hash,
+                receipt_id: receipt_execution_outcome.receipt.receipt_id,
+                block_height: block.height,
+                shard_id,
+            })
+            .await?;
     }
-    Ok(())
+    Ok(matched)
 }
 
+// Saves the batch of the finished transactions and returns the hashes of the saved ones.
+// The details storage keeps an object per transaction and has no multi-object upload,
+// so the objects are uploaded concurrently. The saved transactions are removed from
+// the cache storage with a single command, the failed ones are moved back to the save queue
 #[cfg_attr(feature = "tracing-instrumentation", tracing::instrument(skip_all))]
-async fn save_transaction_details(
+pub(crate) async fn save_transactions_details(
     tx_collecting_storage: &std::sync::Arc<storage::CacheStorage>,
     tx_details_storage: &std::sync::Arc<crate::TxDetailsStorage>,
-    tx_details: readnode_primitives::CollectingTransactionDetails,
-) {
-    let tx_key = tx_details.transaction_key();
-    match save_transaction_details_to_storage(tx_details_storage, tx_details.clone()).await {
-        Ok(_) => {
-            // We assume that the transaction is saved correctly
-            // We can remove the transaction from the cache storage
-            if let Err(err) = tx_collecting_storage
-                .remove_transaction_from_cache(tx_key.clone())
-                .await
-            {
+    transactions_details: Vec<readnode_primitives::CollectingTransactionDetails>,
+) -> Vec<readnode_primitives::indexer::CryptoHash> {
+    let save_futures = transactions_details.into_iter().map(|tx_details| async {
+        let result =
+            save_transaction_details_to_storage(tx_details_storage, tx_details.clone()).await;
+        (tx_details, result)
+    });
+    let mut saved_transaction_keys = vec![];
+    for (tx_details, result) in futures::future::join_all(save_futures).await {
+        let tx_key = tx_details.transaction_key();
+        match result {
+            Ok(_) => saved_transaction_keys.push(tx_key),
+            Err(err) => {
                 tracing::error!(
                     target: crate::INDEXER,
-                    "Failed to remove transaction from cache {}: Error {}",
+                    "Failed to save transaction {}: Error {}",
                     tx_key.transaction_hash,
                     err
                 );
+                // If the transaction wasn't saved correctly, we will move it back to the save queue
+                if let Err(err) = tx_collecting_storage.move_tx_to_save(tx_details).await {
+                    tracing::error!(
+                        target: crate::INDEXER,
+                        "Failed to move transaction to save {}: Error {}",
+                        tx_key.transaction_hash,
+                        err
+                    );
+                };
             }
         }
-        Err(err) => {
-            tracing::error!(
-                target: crate::INDEXER,
-                "Failed to save transaction {}: Error {}",
-                tx_key.transaction_hash,
-                err
-            );
-            // If the transaction wasn't saved correctly, we will move it back to the save queue
-            if let Err(err) = tx_collecting_storage.move_tx_to_save(tx_details).await {
-                tracing::error!(
-                    target: crate::INDEXER,
-                    "Failed to move transaction to save {}: Error {}",
-                    tx_key.transaction_hash,
-                    err
-                );
-            };
-        }
     }
+    // We assume that the transactions are saved correctly
+    // We can remove the transactions from the cache storage
+    if let Err(err) = tx_collecting_storage
+        .remove_transactions_from_cache(&saved_transaction_keys)
+        .await
+    {
+        tracing::error!(
+            target: crate::INDEXER,
+            "Failed to remove {} saved transactions from cache: Error {}",
+            saved_transaction_keys.len(),
+            err
+        );
+    }
+    saved_transaction_keys
+        .into_iter()
+        .map(|tx_key| tx_key.transaction_hash)
+        .collect()
 }
 
 // Save transaction detail into the storage
@@ -475,23 +1008,28 @@ async fn save_transaction_details_to_storage(
     let transaction_details = tx_details.to_final_transaction_result()?;
     let transaction_hash = transaction_details.transaction.hash.to_string();
     let tx_bytes = transaction_details.tx_serialize()?;
+    let outcome_bytes = transaction_details.outcome_serialize()?;
 
     let retry_strategy = FixedInterval::from_millis(500).take(SAVE_ATTEMPTS);
 
     let operation = || async {
-        tx_details_storage
-            .store(&transaction_hash, tx_bytes.clone())
-            .await
-            .map_err(|e| {
-                crate::metrics::TX_STORE_ERRORS_TOTAL.inc();
-                tracing::warn!(
-                    target: crate::INDEXER,
-                    "Failed to save transaction {}: Error: {}",
-                    transaction_hash,
-                    e
-                );
+        store_transaction_details(
+            tx_details_storage,
+            &transaction_hash,
+            tx_bytes.clone(),
+            outcome_bytes.clone(),
+        )
+        .await
+        .map_err(|e| {
+            crate::metrics::TX_STORE_ERRORS_TOTAL.inc();
+            tracing::warn!(
+                target: crate::INDEXER,
+                "Failed to save transaction {}: Error: {}",
+                transaction_hash,
                 e
-            })
+            );
+            e
+        })
     };
 
     Retry::spawn(retry_strategy, operation).await.map_err(|e| {
@@ -513,16 +1051,35 @@ async fn save_transaction_details_to_storage(
     Ok(())
 }
 
+// Store the full transaction details and the slim outcome used by the `tx` method.
+// The outcome is stored after the full details,
+// so the full details always exist if the outcome is found
+pub(crate) async fn store_transaction_details(
+    tx_details_storage: &std::sync::Arc<crate::TxDetailsStorage>,
+    transaction_hash: &str,
+    tx_bytes: Vec<u8>,
+    outcome_bytes: Vec<u8>,
+) -> anyhow::Result<()> {
+    tx_details_storage.store(transaction_hash, tx_bytes).await?;
+    tx_details_storage
+        .store_outcome(transaction_hash, outcome_bytes)
+        .await
+}
+
 // Save receipt_id, parent_transaction_hash, block_height and shard_id to the Db
+// along with the body of the executed receipt
 #[cfg_attr(feature = "tracing-instrumentation", tracing::instrument(skip_all))]
+#[allow(clippy::too_many_arguments)]
 async fn add_outcome_and_receipt_to_save(
     tx_collecting_storage: &std::sync::Arc<storage::CacheStorage>,
-    outcome_id: &near_indexer_primitives::CryptoHash,
-    receipt_id: &near_indexer_primitives::CryptoHash,
-    parent_tx_hash: &near_indexer_primitives::CryptoHash,
-    receiver_id: &near_indexer_primitives::types::AccountId,
+    outcome_id: &readnode_primitives::indexer::CryptoHash,
+    receipt_id: &readnode_primitives::indexer::CryptoHash,
+    parent_tx_hash: &readnode_primitives::indexer::CryptoHash,
+    receiver_id: &readnode_primitives::indexer::types::AccountId,
     block: readnode_primitives::BlockRecord,
     shard_id: u64,
+    index_in_chunk: Option<u32>,
+    receipt: Option<&readnode_primitives::indexer::views::ReceiptView>,
 ) -> anyhow::Result<()> {
     tracing::debug!(
         target: crate::INDEXER,
@@ -538,6 +1095,8 @@ async fn add_outcome_and_receipt_to_save(
             receiver_id,
             block,
             shard_id,
+            index_in_chunk,
+            receipt,
         )
         .await
         .map_err(|err| {
@@ -0,0 +1,222 @@
+use readnode_primitives::BlockDataCounts;
+
+/// Gap report of the indexed data in the requested block heights range
+#[derive(Debug, serde::Serialize)]
+struct CompletenessReport {
+    from_block_height: u64,
+    to_block_height: u64,
+    /// `true` if there are no gaps in the whole range
+    complete: bool,
+    ranges: Vec<RangeReport>,
+}
+
+#[derive(Debug, serde::Serialize)]
+struct RangeReport {
+    from_block_height: u64,
+    to_block_height: u64,
+    expected: BlockDataCounts,
+    actual: BlockDataCounts,
+    /// Blocks with missing data, the heights to backfill
+    gaps: Vec<BlockGap>,
+}
+
+#[derive(Debug, serde::Serialize)]
+struct BlockGap {
+    block_height: u64,
+    block_hash: readnode_primitives::indexer::CryptoHash,
+    expected: BlockDataCounts,
+    actual: BlockDataCounts,
+}
+
+/// Block data expected to be stored by the indexers
+struct ExpectedBlock {
+    block_height: u64,
+    block_hash: readnode_primitives::indexer::CryptoHash,
+    counts: BlockDataCounts,
+}
+
+/// Checks the completeness of the indexed data in the range `from_block_height..=to_block_height`.
+/// The expected counts of blocks, chunks, transactions and receipts are derived from the blocks
+/// streamed from the lake of `lake_config` and compared with the counts stored in the database by ranges of
+/// `range_size` heights. The gap report is written to `output` as JSON.
+/// Returns the heights of the blocks with the missing data
+pub(crate) async fn report_completeness(
+    lake_config: &configuration::LakeConfig,
+    blocks_preload_pool_size: usize,
+    db_manager: &std::sync::Arc<Box<dyn database::TxIndexerDbManager + Sync + Send + 'static>>,
+    indexer_config: &configuration::TxIndexerConfig,
+    from_block_height: u64,
+    to_block_height: u64,
+    range_size: u64,
+    output: &std::path::Path,
+) -> anyhow::Result<Vec<u64>> {
+    if from_block_height > to_block_height {
+        anyhow::bail!(
+            "Invalid range: from {} is greater than to {}",
+            from_block_height,
+            to_block_height
+        );
+    }
+    tracing::info!(
+        target: crate::INDEXER,
+        "Checking completeness of blocks {}..={}...",
+        from_block_height,
+        to_block_height
+    );
+    let (sender, mut stream) = lake_streamer::streamer(
+        lake_config,
+        from_block_height,
+        Some(blocks_preload_pool_size),
+    )
+    .await?;
+
+    let mut ranges = vec![];
+    let mut range_from = from_block_height;
+    let mut expected_blocks = vec![];
+    while range_from <= to_block_height {
+        let range_to = range_from
+            .saturating_add(range_size.max(1) - 1)
+            .min(to_block_height);
+
+        // The skipped heights are absent in the stream, the next block is already
+        // out of the range and belongs to the next one
+        while expected_blocks
+            .last()
+            .map_or(true, |block: &ExpectedBlock| block.block_height <= range_to)
+        {
+            let Some(streamer_message) = stream.recv().await else {
+                break;
+            };
+            let block_height = streamer_message.block.header.height;
+            if block_height > to_block_height {
+                break;
+            }
+            expected_blocks.push(expected_block(&streamer_message, indexer_config));
+        }
+        let next_range_blocks = match expected_blocks
+            .iter()
+            .position(|block| block.block_height > range_to)
+        {
+            Some(index) => expected_blocks.split_off(index),
+            None => vec![],
+        };
+
+        let range_report = range_report(
+            db_manager,
+            range_from,
+            range_to,
+            std::mem::replace(&mut expected_blocks, next_range_blocks),
+        )
+        .await?;
+        tracing::info!(
+            target: crate::INDEXER,
+            "Blocks {}..={} checked, {} gaps found",
+            range_from,
+            range_to,
+            range_report.gaps.len()
+        );
+        ranges.push(range_report);
+        range_from = range_to + 1;
+    }
+    // Stop the streamer, the rest of the blocks is not needed
+    drop(stream);
+    sender.abort();
+
+    let report = CompletenessReport {
+        from_block_height,
+        to_block_height,
+        complete: ranges.iter().all(|range| range.gaps.is_empty()),
+        ranges,
+    };
+    std::fs::write(output, serde_json::to_vec_pretty(&report)?)?;
+    tracing::info!(
+        target: crate::INDEXER,
+        "Completeness report is written to {}. Complete: {}",
+        output.display(),
+        report.complete
+    );
+    Ok(report
+        .ranges
+        .iter()
+        .flat_map(|range| range.gaps.iter().map(|gap| gap.block_height))
+        .collect())
+}
+
+/// Counts the data of the block the indexers are expected to store.
+/// Chunks missed in the block are still stored as duplicates of the previous chunks,
+/// so every shard of the block is expected. Receipts are expected for all the executed
+/// receipts, so they are only comparable when the tx-indexer tracks all the accounts.
+/// The refund receipts are not stored if `fold_refund_receipts` is on, so they are not expected
+fn expected_block(
+    streamer_message: &readnode_primitives::indexer::StreamerMessage,
+    indexer_config: &configuration::TxIndexerConfig,
+) -> ExpectedBlock {
+    let transactions = streamer_message
+        .shards
+        .iter()
+        .filter_map(|shard| shard.chunk.as_ref())
+        .flat_map(|chunk| chunk.transactions.iter())
+        .filter(|tx| indexer_config.tx_should_be_indexed(tx))
+        .count();
+    let receipts = if cfg!(feature = "save_outcomes_and_receipts") {
+        streamer_message
+            .shards
+            .iter()
+            .flat_map(|shard| shard.receipt_execution_outcomes.iter())
+            .filter(|outcome| {
+                !(indexer_config.general.fold_refund_receipts
+                    && outcome.receipt.predecessor_id.as_str() == "system")
+            })
+            .count()
+    } else {
+        0
+    };
+    ExpectedBlock {
+        block_height: streamer_message.block.header.height,
+        block_hash: streamer_message.block.header.hash,
+        counts: BlockDataCounts {
+            blocks: 1,
+            chunks: streamer_message.block.chunks.len() as u64,
+            transactions: transactions as u64,
+            receipts: receipts as u64,
+        },
+    }
+}
+
+async fn range_report(
+    db_manager: &std::sync::Arc<Box<dyn database::TxIndexerDbManager + Sync + Send + 'static>>,
+    from_block_height: u64,
+    to_block_height: u64,
+    expected_blocks: Vec<ExpectedBlock>,
+) -> anyhow::Result<RangeReport> {
+    let stored_counts = db_manager
+        .get_block_data_counts(from_block_height, to_block_height)
+        .await?;
+
+    let mut expected = BlockDataCounts::default();
+    let mut actual = BlockDataCounts::default();
+    let mut gaps = vec![];
+    for block in expected_blocks {
+        let block_actual = stored_counts
+            .get(&block.block_height)
+            .copied()
+            .unwrap_or_default();
+        expected += block.counts;
+        actual += block_actual;
+        if block_actual.is_missing_any_of(&block.counts) {
+            gaps.push(BlockGap {
+                block_height: block.block_height,
+                block_hash: block.block_hash,
+                expected: block.counts,
+                actual: block_actual,
+            });
+        }
+    }
+    Ok(RangeReport {
+        from_block_height,
+        to_block_height,
+        expected,
+        actual,
+        gaps,
+    })
+}
@@ -1,6 +1,6 @@
 pub use clap::{Parser, Subcommand};
-use near_indexer_primitives::types::{BlockReference, Finality};
-use near_jsonrpc_client::{methods, JsonRpcClient};
+use near_jsonrpc_client::methods;
+use readnode_primitives::indexer::types::{BlockReference, Finality};
 
 /// NEAR Indexer for Explorer
 /// Watches for stream of blocks from the chain
@@ -9,6 +9,64 @@ use near_jsonrpc_client::{methods, JsonRpcClient};
 pub(crate) struct Opts {
     #[clap(subcommand)]
     pub start_options: StartOptions,
+    /// Number of blocks preloaded from the lake ahead of the processed one.
+    /// Controls the S3 requests rate and the memory usage of the indexer
+    #[clap(long, default_value_t = 100)]
+    pub blocks_preload_pool_size: usize,
+    /// Start from the block following the start block instead of including it
+    #[clap(long)]
+    pub exclude_start_block: bool,
+    /// Fill the block protocol version and gas price of the transactions
+    /// stored without them, runs alongside the indexing
+    #[clap(long)]
+    pub backfill_block_info: bool,
+    /// Source of the blocks to index
+    #[clap(long, value_enum, default_value_t = BlocksSource::Lake)]
+    pub source: BlocksSource,
+    /// Storage the lake blocks are read from for `--source lake`: s3, gcs or filesystem.
+    /// Overrides the `source` of the `[lake_config]` config section
+    #[clap(long)]
+    pub blocks_source: Option<configuration::LakeSource>,
+    /// Home dir of the local nearcore node for `--source nearcore`. Defaults to ~/.near/
+    #[clap(long)]
+    pub home_dir: Option<std::path::PathBuf>,
+    /// Number of the shards of the block processed in parallel.
+    /// Defaults to the number of the available cores
+    #[clap(long)]
+    pub concurrency: Option<usize>,
+    /// Number of days the transactions, receipts and outcomes are kept for.
+    /// The older ones are pruned in the background, nothing is pruned by default
+    #[clap(long)]
+    pub retention_days: Option<u64>,
+    /// NEAR JSON-RPC endpoint of the indexer, e.g. the own node or the paid provider.
+    /// Overrides the `near_rpc_url` of the config
+    #[clap(long, env = "RPC_URL")]
+    pub rpc_url: Option<String>,
+}
+
+impl Opts {
+    /// NEAR JSON-RPC endpoint from the command line or the config
+    pub fn rpc_url<'a>(&'a self, indexer_config: &'a configuration::TxIndexerConfig) -> &'a str {
+        self.rpc_url
+            .as_deref()
+            .unwrap_or(&indexer_config.general.near_rpc_url)
+    }
+
+    /// Lake config with the blocks source from the command line
+    pub fn lake_config(
+        &self,
+        indexer_config: &configuration::TxIndexerConfig,
+    ) -> configuration::LakeConfig {
+        indexer_config.lake_config.with_source(self.blocks_source)
+    }
+}
+
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq)]
+pub(crate) enum BlocksSource {
+    /// NEAR Lake from the `[lake_config]` config section: S3, GCS or the local directory
+    Lake,
+    /// Local nearcore node running in the process, requires the `nearcore` feature
+    Nearcore,
 }
 
 #[allow(clippy::enum_variant_names)]
@@ -22,10 +80,94 @@ pub enum StartOptions {
         height: Option<u64>,
     },
     FromLatest,
+    FromTimestamp {
+        /// UTC date and time in RFC 3339 format, e.g. `2024-06-18T12:00:00Z`
+        utc: String,
+    },
+    /// Indexes the given block heights range and exits, e.g. to backfill the historical gaps
+    /// with several indexers covering the non-overlapping ranges. The blocks after the range
+    /// are followed to finish the transactions started in it. The interrupted indexer
+    /// resumes from the last processed block of the range
+    FromBlockRange {
+        #[clap(long)]
+        start: u64,
+        #[clap(long)]
+        end: u64,
+    },
+    /// Checks the indexed data in the given block heights range against the blocks from the lake
+    /// and writes the JSON report of the blocks with the missing data instead of indexing
+    ReportCompleteness {
+        #[clap(long)]
+        from: u64,
+        #[clap(long)]
+        to: u64,
+        /// Number of block heights checked and reported at once
+        #[clap(long, default_value_t = 1000)]
+        range_size: u64,
+        /// Path of the report file
+        #[clap(long, default_value = "completeness_report.json")]
+        output: std::path::PathBuf,
+        /// Repair the blocks with the missing data from the archival RPC after the check
+        #[clap(long)]
+        repair: bool,
+    },
+    /// Re-collects the transactions of the given blocks from the archival RPC
+    /// instead of indexing, e.g. when the missing data is detected by a read
+    RepairBlocks {
+        #[clap(required = true, num_args = 1..)]
+        heights: Vec<u64>,
+    },
+    /// Prints the blocks the indexer failed to process as JSON instead of indexing
+    ReportGaps {
+        /// Repair the missed blocks from the archival RPC after the report
+        #[clap(long)]
+        repair: bool,
+    },
+    /// Prints the stored details of the transaction as JSON instead of indexing
+    InspectTx {
+        tx_hash: readnode_primitives::indexer::CryptoHash,
+    },
+    /// Prints the stored receipt with its parent transaction hash as JSON instead of indexing
+    InspectReceipt {
+        receipt_id: readnode_primitives::indexer::CryptoHash,
+    },
+}
+
+/// Max number of the blocks after the end of the block range followed to finish
+/// the transactions started in the range, the ones still in progress are left incomplete
+pub(crate) const BLOCK_RANGE_TAIL_SIZE: u64 = 1000;
+
+impl StartOptions {
+    /// Last block height to index, the indexer exits once it is processed
+    pub(crate) fn end_block_height(&self) -> Option<u64> {
+        match self {
+            StartOptions::FromBlockRange { end, .. } => Some(*end),
+            _ => None,
+        }
+    }
+
+    /// The blocks after the end of the block range belong to the next range,
+    /// no new transactions are collected from them
+    pub(crate) fn is_after_range_end(&self, block_height: u64) -> bool {
+        self.end_block_height()
+            .is_some_and(|end_block_height| block_height > end_block_height)
+    }
+
+    /// Whether the block is indexed. The blocks after the end of the block range are followed
+    /// while the transactions started in the range are in progress
+    pub(crate) fn indexes_block(&self, block_height: u64, transactions_in_progress: usize) -> bool {
+        match self.end_block_height() {
+            Some(end_block_height) if block_height > end_block_height => {
+                transactions_in_progress > 0
+                    && block_height <= end_block_height.saturating_add(BLOCK_RANGE_TAIL_SIZE)
+            }
+            _ => true,
+        }
+    }
 }
 
 pub(crate) async fn get_start_block_height(
-    rpc_client: &JsonRpcClient,
+    rpc_client: &http_client::RpcClient,
     db_manager: &std::sync::Arc<Box<dyn database::TxIndexerDbManager + Sync + Send + 'static>>,
     start_options: &StartOptions,
     indexer_id: &str,
@@ -42,11 +184,62 @@ pub(crate) async fn get_start_block_height(
             }
         }
         StartOptions::FromLatest => final_block_height(rpc_client).await?,
+        StartOptions::FromTimestamp { utc } => {
+            let block_timestamp = parse_utc_to_nanoseconds(utc)?;
+            match db_manager
+                .get_block_height_by_timestamp(block_timestamp)
+                .await
+            {
+                Ok(block_height) => block_height,
+                Err(err) => {
+                    tracing::info!(
+                        target: crate::INDEXER,
+                        "Block height for {} is not found in the database or the indexer has not reached it yet, searching via RPC: {:?}",
+                        utc,
+                        err
+                    );
+                    block_height_by_timestamp(rpc_client, block_timestamp).await?
+                }
+            }
+        }
+        StartOptions::FromBlockRange { start, end } => {
+            if start > end {
+                anyhow::bail!("`--start` {} is greater than `--end` {}", start, end);
+            }
+            match db_manager.get_last_processed_block_height(indexer_id).await {
+                // The indexer interrupted while finishing the transactions after the range
+                // collects them again from the end of the range
+                Ok(block_height) if block_height >= *start => {
+                    let block_height = block_height.min(*end);
+                    tracing::info!(
+                        target: crate::INDEXER,
+                        "Resuming the block range {}..={} from the block {}",
+                        start,
+                        end,
+                        block_height
+                    );
+                    block_height
+                }
+                _ => *start,
+            }
+        }
+        StartOptions::ReportCompleteness { .. } => {
+            anyhow::bail!("`report-completeness` does not start the indexing")
+        }
+        StartOptions::RepairBlocks { .. } => {
+            anyhow::bail!("`repair-blocks` does not start the indexing")
+        }
+        StartOptions::ReportGaps { .. } => {
+            anyhow::bail!("`report-gaps` does not start the indexing")
+        }
+        StartOptions::InspectTx { .. } | StartOptions::InspectReceipt { .. } => {
+            anyhow::bail!("`inspect-tx` and `inspect-receipt` do not start the indexing")
+        }
     };
-    Ok(start_block_height - 100) // Start just a bit earlier to overlap indexed blocks to ensure we don't miss anything in-between
+    Ok(start_block_height.saturating_sub(100)) // Start just a bit earlier to overlap indexed blocks to ensure we don't miss anything in-between
 }
 
-pub async fn final_block_height(rpc_client: &JsonRpcClient) -> anyhow::Result<u64> {
+pub async fn final_block_height(rpc_client: &http_client::RpcClient) -> anyhow::Result<u64> {
     let request = methods::block::RpcBlockRequest {
         block_reference: BlockReference::Finality(Finality::Final),
     };
@@ -55,3 +248,107 @@ pub async fn final_block_height(rpc_client: &JsonRpcClient) -> anyhow::Result<u6
 
     Ok(latest_block.header.height)
 }
+
+fn parse_utc_to_nanoseconds(utc: &str) -> anyhow::Result<u64> {
+    let time = humantime::parse_rfc3339_weak(utc)
+        .map_err(|err| anyhow::anyhow!("Invalid UTC datetime `{}`: {}", utc, err))?;
+    let nanoseconds = time.duration_since(std::time::UNIX_EPOCH)?.as_nanos();
+    Ok(u64::try_from(nanoseconds)?)
+}
+
+// Max number of consecutive heights we expect to be skipped by the network
+const MAX_SKIPPED_BLOCKS: u64 = 100;
+
+/// Fetch block by height, if the height was skipped by the network the next existing block is returned.
+/// Returns `None` if there are no blocks between `height` and `max_height`.
+async fn block_header_from_height(
+    rpc_client: &http_client::RpcClient,
+    height: u64,
+    max_height: u64,
+) -> anyhow::Result<Option<readnode_primitives::indexer::views::BlockHeaderView>> {
+    let scan_until_height = max_height.min(height + MAX_SKIPPED_BLOCKS);
+    for block_height in height..=scan_until_height {
+        let request = methods::block::RpcBlockRequest {
+            block_reference: BlockReference::BlockId(
+                readnode_primitives::indexer::types::BlockId::Height(block_height),
+            ),
+        };
+        match rpc_client.call(request).await {
+            Ok(block) => return Ok(Some(block.header)),
+            Err(err) => match err.handler_error() {
+                Some(methods::block::RpcBlockError::UnknownBlock { .. }) => continue,
+                _ => anyhow::bail!("Failed to fetch block {}: {:?}", block_height, err),
+            },
+        }
+    }
+    if scan_until_height < max_height {
+        anyhow::bail!(
+            "No blocks found in range {}..={}, the RPC might not keep blocks for this period",
+            height,
+            scan_until_height
+        );
+    }
+    Ok(None)
+}
+
+/// Binary search of the latest block produced not later than the given timestamp (nanoseconds).
+/// Uses the network RPC, so the RPC has to keep the blocks from the searched period.
+async fn block_height_by_timestamp(
+    rpc_client: &http_client::RpcClient,
+    block_timestamp: u64,
+) -> anyhow::Result<u64> {
+    let genesis_config = rpc_client
+        .call(methods::EXPERIMENTAL_genesis_config::RpcGenesisConfigRequest)
+        .await?;
+    let mut low = genesis_config.genesis_height;
+    let mut high = final_block_height(rpc_client).await?;
+
+    while low < high {
+        let mid = low + (high - low + 1) / 2;
+        match block_header_from_height(rpc_client, mid, high).await? {
+            Some(header) if header.timestamp <= block_timestamp => low = header.height,
+            _ => high = mid - 1,
+        }
+    }
+    tracing::info!(
+        target: crate::INDEXER,
+        "Found block height {} for timestamp {}",
+        low,
+        block_timestamp
+    );
+    Ok(low)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block_range(start: u64, end: u64) -> StartOptions {
+        StartOptions::FromBlockRange { start, end }
+    }
+
+    #[test]
+    fn test_block_range_indexes_blocks_up_to_end() {
+        let start_options = block_range(100, 200);
+        assert!(start_options.indexes_block(100, 0));
+        assert!(start_options.indexes_block(200, 0));
+        assert!(!start_options.is_after_range_end(200));
+    }
+
+    #[test]
+    fn test_block_range_follows_transactions_in_progress_after_end() {
+        let start_options = block_range(100, 200);
+        assert!(start_options.is_after_range_end(201));
+        assert!(start_options.indexes_block(201, 3));
+        assert!(start_options.indexes_block(200 + BLOCK_RANGE_TAIL_SIZE, 1));
+        assert!(!start_options.indexes_block(201, 0));
+        assert!(!start_options.indexes_block(201 + BLOCK_RANGE_TAIL_SIZE, 1));
+    }
+
+    #[test]
+    fn test_other_start_options_index_every_block() {
+        let start_options = StartOptions::FromBlock { height: 100 };
+        assert!(start_options.indexes_block(u64::MAX, 0));
+        assert!(!start_options.is_after_range_end(u64::MAX));
+    }
+}
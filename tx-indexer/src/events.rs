@@ -0,0 +1,310 @@
+use readnode_primitives::indexer::CryptoHash;
+
+/// Number of the events buffered for the metrics subscriber, the events are dropped
+/// if it falls behind
+const METRICS_EVENTS_CAPACITY: usize = 10_000;
+
+/// Domain event of the transactions collecting emitted by the collector
+#[derive(Debug, Clone)]
+pub(crate) enum CollectorEvent {
+    /// The indexed transaction is found in the chunk and its collecting is started
+    TransactionStarted {
+        transaction_hash: CryptoHash,
+        block_height: u64,
+        shard_id: u64,
+    },
+    /// The receipt executed in the block is matched to the collecting transaction
+    ReceiptMatched {
+        transaction_hash: CryptoHash,
+        receipt_id: CryptoHash,
+        block_height: u64,
+        shard_id: u64,
+    },
+    /// All the receipts of the transaction are executed, the details are ready to be saved.
+    /// The transaction is kept in the cache storage until it's saved by the writer
+    TransactionFinalized {
+        transaction_details: readnode_primitives::CollectingTransactionDetails,
+    },
+}
+
+impl CollectorEvent {
+    /// Name of the event used as the metric label
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            CollectorEvent::TransactionStarted { .. } => "transaction_started",
+            CollectorEvent::ReceiptMatched { .. } => "receipt_matched",
+            CollectorEvent::TransactionFinalized { .. } => "transaction_finalized",
+        }
+    }
+
+    pub(crate) fn is_transaction_finalized(&self) -> bool {
+        matches!(self, CollectorEvent::TransactionFinalized { .. })
+    }
+}
+
+/// How the events are delivered to the subscriber which doesn't keep up with them
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Delivery {
+    /// The collector waits for the free space in the subscriber queue,
+    /// that slows down the stream consumption instead of losing the events
+    Wait,
+    /// The events are dropped when the subscriber queue is full,
+    /// the subscriber never slows down the indexing
+    DropWhenFull,
+}
+
+#[derive(Clone)]
+struct Subscriber {
+    name: &'static str,
+    delivery: Delivery,
+    accepts: fn(&CollectorEvent) -> bool,
+    sender: tokio::sync::mpsc::Sender<std::sync::Arc<CollectorEvent>>,
+}
+
+/// Channel of the collector events with the independent subscribers.
+/// Every subscriber has its own bounded queue and receives only the events it accepts,
+/// so the sinks (the transaction details writer, the metrics, etc.) are added
+/// without touching the collector
+pub(crate) struct CollectorEvents {
+    // The subscribers are replaced on the rare changes, so every event
+    // takes the snapshot of them instead of copying the list
+    subscribers: std::sync::Mutex<std::sync::Arc<Vec<Subscriber>>>,
+}
+
+impl CollectorEvents {
+    pub(crate) fn new() -> Self {
+        Self {
+            subscribers: std::sync::Mutex::new(std::sync::Arc::new(vec![])),
+        }
+    }
+
+    /// Subscribes to the events accepted by `accepts` with the queue of `capacity` events
+    pub(crate) fn subscribe(
+        &self,
+        name: &'static str,
+        capacity: usize,
+        delivery: Delivery,
+        accepts: fn(&CollectorEvent) -> bool,
+    ) -> EventReceiver {
+        let (sender, receiver) = tokio::sync::mpsc::channel(capacity.max(1));
+        let mut subscribers = self
+            .subscribers
+            .lock()
+            .expect("Collector events lock is poisoned");
+        std::sync::Arc::make_mut(&mut subscribers).push(Subscriber {
+            name,
+            delivery,
+            accepts,
+            sender,
+        });
+        EventReceiver {
+            receiver,
+            queue_size: crate::metrics::COLLECTOR_EVENTS_QUEUE_SIZE.with_label_values(&[name]),
+        }
+    }
+
+    /// Delivers the event to the subscribers accepting it.
+    /// Fails if the subscriber with the `Wait` delivery is gone, the event would be lost otherwise
+    pub(crate) async fn emit(&self, event: CollectorEvent) -> anyhow::Result<()> {
+        let subscribers = std::sync::Arc::clone(
+            &self
+                .subscribers
+                .lock()
+                .expect("Collector events lock is poisoned"),
+        );
+        let event = std::sync::Arc::new(event);
+        for subscriber in subscribers
+            .iter()
+            .filter(|subscriber| (subscriber.accepts)(&event))
+        {
+            let queue_size =
+                crate::metrics::COLLECTOR_EVENTS_QUEUE_SIZE.with_label_values(&[subscriber.name]);
+            queue_size.inc();
+            match subscriber.delivery {
+                Delivery::Wait => {
+                    if subscriber
+                        .sender
+                        .send(std::sync::Arc::clone(&event))
+                        .await
+                        .is_err()
+                    {
+                        queue_size.dec();
+                        anyhow::bail!(
+                            "Subscriber {} is gone, {} event was not delivered",
+                            subscriber.name,
+                            event.name()
+                        );
+                    }
+                }
+                Delivery::DropWhenFull => {
+                    if subscriber
+                        .sender
+                        .try_send(std::sync::Arc::clone(&event))
+                        .is_err()
+                    {
+                        queue_size.dec();
+                        crate::metrics::COLLECTOR_EVENTS_DROPPED_TOTAL
+                            .with_label_values(&[subscriber.name])
+                            .inc();
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Unsubscribes everyone, the subscribers receive the events queued so far and stop
+    pub(crate) fn close(&self) {
+        *self
+            .subscribers
+            .lock()
+            .expect("Collector events lock is poisoned") = std::sync::Arc::new(vec![]);
+    }
+}
+
+/// Queue of the events of the subscriber
+pub(crate) struct EventReceiver {
+    receiver: tokio::sync::mpsc::Receiver<std::sync::Arc<CollectorEvent>>,
+    queue_size: prometheus::IntGauge,
+}
+
+impl EventReceiver {
+    /// Receives up to `limit` queued events, returns 0 once the channel is closed
+    pub(crate) async fn recv_many(
+        &mut self,
+        events: &mut Vec<std::sync::Arc<CollectorEvent>>,
+        limit: usize,
+    ) -> usize {
+        let received = self.receiver.recv_many(events, limit).await;
+        self.queue_size.sub(received as i64);
+        received
+    }
+}
+
+/// Counts the collector events by their types. The transactions in progress are
+/// the started ones minus the finalized ones
+pub(crate) fn start_metrics_subscriber(events: &CollectorEvents) {
+    let mut receiver = events.subscribe(
+        "metrics",
+        METRICS_EVENTS_CAPACITY,
+        Delivery::DropWhenFull,
+        |_| true,
+    );
+    tokio::spawn(async move {
+        let mut batch = Vec::with_capacity(METRICS_EVENTS_CAPACITY);
+        while receiver
+            .recv_many(&mut batch, METRICS_EVENTS_CAPACITY)
+            .await
+            > 0
+        {
+            for event in batch.drain(..) {
+                crate::metrics::COLLECTOR_EVENTS_TOTAL
+                    .with_label_values(&[event.name()])
+                    .inc();
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WAIT_TIMEOUT: std::time::Duration = std::time::Duration::from_millis(100);
+
+    fn transaction_started(block_height: u64) -> CollectorEvent {
+        CollectorEvent::TransactionStarted {
+            transaction_hash: CryptoHash::default(),
+            block_height,
+            shard_id: 0,
+        }
+    }
+
+    fn block_heights(events: &[std::sync::Arc<CollectorEvent>]) -> Vec<u64> {
+        events
+            .iter()
+            .map(|event| match event.as_ref() {
+                CollectorEvent::TransactionStarted { block_height, .. } => *block_height,
+                _ => panic!("Unexpected event {}", event.name()),
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_wait_delivery_applies_backpressure() {
+        let events = CollectorEvents::new();
+        let mut receiver = events.subscribe("test_wait", 1, Delivery::Wait, |_| true);
+        events.emit(transaction_started(1)).await.unwrap();
+        // The queue is full, the event waits for the subscriber
+        assert!(
+            tokio::time::timeout(WAIT_TIMEOUT, events.emit(transaction_started(2)))
+                .await
+                .is_err()
+        );
+        let mut received = vec![];
+        assert_eq!(receiver.recv_many(&mut received, 10).await, 1);
+        events.emit(transaction_started(2)).await.unwrap();
+        assert_eq!(receiver.recv_many(&mut received, 10).await, 1);
+        assert_eq!(block_heights(&received), vec![1, 2]);
+    }
+
+    #[tokio::test]
+    async fn test_wait_delivery_fails_without_subscriber() {
+        let events = CollectorEvents::new();
+        let receiver = events.subscribe("test_wait_gone", 1, Delivery::Wait, |_| true);
+        drop(receiver);
+        assert!(events.emit(transaction_started(1)).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_drop_when_full_delivery_drops_events() {
+        let events = CollectorEvents::new();
+        let mut receiver = events.subscribe("test_drop", 2, Delivery::DropWhenFull, |_| true);
+        for block_height in 1..=5 {
+            events
+                .emit(transaction_started(block_height))
+                .await
+                .unwrap();
+        }
+        let mut received = vec![];
+        assert_eq!(receiver.recv_many(&mut received, 10).await, 2);
+        assert_eq!(block_heights(&received), vec![1, 2]);
+        // The gone subscriber doesn't fail the emit either
+        drop(receiver);
+        events.emit(transaction_started(6)).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_subscriber_skips_not_accepted_events() {
+        let events = CollectorEvents::new();
+        let mut receiver = events.subscribe(
+            "test_accepts",
+            10,
+            Delivery::Wait,
+            CollectorEvent::is_transaction_finalized,
+        );
+        events.emit(transaction_started(1)).await.unwrap();
+        events.close();
+        let mut received = vec![];
+        assert_eq!(receiver.recv_many(&mut received, 10).await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_closed_subscribers_drain_queued_events() {
+        let events = CollectorEvents::new();
+        let mut wait_receiver = events.subscribe("test_close_wait", 10, Delivery::Wait, |_| true);
+        let mut drop_receiver =
+            events.subscribe("test_close_drop", 10, Delivery::DropWhenFull, |_| true);
+        events.emit(transaction_started(1)).await.unwrap();
+        events.emit(transaction_started(2)).await.unwrap();
+        events.close();
+        // The events emitted after the close are not delivered
+        events.emit(transaction_started(3)).await.unwrap();
+        for receiver in [&mut wait_receiver, &mut drop_receiver] {
+            let mut received = vec![];
+            assert_eq!(receiver.recv_many(&mut received, 10).await, 2);
+            assert_eq!(block_heights(&received), vec![1, 2]);
+            assert_eq!(receiver.recv_many(&mut received, 10).await, 0);
+        }
+    }
+}
@@ -0,0 +1,134 @@
+use readnode_primitives::indexer::CryptoHash;
+use readnode_primitives::TransactionDetails;
+
+/// Prints the stored blob of the transaction details as it is stored (`blob`)
+/// and the outcome converted from it as it is read by the rpc-server (`outcome`).
+/// The blob is decoded with the versioned JSON path, so the blobs stored
+/// by the previous versions of the indexer are inspected as well.
+/// The blob failed to decode is printed alone with the decoding error.
+/// The receipts of the details missing in the `receipts_map` are reported to stderr
+pub(crate) async fn inspect_transaction(
+    indexer_config: &configuration::TxIndexerConfig,
+    shard_layout: readnode_primitives::indexer::ShardLayout,
+    tx_details_storage: &crate::TxDetailsStorage,
+    tx_hash: &CryptoHash,
+) -> anyhow::Result<()> {
+    let (blob, transaction_details) =
+        retrieve_transaction_details(tx_details_storage, tx_hash).await?;
+    let transaction_details = match transaction_details {
+        Ok(transaction_details) => transaction_details,
+        Err(err) => {
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&serde_json::json!({ "blob": blob }))?
+            );
+            return Err(err);
+        }
+    };
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&serde_json::json!({
+            "blob": blob,
+            "outcome": transaction_details.to_final_execution_outcome_with_receipts(),
+        }))?
+    );
+
+    let db_manager =
+        database::prepare_reader_db_manager(&indexer_config.database, shard_layout).await?;
+    let receipt_ids = transaction_details
+        .receipts
+        .iter()
+        .map(|receipt| receipt.receipt_id)
+        .collect::<Vec<_>>();
+    let stored_receipt_ids = db_manager
+        .get_receipts_by_ids(&receipt_ids, "inspect_transaction")
+        .await
+        .map_err(|err| anyhow::anyhow!("Failed to fetch receipts of {}: {:?}", tx_hash, err))?
+        .into_iter()
+        .map(|receipt_record| receipt_record.receipt_id)
+        .collect::<std::collections::HashSet<_>>();
+    // The folded refund receipts are expected to be missing
+    for receipt_id in receipt_ids
+        .iter()
+        .filter(|receipt_id| !stored_receipt_ids.contains(*receipt_id))
+    {
+        eprintln!("Receipt {} is missing in the receipts_map", receipt_id);
+    }
+    Ok(())
+}
+
+/// Prints the stored receipt record along with the receipt and its outcome
+/// from the details of the parent transaction.
+/// The details blob failed to decode is printed alone with the decoding error
+pub(crate) async fn inspect_receipt(
+    indexer_config: &configuration::TxIndexerConfig,
+    shard_layout: readnode_primitives::indexer::ShardLayout,
+    tx_details_storage: &crate::TxDetailsStorage,
+    receipt_id: &CryptoHash,
+) -> anyhow::Result<()> {
+    let db_manager =
+        database::prepare_reader_db_manager(&indexer_config.database, shard_layout).await?;
+    let receipt_record = db_manager
+        .get_receipt_by_id(*receipt_id, "inspect_receipt")
+        .await
+        .map_err(|err| anyhow::anyhow!("Failed to fetch receipt {}: {:?}", receipt_id, err))?;
+    let (blob, transaction_details) =
+        retrieve_transaction_details(tx_details_storage, &receipt_record.parent_transaction_hash)
+            .await?;
+    let transaction_details = match transaction_details {
+        Ok(transaction_details) => transaction_details,
+        Err(err) => {
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&serde_json::json!({ "blob": blob }))?
+            );
+            return Err(err);
+        }
+    };
+    let receipt = transaction_details
+        .receipts
+        .iter()
+        .find(|receipt| receipt.receipt_id == *receipt_id);
+    let receipt_outcome = transaction_details
+        .receipts_outcome
+        .iter()
+        .find(|outcome| outcome.id == *receipt_id);
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&serde_json::json!({
+            "receipt_id": receipt_record.receipt_id,
+            "parent_transaction_hash": receipt_record.parent_transaction_hash,
+            "receiver_id": receipt_record.receiver_id,
+            "block_height": receipt_record.block_height,
+            "block_hash": receipt_record.block_hash,
+            "shard_id": receipt_record.shard_id,
+            "index_in_chunk": receipt_record.index_in_chunk,
+            "receipt": receipt,
+            "outcome": receipt_outcome,
+        }))?
+    );
+    Ok(())
+}
+
+// Returns the stored blob parsed as the plain JSON along with the result of its decoding
+// to the transaction details, the blob which is not JSON at all fails the inspection
+async fn retrieve_transaction_details(
+    tx_details_storage: &crate::TxDetailsStorage,
+    tx_hash: &CryptoHash,
+) -> anyhow::Result<(serde_json::Value, anyhow::Result<TransactionDetails>)> {
+    let data = tx_details_storage
+        .retrieve(&tx_hash.to_string())
+        .await
+        .map_err(|err| anyhow::anyhow!("Failed to fetch transaction {}: {:?}", tx_hash, err))?;
+    let blob = serde_json::from_slice::<serde_json::Value>(&data).map_err(|err| {
+        anyhow::anyhow!(
+            "Blob of transaction {} ({} bytes) is not JSON: {:?}",
+            tx_hash,
+            data.len(),
+            err
+        )
+    })?;
+    let transaction_details = TransactionDetails::tx_deserialize(&data)
+        .map_err(|err| anyhow::anyhow!("Failed to decode transaction {}: {:?}", tx_hash, err));
+    Ok((blob, transaction_details))
+}
@@ -3,10 +3,35 @@ use futures::{FutureExt, StreamExt};
 
 use tx_details_storage::TxDetailsStorage;
 
+#[cfg(feature = "profiling")]
+#[global_allocator]
+static GLOBAL: profiling::Jemalloc = profiling::Jemalloc;
+
+#[cfg(feature = "profiling")]
+#[allow(non_upper_case_globals)]
+#[export_name = "malloc_conf"]
+pub static malloc_conf: &[u8] = profiling::MALLOC_CONF;
+
+mod backfill;
+mod block_stream;
 mod collector;
+mod completeness;
 mod config;
+mod events;
+mod inspect;
 mod metrics;
+#[cfg(feature = "tx_mirroring")]
+mod mirroring;
+#[cfg(feature = "nearcore")]
+mod nearcore;
+mod protocol_versions;
+mod receipts_pruning;
+mod repair;
+mod retention;
+mod sharding;
+mod shutdown;
 mod storage;
+mod writer;
 
 #[macro_use]
 extern crate lazy_static;
@@ -27,30 +52,146 @@ async fn main() -> anyhow::Result<()> {
 
     let opts = config::Opts::parse();
 
-    let rpc_client =
-        near_jsonrpc_client::JsonRpcClient::connect(&indexer_config.general.near_rpc_url);
+    let http_client_factory = http_client::HttpClientFactory::new(&indexer_config.http_client)?;
+    let rpc_client = http_client_factory
+        .rpc_client("near_rpc", opts.rpc_url(&indexer_config))
+        .api_key(indexer_config.general.near_rpc_api_key.as_deref())?;
 
     tracing::info!(target: INDEXER, "Fetch protocol config...");
     let protocol_config_view = rpc_client
         .call(
             near_jsonrpc_client::methods::EXPERIMENTAL_protocol_config::RpcProtocolConfigRequest {
                 block_reference:
-                    near_indexer_primitives::near_primitives::types::BlockReference::Finality(
-                        near_indexer_primitives::near_primitives::types::Finality::Final,
+                    readnode_primitives::indexer::near_primitives::types::BlockReference::Finality(
+                        readnode_primitives::indexer::near_primitives::types::Finality::Final,
                     ),
             },
         )
         .await?;
 
+    match &opts.start_options {
+        config::StartOptions::InspectTx { tx_hash } => {
+            let tx_details_storage = TxDetailsStorage::new(
+                indexer_config.tx_details_storage.storage_client().await,
+                indexer_config.tx_details_storage.bucket_name.clone(),
+            );
+            return inspect::inspect_transaction(
+                &indexer_config,
+                protocol_config_view.shard_layout,
+                &tx_details_storage,
+                tx_hash,
+            )
+            .await;
+        }
+        config::StartOptions::InspectReceipt { receipt_id } => {
+            let tx_details_storage = TxDetailsStorage::new(
+                indexer_config.tx_details_storage.storage_client().await,
+                indexer_config.tx_details_storage.bucket_name.clone(),
+            );
+            return inspect::inspect_receipt(
+                &indexer_config,
+                protocol_config_view.shard_layout,
+                &tx_details_storage,
+                receipt_id,
+            )
+            .await;
+        }
+        _ => {}
+    }
+
     tracing::info!(target: INDEXER, "Connecting to db...");
     let db_manager: std::sync::Arc<Box<dyn database::TxIndexerDbManager + Sync + Send + 'static>> =
-        std::sync::Arc::new(Box::new(
-            database::prepare_db_manager::<database::PostgresDBManager>(
+        std::sync::Arc::new(
+            database::prepare_tx_indexer_db_manager(
                 &indexer_config.database,
                 protocol_config_view.shard_layout.clone(),
             )
             .await?,
+        );
+
+    tracing::info!(target: INDEXER, "Check the network...");
+    let status = rpc_client
+        .call(near_jsonrpc_client::methods::status::RpcStatusRequest)
+        .await?;
+    let network = readnode_primitives::NetworkIdentity {
+        chain_id: status.chain_id,
+        genesis_hash: status.genesis_hash,
+    };
+    indexer_config
+        .general
+        .chain_id
+        .ensure_matches(&network.chain_id)?;
+    network.ensure_same_network(&db_manager.save_network_identity(&network).await?)?;
+
+    let repair_block_heights = match &opts.start_options {
+        config::StartOptions::ReportCompleteness {
+            from,
+            to,
+            range_size,
+            output,
+            repair,
+        } => {
+            let gap_block_heights = completeness::report_completeness(
+                &opts.lake_config(&indexer_config),
+                opts.blocks_preload_pool_size,
+                &db_manager,
+                &indexer_config,
+                *from,
+                *to,
+                *range_size,
+                output,
+            )
+            .await?;
+            if !*repair || gap_block_heights.is_empty() {
+                return Ok(());
+            }
+            Some((gap_block_heights, "completeness_report"))
+        }
+        config::StartOptions::RepairBlocks { heights } => Some((heights.clone(), "manual")),
+        config::StartOptions::ReportGaps { repair } => {
+            let missed_blocks = db_manager
+                .get_missed_blocks(&indexer_config.general.indexer_id)
+                .await?;
+            println!("{}", serde_json::to_string_pretty(&missed_blocks)?);
+            if !*repair || missed_blocks.is_empty() {
+                return Ok(());
+            }
+            Some((
+                missed_blocks
+                    .iter()
+                    .map(|missed_block| missed_block.block_height)
+                    .collect(),
+                "missed_blocks",
+            ))
+        }
+        _ => None,
+    };
+    if let Some((block_heights, reason)) = repair_block_heights {
+        let archival_rpc_client = http_client_factory
+            .rpc_client(
+                "archival_rpc",
+                indexer_config
+                    .general
+                    .near_archival_rpc_url
+                    .as_deref()
+                    .unwrap_or(opts.rpc_url(&indexer_config)),
+            )
+            .api_key(indexer_config.general.near_rpc_api_key.as_deref())?;
+        let tx_details_storage = std::sync::Arc::new(TxDetailsStorage::new(
+            indexer_config.tx_details_storage.storage_client().await,
+            indexer_config.tx_details_storage.bucket_name.clone(),
         ));
+        return repair::repair_blocks(
+            &archival_rpc_client,
+            &db_manager,
+            &tx_details_storage,
+            &protocol_config_view.shard_layout,
+            &indexer_config,
+            &block_heights,
+            reason,
+        )
+        .await;
+    }
 
     let start_block_height = config::get_start_block_height(
         &rpc_client,
@@ -60,17 +201,53 @@ async fn main() -> anyhow::Result<()> {
     )
     .await?;
 
-    tracing::info!(target: INDEXER, "Generating LakeConfig...");
-    let lake_config = indexer_config
-        .lake_config
-        .lake_config(start_block_height)
-        .await?;
+    if opts.backfill_block_info {
+        let backfill_rpc_client = rpc_client.clone();
+        let backfill_db_manager = std::sync::Arc::clone(&db_manager);
+        tokio::spawn(async move {
+            if let Err(err) =
+                backfill::backfill_transactions_block_info(backfill_rpc_client, backfill_db_manager)
+                    .await
+            {
+                tracing::error!(
+                    target: INDEXER,
+                    "Transactions block info backfill failed: {:?}",
+                    err
+                );
+            }
+        });
+    }
+
+    let start_block_height = if opts.exclude_start_block {
+        start_block_height + 1
+    } else {
+        start_block_height
+    };
+
+    // Runs the periodic maintenance jobs until the indexer stops
+    let scheduler = database::scheduler::Scheduler::new();
+
+    let block_sharding = if let Some(group_id) = &indexer_config.general.group_id {
+        tracing::info!(target: INDEXER, "Joining tx-indexer group {}...", group_id);
+        Some(
+            sharding::BlockSharding::start(
+                std::sync::Arc::clone(&db_manager),
+                &scheduler,
+                group_id.clone(),
+                indexer_config.general.indexer_id.clone(),
+            )
+            .await?,
+        )
+    } else {
+        None
+    };
 
     tracing::info!(target: INDEXER, "Creating cache storage...");
     let tx_collecting_storage = std::sync::Arc::new(
         storage::CacheStorage::init_with_restore(
             indexer_config.general.redis_url.to_string(),
             protocol_config_view.shard_layout,
+            block_sharding.as_deref(),
         )
         .await?,
     );
@@ -81,13 +258,79 @@ async fn main() -> anyhow::Result<()> {
         indexer_config.tx_details_storage.bucket_name.clone(),
     ));
 
+    if let Some(retention_days) = opts.retention_days {
+        retention::start(
+            std::sync::Arc::clone(&db_manager),
+            std::sync::Arc::clone(&tx_details_storage),
+            &scheduler,
+            retention_days,
+        );
+    }
+
+    if !indexer_config.rightsizing.tracked_accounts.is_empty() {
+        receipts_pruning::start(
+            std::sync::Arc::clone(&db_manager),
+            &scheduler,
+            indexer_config.general.indexer_id.clone(),
+            indexer_config.rightsizing.tracked_accounts.clone(),
+        );
+    }
+
+    // The sinks of the collected data subscribe to the collector events
+    let collector_events = std::sync::Arc::new(events::CollectorEvents::new());
+    let write_queue = writer::WriteAheadQueue::start(
+        indexer_config.general.write_queue_size,
+        indexer_config.general.write_workers,
+        &collector_events,
+        std::sync::Arc::clone(&tx_collecting_storage),
+        tx_details_storage,
+        std::sync::Arc::clone(&db_manager),
+    );
+    events::start_metrics_subscriber(&collector_events);
+
+    #[cfg(feature = "tx_mirroring")]
+    let tx_mirror = indexer_config
+        .tx_mirroring
+        .clone()
+        .map(|config| mirroring::TxMirror::start(&http_client_factory, config))
+        .transpose()?;
+
     tracing::info!(target: INDEXER, "Instantiating the stream...",);
-    let (sender, stream) = near_lake_framework::streamer(lake_config);
+    let (sender, stream) = match opts.source {
+        config::BlocksSource::Lake => {
+            tracing::info!(target: INDEXER, "Generating LakeConfig...");
+            lake_streamer::streamer(
+                &opts.lake_config(&indexer_config),
+                start_block_height,
+                Some(opts.blocks_preload_pool_size),
+            )
+            .await?
+        }
+        #[cfg(feature = "nearcore")]
+        config::BlocksSource::Nearcore => {
+            let home_dir = opts
+                .home_dir
+                .clone()
+                .unwrap_or_else(near_indexer::get_default_home);
+            tracing::info!(target: INDEXER, "Starting nearcore node in {}...", home_dir.display());
+            nearcore::streamer(home_dir, start_block_height)
+        }
+        #[cfg(not(feature = "nearcore"))]
+        config::BlocksSource::Nearcore => {
+            anyhow::bail!(
+                "`--source nearcore` requires tx-indexer built with the `nearcore` feature"
+            )
+        }
+    };
 
-    // Initiate metrics http server
+    // Initiate metrics http server, it serves the stream of the indexed blocks as well
+    let block_stream = std::sync::Arc::new(block_stream::BlockStream::new());
     tokio::spawn(
-        metrics::init_server(indexer_config.general.metrics_server_port)
-            .expect("Failed to start metrics server"),
+        metrics::init_server(
+            indexer_config.general.metrics_server_port,
+            std::sync::Arc::clone(&block_stream),
+        )
+        .expect("Failed to start metrics server"),
     );
 
     let stats = std::sync::Arc::new(tokio::sync::RwLock::new(metrics::Stats::new()));
@@ -96,26 +339,115 @@ async fn main() -> anyhow::Result<()> {
         rpc_client.clone(),
     ));
 
+    let protocol_versions = protocol_versions::EpochProtocolVersions::new(rpc_client.clone());
+
+    let concurrency = opts.concurrency.unwrap_or_else(|| {
+        std::thread::available_parallelism().map_or(1, std::num::NonZeroUsize::get)
+    });
     tracing::info!(target: INDEXER, "Starting tx indexer...",);
-    let mut handlers = tokio_stream::wrappers::ReceiverStream::new(stream)
+    let start_options = &opts.start_options;
+    let end_block_reached = std::cell::Cell::new(false);
+    // The blocks stream ends on the shutdown signal or after the end of the block range
+    // once the transactions started in the range are finished, the block in progress is finished.
+    // The blocks are processed one by one, so the next block is checked after the previous one
+    let mut blocks = tokio_stream::wrappers::ReceiverStream::new(stream)
+        .take_while(|streamer_message| {
+            let block_height = streamer_message.block.header.height;
+            let end_block_reached = &end_block_reached;
+            let tx_collecting_storage = &tx_collecting_storage;
+            async move {
+                let transactions_in_progress = if start_options.is_after_range_end(block_height) {
+                    tx_collecting_storage.transactions_in_progress().await
+                } else {
+                    0
+                };
+                let indexes_block =
+                    start_options.indexes_block(block_height, transactions_in_progress);
+                end_block_reached.set(!indexes_block);
+                indexes_block
+            }
+        })
+        .take_until(Box::pin(shutdown::shutdown_signal()));
+    let mut handlers = (&mut blocks)
         .map(|streamer_message| {
+            let block_height = streamer_message.block.header.height;
             handle_streamer_message(
                 streamer_message,
                 &db_manager,
                 &tx_collecting_storage,
-                &tx_details_storage,
+                &collector_events,
+                &block_sharding,
+                start_options.is_after_range_end(block_height),
+                #[cfg(feature = "tx_mirroring")]
+                &tx_mirror,
+                &block_stream,
                 indexer_config.clone(),
+                &protocol_versions,
                 std::sync::Arc::clone(&stats),
+                concurrency,
             )
+            .map(move |result| (block_height, result))
         })
+        // The blocks are processed one by one to match the receipts with the transactions
+        // in the on-chain order, the shards of the block are processed in parallel
         .buffer_unordered(1usize);
 
-    while let Some(_handle_message) = handlers.next().await {
+    while let Some((block_height, _handle_message)) = handlers.next().await {
         if let Err(err) = _handle_message {
             tracing::warn!(target: INDEXER, "{:?}", err);
+            // The indexing goes on, the missed block is reported by `report-gaps` to be repaired
+            if let Err(err) = db_manager
+                .save_missed_block(
+                    &indexer_config.general.indexer_id,
+                    block_height,
+                    &format!("{:#}", err),
+                )
+                .await
+            {
+                tracing::error!(
+                    target: INDEXER,
+                    "#{} failed to save the missed block: {:?}",
+                    block_height,
+                    err
+                );
+            }
         }
     }
-    drop(handlers); // close the channel so the sender will stop
+    drop(handlers);
+    let shutdown_requested = blocks.is_stopped();
+    drop(blocks); // close the channel so the sender will stop
+
+    shutdown::drain(
+        &db_manager,
+        &tx_collecting_storage,
+        &collector_events,
+        write_queue,
+        &indexer_config,
+    )
+    .await?;
+    if shutdown_requested {
+        sender.abort();
+        tracing::info!(target: INDEXER, "Tx indexer stopped");
+        return Ok(());
+    }
+    if end_block_reached.get() {
+        sender.abort();
+        let transactions_in_progress = tx_collecting_storage.transactions_in_progress().await;
+        if transactions_in_progress > 0 {
+            tracing::warn!(
+                target: INDEXER,
+                "{} transactions started in the block range are not finished within {} blocks after it",
+                transactions_in_progress,
+                config::BLOCK_RANGE_TAIL_SIZE
+            );
+        }
+        tracing::info!(
+            target: INDEXER,
+            "Block range is indexed up to the block {}, tx indexer stopped",
+            start_options.end_block_height().unwrap_or_default()
+        );
+        return Ok(());
+    }
 
     // propagate errors from the sender
     match sender.await {
@@ -127,15 +459,30 @@ async fn main() -> anyhow::Result<()> {
 
 #[cfg_attr(feature = "tracing-instrumentation", tracing::instrument(skip_all))]
 async fn handle_streamer_message(
-    streamer_message: near_indexer_primitives::StreamerMessage,
+    streamer_message: readnode_primitives::indexer::StreamerMessage,
     db_manager: &std::sync::Arc<Box<dyn database::TxIndexerDbManager + Sync + Send + 'static>>,
     tx_collecting_storage: &std::sync::Arc<storage::CacheStorage>,
-    tx_details_storage: &std::sync::Arc<TxDetailsStorage>,
+    collector_events: &std::sync::Arc<events::CollectorEvents>,
+    block_sharding: &Option<std::sync::Arc<sharding::BlockSharding>>,
+    after_range_end: bool,
+    #[cfg(feature = "tx_mirroring")] tx_mirror: &Option<mirroring::TxMirror>,
+    block_stream: &block_stream::BlockStream,
     indexer_config: configuration::TxIndexerConfig,
+    protocol_versions: &protocol_versions::EpochProtocolVersions,
     stats: std::sync::Arc<tokio::sync::RwLock<metrics::Stats>>,
+    concurrency: usize,
 ) -> anyhow::Result<u64> {
+    // The block is shared by the tasks collecting its shards
+    let streamer_message = std::sync::Arc::new(streamer_message);
     let block_height = streamer_message.block.header.height;
-    tracing::debug!(target: INDEXER, "Block {}", block_height);
+
+    // The blocks after the end of the block range are processed as claimed by the indexer
+    // of the next range, only the transactions in progress are finished
+    let block_claimed = match block_sharding {
+        _ if after_range_end => false,
+        Some(sharding) => sharding.claims(block_height).await?,
+        None => true,
+    };
 
     stats
         .write()
@@ -143,29 +490,72 @@ async fn handle_streamer_message(
         .block_heights_processing
         .insert(block_height);
 
+    // Transactions are mirrored only from the claimed blocks to not send them twice
+    #[cfg(feature = "tx_mirroring")]
+    if let Some(tx_mirror) = tx_mirror.as_ref().filter(|_| block_claimed) {
+        tx_mirror.mirror_transactions(&streamer_message);
+    }
+
     let tx_future = collector::index_transactions(
         &streamer_message,
         db_manager,
         tx_collecting_storage,
-        tx_details_storage,
+        collector_events,
         &indexer_config,
+        protocol_versions,
+        block_claimed,
+        concurrency,
     );
 
-    let update_meta_future = db_manager.update_meta(
-        &indexer_config.general.indexer_id,
-        streamer_message.block.header.height,
-    );
-
-    match futures::future::join_all([tx_future.boxed(), update_meta_future.boxed()])
-        .await
-        .into_iter()
-        .collect::<Result<Vec<_>, _>>()
-    {
-        Ok(_) => tracing::debug!(
-            target: INDEXER,
-            "#{} collecting transaction details successful",
-            streamer_message.block.header.height,
-        ),
+    // The block is recorded in the meta only after its data is collected,
+    // so the interrupted indexer restarts from the block which is not fully collected
+    let tx_result = match tx_future.await {
+        Ok(stats) => db_manager
+            .update_meta(
+                &indexer_config.general.indexer_id,
+                streamer_message.block.header.height,
+                streamer_message.block.header.timestamp,
+            )
+            .await
+            .map(|_| stats),
+        Err(err) => Err(err),
+    };
+    match tx_result {
+        Ok(stats) => {
+            tracing::debug!(
+                target: INDEXER,
+                block_height = stats.block_height,
+                txs_started = stats.txs_started,
+                receipts_matched = stats.receipts_matched,
+                outcomes_matched = stats.outcomes_matched,
+                txs_finalized = stats.txs_finalized,
+                collect_duration_ms = stats.collect_duration_ms,
+                save_tx_details_duration_ms = stats.save_tx_details_duration_ms,
+                save_outcomes_and_receipts_duration_ms =
+                    stats.save_outcomes_and_receipts_duration_ms,
+                save_transactions_by_public_key_duration_ms =
+                    stats.save_transactions_by_public_key_duration_ms,
+                "#{} block processed",
+                stats.block_height,
+            );
+            // In the group every instance streams the blocks it claims
+            if block_claimed {
+                block_stream.publish(&streamer_message);
+            }
+            if indexer_config.general.save_block_processing_stats {
+                if let Err(err) = db_manager
+                    .save_block_processing_stats(&indexer_config.general.indexer_id, &stats)
+                    .await
+                {
+                    tracing::warn!(
+                        target: INDEXER,
+                        "#{} failed to save block processing stats: {:?}",
+                        stats.block_height,
+                        err
+                    );
+                }
+            }
+        }
         Err(e) => tracing::error!(
             target: INDEXER,
             "#{} an error occurred during collecting transaction details\n{:#?}",
@@ -178,6 +568,7 @@ async fn handle_streamer_message(
     // Prometheus Gauge Metric type do not support u64
     // https://github.com/tikv/rust-prometheus/issues/470
     metrics::LATEST_BLOCK_HEIGHT.set(i64::try_from(streamer_message.block.header.height)?);
+    metrics::observe_block_indexing_latency(streamer_message.block.header.timestamp);
 
     let mut stats_lock = stats.write().await;
     stats_lock.block_heights_processing.remove(&block_height);
@@ -1,6 +1,5 @@
 use actix_web::{get, App, HttpServer, Responder};
-use near_jsonrpc_client::JsonRpcClient;
-use prometheus::{Encoder, IntCounter, IntGauge, Opts};
+use prometheus::{Encoder, Histogram, IntCounter, IntCounterVec, IntGauge, IntGaugeVec, Opts};
 
 type Result<T, E> = std::result::Result<T, E>;
 
@@ -11,6 +10,17 @@ fn try_create_int_counter(name: &str, help: &str) -> Result<IntCounter, promethe
     Ok(counter)
 }
 
+fn try_create_int_counter_vec(
+    name: &str,
+    help: &str,
+    label_names: &[&str],
+) -> Result<IntCounterVec, prometheus::Error> {
+    let opts = Opts::new(name, help);
+    let counter = IntCounterVec::new(opts, label_names)?;
+    prometheus::register(Box::new(counter.clone()))?;
+    Ok(counter)
+}
+
 fn try_create_int_gauge(name: &str, help: &str) -> Result<IntGauge, prometheus::Error> {
     let opts = Opts::new(name, help);
     let gauge = IntGauge::with_opts(opts)?;
@@ -18,6 +28,17 @@ fn try_create_int_gauge(name: &str, help: &str) -> Result<IntGauge, prometheus::
     Ok(gauge)
 }
 
+fn try_create_int_gauge_vec(
+    name: &str,
+    help: &str,
+    label_names: &[&str],
+) -> Result<IntGaugeVec, prometheus::Error> {
+    let opts = Opts::new(name, help);
+    let gauge = IntGaugeVec::new(opts, label_names)?;
+    prometheus::register(Box::new(gauge.clone()))?;
+    Ok(gauge)
+}
+
 lazy_static! {
     pub(crate) static ref BLOCK_PROCESSED_TOTAL: IntCounter = try_create_int_counter(
         "total_blocks_processed",
@@ -29,6 +50,12 @@ lazy_static! {
         "Last seen block height by indexer"
     )
     .unwrap();
+    pub(crate) static ref BLOCK_INDEXING_LATENCY_SECONDS: Histogram = database::metrics::register_histogram(
+        "block_indexing_latency_seconds",
+        "Seconds between the block timestamp and the moment the indexer finished writing the block",
+        &database::metrics::BLOCK_LATENCY_BUCKETS
+    )
+    .unwrap();
     pub(crate) static ref TX_IN_MEMORY_CACHE: IntGauge = try_create_int_gauge(
         "tx_in_memory_cache",
         "Number of transactions in memory cache"
@@ -44,11 +71,91 @@ lazy_static! {
         "Total number of errors while storing transactions"
     )
     .unwrap();
+    // The failed attempts are counted by the `database_write_errors_counter` of the database crate
+    pub(crate) static ref DB_SAVE_FAILURES_TOTAL: IntCounterVec = try_create_int_counter_vec(
+        "total_db_save_failures",
+        "Total number of the database writes failed after all the retries by db_method_name",
+        &["db_method_name"]
+    )
+    .unwrap();
     pub(crate) static ref TX_IN_BLOCK_TOTAL: IntGauge = try_create_int_gauge(
         "tx_in_block_total",
         "Number of transactions in a block"
     )
     .unwrap();
+    pub(crate) static ref COLLECTOR_EVENTS_QUEUE_SIZE: IntGaugeVec = try_create_int_gauge_vec(
+        "collector_events_queue_size",
+        "Number of the collector events waiting in the queue of the subscriber, the `writer` one is the write-ahead queue",
+        &["subscriber"]
+    )
+    .unwrap();
+    pub(crate) static ref COLLECTOR_EVENTS_DROPPED_TOTAL: IntCounterVec = try_create_int_counter_vec(
+        "collector_events_dropped_total",
+        "Total number of the collector events dropped because the subscriber didn't keep up with them",
+        &["subscriber"]
+    )
+    .unwrap();
+    pub(crate) static ref COLLECTOR_EVENTS_TOTAL: IntCounterVec = try_create_int_counter_vec(
+        "collector_events_total",
+        "Total number of the collector events by event: transaction_started, receipt_matched, transaction_finalized",
+        &["event"]
+    )
+    .unwrap();
+    pub(crate) static ref BLOCK_STREAM_SUBSCRIBERS: IntGauge = try_create_int_gauge(
+        "block_stream_subscribers",
+        "Number of the auxiliary indexers subscribed to the stream of the indexed blocks"
+    )
+    .unwrap();
+    pub(crate) static ref GROUP_MEMBERS_TOTAL: IntGauge = try_create_int_gauge(
+        "tx_indexer_group_members_total",
+        "Number of alive tx-indexer instances in the group sharing the blocks"
+    )
+    .unwrap();
+    pub(crate) static ref PRUNED_TX_DETAILS_TOTAL: IntCounter = try_create_int_counter(
+        "total_pruned_tx_details",
+        "Total number of transaction details deleted from the storage by the retention policy"
+    )
+    .unwrap();
+    pub(crate) static ref REFUND_RECEIPTS_FOLDED: IntCounter = try_create_int_counter(
+        "total_refund_receipts_folded",
+        "Total number of refund receipts kept in the transaction details only"
+    )
+    .unwrap();
+    pub(crate) static ref SKIPPED_CHUNKS_TOTAL: IntCounter = try_create_int_counter(
+        "total_skipped_chunks",
+        "Total number of chunks of the block headers missing in the indexed shards"
+    )
+    .unwrap();
+    #[cfg(feature = "tx_mirroring")]
+    pub(crate) static ref MIRRORED_TX_TOTAL: IntCounter = try_create_int_counter(
+        "total_mirrored_tx",
+        "Total number of transactions forwarded to the sandbox node"
+    )
+    .unwrap();
+    #[cfg(feature = "tx_mirroring")]
+    pub(crate) static ref MIRRORED_TX_SKIPPED_TOTAL: IntCounter = try_create_int_counter(
+        "total_mirrored_tx_skipped",
+        "Total number of transactions to the mirrored contracts which can't be re-signed"
+    )
+    .unwrap();
+    #[cfg(feature = "tx_mirroring")]
+    pub(crate) static ref MIRRORED_TX_FAILED_TOTAL: IntCounter = try_create_int_counter(
+        "total_mirrored_tx_failed",
+        "Total number of transactions failed to be forwarded to the sandbox node"
+    )
+    .unwrap();
+    #[cfg(feature = "tx_mirroring")]
+    pub(crate) static ref MIRRORED_TX_DROPPED_TOTAL: IntCounter = try_create_int_counter(
+        "total_mirrored_tx_dropped",
+        "Total number of transactions dropped because the mirroring queue is full"
+    )
+    .unwrap();
+}
+
+/// Records the latency of the indexed block from its timestamp.
+/// The block with the timestamp ahead of the local clock is recorded with the zero latency
+pub(crate) fn observe_block_indexing_latency(block_timestamp: u64) {
+    BLOCK_INDEXING_LATENCY_SECONDS.observe(database::metrics::seconds_since_block(block_timestamp));
 }
 
 #[get("/metrics")]
@@ -73,16 +180,30 @@ async fn get_metrics() -> impl Responder {
     }
 }
 
-pub(crate) fn init_server(port: u16) -> anyhow::Result<actix_web::dev::Server> {
+pub(crate) fn init_server(
+    port: u16,
+    block_stream: std::sync::Arc<crate::block_stream::BlockStream>,
+) -> anyhow::Result<actix_web::dev::Server> {
     tracing::info!(
         target: crate::INDEXER,
         "Starting metrics server on http://0.0.0.0:{port}/metrics"
     );
 
-    Ok(HttpServer::new(|| App::new().service(get_metrics))
-        .bind(("0.0.0.0", port))?
-        .disable_signals()
-        .run())
+    Ok(HttpServer::new(move || {
+        App::new()
+            .app_data(actix_web::web::Data::from(std::sync::Arc::clone(
+                &block_stream,
+            )))
+            .service(get_metrics)
+            .service(crate::block_stream::stream_blocks)
+            .configure(|_config| {
+                #[cfg(feature = "profiling")]
+                profiling::configure(_config);
+            })
+    })
+    .bind(("0.0.0.0", port))?
+    .disable_signals()
+    .run())
 }
 
 #[derive(Debug, Clone)]
@@ -104,7 +225,7 @@ impl Stats {
 
 pub async fn state_logger(
     stats: std::sync::Arc<tokio::sync::RwLock<Stats>>,
-    rpc_client: JsonRpcClient,
+    rpc_client: http_client::RpcClient,
 ) {
     let interval_secs = 10;
     let mut prev_blocks_processed_count: u64 = 0;
@@ -0,0 +1,211 @@
+use std::str::FromStr;
+
+use near_jsonrpc_client::methods;
+use readnode_primitives::indexer::near_primitives;
+use readnode_primitives::indexer::types::AccountId;
+use readnode_primitives::indexer::views::{ActionView, SignedTransactionView};
+
+/// Sink forwarding the indexed transactions to the selected contracts to a near-sandbox
+/// or localnet node to replay the real traffic in the staging environments.
+/// The transactions are re-signed with the dev keys of the signers and sent one by one
+/// in the background, so the indexing is never slowed down by the sandbox node
+pub(crate) struct TxMirror {
+    config: std::sync::Arc<configuration::TxMirroringConfig>,
+    sender: tokio::sync::mpsc::Sender<SignedTransactionView>,
+}
+
+impl TxMirror {
+    pub(crate) fn start(
+        http_client_factory: &http_client::HttpClientFactory,
+        config: configuration::TxMirroringConfig,
+    ) -> anyhow::Result<Self> {
+        let config = std::sync::Arc::new(config);
+        let signers = Signers::new(&config)?;
+        let rpc_client = http_client_factory.rpc_client("sandbox_rpc", &config.rpc_url);
+        let (sender, receiver) = tokio::sync::mpsc::channel(config.queue_size);
+        tracing::info!(
+            target: crate::INDEXER,
+            "Mirroring transactions to {:?} to {}",
+            config.contracts,
+            config.rpc_url
+        );
+        tokio::spawn(forward_transactions(rpc_client, signers, receiver));
+        Ok(Self { config, sender })
+    }
+
+    /// Queues the transactions of the block to the mirrored contracts.
+    /// The transactions are dropped if the sandbox node doesn't keep up with the queue
+    pub(crate) fn mirror_transactions(
+        &self,
+        streamer_message: &readnode_primitives::indexer::StreamerMessage,
+    ) {
+        let transactions = streamer_message
+            .shards
+            .iter()
+            .filter_map(|shard| shard.chunk.as_ref())
+            .flat_map(|chunk| chunk.transactions.iter())
+            .map(|tx| &tx.transaction)
+            .filter(|tx| self.config.is_mirrored_contract(&tx.receiver_id));
+        for transaction in transactions {
+            if self.sender.try_send(transaction.clone()).is_err() {
+                crate::metrics::MIRRORED_TX_DROPPED_TOTAL.inc();
+            }
+        }
+    }
+}
+
+/// Dev signers of the mirrored transactions with the next nonces of their access keys
+struct Signers {
+    signers: std::collections::HashMap<AccountId, near_crypto::InMemorySigner>,
+    default_secret_key: Option<near_crypto::SecretKey>,
+    // Last nonces sent by the signers. The access keys on the sandbox node lag behind them
+    // until the asynchronously sent transactions are executed
+    nonces: std::collections::HashMap<AccountId, u64>,
+}
+
+impl Signers {
+    fn new(config: &configuration::TxMirroringConfig) -> anyhow::Result<Self> {
+        let signers = config
+            .dev_keys
+            .iter()
+            .map(|(account_id, secret_key)| {
+                Ok((
+                    account_id.clone(),
+                    near_crypto::InMemorySigner::from_secret_key(
+                        account_id.clone(),
+                        parse_secret_key(account_id.as_str(), secret_key)?,
+                    ),
+                ))
+            })
+            .collect::<anyhow::Result<_>>()?;
+        let default_secret_key = config
+            .default_dev_key
+            .as_ref()
+            .map(|secret_key| parse_secret_key("default_dev_key", secret_key))
+            .transpose()?;
+        Ok(Self {
+            signers,
+            default_secret_key,
+            nonces: std::collections::HashMap::new(),
+        })
+    }
+
+    fn signer(&mut self, signer_id: &AccountId) -> Option<&near_crypto::InMemorySigner> {
+        if !self.signers.contains_key(signer_id) {
+            let secret_key = self.default_secret_key.clone()?;
+            self.signers.insert(
+                signer_id.clone(),
+                near_crypto::InMemorySigner::from_secret_key(signer_id.clone(), secret_key),
+            );
+        }
+        self.signers.get(signer_id)
+    }
+}
+
+fn parse_secret_key(name: &str, secret_key: &str) -> anyhow::Result<near_crypto::SecretKey> {
+    near_crypto::SecretKey::from_str(secret_key)
+        .map_err(|err| anyhow::anyhow!("Invalid tx_mirroring dev key of {}: {}", name, err))
+}
+
+async fn forward_transactions(
+    rpc_client: http_client::RpcClient,
+    mut signers: Signers,
+    mut receiver: tokio::sync::mpsc::Receiver<SignedTransactionView>,
+) {
+    while let Some(transaction) = receiver.recv().await {
+        let signer_id = transaction.signer_id.clone();
+        let tx_hash = transaction.hash;
+        match forward_transaction(&rpc_client, &mut signers, transaction).await {
+            Ok(Some(mirrored_tx_hash)) => {
+                crate::metrics::MIRRORED_TX_TOTAL.inc();
+                tracing::debug!(
+                    target: crate::INDEXER,
+                    "Transaction {} is mirrored as {}",
+                    tx_hash,
+                    mirrored_tx_hash
+                );
+            }
+            Ok(None) => {
+                crate::metrics::MIRRORED_TX_SKIPPED_TOTAL.inc();
+            }
+            Err(err) => {
+                crate::metrics::MIRRORED_TX_FAILED_TOTAL.inc();
+                // The nonce is fetched from the sandbox node again for the next transaction
+                signers.nonces.remove(&signer_id);
+                tracing::warn!(
+                    target: crate::INDEXER,
+                    "Failed to mirror transaction {}: {:?}",
+                    tx_hash,
+                    err
+                );
+            }
+        }
+    }
+}
+
+/// Re-signs the transaction with the dev key of the signer and sends it to the sandbox node.
+/// Returns `None` if the transaction can't be mirrored: the signer has no dev key
+/// or the transaction contains the delegate actions signed by the mainnet keys
+async fn forward_transaction(
+    rpc_client: &http_client::RpcClient,
+    signers: &mut Signers,
+    transaction: SignedTransactionView,
+) -> anyhow::Result<Option<readnode_primitives::indexer::CryptoHash>> {
+    if transaction
+        .actions
+        .iter()
+        .any(|action| matches!(action, ActionView::Delegate { .. }))
+    {
+        return Ok(None);
+    }
+    let Some(signer) = signers.signer(&transaction.signer_id).cloned() else {
+        return Ok(None);
+    };
+
+    let access_key = rpc_client
+        .call(methods::query::RpcQueryRequest {
+            block_reference: near_primitives::types::BlockReference::Finality(
+                near_primitives::types::Finality::Final,
+            ),
+            request: near_primitives::views::QueryRequest::ViewAccessKey {
+                account_id: signer.account_id.clone(),
+                public_key: signer.public_key.clone(),
+            },
+        })
+        .await?;
+    let methods::query::QueryResponseKind::AccessKey(access_key_view) = access_key.kind else {
+        anyhow::bail!("Unexpected response to the access key query");
+    };
+    let nonce = signers
+        .nonces
+        .get(&signer.account_id)
+        .copied()
+        .unwrap_or(access_key_view.nonce)
+        .max(access_key_view.nonce)
+        + 1;
+
+    let actions = transaction
+        .actions
+        .into_iter()
+        .map(near_primitives::transaction::Action::try_from)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| anyhow::anyhow!("Failed to convert actions: {}", err))?;
+    let signed_transaction = near_primitives::transaction::Transaction::V0(
+        near_primitives::transaction::TransactionV0 {
+            signer_id: signer.account_id.clone(),
+            public_key: signer.public_key.clone(),
+            nonce,
+            receiver_id: transaction.receiver_id,
+            block_hash: access_key.block_hash,
+            actions,
+        },
+    )
+    .sign(&signer);
+    let mirrored_tx_hash = signed_transaction.get_hash();
+
+    rpc_client
+        .call(methods::broadcast_tx_async::RpcBroadcastTxAsyncRequest { signed_transaction })
+        .await?;
+    signers.nonces.insert(signer.account_id.clone(), nonce);
+    Ok(Some(mirrored_tx_hash))
+}
@@ -0,0 +1,36 @@
+/// Number of the blocks buffered between the nearcore indexer and the tx-indexer
+const STREAM_CAPACITY: usize = 100;
+
+/// Streams the blocks from the local nearcore node instead of the NEAR Lake S3 bucket.
+/// The node runs inside the process on its own actix system, the blocks are streamed
+/// from `start_block_height` while the node is syncing. The node must keep the blocks
+/// from the start height (an archival node to index the old blocks) and track all the shards.
+/// Returns the handle of the node and the stream in the same shape as the lake streamer
+pub(crate) fn streamer(
+    home_dir: std::path::PathBuf,
+    start_block_height: u64,
+) -> (
+    tokio::task::JoinHandle<anyhow::Result<()>>,
+    tokio::sync::mpsc::Receiver<readnode_primitives::indexer::StreamerMessage>,
+) {
+    let (sender, receiver) = tokio::sync::mpsc::channel(STREAM_CAPACITY);
+    let handle = tokio::task::spawn_blocking(move || {
+        actix::System::new().block_on(async move {
+            let indexer = near_indexer::Indexer::new(near_indexer::IndexerConfig {
+                home_dir,
+                sync_mode: near_indexer::SyncModeEnum::BlockHeight(start_block_height),
+                await_for_node_synced: near_indexer::AwaitForNodeSyncedEnum::StreamWhileSyncing,
+                validate_genesis: false,
+            })?;
+            let mut stream = indexer.streamer();
+            while let Some(streamer_message) = stream.recv().await {
+                if sender.send(streamer_message).await.is_err() {
+                    // The tx-indexer stopped consuming the stream
+                    break;
+                }
+            }
+            anyhow::Ok(())
+        })
+    });
+    (handle, receiver)
+}
@@ -0,0 +1,57 @@
+use near_jsonrpc_client::methods;
+use readnode_primitives::indexer::types::{BlockId, BlockReference, ProtocolVersion};
+use readnode_primitives::indexer::{near_primitives, views};
+
+// Number of the epochs kept, the blocks are indexed around the current epoch
+const EPOCHS_CACHE_SIZE: usize = 8;
+
+/// Protocol versions the epochs run with, fetched from the RPC once per epoch.
+/// `latest_protocol_version` of the block header is the newest version supported by
+/// the block producer, it is ahead of the version of the epoch around the protocol upgrades
+pub(crate) struct EpochProtocolVersions {
+    rpc_client: http_client::RpcClient,
+    // The lock is held while the version is fetched, so the epoch is fetched once
+    versions: tokio::sync::Mutex<
+        std::collections::HashMap<near_primitives::hash::CryptoHash, ProtocolVersion>,
+    >,
+}
+
+impl EpochProtocolVersions {
+    pub(crate) fn new(rpc_client: http_client::RpcClient) -> Self {
+        Self {
+            rpc_client,
+            versions: tokio::sync::Mutex::new(std::collections::HashMap::new()),
+        }
+    }
+
+    /// Returns the protocol version of the epoch of the block
+    pub(crate) async fn get(
+        &self,
+        block_header: &views::BlockHeaderView,
+    ) -> anyhow::Result<ProtocolVersion> {
+        let mut versions = self.versions.lock().await;
+        if let Some(protocol_version) = versions.get(&block_header.epoch_id) {
+            return Ok(*protocol_version);
+        }
+        let protocol_config = self
+            .rpc_client
+            .call(
+                methods::EXPERIMENTAL_protocol_config::RpcProtocolConfigRequest {
+                    block_reference: BlockReference::BlockId(BlockId::Hash(block_header.hash)),
+                },
+            )
+            .await
+            .map_err(|err| {
+                anyhow::anyhow!(
+                    "Failed to fetch protocol config of block {} from RPC: {:?}",
+                    block_header.height,
+                    err
+                )
+            })?;
+        if versions.len() >= EPOCHS_CACHE_SIZE {
+            versions.clear();
+        }
+        versions.insert(block_header.epoch_id, protocol_config.protocol_version);
+        Ok(protocol_config.protocol_version)
+    }
+}
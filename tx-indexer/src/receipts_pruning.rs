@@ -0,0 +1,91 @@
+// How often the receipts and outcomes of the filtered out transactions are pruned
+const PRUNING_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+const PRUNING_JITTER: std::time::Duration = std::time::Duration::from_secs(10);
+// Number of the blocks behind the last processed one the pruning waits for,
+// the blocks are processed concurrently and the lower ones can be still in progress
+const FINALIZATION_DELAY_BLOCKS: u64 = 1000;
+// Number of the blocks looked back on the first run, covers the blocks indexed before the restart
+const INITIAL_LOOKBACK_BLOCKS: u64 = 10_000;
+// Number of the blocks pruned at once
+const PRUNING_STEP_BLOCKS: u64 = 100;
+
+/// Schedules the job deleting the `receipts_map` and `outcomes_map` rows of the transactions
+/// filtered out by the `tracked_accounts`. Such rows are left by the cross-shard receipt flows
+/// and by the accounts removed from the tracked ones, the pruning keeps the storage
+/// proportional to the tracked accounts
+pub(crate) fn start(
+    db_manager: std::sync::Arc<Box<dyn database::TxIndexerDbManager + Sync + Send + 'static>>,
+    scheduler: &database::scheduler::Scheduler,
+    indexer_id: String,
+    tracked_accounts: Vec<readnode_primitives::indexer::types::AccountId>,
+) {
+    tracing::info!(
+        target: crate::INDEXER,
+        "Pruning the receipts of the transactions filtered out by the tracked accounts"
+    );
+    // The blocks below the cursor are pruned already
+    let cursor = std::sync::Arc::new(std::sync::atomic::AtomicU64::new(0));
+    scheduler.schedule(
+        "tx_indexer_receipts_pruning",
+        database::scheduler::Schedule::every(PRUNING_INTERVAL).with_jitter(PRUNING_JITTER),
+        move || {
+            let db_manager = std::sync::Arc::clone(&db_manager);
+            let cursor = std::sync::Arc::clone(&cursor);
+            let indexer_id = indexer_id.clone();
+            let tracked_accounts = tracked_accounts.clone();
+            async move { prune_filtered(&db_manager, &cursor, &indexer_id, &tracked_accounts).await }
+        },
+    );
+}
+
+async fn prune_filtered(
+    db_manager: &std::sync::Arc<Box<dyn database::TxIndexerDbManager + Sync + Send + 'static>>,
+    cursor: &std::sync::atomic::AtomicU64,
+    indexer_id: &str,
+    tracked_accounts: &[readnode_primitives::indexer::types::AccountId],
+) -> anyhow::Result<()> {
+    let last_processed_block_height = db_manager
+        .get_last_processed_block_height(indexer_id)
+        .await?;
+    let mut finalized_block_height =
+        last_processed_block_height.saturating_sub(FINALIZATION_DELAY_BLOCKS);
+    // The missed blocks are repaired later along with their transactions,
+    // so the receipts of the transactions are kept until then
+    if let Some(lowest_missed_block_height) = db_manager
+        .get_missed_blocks(indexer_id)
+        .await?
+        .iter()
+        .map(|missed_block| missed_block.block_height)
+        .min()
+    {
+        finalized_block_height = finalized_block_height.min(lowest_missed_block_height);
+    }
+
+    let mut from_block_height = cursor.load(std::sync::atomic::Ordering::Acquire);
+    if from_block_height == 0 {
+        from_block_height = finalized_block_height.saturating_sub(INITIAL_LOOKBACK_BLOCKS);
+    }
+    let mut pruned_rows = 0;
+    while from_block_height < finalized_block_height {
+        let to_block_height = finalized_block_height.min(from_block_height + PRUNING_STEP_BLOCKS);
+        pruned_rows += db_manager
+            .prune_receipts_of_filtered_transactions(
+                from_block_height,
+                to_block_height,
+                tracked_accounts,
+            )
+            .await?;
+        from_block_height = to_block_height;
+        cursor.store(from_block_height, std::sync::atomic::Ordering::Release);
+    }
+
+    if pruned_rows > 0 {
+        tracing::info!(
+            target: crate::INDEXER,
+            "Pruned {} receipts and outcomes of the filtered out transactions before block {}",
+            pruned_rows,
+            from_block_height
+        );
+    }
+    Ok(())
+}
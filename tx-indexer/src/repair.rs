@@ -0,0 +1,308 @@
+use near_jsonrpc_client::methods;
+use near_jsonrpc_client::methods::EXPERIMENTAL_tx_status::TransactionInfo;
+use readnode_primitives::indexer::types::{BlockId, BlockReference};
+use readnode_primitives::indexer::views::FinalExecutionOutcomeViewEnum::FinalExecutionOutcomeWithReceipt;
+use readnode_primitives::indexer::{near_primitives, views};
+
+/// Re-collects the transactions of the blocks with the missing data from the archival RPC.
+/// Every transaction of the block passing the `tracked_accounts` filter is stored again
+/// with its details and receipts, the already stored records are kept as is.
+/// Every repair is recorded in the `block_repairs` table with the given reason,
+/// the repaired blocks are removed from the missed blocks of the indexer
+pub(crate) async fn repair_blocks(
+    rpc_client: &http_client::RpcClient,
+    db_manager: &std::sync::Arc<Box<dyn database::TxIndexerDbManager + Sync + Send + 'static>>,
+    tx_details_storage: &std::sync::Arc<crate::TxDetailsStorage>,
+    shard_layout: &near_primitives::shard_layout::ShardLayout,
+    indexer_config: &configuration::TxIndexerConfig,
+    block_heights: &[u64],
+    reason: &str,
+) -> anyhow::Result<()> {
+    tracing::info!(
+        target: crate::INDEXER,
+        "Repairing {} blocks from the archival RPC...",
+        block_heights.len()
+    );
+    let protocol_versions =
+        crate::protocol_versions::EpochProtocolVersions::new(rpc_client.clone());
+    let mut failed_repairs = 0;
+    let mut repaired_block_heights = vec![];
+    for &block_height in block_heights {
+        let block = match rpc_client
+            .call(methods::block::RpcBlockRequest {
+                block_reference: BlockReference::BlockId(BlockId::Height(block_height)),
+            })
+            .await
+        {
+            Ok(block) => block,
+            Err(err) => {
+                // Nothing is touched yet, so the repair is not recorded
+                tracing::warn!(
+                    target: crate::INDEXER,
+                    "Failed to fetch block {} to repair: {:?}",
+                    block_height,
+                    err
+                );
+                failed_repairs += 1;
+                continue;
+            }
+        };
+        let mut repair = readnode_primitives::BlockRepair {
+            block_height,
+            block_hash: block.header.hash,
+            reason: reason.to_string(),
+            transactions: 0,
+            receipts: 0,
+            error: None,
+        };
+        let mut repairer = BlockRepairer {
+            rpc_client,
+            protocol_versions: &protocol_versions,
+            db_manager,
+            tx_details_storage,
+            shard_layout,
+            indexer_config,
+            repair: &mut repair,
+            #[cfg(feature = "save_outcomes_and_receipts")]
+            block_heights_by_hash: std::collections::HashMap::new(),
+        };
+        if let Err(err) = repairer.repair_block(&block).await {
+            tracing::warn!(
+                target: crate::INDEXER,
+                "Failed to repair block {}: {:?}",
+                block_height,
+                err
+            );
+            failed_repairs += 1;
+            repair.error = Some(format!("{:#}", err));
+        } else {
+            tracing::info!(
+                target: crate::INDEXER,
+                "Block {} repaired: {} transactions, {} receipts",
+                block_height,
+                repair.transactions,
+                repair.receipts
+            );
+            repaired_block_heights.push(block_height);
+        }
+        db_manager
+            .save_block_repair(&indexer_config.general.indexer_id, &repair)
+            .await?;
+    }
+    if !repaired_block_heights.is_empty() {
+        db_manager
+            .delete_missed_blocks(&indexer_config.general.indexer_id, &repaired_block_heights)
+            .await?;
+    }
+    if failed_repairs > 0 {
+        anyhow::bail!(
+            "{} of {} blocks failed to be repaired",
+            failed_repairs,
+            block_heights.len()
+        );
+    }
+    Ok(())
+}
+
+struct BlockRepairer<'a> {
+    rpc_client: &'a http_client::RpcClient,
+    protocol_versions: &'a crate::protocol_versions::EpochProtocolVersions,
+    db_manager: &'a std::sync::Arc<Box<dyn database::TxIndexerDbManager + Sync + Send + 'static>>,
+    tx_details_storage: &'a std::sync::Arc<crate::TxDetailsStorage>,
+    shard_layout: &'a near_primitives::shard_layout::ShardLayout,
+    indexer_config: &'a configuration::TxIndexerConfig,
+    repair: &'a mut readnode_primitives::BlockRepair,
+    // Receipts of the transaction are executed in the following blocks,
+    // their heights are fetched from the RPC once per block
+    #[cfg(feature = "save_outcomes_and_receipts")]
+    block_heights_by_hash: std::collections::HashMap<near_primitives::hash::CryptoHash, u64>,
+}
+
+impl BlockRepairer<'_> {
+    async fn repair_block(&mut self, block: &views::BlockView) -> anyhow::Result<()> {
+        for chunk_header in &block.chunks {
+            // The chunk missed in the block is the previous chunk of the shard,
+            // its transactions belong to the block the chunk is included in
+            if chunk_header.height_included != block.header.height {
+                continue;
+            }
+            let chunk = self
+                .rpc_client
+                .call(methods::chunk::RpcChunkRequest {
+                    chunk_reference: methods::chunk::ChunkReference::ChunkHash {
+                        chunk_id: chunk_header.chunk_hash,
+                    },
+                })
+                .await
+                .map_err(|err| {
+                    anyhow::anyhow!(
+                        "Failed to fetch chunk {}: {:?}",
+                        chunk_header.chunk_hash,
+                        err
+                    )
+                })?;
+            let transactions = chunk
+                .transactions
+                .into_iter()
+                .filter(|tx| self.indexer_config.signed_tx_should_be_indexed(tx))
+                .collect::<Vec<_>>();
+            if transactions.is_empty() {
+                continue;
+            }
+
+            let protocol_version = self.protocol_versions.get(&block.header).await?;
+            self.db_manager
+                .save_transactions_by_public_key(
+                    transactions
+                        .iter()
+                        .map(|tx| {
+                            readnode_primitives::TransactionByPublicKeyRecord::new(
+                                tx,
+                                &block.header,
+                                chunk.header.shard_id,
+                                protocol_version,
+                            )
+                        })
+                        .collect(),
+                )
+                .await?;
+            for transaction in transactions {
+                self.repair_transaction(transaction).await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn repair_transaction(
+        &mut self,
+        transaction: views::SignedTransactionView,
+    ) -> anyhow::Result<()> {
+        let tx_status = self
+            .rpc_client
+            .call(
+                methods::EXPERIMENTAL_tx_status::RpcTransactionStatusRequest {
+                    transaction_info: TransactionInfo::TransactionId {
+                        tx_hash: transaction.hash,
+                        sender_account_id: transaction.signer_id.clone(),
+                    },
+                    wait_until: views::TxExecutionStatus::Final,
+                },
+            )
+            .await
+            .map_err(|err| {
+                anyhow::anyhow!(
+                    "Failed to fetch transaction {} status: {:?}",
+                    transaction.hash,
+                    err
+                )
+            })?;
+        let tx_details = match tx_status.final_execution_outcome {
+            Some(FinalExecutionOutcomeWithReceipt(outcome)) => {
+                readnode_primitives::TransactionDetails::from(outcome)
+            }
+            _ => anyhow::bail!("Transaction {} is not finished", transaction.hash),
+        };
+
+        #[cfg(feature = "save_outcomes_and_receipts")]
+        self.repair_receipts(&tx_details).await?;
+
+        crate::collector::store_transaction_details(
+            self.tx_details_storage,
+            &transaction.hash.to_string(),
+            tx_details.tx_serialize()?,
+            tx_details.outcome_serialize()?,
+        )
+        .await?;
+        self.repair.transactions += 1;
+        Ok(())
+    }
+
+    // Stores the receipts and outcomes of the transaction by the blocks they are executed in
+    #[cfg(feature = "save_outcomes_and_receipts")]
+    async fn repair_receipts(
+        &mut self,
+        tx_details: &readnode_primitives::TransactionDetails,
+    ) -> anyhow::Result<()> {
+        let mut receipts_by_shard: std::collections::HashMap<
+            database::primitives::ShardId,
+            (
+                Vec<readnode_primitives::ReceiptRecord>,
+                Vec<readnode_primitives::OutcomeRecord>,
+            ),
+        > = std::collections::HashMap::new();
+        // The refund receipts are folded into the transaction details the same way
+        // as by the collector if it is configured
+        let folded_refund_receipt_ids = tx_details
+            .receipts
+            .iter()
+            .filter(|receipt| {
+                self.indexer_config.general.fold_refund_receipts
+                    && receipt.predecessor_id.as_str() == "system"
+            })
+            .map(|receipt| receipt.receipt_id)
+            .collect::<std::collections::HashSet<_>>();
+        for outcome in &tx_details.receipts_outcome {
+            if folded_refund_receipt_ids.contains(&outcome.id) {
+                continue;
+            }
+            let receiver_id = &outcome.outcome.executor_id;
+            let shard_id = readnode_primitives::indexer::account_id_to_shard_id(
+                receiver_id,
+                self.shard_layout,
+            );
+            let block_height = self.block_height_by_hash(outcome.block_hash).await?;
+            let (receipts, outcomes) = receipts_by_shard.entry(shard_id).or_default();
+            receipts.push(readnode_primitives::ReceiptRecord {
+                receipt_id: outcome.id,
+                parent_transaction_hash: tx_details.transaction.hash,
+                receiver_id: receiver_id.clone(),
+                block_height,
+                block_hash: outcome.block_hash,
+                shard_id,
+                // The RPC doesn't expose the position of the outcome in the chunk
+                index_in_chunk: None,
+                receipt: tx_details
+                    .receipts
+                    .iter()
+                    .find(|receipt| receipt.receipt_id == outcome.id)
+                    .cloned(),
+            });
+            outcomes.push(readnode_primitives::OutcomeRecord {
+                outcome_id: outcome.id,
+                parent_transaction_hash: tx_details.transaction.hash,
+                receiver_id: receiver_id.clone(),
+                block_height,
+                block_hash: outcome.block_hash,
+                shard_id,
+                index_in_chunk: None,
+            });
+        }
+        for (shard_id, (receipts, outcomes)) in receipts_by_shard {
+            self.repair.receipts += receipts.len() as u64;
+            self.db_manager
+                .save_outcome_and_receipt(shard_id, receipts, outcomes)
+                .await?;
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "save_outcomes_and_receipts")]
+    async fn block_height_by_hash(
+        &mut self,
+        block_hash: near_primitives::hash::CryptoHash,
+    ) -> anyhow::Result<u64> {
+        if let Some(block_height) = self.block_heights_by_hash.get(&block_hash) {
+            return Ok(*block_height);
+        }
+        let block = self
+            .rpc_client
+            .call(methods::block::RpcBlockRequest {
+                block_reference: BlockReference::BlockId(BlockId::Hash(block_hash)),
+            })
+            .await
+            .map_err(|err| anyhow::anyhow!("Failed to fetch block {}: {:?}", block_hash, err))?;
+        self.block_heights_by_hash
+            .insert(block_hash, block.header.height);
+        Ok(block.header.height)
+    }
+}
@@ -0,0 +1,109 @@
+use futures::StreamExt;
+
+// How often the expired transactions, receipts and outcomes are pruned
+const PRUNING_INTERVAL: std::time::Duration = std::time::Duration::from_secs(600);
+const PRUNING_JITTER: std::time::Duration = std::time::Duration::from_secs(60);
+// Number of the transactions (and the receipts of every shard) deleted at once
+const PRUNING_BATCH_SIZE: u64 = 1000;
+// Number of the transaction details deleted from the storage concurrently
+const STORAGE_DELETE_CONCURRENCY: usize = 16;
+
+/// Schedules the job deleting the data of the blocks older than the retention period:
+/// the transaction details from the storage, the `transactions_by_public_key` rows
+/// and the `receipts_map` and `outcomes_map` rows of all the shards.
+/// The transactions are enumerated by the block height of their outcomes
+pub(crate) fn start(
+    db_manager: std::sync::Arc<Box<dyn database::TxIndexerDbManager + Sync + Send + 'static>>,
+    tx_details_storage: std::sync::Arc<crate::TxDetailsStorage>,
+    scheduler: &database::scheduler::Scheduler,
+    retention_days: u64,
+) {
+    tracing::info!(
+        target: crate::INDEXER,
+        "Pruning the transactions older than {} days",
+        retention_days
+    );
+    scheduler.schedule(
+        "tx_indexer_retention_pruning",
+        database::scheduler::Schedule::every(PRUNING_INTERVAL).with_jitter(PRUNING_JITTER),
+        move || {
+            let db_manager = std::sync::Arc::clone(&db_manager);
+            let tx_details_storage = std::sync::Arc::clone(&tx_details_storage);
+            async move { prune_expired(&db_manager, &tx_details_storage, retention_days).await }
+        },
+    );
+}
+
+async fn prune_expired(
+    db_manager: &std::sync::Arc<Box<dyn database::TxIndexerDbManager + Sync + Send + 'static>>,
+    tx_details_storage: &std::sync::Arc<crate::TxDetailsStorage>,
+    retention_days: u64,
+) -> anyhow::Result<()> {
+    let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH)?;
+    let expired_before = now.saturating_sub(std::time::Duration::from_secs(
+        retention_days.saturating_mul(24 * 60 * 60),
+    ));
+    // The database younger than the retention period has no blocks produced before it
+    let before_block_height = match db_manager
+        .get_block_height_by_timestamp(expired_before.as_nanos() as u64)
+        .await
+    {
+        Ok(block_height) => block_height,
+        Err(err) => {
+            tracing::debug!(
+                target: crate::INDEXER,
+                "No blocks older than the retention period to prune: {:?}",
+                err
+            );
+            return Ok(());
+        }
+    };
+
+    // The details are deleted from the storage before the rows,
+    // so the failed run leaves the transactions to prune them by the next one
+    let mut pruned_transactions = 0;
+    loop {
+        let transaction_hashes = db_manager
+            .get_transactions_to_prune(before_block_height, PRUNING_BATCH_SIZE)
+            .await?;
+        if transaction_hashes.is_empty() {
+            break;
+        }
+        futures::stream::iter(transaction_hashes.iter())
+            .map(|transaction_hash| async move {
+                let key = transaction_hash.to_string();
+                tx_details_storage.delete(&key).await?;
+                tx_details_storage.delete_outcome(&key).await
+            })
+            .buffer_unordered(STORAGE_DELETE_CONCURRENCY)
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .collect::<anyhow::Result<()>>()?;
+        db_manager
+            .prune_transactions(before_block_height, &transaction_hashes)
+            .await?;
+        crate::metrics::PRUNED_TX_DETAILS_TOTAL.inc_by(transaction_hashes.len() as u64);
+        pruned_transactions += transaction_hashes.len();
+    }
+
+    let mut pruned_receipts_and_outcomes = 0;
+    loop {
+        let pruned_rows = db_manager
+            .prune_receipts_and_outcomes(before_block_height, PRUNING_BATCH_SIZE)
+            .await?;
+        if pruned_rows == 0 {
+            break;
+        }
+        pruned_receipts_and_outcomes += pruned_rows;
+    }
+
+    tracing::info!(
+        target: crate::INDEXER,
+        "Pruned {} transactions and {} receipts and outcomes before block {}",
+        pruned_transactions,
+        pruned_receipts_and_outcomes,
+        before_block_height
+    );
+    Ok(())
+}
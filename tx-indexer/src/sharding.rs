@@ -0,0 +1,222 @@
+// How often the instance updates its heartbeat and refreshes the group assignments
+const HEARTBEAT_INTERVAL: std::time::Duration = std::time::Duration::from_secs(10);
+// Members without a heartbeat during this time are excluded from the next assignment
+// and their blocks are taken over by the alive members
+const MEMBER_TIMEOUT_SECS: u64 = 30;
+// Number of the blocks the instance leases ahead of the handled one. The next assignment
+// starts after the leases of all the members, so it takes effect within this number of blocks
+const LEASE_BLOCKS: u64 = 100;
+
+/// Splits the blocks between the tx-indexer instances of the same group.
+/// The group agrees on the assignments in the database: the members sorted by indexer_id
+/// share the blocks starting from `from_block_height` and the instance at position `slot`
+/// claims the blocks where `block_height % N == slot`. Before deciding the claim of the block
+/// the instance leases the heights up to it, and the new assignment starts only after
+/// the leases of all the members, so every block is claimed by exactly one member
+/// even when the membership changes.
+/// Every instance still handles all the blocks to follow the receipts of the transactions
+/// in progress, but starts collecting new transactions only from the claimed blocks,
+/// so the same transaction is never collected and written by two instances.
+pub(crate) struct BlockSharding {
+    db_manager: std::sync::Arc<Box<dyn database::TxIndexerDbManager + Sync + Send + 'static>>,
+    group_id: String,
+    indexer_id: String,
+    state: std::sync::RwLock<ShardingState>,
+    // The lease is extended by one task at a time
+    sync_lock: tokio::sync::Mutex<()>,
+}
+
+#[derive(Default)]
+struct ShardingState {
+    lease_block_height: u64,
+    assignments: Vec<readnode_primitives::GroupAssignment>,
+}
+
+impl BlockSharding {
+    /// Registers the instance in the group and schedules the heartbeat job
+    pub(crate) async fn start(
+        db_manager: std::sync::Arc<Box<dyn database::TxIndexerDbManager + Sync + Send + 'static>>,
+        scheduler: &database::scheduler::Scheduler,
+        group_id: String,
+        indexer_id: String,
+    ) -> anyhow::Result<std::sync::Arc<Self>> {
+        let sharding = std::sync::Arc::new(Self {
+            db_manager,
+            group_id,
+            indexer_id,
+            state: std::sync::RwLock::new(ShardingState::default()),
+            sync_lock: tokio::sync::Mutex::new(()),
+        });
+        sharding.heartbeat().await?;
+
+        let heartbeat_sharding = std::sync::Arc::clone(&sharding);
+        scheduler.schedule(
+            "tx_indexer_group_heartbeat",
+            database::scheduler::Schedule::every(HEARTBEAT_INTERVAL),
+            move || {
+                let sharding = std::sync::Arc::clone(&heartbeat_sharding);
+                async move {
+                    sharding.heartbeat().await.map_err(|err| {
+                        anyhow::anyhow!(
+                            "Failed to update heartbeat of {} in group {}: {:?}",
+                            sharding.indexer_id,
+                            sharding.group_id,
+                            err
+                        )
+                    })
+                }
+            },
+        );
+        Ok(sharding)
+    }
+
+    /// Updates the heartbeat keeping the lease and refreshes the assignments.
+    /// If they can't be refreshed the known ones are kept, they are valid up to the lease
+    async fn heartbeat(&self) -> anyhow::Result<()> {
+        let _sync_guard = self.sync_lock.lock().await;
+        self.sync(self.lease_block_height()).await
+    }
+
+    /// Updates the heartbeat with the lease up to `lease_block_height`
+    /// and refreshes the assignments of the group
+    async fn sync(&self, lease_block_height: u64) -> anyhow::Result<()> {
+        let assignments = self
+            .db_manager
+            .sync_group_assignments(
+                &self.group_id,
+                &self.indexer_id,
+                lease_block_height,
+                MEMBER_TIMEOUT_SECS,
+            )
+            .await?;
+
+        let mut state = self
+            .state
+            .write()
+            .map_err(|err| anyhow::anyhow!("Failed to lock sharding state: {}", err))?;
+        if let Some(latest) = assignments.last() {
+            if state.assignments.last() != Some(latest) {
+                tracing::info!(
+                    target: crate::INDEXER,
+                    "Group {} assignment from block {}: instance {} claims slot {:?} of {} members {:?}",
+                    self.group_id,
+                    latest.from_block_height,
+                    self.indexer_id,
+                    latest.members.iter().position(|member| member == &self.indexer_id),
+                    latest.members.len(),
+                    latest.members,
+                );
+            }
+            crate::metrics::GROUP_MEMBERS_TOTAL.set(latest.members.len() as i64);
+        }
+        state.lease_block_height = state.lease_block_height.max(lease_block_height);
+        state.assignments = assignments;
+        Ok(())
+    }
+
+    fn lease_block_height(&self) -> u64 {
+        self.state
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .lease_block_height
+    }
+
+    /// Checks if the block is claimed by this instance. The block past the lease is leased first,
+    /// so the claim is decided with all the assignments starting up to the block
+    pub(crate) async fn claims(&self, block_height: u64) -> anyhow::Result<bool> {
+        if block_height > self.lease_block_height() {
+            let _sync_guard = self.sync_lock.lock().await;
+            // The lease could be extended while waiting for the lock
+            if block_height > self.lease_block_height() {
+                self.sync(block_height + LEASE_BLOCKS).await?;
+            }
+        }
+        Ok(self.claimed(block_height))
+    }
+
+    /// Checks if the block is claimed by this instance with the known assignments.
+    /// Used for the blocks handled before, they are within the lease of the instance
+    pub(crate) fn claimed(&self, block_height: u64) -> bool {
+        let state = self
+            .state
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        claimed_by(&state.assignments, &self.indexer_id, block_height)
+    }
+}
+
+/// Checks if the block is claimed by the member with the assignment in effect at the block height
+fn claimed_by(
+    assignments: &[readnode_primitives::GroupAssignment],
+    indexer_id: &str,
+    block_height: u64,
+) -> bool {
+    let Some(assignment) = assignments
+        .iter()
+        .rev()
+        .find(|assignment| assignment.from_block_height <= block_height)
+    else {
+        return false;
+    };
+    assignment
+        .members
+        .iter()
+        .position(|member| member == indexer_id)
+        .map_or(false, |slot| {
+            block_height % assignment.members.len() as u64 == slot as u64
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assignment(
+        from_block_height: u64,
+        members: &[&str],
+    ) -> readnode_primitives::GroupAssignment {
+        readnode_primitives::GroupAssignment {
+            from_block_height,
+            members: members.iter().map(|member| member.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_every_block_is_claimed_by_one_member_across_assignments() {
+        let assignments = vec![
+            assignment(0, &["a", "b"]),
+            assignment(105, &["a", "b", "c"]),
+            assignment(211, &["b", "c"]),
+        ];
+        for block_height in 0..300 {
+            let claimed_by_members = ["a", "b", "c"]
+                .into_iter()
+                .filter(|member| claimed_by(&assignments, member, block_height))
+                .count();
+            assert_eq!(claimed_by_members, 1, "block {}", block_height);
+        }
+    }
+
+    #[test]
+    fn test_assignment_takes_effect_from_its_height() {
+        let assignments = vec![
+            assignment(0, &["a", "b"]),
+            assignment(105, &["a", "b", "c"]),
+        ];
+        // 104 % 2 == 0 is the slot of `a` in the first assignment
+        assert!(claimed_by(&assignments, "a", 104));
+        assert!(!claimed_by(&assignments, "c", 104));
+        // 105 % 3 == 0 is the slot of `a` in the second one
+        assert!(claimed_by(&assignments, "a", 105));
+        assert!(claimed_by(&assignments, "c", 107));
+    }
+
+    #[test]
+    fn test_member_out_of_assignment_claims_nothing() {
+        let assignments = vec![assignment(10, &["a"])];
+        assert!(!claimed_by(&assignments, "b", 11));
+        // The blocks before the first assignment are not claimed
+        assert!(!claimed_by(&assignments, "a", 9));
+        assert!(!claimed_by(&[], "a", 9));
+    }
+}
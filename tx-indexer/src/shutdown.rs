@@ -0,0 +1,46 @@
+/// Resolves on SIGTERM or SIGINT. The indexer stops pulling the new blocks,
+/// finishes the block in progress and drains the collected data before exiting
+pub(crate) async fn shutdown_signal() {
+    let mut terminate = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+        .expect("Failed to install the SIGTERM handler");
+    tokio::select! {
+        _ = tokio::signal::ctrl_c() => {}
+        _ = terminate.recv() => {}
+    }
+    tracing::info!(
+        target: crate::INDEXER,
+        "Shutdown signal received, draining the blocks in progress..."
+    );
+}
+
+/// Saves everything collected from the processed blocks before exiting.
+/// The finished transactions are flushed through the write-ahead queue,
+/// the receipts and outcomes waiting for the next block are saved to the shards databases.
+/// The transactions in progress are kept in the cache storage as they are collected,
+/// so they are restored on the next start
+pub(crate) async fn drain(
+    db_manager: &std::sync::Arc<Box<dyn database::TxIndexerDbManager + Sync + Send + 'static>>,
+    tx_collecting_storage: &std::sync::Arc<crate::storage::CacheStorage>,
+    collector_events: &crate::events::CollectorEvents,
+    write_queue: crate::writer::WriteAheadQueue,
+    indexer_config: &configuration::TxIndexerConfig,
+) -> anyhow::Result<()> {
+    crate::collector::save_finished_transaction_details(tx_collecting_storage, collector_events)
+        .await?;
+    // The subscribers receive the events emitted so far and stop
+    collector_events.close();
+    write_queue.close().await;
+
+    #[cfg(feature = "save_outcomes_and_receipts")]
+    crate::collector::flush_outcomes_and_receipts(
+        db_manager,
+        tx_collecting_storage,
+        indexer_config.general.save_batch_size,
+    )
+    .await?;
+    #[cfg(not(feature = "save_outcomes_and_receipts"))]
+    let _ = (db_manager, indexer_config);
+
+    tracing::info!(target: crate::INDEXER, "Collected data is drained");
+    Ok(())
+}
@@ -35,7 +35,7 @@ pub struct ReceiptsAndOutcomesToSave {
 
 pub struct CacheStorage {
     storage: cache_storage::TxIndexerCache,
-    shard_layout: near_indexer_primitives::near_primitives::shard_layout::ShardLayout,
+    shard_layout: readnode_primitives::indexer::near_primitives::shard_layout::ShardLayout,
     transactions: futures_locks::RwLock<
         std::collections::HashMap<
             readnode_primitives::TransactionKey,
@@ -56,13 +56,16 @@ pub struct CacheStorage {
     outcomes_and_receipts_to_save: futures_locks::RwLock<
         std::collections::HashMap<database::primitives::ShardId, ReceiptsAndOutcomesCacheStorage>,
     >,
+    // Read by every task saving the receipts and outcomes, written to wait for all of them
+    #[cfg(feature = "save_outcomes_and_receipts")]
+    saving_in_progress: std::sync::Arc<tokio::sync::RwLock<()>>,
 }
 
 impl CacheStorage {
     /// Init storage without restore transactions with receipts after interruption
     pub(crate) async fn init_storage(
         redis_url: String,
-        shard_layout: near_indexer_primitives::near_primitives::shard_layout::ShardLayout,
+        shard_layout: readnode_primitives::indexer::near_primitives::shard_layout::ShardLayout,
     ) -> Self {
         let cache_storage = cache_storage::TxIndexerCache::new(redis_url)
             .await
@@ -77,24 +80,41 @@ impl CacheStorage {
             outcomes_and_receipts_to_save: futures_locks::RwLock::new(
                 std::collections::HashMap::new(),
             ),
+            #[cfg(feature = "save_outcomes_and_receipts")]
+            saving_in_progress: std::sync::Arc::new(tokio::sync::RwLock::new(())),
         }
     }
 
     /// Init storage with restore transactions with receipts after interruption
+    /// With block sharding enabled only the transactions from the blocks claimed
+    /// by this instance are restored, the rest belong to the other group members
     pub(crate) async fn init_with_restore(
         redis_url: String,
-        shard_layout: near_indexer_primitives::near_primitives::shard_layout::ShardLayout,
+        shard_layout: readnode_primitives::indexer::near_primitives::shard_layout::ShardLayout,
+        block_sharding: Option<&crate::sharding::BlockSharding>,
     ) -> anyhow::Result<Self> {
         let storage = Self::init_storage(redis_url, shard_layout).await;
         storage
-            .restore_transactions_with_receipts_after_interruption()
+            .restore_transactions_with_receipts_after_interruption(block_sharding)
             .await?;
         Ok(storage)
     }
 
     /// Restore transactions with receipts after interruption
-    async fn restore_transactions_with_receipts_after_interruption(&self) -> anyhow::Result<()> {
-        let tx_in_process = self.storage.get_txs_in_process().await.unwrap_or_default();
+    async fn restore_transactions_with_receipts_after_interruption(
+        &self,
+        block_sharding: Option<&crate::sharding::BlockSharding>,
+    ) -> anyhow::Result<()> {
+        let tx_in_process = self
+            .storage
+            .get_txs_in_process()
+            .await
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|tx_key| {
+                block_sharding.map_or(true, |sharding| sharding.claimed(tx_key.block_height))
+            })
+            .collect::<Vec<_>>();
         let tx_futures = tx_in_process
             .iter()
             .map(|tx_key| self.restore_transaction_with_receipts(tx_key));
@@ -158,7 +178,7 @@ impl CacheStorage {
     async fn push_outcome_and_receipt_to_storage(
         &self,
         transaction_key: readnode_primitives::TransactionKey,
-        indexer_execution_outcome_with_receipt: near_indexer_primitives::IndexerExecutionOutcomeWithReceipt,
+        indexer_execution_outcome_with_receipt: readnode_primitives::indexer::IndexerExecutionOutcomeWithReceipt,
     ) -> anyhow::Result<()> {
         self.storage
             .set_outcomes_and_receipts(&transaction_key, indexer_execution_outcome_with_receipt)
@@ -170,7 +190,7 @@ impl CacheStorage {
     async fn push_outcome_and_receipt_to_cache(
         &self,
         transaction_key: &readnode_primitives::TransactionKey,
-        indexer_execution_outcome_with_receipt: near_indexer_primitives::IndexerExecutionOutcomeWithReceipt,
+        indexer_execution_outcome_with_receipt: readnode_primitives::indexer::IndexerExecutionOutcomeWithReceipt,
     ) -> anyhow::Result<()> {
         if let Ok(mut transaction_details) = self.get_tx(transaction_key).await {
             self.remove_receipt_from_watching_list(
@@ -180,12 +200,17 @@ impl CacheStorage {
                     .to_string(),
             )
             .await?;
-            transaction_details
-                .receipts
-                .push(indexer_execution_outcome_with_receipt.receipt.clone());
-            transaction_details
-                .execution_outcomes
-                .push(indexer_execution_outcome_with_receipt.execution_outcome);
+            if !transaction_details.add_receipt_and_outcome(
+                indexer_execution_outcome_with_receipt.receipt.clone(),
+                indexer_execution_outcome_with_receipt.execution_outcome,
+            ) {
+                tracing::debug!(
+                    target: STORAGE,
+                    "Duplicate receipt {} for transaction {} skipped",
+                    indexer_execution_outcome_with_receipt.receipt.receipt_id,
+                    transaction_key.transaction_hash
+                );
+            }
             // Check receipts counter and if all receipts and outcomes already collected
             // then we move the transaction to save otherwise update it and wait for the rest of the receipts
             if self.receipts_transaction_count(transaction_key).await? == 0 {
@@ -276,6 +301,11 @@ impl CacheStorage {
     }
 
     #[cfg_attr(feature = "tracing-instrumentation", tracing::instrument(skip_all))]
+    /// Number of the transactions collected in memory and not finished yet
+    pub(crate) async fn transactions_in_progress(&self) -> usize {
+        self.transactions.read().await.len()
+    }
+
     pub(crate) async fn set_tx(
         &self,
         transaction_details: readnode_primitives::CollectingTransactionDetails,
@@ -325,11 +355,11 @@ impl CacheStorage {
     }
 
     #[cfg_attr(feature = "tracing-instrumentation", tracing::instrument(skip_all))]
-    pub(crate) async fn remove_transaction_from_cache(
+    pub(crate) async fn remove_transactions_from_cache(
         &self,
-        transaction_key: readnode_primitives::TransactionKey,
+        transaction_keys: &[readnode_primitives::TransactionKey],
     ) -> anyhow::Result<()> {
-        self.storage.del_tx(&transaction_key).await
+        self.storage.del_txs(transaction_keys).await
     }
 
     #[cfg_attr(feature = "tracing-instrumentation", tracing::instrument(skip_all))]
@@ -408,21 +438,35 @@ impl CacheStorage {
         Ok(outcomes_and_receipts)
     }
 
+    /// Marks the saving of the receipts and outcomes in progress until the guard is dropped
+    #[cfg(feature = "save_outcomes_and_receipts")]
+    pub(crate) async fn start_saving(&self) -> tokio::sync::OwnedRwLockReadGuard<()> {
+        std::sync::Arc::clone(&self.saving_in_progress)
+            .read_owned()
+            .await
+    }
+
+    /// Waits until all the savings of the receipts and outcomes in progress are finished
+    #[cfg(feature = "save_outcomes_and_receipts")]
+    pub(crate) async fn wait_for_saving(&self) {
+        let _ = self.saving_in_progress.write().await;
+    }
+
     #[cfg_attr(feature = "tracing-instrumentation", tracing::instrument(skip_all))]
+    #[allow(clippy::too_many_arguments)]
     pub(crate) async fn push_outcome_and_receipt_to_save(
         &self,
-        outcome_id: &near_indexer_primitives::CryptoHash,
-        receipt_id: &near_indexer_primitives::CryptoHash,
-        parent_tx_hash: &near_indexer_primitives::CryptoHash,
-        receiver_id: &near_indexer_primitives::types::AccountId,
+        outcome_id: &readnode_primitives::indexer::CryptoHash,
+        receipt_id: &readnode_primitives::indexer::CryptoHash,
+        parent_tx_hash: &readnode_primitives::indexer::CryptoHash,
+        receiver_id: &readnode_primitives::indexer::types::AccountId,
         block: readnode_primitives::BlockRecord,
         shard_id: u64,
+        index_in_chunk: Option<u32>,
+        receipt: Option<&readnode_primitives::indexer::views::ReceiptView>,
     ) -> anyhow::Result<()> {
         let database_shard_id =
-            near_indexer_primitives::near_primitives::shard_layout::account_id_to_shard_id(
-                receiver_id,
-                &self.shard_layout,
-            );
+            readnode_primitives::indexer::account_id_to_shard_id(receiver_id, &self.shard_layout);
 
         let receipt_record = readnode_primitives::ReceiptRecord {
             receipt_id: *receipt_id,
@@ -431,6 +475,8 @@ impl CacheStorage {
             block_height: block.height,
             block_hash: block.hash,
             shard_id,
+            index_in_chunk,
+            receipt: receipt.cloned(),
         };
         let outcome_record = readnode_primitives::OutcomeRecord {
             outcome_id: *outcome_id,
@@ -439,15 +485,26 @@ impl CacheStorage {
             block_height: block.height,
             block_hash: block.hash,
             shard_id,
+            index_in_chunk,
         };
         self.outcomes_and_receipts_to_save
             .write()
             .await
             .entry(database_shard_id)
             .and_modify(|receipts_and_outcomes| {
+                // The local receipt is executed in the block of its transaction,
+                // the body of the executed receipt is kept if the transaction maps it after
+                let mut receipt_record = receipt_record.clone();
+                if let Some(mapped_receipt) =
+                    receipts_and_outcomes.receipts.get(&receipt_id.to_string())
+                {
+                    receipt_record.receipt = receipt_record
+                        .receipt
+                        .or_else(|| mapped_receipt.receipt.clone());
+                }
                 receipts_and_outcomes
                     .receipts
-                    .insert(receipt_id.to_string(), receipt_record.clone());
+                    .insert(receipt_id.to_string(), receipt_record);
                 receipts_and_outcomes
                     .outcomes
                     .insert(outcome_id.to_string(), outcome_record.clone());
@@ -503,7 +560,7 @@ impl CacheStorage {
     pub(crate) async fn push_outcome_and_receipt(
         &self,
         transaction_key: &readnode_primitives::TransactionKey,
-        indexer_execution_outcome_with_receipt: near_indexer_primitives::IndexerExecutionOutcomeWithReceipt,
+        indexer_execution_outcome_with_receipt: readnode_primitives::indexer::IndexerExecutionOutcomeWithReceipt,
     ) -> anyhow::Result<()> {
         self.push_outcome_and_receipt_to_storage(
             transaction_key.clone(),
@@ -0,0 +1,134 @@
+use crate::events;
+use crate::storage;
+
+// Max number of transactions taken from the queue by a writer worker at once
+const WRITE_BATCH_SIZE: usize = 100;
+
+/// Bounded write-ahead queue between the collector and the transaction details storage.
+/// The queue is the subscription to the `TransactionFinalized` collector events and the pool
+/// of writer workers flushes the finished transactions to the storage by batches.
+/// Every batch is removed from the cache storage with one command and announced
+/// to the rpc-servers with one notification, only the transaction objects are uploaded
+/// one by one since the details storage has no multi-object upload.
+/// Transactions stay in the cache storage until they are saved,
+/// so they are restored after the interruption. That is why the queue itself is kept
+/// in process instead of Redis, a Redis-backed queue would store the same transactions twice.
+/// When the queue is full the collector waits for the free space, that slows down
+/// the stream consumption instead of dropping the transactions.
+pub(crate) struct WriteAheadQueue {
+    workers: Vec<tokio::task::JoinHandle<()>>,
+}
+
+impl WriteAheadQueue {
+    /// Subscribes to the finished transactions and spawns the writer workers
+    pub(crate) fn start(
+        queue_size: usize,
+        workers: usize,
+        collector_events: &events::CollectorEvents,
+        tx_collecting_storage: std::sync::Arc<storage::CacheStorage>,
+        tx_details_storage: std::sync::Arc<crate::TxDetailsStorage>,
+        db_manager: std::sync::Arc<Box<dyn database::TxIndexerDbManager + Sync + Send + 'static>>,
+    ) -> Self {
+        tracing::info!(
+            target: crate::INDEXER,
+            "Starting write-ahead queue with size {} and {} writer workers",
+            queue_size,
+            workers,
+        );
+        let receiver = collector_events.subscribe(
+            "writer",
+            queue_size,
+            events::Delivery::Wait,
+            events::CollectorEvent::is_transaction_finalized,
+        );
+        let receiver = std::sync::Arc::new(tokio::sync::Mutex::new(receiver));
+        let workers = (0..workers.max(1))
+            .map(|worker_id| {
+                tokio::spawn(writer_worker(
+                    worker_id,
+                    std::sync::Arc::clone(&receiver),
+                    std::sync::Arc::clone(&tx_collecting_storage),
+                    std::sync::Arc::clone(&tx_details_storage),
+                    std::sync::Arc::clone(&db_manager),
+                ))
+            })
+            .collect();
+        Self { workers }
+    }
+
+    /// Waits until the writer workers flush all the enqueued transactions.
+    /// The queue is closed by closing the collector events
+    pub(crate) async fn close(self) {
+        for result in futures::future::join_all(self.workers).await {
+            if let Err(err) = result {
+                tracing::warn!(target: crate::INDEXER, "Writer worker failed: {:?}", err);
+            }
+        }
+    }
+}
+
+async fn writer_worker(
+    worker_id: usize,
+    receiver: std::sync::Arc<tokio::sync::Mutex<events::EventReceiver>>,
+    tx_collecting_storage: std::sync::Arc<storage::CacheStorage>,
+    tx_details_storage: std::sync::Arc<crate::TxDetailsStorage>,
+    db_manager: std::sync::Arc<Box<dyn database::TxIndexerDbManager + Sync + Send + 'static>>,
+) {
+    loop {
+        let mut batch = Vec::with_capacity(WRITE_BATCH_SIZE);
+        // Hold the lock only while receiving to let other workers take the next batch
+        // while this one is flushing
+        let received = receiver
+            .lock()
+            .await
+            .recv_many(&mut batch, WRITE_BATCH_SIZE)
+            .await;
+        if received == 0 {
+            tracing::info!(
+                target: crate::INDEXER,
+                "Write-ahead queue is closed. Writer worker {} stopped",
+                worker_id
+            );
+            break;
+        }
+        tracing::debug!(
+            target: crate::INDEXER,
+            "Writer worker {} flushes {} transactions",
+            worker_id,
+            received
+        );
+        // Failed transactions are moved back to the save queue of the cache storage
+        // and will be enqueued again with the next block
+        let transactions_details = batch
+            .into_iter()
+            .filter_map(|event| match std::sync::Arc::unwrap_or_clone(event) {
+                events::CollectorEvent::TransactionFinalized {
+                    transaction_details,
+                } => Some(transaction_details),
+                _ => None,
+            })
+            .collect();
+        let saved_transaction_hashes = crate::collector::save_transactions_details(
+            &tx_collecting_storage,
+            &tx_details_storage,
+            transactions_details,
+        )
+        .await;
+        if saved_transaction_hashes.is_empty() {
+            continue;
+        }
+        // The rpc-servers waiting for the transactions learn about them right away,
+        // the whole batch is announced by a single notification
+        if let Err(err) = db_manager
+            .notify_finalized_transactions(&saved_transaction_hashes)
+            .await
+        {
+            tracing::warn!(
+                target: crate::INDEXER,
+                "Failed to notify about {} saved transactions: {:?}",
+                saved_transaction_hashes.len(),
+                err
+            );
+        }
+    }
+}